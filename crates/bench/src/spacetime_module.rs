@@ -58,6 +58,7 @@ impl<L: ModuleLanguage> BenchDatabase for SpacetimeModule<L> {
         let config = Config {
             storage: if in_memory { Storage::Memory } else { Storage::Disk },
             page_pool_max_size: None,
+            table_memory_limit: None,
         };
 
         let module = runtime.block_on(async {
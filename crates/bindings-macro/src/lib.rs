@@ -46,6 +46,8 @@ mod sym {
     symbol!(columns);
     symbol!(crate_, crate);
     symbol!(direct);
+    symbol!(filter);
+    symbol!(full_text);
     symbol!(index);
     symbol!(init);
     symbol!(name);
@@ -243,6 +245,27 @@ pub fn schema_type(input: StdTokenStream) -> StdTokenStream {
     })
 }
 
+#[proc_macro_derive(ReducerError)]
+pub fn reducer_error(input: StdTokenStream) -> StdTokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics spacetimedb::rt::IntoReducerError for #ident #ty_generics #where_clause {
+            type Err = Self;
+
+            fn into_reducer_error(self) -> spacetimedb::rt::ReducerFailure<Self> {
+                spacetimedb::rt::ReducerFailure {
+                    message: ::std::format!("{self:?}").into(),
+                    value: ::core::option::Option::Some(self),
+                }
+            }
+        }
+    }
+    .into()
+}
+
 #[proc_macro_attribute]
 pub fn client_visibility_filter(args: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
     ok_or_compile_error(|| {
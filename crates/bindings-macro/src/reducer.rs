@@ -116,7 +116,7 @@ pub(crate) fn reducer_impl(args: ReducerArgs, original_function: &ItemFn) -> syn
     let generated_describe_function = quote! {
         #[export_name = #register_describer_symbol]
         pub extern "C" fn __register_describer() {
-            spacetimedb::rt::register_reducer::<_, #func_name>(#func_name)
+            spacetimedb::rt::register_reducer::<_, #func_name, _>(#func_name)
         }
     };
 
@@ -134,7 +134,7 @@ pub(crate) fn reducer_impl(args: ReducerArgs, original_function: &ItemFn) -> syn
             }
         };
         impl #func_name {
-            fn invoke(__ctx: spacetimedb::ReducerContext, __args: &[u8]) -> spacetimedb::ReducerResult {
+            fn invoke(__ctx: spacetimedb::ReducerContext, __args: &[u8]) -> spacetimedb::rt::ReducerOutput {
                 spacetimedb::rt::invoke_reducer(#func_name, __ctx, __args)
             }
         }
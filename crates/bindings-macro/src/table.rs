@@ -12,7 +12,7 @@ use syn::parse::Parse;
 use syn::parse::Parser as _;
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{parse_quote, Ident, Path, Token};
+use syn::{parse_quote, Ident, LitStr, Path, Token};
 
 pub(crate) struct TableArgs {
     access: Option<TableAccess>,
@@ -62,6 +62,66 @@ impl IndexArg {
 enum IndexType {
     BTree { columns: Vec<Ident> },
     Direct { column: Ident },
+    BTreeExpr { keys: Vec<IndexKeyArg> },
+    PartialBTree { columns: Vec<Ident>, filter: LitStr },
+    FullText { column: Ident },
+}
+
+/// A key of an expression index, e.g. `position.x` or `lower(name)`.
+struct IndexKeyArg {
+    column: Ident,
+    fields: Vec<Ident>,
+    function: Option<Ident>,
+}
+
+impl IndexKeyArg {
+    /// Is this key just a column?
+    fn is_column(&self) -> bool {
+        self.fields.is_empty() && self.function.is_none()
+    }
+
+    /// The name of this key, as used in the index name.
+    fn name(&self) -> String {
+        let mut parts = vec![self.column.unraw().to_string()];
+        parts.extend(self.fields.iter().map(|field| field.unraw().to_string()));
+        parts.extend(self.function.iter().map(|function| function.to_string()));
+        parts.join("_")
+    }
+}
+
+impl Parse for IndexKeyArg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek2(syn::token::Paren) {
+            let function = Ident::parse(input)?;
+            if function != "lower" {
+                return Err(syn::Error::new(
+                    function.span(),
+                    "unknown index key function, expected `lower`",
+                ));
+            }
+            let inner;
+            syn::parenthesized!(inner in input);
+            let key = Self::parse(&inner)?;
+            if key.function.is_some() {
+                return Err(inner.error("index key functions can't be nested"));
+            }
+            return Ok(Self {
+                function: Some(function),
+                ..key
+            });
+        }
+        let column = Ident::parse(input)?;
+        let mut fields = vec![];
+        while input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            fields.push(Ident::parse_any(input)?);
+        }
+        Ok(Self {
+            column,
+            fields,
+            function: None,
+        })
+    }
 }
 
 impl TableArgs {
@@ -158,6 +218,10 @@ impl IndexArg {
                     check_duplicate_msg(&algo, &meta, "index algorithm specified twice")?;
                     algo = Some(Self::parse_direct(meta)?);
                 }
+                sym::full_text => {
+                    check_duplicate_msg(&algo, &meta, "index algorithm specified twice")?;
+                    algo = Some(Self::parse_full_text(meta)?);
+                }
             });
             Ok(())
         })?;
@@ -171,6 +235,7 @@ impl IndexArg {
 
     fn parse_btree(meta: ParseNestedMeta) -> syn::Result<IndexType> {
         let mut columns = None;
+        let mut filter = None;
         meta.parse_nested_meta(|meta| {
             match_meta!(match meta {
                 sym::columns => {
@@ -179,17 +244,35 @@ impl IndexArg {
                     let inner;
                     syn::bracketed!(inner in value);
                     columns = Some(
-                        Punctuated::<Ident, Token![,]>::parse_terminated(&inner)?
+                        Punctuated::<IndexKeyArg, Token![,]>::parse_terminated(&inner)?
                             .into_iter()
                             .collect::<Vec<_>>(),
                     );
                 }
+                sym::filter => {
+                    check_duplicate(&filter, &meta)?;
+                    filter = Some(meta.value()?.parse::<LitStr>()?);
+                }
             });
             Ok(())
         })?;
-        let columns = columns
+        let keys = columns
             .ok_or_else(|| meta.error("must specify columns for btree index, e.g. `btree(columns = [col1, col2])`"))?;
-        Ok(IndexType::BTree { columns })
+        // A filter, e.g. `btree(columns = [col1], filter = "col2 = 'x'")`, makes this a partial index.
+        if let Some(filter) = filter {
+            if !keys.iter().all(IndexKeyArg::is_column) {
+                return Err(meta.error("a partial index can only be on columns, not on nested fields or functions"));
+            }
+            let columns = keys.into_iter().map(|key| key.column).collect();
+            return Ok(IndexType::PartialBTree { columns, filter });
+        }
+        // Nested fields and functions, e.g. `btree(columns = [position.x])`, make this an expression index.
+        if keys.iter().all(IndexKeyArg::is_column) {
+            let columns = keys.into_iter().map(|key| key.column).collect();
+            Ok(IndexType::BTree { columns })
+        } else {
+            Ok(IndexType::BTreeExpr { keys })
+        }
     }
 
     fn parse_direct(meta: ParseNestedMeta) -> syn::Result<IndexType> {
@@ -211,7 +294,24 @@ impl IndexArg {
         Ok(IndexType::Direct { column })
     }
 
-    /// Parses an inline `#[index(btree)]` or `#[index(direct)]` attribute on a field.
+    fn parse_full_text(meta: ParseNestedMeta) -> syn::Result<IndexType> {
+        let mut column = None;
+        meta.parse_nested_meta(|meta| {
+            match_meta!(match meta {
+                sym::column => {
+                    check_duplicate(&column, &meta)?;
+                    column = Some(meta.value()?.parse()?);
+                }
+            });
+            Ok(())
+        })?;
+        let column = column.ok_or_else(|| {
+            meta.error("must specify the column for full-text index, e.g. `full_text(column = col1)`")
+        })?;
+        Ok(IndexType::FullText { column })
+    }
+
+    /// Parses an inline `#[index(btree)]`, `#[index(direct)]`, or `#[index(full_text)]` attribute on a field.
     fn parse_index_attr(field: &Ident, attr: &syn::Attribute) -> syn::Result<Self> {
         let mut kind = None;
         attr.parse_nested_meta(|meta| {
@@ -226,11 +326,19 @@ impl IndexArg {
                     check_duplicate_msg(&kind, &meta, "index type specified twice")?;
                     kind = Some(IndexType::Direct { column: field.clone() })
                 }
+                sym::full_text => {
+                    check_duplicate_msg(&kind, &meta, "index type specified twice")?;
+                    kind = Some(IndexType::FullText { column: field.clone() })
+                }
             });
             Ok(())
         })?;
-        let kind = kind
-            .ok_or_else(|| syn::Error::new_spanned(&attr.meta, "must specify kind of index (`btree` or `direct`)"))?;
+        let kind = kind.ok_or_else(|| {
+            syn::Error::new_spanned(
+                &attr.meta,
+                "must specify kind of index (`btree`, `direct`, or `full_text`)",
+            )
+        })?;
         let name = field.clone();
         Ok(IndexArg::new(name, kind))
     }
@@ -254,15 +362,36 @@ impl IndexArg {
 
                 ValidatedIndexType::Direct { col }
             }
+            IndexType::BTreeExpr { keys } => {
+                let keys = keys
+                    .iter()
+                    .map(|key| Ok((find_column(&key.column)?, key)))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                ValidatedIndexType::BTreeExpr { keys }
+            }
+            IndexType::PartialBTree { columns, filter } => {
+                let cols = columns.iter().map(find_column).collect::<syn::Result<Vec<_>>>()?;
+                ValidatedIndexType::PartialBTree { cols, filter }
+            }
+            IndexType::FullText { column } => {
+                let col = find_column(column)?;
+                ValidatedIndexType::FullText { col }
+            }
         };
         // See crates/schema/src/validate/v9.rs for the format of index names.
         // It's slightly unnerving that we just trust that component to generate this format correctly,
         // but what can you do.
         let (cols, kind_str) = match &kind {
-            ValidatedIndexType::BTree { cols } => (&**cols, "btree"),
-            ValidatedIndexType::Direct { col } => (&[*col] as &[_], "direct"),
+            ValidatedIndexType::BTree { cols } => (cols.iter().map(|col| col.ident.to_string()).collect(), "btree"),
+            ValidatedIndexType::Direct { col } => (vec![col.ident.to_string()], "direct"),
+            ValidatedIndexType::BTreeExpr { keys } => {
+                (keys.iter().map(|(_, key)| key.name()).collect::<Vec<_>>(), "btree")
+            }
+            ValidatedIndexType::PartialBTree { cols, .. } => {
+                (cols.iter().map(|col| col.ident.to_string()).collect(), "partial")
+            }
+            ValidatedIndexType::FullText { col } => (vec![col.ident.to_string()], "fulltext"),
         };
-        let cols = cols.iter().map(|col| col.ident.to_string()).collect::<Vec<_>>();
         let cols = cols.join("_");
         let index_name = format!("{table_name}_{cols}_idx_{kind_str}");
 
@@ -308,6 +437,20 @@ impl AccessorType {
             AccessorType::ReadWrite => "RangedIndex",
         }
     }
+
+    fn text(&self) -> proc_macro2::TokenStream {
+        match self {
+            AccessorType::Read => quote!(spacetimedb::TextIndexReadOnly),
+            AccessorType::ReadWrite => quote!(spacetimedb::TextIndex),
+        }
+    }
+
+    fn text_doc_typename(&self) -> &'static str {
+        match self {
+            AccessorType::Read => "TextIndexReadOnly",
+            AccessorType::ReadWrite => "TextIndex",
+        }
+    }
 }
 
 struct ValidatedIndex<'a> {
@@ -318,8 +461,22 @@ struct ValidatedIndex<'a> {
 }
 
 enum ValidatedIndexType<'a> {
-    BTree { cols: Vec<&'a Column<'a>> },
-    Direct { col: &'a Column<'a> },
+    BTree {
+        cols: Vec<&'a Column<'a>>,
+    },
+    Direct {
+        col: &'a Column<'a>,
+    },
+    BTreeExpr {
+        keys: Vec<(&'a Column<'a>, &'a IndexKeyArg)>,
+    },
+    PartialBTree {
+        cols: Vec<&'a Column<'a>>,
+        filter: &'a LitStr,
+    },
+    FullText {
+        col: &'a Column<'a>,
+    },
 }
 
 impl ValidatedIndex<'_> {
@@ -337,6 +494,37 @@ impl ValidatedIndex<'_> {
                     column: #col_id
                 })
             }
+            ValidatedIndexType::BTreeExpr { keys } => {
+                let keys = keys.iter().map(|(col, key)| {
+                    let col_id = col.index;
+                    let fields = key.fields.iter().map(|field| field.unraw().to_string());
+                    let function = match &key.function {
+                        Some(_) => quote!(Some(spacetimedb::table::IndexKeyFunction::Lower)),
+                        None => quote!(None),
+                    };
+                    quote!(spacetimedb::table::IndexKeyDesc {
+                        column: #col_id,
+                        fields: &[#(#fields),*],
+                        function: #function,
+                    })
+                });
+                quote!(spacetimedb::table::IndexAlgo::BTreeExpr {
+                    keys: &[#(#keys),*]
+                })
+            }
+            ValidatedIndexType::PartialBTree { cols, filter } => {
+                let col_ids = cols.iter().map(|col| col.index);
+                quote!(spacetimedb::table::IndexAlgo::PartialBTree {
+                    columns: &[#(#col_ids),*],
+                    filter: #filter,
+                })
+            }
+            ValidatedIndexType::FullText { col } => {
+                let col_id = col.index;
+                quote!(spacetimedb::table::IndexAlgo::FullText {
+                    column: #col_id
+                })
+            }
         };
         let accessor_name = ident_to_litstr(self.accessor_name);
         // Note: we do not pass the index_name through here.
@@ -357,6 +545,15 @@ impl ValidatedIndex<'_> {
         let cols = match &self.kind {
             ValidatedIndexType::BTree { cols } => &**cols,
             ValidatedIndexType::Direct { col } => slice::from_ref(col),
+            // The keys of an expression index don't have a Rust type we can name here,
+            // so these are only used by the query planner and don't get an accessor.
+            ValidatedIndexType::BTreeExpr { .. } => return TokenStream::new(),
+            // Likewise, a partial index is only used by the query planner
+            // for queries whose filter implies the index's filter.
+            ValidatedIndexType::PartialBTree { .. } => return TokenStream::new(),
+            ValidatedIndexType::FullText { col } => {
+                return self.text_accessor(vis, col, row_type_ident, tbl_type_ident, flavor)
+            }
         };
         if self.is_unique {
             assert_eq!(cols.len(), 1);
@@ -432,12 +629,41 @@ impl ValidatedIndex<'_> {
         }
     }
 
+    fn text_accessor(
+        &self,
+        vis: &syn::Visibility,
+        col: &Column<'_>,
+        row_type_ident: &Ident,
+        tbl_type_ident: &Ident,
+        flavor: AccessorType,
+    ) -> TokenStream {
+        let index_ident = self.accessor_name;
+        let column_ident = col.ident;
+
+        let text_ty = flavor.text();
+        let tbl_token = quote!(#tbl_type_ident);
+        let doc_type = flavor.text_doc_typename();
+
+        let doc = format!(
+            "Gets the `{index_ident}` [`{doc_type}`][spacetimedb::{doc_type}] for the \
+             [`{column_ident}`][{row_type_ident}#structfield.{column_ident}] column."
+        );
+        quote! {
+            #[doc = #doc]
+            #vis fn #index_ident(&self) -> #text_ty<#tbl_token, __indices::#index_ident> {
+                #text_ty::__NEW
+            }
+        }
+    }
+
     fn marker_type(&self, vis: &syn::Visibility, tablehandle_ident: &Ident) -> TokenStream {
         let index_ident = self.accessor_name;
         let index_name = &self.index_name;
 
         let (cols, typeck_direct_index) = match &self.kind {
             ValidatedIndexType::BTree { cols } => (&**cols, None),
+            ValidatedIndexType::BTreeExpr { .. } | ValidatedIndexType::PartialBTree { .. } => (&[] as &[_], None),
+            ValidatedIndexType::FullText { col } => (slice::from_ref(col), None),
             ValidatedIndexType::Direct { col } => {
                 let col_ty = col.ty;
                 let typeck = quote_spanned!(col_ty.span()=>
@@ -469,7 +695,8 @@ impl ValidatedIndex<'_> {
                 }
             }
         };
-        if self.is_unique {
+        // A full-text index checks the rows it finds against the column's text.
+        if self.is_unique || matches!(self.kind, ValidatedIndexType::FullText { .. }) {
             let col = cols[0];
             let col_ty = col.ty;
             let col_name = col.ident.to_string();
@@ -691,6 +918,7 @@ pub(crate) fn table_impl(mut args: TableArgs, item: &syn::DeriveInput) -> syn::R
             let covered_by_index = match &index.kind {
                 IndexType::BTree { columns } => &**columns == slice::from_ref(unique_col.ident),
                 IndexType::Direct { column } => column == unique_col.ident,
+                IndexType::BTreeExpr { .. } | IndexType::PartialBTree { .. } | IndexType::FullText { .. } => false,
             };
             index.is_unique |= covered_by_index;
             covered_by_index
@@ -41,6 +41,8 @@ import { CallReducer } from './call_reducer_type.ts';
 export { CallReducer };
 import { ClientMessage } from './client_message_type.ts';
 export { ClientMessage };
+import { CaughtUp } from './caught_up_type.ts';
+export { CaughtUp };
 import { CompressableQueryUpdate } from './compressable_query_update_type.ts';
 export { CompressableQueryUpdate };
 import { DatabaseUpdate } from './database_update_type.ts';
@@ -63,6 +65,12 @@ import { QueryUpdate } from './query_update_type.ts';
 export { QueryUpdate };
 import { ReducerCallInfo } from './reducer_call_info_type.ts';
 export { ReducerCallInfo };
+import { ReducerResult } from './reducer_result_type.ts';
+export { ReducerResult };
+import { ResumeSubscriptions } from './resume_subscriptions_type.ts';
+export { ResumeSubscriptions };
+import { ResumedSubscription } from './resumed_subscription_type.ts';
+export { ResumedSubscription };
 import { RowSizeHint } from './row_size_hint_type.ts';
 export { RowSizeHint };
 import { ServerMessage } from './server_message_type.ts';
@@ -81,6 +89,8 @@ import { SubscribeSingle } from './subscribe_single_type.ts';
 export { SubscribeSingle };
 import { SubscriptionError } from './subscription_error_type.ts';
 export { SubscriptionError };
+import { SubscriptionsResumed } from './subscriptions_resumed_type.ts';
+export { SubscriptionsResumed };
 import { TableUpdate } from './table_update_type.ts';
 export { TableUpdate };
 import { TransactionUpdate } from './transaction_update_type.ts';
@@ -57,6 +57,15 @@ declare type __keep_SubscribeMultiApplied = SubscribeMultiApplied;
 import { UnsubscribeMultiApplied } from './unsubscribe_multi_applied_type';
 // Mark import as potentially unused
 declare type __keep_UnsubscribeMultiApplied = UnsubscribeMultiApplied;
+import { ReducerResult } from './reducer_result_type';
+// Mark import as potentially unused
+declare type __keep_ReducerResult = ReducerResult;
+import { SubscriptionsResumed } from './subscriptions_resumed_type';
+// Mark import as potentially unused
+declare type __keep_SubscriptionsResumed = SubscriptionsResumed;
+import { CaughtUp } from './caught_up_type';
+// Mark import as potentially unused
+declare type __keep_CaughtUp = CaughtUp;
 
 import * as ServerMessageVariants from './server_message_variants';
 
@@ -71,7 +80,10 @@ export type ServerMessage =
   | ServerMessageVariants.UnsubscribeApplied
   | ServerMessageVariants.SubscriptionError
   | ServerMessageVariants.SubscribeMultiApplied
-  | ServerMessageVariants.UnsubscribeMultiApplied;
+  | ServerMessageVariants.UnsubscribeMultiApplied
+  | ServerMessageVariants.ReducerResult
+  | ServerMessageVariants.SubscriptionsResumed
+  | ServerMessageVariants.CaughtUp;
 
 let _cached_ServerMessage_type_value: __AlgebraicTypeType | null = null;
 
@@ -140,6 +152,19 @@ export const ServerMessage = {
     tag: 'UnsubscribeMultiApplied',
     value,
   }),
+  ReducerResult: (
+    value: ReducerResult
+  ): ServerMessageVariants.ReducerResult => ({ tag: 'ReducerResult', value }),
+  SubscriptionsResumed: (
+    value: SubscriptionsResumed
+  ): ServerMessageVariants.SubscriptionsResumed => ({
+    tag: 'SubscriptionsResumed',
+    value,
+  }),
+  CaughtUp: (value: CaughtUp): ServerMessageVariants.CaughtUp => ({
+    tag: 'CaughtUp',
+    value,
+  }),
 
   getTypeScriptAlgebraicType(): __AlgebraicTypeType {
     if (_cached_ServerMessage_type_value)
@@ -187,6 +212,18 @@ export const ServerMessage = {
       {
         name: 'UnsubscribeMultiApplied',
         algebraicType: UnsubscribeMultiApplied.getTypeScriptAlgebraicType(),
+      },
+      {
+        name: 'ReducerResult',
+        algebraicType: ReducerResult.getTypeScriptAlgebraicType(),
+      },
+      {
+        name: 'SubscriptionsResumed',
+        algebraicType: SubscriptionsResumed.getTypeScriptAlgebraicType(),
+      },
+      {
+        name: 'CaughtUp',
+        algebraicType: CaughtUp.getTypeScriptAlgebraicType(),
       }
     );
     return _cached_ServerMessage_type_value;
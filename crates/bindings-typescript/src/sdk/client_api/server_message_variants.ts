@@ -57,6 +57,15 @@ declare type __keep_SubscribeMultiAppliedType = SubscribeMultiAppliedType;
 import { UnsubscribeMultiApplied as UnsubscribeMultiAppliedType } from './unsubscribe_multi_applied_type';
 // Mark import as potentially unused
 declare type __keep_UnsubscribeMultiAppliedType = UnsubscribeMultiAppliedType;
+import { ReducerResult as ReducerResultType } from './reducer_result_type';
+// Mark import as potentially unused
+declare type __keep_ReducerResultType = ReducerResultType;
+import { SubscriptionsResumed as SubscriptionsResumedType } from './subscriptions_resumed_type';
+// Mark import as potentially unused
+declare type __keep_SubscriptionsResumedType = SubscriptionsResumedType;
+import { CaughtUp as CaughtUpType } from './caught_up_type';
+// Mark import as potentially unused
+declare type __keep_CaughtUpType = CaughtUpType;
 
 export type InitialSubscription = {
   tag: 'InitialSubscription';
@@ -95,3 +104,12 @@ export type UnsubscribeMultiApplied = {
   tag: 'UnsubscribeMultiApplied';
   value: UnsubscribeMultiAppliedType;
 };
+export type ReducerResult = {
+  tag: 'ReducerResult';
+  value: ReducerResultType;
+};
+export type SubscriptionsResumed = {
+  tag: 'SubscriptionsResumed';
+  value: SubscriptionsResumedType;
+};
+export type CaughtUp = { tag: 'CaughtUp'; value: CaughtUpType };
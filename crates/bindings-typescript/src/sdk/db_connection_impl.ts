@@ -32,7 +32,7 @@ import type {
   SubscribeAppliedMessage,
   UnsubscribeAppliedMessage,
 } from './message_types.ts';
-import type { ReducerEvent } from './reducer_event.ts';
+import type { ReducerEvent, ReducerResult } from './reducer_event.ts';
 import type RemoteModule from './spacetime_module.ts';
 import {
   TableCache,
@@ -383,8 +383,14 @@ export class DbConnectionImpl<
         return subscriptionUpdate;
       }
 
-      case 'TransactionUpdate': {
-        const txUpdate = message.value;
+      case 'TransactionUpdate':
+      case 'ReducerResult': {
+        // A `ReducerResult` is the update for one of our own reducer calls,
+        // along with the value the reducer returned.
+        const [txUpdate, returnValue] =
+          message.tag === 'ReducerResult'
+            ? [message.value.update, message.value.returnValue]
+            : [message.value, undefined];
         const identity = txUpdate.callerIdentity;
         const connectionId = ConnectionId.nullIfZero(
           txUpdate.callerConnectionId
@@ -420,12 +426,14 @@ export class DbConnectionImpl<
           | {
               reducerName: string;
               args: Uint8Array;
+              returnValue?: Uint8Array;
             }
           | undefined;
         if (reducerName !== '') {
           reducerInfo = {
             reducerName,
             args,
+            returnValue,
           };
         }
 
@@ -628,6 +636,33 @@ export class DbConnectionImpl<
         reducerInfo = reducerInfo!;
         reducerTypeInfo = reducerTypeInfo!;
 
+        // Reducers which declare return types also send the value they returned or failed with.
+        let reducerResult: ReducerResult | undefined;
+        if (
+          reducerInfo.returnValue &&
+          reducerTypeInfo.okType &&
+          reducerTypeInfo.errType
+        ) {
+          const reader = new BinaryReader(reducerInfo.returnValue);
+          if (message.status.tag === 'Committed') {
+            reducerResult = {
+              tag: 'ok',
+              value: AlgebraicType.deserializeValue(
+                reader,
+                reducerTypeInfo.okType
+              ),
+            };
+          } else if (message.status.tag === 'Failed') {
+            reducerResult = {
+              tag: 'err',
+              value: AlgebraicType.deserializeValue(
+                reader,
+                reducerTypeInfo.errType
+              ),
+            };
+          }
+        }
+
         // Thus this must be a reducer event create it and emit it.
         const reducerEvent = {
           callerIdentity: message.identity,
@@ -638,6 +673,7 @@ export class DbConnectionImpl<
           reducer: {
             name: reducerInfo.reducerName,
             args: reducerArgs,
+            result: reducerResult,
           },
         };
         const event: Event<typeof reducerEvent.reducer> = {
@@ -17,6 +17,7 @@ export type TransactionUpdateMessage<RowType extends Record<string, any>> = {
   reducerInfo?: {
     reducerName: string;
     args: Uint8Array;
+    returnValue?: Uint8Array;
   };
   status: UpdateStatus;
   message: string;
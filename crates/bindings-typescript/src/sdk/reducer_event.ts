@@ -3,7 +3,17 @@ import { Timestamp } from '../';
 import type { UpdateStatus } from './client_api/index.ts';
 import { Identity } from '../';
 
-export type ReducerInfoType = { name: string; args?: any } | never;
+export type ReducerInfoType =
+  | { name: string; args?: any; result?: ReducerResult }
+  | never;
+
+/**
+ * The value returned by a reducer which declares return types, if it committed,
+ * or the error value it failed with, if it failed.
+ */
+export type ReducerResult<T = any, E = any> =
+  | { tag: 'ok'; value: T }
+  | { tag: 'err'; value: E };
 
 export type ReducerEvent<Reducer extends ReducerInfoType> = {
   /**
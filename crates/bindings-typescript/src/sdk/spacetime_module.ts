@@ -15,6 +15,10 @@ export interface PrimaryKeyInfo {
 export interface ReducerRuntimeTypeInfo {
   reducerName: string;
   argsType: AlgebraicType;
+  /** The type of the value returned by the reducer, if it declares return types. */
+  okType?: AlgebraicType;
+  /** The type of the error value returned by the reducer, if it declares return types. */
+  errType?: AlgebraicType;
 }
 
 export default interface RemoteModule {
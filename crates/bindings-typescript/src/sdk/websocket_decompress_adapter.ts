@@ -120,6 +120,8 @@ export class WebsocketDecompressAdapter {
     if (confirmedReads !== undefined) {
       databaseUrl.searchParams.set('confirmed', confirmedReads.toString());
     }
+    // We know how to decode the values returned by our reducer calls.
+    databaseUrl.searchParams.set('reducer_results', 'true');
 
     const ws = new WS(databaseUrl.toString(), wsProtocol);
 
@@ -43,8 +43,8 @@ pub use spacetimedb_lib::Timestamp;
 pub use spacetimedb_primitives::TableId;
 pub use sys::Errno;
 pub use table::{
-    AutoIncOverflow, RangedIndex, RangedIndexReadOnly, Table, TryInsertError, UniqueColumn, UniqueColumnReadOnly,
-    UniqueConstraintViolation,
+    AutoIncOverflow, RangedIndex, RangedIndexReadOnly, Table, TextIndex, TextIndexReadOnly, TryInsertError,
+    UniqueColumn, UniqueColumnReadOnly, UniqueConstraintViolation,
 };
 
 pub type ReducerResult = core::result::Result<(), Box<str>>;
@@ -239,6 +239,32 @@ pub use spacetimedb_bindings_macro::client_visibility_filter;
 ///     ctx.db.user().by_id_and_username();
 /// ```
 ///
+/// An index may also be keyed on fields nested inside a column of a product type,
+/// or on `lower(...)` of a `String` column or field:
+/// `index(name = by_x, btree(columns = [position.x]))`
+///
+/// Such expression indexes are used by SQL queries and subscriptions,
+/// e.g. `SELECT * FROM entity WHERE entity.position.x = 5`,
+/// but they don't get an accessor on the table handle and are never unique.
+///
+/// An index may be restricted to the rows matching a SQL predicate:
+/// `index(name = active_by_level, btree(columns = [level], filter = "status = 'active'"))`
+///
+/// Such partial indexes are used by SQL queries and subscriptions
+/// whose `WHERE` clause implies the filter of the index,
+/// e.g. `SELECT * FROM player WHERE status = 'active' AND level = 10`.
+/// The filter may compare columns with literals using `AND` and `OR`.
+/// Like expression indexes, partial indexes don't get an accessor and are never unique.
+///
+/// A `String` column may have a full-text index, which maps each token of the text to its rows:
+/// `index(name = by_text, full_text(column = description))`, or `#[index(full_text)]` on the field.
+///
+/// Such an index gets a [`TextIndex`] accessor to find the rows containing every token of a query,
+/// e.g. `ctx.db.item().by_text().filter_text("red sword")`,
+/// and is used by SQL queries and subscriptions such as
+/// `SELECT * FROM item WHERE match(description, 'red sword')`.
+/// Tokens are lowercased runs of alphanumeric characters.
+///
 /// ### `scheduled(reducer_name)`
 ///
 /// Used to declare a [scheduled reducer](macro@crate::reducer#scheduled-reducers).
@@ -493,9 +519,37 @@ pub use spacetimedb_bindings_macro::table;
 /// Failures will abort the active database transaction.
 /// Any changes to the database made by the failed reducer call will be rolled back.
 ///
+/// # Return values
+///
+/// A reducer returning `Result<T, E>` sends its result to the client which called it,
+/// where `T` is any [`SpacetimeType`].
+/// Errors implementing [`Display`](std::fmt::Display) are sent as their message.
+/// Errors deriving [`ReducerError`](macro@ReducerError) are sent as values of their type,
+/// so that clients can match on them rather than on message text:
+///
+/// ```no_run
+/// # #[cfg(target_arch = "wasm32")] mod demo {
+/// use spacetimedb::{reducer, ReducerContext, ReducerError, SpacetimeType};
+///
+/// #[derive(SpacetimeType, ReducerError, Debug)]
+/// pub enum PlaceError {
+///     Occupied,
+///     OutOfBounds { width: u32, height: u32 },
+/// }
+///
+/// #[reducer]
+/// pub fn place_piece(ctx: &ReducerContext, x: u32, y: u32) -> Result<u64, PlaceError> {
+///     // ... place the piece, returning its id ...
+///     # Ok(0)
+/// }
+/// # }
+/// ```
+///
+/// The return and error types are recorded in the module definition,
+/// and generated client bindings decode them at those types.
+///
 /// Reducers are limited in their ability to interact with the outside world.
-/// They do not directly return data aside from errors, and have no access to any
-/// network or filesystem interfaces.
+/// They have no access to any network or filesystem interfaces.
 /// Calling methods from [`std::io`], [`std::net`], or [`std::fs`]
 /// inside a reducer will result in runtime errors.
 ///
@@ -667,6 +721,18 @@ pub use spacetimedb_bindings_macro::table;
 #[doc(inline)]
 pub use spacetimedb_bindings_macro::reducer;
 
+/// Makes a [`SpacetimeType`] usable as the error type of a reducer,
+/// which clients receive as a value of that type rather than as a message.
+///
+/// The type must implement [`Debug`](std::fmt::Debug),
+/// which formats the error message logged by the database.
+/// It must not implement [`Display`](std::fmt::Display):
+/// errors implementing `Display` are always sent as their message.
+///
+/// See the [`reducer`](macro@reducer) documentation for an example.
+#[doc(inline)]
+pub use spacetimedb_bindings_macro::ReducerError;
+
 /// One of two possible types that can be passed as the first argument to a `#[view]`.
 /// The other is [`ViewContext`].
 /// Use this type if the view does not depend on the caller's identity.
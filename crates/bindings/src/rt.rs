@@ -1,14 +1,14 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
-use crate::table::IndexAlgo;
-use crate::{sys, IterBuf, ReducerContext, ReducerResult, SpacetimeType, Table};
+use crate::table::{IndexAlgo, IndexKeyDesc};
+use crate::{sys, IterBuf, ReducerContext, SpacetimeType, Table};
 pub use spacetimedb_lib::db::raw_def::v9::Lifecycle as LifecycleReducer;
-use spacetimedb_lib::db::raw_def::v9::{RawIndexAlgorithm, RawModuleDefV9Builder, TableType};
+use spacetimedb_lib::db::raw_def::v9::{RawIndexAlgorithm, RawIndexKeyExprV9, RawModuleDefV9Builder, TableType};
 use spacetimedb_lib::de::{self, Deserialize, Error as _, SeqProductAccess};
 use spacetimedb_lib::sats::typespace::TypespaceBuilder;
-use spacetimedb_lib::sats::{impl_deserialize, impl_serialize, ProductTypeElement};
+use spacetimedb_lib::sats::{impl_deserialize, impl_serialize, AlgebraicTypeRef, ProductTypeElement, Typespace};
 use spacetimedb_lib::ser::{Serialize, SerializeSeqProduct};
-use spacetimedb_lib::{bsatn, ConnectionId, Identity, ProductType, RawModuleDef, Timestamp};
+use spacetimedb_lib::{bsatn, AlgebraicType, ConnectionId, Identity, ProductType, RawModuleDef, Timestamp};
 use spacetimedb_primitives::*;
 use std::fmt;
 use std::marker::PhantomData;
@@ -17,30 +17,57 @@ use sys::raw::{BytesSink, BytesSource};
 
 /// The `sender` invokes `reducer` at `timestamp` and provides it with the given `args`.
 ///
-/// Returns an invalid buffer on success
-/// and otherwise the error is written into the fresh one returned.
+/// Returns the BSATN-encoded value returned by the reducer on success,
+/// and otherwise the error it failed with.
 pub fn invoke_reducer<'a, A: Args<'a>>(
     reducer: impl Reducer<'a, A>,
     ctx: ReducerContext,
     args: &'a [u8],
-) -> Result<(), Box<str>> {
+) -> ReducerOutput {
     // Deserialize the arguments from a bsatn encoding.
     let SerDeArgs(args) = bsatn::from_slice(args).expect("unable to decode args");
 
-    reducer.invoke(&ctx, args)
+    match reducer.invoke(&ctx, args).into_result() {
+        Ok(value) => Ok(encode_return_value(&value)),
+        Err(ReducerFailure { message, value }) => Err(EncodedReducerFailure {
+            message,
+            value: value.as_ref().map(encode_return_value),
+        }),
+    }
+}
+
+fn encode_return_value(value: &impl Serialize) -> Vec<u8> {
+    bsatn::to_vec(value).expect("unable to encode reducer return value")
+}
+
+/// The outcome of invoking a reducer through [`invoke_reducer`].
+///
+/// On success, this holds the BSATN-encoded value returned by the reducer.
+pub type ReducerOutput = Result<Vec<u8>, EncodedReducerFailure>;
+
+/// A [`ReducerFailure`] with its error value, if any, encoded as BSATN.
+pub struct EncodedReducerFailure {
+    /// A message describing the error.
+    pub message: Box<str>,
+    /// The BSATN-encoded error value.
+    pub value: Option<Vec<u8>>,
 }
+
 /// A trait for types representing the *execution logic* of a reducer.
 #[diagnostic::on_unimplemented(
     message = "invalid reducer signature",
     label = "this reducer signature is not valid",
     note = "",
     note = "reducer signatures must match the following pattern:",
-    note = "    `Fn(&ReducerContext, [T1, ...]) [-> Result<(), impl Display>]`",
-    note = "where each `Ti` type implements `SpacetimeType`.",
+    note = "    `Fn(&ReducerContext, [T1, ...]) [-> Result<T, E>]`",
+    note = "where each `Ti` type and `T` implement `SpacetimeType`.",
     note = ""
 )]
 pub trait Reducer<'de, A: Args<'de>> {
-    fn invoke(&self, ctx: &ReducerContext, args: A) -> ReducerResult;
+    /// The return type of the reducer.
+    type Output: IntoReducerResult;
+
+    fn invoke(&self, ctx: &ReducerContext, args: A) -> Self::Output;
 }
 
 /// A trait for types that can *describe* a reducer.
@@ -76,23 +103,71 @@ pub trait Args<'de>: Sized {
 /// A trait of types representing the result of executing a reducer.
 #[diagnostic::on_unimplemented(
     message = "`{Self}` is not a valid reducer return type",
-    note = "reducers cannot return values -- you can only return `()` or `Result<(), impl Display>`"
+    note = "reducers can only return `()` or `Result<T, E>`, where `T` implements `SpacetimeType`"
 )]
 pub trait IntoReducerResult {
-    /// Convert the result into form where there is no value
-    /// and the error message is a string.
-    fn into_result(self) -> Result<(), Box<str>>;
+    /// The type of the value returned when the reducer succeeds.
+    type Ok: SpacetimeType + Serialize;
+    /// The type of the error value returned when the reducer fails.
+    type Err: SpacetimeType + Serialize;
+
+    /// Convert the result into the value returned on success,
+    /// or the failure returned otherwise.
+    fn into_result(self) -> Result<Self::Ok, ReducerFailure<Self::Err>>;
 }
 impl IntoReducerResult for () {
+    type Ok = ();
+    type Err = String;
+
     #[inline]
-    fn into_result(self) -> Result<(), Box<str>> {
+    fn into_result(self) -> Result<(), ReducerFailure<String>> {
         Ok(self)
     }
 }
-impl<E: fmt::Display> IntoReducerResult for Result<(), E> {
+impl<T: SpacetimeType + Serialize, E: IntoReducerError> IntoReducerResult for Result<T, E> {
+    type Ok = T;
+    type Err = E::Err;
+
     #[inline]
-    fn into_result(self) -> Result<(), Box<str>> {
-        self.map_err(|e| e.to_string().into())
+    fn into_result(self) -> Result<T, ReducerFailure<E::Err>> {
+        self.map_err(E::into_reducer_error)
+    }
+}
+
+/// The failure of a reducer.
+pub struct ReducerFailure<E> {
+    /// A message describing the error, which is logged by the host.
+    pub message: Box<str>,
+    /// The error value sent to clients.
+    /// If `None`, clients receive the `message` instead.
+    pub value: Option<E>,
+}
+
+/// A trait of types which can be the error of a reducer returning `Result<T, E>`.
+///
+/// Errors implementing [`Display`](fmt::Display) are sent to clients as their message.
+/// Types deriving [`ReducerError`](macro@crate::ReducerError) are instead sent as values,
+/// which clients receive with their declared type.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a valid reducer error type",
+    note = "reducer errors must either implement `Display`, or implement `SpacetimeType` and derive `ReducerError`"
+)]
+pub trait IntoReducerError {
+    /// The type of the error value sent to clients.
+    type Err: SpacetimeType + Serialize;
+
+    /// Convert the error into a [`ReducerFailure`].
+    fn into_reducer_error(self) -> ReducerFailure<Self::Err>;
+}
+impl<E: fmt::Display> IntoReducerError for E {
+    type Err = String;
+
+    #[inline]
+    fn into_reducer_error(self) -> ReducerFailure<String> {
+        ReducerFailure {
+            message: self.to_string().into(),
+            value: None,
+        }
     }
 }
 
@@ -257,10 +332,12 @@ macro_rules! impl_reducer {
             Func: Fn(&ReducerContext, $($T),*) -> Ret,
             Ret: IntoReducerResult
         {
+            type Output = Ret;
+
             #[allow(non_snake_case)]
-            fn invoke(&self, ctx: &ReducerContext, args: ($($T,)*)) -> Result<(), Box<str>> {
+            fn invoke(&self, ctx: &ReducerContext, args: ($($T,)*)) -> Ret {
                 let ($($T,)*) = args;
-                self(ctx, $($T),*).into_result()
+                self(ctx, $($T),*)
             }
         }
 
@@ -319,6 +396,13 @@ pub fn register_reftype<T: SpacetimeType>() {
 pub fn register_table<T: Table>() {
     register_describer(|module| {
         let product_type_ref = *T::Row::make_type(&mut module.inner).as_ref().unwrap();
+        let indexes = T::INDEXES
+            .iter()
+            .map(|index| {
+                let algo = index.algo.into_raw(module.inner.typespace(), product_type_ref);
+                (algo, index.accessor_name)
+            })
+            .collect::<Vec<_>>();
 
         let mut table = module
             .inner
@@ -329,8 +413,8 @@ pub fn register_table<T: Table>() {
         for &col in T::UNIQUE_COLUMNS {
             table = table.with_unique_constraint(col);
         }
-        for &index in T::INDEXES {
-            table = table.with_index(index.algo.into(), index.accessor_name);
+        for (algo, accessor_name) in indexes {
+            table = table.with_index(algo, accessor_name);
         }
         if let Some(primary_key) = T::PRIMARY_KEY {
             table = table.with_primary_key(primary_key);
@@ -350,22 +434,75 @@ pub fn register_table<T: Table>() {
     })
 }
 
-impl From<IndexAlgo<'_>> for RawIndexAlgorithm {
-    fn from(algo: IndexAlgo<'_>) -> RawIndexAlgorithm {
-        match algo {
+impl IndexAlgo<'_> {
+    /// Converts to the raw index algorithm,
+    /// resolving the field names of any expression keys against the row type `row_type`.
+    fn into_raw(self, typespace: &Typespace, row_type: AlgebraicTypeRef) -> RawIndexAlgorithm {
+        match self {
             IndexAlgo::BTree { columns } => RawIndexAlgorithm::BTree {
                 columns: columns.iter().copied().collect(),
             },
             IndexAlgo::Direct { column } => RawIndexAlgorithm::Direct { column: column.into() },
+            IndexAlgo::BTreeExpr { keys } => RawIndexAlgorithm::BTreeExpr {
+                keys: keys
+                    .iter()
+                    .map(|key| RawIndexKeyExprV9 {
+                        column: key.column.into(),
+                        fields: resolve_key_fields(typespace, row_type, key),
+                        function: key.function,
+                    })
+                    .collect(),
+            },
+            IndexAlgo::PartialBTree { columns, filter } => RawIndexAlgorithm::PartialBTree {
+                columns: columns.iter().copied().collect(),
+                filter: filter.into(),
+            },
+            IndexAlgo::FullText { column } => RawIndexAlgorithm::FullText { column: column.into() },
         }
     }
 }
 
+/// Resolves the field names of `key` to their positions in each enclosing product type.
+fn resolve_key_fields(typespace: &Typespace, row_type: AlgebraicTypeRef, key: &IndexKeyDesc<'_>) -> Vec<u16> {
+    let resolve = |mut ty: &AlgebraicType| {
+        while let AlgebraicType::Ref(r) = ty {
+            ty = &typespace[*r];
+        }
+        ty
+    };
+    let row = resolve(&typespace[row_type])
+        .as_product()
+        .expect("table row type must be a product");
+    let mut ty = &row.elements[key.column as usize].algebraic_type;
+    key.fields
+        .iter()
+        .map(|&name| {
+            let (pos, field) = resolve(ty)
+                .as_product()
+                .and_then(|ty| ty.elements.iter().enumerate().find(|(_, field)| field.has_name(name)))
+                .unwrap_or_else(|| panic!("index key field `{name}` is not a field of a product type"));
+            ty = &field.algebraic_type;
+            pos as u16
+        })
+        .collect()
+}
+
 /// Registers a describer for the reducer `I` with arguments `A`.
-pub fn register_reducer<'a, A: Args<'a>, I: ReducerInfo>(_: impl Reducer<'a, A>) {
+pub fn register_reducer<'a, A: Args<'a>, I: ReducerInfo, R: Reducer<'a, A>>(_: R) {
     register_describer(|module| {
         let params = A::schema::<I>(&mut module.inner);
         module.inner.add_reducer(I::NAME, params, I::LIFECYCLE);
+
+        // Only declare return types other than the default `()` and `String`,
+        // so that modules which don't use them remain readable by older hosts.
+        let ok_return_type = <<R::Output as IntoReducerResult>::Ok>::make_type(&mut module.inner);
+        let err_return_type = <<R::Output as IntoReducerResult>::Err>::make_type(&mut module.inner);
+        if !ok_return_type.is_unit() || err_return_type != AlgebraicType::String {
+            module
+                .inner
+                .add_reducer_return_type(I::NAME, ok_return_type, err_return_type);
+        }
+
         module.reducers.push(I::INVOKE);
     })
 }
@@ -391,7 +528,7 @@ static DESCRIBERS: Mutex<Vec<Box<dyn DescriberFn>>> = Mutex::new(Vec::new());
 
 /// A reducer function takes in `(ReducerContext, Args)`
 /// and returns a result with a possible error message.
-pub type ReducerFn = fn(ReducerContext, &[u8]) -> ReducerResult;
+pub type ReducerFn = fn(ReducerContext, &[u8]) -> ReducerOutput;
 static REDUCERS: OnceLock<Vec<ReducerFn>> = OnceLock::new();
 
 /// Called by the host when the module is initialized
@@ -455,12 +592,17 @@ extern "C" fn __describe_module__(description: BytesSink) {
 /// The contents of the buffer are the BSATN-encoding of the arguments to the reducer.
 /// In the case of empty arguments, `args` will be 0, that is, invalid.
 ///
-/// The `error` is a `BytesSink`, registered on the host side,
+/// The `result` is a `BytesSink`, registered on the host side,
 /// which can be written to with `bytes_sink_write`.
-/// When `error` is written to,
-/// it is expected that `HOST_CALL_FAILURE` is returned.
-/// Otherwise, `0` should be returned, i.e., the reducer completed successfully.
-/// Note that in the future, more failure codes could be supported.
+/// When the reducer completes successfully, `0` is returned,
+/// and the BSATN-encoding of the returned value is written to `result`.
+/// For reducers returning `()`, nothing is written.
+/// When the reducer fails with an error message,
+/// the message is written to `result` and `HOST_CALL_FAILURE` is returned.
+/// When the reducer fails with an error value,
+/// the BSATN-encoding of the message as a string,
+/// followed by the BSATN-encoding of the value, is written to `result`,
+/// and `HOST_CALL_FAILURE_VALUE` is returned.
 #[no_mangle]
 extern "C" fn __call_reducer__(
     id: usize,
@@ -472,7 +614,7 @@ extern "C" fn __call_reducer__(
     conn_id_1: u64,
     timestamp: u64,
     args: BytesSource,
-    error: BytesSink,
+    result: BytesSink,
 ) -> i16 {
     // Piece together `sender_i` into an `Identity`.
     let sender = [sender_0, sender_1, sender_2, sender_3];
@@ -494,13 +636,27 @@ extern "C" fn __call_reducer__(
     let reducers = REDUCERS.get().unwrap();
     // Dispatch to it with the arguments read.
     let res = with_read_args(args, |args| reducers[id](ctx, args));
-    // Convert any error message to an error code and writes to the `error` sink.
+    // Write the return value or error to the `result` sink and convert the outcome to a code.
     match res {
-        Ok(()) => 0,
-        Err(msg) => {
-            write_to_sink(error, msg.as_bytes());
+        Ok(value) => {
+            if !value.is_empty() {
+                write_to_sink(result, &value);
+            }
+            0
+        }
+        Err(EncodedReducerFailure { message, value: None }) => {
+            write_to_sink(result, message.as_bytes());
             errno::HOST_CALL_FAILURE.get() as i16
         }
+        Err(EncodedReducerFailure {
+            message,
+            value: Some(value),
+        }) => {
+            let mut buf = bsatn::to_vec(&message).expect("unable to encode reducer error message");
+            buf.extend_from_slice(&value);
+            write_to_sink(result, &buf);
+            errno::HOST_CALL_FAILURE_VALUE.get() as i16
+        }
     }
 }
 
@@ -3,12 +3,12 @@ use core::borrow::Borrow;
 use core::convert::Infallible;
 use core::fmt;
 use core::marker::PhantomData;
-pub use spacetimedb_lib::db::raw_def::v9::TableAccess;
+pub use spacetimedb_lib::db::raw_def::v9::{IndexKeyFunction, TableAccess};
 use spacetimedb_lib::{
     buffer::{BufReader, Cursor, DecodeError},
     AlgebraicValue,
 };
-use spacetimedb_lib::{FilterableValue, IndexScanRangeBoundsTerminator};
+use spacetimedb_lib::{full_text, FilterableValue, IndexScanRangeBoundsTerminator};
 pub use spacetimedb_primitives::{ColId, IndexId};
 
 /// Implemented for every `TableHandle` struct generated by the [`table`](macro@crate::table) macro.
@@ -146,6 +146,20 @@ pub struct IndexDesc<'a> {
 pub enum IndexAlgo<'a> {
     BTree { columns: &'a [u16] },
     Direct { column: u16 },
+    BTreeExpr { keys: &'a [IndexKeyDesc<'a>] },
+    PartialBTree { columns: &'a [u16], filter: &'a str },
+    FullText { column: u16 },
+}
+
+/// Describe one key of an expression index:
+/// a column, a path of field names into it, and an optional function applied to the result.
+///
+/// Field names are resolved to positions against the row type when the module is described.
+#[derive(Clone, Copy)]
+pub struct IndexKeyDesc<'a> {
+    pub column: u16,
+    pub fields: &'a [&'a str],
+    pub function: Option<IndexKeyFunction>,
 }
 
 pub struct ScheduleDesc<'a> {
@@ -723,6 +737,89 @@ impl<Tbl: Table, IndexType, Idx: Index> RangedIndexReadOnly<Tbl, IndexType, Idx>
     }
 }
 
+/// A handle to a full-text index on a `String` column.
+///
+/// To get one of these from a `ReducerContext`, use:
+/// ```text
+/// ctx.db.{table}().{index}()
+/// ```
+/// for a table *table* and an index *index*.
+///
+/// Example:
+///
+/// ```no_run
+/// # #[cfg(target_arch = "wasm32")] mod demo {
+/// use spacetimedb::{table, TextIndex, ReducerContext, DbContext};
+///
+/// #[table(name = item)]
+/// struct Item {
+///     id: u32,
+///     #[index(full_text)]
+///     description: String,
+/// }
+///
+/// fn demo(ctx: &ReducerContext) {
+///     let by_description: TextIndex<_, _> = ctx.db.item().description();
+///     for item in by_description.filter_text("red sword") {
+///         log::info!("{}", item.description);
+///     }
+/// }
+/// # }
+/// ```
+pub struct TextIndex<Tbl, Col> {
+    _marker: PhantomData<(Tbl, Col)>,
+}
+
+impl<Tbl: Table, Col: Index + Column<Table = Tbl, ColType = String>> TextIndex<Tbl, Col> {
+    #[doc(hidden)]
+    pub const __NEW: Self = Self { _marker: PhantomData };
+
+    /// Returns an iterator over all rows in the database state
+    /// where the indexed column contains every token of `query`.
+    ///
+    /// Tokens are runs of alphanumeric characters and are compared case-insensitively,
+    /// so `"Red sword!"` matches a row with `"a sword, red as blood"`.
+    /// A query without any tokens matches no rows.
+    pub fn filter_text(&self, query: &str) -> impl Iterator<Item = Tbl::Row> {
+        filter_text::<Tbl, Col>(query)
+    }
+}
+
+fn filter_text<Tbl: Table, Col: Index + Column<Table = Tbl, ColType = String>>(
+    query: &str,
+) -> impl Iterator<Item = Tbl::Row> {
+    let terms = full_text::tokenize(query);
+    // Scan the rows containing the longest token, as it's likely the rarest,
+    // and then check those rows for the rest of the query.
+    let rows = terms.iter().max_by_key(|term| term.len()).map(|term| {
+        let args = get_args::<Tbl, Col>(term);
+        let (prefix, prefix_elems, rstart, rend) = args.args_for_syscall();
+        let iter = sys::datastore_index_scan_range_bsatn(Col::index_id(), prefix, prefix_elems, rstart, rend)
+            .unwrap_or_else(|e| panic!("unexpected error from `datastore_index_scan_range_bsatn`: {e}"));
+        TableIter::<Tbl::Row>::new_with_buf(iter, args.data)
+    });
+    rows.into_iter()
+        .flatten()
+        .filter(move |row| full_text::matches(Col::get_field(row), &terms))
+}
+
+/// A read-only handle to a full-text index.
+///
+/// This is the read-only version of [`TextIndex`].
+/// It is used by `{table}__ViewHandle` to keep view code read-only at compile time.
+pub struct TextIndexReadOnly<Tbl, Col> {
+    _marker: PhantomData<(Tbl, Col)>,
+}
+
+impl<Tbl: Table, Col: Index + Column<Table = Tbl, ColType = String>> TextIndexReadOnly<Tbl, Col> {
+    #[doc(hidden)]
+    pub const __NEW: Self = Self { _marker: PhantomData };
+
+    pub fn filter_text(&self, query: &str) -> impl Iterator<Item = Tbl::Row> {
+        filter_text::<Tbl, Col>(query)
+    }
+}
+
 /// Trait used for overloading methods on [`RangedIndex`].
 /// See [`RangedIndex`] for more information.
 pub trait IndexScanRangeBounds<T, K = ()> {
@@ -4,6 +4,7 @@ use std::ops::Add;
 use reqwest::{header, Client, RequestBuilder};
 use serde::Deserialize;
 
+use spacetimedb_client_api_messages::replay::ReplayReport;
 use spacetimedb_lib::db::raw_def::v9::RawModuleDefV9;
 use spacetimedb_lib::de::serde::DeserializeWrapper;
 use spacetimedb_lib::Identity;
@@ -72,6 +73,17 @@ impl ClientApi {
         Ok(module_def)
     }
 
+    /// Replays the transaction at `tx_offset` via the `replay` endpoint.
+    pub async fn replay(&self, tx_offset: u64) -> anyhow::Result<ReplayReport> {
+        let res = self
+            .client
+            .post(self.con.db_uri("replay"))
+            .query(&[("tx_offset", tx_offset)])
+            .send()
+            .await?;
+        res.json_or_error().await
+    }
+
     pub async fn call(&self, reducer_name: &str, arg_json: String) -> anyhow::Result<reqwest::Response> {
         Ok(self
             .client
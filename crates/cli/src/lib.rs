@@ -22,11 +22,14 @@ pub fn get_subcommands() -> Vec<Command> {
     vec![
         publish::cli(),
         delete::cli(),
+        db::cli(),
+        token::cli(),
         logs::cli(),
         call::cli(),
         describe::cli(),
         energy::cli(),
         sql::cli(),
+        replay::cli(),
         dns::cli(),
         generate::cli(),
         list::cli(),
@@ -34,6 +37,7 @@ pub fn get_subcommands() -> Vec<Command> {
         logout::cli(),
         init::cli(),
         build::cli(),
+        dev::cli(),
         server::cli(),
         subscribe::cli(),
         start::cli(),
@@ -54,13 +58,17 @@ pub async fn exec_subcommand(
         "energy" => energy::exec(config, args).await,
         "publish" => publish::exec(config, args).await,
         "delete" => delete::exec(config, args).await,
+        "db" => db::exec(config, args).await,
+        "token" => token::exec(config, args).await,
         "logs" => logs::exec(config, args).await,
         "sql" => sql::exec(config, args).await,
+        "replay" => return replay::exec(config, args).await,
         "rename" => dns::exec(config, args).await,
         "generate" => generate::exec(config, args).await,
         "list" => list::exec(config, args).await,
         "init" => init::exec(config, args).await,
         "build" => build::exec(config, args).await.map(drop),
+        "dev" => dev::exec(config, args).await,
         "server" => server::exec(config, paths, args).await,
         "subscribe" => subscribe::exec(config, args).await,
         "start" => return start::exec(paths, args).await,
@@ -84,6 +84,12 @@ pub async fn exec(config: Config, args: &ArgMatches) -> Result<(), Error> {
         return error.context(error_msg);
     }
 
+    // Reducers which declare a return type respond with the value they returned.
+    let return_value = res.text().await?;
+    if !return_value.is_empty() {
+        println!("{return_value}");
+    }
+
     Ok(())
 }
 
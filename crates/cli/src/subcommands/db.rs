@@ -0,0 +1,283 @@
+use crate::common_args;
+use crate::config::Config;
+use crate::util::{add_auth_header_opt, database_identity, get_auth_header, ResponseExt};
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use spacetimedb_client_api_messages::auth_policy::AuthPolicy;
+use spacetimedb_client_api_messages::collaborators::{Collaborator, DatabaseRole, GetCollaboratorsResponse};
+use spacetimedb_client_api_messages::snapshot::TakeSnapshotResponse;
+use spacetimedb_lib::Identity;
+use tabled::{
+    settings::{object::Columns, Alignment, Modify, Style},
+    Table, Tabled,
+};
+
+pub fn cli() -> Command {
+    Command::new("db")
+        .about("Manage access to and snapshots of a database")
+        .args_conflicts_with_subcommands(true)
+        .subcommand_required(true)
+        .subcommands(get_subcommands())
+}
+
+fn database_arg() -> Arg {
+    Arg::new("database")
+        .required(true)
+        .help("The name or identity of the database")
+}
+
+fn collaborator_arg() -> Arg {
+    Arg::new("collaborator")
+        .required(true)
+        .value_parser(clap::value_parser!(Identity))
+        .help("The identity of the collaborator")
+}
+
+fn get_subcommands() -> Vec<Command> {
+    vec![
+        Command::new("grant")
+            .about("Grant a role on a database to an identity, replacing any role it held before")
+            .arg(database_arg())
+            .arg(collaborator_arg())
+            .arg(
+                Arg::new("role")
+                    .long("role")
+                    .short('r')
+                    .required(true)
+                    .value_parser(["read-only", "developer", "admin", "owner"])
+                    .help("The role to grant"),
+            )
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+        Command::new("revoke")
+            .about("Revoke the role an identity holds on a database")
+            .arg(database_arg())
+            .arg(collaborator_arg())
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+        Command::new("collaborators")
+            .about("List the identities holding a role on a database")
+            .arg(database_arg())
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+        Command::new("auth-policy")
+            .about("Show the issuers and audiences of tokens accepted by a database")
+            .arg(database_arg())
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+        Command::new("set-auth-policy")
+            .about("Restrict the issuers and audiences of tokens accepted by a database")
+            .arg(database_arg())
+            .arg(
+                Arg::new("issuer")
+                    .long("issuer")
+                    .action(ArgAction::Append)
+                    .help("An accepted token issuer. May be repeated. If omitted, tokens from any issuer are accepted"),
+            )
+            .arg(
+                Arg::new("audience").long("audience").action(ArgAction::Append).help(
+                    "An accepted token audience. May be repeated. If omitted, tokens for any audience are accepted",
+                ),
+            )
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+        Command::new("snapshot")
+            .about("Take a snapshot of a database now, regardless of its snapshot policy")
+            .arg(database_arg())
+            .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+            .arg(common_args::yes()),
+    ]
+}
+
+pub async fn exec(config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let (cmd, subcommand_args) = args.subcommand().expect("Subcommand required");
+    match cmd {
+        "grant" => exec_grant(config, subcommand_args).await,
+        "revoke" => exec_revoke(config, subcommand_args).await,
+        "collaborators" => exec_collaborators(config, subcommand_args).await,
+        "auth-policy" => exec_auth_policy(config, subcommand_args).await,
+        "set-auth-policy" => exec_set_auth_policy(config, subcommand_args).await,
+        "snapshot" => exec_snapshot(config, subcommand_args).await,
+        unknown => Err(anyhow::anyhow!("Invalid subcommand: {unknown}")),
+    }
+}
+
+async fn exec_grant(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+    let collaborator = args.get_one::<Identity>("collaborator").unwrap();
+    let role: DatabaseRole = args.get_one::<String>("role").unwrap().parse()?;
+    let force = args.get_flag("force");
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let builder = reqwest::Client::new()
+        .put(format!(
+            "{}/v1/database/{}/collaborators/{}",
+            config.get_host_url(server)?,
+            identity,
+            collaborator
+        ))
+        .query(&[("role", role.to_string())]);
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let Collaborator { identity: _, role } = builder.send().await?.json_or_error().await?;
+
+    println!("Granted the `{role}` role on {database} to {collaborator}.");
+
+    Ok(())
+}
+
+async fn exec_revoke(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+    let collaborator = args.get_one::<Identity>("collaborator").unwrap();
+    let force = args.get_flag("force");
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let builder = reqwest::Client::new().delete(format!(
+        "{}/v1/database/{}/collaborators/{}",
+        config.get_host_url(server)?,
+        identity,
+        collaborator
+    ));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let res = builder.send().await?;
+    if let Err(e) = res.error_for_status_ref() {
+        let text = res.text().await?;
+        return Err(anyhow::Error::from(e).context(text));
+    }
+
+    println!("Revoked the role of {collaborator} on {database}.");
+
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct CollaboratorRow {
+    identity: Identity,
+    role: DatabaseRole,
+}
+
+async fn exec_collaborators(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+    let force = args.get_flag("force");
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let builder = reqwest::Client::new().get(format!(
+        "{}/v1/database/{}/collaborators",
+        config.get_host_url(server)?,
+        identity
+    ));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let GetCollaboratorsResponse { collaborators } = builder
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to retrieve collaborators")?;
+
+    let mut table = Table::new(
+        collaborators
+            .into_iter()
+            .map(|Collaborator { identity, role }| CollaboratorRow { identity, role }),
+    );
+    table
+        .with(Style::psql())
+        .with(Modify::new(Columns::first()).with(Alignment::left()));
+    println!("{table}");
+
+    Ok(())
+}
+
+fn print_auth_policy(database: &str, AuthPolicy { issuers, audiences }: &AuthPolicy) {
+    let or_any = |values: &[String]| match values {
+        [] => "any".to_owned(),
+        values => values.join(", "),
+    };
+    println!("Tokens accepted by {database}:");
+    println!("  issuers:   {}", or_any(issuers));
+    println!("  audiences: {}", or_any(audiences));
+}
+
+async fn exec_auth_policy(config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let policy: AuthPolicy = reqwest::Client::new()
+        .get(format!(
+            "{}/v1/database/{}/auth-policy",
+            config.get_host_url(server)?,
+            identity
+        ))
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to retrieve auth policy")?;
+
+    print_auth_policy(database, &policy);
+
+    Ok(())
+}
+
+async fn exec_set_auth_policy(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+    let force = args.get_flag("force");
+    let values = |arg: &str| args.get_many::<String>(arg).into_iter().flatten().cloned().collect();
+    let policy = AuthPolicy {
+        issuers: values("issuer"),
+        audiences: values("audience"),
+    };
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let builder = reqwest::Client::new()
+        .put(format!(
+            "{}/v1/database/{}/auth-policy",
+            config.get_host_url(server)?,
+            identity
+        ))
+        .json(&policy);
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let policy: AuthPolicy = builder.send().await?.json_or_error().await?;
+
+    print_auth_policy(database, &policy);
+
+    Ok(())
+}
+
+async fn exec_snapshot(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let database = args.get_one::<String>("database").unwrap();
+    let force = args.get_flag("force");
+
+    let identity = database_identity(&config, database, server).await?;
+
+    let builder = reqwest::Client::new().post(format!(
+        "{}/v1/database/{}/snapshot",
+        config.get_host_url(server)?,
+        identity
+    ));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let TakeSnapshotResponse { tx_offset } = builder
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to take snapshot")?;
+
+    println!("Took a snapshot of {database} at transaction offset {tx_offset}.");
+
+    Ok(())
+}
@@ -6,6 +6,8 @@ use crate::util::UNSTABLE_WARNING;
 use anyhow::Context;
 use clap::{Arg, ArgAction, ArgMatches};
 use spacetimedb_lib::sats;
+use spacetimedb_schema::def::ModuleDef;
+use spacetimedb_schema::openapi::openapi_document;
 
 pub fn cli() -> clap::Command {
     clap::Command::new("describe")
@@ -33,12 +35,22 @@ pub fn cli() -> clap::Command {
                 .long("json")
                 .action(ArgAction::SetTrue)
                 // make not required() once we have a human readable output
-                .required(true)
+                .required_unless_present("openapi")
                 .help(
                     "Output the schema in JSON format. Currently required; in the future, omitting this will \
                      give human-readable output.",
                 ),
         )
+        .arg(
+            Arg::new("openapi")
+                .long("openapi")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["json", "entity_type"])
+                .help(
+                    "Output an OpenAPI document describing the database's HTTP API, \
+                     with JSON Schemas for the arguments of its reducers and the rows of its tables",
+                ),
+        )
         .arg(common_args::anonymous())
         .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
         .arg(common_args::yes())
@@ -58,13 +70,21 @@ pub async fn exec(config: Config, args: &ArgMatches) -> Result<(), anyhow::Error
     let entity_type = args.get_one::<EntityType>("entity_type");
     let entity = entity_type.zip(entity_name);
     let json = args.get_flag("json");
+    let openapi = args.get_flag("openapi");
 
     let conn = parse_req(config, args).await?;
+    let host = conn.host.clone();
+    let database = conn.database.clone();
     let api = ClientApi::new(conn);
 
     let module_def = api.module_def().await?;
 
-    if json {
+    if openapi {
+        let module_def = ModuleDef::try_from(module_def)?;
+        let mut document = openapi_document(&module_def, &database);
+        document["servers"] = serde_json::json!([{ "url": host }]);
+        println!("{}", serde_json::to_string_pretty(&document)?);
+    } else if json {
         fn sats_to_json<T: sats::Serialize>(v: &T) -> serde_json::Result<String> {
             serde_json::to_string_pretty(sats::serde::SerdeWrapper::from_ref(v))
         }
@@ -0,0 +1,442 @@
+use std::ffi::OsStr;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::ArgAction::Set;
+use clap::{Arg, ArgMatches, ValueEnum};
+use reqwest::Url;
+use spacetimedb_client_api_messages::name::{
+    is_identity, parse_database_name, MigrationPolicy, PublishOp, PublishResult,
+};
+use tokio::task::{JoinHandle, LocalSet};
+
+use crate::common_args;
+use crate::config::Config;
+use crate::subcommands::generate::{self, Language};
+use crate::subcommands::{build, logs, publish};
+use crate::util::{add_auth_header_opt, get_auth_header, y_or_n, AuthHeader, ResponseExt, UNSTABLE_WARNING};
+
+/// How often the project directory is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directories which hold build output or dependencies rather than module sources.
+const IGNORED_DIRS: &[&str] = &["target", "node_modules", "bin", "obj", "dist"];
+
+pub fn cli() -> clap::Command {
+    clap::Command::new("dev")
+        .about(format!(
+            "Rebuild and republish a module to a local server whenever its sources change. {UNSTABLE_WARNING}"
+        ))
+        .arg(
+            Arg::new("database")
+                .required(true)
+                .help("The name or identity of the local database to publish to"),
+        )
+        .arg(
+            Arg::new("project_path")
+                .value_parser(clap::value_parser!(PathBuf))
+                .default_value(".")
+                .long("project-path")
+                .short('p')
+                .help("The system path (absolute or relative) to the module project"),
+        )
+        .arg(
+            Arg::new("build_options")
+                .long("build-options")
+                .alias("build-opts")
+                .action(Set)
+                .default_value("")
+                .help("Options to pass to the build command, for example --build-options='--lint-dir='"),
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .short('l')
+                .value_parser(clap::value_parser!(Language))
+                .requires("out_dir")
+                .help("The language to regenerate client bindings in after each publish"),
+        )
+        .arg(
+            Arg::new("out_dir")
+                .value_parser(clap::value_parser!(PathBuf))
+                .long("out-dir")
+                .short('o')
+                .requires("lang")
+                .help("The system path (absolute or relative) to write client bindings to"),
+        )
+        .arg(common_args::server().help("The nickname, host name or URL of the local server to publish to"))
+        .arg(common_args::yes())
+        .after_help("Run `spacetime help dev` for more detailed information.\n")
+}
+
+pub async fn exec(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    eprintln!("{UNSTABLE_WARNING}\n");
+
+    let database = args.get_one::<String>("database").unwrap();
+    let project_path = args.get_one::<PathBuf>("project_path").unwrap();
+    let build_options = args.get_one::<String>("build_options").unwrap();
+    let bindings = args
+        .get_one::<Language>("lang")
+        .copied()
+        .zip(args.get_one::<PathBuf>("out_dir"));
+    let server = args.get_one::<String>("server").map(|s| s.as_str());
+    let force = args.get_flag("force");
+
+    if !project_path.is_dir() {
+        anyhow::bail!("Project path does not exist: {}", project_path.display());
+    }
+    if !is_identity(database) {
+        parse_database_name(database)?;
+    }
+    if let Some((Language::UnrealCpp, _)) = bindings {
+        anyhow::bail!("`spacetime dev` cannot generate Unreal bindings; run `spacetime generate` instead");
+    }
+
+    let database_host = config.get_host_url(server)?;
+    let server_address = Url::parse(&database_host)?.host_str().unwrap_or_default().to_owned();
+    if server_address != "localhost" && server_address != "127.0.0.1" {
+        anyhow::bail!(
+            "`spacetime dev` only publishes to a local server, but {} is at {server_address}",
+            config.server_nick_or_host(server)?
+        );
+    }
+
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+
+    // Don't rebuild because the bindings we just generated changed.
+    let mut ignored = vec![];
+    if let Some((_, out_dir)) = bindings {
+        fs_err::create_dir_all(out_dir)?;
+        ignored.push(out_dir.canonicalize()?);
+    }
+
+    let encode_set = const { &percent_encoding::NON_ALPHANUMERIC.remove(b'_').remove(b'-') };
+    let dev = Dev {
+        config: config.clone(),
+        client: reqwest::Client::new(),
+        domain: percent_encoding::percent_encode(database.as_bytes(), encode_set).to_string(),
+        database_host,
+        database,
+        server,
+        auth_header,
+        force,
+    };
+
+    // The log tail runs alongside the watch loop on this thread,
+    // as the log printer holds a lock on stdout across awaits.
+    LocalSet::new()
+        .run_until(async move {
+            let mut logs: Option<JoinHandle<()>> = None;
+            loop {
+                let fingerprint = source_fingerprint(project_path, &ignored);
+
+                match dev.rebuild(project_path, build_options, bindings).await {
+                    Ok(Publish::Skipped) => {}
+                    Ok(publish) => {
+                        let restart = publish == Publish::Replaced || logs.as_ref().is_none_or(|l| l.is_finished());
+                        if restart {
+                            if let Some(logs) = logs.take() {
+                                logs.abort();
+                            }
+                            logs = Some(dev.tail_logs());
+                        }
+                    }
+                    Err(e) => eprintln!("Error: {e:?}"),
+                }
+
+                println!("Watching {} for changes...", project_path.display());
+                wait_for_change(project_path, &ignored, fingerprint).await;
+            }
+        })
+        .await
+}
+
+/// The result of trying to publish a freshly built module.
+#[derive(PartialEq, Eq)]
+enum Publish {
+    /// The user declined to clear the database, so nothing was published.
+    Skipped,
+    /// The running database was migrated to the new module.
+    Updated,
+    /// The database was created, or its data was cleared.
+    Replaced,
+}
+
+struct Dev<'a> {
+    config: Config,
+    client: reqwest::Client,
+    database_host: String,
+    /// The database name or identity, encoded for use in a URL path.
+    domain: String,
+    database: &'a str,
+    server: Option<&'a str>,
+    auth_header: AuthHeader,
+    force: bool,
+}
+
+impl Dev<'_> {
+    /// Build the module, publish it and regenerate the client bindings.
+    async fn rebuild(
+        &self,
+        project_path: &Path,
+        build_options: &str,
+        bindings: Option<(Language, &PathBuf)>,
+    ) -> anyhow::Result<Publish> {
+        let (program_path, host_type) =
+            build::exec_with_argstring(self.config.clone(), project_path, build_options).await?;
+        let program_bytes = fs_err::read(&program_path)?;
+
+        let publish = self.publish(host_type, program_bytes).await?;
+
+        if let Some((lang, out_dir)) = bindings {
+            if publish != Publish::Skipped {
+                self.generate(&program_path, host_type, lang, out_dir).await?;
+            }
+        }
+
+        Ok(publish)
+    }
+
+    /// Publish with [`MigrationPolicy::Compatible`],
+    /// offering to clear the database if the module can't be migrated without breaking clients.
+    async fn publish(&self, host_type: &str, program_bytes: Vec<u8>) -> anyhow::Result<Publish> {
+        let pre_publish = publish::call_pre_publish(
+            &self.client,
+            &self.database_host,
+            &self.domain,
+            host_type,
+            &program_bytes,
+            &self.auth_header,
+        )
+        .await;
+        let compatible = match pre_publish {
+            Ok(None) => true,
+            Ok(Some(pre)) => {
+                println!("{}", pre.migrate_plan);
+                if pre.break_clients {
+                    println!("The above changes would BREAK existing clients.");
+                }
+                !pre.break_clients
+            }
+            Err(e) => {
+                eprintln!("{e:#}");
+                false
+            }
+        };
+
+        if compatible {
+            match self.put(host_type, &program_bytes, false).await {
+                Ok(PublishOp::Created) => return Ok(Publish::Replaced),
+                Ok(PublishOp::Updated) => return Ok(Publish::Updated),
+                Err(e) => eprintln!("Failed to update {}: {e:#}", self.database),
+            }
+        }
+
+        if !y_or_n(
+            self.force,
+            &format!(
+                "Delete ALL data in {} and publish the module from scratch?",
+                self.database
+            ),
+        )? {
+            println!("Not publishing this build.");
+            return Ok(Publish::Skipped);
+        }
+        self.put(host_type, &program_bytes, true).await?;
+        Ok(Publish::Replaced)
+    }
+
+    async fn put(&self, host_type: &str, program_bytes: &[u8], clear: bool) -> anyhow::Result<PublishOp> {
+        println!("Publishing module...");
+        let mut builder = self
+            .client
+            .put(format!("{}/v1/database/{}", self.database_host, self.domain))
+            .query(&[("host_type", host_type)])
+            .query(&[("policy", MigrationPolicy::Compatible)]);
+        if clear {
+            builder = builder.query(&[("clear", true)]);
+        }
+        builder = add_auth_header_opt(builder, &self.auth_header);
+
+        let res = builder.body(program_bytes.to_vec()).send().await?;
+        match res.json_or_error().await? {
+            PublishResult::Success {
+                database_identity, op, ..
+            } => {
+                let op_name = match op {
+                    PublishOp::Created => "Created new",
+                    PublishOp::Updated => "Updated",
+                };
+                println!("{op_name} database {}, identity: {database_identity}", self.database);
+                Ok(op)
+            }
+            PublishResult::PermissionDenied { name } => {
+                anyhow::bail!("The database {name} is not registered to your identity; pick another name")
+            }
+        }
+    }
+
+    async fn generate(
+        &self,
+        program_path: &Path,
+        host_type: &str,
+        lang: Language,
+        out_dir: &Path,
+    ) -> anyhow::Result<()> {
+        let lang = lang.to_possible_value().unwrap();
+        let program_flag = if host_type == "Js" { "--js-path" } else { "--bin-path" };
+        let args = generate::cli().try_get_matches_from([
+            OsStr::new("generate"),
+            OsStr::new("--lang"),
+            OsStr::new(lang.get_name()),
+            OsStr::new("--out-dir"),
+            out_dir.as_os_str(),
+            OsStr::new(program_flag),
+            program_path.as_os_str(),
+            OsStr::new("--yes"),
+        ])?;
+        generate::exec(self.config.clone(), &args)
+            .await
+            .context("failed to generate client bindings")
+    }
+
+    /// Print the database's logs as they are written, until the returned task is aborted.
+    fn tail_logs(&self) -> JoinHandle<()> {
+        let mut argv = vec!["logs", self.database, "--follow"];
+        if let Some(server) = self.server {
+            argv.extend(["--server", server]);
+        }
+        if self.force {
+            argv.push("--yes");
+        }
+        let args = logs::cli().get_matches_from(argv);
+        let config = self.config.clone();
+        tokio::task::spawn_local(async move {
+            if let Err(e) = logs::exec(config, &args).await {
+                eprintln!("Stopped following logs: {e:#}");
+            }
+        })
+    }
+}
+
+/// Wait until the sources under `project_path` differ from `fingerprint`
+/// and have then stayed the same for one poll, so that a burst of saves leads to a single rebuild.
+async fn wait_for_change(project_path: &Path, ignored: &[PathBuf], fingerprint: u64) {
+    let mut debounce = Debounce::new(fingerprint);
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if debounce.poll(source_fingerprint(project_path, ignored)) {
+            return;
+        }
+    }
+}
+
+/// Tracks the source fingerprints seen since the last build.
+struct Debounce {
+    /// The fingerprint of the sources the last build was started from.
+    built: u64,
+    /// The fingerprint seen by the latest poll.
+    current: u64,
+}
+
+impl Debounce {
+    fn new(built: u64) -> Self {
+        Self { built, current: built }
+    }
+
+    /// Record the fingerprint `next` of a poll,
+    /// and return whether the sources changed since the build and then settled.
+    fn poll(&mut self, next: u64) -> bool {
+        let settled = next == self.current && self.current != self.built;
+        self.current = next;
+        settled
+    }
+}
+
+/// Hash the path and modification time of every file in the project,
+/// skipping hidden directories, build output and `ignored` paths.
+fn source_fingerprint(project_path: &Path, ignored: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let walk = walkdir::WalkDir::new(project_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            let is_ignored_dir = entry.depth() > 0
+                && entry.file_type().is_dir()
+                && (name.starts_with('.') || IGNORED_DIRS.contains(&&*name));
+            let is_ignored_path = || {
+                let path = entry.path().canonicalize();
+                path.is_ok_and(|path| ignored.iter().any(|ignored| path.starts_with(ignored)))
+            };
+            !is_ignored_dir && !(entry.file_type().is_dir() && is_ignored_path())
+        });
+    for entry in walk.filter_map(Result::ok).filter(|entry| entry.file_type().is_file()) {
+        entry.path().hash(&mut hasher);
+        if let Some(modified) = entry.metadata().ok().and_then(|meta| meta.modified().ok()) {
+            modified.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_debounce() {
+        let mut debounce = Debounce::new(1);
+        // Nothing changed.
+        assert!(!debounce.poll(1));
+        // A burst of saves.
+        assert!(!debounce.poll(2));
+        assert!(!debounce.poll(3));
+        // The sources settled.
+        assert!(debounce.poll(3));
+
+        // Changes which are undone before they settle don't lead to a rebuild.
+        let mut debounce = Debounce::new(1);
+        assert!(!debounce.poll(2));
+        assert!(!debounce.poll(1));
+        assert!(!debounce.poll(1));
+    }
+
+    #[test]
+    fn test_source_fingerprint() -> anyhow::Result<()> {
+        // The project directory itself is hidden, which must not hide its contents.
+        let tmp = tempfile::tempdir()?;
+        let project = tmp.path().join(".project");
+        let out_dir = project.join("client").join("module_bindings");
+        fs::create_dir_all(project.join("src"))?;
+        fs::create_dir_all(&out_dir)?;
+        fs::write(project.join("src/lib.rs"), "")?;
+        let ignored = [out_dir.canonicalize()?];
+
+        let fingerprint = || source_fingerprint(&project, &ignored);
+        let initial = fingerprint();
+
+        // Build output, dependencies, hidden directories and generated bindings are skipped.
+        for dir in IGNORED_DIRS.iter().chain(&[".git"]) {
+            fs::create_dir_all(project.join(dir))?;
+            fs::write(project.join(dir).join("file"), "")?;
+        }
+        fs::write(out_dir.join("mod.rs"), "")?;
+        assert_eq!(fingerprint(), initial);
+
+        // Only directories are skipped by name.
+        fs::write(project.join("src/target"), "")?;
+        let with_file = fingerprint();
+        assert_ne!(with_file, initial);
+
+        // Sources in nested directories are watched.
+        fs::create_dir_all(project.join("src/tables"))?;
+        fs::write(project.join("src/tables/player.rs"), "")?;
+        assert_ne!(fingerprint(), with_file);
+
+        Ok(())
+    }
+}
@@ -1,9 +1,18 @@
 // use clap::Arg;
 use crate::common_args;
-use clap::ArgMatches;
+use anyhow::Context;
+use clap::{Arg, ArgMatches};
+use spacetimedb_client_api_messages::energy::{EnergyUsageRecord, GetEnergyUsageResponse};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tabled::{
+    settings::{object::Columns, Alignment, Modify, Style},
+    Table, Tabled,
+};
 
 use crate::config::Config;
-use crate::util::{self, get_login_token_or_log_in, UNSTABLE_WARNING};
+use crate::util::{
+    self, add_auth_header_opt, get_auth_header, get_login_token_or_log_in, ResponseExt, UNSTABLE_WARNING,
+};
 
 pub fn cli() -> clap::Command {
     clap::Command::new("energy")
@@ -16,25 +25,45 @@ pub fn cli() -> clap::Command {
 }
 
 fn get_energy_subcommands() -> Vec<clap::Command> {
-    vec![clap::Command::new("balance")
-        .about("Show current energy balance for an identity")
-        .arg(
-            common_args::identity()
-                .help("The identity to check the balance for")
-                .long_help(
+    vec![
+        clap::Command::new("balance")
+            .about("Show current energy balance for an identity")
+            .arg(
+                common_args::identity()
+                    .help("The identity to check the balance for")
+                    .long_help(
                     "The identity to check the balance for. If no identity is provided, the default one will be used.",
                 ),
-        )
-        .arg(
-            common_args::server()
-                .help("The nickname, host name or URL of the server from which to request balance information"),
-        )
-        .arg(common_args::yes())]
+            )
+            .arg(
+                common_args::server()
+                    .help("The nickname, host name or URL of the server from which to request balance information"),
+            )
+            .arg(common_args::yes()),
+        clap::Command::new("usage")
+            .about("Show the energy used by the reducers of the databases you own")
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_parser(humantime::parse_duration)
+                    .default_value("24h")
+                    .help("How far back to show usage, e.g. `7d`"),
+            )
+            .arg(
+                Arg::new("database")
+                    .long("database")
+                    .short('d')
+                    .help("Only show the usage of this database, by name or identity"),
+            )
+            .arg(common_args::server().help("The nickname, host name or URL of the server to request usage from"))
+            .arg(common_args::yes()),
+    ]
 }
 
 async fn exec_subcommand(config: Config, cmd: &str, args: &ArgMatches) -> Result<(), anyhow::Error> {
     match cmd {
         "balance" => exec_status(config, args).await,
+        "usage" => exec_usage(config, args).await,
         unknown => Err(anyhow::anyhow!("Invalid subcommand: {unknown}")),
     }
 }
@@ -70,3 +99,76 @@ async fn exec_status(mut config: Config, args: &ArgMatches) -> Result<(), anyhow
 
     Ok(())
 }
+
+#[derive(Tabled)]
+struct EnergyUsageRow {
+    period: String,
+    database: String,
+    reducer: String,
+    calls: u64,
+    energy: u128,
+    duration: String,
+}
+
+impl From<EnergyUsageRecord> for EnergyUsageRow {
+    fn from(record: EnergyUsageRecord) -> Self {
+        Self {
+            period: chrono::DateTime::<chrono::Utc>::from(record.period_start)
+                .format("%Y-%m-%d %H:00 UTC")
+                .to_string(),
+            database: record.database_identity.to_string(),
+            reducer: record.reducer,
+            calls: record.calls,
+            energy: record.energy_used,
+            duration: humantime::format_duration(record.execution_duration).to_string(),
+        }
+    }
+}
+
+async fn exec_usage(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let force = args.get_flag("force");
+    let since = *args.get_one::<Duration>("since").unwrap();
+    let database = match args.get_one::<String>("database") {
+        Some(database) => Some(util::database_identity(&config, database, server).await?),
+        None => None,
+    };
+
+    let token = get_login_token_or_log_in(&mut config, server, !force).await?;
+    let identity = util::decode_identity(&token)?;
+    let since = SystemTime::now()
+        .checked_sub(since)
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let builder = reqwest::Client::new().get(format!(
+        "{}/v1/energy/{}/usage?since={}",
+        config.get_host_url(server)?,
+        identity,
+        since.as_secs()
+    ));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let GetEnergyUsageResponse { mut usage } = builder
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to retrieve energy usage")?;
+
+    if let Some(database) = database {
+        usage.retain(|record| record.database_identity == database);
+    }
+    let total_calls: u64 = usage.iter().map(|record| record.calls).sum();
+    let total_energy: u128 = usage.iter().map(|record| record.energy_used).sum();
+
+    let mut table = Table::new(usage.into_iter().map(EnergyUsageRow::from));
+    table
+        .with(Style::psql())
+        .with(Modify::new(Columns::first()).with(Alignment::left()));
+    println!("{table}");
+    println!("Total: {total_calls} calls, {total_energy} energy");
+
+    Ok(())
+}
@@ -5,7 +5,9 @@ use clap::parser::ValueSource;
 use clap::Arg;
 use clap::ArgAction::Set;
 use fs_err as fs;
-use spacetimedb_codegen::{generate, Csharp, Lang, OutputFile, Rust, TypeScript, UnrealCpp, AUTO_GENERATED_PREFIX};
+use spacetimedb_codegen::{
+    generate, python, Csharp, Go, Lang, OutputFile, Python, Rust, TypeScript, UnrealCpp, AUTO_GENERATED_PREFIX,
+};
 use spacetimedb_lib::de::serde::DeserializeWrapper;
 use spacetimedb_lib::{sats, RawModuleDef};
 use spacetimedb_schema;
@@ -72,7 +74,9 @@ pub fn cli() -> clap::Command {
                 .help("The system path (absolute or relative) to the generate output directory")
                 .required_if_eq("lang", "rust")
                 .required_if_eq("lang", "csharp")
-                .required_if_eq("lang", "typescript"),
+                .required_if_eq("lang", "typescript")
+                .required_if_eq("lang", "python")
+                .required_if_eq("lang", "go"),
         )
         .arg(
             Arg::new("uproject_dir")
@@ -177,6 +181,8 @@ pub async fn exec_ex(
 
     let csharp_lang;
     let unreal_cpp_lang;
+    let go_package_name;
+    let go_lang;
     let gen_lang = match lang {
         Language::Csharp => {
             csharp_lang = Csharp { namespace };
@@ -191,6 +197,14 @@ pub async fn exec_ex(
         }
         Language::Rust => &Rust,
         Language::TypeScript => &TypeScript,
+        Language::Python => &Python,
+        Language::Go => {
+            go_package_name = go_package_name_for_dir(out_dir);
+            go_lang = Go {
+                package_name: &go_package_name,
+            };
+            &go_lang as &dyn Lang
+        }
     };
 
     for OutputFile { filename, code } in generate(&module, gen_lang) {
@@ -211,7 +225,11 @@ pub async fn exec_ex(
     };
 
     // TODO: We should probably just delete all generated files before we generate any, rather than selectively deleting some afterward.
-    let mut auto_generated_buf: [u8; AUTO_GENERATED_PREFIX.len()] = [0; AUTO_GENERATED_PREFIX.len()];
+    let auto_generated_prefix = match lang {
+        Language::Python => python::AUTO_GENERATED_PREFIX,
+        _ => AUTO_GENERATED_PREFIX,
+    };
+    let mut auto_generated_buf = vec![0; auto_generated_prefix.len()];
     let files_to_delete = walkdir::WalkDir::new(&cleanup_root)
         .into_iter()
         .map(|entry_result| {
@@ -228,7 +246,7 @@ pub async fn exec_ex(
             // Only delete files that start with the auto-generated prefix.
             let mut file = fs::File::open(&path)?;
             Ok(match file.read_exact(&mut auto_generated_buf) {
-                Ok(()) => (auto_generated_buf == AUTO_GENERATED_PREFIX.as_bytes()).then_some(path),
+                Ok(()) => (auto_generated_buf == auto_generated_prefix.as_bytes()).then_some(path),
                 Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => None,
                 Err(err) => return Err(err.into()),
             })
@@ -268,11 +286,20 @@ pub enum Language {
     TypeScript,
     Rust,
     UnrealCpp,
+    Python,
+    Go,
 }
 
 impl clap::ValueEnum for Language {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Csharp, Self::TypeScript, Self::Rust, Self::UnrealCpp]
+        &[
+            Self::Csharp,
+            Self::TypeScript,
+            Self::Rust,
+            Self::UnrealCpp,
+            Self::Python,
+            Self::Go,
+        ]
     }
     fn to_possible_value(&self) -> Option<PossibleValue> {
         Some(match self {
@@ -280,6 +307,8 @@ impl clap::ValueEnum for Language {
             Self::TypeScript => clap::builder::PossibleValue::new("typescript").aliases(["ts", "TS"]),
             Self::Rust => clap::builder::PossibleValue::new("rust").aliases(["rs", "RS"]),
             Self::UnrealCpp => PossibleValue::new("unrealcpp").aliases(["uecpp", "ue5cpp", "unreal"]),
+            Self::Python => PossibleValue::new("python").aliases(["py", "PY"]),
+            Self::Go => PossibleValue::new("go").aliases(["golang"]),
         })
     }
 }
@@ -295,12 +324,53 @@ impl Language {
             Language::UnrealCpp => {
                 // TODO: implement formatting.
             }
+            Language::Python => {
+                // TODO: implement formatting.
+            }
+            Language::Go => gofmt(generated_files)?,
         }
 
         Ok(())
     }
 }
 
+/// The name of the Go package generated into `out_dir`: its last component, made into a valid identifier.
+fn go_package_name_for_dir(out_dir: &Path) -> String {
+    let dir_name = out_dir
+        .canonicalize()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    let mut name = dir_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.is_empty() {
+        name = "module_bindings".into();
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn gofmt(files: impl IntoIterator<Item = PathBuf>) -> anyhow::Result<()> {
+    duct::cmd(
+        "gofmt",
+        itertools::chain(
+            [std::ffi::OsString::from("-w")],
+            files.into_iter().map(PathBuf::into_os_string),
+        ),
+    )
+    .run()?;
+    Ok(())
+}
+
 pub type ExtractDescriptions = fn(&Path) -> anyhow::Result<ModuleDef>;
 fn extract_descriptions(wasm_file: &Path) -> anyhow::Result<ModuleDef> {
     let bin_path = resolve_sibling_binary("spacetimedb-standalone")?;
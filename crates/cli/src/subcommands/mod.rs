@@ -1,7 +1,9 @@
 pub mod build;
 pub mod call;
+pub mod db;
 pub mod delete;
 pub mod describe;
+pub mod dev;
 pub mod dns;
 pub mod energy;
 pub mod generate;
@@ -12,8 +14,10 @@ pub mod logout;
 pub mod logs;
 pub mod publish;
 pub mod repl;
+pub mod replay;
 pub mod server;
 pub mod sql;
 pub mod start;
 pub mod subscribe;
+pub mod token;
 pub mod version;
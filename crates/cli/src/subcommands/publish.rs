@@ -324,10 +324,13 @@ async fn apply_pre_publish_if_needed(
     Ok(builder)
 }
 
-async fn call_pre_publish(
+/// Ask the server how publishing `program_bytes` to `domain` would migrate the existing database.
+///
+/// Returns `None` if there is no database with that name yet.
+pub(crate) async fn call_pre_publish(
     client: &reqwest::Client,
     database_host: &str,
-    domain: &String,
+    domain: &str,
     host_type: &str,
     program_bytes: &[u8],
     auth_header: &AuthHeader,
@@ -0,0 +1,87 @@
+use std::process::ExitCode;
+
+use crate::api::ClientApi;
+use crate::common_args;
+use crate::config::Config;
+use crate::sql::parse_req;
+use crate::util::UNSTABLE_WARNING;
+use clap::{Arg, ArgAction, ArgMatches};
+use spacetimedb_lib::Timestamp;
+
+pub fn cli() -> clap::Command {
+    clap::Command::new("replay")
+        .about(format!(
+            "Re-run the reducer of a past transaction and check that it writes the same rows. {UNSTABLE_WARNING}"
+        ))
+        .arg(
+            Arg::new("database")
+                .required(true)
+                .help("The name or identity of the database the transaction was committed to"),
+        )
+        .arg(
+            Arg::new("tx_offset")
+                .long("tx")
+                .required(true)
+                .value_parser(clap::value_parser!(u64))
+                .help("The offset of the transaction in the database's commitlog"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the replay report as JSON"),
+        )
+        .arg(common_args::anonymous())
+        .arg(common_args::server().help("The nickname, host name or URL of the server hosting the database"))
+        .arg(common_args::yes())
+        .after_help("Run `spacetime help replay` for more detailed information.\n")
+}
+
+pub async fn exec(config: Config, args: &ArgMatches) -> Result<ExitCode, anyhow::Error> {
+    eprintln!("{UNSTABLE_WARNING}\n");
+
+    let tx_offset = *args.get_one::<u64>("tx_offset").unwrap();
+    let json = args.get_flag("json");
+
+    let api = ClientApi::new(parse_req(config, args).await?);
+    let report = api.replay(tx_offset).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let timestamp = Timestamp::from_micros_since_unix_epoch(report.timestamp_micros);
+        println!("Transaction {}: reducer `{}`", report.tx_offset, report.reducer);
+        println!(
+            "  called by {} (connection {}) at {timestamp}",
+            report.caller_identity, report.caller_connection_id,
+        );
+        if let Some(error) = &report.error {
+            println!("Replay failed: {error}");
+        }
+        for diff in &report.differences {
+            println!("Table `{}`:", diff.table_name);
+            for (label, rows) in [
+                ("inserted originally, but not by the replay", &diff.missing_inserts),
+                ("inserted by the replay, but not originally", &diff.extra_inserts),
+                ("deleted originally, but not by the replay", &diff.missing_deletes),
+                ("deleted by the replay, but not originally", &diff.extra_deletes),
+            ] {
+                if !rows.is_empty() {
+                    println!("  {label}:");
+                    for row in rows {
+                        println!("    {row}");
+                    }
+                }
+            }
+        }
+        if report.is_deterministic() {
+            println!("The replay wrote the same rows as the original transaction.");
+        }
+    }
+
+    Ok(if report.is_deterministic() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,214 @@
+use crate::common_args;
+use crate::config::Config;
+use crate::util::{add_auth_header_opt, database_identity, get_auth_header, ResponseExt};
+use anyhow::Context;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use itertools::Itertools;
+use spacetimedb_client_api_messages::collaborators::DatabaseAction;
+use spacetimedb_client_api_messages::tokens::{
+    CreateServiceTokenRequest, CreateServiceTokenResponse, GetServiceTokensResponse, ServiceToken, TokenScope,
+};
+use std::time::{Duration, SystemTime};
+use tabled::{
+    settings::{object::Columns, Alignment, Modify, Style},
+    Table, Tabled,
+};
+
+pub fn cli() -> Command {
+    Command::new("token")
+        .about("Manage service tokens, which act on your behalf with a restricted scope")
+        .args_conflicts_with_subcommands(true)
+        .subcommand_required(true)
+        .subcommands(get_subcommands())
+}
+
+fn get_subcommands() -> Vec<Command> {
+    vec![
+        Command::new("create")
+            .about("Create a service token which acts as you, restricted to the given actions and databases")
+            .arg(
+                Arg::new("database")
+                    .long("database")
+                    .short('d')
+                    .required(true)
+                    .action(ArgAction::Append)
+                    .help("The name or identity of a database the token may act upon. May be repeated"),
+            )
+            .arg(
+                Arg::new("action")
+                    .long("action")
+                    .short('a')
+                    .required(true)
+                    .action(ArgAction::Append)
+                    .value_parser([
+                        "connect",
+                        "list_collaborators",
+                        "read_logs",
+                        "read_sql",
+                        "write_sql",
+                        "publish",
+                        "rename",
+                        "manage_collaborators",
+                        "manage_auth_policy",
+                        "delete",
+                        "take_snapshot",
+                    ])
+                    .help("An action the token may perform. May be repeated"),
+            )
+            .arg(
+                Arg::new("expires_in")
+                    .long("expires-in")
+                    .value_parser(humantime::parse_duration)
+                    .help("How long the token remains valid, e.g. `30d`. If omitted, the token is valid until revoked"),
+            )
+            .arg(
+                Arg::new("description")
+                    .long("description")
+                    .help("A description of what the token is used for"),
+            )
+            .arg(common_args::server().help("The nickname, host name or URL of the server to create the token on"))
+            .arg(common_args::yes()),
+        Command::new("list")
+            .about("List the service tokens you have created")
+            .arg(common_args::server().help("The nickname, host name or URL of the server to list the tokens of"))
+            .arg(common_args::yes()),
+        Command::new("revoke")
+            .about("Revoke a service token")
+            .arg(Arg::new("id").required(true).help("The id of the token to revoke"))
+            .arg(common_args::server().help("The nickname, host name or URL of the server the token was created on"))
+            .arg(common_args::yes()),
+    ]
+}
+
+pub async fn exec(config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let (cmd, subcommand_args) = args.subcommand().expect("Subcommand required");
+    match cmd {
+        "create" => exec_create(config, subcommand_args).await,
+        "list" => exec_list(config, subcommand_args).await,
+        "revoke" => exec_revoke(config, subcommand_args).await,
+        unknown => Err(anyhow::anyhow!("Invalid subcommand: {unknown}")),
+    }
+}
+
+async fn exec_create(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let force = args.get_flag("force");
+    let description = args.get_one::<String>("description").cloned();
+    let expires_in = args.get_one::<Duration>("expires_in");
+
+    let mut databases = vec![];
+    for database in args.get_many::<String>("database").unwrap() {
+        databases.push(database_identity(&config, database, server).await?);
+    }
+    let actions = args
+        .get_many::<String>("action")
+        .unwrap()
+        .map(|action| action.parse::<DatabaseAction>())
+        .collect::<Result<_, _>>()?;
+
+    let request = CreateServiceTokenRequest {
+        description,
+        scope: TokenScope { databases, actions },
+        expires_in_secs: expires_in.map(Duration::as_secs),
+    };
+
+    let builder = reqwest::Client::new()
+        .post(format!("{}/v1/identity/service-tokens", config.get_host_url(server)?))
+        .json(&request);
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let CreateServiceTokenResponse { service_token, token } = builder
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to create service token")?;
+
+    eprintln!("Created service token {}.", service_token.id);
+    eprintln!("This is the only time the token is shown. Store it securely:");
+    println!("{token}");
+
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct ServiceTokenRow {
+    id: String,
+    description: String,
+    databases: String,
+    actions: String,
+    expires: String,
+    status: &'static str,
+}
+
+fn format_time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time)
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+impl From<ServiceToken> for ServiceTokenRow {
+    fn from(token: ServiceToken) -> Self {
+        let status = if token.revoked {
+            "revoked"
+        } else if token.is_expired(SystemTime::now()) {
+            "expired"
+        } else {
+            "active"
+        };
+        Self {
+            status,
+            expires: token.expires_at.map_or_else(|| "never".to_owned(), format_time),
+            id: token.id,
+            description: token.description.unwrap_or_default(),
+            databases: token.scope.databases.iter().join("\n"),
+            actions: token.scope.actions.iter().join("\n"),
+        }
+    }
+}
+
+async fn exec_list(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let force = args.get_flag("force");
+
+    let builder = reqwest::Client::new().get(format!("{}/v1/identity/service-tokens", config.get_host_url(server)?));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let GetServiceTokensResponse { service_tokens } = builder
+        .send()
+        .await?
+        .json_or_error()
+        .await
+        .context("unable to retrieve service tokens")?;
+
+    let mut table = Table::new(service_tokens.into_iter().map(ServiceTokenRow::from));
+    table
+        .with(Style::psql())
+        .with(Modify::new(Columns::first()).with(Alignment::left()));
+    println!("{table}");
+
+    Ok(())
+}
+
+async fn exec_revoke(mut config: Config, args: &ArgMatches) -> Result<(), anyhow::Error> {
+    let server = args.get_one::<String>("server").map(|s| s.as_ref());
+    let id = args.get_one::<String>("id").unwrap();
+    let force = args.get_flag("force");
+
+    let builder = reqwest::Client::new().delete(format!(
+        "{}/v1/identity/service-tokens/{}",
+        config.get_host_url(server)?,
+        id
+    ));
+    let auth_header = get_auth_header(&mut config, false, server, !force).await?;
+    let builder = add_auth_header_opt(builder, &auth_header);
+    let res = builder.send().await?;
+    if let Err(e) = res.error_for_status_ref() {
+        let text = res.text().await?;
+        return Err(anyhow::Error::from(e).context(text));
+    }
+
+    println!("Revoked service token {id}.");
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Types for restricting which tokens may connect to a database.
+
+/// Restricts the tokens which may be used to connect to a database,
+/// on top of the validation the server performs for every token.
+///
+/// The owner and collaborators of the database are exempt,
+/// so that a misconfigured policy cannot lock them out.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AuthPolicy {
+    /// The accepted values of the `iss` claim.
+    ///
+    /// If empty, tokens from any issuer are accepted.
+    #[serde(default)]
+    pub issuers: Vec<String>,
+    /// The accepted values of the `aud` claim.
+    /// A token is accepted if any of its audiences is listed.
+    ///
+    /// If empty, tokens for any audience are accepted.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+}
+
+/// The reason a token was rejected by an [`AuthPolicy`].
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum AuthPolicyViolation {
+    #[error("tokens issued by `{0}` are not accepted by this database")]
+    Issuer(String),
+    #[error("tokens must be issued for one of the audiences {0:?} to connect to this database")]
+    Audience(Vec<String>),
+}
+
+impl AuthPolicy {
+    /// Returns whether this policy accepts all tokens.
+    pub fn is_unrestricted(&self) -> bool {
+        self.issuers.is_empty() && self.audiences.is_empty()
+    }
+
+    /// Checks that a token with the given `iss` and `aud` claims is accepted by this policy.
+    ///
+    /// Issuers are compared ignoring trailing slashes.
+    pub fn check(&self, issuer: &str, audience: &[String]) -> Result<(), AuthPolicyViolation> {
+        let normalize = |iss: &str| iss.trim_end_matches('/').to_owned();
+        if !self.issuers.is_empty() && !self.issuers.iter().any(|iss| normalize(iss) == normalize(issuer)) {
+            return Err(AuthPolicyViolation::Issuer(issuer.to_owned()));
+        }
+        if !self.audiences.is_empty() && !audience.iter().any(|aud| self.audiences.contains(aud)) {
+            return Err(AuthPolicyViolation::Audience(self.audiences.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_accepts_everything() {
+        let policy = AuthPolicy::default();
+        assert!(policy.is_unrestricted());
+        assert_eq!(policy.check("localhost", &[]), Ok(()));
+    }
+
+    #[test]
+    fn policy_checks_issuer_and_audience() {
+        let policy = AuthPolicy {
+            issuers: vec!["https://auth.example.com/".into()],
+            audiences: vec!["my-game".into()],
+        };
+        let aud = |a: &str| vec![a.to_owned()];
+
+        assert_eq!(policy.check("https://auth.example.com", &aud("my-game")), Ok(()));
+        assert_eq!(
+            policy.check("localhost", &aud("my-game")),
+            Err(AuthPolicyViolation::Issuer("localhost".into()))
+        );
+        assert_eq!(
+            policy.check("https://auth.example.com", &aud("other-game")),
+            Err(AuthPolicyViolation::Audience(aud("my-game")))
+        );
+        assert!(policy.check("https://auth.example.com", &[]).is_err());
+    }
+}
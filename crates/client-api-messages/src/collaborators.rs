@@ -0,0 +1,153 @@
+//! Types for sharing access to a database with identities other than its owner.
+
+use spacetimedb_lib::Identity;
+
+/// The role a collaborator holds on a database.
+///
+/// Roles are ordered from least to most privileged,
+/// and each role is permitted everything the roles below it are.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum DatabaseRole {
+    /// May read logs and run SQL queries with the privileges of the owner,
+    /// but may not modify the database.
+    ReadOnly,
+    /// May additionally publish updates to the module and run SQL DML statements.
+    Developer,
+    /// May additionally change the names of the database,
+    /// manage its collaborators and its auth policy.
+    Admin,
+    /// May do anything the owner of the database may do,
+    /// including deleting it.
+    Owner,
+}
+
+impl DatabaseRole {
+    /// Returns whether this role is permitted to perform `action`.
+    pub fn permits(self, action: DatabaseAction) -> bool {
+        action.required_role().is_none_or(|required| self >= required)
+    }
+}
+
+/// An operation on a database which may require some [`DatabaseRole`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, strum::Display, strum::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DatabaseAction {
+    /// Call reducers, subscribe or run one-off queries over a client connection.
+    ///
+    /// Everyone may do this, unless restricted by the scope of a service token.
+    Connect,
+    /// List the collaborators of the database.
+    ListCollaborators,
+    /// Read the module logs.
+    ReadLogs,
+    /// Run SQL queries with the privileges of the owner.
+    ReadSql,
+    /// Run SQL DML statements.
+    WriteSql,
+    /// Publish an update to the module, or inspect the migration plan thereof.
+    Publish,
+    /// Replace the names of the database.
+    Rename,
+    /// Add, modify or remove collaborators.
+    ManageCollaborators,
+    /// Change the issuers and audiences of tokens accepted by the database.
+    ManageAuthPolicy,
+    /// Delete the database, or clear its data when publishing.
+    Delete,
+    /// Take a snapshot of the database on demand.
+    TakeSnapshot,
+}
+
+impl DatabaseAction {
+    /// The least privileged role permitted to perform this action,
+    /// or `None` if no role is required.
+    pub fn required_role(self) -> Option<DatabaseRole> {
+        match self {
+            Self::Connect => None,
+            Self::ListCollaborators | Self::ReadLogs | Self::ReadSql => Some(DatabaseRole::ReadOnly),
+            Self::WriteSql | Self::Publish => Some(DatabaseRole::Developer),
+            Self::Rename | Self::ManageCollaborators | Self::ManageAuthPolicy => Some(DatabaseRole::Admin),
+            Self::Delete | Self::TakeSnapshot => Some(DatabaseRole::Owner),
+        }
+    }
+}
+
+/// An identity which has been granted a [`DatabaseRole`] on a database.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Collaborator {
+    pub identity: Identity,
+    pub role: DatabaseRole,
+}
+
+/// The response to `GET /v1/database/:name_or_identity/collaborators`.
+///
+/// The owner of the database is always included, with the role [`DatabaseRole::Owner`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GetCollaboratorsResponse {
+    pub collaborators: Vec<Collaborator>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_are_cumulative() {
+        use DatabaseAction::*;
+        let actions = [
+            Connect,
+            ListCollaborators,
+            ReadLogs,
+            ReadSql,
+            WriteSql,
+            Publish,
+            Rename,
+            ManageCollaborators,
+            ManageAuthPolicy,
+            Delete,
+            TakeSnapshot,
+        ];
+
+        assert!(actions.iter().all(|&a| DatabaseRole::Owner.permits(a)));
+        assert!(DatabaseRole::ReadOnly.permits(Connect));
+        assert!(!DatabaseRole::Admin.permits(Delete));
+        assert!(!DatabaseRole::Admin.permits(TakeSnapshot));
+        assert!(DatabaseRole::Admin.permits(ManageCollaborators));
+        assert!(DatabaseRole::Developer.permits(Publish));
+        assert!(!DatabaseRole::Developer.permits(Rename));
+        assert!(DatabaseRole::ReadOnly.permits(ReadSql));
+        assert!(!DatabaseRole::ReadOnly.permits(WriteSql));
+    }
+
+    #[test]
+    fn role_round_trips_through_str() {
+        for role in [
+            DatabaseRole::ReadOnly,
+            DatabaseRole::Developer,
+            DatabaseRole::Admin,
+            DatabaseRole::Owner,
+        ] {
+            assert_eq!(role.to_string().parse::<DatabaseRole>().unwrap(), role);
+            let json = serde_json::to_string(&role).unwrap();
+            assert_eq!(json, format!("\"{role}\""));
+        }
+    }
+}
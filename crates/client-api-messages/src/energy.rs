@@ -1,7 +1,8 @@
 use derive_more::{Add, AddAssign, From, Sub, SubAssign};
+use spacetimedb_lib::Identity;
 use spacetimedb_sats::SpacetimeType;
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 /// [EnergyQuanta] represents an amount of energy in a canonical unit.
 /// It represents the smallest unit of energy that can be used to pay for
@@ -164,3 +165,30 @@ impl fmt::Debug for ReducerBudget {
             .finish()
     }
 }
+
+/// The energy used by the calls to a reducer of a database during one period of the energy ledger.
+#[serde_with::serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EnergyUsageRecord {
+    pub database_identity: Identity,
+    pub reducer: String,
+    /// The start of the period covered by this record.
+    #[serde_as(as = "serde_with::TimestampSeconds")]
+    pub period_start: SystemTime,
+    /// The number of calls to the reducer during the period.
+    pub calls: u64,
+    /// The energy used by those calls, in [`EnergyQuanta`].
+    // Note: returned as a string to avoid truncation.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    pub energy_used: u128,
+    /// The total time spent executing those calls.
+    #[serde_as(as = "serde_with::DurationMicroSeconds")]
+    pub execution_duration: Duration,
+}
+
+/// The response to `GET /v1/energy/:identity/usage`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GetEnergyUsageResponse {
+    /// The usage records of all databases owned by the identity, oldest first.
+    pub usage: Vec<EnergyUsageRecord>,
+}
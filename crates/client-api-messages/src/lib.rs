@@ -1,6 +1,11 @@
 //! Schemas of various messages sent over SpacetimeDB's HTTP and WebSocket APIs.
 
+pub mod auth_policy;
+pub mod collaborators;
 pub mod energy;
 pub mod http;
 pub mod name;
+pub mod replay;
+pub mod snapshot;
+pub mod tokens;
 pub mod websocket;
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use spacetimedb_lib::{ConnectionId, Identity};
+
+/// The result of re-running the reducer which committed a transaction,
+/// against the state of the database just before that transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    /// The offset of the replayed transaction in the commitlog.
+    pub tx_offset: u64,
+    /// The name of the reducer which committed the transaction.
+    pub reducer: String,
+    pub caller_identity: Identity,
+    pub caller_connection_id: ConnectionId,
+    /// The timestamp the reducer observed, and was replayed at,
+    /// in microseconds since the Unix epoch.
+    pub timestamp_micros: i64,
+    /// The error the reducer failed with when replayed, if any.
+    ///
+    /// Only committed transactions are recorded,
+    /// so a replay which fails is itself a divergence.
+    pub error: Option<String>,
+    /// The tables whose rows were written differently by the replay.
+    pub differences: Vec<TableDiff>,
+}
+
+impl ReplayReport {
+    /// Did the replay write exactly what the original transaction wrote?
+    pub fn is_deterministic(&self) -> bool {
+        self.error.is_none() && self.differences.is_empty()
+    }
+}
+
+/// How the writes to a single table differ between the committed transaction and its replay.
+///
+/// Rows are rendered in SATN, i.e. the format of `spacetime sql` output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub table_name: String,
+    /// Rows the committed transaction inserted, but the replay did not.
+    pub missing_inserts: Vec<String>,
+    /// Rows the replay inserted, but the committed transaction did not.
+    pub extra_inserts: Vec<String>,
+    /// Rows the committed transaction deleted, but the replay did not.
+    pub missing_deletes: Vec<String>,
+    /// Rows the replay deleted, but the committed transaction did not.
+    pub extra_deletes: Vec<String>,
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// The response to a request to take a snapshot of a database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeSnapshotResponse {
+    /// The offset of the last transaction included in the snapshot.
+    pub tx_offset: u64,
+}
@@ -0,0 +1,115 @@
+//! Types for service tokens, which act on behalf of an identity
+//! with a restricted set of permissions.
+
+use crate::collaborators::DatabaseAction;
+use spacetimedb_lib::Identity;
+use std::time::SystemTime;
+
+/// The permissions a service token is restricted to.
+///
+/// A service token never grants more than the identity which created it is permitted to do:
+/// the scope is checked in addition to the role of that identity.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TokenScope {
+    /// The databases the token may act upon.
+    pub databases: Vec<Identity>,
+    /// The actions the token may perform on those databases.
+    pub actions: Vec<DatabaseAction>,
+}
+
+impl TokenScope {
+    /// Returns whether this scope includes performing `action` on `database`.
+    pub fn permits(&self, database: &Identity, action: DatabaseAction) -> bool {
+        self.databases.contains(database) && self.actions.contains(&action)
+    }
+}
+
+/// A service token as recorded by the server.
+///
+/// The signed token itself is only returned once, upon creation.
+#[serde_with::serde_as]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ServiceToken {
+    /// The unique id of the token, which is also its `jti` claim.
+    pub id: String,
+    /// The identity the token acts on behalf of.
+    pub owner_identity: Identity,
+    /// A human-readable description of what the token is used for.
+    #[serde(default)]
+    pub description: Option<String>,
+    pub scope: TokenScope,
+    #[serde_as(as = "serde_with::TimestampSeconds")]
+    pub created_at: SystemTime,
+    #[serde_as(as = "Option<serde_with::TimestampSeconds>")]
+    pub expires_at: Option<SystemTime>,
+    /// Whether the token has been revoked.
+    ///
+    /// Revoked tokens are kept around so that they keep being rejected until they expire.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl ServiceToken {
+    /// Returns whether the token is expired as of `now`.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|exp| exp <= now)
+    }
+}
+
+/// The body of `POST /v1/identity/service-tokens`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CreateServiceTokenRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub scope: TokenScope,
+    /// The number of seconds after which the token expires.
+    ///
+    /// If `None`, the token remains valid until revoked.
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+}
+
+/// The response to `POST /v1/identity/service-tokens`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CreateServiceTokenResponse {
+    pub service_token: ServiceToken,
+    /// The signed token, to be passed as `Authorization: Bearer $token`.
+    pub token: String,
+}
+
+/// The response to `GET /v1/identity/service-tokens`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GetServiceTokensResponse {
+    pub service_tokens: Vec<ServiceToken>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_requires_database_and_action() {
+        let db = Identity::from_claims("localhost", "a");
+        let other = Identity::from_claims("localhost", "b");
+        let scope = TokenScope {
+            databases: vec![db],
+            actions: vec![DatabaseAction::Publish, DatabaseAction::ReadLogs],
+        };
+
+        assert!(scope.permits(&db, DatabaseAction::Publish));
+        assert!(scope.permits(&db, DatabaseAction::ReadLogs));
+        assert!(!scope.permits(&db, DatabaseAction::Delete));
+        assert!(!scope.permits(&other, DatabaseAction::Publish));
+    }
+
+    #[test]
+    fn scope_round_trips_through_json() {
+        let scope = TokenScope {
+            databases: vec![Identity::from_claims("localhost", "a")],
+            actions: vec![DatabaseAction::Connect, DatabaseAction::WriteSql],
+        };
+        let json = serde_json::to_value(&scope).unwrap();
+        assert_eq!(json["actions"], serde_json::json!(["connect", "write_sql"]));
+        assert_eq!(serde_json::from_value::<TokenScope>(json).unwrap(), scope);
+    }
+}
@@ -105,6 +105,9 @@ pub enum ClientMessage<Args> {
     /// Remove a subscription to a SQL query that was added with SubscribeSingle.
     Unsubscribe(Unsubscribe),
     UnsubscribeMulti(UnsubscribeMulti),
+    /// Register the query sets a client already holds the matching rows of,
+    /// e.g., because it persisted them, asking for just the changes since then.
+    ResumeSubscriptions(ResumeSubscriptions),
 }
 
 impl<Args> ClientMessage<Args> {
@@ -127,6 +130,7 @@ impl<Args> ClientMessage<Args> {
             ClientMessage::Subscribe(x) => ClientMessage::Subscribe(x),
             ClientMessage::SubscribeMulti(x) => ClientMessage::SubscribeMulti(x),
             ClientMessage::UnsubscribeMulti(x) => ClientMessage::UnsubscribeMulti(x),
+            ClientMessage::ResumeSubscriptions(x) => ClientMessage::ResumeSubscriptions(x),
         }
     }
 }
@@ -209,7 +213,7 @@ impl QueryId {
 /// will be subscribed to `B` but not `A`. In this case, the client will receive a
 /// `SubscriptionUpdate` containing every existing row that matches `B`, even if some were
 /// already in `A`.
-#[derive(SpacetimeType)]
+#[derive(SpacetimeType, Clone)]
 #[sats(crate = spacetimedb_lib)]
 pub struct Subscribe {
     /// A sequence of SQL queries.
@@ -241,7 +245,7 @@ pub struct SubscribeSingle {
     pub query_id: QueryId,
 }
 
-#[derive(SpacetimeType)]
+#[derive(SpacetimeType, Clone)]
 #[sats(crate = spacetimedb_lib)]
 pub struct SubscribeMulti {
     /// A single SQL `SELECT` query to subscribe to.
@@ -277,6 +281,30 @@ pub struct UnsubscribeMulti {
     pub query_id: QueryId,
 }
 
+/// Sent by a client which holds the matching rows of some query sets,
+/// as of the transaction described by `since`, to subscribe to them again.
+///
+/// The server registers each query set as if by a [`SubscribeMulti`] message,
+/// then responds with a single [`SubscriptionsResumed`] message.
+/// If the server still knows every transaction after `since`,
+/// that message contains just the changes the client missed.
+/// Otherwise it contains every matching row,
+/// and the client must replace the rows it holds with those.
+///
+/// A query set which fails to compile or evaluate results in a [`SubscriptionError`] for its `query_id`.
+/// The other query sets are then resumed with every matching row.
+#[derive(SpacetimeType, Clone)]
+#[sats(crate = spacetimedb_lib)]
+pub struct ResumeSubscriptions {
+    /// An identifier for a client request.
+    pub request_id: u32,
+    /// The last [`CaughtUp`] message the client received before it stopped receiving updates,
+    /// or `None` if it doesn't know which transaction its rows reflect.
+    pub since: Option<CaughtUp>,
+    /// The query sets to subscribe to, each with its own `query_id`.
+    pub subscriptions: Box<[SubscribeMulti]>,
+}
+
 /// A one-off query submission.
 ///
 /// Query should be a "SELECT * FROM Table WHERE ...". Other types of queries will be rejected.
@@ -326,6 +354,17 @@ pub enum ServerMessage<F: WebsocketFormat> {
     SubscribeMultiApplied(SubscribeMultiApplied<F>),
     /// Sent in response to an `UnsubscribeMulti` message. This contains the matching rows.
     UnsubscribeMultiApplied(UnsubscribeMultiApplied<F>),
+    /// Upon reducer run, to the caller of a reducer which returned a value,
+    /// in place of the [`TransactionUpdate`] for their call.
+    ///
+    /// Only sent to clients which asked for reducer results when connecting.
+    ReducerResult(ReducerResult<F>),
+    /// Sent in response to a `ResumeSubscriptions` message.
+    SubscriptionsResumed(SubscriptionsResumed<F>),
+    /// Sent after every message which reflects a transaction, naming the latest one it reflects.
+    ///
+    /// Only sent to clients which asked for tx offsets when connecting.
+    CaughtUp(CaughtUp),
 }
 
 /// The matching rows of a subscription query.
@@ -435,6 +474,51 @@ pub struct UnsubscribeMultiApplied<F: WebsocketFormat> {
     pub update: DatabaseUpdate<F>,
 }
 
+/// Response to [`ResumeSubscriptions`].
+#[derive(SpacetimeType)]
+#[sats(crate = spacetimedb_lib)]
+pub struct SubscriptionsResumed<F: WebsocketFormat> {
+    /// The request_id of the corresponding `ResumeSubscriptions` message.
+    pub request_id: u32,
+    /// The overall time between the server receiving a request and sending the response.
+    pub total_host_execution_duration_micros: u64,
+    /// If `true`, each update contains the changes to its query set's rows
+    /// since the transaction the client named in `since`.
+    ///
+    /// If `false`, each update contains every row matching its query set,
+    /// and the rows the client held for the resumed query sets are no longer valid.
+    pub is_delta: bool,
+    /// The resumed query sets, except for those which failed with a [`SubscriptionError`].
+    pub subscriptions: Vec<ResumedSubscription<F>>,
+}
+
+/// A query set resumed by [`SubscriptionsResumed`].
+#[derive(SpacetimeType)]
+#[sats(crate = spacetimedb_lib)]
+pub struct ResumedSubscription<F: WebsocketFormat> {
+    /// The ID sent by the client for this query set.
+    pub query_id: QueryId,
+    /// The changes to, or all of, the matching rows for this query set.
+    pub update: DatabaseUpdate<F>,
+}
+
+/// Received by clients which asked for tx offsets when connecting,
+/// after each message which reflects the state of the database as of some transaction.
+///
+/// Once a client has processed every message up to a `CaughtUp`,
+/// its subscribed rows reflect every transaction up to and including `tx_offset`.
+/// A client which persists those rows can present this position in a [`ResumeSubscriptions`] message.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+#[sats(crate = spacetimedb_lib)]
+pub struct CaughtUp {
+    /// Identifies the server's history of recent transactions `tx_offset` refers to.
+    ///
+    /// Opaque to clients; it changes e.g. when the database restarts.
+    pub history_id: u64,
+    /// The offset of the latest transaction reflected by the preceding messages.
+    pub tx_offset: u64,
+}
+
 /// Response to [`Subscribe`] containing the initial matching rows.
 #[derive(SpacetimeType)]
 #[sats(crate = spacetimedb_lib)]
@@ -528,6 +612,18 @@ pub struct TransactionUpdateLight<F: WebsocketFormat> {
     pub update: DatabaseUpdate<F>,
 }
 
+/// Received by the caller of a reducer which declares return types,
+/// if they asked for reducer results when connecting.
+#[derive(SpacetimeType, Debug)]
+#[sats(crate = spacetimedb_lib)]
+pub struct ReducerResult<F: WebsocketFormat> {
+    /// The update that would otherwise have been sent to the caller.
+    pub update: TransactionUpdate<F>,
+    /// The BSATN-encoded value the reducer returned, if it committed,
+    /// or the BSATN-encoded error value it failed with, if it failed.
+    pub return_value: Bytes,
+}
+
 /// Contained in a [`TransactionUpdate`], metadata about a reducer invocation.
 #[derive(SpacetimeType, Debug)]
 #[sats(crate = spacetimedb_lib)]
@@ -6,7 +6,8 @@ use axum_extra::typed_header::TypedHeader;
 use headers::{authorization, HeaderMapExt};
 use http::{request, HeaderValue, StatusCode};
 use serde::{Deserialize, Serialize};
-use spacetimedb::auth::identity::{ConnectionAuthCtx, SpacetimeIdentityClaims};
+use spacetimedb::auth::identity::ConnectionAuthCtx;
+pub use spacetimedb::auth::identity::SpacetimeIdentityClaims;
 use spacetimedb::auth::identity::{JwtError, JwtErrorKind};
 use spacetimedb::auth::token_validation::{
     new_validator, DefaultValidator, TokenSigner, TokenValidationError, TokenValidator,
@@ -14,6 +15,8 @@ use spacetimedb::auth::token_validation::{
 use spacetimedb::auth::JwtKeys;
 use spacetimedb::energy::EnergyQuanta;
 use spacetimedb::identity::Identity;
+use spacetimedb_client_api_messages::tokens::TokenScope;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
@@ -87,6 +90,8 @@ pub struct SpacetimeAuth {
     pub claims: SpacetimeIdentityClaims,
     /// The JWT payload as a json string (after base64 decoding).
     pub jwt_payload: String,
+    /// If the credentials are a service token, the scope it is restricted to.
+    pub service_token: Option<ServiceTokenClaims>,
 }
 
 impl SpacetimeAuth {
@@ -98,10 +103,47 @@ impl SpacetimeAuth {
             creds,
             claims,
             jwt_payload: payload,
+            service_token: None,
         })
     }
 }
 
+/// The additional claims of a service token.
+///
+/// A service token is signed by this instance on behalf of an identity,
+/// and carries the same `iss` and `sub` claims as that identity's token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServiceTokenClaims {
+    /// The id of the token, used to look it up in the [`RevocationList`].
+    pub jti: String,
+    /// Namespaced, as the standard `scope` claim of OAuth access tokens is a space-separated string.
+    #[serde(rename = "spacetimedb_scope")]
+    pub scope: TokenScope,
+}
+
+/// The claims of a service token, as they are signed.
+#[derive(Serialize)]
+struct SignedServiceTokenClaims<'a> {
+    #[serde(flatten)]
+    claims: &'a SpacetimeIdentityClaims,
+    #[serde(flatten)]
+    service_token: &'a ServiceTokenClaims,
+}
+
+/// The issuer of a token, as decoded before its signature is verified.
+#[derive(Deserialize)]
+struct IncomingIssuerClaim {
+    iss: String,
+}
+
+/// The claims relevant to service tokens, as decoded from a token issued by this instance.
+#[derive(Deserialize)]
+struct IncomingServiceTokenClaims {
+    jti: Option<String>,
+    #[serde(rename = "spacetimedb_scope")]
+    scope: Option<TokenScope>,
+}
+
 impl From<SpacetimeAuth> for ConnectionAuthCtx {
     fn from(auth: SpacetimeAuth) -> Self {
         ConnectionAuthCtx {
@@ -124,8 +166,8 @@ impl From<SpacetimeAuth> for TokenClaims {
         Self {
             issuer: auth.claims.issuer,
             subject: auth.claims.subject,
-            // This will need to be changed when we care about audiencies.
-            audience: Vec::new(),
+            // Keep the audience, so that re-signed tokens pass the same auth policies.
+            audience: auth.claims.audience,
         }
     }
 }
@@ -144,28 +186,48 @@ impl TokenClaims {
         Identity::from_claims(&self.issuer, &self.subject)
     }
 
-    /// Encode the claims into a JWT token and sign it with the provided signer.
-    /// This also adds claims for expiry and issued at time.
-    /// Returns an object representing the claims and the signed token.
-    pub fn encode_and_sign_with_expiry(
-        &self,
-        signer: &impl TokenSigner,
-        expiry: Option<Duration>,
-    ) -> Result<(SpacetimeIdentityClaims, String), JwtError> {
+    fn with_expiry(&self, expiry: Option<Duration>) -> SpacetimeIdentityClaims {
         let iat = SystemTime::now();
         let exp = expiry.map(|dur| iat + dur);
-        let claims = SpacetimeIdentityClaims {
+        SpacetimeIdentityClaims {
             identity: self.id(),
             subject: self.subject.clone(),
             issuer: self.issuer.clone(),
             audience: self.audience.clone(),
             iat,
             exp,
-        };
+        }
+    }
+
+    /// Encode the claims into a JWT token and sign it with the provided signer.
+    /// This also adds claims for expiry and issued at time.
+    /// Returns an object representing the claims and the signed token.
+    pub fn encode_and_sign_with_expiry(
+        &self,
+        signer: &impl TokenSigner,
+        expiry: Option<Duration>,
+    ) -> Result<(SpacetimeIdentityClaims, String), JwtError> {
+        let claims = self.with_expiry(expiry);
         let token = signer.sign(&claims)?;
         Ok((claims, token))
     }
 
+    /// Like [`Self::encode_and_sign_with_expiry`],
+    /// but restricts the token to the scope of `service_token`.
+    pub fn encode_and_sign_service_token(
+        &self,
+        signer: &impl TokenSigner,
+        expiry: Option<Duration>,
+        service_token: &ServiceTokenClaims,
+    ) -> Result<(SpacetimeIdentityClaims, String), JwtError> {
+        let claims = self.with_expiry(expiry);
+        let token = signer.sign(&SignedServiceTokenClaims {
+            claims: &claims,
+            service_token,
+        })?;
+        Ok((claims, token))
+    }
+
     /// Encode the claims into a JWT token and sign it with the provided signer.
     /// This also adds a claim for issued at time.
     /// Returns an object representing the claims and the signed token.
@@ -203,12 +265,17 @@ impl SpacetimeAuth {
     // Sign a new token with the same claims and a new expiry.
     // Note that this will not change the issuer, so the private_key might not match.
     // We do this to create short-lived tokens that we will be able to verify.
+    // The scope of a service token carries over to the new token.
     pub fn re_sign_with_expiry(
         &self,
         signer: &impl TokenSigner,
         expiry: Duration,
     ) -> Result<(SpacetimeIdentityClaims, String), JwtError> {
-        TokenClaims::from(self.clone()).encode_and_sign_with_expiry(signer, Some(expiry))
+        let claims = TokenClaims::from(self.clone());
+        match &self.service_token {
+            Some(service_token) => claims.encode_and_sign_service_token(signer, Some(expiry), service_token),
+            None => claims.encode_and_sign_with_expiry(signer, Some(expiry)),
+        }
     }
 }
 
@@ -225,12 +292,25 @@ pub trait JwtAuthProvider: Sync + Send + TokenSigner {
     ///
     /// The `/identity/public-key` route calls this method to return the public key to callers.
     fn public_key_bytes(&self) -> &[u8];
+
+    /// Validate the service token claims of a `token` which was already accepted by [`Self::validator`].
+    ///
+    /// Returns `None` if `token` is not a service token,
+    /// and an error if it is one which was not signed by this instance or has been revoked.
+    fn validate_service_token(&self, token: &str) -> Result<Option<ServiceTokenClaims>, TokenValidationError>;
+}
+
+/// The set of service tokens which may no longer be used.
+pub trait RevocationList: Send + Sync {
+    /// Returns whether the service token with the given `jti` claim has been revoked.
+    fn is_revoked(&self, token_id: &str) -> anyhow::Result<bool>;
 }
 
 pub struct JwtKeyAuthProvider<TV: TokenValidator + Send + Sync> {
     keys: JwtKeys,
     local_issuer: String,
     validator: TV,
+    revocation_list: Option<Arc<dyn RevocationList>>,
 }
 
 pub type DefaultJwtAuthProvider = JwtKeyAuthProvider<DefaultValidator>;
@@ -247,6 +327,17 @@ impl<TV: TokenValidator + Send + Sync> JwtKeyAuthProvider<TV> {
             keys,
             local_issuer,
             validator,
+            revocation_list: None,
+        }
+    }
+
+    /// Accept service tokens, checking them against `revocation_list`.
+    ///
+    /// Without a revocation list, all service tokens are rejected.
+    pub fn with_revocation_list(self, revocation_list: Arc<dyn RevocationList>) -> Self {
+        Self {
+            revocation_list: Some(revocation_list),
+            ..self
         }
     }
 }
@@ -272,15 +363,61 @@ impl<TV: TokenValidator + Send + Sync> JwtAuthProvider for JwtKeyAuthProvider<TV
     fn public_key_bytes(&self) -> &[u8] {
         &self.keys.public_pem
     }
+
+    fn validate_service_token(&self, token: &str) -> Result<Option<ServiceTokenClaims>, TokenValidationError> {
+        let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::ES256);
+        validation.set_required_spec_claims(&["sub", "iss"]);
+        validation.validate_aud = false;
+
+        // Peek at the issuer first, as tokens from other issuers won't verify against our key.
+        // Only we issue service tokens, so the other claims of those tokens are none of our business.
+        let mut unverified = validation.clone();
+        unverified.insecure_disable_signature_validation();
+        let dummy_key = jsonwebtoken::DecodingKey::from_secret(b"fake");
+        let IncomingIssuerClaim { iss } =
+            jsonwebtoken::decode::<IncomingIssuerClaim>(token, &dummy_key, &unverified)?.claims;
+        if iss != self.local_issuer {
+            return Ok(None);
+        }
+
+        // Only we get to restrict a token's scope, so it must have been signed with our key.
+        let IncomingServiceTokenClaims { jti, scope } =
+            jsonwebtoken::decode::<IncomingServiceTokenClaims>(token, &self.keys.public, &validation)?.claims;
+        let Some(scope) = scope else {
+            return Ok(None);
+        };
+        let Some(jti) = jti else {
+            return Err(TokenValidationError::Other(anyhow!(
+                "Service token is missing its `jti` claim"
+            )));
+        };
+        let revocation_list = self
+            .revocation_list
+            .as_ref()
+            .ok_or_else(|| anyhow!("Service tokens are not supported by this instance"))?;
+        if revocation_list.is_revoked(&jti)? {
+            return Err(TokenValidationError::Other(anyhow!(
+                "Service token {jti} has been revoked"
+            )));
+        }
+        Ok(Some(ServiceTokenClaims { jti, scope }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::auth::{SpacetimeCreds, TokenClaims};
+    use crate::auth::{
+        JwtAuthProvider, JwtKeyAuthProvider, RevocationList, ServiceTokenClaims, SpacetimeCreds, TokenClaims,
+    };
     use anyhow::Ok;
 
-    use spacetimedb::auth::{token_validation::TokenValidator, JwtKeys};
+    use spacetimedb::auth::token_validation::{TokenSigner, TokenValidator};
+    use spacetimedb::auth::JwtKeys;
+    use spacetimedb::identity::Identity;
+    use spacetimedb_client_api_messages::collaborators::DatabaseAction;
+    use spacetimedb_client_api_messages::tokens::TokenScope;
     use std::collections::HashSet;
+    use std::sync::Arc;
 
     // Make sure that when we encode TokenClaims, we can decode to get the expected identity.
     #[tokio::test]
@@ -335,13 +472,108 @@ mod tests {
         assert_eq!(keys, expected_keys);
         Ok(())
     }
+
+    struct Revoked(Vec<String>);
+
+    impl RevocationList for Revoked {
+        fn is_revoked(&self, token_id: &str) -> anyhow::Result<bool> {
+            Ok(self.0.iter().any(|id| id == token_id))
+        }
+    }
+
+    // Make sure that service tokens validate as the identity they were created for,
+    // and carry their scope unless revoked.
+    #[tokio::test]
+    async fn service_token_scope() -> Result<(), anyhow::Error> {
+        let kp = JwtKeys::generate()?;
+        let validator = kp.public.clone();
+        let provider = JwtKeyAuthProvider::new(kp, "localhost".to_string(), validator)
+            .with_revocation_list(Arc::new(Revoked(vec!["revoked".to_string()])));
+
+        let claims = TokenClaims::new("localhost".to_string(), "test-subject".to_string());
+        let scope = TokenScope {
+            databases: vec![Identity::ZERO],
+            actions: vec![DatabaseAction::Publish],
+        };
+        let service_token = |jti: &str| ServiceTokenClaims {
+            jti: jti.to_string(),
+            scope: scope.clone(),
+        };
+
+        let (_, plain) = claims.encode_and_sign(&provider)?;
+        assert!(provider.validate_service_token(&plain)?.is_none());
+
+        let (_, token) = claims.encode_and_sign_service_token(&provider, None, &service_token("ci"))?;
+        let decoded = provider.validator().validate_token(&token).await?;
+        assert_eq!(decoded.identity, claims.id());
+        let validated = provider.validate_service_token(&token)?;
+        assert_eq!(validated.map(|t| t.scope), Some(scope.clone()));
+
+        let (_, revoked) = claims.encode_and_sign_service_token(&provider, None, &service_token("revoked"))?;
+        assert!(provider.validate_service_token(&revoked).is_err());
+
+        // Only we may sign service tokens.
+        let other_kp = JwtKeys::generate()?;
+        let (_, forged) = claims.encode_and_sign_service_token(&other_kp.private, None, &service_token("ci"))?;
+        assert!(provider.validate_service_token(&forged).is_err());
+        Ok(())
+    }
+
+    // Make sure that tokens from other issuers are never taken for service tokens,
+    // even if they carry the standard OAuth `scope` claim.
+    #[tokio::test]
+    async fn third_party_token_is_not_service_token() -> Result<(), anyhow::Error> {
+        let kp = JwtKeys::generate()?;
+        let validator = kp.public.clone();
+        let provider = JwtKeyAuthProvider::new(kp, "localhost".to_string(), validator)
+            .with_revocation_list(Arc::new(Revoked(vec![])));
+
+        let other_kp = JwtKeys::generate()?;
+        let oidc_token = other_kp.private.sign(&serde_json::json!({
+            "iss": "https://accounts.example.com",
+            "sub": "test-subject",
+            "aud": "spacetimedb",
+            "jti": "some-id",
+            "scope": "openid profile",
+        }))?;
+        assert!(provider.validate_service_token(&oidc_token)?.is_none());
+
+        // Nor do other issuers get to restrict the scope of their tokens.
+        let scoped = other_kp.private.sign(&serde_json::json!({
+            "iss": "https://accounts.example.com",
+            "sub": "test-subject",
+            "jti": "some-id",
+            "spacetimedb_scope": { "databases": [], "actions": [] },
+        }))?;
+        assert!(provider.validate_service_token(&scoped)?.is_none());
+        Ok(())
+    }
 }
 
+/// Validate `token`, rejecting service tokens.
+///
+/// Use [`validate_token_and_scope`] where the scope of a service token is enforced.
 pub async fn validate_token<S: NodeDelegate>(
     state: &S,
     token: &str,
 ) -> Result<SpacetimeIdentityClaims, TokenValidationError> {
-    state.jwt_auth_provider().validator().validate_token(token).await
+    match validate_token_and_scope(state, token).await? {
+        (claims, None) => Ok(claims),
+        (_, Some(_)) => Err(TokenValidationError::Other(anyhow!(
+            "Service tokens are not accepted for this operation"
+        ))),
+    }
+}
+
+/// Validate `token`, and if it is a service token, the scope it is restricted to.
+pub async fn validate_token_and_scope<S: NodeDelegate>(
+    state: &S,
+    token: &str,
+) -> Result<(SpacetimeIdentityClaims, Option<ServiceTokenClaims>), TokenValidationError> {
+    let provider = state.jwt_auth_provider();
+    let claims = provider.validator().validate_token(token).await?;
+    let service_token = provider.validate_service_token(token)?;
+    Ok((claims, service_token))
 }
 
 pub struct SpacetimeAuthHeader {
@@ -356,7 +588,7 @@ impl<S: NodeDelegate + Send + Sync> axum::extract::FromRequestParts<S> for Space
             return Ok(Self { auth: None });
         };
 
-        let claims = validate_token(state, &creds.token)
+        let (claims, service_token) = validate_token_and_scope(state, &creds.token)
             .await
             .map_err(AuthorizationRejection::Custom)?;
 
@@ -367,6 +599,7 @@ impl<S: NodeDelegate + Send + Sync> axum::extract::FromRequestParts<S> for Space
             creds,
             claims,
             jwt_payload: payload,
+            service_token,
         };
         Ok(Self { auth: Some(auth) })
     }
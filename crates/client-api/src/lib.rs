@@ -1,18 +1,25 @@
 use std::num::NonZeroU8;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use axum::response::ErrorResponse;
 use http::StatusCode;
 
+use spacetimedb::client::rate_limit::RateLimiter;
 use spacetimedb::client::ClientActorIndex;
 use spacetimedb::energy::{EnergyBalance, EnergyQuanta};
 use spacetimedb::host::{HostController, MigratePlanResult, ModuleHost, NoSuchModule, UpdateDatabaseResult};
 use spacetimedb::identity::{AuthCtx, Identity};
 use spacetimedb::messages::control_db::{Database, HostType, Node, Replica};
 use spacetimedb::sql;
+use spacetimedb_client_api_messages::auth_policy::AuthPolicy;
+use spacetimedb_client_api_messages::collaborators::{Collaborator, DatabaseRole};
+use spacetimedb_client_api_messages::energy::EnergyUsageRecord;
 use spacetimedb_client_api_messages::http::{SqlStmtResult, SqlStmtStats};
 use spacetimedb_client_api_messages::name::{DomainName, InsertDomainResult, RegisterTldResult, SetDomainsResult, Tld};
+use spacetimedb_client_api_messages::replay::ReplayReport;
+use spacetimedb_client_api_messages::tokens::ServiceToken;
 use spacetimedb_lib::{ProductTypeElement, ProductValue};
 use spacetimedb_paths::server::ModuleLogsDir;
 use spacetimedb_schema::auto_migrate::{MigrationPolicy, PrettyPrintStyle};
@@ -31,6 +38,7 @@ pub mod util;
 pub trait NodeDelegate: Send + Sync {
     fn gather_metrics(&self) -> Vec<prometheus::proto::MetricFamily>;
     fn client_actor_index(&self) -> &ClientActorIndex;
+    fn rate_limiter(&self) -> &Arc<RateLimiter>;
 
     type JwtAuthProviderT: auth::JwtAuthProvider;
     fn jwt_auth_provider(&self) -> &Self::JwtAuthProviderT;
@@ -142,6 +150,19 @@ impl Host {
         Ok(json)
     }
 
+    /// Replay the transaction at `tx_offset` against a copy of the database,
+    /// reporting whether its reducer writes the same rows again.
+    pub async fn replay(&self, database: Database, tx_offset: u64) -> anyhow::Result<ReplayReport> {
+        self.host_controller
+            .replay_transaction(database, self.replica_id, tx_offset)
+            .await
+    }
+
+    /// Take a snapshot of the database, returning the offset of the snapshot.
+    pub async fn take_snapshot(&self, database: Database) -> anyhow::Result<u64> {
+        self.host_controller.take_snapshot(database, self.replica_id).await
+    }
+
     pub async fn update(
         &self,
         database: Database,
@@ -212,10 +233,41 @@ pub trait ControlStateReadAccess {
 
     // Energy
     fn get_energy_balance(&self, identity: &Identity) -> anyhow::Result<Option<EnergyBalance>>;
+    /// Returns the energy used by the databases owned by `owner_identity`,
+    /// starting with the ledger period which includes `since`.
+    fn get_energy_usage(&self, owner_identity: &Identity, since: SystemTime) -> anyhow::Result<Vec<EnergyUsageRecord>>;
 
     // DNS
     fn lookup_identity(&self, domain: &str) -> anyhow::Result<Option<Identity>>;
     fn reverse_lookup(&self, database_identity: &Identity) -> anyhow::Result<Vec<DomainName>>;
+
+    // Collaborators
+    /// Returns the collaborators of the database, not including its owner.
+    fn get_collaborators(&self, database_identity: &Identity) -> anyhow::Result<Vec<Collaborator>>;
+    fn get_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        identity: &Identity,
+    ) -> anyhow::Result<Option<DatabaseRole>>;
+
+    /// Returns the role `identity` holds on `database`, if any.
+    ///
+    /// The owner of the database always holds [`DatabaseRole::Owner`].
+    fn get_database_role(&self, database: &Database, identity: &Identity) -> anyhow::Result<Option<DatabaseRole>> {
+        if &database.owner_identity == identity {
+            return Ok(Some(DatabaseRole::Owner));
+        }
+        self.get_collaborator_role(&database.database_identity, identity)
+    }
+
+    // Auth policies
+    fn get_auth_policy(&self, database_identity: &Identity) -> anyhow::Result<AuthPolicy>;
+
+    // Service tokens
+    fn get_service_token(&self, id: &str) -> anyhow::Result<Option<ServiceToken>>;
+    /// Returns the service tokens created on behalf of `owner_identity`,
+    /// including revoked and expired ones.
+    fn get_service_tokens(&self, owner_identity: &Identity) -> anyhow::Result<Vec<ServiceToken>>;
 }
 
 /// Write operations on the SpacetimeDB control plane.
@@ -268,6 +320,37 @@ pub trait ControlStateWriteAccess: Send + Sync {
         owner_identity: &Identity,
         domain_names: &[DomainName],
     ) -> anyhow::Result<SetDomainsResult>;
+
+    // Collaborators
+    /// Grant `role` on the database to `collaborator`,
+    /// replacing any role previously granted to them.
+    ///
+    /// Authorization is the responsibility of the caller.
+    async fn set_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        collaborator: &Identity,
+        role: DatabaseRole,
+    ) -> anyhow::Result<()>;
+    /// Revoke the role `collaborator` holds on the database.
+    ///
+    /// Returns `false` if `collaborator` did not hold a role.
+    async fn remove_collaborator(&self, database_identity: &Identity, collaborator: &Identity) -> anyhow::Result<bool>;
+
+    // Auth policies
+    /// Replace the auth policy of the database.
+    ///
+    /// Authorization is the responsibility of the caller.
+    async fn set_auth_policy(&self, database_identity: &Identity, policy: &AuthPolicy) -> anyhow::Result<()>;
+
+    // Service tokens
+    /// Record a newly minted service token.
+    async fn create_service_token(&self, token: &ServiceToken) -> anyhow::Result<()>;
+    /// Revoke the service token with the given `id`.
+    ///
+    /// Authorization is the responsibility of the caller.
+    /// Returns `false` if no such token exists.
+    async fn revoke_service_token(&self, id: &str) -> anyhow::Result<bool>;
 }
 
 impl<T: ControlStateReadAccess + ?Sized> ControlStateReadAccess for Arc<T> {
@@ -305,6 +388,9 @@ impl<T: ControlStateReadAccess + ?Sized> ControlStateReadAccess for Arc<T> {
     fn get_energy_balance(&self, identity: &Identity) -> anyhow::Result<Option<EnergyBalance>> {
         (**self).get_energy_balance(identity)
     }
+    fn get_energy_usage(&self, owner_identity: &Identity, since: SystemTime) -> anyhow::Result<Vec<EnergyUsageRecord>> {
+        (**self).get_energy_usage(owner_identity, since)
+    }
 
     // DNS
     fn lookup_identity(&self, domain: &str) -> anyhow::Result<Option<Identity>> {
@@ -318,6 +404,33 @@ impl<T: ControlStateReadAccess + ?Sized> ControlStateReadAccess for Arc<T> {
     fn get_leader_replica_by_database(&self, database_id: u64) -> Option<Replica> {
         (**self).get_leader_replica_by_database(database_id)
     }
+
+    // Collaborators
+    fn get_collaborators(&self, database_identity: &Identity) -> anyhow::Result<Vec<Collaborator>> {
+        (**self).get_collaborators(database_identity)
+    }
+
+    fn get_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        identity: &Identity,
+    ) -> anyhow::Result<Option<DatabaseRole>> {
+        (**self).get_collaborator_role(database_identity, identity)
+    }
+
+    // Auth policies
+    fn get_auth_policy(&self, database_identity: &Identity) -> anyhow::Result<AuthPolicy> {
+        (**self).get_auth_policy(database_identity)
+    }
+
+    // Service tokens
+    fn get_service_token(&self, id: &str) -> anyhow::Result<Option<ServiceToken>> {
+        (**self).get_service_token(id)
+    }
+
+    fn get_service_tokens(&self, owner_identity: &Identity) -> anyhow::Result<Vec<ServiceToken>> {
+        (**self).get_service_tokens(owner_identity)
+    }
 }
 
 #[async_trait]
@@ -369,6 +482,33 @@ impl<T: ControlStateWriteAccess + ?Sized> ControlStateWriteAccess for Arc<T> {
             .replace_dns_records(database_identity, owner_identity, domain_names)
             .await
     }
+
+    async fn set_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        collaborator: &Identity,
+        role: DatabaseRole,
+    ) -> anyhow::Result<()> {
+        (**self)
+            .set_collaborator_role(database_identity, collaborator, role)
+            .await
+    }
+
+    async fn remove_collaborator(&self, database_identity: &Identity, collaborator: &Identity) -> anyhow::Result<bool> {
+        (**self).remove_collaborator(database_identity, collaborator).await
+    }
+
+    async fn set_auth_policy(&self, database_identity: &Identity, policy: &AuthPolicy) -> anyhow::Result<()> {
+        (**self).set_auth_policy(database_identity, policy).await
+    }
+
+    async fn create_service_token(&self, token: &ServiceToken) -> anyhow::Result<()> {
+        (**self).create_service_token(token).await
+    }
+
+    async fn revoke_service_token(&self, id: &str) -> anyhow::Result<bool> {
+        (**self).revoke_service_token(id).await
+    }
 }
 
 #[async_trait]
@@ -382,6 +522,10 @@ impl<T: NodeDelegate + ?Sized> NodeDelegate for Arc<T> {
         (**self).client_actor_index()
     }
 
+    fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        (**self).rate_limiter()
+    }
+
     fn jwt_auth_provider(&self) -> &Self::JwtAuthProviderT {
         (**self).jwt_auth_provider()
     }
@@ -3,9 +3,10 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use crate::auth::{
-    anon_auth_middleware, SpacetimeAuth, SpacetimeEnergyUsed, SpacetimeExecutionDurationMicros, SpacetimeIdentity,
-    SpacetimeIdentityToken,
+    anon_auth_middleware, ServiceTokenClaims, SpacetimeAuth, SpacetimeEnergyUsed, SpacetimeExecutionDurationMicros,
+    SpacetimeIdentity, SpacetimeIdentityClaims, SpacetimeIdentityToken,
 };
+use crate::routes::identity::IdentityForUrl;
 use crate::routes::subscribe::generate_random_connection_id;
 pub use crate::util::{ByteStringBody, NameOrIdentity};
 use crate::{log_and_500, ControlStateDelegate, DatabaseDef, NodeDelegate};
@@ -18,7 +19,9 @@ use axum_extra::TypedHeader;
 use futures::StreamExt;
 use http::StatusCode;
 use serde::Deserialize;
+use spacetimedb::client::rate_limit::RateLimitKind;
 use spacetimedb::database_logger::DatabaseLogger;
+use spacetimedb::db::snapshot::NoTransactionsYet;
 use spacetimedb::host::module_host::ClientConnectedError;
 use spacetimedb::host::ReducerCallError;
 use spacetimedb::host::ReducerOutcome;
@@ -26,15 +29,24 @@ use spacetimedb::host::UpdateDatabaseResult;
 use spacetimedb::host::{FunctionArgs, MigratePlanResult};
 use spacetimedb::identity::Identity;
 use spacetimedb::messages::control_db::{Database, HostType};
+use spacetimedb_client_api_messages::auth_policy::AuthPolicy;
+use spacetimedb_client_api_messages::collaborators::{
+    Collaborator, DatabaseAction, DatabaseRole, GetCollaboratorsResponse,
+};
 use spacetimedb_client_api_messages::name::{
     self, DatabaseName, DomainName, MigrationPolicy, PrePublishResult, PrettyPrintStyle, PublishOp, PublishResult,
 };
+use spacetimedb_client_api_messages::replay::ReplayReport;
+use spacetimedb_client_api_messages::snapshot::TakeSnapshotResponse;
 use spacetimedb_lib::db::raw_def::v9::RawModuleDefV9;
+use spacetimedb_lib::de::DeserializeSeed;
 use spacetimedb_lib::identity::AuthCtx;
-use spacetimedb_lib::{sats, ProductValue, Timestamp};
+use spacetimedb_lib::{bsatn, sats, AlgebraicType, ProductValue, Timestamp};
 use spacetimedb_schema::auto_migrate::{
     MigrationPolicy as SchemaMigrationPolicy, MigrationToken, PrettyPrintStyle as AutoMigratePrettyPrintStyle,
 };
+use spacetimedb_schema::def::ModuleDef;
+use spacetimedb_schema::openapi::openapi_document;
 
 use super::subscribe::{handle_websocket, HasWebSocketOptions};
 
@@ -64,12 +76,15 @@ pub async fn call<S: ControlStateDelegate + NodeDelegate>(
     let args = FunctionArgs::Json(body);
 
     let db_identity = name_or_identity.resolve(&worker_ctx).await?;
+    check_scope(&auth, &db_identity, DatabaseAction::Connect)?;
     let database = worker_ctx_find_database(&worker_ctx, &db_identity)
         .await?
         .ok_or_else(|| {
             log::error!("Could not find database: {}", db_identity.to_hex());
             NO_SUCH_DATABASE
         })?;
+    check_auth_policy(&worker_ctx, &database, &auth.claims)?;
+    check_rate_limit(&worker_ctx, db_identity, caller_identity, RateLimitKind::ReducerCall)?;
     let identity = database.owner_identity;
 
     let leader = worker_ctx
@@ -149,7 +164,10 @@ pub async fn call<S: ControlStateDelegate + NodeDelegate>(
 
     match result {
         Ok(result) => {
-            let (status, body) = reducer_outcome_response(&identity, &reducer, result.outcome);
+            let return_value = result.return_value.as_deref().and_then(|value| {
+                reducer_return_value_json(&module.info().module_def, &reducer, &result.outcome, value)
+            });
+            let (status, body) = reducer_outcome_response(&identity, &reducer, result.outcome, return_value);
             Ok((
                 status,
                 TypedHeader(SpacetimeEnergyUsed(result.energy_used)),
@@ -161,23 +179,59 @@ pub async fn call<S: ControlStateDelegate + NodeDelegate>(
     }
 }
 
-fn reducer_outcome_response(identity: &Identity, reducer: &str, outcome: ReducerOutcome) -> (StatusCode, String) {
-    match outcome {
-        ReducerOutcome::Committed => (StatusCode::OK, "".to_owned()),
-        ReducerOutcome::Failed(errmsg) => {
-            // TODO: different status code? this is what cloudflare uses, sorta
-            (StatusCode::from_u16(530).unwrap(), errmsg)
+/// Renders the value returned by `reducer` or the error value it failed with as JSON,
+/// for reducers declaring a return type other than `()` or an error type other than `String`.
+fn reducer_return_value_json(
+    module_def: &ModuleDef,
+    reducer: &str,
+    outcome: &ReducerOutcome,
+    value: &[u8],
+) -> Option<String> {
+    let reducer_def = module_def.reducer(reducer)?;
+    let ty = match outcome {
+        ReducerOutcome::Committed if !reducer_def.ok_return_type.is_unit() => &reducer_def.ok_return_type,
+        ReducerOutcome::Failed(_) if reducer_def.err_return_type != AlgebraicType::String => {
+            &reducer_def.err_return_type
         }
-        ReducerOutcome::BudgetExceeded => {
+        _ => return None,
+    };
+    let ty = module_def.typespace().with_type(ty);
+    let value = match ty.deserialize(bsatn::Deserializer::new(&mut &*value)) {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("failed to decode the return value of reducer {reducer}: {e}");
+            return None;
+        }
+    };
+    serde_json::to_string(&sats::serde::SerdeWrapper(ty.with_value(&value))).ok()
+}
+
+fn reducer_outcome_response(
+    identity: &Identity,
+    reducer: &str,
+    outcome: ReducerOutcome,
+    return_value: Option<String>,
+) -> (StatusCode, axum::response::Response) {
+    match (outcome, return_value) {
+        (ReducerOutcome::Committed, Some(value)) => (StatusCode::OK, json_response(value)),
+        (ReducerOutcome::Committed, None) => (StatusCode::OK, "".into_response()),
+        // TODO: different status code? this is what cloudflare uses, sorta
+        (ReducerOutcome::Failed(_), Some(value)) => (StatusCode::from_u16(530).unwrap(), json_response(value)),
+        (ReducerOutcome::Failed(errmsg), None) => (StatusCode::from_u16(530).unwrap(), errmsg.into_response()),
+        (ReducerOutcome::BudgetExceeded, _) => {
             log::warn!("Node's energy budget exceeded for identity: {identity} while executing {reducer}");
             (
                 StatusCode::PAYMENT_REQUIRED,
-                "Module energy budget exhausted.".to_owned(),
+                "Module energy budget exhausted.".into_response(),
             )
         }
     }
 }
 
+fn json_response(json: String) -> axum::response::Response {
+    ([(http::header::CONTENT_TYPE, "application/json")], json).into_response()
+}
+
 #[derive(Debug, derive_more::From)]
 pub enum DBCallErr {
     HandlerError(ErrorResponse),
@@ -236,6 +290,32 @@ where
     ))
 }
 
+/// Describes the HTTP API of a database as an OpenAPI document,
+/// with JSON Schemas for its reducers' arguments and its tables' rows.
+pub async fn openapi<S>(
+    State(worker_ctx): State<S>,
+    Path(SchemaParams { name_or_identity }): Path<SchemaParams>,
+) -> axum::response::Result<impl IntoResponse>
+where
+    S: ControlStateDelegate + NodeDelegate,
+{
+    let database_name = name_or_identity.to_string();
+    let db_identity = name_or_identity.resolve(&worker_ctx).await?;
+    let database = worker_ctx_find_database(&worker_ctx, &db_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    let leader = worker_ctx
+        .leader(database.id)
+        .await
+        .map_err(log_and_500)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let module = leader.module().await.map_err(log_and_500)?;
+
+    let document = openapi_document(&module.info.module_def, &database_name);
+    Ok(axum::Json(document))
+}
+
 #[derive(Deserialize)]
 pub struct DatabaseParam {
     name_or_identity: NameOrIdentity,
@@ -297,25 +377,15 @@ pub async fn logs<S>(
 where
     S: ControlStateDelegate + NodeDelegate,
 {
-    // You should not be able to read the logs from a database that you do not own
-    // so, unless you are the owner, this will fail.
+    // You should not be able to read the logs from a database that you do not
+    // have a role on, so unless you are the owner or a collaborator, this will fail.
 
     let database_identity: Identity = name_or_identity.resolve(&worker_ctx).await?;
     let database = worker_ctx_find_database(&worker_ctx, &database_identity)
         .await?
         .ok_or(NO_SUCH_DATABASE)?;
 
-    if database.owner_identity != auth.claims.identity {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "Identity does not own database, expected: {} got: {}",
-                database.owner_identity.to_hex(),
-                auth.claims.identity.to_hex()
-            ),
-        )
-            .into());
-    }
+    authorize(&worker_ctx, &database, &auth, DatabaseAction::ReadLogs)?;
 
     let replica = worker_ctx
         .get_leader_replica_by_database(database.id)
@@ -372,6 +442,114 @@ fn mime_ndjson() -> mime::Mime {
     "application/x-ndjson".parse().unwrap()
 }
 
+/// Checks that the caller holds a role on `database` which permits `action`,
+/// and that `action` is within the scope of their credentials,
+/// and returns that role.
+pub(crate) fn authorize(
+    ctx: &(impl ControlStateDelegate + ?Sized),
+    database: &Database,
+    auth: &SpacetimeAuth,
+    action: DatabaseAction,
+) -> axum::response::Result<DatabaseRole> {
+    check_scope(auth, &database.database_identity, action)?;
+    authorize_identity(ctx, database, &auth.claims.identity, action)
+}
+
+/// Checks that `identity` holds a role on `database` which permits `action`,
+/// and returns that role.
+fn authorize_identity(
+    ctx: &(impl ControlStateDelegate + ?Sized),
+    database: &Database,
+    identity: &Identity,
+    action: DatabaseAction,
+) -> axum::response::Result<DatabaseRole> {
+    match ctx.get_database_role(database, identity).map_err(log_and_500)? {
+        Some(role) if role.permits(action) => Ok(role),
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            format!(
+                "Identity {} is not permitted to perform `{action}` on database {}, which requires the `{}` role",
+                identity.to_hex(),
+                database.database_identity.to_hex(),
+                action.required_role().unwrap_or(DatabaseRole::ReadOnly),
+            ),
+        )
+            .into()),
+    }
+}
+
+/// Checks that `action` on the database with identity `database_identity`
+/// is within the scope of the caller's credentials.
+pub(crate) fn check_scope(
+    auth: &SpacetimeAuth,
+    database_identity: &Identity,
+    action: DatabaseAction,
+) -> axum::response::Result<()> {
+    check_token_scope(auth.service_token.as_ref(), database_identity, action)
+}
+
+fn check_token_scope(
+    service_token: Option<&ServiceTokenClaims>,
+    database_identity: &Identity,
+    action: DatabaseAction,
+) -> axum::response::Result<()> {
+    match service_token {
+        Some(token) if !token.scope.permits(database_identity, action) => Err((
+            StatusCode::FORBIDDEN,
+            format!(
+                "Service token {} does not permit `{action}` on database {}",
+                token.jti,
+                database_identity.to_hex(),
+            ),
+        )
+            .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the caller's token is accepted by the [`AuthPolicy`] of `database`.
+///
+/// This must happen before `client_connected` runs,
+/// so that the module never observes rejected tokens.
+pub(crate) fn check_auth_policy(
+    ctx: &(impl ControlStateDelegate + ?Sized),
+    database: &Database,
+    claims: &SpacetimeIdentityClaims,
+) -> axum::response::Result<()> {
+    let policy = ctx.get_auth_policy(&database.database_identity).map_err(log_and_500)?;
+    if policy.is_unrestricted() {
+        return Ok(());
+    }
+    // The owner and collaborators are exempt, lest they lock themselves out.
+    let role = ctx.get_database_role(database, &claims.identity).map_err(log_and_500)?;
+    if role.is_some() {
+        return Ok(());
+    }
+    policy
+        .check(&claims.issuer, &claims.audience)
+        .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()).into())
+}
+
+/// Charges a request by `caller` to `database` against the per-identity rate limits,
+/// responding with `429 Too Many Requests` if they are exceeded.
+pub(crate) fn check_rate_limit(
+    ctx: &(impl NodeDelegate + ?Sized),
+    database: Identity,
+    caller: Identity,
+    kind: RateLimitKind,
+) -> axum::response::Result<()> {
+    ctx.rate_limiter().check_identity(database, caller, kind).map_err(|e| {
+        // `Retry-After` is in whole seconds, so round up.
+        let retry_after = e.retry_after().map(|d| d.as_secs_f64().ceil() as u64);
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(http::header::RETRY_AFTER, retry_after.unwrap_or(1).to_string())],
+            e.to_string(),
+        )
+            .into()
+    })
+}
+
 pub(crate) async fn worker_ctx_find_database(
     worker_ctx: &(impl ControlStateDelegate + ?Sized),
     database_identity: &Identity,
@@ -398,13 +576,17 @@ pub async fn sql_direct<S>(
     worker_ctx: S,
     SqlParams { name_or_identity }: SqlParams,
     SqlQueryParams { confirmed }: SqlQueryParams,
-    caller_identity: Identity,
+    claims: &SpacetimeIdentityClaims,
+    service_token: Option<&ServiceTokenClaims>,
     sql: String,
 ) -> axum::response::Result<Vec<SqlStmtResult<ProductValue>>>
 where
     S: NodeDelegate + ControlStateDelegate,
 {
-    // Anyone is authorized to execute SQL queries. The SQL engine will determine
+    let caller_identity = claims.identity;
+
+    // Anyone whose token is accepted by the database's auth policy is authorized
+    // to execute SQL queries. The SQL engine will determine
     // which queries this identity is allowed to execute against the database.
 
     let db_identity = name_or_identity.resolve(&worker_ctx).await?;
@@ -412,7 +594,27 @@ where
         .await?
         .ok_or(NO_SUCH_DATABASE)?;
 
-    let auth = AuthCtx::new(database.owner_identity, caller_identity);
+    let action = if is_query(&sql) {
+        DatabaseAction::ReadSql
+    } else {
+        DatabaseAction::WriteSql
+    };
+    check_token_scope(service_token, &db_identity, action)?;
+    check_auth_policy(&worker_ctx, &database, claims)?;
+    check_rate_limit(&worker_ctx, db_identity, caller_identity, RateLimitKind::OneOffQuery)?;
+
+    // Collaborators act with the privileges of the owner,
+    // while `:sender` still resolves to their own identity.
+    let role = worker_ctx
+        .get_database_role(&database, &caller_identity)
+        .map_err(log_and_500)?;
+    let auth = match role {
+        Some(role) if role.permits(DatabaseAction::ReadSql) => {
+            authorize_identity(&worker_ctx, &database, &caller_identity, action)?;
+            AuthCtx::for_current(caller_identity)
+        }
+        _ => AuthCtx::new(database.owner_identity, caller_identity),
+    };
     log::debug!("auth: {auth:?}");
 
     let host = worker_ctx
@@ -424,6 +626,18 @@ where
     host.exec_sql(auth, database, confirmed, sql).await
 }
 
+/// Returns whether `sql` is a read-only statement.
+///
+/// Unparseable statements are reported as queries,
+/// so that the SQL engine gets to report the actual error.
+fn is_query(sql: &str) -> bool {
+    use spacetimedb_sql_parser::{ast::sql::SqlAst, parser::sql::parse_sql};
+    matches!(
+        parse_sql(sql),
+        Ok(SqlAst::Select(_) | SqlAst::Show(_) | SqlAst::Explain(_)) | Err(_)
+    )
+}
+
 pub async fn sql<S>(
     State(worker_ctx): State<S>,
     Path(name_or_identity): Path<SqlParams>,
@@ -434,7 +648,15 @@ pub async fn sql<S>(
 where
     S: NodeDelegate + ControlStateDelegate,
 {
-    let json = sql_direct(worker_ctx, name_or_identity, params, auth.claims.identity, body).await?;
+    let json = sql_direct(
+        worker_ctx,
+        name_or_identity,
+        params,
+        &auth.claims,
+        auth.service_token.as_ref(),
+        body,
+    )
+    .await?;
 
     let total_duration = json.iter().fold(0, |acc, x| acc + x.total_duration_micros);
 
@@ -444,6 +666,90 @@ where
     ))
 }
 
+#[derive(Deserialize)]
+pub struct ReplayParams {
+    name_or_identity: NameOrIdentity,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayQueryParams {
+    tx_offset: u64,
+}
+
+/// Re-run the reducer which committed the transaction at `tx_offset`
+/// against the state of the database just before it,
+/// and respond with a [`ReplayReport`] of how its writes differ from the original.
+///
+/// As the report contains row data, this requires the privileges of [`DatabaseAction::ReadSql`].
+pub async fn replay<S>(
+    State(worker_ctx): State<S>,
+    Path(ReplayParams { name_or_identity }): Path<ReplayParams>,
+    Query(ReplayQueryParams { tx_offset }): Query<ReplayQueryParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+) -> axum::response::Result<impl IntoResponse>
+where
+    S: NodeDelegate + ControlStateDelegate,
+{
+    let database_identity = name_or_identity.resolve(&worker_ctx).await?;
+    let database = worker_ctx_find_database(&worker_ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    authorize(&worker_ctx, &database, &auth, DatabaseAction::ReadSql)?;
+
+    let host = worker_ctx
+        .leader(database.id)
+        .await
+        .map_err(log_and_500)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let report: ReplayReport =
+        host.replay(database, tx_offset)
+            .await
+            .map_err(|e| match e.downcast_ref::<NoTransactionsYet>() {
+                Some(e) => (StatusCode::CONFLICT, e.to_string()).into(),
+                None => log_and_500(e),
+            })?;
+
+    Ok(axum::Json(report))
+}
+
+#[derive(Deserialize)]
+pub struct TakeSnapshotParams {
+    name_or_identity: NameOrIdentity,
+}
+
+/// Take a snapshot of the database now, regardless of its snapshot policy,
+/// and respond with the offset of the snapshot once it is stored on disk.
+pub async fn take_snapshot<S>(
+    State(worker_ctx): State<S>,
+    Path(TakeSnapshotParams { name_or_identity }): Path<TakeSnapshotParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+) -> axum::response::Result<impl IntoResponse>
+where
+    S: NodeDelegate + ControlStateDelegate,
+{
+    let database_identity = name_or_identity.resolve(&worker_ctx).await?;
+    let database = worker_ctx_find_database(&worker_ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    authorize(&worker_ctx, &database, &auth, DatabaseAction::TakeSnapshot)?;
+
+    let host = worker_ctx
+        .leader(database.id)
+        .await
+        .map_err(log_and_500)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let tx_offset = host
+        .take_snapshot(database)
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")))?;
+
+    Ok(axum::Json(TakeSnapshotResponse { tx_offset }))
+}
+
 #[derive(Deserialize)]
 pub struct DNSParams {
     name_or_identity: NameOrIdentity,
@@ -513,6 +819,13 @@ fn require_spacetime_auth_for_creation() -> bool {
 
 // A hacky function to let us restrict database creation on maincloud.
 fn allow_creation(auth: &SpacetimeAuth) -> Result<(), ErrorResponse> {
+    if auth.service_token.is_some() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Service tokens cannot be used to create databases.",
+        )
+            .into());
+    }
     if !require_spacetime_auth_for_creation() {
         return Ok(());
     }
@@ -544,8 +857,9 @@ pub async fn publish<S: NodeDelegate + ControlStateDelegate>(
     Extension(auth): Extension<SpacetimeAuth>,
     body: Bytes,
 ) -> axum::response::Result<axum::Json<PublishResult>> {
-    // You should not be able to publish to a database that you do not own
-    // so, unless you are the owner, this will fail.
+    // You should not be able to publish to a database that you do not have
+    // a sufficient role on, so unless you are the owner or a collaborator
+    // permitted to publish, this will fail.
 
     let (database_identity, db_name) = match &name_or_identity {
         Some(noa) => match noa.try_resolve(&ctx).await.map_err(log_and_500)? {
@@ -609,68 +923,102 @@ pub async fn publish<S: NodeDelegate + ControlStateDelegate>(
         MigrationPolicy::Compatible => Ok(SchemaMigrationPolicy::Compatible),
     }?;
 
+    let num_replicas = num_replicas
+        .map(|n| {
+            let n = u8::try_from(n).map_err(|_| (StatusCode::BAD_REQUEST, "Replication factor {n} out of bounds"))?;
+            Ok::<_, ErrorResponse>(NonZeroU8::new(n))
+        })
+        .transpose()?
+        .flatten();
+
     log::trace!("Publishing to the identity: {}", database_identity.to_hex());
 
+    // The identity on whose behalf the database is published.
+    // When clearing an existing database, it is re-created with its original
+    // owner and collaborators, rather than handed over to the caller.
+    let mut publisher = auth.claims.identity;
+    let mut restore_collaborators = vec![];
+    let mut restore_auth_policy = None;
+
     let op = {
-        let exists = ctx
-            .get_database_by_identity(&database_identity)
-            .map_err(log_and_500)?
-            .is_some();
-        if !exists {
-            allow_creation(&auth)?;
+        let existing = ctx.get_database_by_identity(&database_identity).map_err(log_and_500)?;
+        match &existing {
+            Some(database) => {
+                let action = if clear {
+                    DatabaseAction::Delete
+                } else {
+                    DatabaseAction::Publish
+                };
+                authorize(&ctx, database, &auth, action)?;
+            }
+            None => allow_creation(&auth)?,
         }
 
-        if clear && exists {
+        if let (true, Some(database)) = (clear, &existing) {
+            restore_collaborators = ctx.get_collaborators(&database_identity).map_err(log_and_500)?;
+            restore_auth_policy = Some(ctx.get_auth_policy(&database_identity).map_err(log_and_500)?);
+            publisher = database.owner_identity;
             ctx.delete_database(&auth.claims.identity, &database_identity)
                 .await
                 .map_err(log_and_500)?;
         }
 
-        if exists {
+        if existing.is_some() {
             PublishOp::Updated
         } else {
             PublishOp::Created
         }
     };
 
-    let num_replicas = num_replicas
-        .map(|n| {
-            let n = u8::try_from(n).map_err(|_| (StatusCode::BAD_REQUEST, "Replication factor {n} out of bounds"))?;
-            Ok::<_, ErrorResponse>(NonZeroU8::new(n))
-        })
-        .transpose()?
-        .flatten();
-
-    let maybe_updated = ctx
-        .publish_database(
-            &auth.claims.identity,
-            DatabaseDef {
-                database_identity,
-                program_bytes: body.into(),
-                num_replicas,
-                host_type,
-            },
-            policy,
-        )
-        .await
-        .map_err(log_and_500)?;
+    async {
+        let maybe_updated = ctx
+            .publish_database(
+                &publisher,
+                DatabaseDef {
+                    database_identity,
+                    program_bytes: body.into(),
+                    num_replicas,
+                    host_type,
+                },
+                policy,
+            )
+            .await
+            .map_err(log_and_500)?;
 
-    if let Some(updated) = maybe_updated {
-        match updated {
-            UpdateDatabaseResult::AutoMigrateError(errs) => {
-                return Err((StatusCode::BAD_REQUEST, format!("Database update rejected: {errs}")).into());
-            }
-            UpdateDatabaseResult::ErrorExecutingMigration(err) => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    format!("Failed to create or update the database: {err}"),
-                )
-                    .into());
+        if let Some(updated) = maybe_updated {
+            match updated {
+                UpdateDatabaseResult::AutoMigrateError(errs) => {
+                    return Err((StatusCode::BAD_REQUEST, format!("Database update rejected: {errs}")).into());
+                }
+                UpdateDatabaseResult::ErrorExecutingMigration(err) => {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to create or update the database: {err}"),
+                    )
+                        .into());
+                }
+                UpdateDatabaseResult::NoUpdateNeeded
+                | UpdateDatabaseResult::UpdatePerformed
+                | UpdateDatabaseResult::UpdatePerformedWithClientDisconnect => {}
             }
-            UpdateDatabaseResult::NoUpdateNeeded
-            | UpdateDatabaseResult::UpdatePerformed
-            | UpdateDatabaseResult::UpdatePerformedWithClientDisconnect => {}
         }
+        Ok::<_, ErrorResponse>(())
+    }
+    .await?;
+
+    // Restore the collaborators and auth policy of a cleared database
+    // only once it has been re-created.
+    // If that failed, there is no database for them to refer to,
+    // and writing them would leave orphaned rows behind.
+    for Collaborator { identity, role } in restore_collaborators {
+        ctx.set_collaborator_role(&database_identity, &identity, role)
+            .await
+            .map_err(log_and_500)?;
+    }
+    if let Some(policy) = restore_auth_policy {
+        ctx.set_auth_policy(&database_identity, &policy)
+            .await
+            .map_err(log_and_500)?;
     }
 
     Ok(axum::Json(PublishResult::Success {
@@ -700,8 +1048,8 @@ pub async fn pre_publish<S: NodeDelegate + ControlStateDelegate>(
     Extension(auth): Extension<SpacetimeAuth>,
     body: Bytes,
 ) -> axum::response::Result<axum::Json<PrePublishResult>> {
-    // User should not be able to print migration plans for a database that they do not own
-    let database_identity = resolve_and_authenticate(&ctx, &name_or_identity, &auth).await?;
+    // User should not be able to print migration plans for a database that they may not publish to
+    let database_identity = resolve_and_authorize(&ctx, &name_or_identity, &auth, DatabaseAction::Publish).await?;
     let style = match style {
         PrettyPrintStyle::NoColor => AutoMigratePrettyPrintStyle::NoColor,
         PrettyPrintStyle::AnsiColor => AutoMigratePrettyPrintStyle::AnsiColor,
@@ -750,11 +1098,12 @@ pub async fn pre_publish<S: NodeDelegate + ControlStateDelegate>(
 }
 
 /// Resolves the [`NameOrIdentity`] to a database identity and checks if the
-/// `auth` identity owns the database.
-async fn resolve_and_authenticate<S: ControlStateDelegate>(
+/// `auth` identity holds a role on the database which permits `action`.
+async fn resolve_and_authorize<S: ControlStateDelegate>(
     ctx: &S,
     name_or_identity: &NameOrIdentity,
     auth: &SpacetimeAuth,
+    action: DatabaseAction,
 ) -> axum::response::Result<Identity> {
     let database_identity = name_or_identity.resolve(ctx).await?;
 
@@ -762,17 +1111,7 @@ async fn resolve_and_authenticate<S: ControlStateDelegate>(
         .await?
         .ok_or(NO_SUCH_DATABASE)?;
 
-    if database.owner_identity != auth.claims.identity {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            format!(
-                "Identity does not own database, expected: {} got: {}",
-                database.owner_identity.to_hex(),
-                auth.claims.identity.to_hex()
-            ),
-        )
-            .into());
-    }
+    authorize(ctx, &database, auth, action)?;
 
     Ok(database_identity)
 }
@@ -789,6 +1128,10 @@ pub async fn delete_database<S: ControlStateDelegate>(
 ) -> axum::response::Result<impl IntoResponse> {
     let database_identity = name_or_identity.resolve(&ctx).await?;
 
+    if let Some(database) = worker_ctx_find_database(&ctx, &database_identity).await? {
+        authorize(&ctx, &database, &auth, DatabaseAction::Delete)?;
+    }
+
     ctx.delete_database(&auth.claims.identity, &database_identity)
         .await
         .map_err(log_and_500)?;
@@ -808,10 +1151,29 @@ pub async fn add_name<S: ControlStateDelegate>(
     name: String,
 ) -> axum::response::Result<impl IntoResponse> {
     let name = DatabaseName::try_from(name).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    let domain = DomainName::from(name);
     let database_identity = name_or_identity.resolve(&ctx).await?;
 
+    let database = worker_ctx_find_database(&ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    check_scope(&auth, &database_identity, DatabaseAction::Rename)?;
+    let permitted = ctx
+        .get_database_role(&database, &auth.claims.identity)
+        .map_err(log_and_500)?
+        .is_some_and(|role| role.permits(DatabaseAction::Rename));
+    if !permitted {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            axum::Json(name::InsertDomainResult::PermissionDenied { domain }),
+        ));
+    }
+
+    // Like `set_names`, names are registered on behalf of the database owner,
+    // who must own their top-level domain.
     let response = ctx
-        .create_dns_record(&auth.claims.identity, &name.into(), &database_identity)
+        .create_dns_record(&database.owner_identity, &domain, &database_identity)
         .await
         // TODO: better error code handling
         .map_err(log_and_500)?;
@@ -854,7 +1216,12 @@ pub async fn set_names<S: ControlStateDelegate>(
         ));
     };
 
-    if database.owner_identity != auth.claims.identity {
+    check_scope(&auth, &database_identity, DatabaseAction::Rename)?;
+    let permitted = ctx
+        .get_database_role(&database, &auth.claims.identity)
+        .map_err(log_and_500)?
+        .is_some_and(|role| role.permits(DatabaseAction::Rename));
+    if !permitted {
         return Ok((
             StatusCode::UNAUTHORIZED,
             axum::Json(name::SetDomainsResult::NotYourDatabase {
@@ -891,6 +1258,201 @@ pub async fn set_names<S: ControlStateDelegate>(
     Ok((status, axum::Json(response)))
 }
 
+#[derive(Deserialize)]
+pub struct CollaboratorsParams {
+    name_or_identity: NameOrIdentity,
+}
+
+/// Lists the identities holding a role on the database, including its owner.
+pub async fn get_collaborators<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(CollaboratorsParams { name_or_identity }): Path<CollaboratorsParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+) -> axum::response::Result<impl IntoResponse> {
+    let database_identity = name_or_identity.resolve(&ctx).await?;
+    let database = worker_ctx_find_database(&ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    authorize(&ctx, &database, &auth, DatabaseAction::ListCollaborators)?;
+
+    let owner = Collaborator {
+        identity: database.owner_identity,
+        role: DatabaseRole::Owner,
+    };
+    let collaborators = std::iter::once(owner)
+        .chain(ctx.get_collaborators(&database_identity).map_err(log_and_500)?)
+        .collect();
+
+    Ok(axum::Json(GetCollaboratorsResponse { collaborators }))
+}
+
+#[derive(Deserialize)]
+pub struct CollaboratorParams {
+    name_or_identity: NameOrIdentity,
+    identity: IdentityForUrl,
+}
+
+#[derive(Deserialize)]
+pub struct CollaboratorQueryParams {
+    role: DatabaseRole,
+}
+
+/// Resolves the database and checks that `caller` may change the role of
+/// `collaborator` on it.
+///
+/// Callers may only manage collaborators whose role, if any,
+/// does not exceed their own.
+async fn authorize_collaborator_change<S: ControlStateDelegate>(
+    ctx: &S,
+    name_or_identity: &NameOrIdentity,
+    caller: &SpacetimeAuth,
+    collaborator: &Identity,
+) -> axum::response::Result<(Database, DatabaseRole, Option<DatabaseRole>)> {
+    let database_identity = name_or_identity.resolve(ctx).await?;
+    let database = worker_ctx_find_database(ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    if &database.owner_identity == collaborator {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The role of the database owner cannot be changed.",
+        )
+            .into());
+    }
+
+    let caller_role = authorize(ctx, &database, caller, DatabaseAction::ManageCollaborators)?;
+    let current_role = ctx
+        .get_collaborator_role(&database_identity, collaborator)
+        .map_err(log_and_500)?;
+    if current_role.is_some_and(|role| role > caller_role) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            format!(
+                "Identity {} holds a role more privileged than `{caller_role}`",
+                collaborator.to_hex()
+            ),
+        )
+            .into());
+    }
+
+    Ok((database, caller_role, current_role))
+}
+
+/// Grants a role on the database to an identity,
+/// replacing any role previously granted to it.
+pub async fn set_collaborator<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(CollaboratorParams {
+        name_or_identity,
+        identity,
+    }): Path<CollaboratorParams>,
+    Query(CollaboratorQueryParams { role }): Query<CollaboratorQueryParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+) -> axum::response::Result<impl IntoResponse> {
+    let collaborator = Identity::from(identity);
+    let (database, caller_role, _) =
+        authorize_collaborator_change(&ctx, &name_or_identity, &auth, &collaborator).await?;
+
+    if role > caller_role {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            format!("Cannot grant the `{role}` role, which is more privileged than `{caller_role}`"),
+        )
+            .into());
+    }
+
+    ctx.set_collaborator_role(&database.database_identity, &collaborator, role)
+        .await
+        .map_err(log_and_500)?;
+
+    Ok(axum::Json(Collaborator {
+        identity: collaborator,
+        role,
+    }))
+}
+
+/// Revokes the role an identity holds on the database.
+///
+/// Collaborators may always revoke their own role.
+pub async fn remove_collaborator<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(CollaboratorParams {
+        name_or_identity,
+        identity,
+    }): Path<CollaboratorParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+) -> axum::response::Result<impl IntoResponse> {
+    let collaborator = Identity::from(identity);
+    let database_identity = if collaborator == auth.claims.identity {
+        let database_identity = name_or_identity.resolve(&ctx).await?;
+        check_scope(&auth, &database_identity, DatabaseAction::ManageCollaborators)?;
+        database_identity
+    } else {
+        let (database, ..) = authorize_collaborator_change(&ctx, &name_or_identity, &auth, &collaborator).await?;
+        database.database_identity
+    };
+
+    let removed = ctx
+        .remove_collaborator(&database_identity, &collaborator)
+        .await
+        .map_err(log_and_500)?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Identity {} is not a collaborator", collaborator.to_hex()),
+        )
+            .into());
+    }
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct AuthPolicyParams {
+    name_or_identity: NameOrIdentity,
+}
+
+/// Returns the issuers and audiences of tokens accepted by the database.
+///
+/// Anyone may read the policy, as clients need to know which tokens to obtain.
+pub async fn get_auth_policy<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(AuthPolicyParams { name_or_identity }): Path<AuthPolicyParams>,
+) -> axum::response::Result<impl IntoResponse> {
+    let database_identity = name_or_identity.resolve(&ctx).await?;
+    worker_ctx_find_database(&ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    let policy = ctx.get_auth_policy(&database_identity).map_err(log_and_500)?;
+    Ok(axum::Json(policy))
+}
+
+/// Replaces the issuers and audiences of tokens accepted by the database.
+///
+/// Clients which are already connected are not affected.
+pub async fn set_auth_policy<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(AuthPolicyParams { name_or_identity }): Path<AuthPolicyParams>,
+    Extension(auth): Extension<SpacetimeAuth>,
+    axum::Json(policy): axum::Json<AuthPolicy>,
+) -> axum::response::Result<impl IntoResponse> {
+    let database_identity = name_or_identity.resolve(&ctx).await?;
+    let database = worker_ctx_find_database(&ctx, &database_identity)
+        .await?
+        .ok_or(NO_SUCH_DATABASE)?;
+
+    authorize(&ctx, &database, &auth, DatabaseAction::ManageAuthPolicy)?;
+
+    ctx.set_auth_policy(&database_identity, &policy)
+        .await
+        .map_err(log_and_500)?;
+
+    Ok(axum::Json(policy))
+}
+
 #[derive(serde::Deserialize)]
 pub struct TimestampParams {
     name_or_identity: NameOrIdentity,
@@ -942,14 +1504,30 @@ pub struct DatabaseRoutes<S> {
     pub call_reducer_post: MethodRouter<S>,
     /// GET: /database/:name_or_identity/schema
     pub schema_get: MethodRouter<S>,
+    /// GET: /database/:name_or_identity/openapi.json
+    pub openapi_get: MethodRouter<S>,
     /// GET: /database/:name_or_identity/logs
     pub logs_get: MethodRouter<S>,
     /// POST: /database/:name_or_identity/sql
     pub sql_post: MethodRouter<S>,
+    /// POST: /database/:name_or_identity/replay
+    pub replay_post: MethodRouter<S>,
+    /// POST: /database/:name_or_identity/snapshot
+    pub snapshot_post: MethodRouter<S>,
     /// POST: /database/:name_or_identity/pre-publish
     pub pre_publish: MethodRouter<S>,
     /// GET: /database/: name_or_identity/unstable/timestamp
     pub timestamp_get: MethodRouter<S>,
+    /// GET: /database/:name_or_identity/collaborators
+    pub collaborators_get: MethodRouter<S>,
+    /// PUT: /database/:name_or_identity/collaborators/:identity
+    pub collaborator_put: MethodRouter<S>,
+    /// DELETE: /database/:name_or_identity/collaborators/:identity
+    pub collaborator_delete: MethodRouter<S>,
+    /// GET: /database/:name_or_identity/auth-policy
+    pub auth_policy_get: MethodRouter<S>,
+    /// PUT: /database/:name_or_identity/auth-policy
+    pub auth_policy_put: MethodRouter<S>,
 }
 
 impl<S> Default for DatabaseRoutes<S>
@@ -970,10 +1548,18 @@ where
             subscribe_get: get(handle_websocket::<S>),
             call_reducer_post: post(call::<S>),
             schema_get: get(schema::<S>),
+            openapi_get: get(openapi::<S>),
             logs_get: get(logs::<S>),
             sql_post: post(sql::<S>),
+            replay_post: post(replay::<S>),
+            snapshot_post: post(take_snapshot::<S>),
             pre_publish: post(pre_publish::<S>),
             timestamp_get: get(get_timestamp::<S>),
+            collaborators_get: get(get_collaborators::<S>),
+            collaborator_put: put(set_collaborator::<S>),
+            collaborator_delete: delete(remove_collaborator::<S>),
+            auth_policy_get: get(get_auth_policy::<S>),
+            auth_policy_put: put(set_auth_policy::<S>),
         }
     }
 }
@@ -994,10 +1580,18 @@ where
             .route("/subscribe", self.subscribe_get)
             .route("/call/:reducer", self.call_reducer_post)
             .route("/schema", self.schema_get)
+            .route("/openapi.json", self.openapi_get)
             .route("/logs", self.logs_get)
             .route("/sql", self.sql_post)
+            .route("/replay", self.replay_post)
+            .route("/snapshot", self.snapshot_post)
             .route("/unstable/timestamp", self.timestamp_get)
-            .route("/pre_publish", self.pre_publish);
+            .route("/pre_publish", self.pre_publish)
+            .route("/collaborators", self.collaborators_get)
+            .route("/collaborators/:identity", self.collaborator_put)
+            .route("/collaborators/:identity", self.collaborator_delete)
+            .route("/auth-policy", self.auth_policy_get)
+            .route("/auth-policy", self.auth_policy_put);
 
         axum::Router::new()
             .route("/", self.root_post)
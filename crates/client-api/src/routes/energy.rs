@@ -4,7 +4,9 @@ use http::StatusCode;
 use serde::{Deserialize, Serialize};
 
 use spacetimedb::energy::EnergyQuanta;
+use spacetimedb_client_api_messages::energy::GetEnergyUsageResponse;
 use spacetimedb_lib::Identity;
+use std::time::{Duration, UNIX_EPOCH};
 
 use crate::auth::SpacetimeAuthRequired;
 use crate::{log_and_500, ControlStateDelegate, NodeDelegate};
@@ -42,6 +44,13 @@ pub async fn add_energy<S: ControlStateDelegate>(
     Query(AddEnergyQueryParams { amount }): Query<AddEnergyQueryParams>,
     SpacetimeAuthRequired(auth): SpacetimeAuthRequired,
 ) -> axum::response::Result<impl IntoResponse> {
+    // Now that energy is metered, identities must not be able to refill their own balance.
+    // As with `set_energy_balance`, no one is able to be the dummy identity,
+    // so this always returns unauthorized.
+    if auth.claims.identity != Identity::__dummy() {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+
     // Nb.: Negative amount withdraws
     let amount = amount.map(|s| s.parse::<u128>()).transpose().map_err(|e| {
         log::error!("Failed to parse amount: {e:?}");
@@ -119,15 +128,43 @@ pub async fn set_energy_balance<S: ControlStateDelegate>(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct EnergyUsageQueryParams {
+    /// Skip usage before this time, in seconds since the Unix epoch.
+    since: Option<u64>,
+}
+
+/// Returns the energy ledger of the databases owned by `identity`.
+///
+/// Only `identity` itself may read its ledger.
+pub async fn get_energy_usage<S: ControlStateDelegate>(
+    State(ctx): State<S>,
+    Path(IdentityParams { identity }): Path<IdentityParams>,
+    Query(EnergyUsageQueryParams { since }): Query<EnergyUsageQueryParams>,
+    SpacetimeAuthRequired(auth): SpacetimeAuthRequired,
+) -> axum::response::Result<impl IntoResponse> {
+    let identity = Identity::from(identity);
+    if auth.claims.identity != identity || auth.service_token.is_some() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let since = UNIX_EPOCH + Duration::from_secs(since.unwrap_or(0));
+    let usage = ctx.get_energy_usage(&identity, since).map_err(log_and_500)?;
+
+    Ok(axum::Json(GetEnergyUsageResponse { usage }))
+}
+
 pub fn router<S>() -> axum::Router<S>
 where
     S: NodeDelegate + ControlStateDelegate + Clone + 'static,
 {
     use axum::routing::get;
-    axum::Router::new().route(
-        "/:identity",
-        get(get_energy_balance::<S>)
-            .put(set_energy_balance::<S>)
-            .post(add_energy::<S>),
-    )
+    axum::Router::new()
+        .route(
+            "/:identity",
+            get(get_energy_balance::<S>)
+                .put(set_energy_balance::<S>)
+                .post(add_energy::<S>),
+        )
+        .route("/:identity/usage", get(get_energy_usage::<S>))
 }
@@ -6,10 +6,14 @@ use http::header::CONTENT_TYPE;
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
 
+use spacetimedb_client_api_messages::tokens::{
+    CreateServiceTokenRequest, CreateServiceTokenResponse, GetServiceTokensResponse, ServiceToken,
+};
 use spacetimedb_lib::de::serde::DeserializeWrapper;
 use spacetimedb_lib::Identity;
+use uuid::Uuid;
 
-use crate::auth::{JwtAuthProvider, SpacetimeAuth, SpacetimeAuthRequired};
+use crate::auth::{JwtAuthProvider, ServiceTokenClaims, SpacetimeAuth, SpacetimeAuthRequired, TokenClaims};
 use crate::{log_and_500, ControlStateDelegate, NodeDelegate};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,15 +139,117 @@ pub async fn get_public_key<S: NodeDelegate>(State(ctx): State<S>) -> axum::resp
     ))
 }
 
+/// Service tokens may not be used to manage service tokens,
+/// lest they could escape their scope.
+fn reject_service_token(auth: &SpacetimeAuth) -> axum::response::Result<()> {
+    if auth.service_token.is_some() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Service tokens cannot be used to manage service tokens.",
+        )
+            .into());
+    }
+    Ok(())
+}
+
+/// Mints a service token acting on behalf of the caller,
+/// restricted to the requested scope.
+pub async fn create_service_token<S: NodeDelegate + ControlStateDelegate>(
+    State(ctx): State<S>,
+    SpacetimeAuthRequired(auth): SpacetimeAuthRequired,
+    axum::Json(request): axum::Json<CreateServiceTokenRequest>,
+) -> axum::response::Result<impl IntoResponse> {
+    reject_service_token(&auth)?;
+    // We only honor the scope of tokens we issued, so a service token must be for an identity we issued,
+    // as it keeps the issuer of the caller's token.
+    if auth.claims.issuer != ctx.jwt_auth_provider().local_issuer() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Service tokens can only be created for identities issued by this instance.",
+        )
+            .into());
+    }
+    let CreateServiceTokenRequest {
+        description,
+        scope,
+        expires_in_secs,
+    } = request;
+    if scope.databases.is_empty() || scope.actions.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "A service token must be scoped to at least one database and one action.",
+        )
+            .into());
+    }
+
+    let service_token = ServiceTokenClaims {
+        jti: Uuid::new_v4().to_string(),
+        scope,
+    };
+    let expiry = expires_in_secs.map(Duration::from_secs);
+    let (claims, token) = TokenClaims::from(auth)
+        .encode_and_sign_service_token(ctx.jwt_auth_provider(), expiry, &service_token)
+        .map_err(log_and_500)?;
+
+    let ServiceTokenClaims { jti, scope } = service_token;
+    let service_token = ServiceToken {
+        id: jti,
+        owner_identity: claims.identity,
+        description,
+        scope,
+        created_at: claims.iat,
+        expires_at: claims.exp,
+        revoked: false,
+    };
+    ctx.create_service_token(&service_token).await.map_err(log_and_500)?;
+
+    Ok(axum::Json(CreateServiceTokenResponse { service_token, token }))
+}
+
+/// Lists the service tokens created on behalf of the caller.
+pub async fn get_service_tokens<S: ControlStateDelegate + NodeDelegate>(
+    State(ctx): State<S>,
+    SpacetimeAuthRequired(auth): SpacetimeAuthRequired,
+) -> axum::response::Result<impl IntoResponse> {
+    reject_service_token(&auth)?;
+    let service_tokens = ctx.get_service_tokens(&auth.claims.identity).map_err(log_and_500)?;
+    Ok(axum::Json(GetServiceTokensResponse { service_tokens }))
+}
+
+#[derive(Deserialize)]
+pub struct ServiceTokenParams {
+    id: String,
+}
+
+/// Revokes a service token created on behalf of the caller.
+pub async fn revoke_service_token<S: ControlStateDelegate + NodeDelegate>(
+    State(ctx): State<S>,
+    Path(ServiceTokenParams { id }): Path<ServiceTokenParams>,
+    SpacetimeAuthRequired(auth): SpacetimeAuthRequired,
+) -> axum::response::Result<impl IntoResponse> {
+    reject_service_token(&auth)?;
+    let token = ctx.get_service_token(&id).map_err(log_and_500)?;
+    if !token.is_some_and(|token| token.owner_identity == auth.claims.identity) {
+        return Err((StatusCode::NOT_FOUND, format!("No such service token: {id}")).into());
+    }
+    ctx.revoke_service_token(&id).await.map_err(log_and_500)?;
+    Ok(StatusCode::OK)
+}
+
 pub fn router<S>() -> axum::Router<S>
 where
     S: NodeDelegate + ControlStateDelegate + Clone + 'static,
 {
-    use axum::routing::{get, post};
+    use axum::routing::{delete, get, post};
     axum::Router::new()
         .route("/", post(create_identity::<S>))
         .route("/public-key", get(get_public_key::<S>))
         .route("/websocket-token", post(create_websocket_token::<S>))
+        .route(
+            "/service-tokens",
+            get(get_service_tokens::<S>).post(create_service_token::<S>),
+        )
+        .route("/service-tokens/:id", delete(revoke_service_token::<S>))
         .route("/:identity/verify", get(validate_token))
         .route("/:identity/databases", get(get_databases::<S>))
 }
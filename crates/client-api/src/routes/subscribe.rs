@@ -24,6 +24,7 @@ use serde::Deserialize;
 use spacetimedb::client::messages::{
     serialize, IdentityTokenMessage, SerializableMessage, SerializeBuffer, SwitchedServerMessage, ToProtocol,
 };
+use spacetimedb::client::rate_limit::ConnectionRateLimiter;
 use spacetimedb::client::{
     ClientActorId, ClientConfig, ClientConnection, ClientConnectionReceiver, DataMessage, MessageExecutionError,
     MessageHandleError, MeteredReceiver, MeteredSender, Protocol,
@@ -33,6 +34,7 @@ use spacetimedb::host::NoSuchModule;
 use spacetimedb::util::spawn_rayon;
 use spacetimedb::worker_metrics::WORKER_METRICS;
 use spacetimedb::Identity;
+use spacetimedb_client_api_messages::collaborators::DatabaseAction;
 use spacetimedb_client_api_messages::websocket::{self as ws_api, Compression};
 use spacetimedb_datastore::execution_context::WorkloadType;
 use spacetimedb_lib::connection_id::{ConnectionId, ConnectionIdForUrl};
@@ -44,6 +46,7 @@ use tokio::time::{sleep_until, timeout};
 use tokio_tungstenite::tungstenite::Utf8Bytes;
 
 use crate::auth::SpacetimeAuth;
+use crate::routes::database::{check_auth_policy, check_scope};
 use crate::util::serde::humantime_duration;
 use crate::util::websocket::{
     CloseCode, CloseFrame, Message as WsMessage, WebSocketConfig, WebSocketStream, WebSocketUpgrade, WsError,
@@ -86,6 +89,14 @@ pub struct SubscribeQueryParams {
     /// If `false`, send them immediately.
     #[serde(default)]
     pub confirmed: bool,
+    /// If `true`, send the caller of a reducer which returned a value
+    /// a `ReducerResult` carrying that value, in place of its `TransactionUpdate`.
+    #[serde(default)]
+    pub reducer_results: bool,
+    /// If `true`, follow each message which reflects a transaction with a `CaughtUp` message
+    /// naming the latest transaction the client's subscribed rows reflect.
+    #[serde(default)]
+    pub tx_offsets: bool,
 }
 
 pub fn generate_random_connection_id() -> ConnectionId {
@@ -100,6 +111,8 @@ pub async fn handle_websocket<S>(
         compression,
         light,
         confirmed,
+        reducer_results,
+        tx_offsets,
     }): Query<SubscribeQueryParams>,
     forwarded_for: Option<TypedHeader<XForwardedFor>>,
     Extension(auth): Extension<SpacetimeAuth>,
@@ -125,6 +138,7 @@ where
     }
 
     let db_identity = name_or_identity.resolve(&ctx).await?;
+    check_scope(&auth, &db_identity, DatabaseAction::Connect)?;
 
     let (res, ws_upgrade, protocol) =
         ws.select_protocol([(BIN_PROTOCOL, Protocol::Binary), (TEXT_PROTOCOL, Protocol::Text)]);
@@ -135,6 +149,8 @@ where
         compression,
         tx_update_full: !light,
         confirmed_reads: confirmed,
+        reducer_results,
+        tx_offsets,
     };
 
     // TODO: Should also maybe refactor the code and the protocol to allow a single websocket
@@ -144,6 +160,7 @@ where
         .get_database_by_identity(&db_identity)
         .unwrap()
         .ok_or(StatusCode::NOT_FOUND)?;
+    check_auth_policy(&ctx, &database, &auth.claims)?;
 
     let leader = ctx
         .leader(database.id)
@@ -167,6 +184,7 @@ where
         .max_frame_size(None)
         .accept_unmasked_frames(false);
     let ws_opts = ctx.websocket_options();
+    let rate_limiter = ctx.rate_limiter().clone();
 
     tokio::spawn(async move {
         let ws = match ws_upgrade.upgrade(ws_config).await {
@@ -219,6 +237,7 @@ where
             client_id,
             auth.into(),
             client_config,
+            &rate_limiter,
             leader.replica_id,
             module_rx,
             actor,
@@ -248,16 +267,23 @@ struct ActorState {
     pub client_id: ClientActorId,
     pub database: Identity,
     config: WebSocketOptions,
+    rate_limiter: Arc<ConnectionRateLimiter>,
     closed: AtomicBool,
     got_pong: AtomicBool,
 }
 
 impl ActorState {
-    pub fn new(database: Identity, client_id: ClientActorId, config: WebSocketOptions) -> Self {
+    pub fn new(
+        database: Identity,
+        client_id: ClientActorId,
+        config: WebSocketOptions,
+        rate_limiter: Arc<ConnectionRateLimiter>,
+    ) -> Self {
         Self {
             database,
             client_id,
             config,
+            rate_limiter,
             closed: AtomicBool::new(false),
             got_pong: AtomicBool::new(true),
         }
@@ -385,7 +411,12 @@ async fn ws_client_actor_inner(
     let database = client.module().info().database_identity;
     let client_id = client.id;
     let client_closed_metric = WORKER_METRICS.ws_clients_closed_connection.with_label_values(&database);
-    let state = Arc::new(ActorState::new(database, client_id, config));
+    let state = Arc::new(ActorState::new(
+        database,
+        client_id,
+        config,
+        client.rate_limiter.clone(),
+    ));
 
     // Channel for [`UnorderedWsMessage`]s.
     let (unordered_tx, unordered_rx) = mpsc::unbounded_channel();
@@ -1036,6 +1067,10 @@ impl Receiver for mpsc::Receiver<SerializableMessage> {
 /// This is so `ws_client_actor_inner` keeps polling the receive end of the
 /// socket until the close handshake completes -- it would otherwise exit early
 /// when sending to `unordered` fails.
+///
+/// If the connection is subject to an outbound rate limit, stops polling
+/// `messages` after sending a message until the limit permits sending more.
+/// `unordered` keeps being polled in the meantime.
 async fn ws_send_loop(
     state: Arc<ActorState>,
     config: ClientConfig,
@@ -1044,6 +1079,8 @@ async fn ws_send_loop(
     mut unordered: mpsc::UnboundedReceiver<UnorderedWsMessage>,
 ) {
     let mut serialize_buf = SerializeBuffer::new(config);
+    let mut throttle = pin!(tokio::time::sleep(Duration::ZERO));
+    let mut throttled = false;
 
     loop {
         let closed = state.closed();
@@ -1106,7 +1143,11 @@ async fn ws_send_loop(
                 }
             },
 
-            maybe_message = messages.recv(), if !closed => {
+            () = &mut throttle, if throttled => {
+                throttled = false;
+            },
+
+            maybe_message = messages.recv(), if !closed && !throttled => {
                 let Some(message) = maybe_message else {
                     // The message sender was dropped, even though no close
                     // handshake is in progress. This should not normally happen,
@@ -1132,9 +1173,18 @@ async fn ws_send_loop(
                 ).await;
                 serialize_buf = msg_alloc;
 
-                if let Err(e) = res {
-                    log::warn!("websocket send error: {e}");
-                    return;
+                match res {
+                    Ok(len) => {
+                        let delay = state.rate_limiter.throttle_outbound(len);
+                        if !delay.is_zero() {
+                            throttle.as_mut().reset((Instant::now() + delay).into());
+                            throttled = true;
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("websocket send error: {e}");
+                        return;
+                    }
                 }
             },
         }
@@ -1147,6 +1197,8 @@ async fn ws_send_loop(
 }
 
 /// Serialize and potentially compress `message`, and feed it to the `ws` sink.
+///
+/// Returns the size of the message as sent.
 async fn send_message<S: Sink<WsMessage> + Unpin>(
     database_identity: &Identity,
     config: ClientConfig,
@@ -1154,7 +1206,7 @@ async fn send_message<S: Sink<WsMessage> + Unpin>(
     metrics_metadata: Option<(WorkloadType, usize)>,
     ws: &mut S,
     message: impl ToProtocol<Encoded = SwitchedServerMessage> + Send + 'static,
-) -> (SerializeBuffer, Result<(), S::Error>) {
+) -> (SerializeBuffer, Result<usize, S::Error>) {
     let (workload, num_rows) = metrics_metadata.unzip();
     // Move large messages to a rayon thread,
     // as serialization and compression can take a long time.
@@ -1170,6 +1222,7 @@ async fn send_message<S: Sink<WsMessage> + Unpin>(
         serialize_and_compress(serialize_buf, message, config)
     };
     report_ws_sent_metrics(database_identity, workload, num_rows, timing, &msg_data);
+    let len = msg_data.len();
 
     let res = async {
         ws.feed(datamsg_to_wsmsg(msg_data)).await?;
@@ -1179,7 +1232,8 @@ async fn send_message<S: Sink<WsMessage> + Unpin>(
         // We don't want to flush the inner sink just yet, as we might be
         // writing many messages.
         // `SplitSink::poll_ready` does what we want.
-        poll_fn(|cx| ws.poll_ready_unpin(cx)).await
+        poll_fn(|cx| ws.poll_ready_unpin(cx)).await?;
+        Ok::<_, S::Error>(len)
     }
     .await;
     // Reclaim can fail if we didn't succeed pushing down the data to the
@@ -1287,7 +1341,9 @@ mod tests {
     }
 
     fn dummy_actor_state_with_config(config: WebSocketOptions) -> ActorState {
-        ActorState::new(Identity::ZERO, dummy_client_id(), config)
+        let client_id = dummy_client_id();
+        let rate_limiter = Arc::new(ConnectionRateLimiter::unlimited(client_id.identity));
+        ActorState::new(Identity::ZERO, client_id, config, rate_limiter)
     }
 
     #[tokio::test]
@@ -13,8 +13,8 @@ use crate::util::{
 use crate::{indent_scope, OutputFile};
 use convert_case::{Case, Casing};
 use spacetimedb_lib::sats::layout::PrimitiveType;
-use spacetimedb_primitives::ColId;
-use spacetimedb_schema::def::{BTreeAlgorithm, IndexAlgorithm, ModuleDef, TableDef, TypeDef};
+use spacetimedb_primitives::{ColId, ColList};
+use spacetimedb_schema::def::{BTreeAlgorithm, DirectAlgorithm, IndexAlgorithm, ModuleDef, TableDef, TypeDef};
 use spacetimedb_schema::schema::{Schema, TableSchema};
 use spacetimedb_schema::type_for_generate::{
     AlgebraicTypeDef, AlgebraicTypeUse, PlainEnumTypeDef, ProductTypeDef, SumTypeDef, TypespaceForGenerate,
@@ -479,62 +479,73 @@ impl Lang for Csharp<'_> {
                         continue;
                     };
 
-                    match &idx.algorithm {
-                        IndexAlgorithm::BTree(BTreeAlgorithm { columns }) => {
-                            let get_csharp_field_name_and_type = |col_pos: ColId| {
-                                let (field_name, field_type) = &product_type.elements[col_pos.idx()];
-                                let csharp_field_name_pascal = field_name.deref().to_case(Case::Pascal);
-                                let csharp_field_type = ty_fmt(module, field_type);
-                                (csharp_field_name_pascal, csharp_field_type)
-                            };
-
-                            let (row_to_key, key_type) = match columns.as_singleton() {
-                                Some(col_pos) => {
-                                    let (field_name, field_type) = get_csharp_field_name_and_type(col_pos);
-                                    (format!("row.{field_name}"), field_type.to_string())
-                                }
-                                None => {
-                                    let mut key_accessors = Vec::new();
-                                    let mut key_type_elems = Vec::new();
-                                    for (field_name, field_type) in columns.iter().map(get_csharp_field_name_and_type) {
-                                        key_accessors.push(format!("row.{field_name}"));
-                                        key_type_elems.push(format!("{field_type} {field_name}"));
-                                    }
-                                    (
-                                        format!("({})", key_accessors.join(", ")),
-                                        format!("({})", key_type_elems.join(", ")),
-                                    )
-                                }
-                            };
-
-                            let csharp_index_name = accessor_name.deref().to_case(Case::Pascal);
-
-                            let mut csharp_index_class_name = csharp_index_name.clone();
-                            let csharp_index_base_class_name = if schema.is_unique(columns) {
-                                csharp_index_class_name += "UniqueIndex";
-                                "UniqueIndexBase"
-                            } else {
-                                csharp_index_class_name += "Index";
-                                "BTreeIndexBase"
-                            };
-
-                            writeln!(output, "public sealed class {csharp_index_class_name} : {csharp_index_base_class_name}<{key_type}>");
-                            indented_block(output, |output| {
-                                writeln!(
-                                    output,
-                                    "protected override {key_type} GetKey({table_type} row) => {row_to_key};"
-                                );
-                                writeln!(output);
-                                writeln!(output, "public {csharp_index_class_name}({csharp_table_class_name} table) : base(table) {{ }}");
-                            });
-                            writeln!(output);
-                            writeln!(output, "public readonly {csharp_index_class_name} {csharp_index_name};");
-                            writeln!(output);
-
-                            index_names.push(csharp_index_name);
+                    let columns: ColList = match &idx.algorithm {
+                        IndexAlgorithm::BTree(BTreeAlgorithm { columns }) => columns.clone(),
+                        // A direct index is keyed on a single column, like a single-column btree index.
+                        IndexAlgorithm::Direct(DirectAlgorithm { column }) => (*column).into(),
+                        // Clients can't compute the keys of these, so they get no accessor.
+                        IndexAlgorithm::BTreeExpr(_) => continue,
+                        // Clients don't evaluate index filters or tokenize text, so these get no accessor either.
+                        IndexAlgorithm::PartialBTree(_) | IndexAlgorithm::FullText(_) => continue,
+                    };
+
+                    let get_csharp_field_name_and_type = |col_pos: ColId| {
+                        let (field_name, field_type) = &product_type.elements[col_pos.idx()];
+                        let csharp_field_name_pascal = field_name.deref().to_case(Case::Pascal);
+                        let csharp_field_type = ty_fmt(module, field_type);
+                        (csharp_field_name_pascal, csharp_field_type)
+                    };
+
+                    let (row_to_key, key_type) = match columns.as_singleton() {
+                        Some(col_pos) => {
+                            let (field_name, field_type) = get_csharp_field_name_and_type(col_pos);
+                            (format!("row.{field_name}"), field_type.to_string())
                         }
-                        _ => todo!(),
-                    }
+                        None => {
+                            let mut key_accessors = Vec::new();
+                            let mut key_type_elems = Vec::new();
+                            for (field_name, field_type) in columns.iter().map(get_csharp_field_name_and_type) {
+                                key_accessors.push(format!("row.{field_name}"));
+                                key_type_elems.push(format!("{field_type} {field_name}"));
+                            }
+                            (
+                                format!("({})", key_accessors.join(", ")),
+                                format!("({})", key_type_elems.join(", ")),
+                            )
+                        }
+                    };
+
+                    let csharp_index_name = accessor_name.deref().to_case(Case::Pascal);
+
+                    let mut csharp_index_class_name = csharp_index_name.clone();
+                    let csharp_index_base_class_name = if schema.is_unique(&columns) {
+                        csharp_index_class_name += "UniqueIndex";
+                        "UniqueIndexBase"
+                    } else {
+                        csharp_index_class_name += "Index";
+                        "BTreeIndexBase"
+                    };
+
+                    writeln!(
+                        output,
+                        "public sealed class {csharp_index_class_name} : {csharp_index_base_class_name}<{key_type}>"
+                    );
+                    indented_block(output, |output| {
+                        writeln!(
+                            output,
+                            "protected override {key_type} GetKey({table_type} row) => {row_to_key};"
+                        );
+                        writeln!(output);
+                        writeln!(
+                            output,
+                            "public {csharp_index_class_name}({csharp_table_class_name} table) : base(table) {{ }}"
+                        );
+                    });
+                    writeln!(output);
+                    writeln!(output, "public readonly {csharp_index_class_name} {csharp_index_name};");
+                    writeln!(output);
+
+                    index_names.push(csharp_index_name);
                 }
 
                 writeln!(
@@ -670,10 +681,58 @@ impl Lang for Csharp<'_> {
                 writeln!(output, ");");
                 writeln!(output, "return true;");
             });
+
+            if reducer.has_typed_return() {
+                writeln!(output);
+                writeln!(output, "/// <summary>");
+                writeln!(
+                    output,
+                    "/// Decode the value returned by the reducer <c>{}</c>, if it committed, or the error value it failed with, if it failed.",
+                    reducer.name
+                );
+                writeln!(output, "///");
+                writeln!(
+                    output,
+                    "/// Returns <c>null</c> if <c>ctx</c> is not an invocation of this reducer, or if it did not run to completion."
+                );
+                writeln!(output, "/// </summary>");
+                writeln!(
+                    output,
+                    "public static {func_name_pascal_case}Result? Get{func_name_pascal_case}Result(ReducerEventContext ctx) =>"
+                );
+                {
+                    indent_scope!(output);
+                    writeln!(output, "ctx.Event.Reducer is Reducer.{func_name_pascal_case}");
+                    {
+                        indent_scope!(output);
+                        writeln!(
+                            output,
+                            "? ctx.Event.DecodeReturnValue<{func_name_pascal_case}Result, {func_name_pascal_case}Result.BSATN>()"
+                        );
+                        writeln!(output, ": null;");
+                    }
+                }
+            }
         });
 
         writeln!(output);
 
+        if reducer.has_typed_return() {
+            let func_name_pascal_case = reducer.name.deref().to_case(Case::Pascal);
+            writeln!(output, "[SpacetimeDB.Type]");
+            writeln!(
+                output,
+                "public partial record {func_name_pascal_case}Result : SpacetimeDB.TaggedEnum<("
+            );
+            {
+                indent_scope!(output);
+                writeln!(output, "{} Ok,", ty_fmt(module, &reducer.ok_return_type_for_generate));
+                writeln!(output, "{} Err", ty_fmt(module, &reducer.err_return_type_for_generate));
+            }
+            writeln!(output, ")>;");
+            writeln!(output);
+        }
+
         writeln!(output, "public abstract partial class Reducer");
         indented_block(&mut output, |output| {
             autogen_csharp_product_common(
@@ -0,0 +1,832 @@
+//! Go client bindings, for use with the Go client SDK in `sdks/go`.
+//!
+//! The generated files form a single Go package, named by [`Go::package_name`]:
+//! a struct (or interface, or enum) per type, a cache type per table,
+//! a method on `RemoteReducers` per reducer, and a `Connect` function which ties them together.
+
+use crate::util::{
+    is_reducer_invokable, iter_client_btree_indexes, iter_tables, iter_unique_cols, print_auto_generated_file_comment,
+    print_auto_generated_version_comment,
+};
+use crate::OutputFile;
+
+use super::util::{collect_case, type_ref_name};
+
+use std::ops::Deref;
+
+use convert_case::{Case, Casing};
+use spacetimedb_lib::sats::layout::PrimitiveType;
+use spacetimedb_schema::def::{ModuleDef, ReducerDef, ScopedTypeName, TableDef, TypeDef};
+use spacetimedb_schema::identifier::Identifier;
+use spacetimedb_schema::schema::{Schema, TableSchema};
+use spacetimedb_schema::type_for_generate::{AlgebraicTypeDef, AlgebraicTypeUse};
+
+use super::code_indenter::{CodeIndenter, Indenter};
+use super::Lang;
+
+const INDENT: &str = "\t";
+
+/// The import path of the Go client SDK.
+const SDK_IMPORT_PATH: &str = "github.com/clockworklabs/SpacetimeDB/sdks/go";
+
+/// The name under which generated files import the SDK.
+const SDK: &str = "spacetimedb";
+
+/// Names which generated code uses for receivers and locals,
+/// and so can't also be used for reducer arguments or index columns.
+const RESERVED_LOCALS: &[&str] = &["t", "row", "reducers", SDK];
+
+const GO_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+];
+
+pub struct Go<'a> {
+    /// The name of the generated Go package, conventionally the last component of the output directory.
+    pub package_name: &'a str,
+}
+
+impl Lang for Go<'_> {
+    fn generate_type_files(&self, module: &ModuleDef, typ: &TypeDef) -> Vec<OutputFile> {
+        let type_name = collect_case(Case::Pascal, typ.name.name_segments());
+
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        match &module.typespace_for_generate()[typ.ty] {
+            AlgebraicTypeDef::Product(product) => {
+                self.print_file_header(out, &[]);
+                writeln!(out, "// {type_name} is the type `{type_name}` defined by the module.");
+                define_struct(module, out, &type_name, &product.elements);
+            }
+            AlgebraicTypeDef::Sum(sum) => {
+                self.print_file_header(out, &["fmt"]);
+                define_sum(module, out, &type_name, &sum.variants);
+            }
+            AlgebraicTypeDef::PlainEnum(plain_enum) => {
+                self.print_file_header(out, &["fmt"]);
+                define_plain_enum(out, &type_name, &plain_enum.variants);
+            }
+        }
+
+        vec![OutputFile {
+            filename: type_file_name(&typ.name),
+            code: output.into_inner(),
+        }]
+    }
+
+    fn generate_table_file(&self, module: &ModuleDef, table: &TableDef) -> OutputFile {
+        let schema = TableSchema::from_module_def(module, table, (), 0.into())
+            .validated()
+            .expect("Failed to generate table due to validation errors");
+
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        self.print_file_header(out, &[]);
+
+        let typespace = module.typespace_for_generate();
+        let product_def = typespace[table.product_type_ref].as_product().unwrap();
+        let row_type = type_ref_name(module, table.product_type_ref);
+        let table_type = table_type_name(&table.name);
+        let table_name = table.name.deref();
+
+        writeln!(
+            out,
+            "// {table_type} is the client cache of the table `{table_name}`, whose rows are {row_type}s."
+        );
+        writeln!(out, "type {table_type} struct {{");
+        out.with_indent(|out| writeln!(out, "*{SDK}.TableCache[{row_type}]"));
+        writeln!(out, "}}");
+        writeln!(out);
+
+        // Rows are matched into updates by using their primary key as a map key,
+        // so only primary keys which Go can compare with `==` are usable.
+        let primary_key = schema
+            .pk()
+            .map(|pk| &product_def.elements[pk.col_pos.idx()])
+            .filter(|(_, ty)| is_comparable(module, ty))
+            .map(|(ident, _)| format!("func(row {row_type}) any {{ return row.{} }}", field_name(ident)))
+            .unwrap_or_else(|| "nil".into());
+        writeln!(out, "func new{table_type}() {table_type} {{");
+        out.with_indent(|out| {
+            writeln!(
+                out,
+                "return {table_type}{{{SDK}.NewTableCache(\"{table_name}\", Read{row_type}, {primary_key})}}"
+            );
+        });
+        writeln!(out, "}}");
+
+        for (ident, ty) in iter_unique_cols(typespace, &schema, product_def) {
+            if !is_comparable(module, ty) {
+                continue;
+            }
+            let field = field_name(ident);
+            let param = local_name(ident);
+            writeln!(out);
+            writeln!(
+                out,
+                "// FindBy{field} returns the subscribed row whose `{}` is {param}, if there is one.",
+                ident.deref()
+            );
+            writeln!(
+                out,
+                "func (t {table_type}) FindBy{field}({param} {}) ({row_type}, bool) {{",
+                type_name(module, ty)
+            );
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "return t.Find(func(row {row_type}) bool {{ return row.{field} == {param} }})"
+                );
+            });
+            writeln!(out, "}}");
+        }
+
+        for (accessor_name, columns) in iter_client_btree_indexes(&schema, table, product_def) {
+            let method = format!("FilterBy{}", accessor_name.deref().to_case(Case::Pascal));
+            let params = columns
+                .iter()
+                .map(|(ident, ty)| format!("{} {}", local_name(ident), type_name(module, ty)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let cond = columns
+                .iter()
+                .map(|(ident, _)| format!("row.{} == {}", field_name(ident), local_name(ident)))
+                .collect::<Vec<_>>()
+                .join(" && ");
+            let column_names = columns
+                .iter()
+                .map(|(ident, _)| format!("`{}`", ident.deref()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(out);
+            writeln!(
+                out,
+                "// {method} returns the subscribed rows whose {column_names} match the arguments, over the columns of the index `{}`.",
+                accessor_name.deref()
+            );
+            writeln!(out, "func (t {table_type}) {method}({params}) []{row_type} {{");
+            out.with_indent(|out| {
+                writeln!(out, "return t.Filter(func(row {row_type}) bool {{ return {cond} }})");
+            });
+            writeln!(out, "}}");
+        }
+
+        OutputFile {
+            filename: table.name.deref().to_case(Case::Snake) + "_table.go",
+            code: output.into_inner(),
+        }
+    }
+
+    fn generate_reducer_file(&self, module: &ModuleDef, reducer: &ReducerDef) -> OutputFile {
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        self.print_file_header(out, &[]);
+
+        let reducer_name = reducer.name.deref();
+        let func_name = reducer_name.to_case(Case::Pascal);
+        let args_type = format!("{func_name}Args");
+        let params = &reducer.params_for_generate.elements;
+
+        writeln!(out, "// {args_type} are the arguments to the reducer `{reducer_name}`.");
+        define_struct(module, out, &args_type, params);
+
+        if is_reducer_invokable(reducer) {
+            let typed_params = params
+                .iter()
+                .map(|(ident, ty)| format!("{} {}", local_name(ident), type_name(module, ty)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let fields = params
+                .iter()
+                .map(|(ident, _)| format!("{}: {}", field_name(ident), local_name(ident)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(out);
+            writeln!(
+                out,
+                "// {func_name} requests that the database run the reducer `{reducer_name}`, returning the ID of the request."
+            );
+            writeln!(
+                out,
+                "func (reducers RemoteReducers) {func_name}({typed_params}) (uint32, error) {{"
+            );
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "return reducers.conn.CallReducer(\"{reducer_name}\", {SDK}.Encode({args_type}{{{fields}}}), {SDK}.FullUpdate)"
+                );
+            });
+            writeln!(out, "}}");
+
+            writeln!(out);
+            writeln!(
+                out,
+                "// On{func_name} registers cb to run whenever the server reports a call to the reducer `{reducer_name}`."
+            );
+            writeln!(out, "//");
+            writeln!(out, "// Calls whose arguments fail to decode are not reported to cb.");
+            writeln!(
+                out,
+                "func (reducers RemoteReducers) On{func_name}(cb func(ev *{SDK}.ReducerEvent, args {args_type})) {SDK}.CallbackID {{"
+            );
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "return reducers.conn.OnReducer(\"{reducer_name}\", func(ev *{SDK}.ReducerEvent) {{"
+                );
+                out.with_indent(|out| {
+                    writeln!(out, "args, err := {SDK}.DecodeWith(ev.Args, Read{args_type})");
+                    writeln!(out, "if err != nil {{");
+                    out.with_indent(|out| writeln!(out, "return"));
+                    writeln!(out, "}}");
+                    writeln!(out, "cb(ev, args)");
+                });
+                writeln!(out, "}})");
+            });
+            writeln!(out, "}}");
+
+            writeln!(out);
+            writeln!(
+                out,
+                "// RemoveOn{func_name} removes a callback registered with On{func_name}."
+            );
+            writeln!(
+                out,
+                "func (reducers RemoteReducers) RemoveOn{func_name}(id {SDK}.CallbackID) {{"
+            );
+            out.with_indent(|out| writeln!(out, "reducers.conn.RemoveOnReducer(\"{reducer_name}\", id)"));
+            writeln!(out, "}}");
+        }
+
+        if reducer.has_typed_return() {
+            let ok_ty = &reducer.ok_return_type_for_generate;
+            let err_ty = &reducer.err_return_type_for_generate;
+            let (ok_name, err_name) = (type_name(module, ok_ty), type_name(module, err_ty));
+
+            writeln!(out);
+            writeln!(
+                out,
+                "// Decode{func_name}Result decodes the value the reducer `{reducer_name}` returned in ev, if it committed,"
+            );
+            writeln!(out, "// or the error value it failed with, if it failed.");
+            writeln!(out, "// Both are nil if the reducer did not run to completion.");
+            writeln!(
+                out,
+                "func Decode{func_name}Result(ev *{SDK}.ReducerEvent) (*{ok_name}, *{err_name}, error) {{"
+            );
+            out.with_indent(|out| {
+                writeln!(out, "if ev.ReturnValue == nil {{");
+                out.with_indent(|out| writeln!(out, "return nil, nil, nil"));
+                writeln!(out, "}}");
+                writeln!(out, "switch ev.Status {{");
+                writeln!(out, "case {SDK}.Committed:");
+                out.with_indent(|out| {
+                    writeln!(
+                        out,
+                        "v, err := {SDK}.DecodeWith(*ev.ReturnValue, {})",
+                        reader_fn(module, ok_ty)
+                    );
+                    writeln!(out, "if err != nil {{");
+                    out.with_indent(|out| writeln!(out, "return nil, nil, err"));
+                    writeln!(out, "}}");
+                    writeln!(out, "return &v, nil, nil");
+                });
+                writeln!(out, "case {SDK}.Failed:");
+                out.with_indent(|out| {
+                    writeln!(
+                        out,
+                        "v, err := {SDK}.DecodeWith(*ev.ReturnValue, {})",
+                        reader_fn(module, err_ty)
+                    );
+                    writeln!(out, "if err != nil {{");
+                    out.with_indent(|out| writeln!(out, "return nil, nil, err"));
+                    writeln!(out, "}}");
+                    writeln!(out, "return nil, &v, nil");
+                });
+                writeln!(out, "}}");
+                writeln!(out, "return nil, nil, nil");
+            });
+            writeln!(out, "}}");
+        }
+
+        OutputFile {
+            filename: reducer_name.to_case(Case::Snake) + "_reducer.go",
+            code: output.into_inner(),
+        }
+    }
+
+    fn generate_global_files(&self, module: &ModuleDef) -> Vec<OutputFile> {
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        print_auto_generated_file_comment(out);
+        print_auto_generated_version_comment(out);
+        self.print_package_and_imports(out, &["context"]);
+
+        let tables = iter_tables(module)
+            .map(|table| (table_field_name(&table.name), table_type_name(&table.name)))
+            .collect::<Vec<_>>();
+        let field_width = tables.iter().map(|(field, _)| field.len()).max().unwrap_or(0);
+
+        writeln!(
+            out,
+            "// RemoteTables holds the client cache of each table in the module."
+        );
+        writeln!(out, "type RemoteTables struct {{");
+        out.with_indent(|out| {
+            for (field, table_type) in &tables {
+                writeln!(out, "{field:field_width$} {table_type}");
+            }
+        });
+        writeln!(out, "}}");
+        writeln!(out);
+
+        writeln!(
+            out,
+            "// RemoteReducers has methods to call each reducer in the module, and to observe calls to it."
+        );
+        writeln!(out, "type RemoteReducers struct {{");
+        out.with_indent(|out| writeln!(out, "conn *{SDK}.Conn"));
+        writeln!(out, "}}");
+        writeln!(out);
+
+        writeln!(out, "// DbConnection is a connection to a database running the module.");
+        writeln!(out, "type DbConnection struct {{");
+        out.with_indent(|out| {
+            writeln!(out, "*{SDK}.Conn");
+            writeln!(out, "Db       RemoteTables");
+            writeln!(out, "Reducers RemoteReducers");
+        });
+        writeln!(out, "}}");
+        writeln!(out);
+
+        writeln!(
+            out,
+            "// Connect connects to the database described by opts, waiting until the server has identified the connection."
+        );
+        writeln!(
+            out,
+            "func Connect(ctx context.Context, opts {SDK}.ConnectOptions) (*DbConnection, error) {{"
+        );
+        out.with_indent(|out| {
+            writeln!(out, "db := RemoteTables{{");
+            out.with_indent(|out| {
+                for (field, table_type) in &tables {
+                    let key = format!("{field}:");
+                    writeln!(out, "{key:width$} new{table_type}(),", width = field_width + 1);
+                }
+            });
+            writeln!(out, "}}");
+            let handles = tables
+                .iter()
+                .map(|(field, _)| format!(", db.{field}.TableCache"))
+                .collect::<String>();
+            writeln!(out, "conn, err := {SDK}.Connect(ctx, opts{handles})");
+            writeln!(out, "if err != nil {{");
+            out.with_indent(|out| writeln!(out, "return nil, err"));
+            writeln!(out, "}}");
+            writeln!(
+                out,
+                "return &DbConnection{{Conn: conn, Db: db, Reducers: RemoteReducers{{conn: conn}}}}, nil"
+            );
+        });
+        writeln!(out, "}}");
+
+        vec![OutputFile {
+            filename: "db_connection.go".to_string(),
+            code: output.into_inner(),
+        }]
+    }
+}
+
+impl Go<'_> {
+    fn print_file_header(&self, out: &mut Indenter, std_imports: &[&str]) {
+        print_auto_generated_file_comment(out);
+        self.print_package_and_imports(out, std_imports);
+    }
+
+    fn print_package_and_imports(&self, out: &mut Indenter, std_imports: &[&str]) {
+        writeln!(out, "// Code generated by spacetimedb. DO NOT EDIT.");
+        writeln!(out);
+        writeln!(out, "package {}", self.package_name);
+        writeln!(out);
+        writeln!(out, "import (");
+        out.with_indent(|out| {
+            for import in std_imports {
+                writeln!(out, "\"{import}\"");
+            }
+            if !std_imports.is_empty() {
+                writeln!(out);
+            }
+            writeln!(out, "{SDK} \"{SDK_IMPORT_PATH}\"");
+        });
+        writeln!(out, ")");
+        writeln!(out);
+    }
+}
+
+/// Define a struct named `name` with the `elements` as fields,
+/// along with its `EncodeBSATN` and `DecodeBSATN` methods and a `Read{name}` function.
+fn define_struct(module: &ModuleDef, out: &mut Indenter, name: &str, elements: &[(Identifier, AlgebraicTypeUse)]) {
+    let fields = elements
+        .iter()
+        .map(|(ident, ty)| (field_name(ident), ty))
+        .collect::<Vec<_>>();
+    let width = fields.iter().map(|(field, _)| field.len()).max().unwrap_or(0);
+
+    if fields.is_empty() {
+        writeln!(out, "type {name} struct{{}}");
+    } else {
+        writeln!(out, "type {name} struct {{");
+        out.with_indent(|out| {
+            for (field, ty) in &fields {
+                writeln!(out, "{field:width$} {}", type_name(module, ty));
+            }
+        });
+        writeln!(out, "}}");
+    }
+
+    writeln!(out);
+    writeln!(out, "func (v {name}) EncodeBSATN(w *{SDK}.Writer) {{");
+    out.with_indent(|out| {
+        for (field, ty) in &fields {
+            writeln!(out, "{}", write_stmt(module, ty, &format!("v.{field}")));
+        }
+    });
+    writeln!(out, "}}");
+
+    writeln!(out);
+    writeln!(out, "func (v *{name}) DecodeBSATN(r *{SDK}.Reader) {{");
+    out.with_indent(|out| writeln!(out, "*v = Read{name}(r)"));
+    writeln!(out, "}}");
+
+    writeln!(out);
+    writeln!(out, "// Read{name} reads a {name} from r.");
+    writeln!(out, "func Read{name}(r *{SDK}.Reader) {name} {{");
+    out.with_indent(|out| {
+        if fields.is_empty() {
+            writeln!(out, "return {name}{{}}");
+            return;
+        }
+        // The elements of a composite literal are evaluated in order, so the fields are read in order.
+        writeln!(out, "return {name}{{");
+        out.with_indent(|out| {
+            for (field, ty) in &fields {
+                let key = format!("{field}:");
+                writeln!(out, "{key:width$} {},", read_expr(module, ty), width = width + 1);
+            }
+        });
+        writeln!(out, "}}");
+    });
+    writeln!(out, "}}");
+}
+
+/// Define a sum type named `name` as an interface implemented by one struct per variant,
+/// along with a `Read{name}` function.
+///
+/// Variants which carry data hold it in their `Value` field.
+fn define_sum(module: &ModuleDef, out: &mut Indenter, name: &str, variants: &[(Identifier, AlgebraicTypeUse)]) {
+    let variant_type = |ident: &Identifier| format!("{name}{}", ident.deref().to_case(Case::Pascal));
+
+    writeln!(out, "// {name} is the sum type `{name}` defined by the module.");
+    writeln!(out, "//");
+    writeln!(out, "// It is implemented by one struct per variant:");
+    for (ident, _) in variants {
+        writeln!(out, "// {}.", variant_type(ident));
+    }
+    writeln!(out, "type {name} interface {{");
+    out.with_indent(|out| {
+        writeln!(out, "{SDK}.Encoder");
+        writeln!(out, "is{name}()");
+    });
+    writeln!(out, "}}");
+
+    for (ident, ty) in variants {
+        writeln!(out);
+        writeln!(
+            out,
+            "// {} is the `{}` variant of {name}.",
+            variant_type(ident),
+            ident.deref()
+        );
+        match ty {
+            AlgebraicTypeUse::Unit => writeln!(out, "type {} struct{{}}", variant_type(ident)),
+            ty => {
+                writeln!(out, "type {} struct {{", variant_type(ident));
+                out.with_indent(|out| writeln!(out, "Value {}", type_name(module, ty)));
+                writeln!(out, "}}");
+            }
+        }
+    }
+
+    if !variants.is_empty() {
+        writeln!(out);
+        let signatures = variants
+            .iter()
+            .map(|(ident, _)| format!("func ({}) is{name}()", variant_type(ident)))
+            .collect::<Vec<_>>();
+        let width = signatures.iter().map(|signature| signature.len()).max().unwrap();
+        for signature in signatures {
+            writeln!(out, "{signature:width$} {{}}");
+        }
+    }
+
+    for (tag, (ident, ty)) in variants.iter().enumerate() {
+        writeln!(out);
+        match ty {
+            AlgebraicTypeUse::Unit => {
+                writeln!(out, "func ({}) EncodeBSATN(w *{SDK}.Writer) {{", variant_type(ident));
+                out.with_indent(|out| writeln!(out, "w.WriteTag({tag})"));
+            }
+            ty => {
+                writeln!(out, "func (v {}) EncodeBSATN(w *{SDK}.Writer) {{", variant_type(ident));
+                out.with_indent(|out| {
+                    writeln!(out, "w.WriteTag({tag})");
+                    writeln!(out, "{}", write_stmt(module, ty, "v.Value"));
+                });
+            }
+        }
+        writeln!(out, "}}");
+    }
+
+    writeln!(out);
+    writeln!(out, "// Read{name} reads a {name} from r.");
+    writeln!(out, "func Read{name}(r *{SDK}.Reader) {name} {{");
+    out.with_indent(|out| {
+        writeln!(out, "switch tag := r.ReadTag(); tag {{");
+        for (tag, (ident, ty)) in variants.iter().enumerate() {
+            writeln!(out, "case {tag}:");
+            out.with_indent(|out| match ty {
+                AlgebraicTypeUse::Unit => writeln!(out, "return {}{{}}", variant_type(ident)),
+                ty => writeln!(
+                    out,
+                    "return {}{{Value: {}}}",
+                    variant_type(ident),
+                    read_expr(module, ty)
+                ),
+            });
+        }
+        writeln!(out, "default:");
+        out.with_indent(|out| {
+            writeln!(out, "r.Fail(fmt.Errorf(\"unknown tag %d for sum type {name}\", tag))");
+            writeln!(out, "return nil");
+        });
+        writeln!(out, "}}");
+    });
+    writeln!(out, "}}");
+}
+
+/// Define a sum type named `name` whose variants carry no data as a `uint8` with one constant per variant.
+fn define_plain_enum(out: &mut Indenter, name: &str, variants: &[Identifier]) {
+    writeln!(out, "// {name} is the enum `{name}` defined by the module.");
+    writeln!(out, "type {name} uint8");
+    writeln!(out);
+
+    let variant_const = |ident: &Identifier| format!("{name}{}", ident.deref().to_case(Case::Pascal));
+    if !variants.is_empty() {
+        writeln!(out, "const (");
+        out.with_indent(|out| {
+            for (i, ident) in variants.iter().enumerate() {
+                if i == 0 {
+                    writeln!(out, "{} {name} = iota", variant_const(ident));
+                } else {
+                    writeln!(out, "{}", variant_const(ident));
+                }
+            }
+        });
+        writeln!(out, ")");
+        writeln!(out);
+    }
+
+    writeln!(out, "func (v {name}) EncodeBSATN(w *{SDK}.Writer) {{");
+    out.with_indent(|out| writeln!(out, "w.WriteTag(uint8(v))"));
+    writeln!(out, "}}");
+    writeln!(out);
+
+    writeln!(out, "func (v *{name}) DecodeBSATN(r *{SDK}.Reader) {{");
+    out.with_indent(|out| writeln!(out, "*v = Read{name}(r)"));
+    writeln!(out, "}}");
+    writeln!(out);
+
+    writeln!(out, "func (v {name}) String() string {{");
+    out.with_indent(|out| {
+        writeln!(out, "switch v {{");
+        for ident in variants {
+            writeln!(out, "case {}:", variant_const(ident));
+            out.with_indent(|out| writeln!(out, "return \"{}\"", ident.deref()));
+        }
+        writeln!(out, "default:");
+        out.with_indent(|out| writeln!(out, "return fmt.Sprintf(\"{name}(%d)\", uint8(v))"));
+        writeln!(out, "}}");
+    });
+    writeln!(out, "}}");
+    writeln!(out);
+
+    writeln!(out, "// Read{name} reads a {name} from r.");
+    writeln!(out, "func Read{name}(r *{SDK}.Reader) {name} {{");
+    out.with_indent(|out| {
+        writeln!(out, "tag := r.ReadTag()");
+        writeln!(out, "if tag >= {} {{", variants.len());
+        out.with_indent(|out| writeln!(out, "r.Fail(fmt.Errorf(\"unknown tag %d for enum {name}\", tag))"));
+        writeln!(out, "}}");
+        writeln!(out, "return {name}(tag)");
+    });
+    writeln!(out, "}}");
+}
+
+/// The Go type for `ty`.
+fn type_name(module: &ModuleDef, ty: &AlgebraicTypeUse) -> String {
+    match ty {
+        AlgebraicTypeUse::Unit => format!("{SDK}.Unit"),
+        AlgebraicTypeUse::Never => format!("{SDK}.Never"),
+        AlgebraicTypeUse::Identity => format!("{SDK}.Identity"),
+        AlgebraicTypeUse::ConnectionId => format!("{SDK}.ConnectionId"),
+        AlgebraicTypeUse::Timestamp => format!("{SDK}.Timestamp"),
+        AlgebraicTypeUse::TimeDuration => format!("{SDK}.TimeDuration"),
+        AlgebraicTypeUse::ScheduleAt => format!("{SDK}.ScheduleAt"),
+        AlgebraicTypeUse::Option(inner_ty) => format!("*{}", type_name(module, inner_ty)),
+        AlgebraicTypeUse::Primitive(prim) => match prim {
+            PrimitiveType::Bool => "bool".into(),
+            PrimitiveType::I8 => "int8".into(),
+            PrimitiveType::U8 => "uint8".into(),
+            PrimitiveType::I16 => "int16".into(),
+            PrimitiveType::U16 => "uint16".into(),
+            PrimitiveType::I32 => "int32".into(),
+            PrimitiveType::U32 => "uint32".into(),
+            PrimitiveType::I64 => "int64".into(),
+            PrimitiveType::U64 => "uint64".into(),
+            PrimitiveType::I128 => format!("{SDK}.I128"),
+            PrimitiveType::U128 => format!("{SDK}.U128"),
+            PrimitiveType::I256 => format!("{SDK}.I256"),
+            PrimitiveType::U256 => format!("{SDK}.U256"),
+            PrimitiveType::F32 => "float32".into(),
+            PrimitiveType::F64 => "float64".into(),
+        },
+        AlgebraicTypeUse::String => "string".into(),
+        AlgebraicTypeUse::Array(elem_ty) => format!("[]{}", type_name(module, elem_ty)),
+        AlgebraicTypeUse::Ref(r) => type_ref_name(module, *r),
+    }
+}
+
+/// The name of the `Reader` and `Writer` methods for `ty`, if it has them.
+fn codec_method_suffix(ty: &AlgebraicTypeUse) -> Option<&'static str> {
+    Some(match ty {
+        AlgebraicTypeUse::Unit => "Unit",
+        AlgebraicTypeUse::Never => "Never",
+        AlgebraicTypeUse::Identity => "Identity",
+        AlgebraicTypeUse::ConnectionId => "ConnectionId",
+        AlgebraicTypeUse::Timestamp => "Timestamp",
+        AlgebraicTypeUse::TimeDuration => "TimeDuration",
+        AlgebraicTypeUse::ScheduleAt => "ScheduleAt",
+        AlgebraicTypeUse::String => "String",
+        AlgebraicTypeUse::Array(elem_ty) if matches!(**elem_ty, AlgebraicTypeUse::Primitive(PrimitiveType::U8)) => {
+            "Bytes"
+        }
+        AlgebraicTypeUse::Primitive(prim) => match prim {
+            PrimitiveType::Bool => "Bool",
+            PrimitiveType::I8 => "I8",
+            PrimitiveType::U8 => "U8",
+            PrimitiveType::I16 => "I16",
+            PrimitiveType::U16 => "U16",
+            PrimitiveType::I32 => "I32",
+            PrimitiveType::U32 => "U32",
+            PrimitiveType::I64 => "I64",
+            PrimitiveType::U64 => "U64",
+            PrimitiveType::I128 => "I128",
+            PrimitiveType::U128 => "U128",
+            PrimitiveType::I256 => "I256",
+            PrimitiveType::U256 => "U256",
+            PrimitiveType::F32 => "F32",
+            PrimitiveType::F64 => "F64",
+        },
+        AlgebraicTypeUse::Option(_) | AlgebraicTypeUse::Array(_) | AlgebraicTypeUse::Ref(_) => return None,
+    })
+}
+
+/// An expression which reads a `ty` from the `*spacetimedb.Reader` named `r`.
+fn read_expr(module: &ModuleDef, ty: &AlgebraicTypeUse) -> String {
+    if let Some(suffix) = codec_method_suffix(ty) {
+        return format!("r.Read{suffix}()");
+    }
+    match ty {
+        AlgebraicTypeUse::Option(inner_ty) => format!("{SDK}.ReadOption(r, {})", reader_fn(module, inner_ty)),
+        AlgebraicTypeUse::Array(elem_ty) => format!("{SDK}.ReadArray(r, {})", reader_fn(module, elem_ty)),
+        AlgebraicTypeUse::Ref(r) => format!("Read{}(r)", type_ref_name(module, *r)),
+        _ => unreachable!("{ty:?} has a codec method"),
+    }
+}
+
+/// A function of type `func(r *spacetimedb.Reader) T` which reads a `ty`.
+fn reader_fn(module: &ModuleDef, ty: &AlgebraicTypeUse) -> String {
+    if let Some(suffix) = codec_method_suffix(ty) {
+        return format!("(*{SDK}.Reader).Read{suffix}");
+    }
+    match ty {
+        AlgebraicTypeUse::Ref(r) => format!("Read{}", type_ref_name(module, *r)),
+        ty => format!(
+            "func(r *{SDK}.Reader) {} {{ return {} }}",
+            type_name(module, ty),
+            read_expr(module, ty)
+        ),
+    }
+}
+
+/// A statement which writes `value`, a `ty`, to the `*spacetimedb.Writer` named `w`.
+fn write_stmt(module: &ModuleDef, ty: &AlgebraicTypeUse, value: &str) -> String {
+    if let Some(suffix) = codec_method_suffix(ty) {
+        return format!("w.Write{suffix}({value})");
+    }
+    match ty {
+        AlgebraicTypeUse::Option(inner_ty) => {
+            format!("{SDK}.WriteOption(w, {value}, {})", writer_fn(module, inner_ty))
+        }
+        AlgebraicTypeUse::Array(elem_ty) => format!("{SDK}.WriteArray(w, {value}, {})", writer_fn(module, elem_ty)),
+        AlgebraicTypeUse::Ref(_) => format!("{value}.EncodeBSATN(w)"),
+        _ => unreachable!("{ty:?} has a codec method"),
+    }
+}
+
+/// A function of type `func(w *spacetimedb.Writer, v T)` which writes a `ty`.
+fn writer_fn(module: &ModuleDef, ty: &AlgebraicTypeUse) -> String {
+    if let Some(suffix) = codec_method_suffix(ty) {
+        return format!("(*{SDK}.Writer).Write{suffix}");
+    }
+    format!(
+        "func(w *{SDK}.Writer, v {}) {{ {} }}",
+        type_name(module, ty),
+        write_stmt(module, ty, "v")
+    )
+}
+
+/// Can values of `ty` be compared with `==`, with the same meaning as equality in the module?
+///
+/// Go compares pointers and interfaces by identity, and can't compare slices at all,
+/// so types containing them are excluded.
+fn is_comparable(module: &ModuleDef, ty: &AlgebraicTypeUse) -> bool {
+    match ty {
+        AlgebraicTypeUse::Primitive(_)
+        | AlgebraicTypeUse::String
+        | AlgebraicTypeUse::Identity
+        | AlgebraicTypeUse::ConnectionId
+        | AlgebraicTypeUse::Timestamp
+        | AlgebraicTypeUse::TimeDuration
+        | AlgebraicTypeUse::Unit => true,
+        AlgebraicTypeUse::Ref(r) => matches!(module.typespace_for_generate()[*r], AlgebraicTypeDef::PlainEnum(_)),
+        _ => false,
+    }
+}
+
+fn type_file_name(type_name: &ScopedTypeName) -> String {
+    collect_case(Case::Snake, type_name.name_segments()) + "_type.go"
+}
+
+fn table_type_name(table_name: &Identifier) -> String {
+    table_field_name(table_name) + "Table"
+}
+
+/// The name of the table's field in `RemoteTables`.
+fn table_field_name(table_name: &Identifier) -> String {
+    table_name.deref().to_case(Case::Pascal)
+}
+
+/// The exported name of a struct field.
+fn field_name(ident: &Identifier) -> String {
+    ident.deref().to_case(Case::Pascal)
+}
+
+/// The name of a function parameter, escaped if it collides with a keyword or a name generated code uses.
+fn local_name(ident: &Identifier) -> String {
+    let name = ident.deref().to_case(Case::Camel);
+    if GO_KEYWORDS.contains(&&*name) || RESERVED_LOCALS.contains(&&*name) {
+        name + "_"
+    } else {
+        name
+    }
+}
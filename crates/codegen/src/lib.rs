@@ -2,12 +2,16 @@ use spacetimedb_schema::def::{ModuleDef, ReducerDef, TableDef, TypeDef};
 
 mod code_indenter;
 pub mod csharp;
+pub mod go;
+pub mod python;
 pub mod rust;
 pub mod typescript;
 pub mod unrealcpp;
 mod util;
 
 pub use self::csharp::Csharp;
+pub use self::go::Go;
+pub use self::python::Python;
 pub use self::rust::Rust;
 pub use self::typescript::TypeScript;
 pub use self::unrealcpp::UnrealCpp;
@@ -0,0 +1,1208 @@
+//! Python client bindings.
+//!
+//! There is no Python client SDK, so the generated package is self-contained:
+//! a small BSATN codec and the SpacetimeDB special types are emitted as `_bsatn.py`,
+//! alongside a dataclass (or enum) per type, a metadata class per table and a call stub per reducer.
+//! The bindings do not open connections themselves; they encode and decode the values
+//! exchanged with a database, for use with whatever WebSocket or HTTP client the caller prefers.
+
+use crate::util::{is_reducer_invokable, iter_reducers, iter_tables, iter_types, print_lines};
+use crate::OutputFile;
+
+use super::util::{collect_case, type_ref_name};
+
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+use convert_case::{Case, Casing};
+use spacetimedb_lib::sats::layout::PrimitiveType;
+use spacetimedb_lib::sats::AlgebraicTypeRef;
+use spacetimedb_lib::version;
+use spacetimedb_schema::def::{ModuleDef, ReducerDef, ScopedTypeName, TableDef, TypeDef};
+use spacetimedb_schema::identifier::Identifier;
+use spacetimedb_schema::schema::{Schema, TableSchema};
+use spacetimedb_schema::type_for_generate::{AlgebraicTypeDef, AlgebraicTypeUse};
+
+use super::code_indenter::{CodeIndenter, Indenter};
+use super::Lang;
+
+const INDENT: &str = "    ";
+
+/// The first line of every file generated by [`Python`].
+///
+/// Python comments start with `#`, so generated Python files can't use [`crate::AUTO_GENERATED_PREFIX`].
+pub const AUTO_GENERATED_PREFIX: &str = "# THIS FILE IS AUTOMATICALLY GENERATED BY SPACETIMEDB";
+
+const AUTO_GENERATED_FILE_COMMENT: &[&str] = &[
+    "# THIS FILE IS AUTOMATICALLY GENERATED BY SPACETIMEDB. EDITS TO THIS FILE",
+    "# WILL NOT BE SAVED. MODIFY TABLES IN YOUR MODULE SOURCE CODE INSTEAD.",
+    "",
+];
+
+/// The name of the runtime module, relative to the generated package.
+const RUNTIME_MODULE: &str = "_bsatn";
+
+pub struct Python;
+
+impl Lang for Python {
+    fn generate_type_files(&self, module: &ModuleDef, typ: &TypeDef) -> Vec<OutputFile> {
+        let type_name = collect_case(Case::Pascal, typ.name.name_segments());
+
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        print_file_header(out, false);
+
+        match &module.typespace_for_generate()[typ.ty] {
+            AlgebraicTypeDef::Product(product) => {
+                let std_imports = StdImports {
+                    dataclass: true,
+                    ..<_>::default()
+                };
+                print_imports(module, out, &product.elements, Some(typ.ty), std_imports);
+                define_dataclass(module, out, &type_name, &product.elements, Some(typ.ty));
+            }
+            AlgebraicTypeDef::Sum(sum) => {
+                let mut std_imports = StdImports {
+                    dataclass: !sum.variants.is_empty(),
+                    ..<_>::default()
+                };
+                std_imports
+                    .typing
+                    .insert(if sum.variants.is_empty() { "NoReturn" } else { "Union" });
+                print_imports(module, out, &sum.variants, Some(typ.ty), std_imports);
+                define_sum(module, out, &type_name, &sum.variants, Some(typ.ty));
+            }
+            AlgebraicTypeDef::PlainEnum(plain_enum) => {
+                let std_imports = StdImports {
+                    enum_: true,
+                    ..<_>::default()
+                };
+                print_imports(module, out, &[], None, std_imports);
+                define_plain_enum(out, &type_name, &plain_enum.variants);
+            }
+        }
+
+        vec![OutputFile {
+            filename: type_module_name(&typ.name) + ".py",
+            code: output.into_inner(),
+        }]
+    }
+
+    fn generate_table_file(&self, module: &ModuleDef, table: &TableDef) -> OutputFile {
+        let schema = TableSchema::from_module_def(module, table, (), 0.into())
+            .validated()
+            .expect("Failed to generate table due to validation errors");
+
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        print_file_header(out, false);
+
+        let type_ref = table.product_type_ref;
+        let row_type = format!(
+            "{}.{}",
+            type_ref_module_name(module, type_ref),
+            type_ref_name(module, type_ref)
+        );
+        let row_type_module = type_ref_module_name(module, type_ref);
+        let product_def = module.typespace_for_generate()[type_ref].as_product().unwrap();
+
+        writeln!(out, "from typing import Optional");
+        writeln!(out);
+        writeln!(out, "from . import {RUNTIME_MODULE}");
+        writeln!(out, "from . import {row_type_module}");
+        writeln!(out);
+        writeln!(out);
+
+        let table_name = table.name.deref();
+        writeln!(out, "class {}:", table_class_name(&table.name));
+        out.with_indent(|out| {
+            writeln!(
+                out,
+                "\"\"\"Metadata and row codec for the table `{table_name}`, whose rows are `{row_type}`s.\"\"\""
+            );
+            writeln!(out);
+            writeln!(out, "NAME = \"{table_name}\"");
+            match schema.pk() {
+                Some(pk) => {
+                    let (pk_field, _) = &product_def.elements[pk.col_pos.idx()];
+                    writeln!(out, "PRIMARY_KEY: Optional[str] = \"{}\"", field_name(pk_field));
+                }
+                None => writeln!(out, "PRIMARY_KEY: Optional[str] = None"),
+            }
+            writeln!(out);
+            writeln!(out, "@staticmethod");
+            writeln!(out, "def decode_row(data: bytes) -> {row_type}:");
+            out.with_indent(|out| {
+                writeln!(out, "\"\"\"Decode the BSATN of a single row of this table.\"\"\"");
+                writeln!(
+                    out,
+                    "return {RUNTIME_MODULE}.decode({row_type_module}.read_bsatn, data)"
+                );
+            });
+            writeln!(out);
+            writeln!(out, "@staticmethod");
+            writeln!(out, "def encode_row(row: {row_type}) -> bytes:");
+            out.with_indent(|out| {
+                writeln!(out, "\"\"\"Encode `row` as BSATN.\"\"\"");
+                writeln!(
+                    out,
+                    "return {RUNTIME_MODULE}.encode({row_type_module}.write_bsatn, row)"
+                );
+            });
+        });
+
+        OutputFile {
+            filename: table_module_name(&table.name) + ".py",
+            code: output.into_inner(),
+        }
+    }
+
+    fn generate_reducer_file(&self, module: &ModuleDef, reducer: &ReducerDef) -> OutputFile {
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        print_file_header(out, false);
+
+        // Reducers which declare return types also need their types imported.
+        let return_types = reducer.has_typed_return().then(|| {
+            [
+                (reducer.name.clone(), reducer.ok_return_type_for_generate.clone()),
+                (reducer.name.clone(), reducer.err_return_type_for_generate.clone()),
+            ]
+        });
+        let import_roots = reducer
+            .params_for_generate
+            .elements
+            .iter()
+            .cloned()
+            .chain(return_types.into_iter().flatten())
+            .collect::<Vec<_>>();
+        let std_imports = StdImports {
+            dataclass: true,
+            ..<_>::default()
+        };
+        print_imports(module, out, &import_roots, None, std_imports);
+
+        let args_type = reducer_args_type_name(&reducer.name);
+        let params = &reducer.params_for_generate.elements;
+        define_dataclass(module, out, &args_type, params, None);
+
+        if is_reducer_invokable(reducer) {
+            let reducer_name = reducer.name.deref();
+            writeln!(out);
+            writeln!(out);
+            write!(out, "def {}(", reducer_function_name(reducer));
+            for (i, (ident, ty)) in params.iter().enumerate() {
+                if i != 0 {
+                    write!(out, ", ");
+                }
+                write!(out, "{}: {}", field_name(ident), type_name(module, ty, None));
+            }
+            writeln!(out, ") -> {RUNTIME_MODULE}.CallReducer:");
+            out.with_indent(|out| {
+                writeln!(out, "\"\"\"Build a request to call the reducer `{reducer_name}`.");
+                writeln!(out);
+                writeln!(
+                    out,
+                    "Encode the result with `CallReducer.encode` and send it over the database's WebSocket."
+                );
+                writeln!(out, "\"\"\"");
+                let args = params
+                    .iter()
+                    .map(|(ident, _)| format!("{0}={0}", field_name(ident)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(out, "args = {RUNTIME_MODULE}.encode(write_bsatn, {args_type}({args}))");
+                writeln!(
+                    out,
+                    "return {RUNTIME_MODULE}.CallReducer(reducer=\"{reducer_name}\", args=args)"
+                );
+            });
+        }
+
+        if reducer.has_typed_return() {
+            for (kind, ty, doc) in [
+                (
+                    "ok",
+                    &reducer.ok_return_type_for_generate,
+                    "Read the value returned by the reducer, if it committed.",
+                ),
+                (
+                    "err",
+                    &reducer.err_return_type_for_generate,
+                    "Read the error value the reducer failed with.",
+                ),
+            ] {
+                writeln!(out);
+                writeln!(out);
+                writeln!(
+                    out,
+                    "def read_{kind}_bsatn(r: {RUNTIME_MODULE}.BsatnReader) -> {}:",
+                    type_name(module, ty, None)
+                );
+                out.with_indent(|out| {
+                    writeln!(out, "\"\"\"{doc}\"\"\"");
+                    writeln!(out, "return {}", read_expr(module, ty, None));
+                });
+            }
+        }
+
+        OutputFile {
+            filename: reducer_module_name(&reducer.name) + ".py",
+            code: output.into_inner(),
+        }
+    }
+
+    fn generate_global_files(&self, module: &ModuleDef) -> Vec<OutputFile> {
+        let mut output = CodeIndenter::new(String::new(), INDENT);
+        let out = &mut output;
+
+        print_file_header(out, true);
+
+        writeln!(out, "from typing import Any, Dict, Type");
+        writeln!(out);
+        writeln!(out, "from . import {RUNTIME_MODULE}");
+        writeln!(
+            out,
+            "from .{RUNTIME_MODULE} import (
+    BsatnError,
+    BsatnReader,
+    BsatnWriter,
+    CallReducer,
+    ConnectionId,
+    Identity,
+    ScheduleAt,
+    ScheduleAtInterval,
+    ScheduleAtTime,
+    TimeDuration,
+    Timestamp,
+    decode,
+    encode,
+    read_row_list,
+)"
+        );
+
+        writeln!(out);
+        writeln!(out, "# Import and reexport all reducer arg types and call stubs");
+        for reducer in iter_reducers(module) {
+            let reducer_module_name = reducer_module_name(&reducer.name);
+            let args_type = reducer_args_type_name(&reducer.name);
+            if is_reducer_invokable(reducer) {
+                let function_name = reducer_function_name(reducer);
+                writeln!(out, "from .{reducer_module_name} import {args_type}, {function_name}");
+            } else {
+                writeln!(out, "from .{reducer_module_name} import {args_type}");
+            }
+        }
+
+        writeln!(out);
+        writeln!(out, "# Import and reexport all table classes");
+        for table in iter_tables(module) {
+            writeln!(
+                out,
+                "from .{} import {}",
+                table_module_name(&table.name),
+                table_class_name(&table.name)
+            );
+        }
+
+        writeln!(out);
+        writeln!(out, "# Import and reexport all types");
+        for ty in iter_types(module) {
+            writeln!(
+                out,
+                "from .{} import {}",
+                type_module_name(&ty.name),
+                collect_case(Case::Pascal, ty.name.name_segments())
+            );
+        }
+
+        writeln!(out);
+        writeln!(out, "# The class for each table, keyed by table name.");
+        writeln!(out, "TABLES: Dict[str, Type[Any]] = {{");
+        out.with_indent(|out| {
+            for table in iter_tables(module) {
+                writeln!(out, "\"{}\": {},", table.name.deref(), table_class_name(&table.name));
+            }
+        });
+        writeln!(out, "}}");
+
+        writeln!(out);
+        writeln!(out);
+        writeln!(out, "def decode_row(table_name: str, data: bytes) -> Any:");
+        out.with_indent(|out| {
+            writeln!(
+                out,
+                "\"\"\"Decode the BSATN of a single row of the table named `table_name`.\"\"\""
+            );
+            writeln!(out, "return TABLES[table_name].decode_row(data)");
+        });
+
+        vec![
+            OutputFile {
+                filename: "__init__.py".to_string(),
+                code: output.into_inner(),
+            },
+            generate_runtime_file(),
+        ]
+    }
+}
+
+fn generate_runtime_file() -> OutputFile {
+    let mut output = CodeIndenter::new(String::new(), INDENT);
+    let out = &mut output;
+
+    print_file_header(out, false);
+    write!(out, "{RUNTIME}");
+
+    OutputFile {
+        filename: format!("{RUNTIME_MODULE}.py"),
+        code: output.into_inner(),
+    }
+}
+
+fn print_file_header(output: &mut Indenter, with_version: bool) {
+    print_lines(output, AUTO_GENERATED_FILE_COMMENT);
+    if with_version {
+        writeln!(
+            output,
+            "# This was generated using spacetimedb cli version {} (commit {}).",
+            version::spacetimedb_lib_version(),
+            version::GIT_HASH
+        );
+        writeln!(output);
+    }
+    // Annotations are only evaluated lazily, so that types can refer to each other
+    // regardless of the order in which their modules are imported.
+    writeln!(output, "from __future__ import annotations");
+    writeln!(output);
+}
+
+/// The standard library names used by a generated module.
+#[derive(Default)]
+struct StdImports {
+    /// Does the module define an `enum.IntEnum`?
+    enum_: bool,
+    /// Does the module define a `@dataclass`?
+    dataclass: bool,
+    /// The names used from `typing`.
+    typing: BTreeSet<&'static str>,
+}
+
+impl StdImports {
+    /// Add the names from `typing` which [`type_name`] uses to annotate `ty`.
+    fn add_typing_names(&mut self, ty: &AlgebraicTypeUse) {
+        match ty {
+            AlgebraicTypeUse::Never => {
+                self.typing.insert("NoReturn");
+            }
+            AlgebraicTypeUse::Option(inner_ty) => {
+                self.typing.insert("Optional");
+                self.add_typing_names(inner_ty);
+            }
+            AlgebraicTypeUse::Array(elem_ty) => {
+                if !matches!(&**elem_ty, AlgebraicTypeUse::Primitive(PrimitiveType::U8)) {
+                    self.typing.insert("List");
+                    self.add_typing_names(elem_ty);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Print the imports needed by a module which defines a type with the `roots` as fields or variants.
+///
+/// `std_imports` are the standard library names used by the definition itself,
+/// to which those used to annotate the `roots` are added.
+/// Only the names the module uses are imported.
+///
+/// `this_type` is skipped, so that a recursive type doesn't import its own module.
+/// Type modules are imported as modules rather than importing their types by name,
+/// so that mutually recursive types can import each other.
+fn print_imports(
+    module: &ModuleDef,
+    out: &mut Indenter,
+    roots: &[(Identifier, AlgebraicTypeUse)],
+    this_type: Option<AlgebraicTypeRef>,
+    mut std_imports: StdImports,
+) {
+    let mut imports = BTreeSet::new();
+    for (_, ty) in roots {
+        ty.for_each_ref(|r| {
+            imports.insert(r);
+        });
+        std_imports.add_typing_names(ty);
+    }
+    if let Some(this_type) = this_type {
+        imports.remove(&this_type);
+    }
+
+    if std_imports.enum_ {
+        writeln!(out, "import enum");
+    }
+    if std_imports.dataclass {
+        writeln!(out, "from dataclasses import dataclass");
+    }
+    if !std_imports.typing.is_empty() {
+        let names = std_imports.typing.into_iter().collect::<Vec<_>>().join(", ");
+        writeln!(out, "from typing import {names}");
+    }
+    if std_imports.enum_ || std_imports.dataclass || !std_imports.typing.is_empty() {
+        writeln!(out);
+    }
+    writeln!(out, "from . import {RUNTIME_MODULE}");
+    for typeref in imports {
+        writeln!(out, "from . import {}", type_ref_module_name(module, typeref));
+    }
+    writeln!(out);
+    writeln!(out);
+}
+
+/// Define a dataclass named `name` with the `elements` as fields,
+/// along with the module-level `read_bsatn` and `write_bsatn` functions which encode it.
+fn define_dataclass(
+    module: &ModuleDef,
+    out: &mut Indenter,
+    name: &str,
+    elements: &[(Identifier, AlgebraicTypeUse)],
+    this_type: Option<AlgebraicTypeRef>,
+) {
+    write_dataclass(module, out, name, elements, this_type);
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(out, "def read_bsatn(r: {RUNTIME_MODULE}.BsatnReader) -> {name}:");
+    out.with_indent(|out| {
+        // Keyword arguments are evaluated left to right, so the fields are read in order.
+        writeln!(out, "return {name}({})", read_fields(module, elements, this_type));
+    });
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(
+        out,
+        "def write_bsatn(w: {RUNTIME_MODULE}.BsatnWriter, value: {name}) -> None:"
+    );
+    out.with_indent(|out| {
+        for (ident, ty) in elements {
+            let value = format!("value.{}", field_name(ident));
+            writeln!(out, "{}", write_expr(module, ty, &value, this_type));
+        }
+        if elements.is_empty() {
+            writeln!(out, "pass");
+        }
+    });
+}
+
+/// Define a sum type named `name` as a `Union` of one dataclass per variant,
+/// along with the module-level `read_bsatn` and `write_bsatn` functions which encode it.
+///
+/// Variants which carry data hold it in their `value` field.
+fn define_sum(
+    module: &ModuleDef,
+    out: &mut Indenter,
+    name: &str,
+    variants: &[(Identifier, AlgebraicTypeUse)],
+    this_type: Option<AlgebraicTypeRef>,
+) {
+    let variant_class = |ident: &Identifier| format!("{name}{}", ident.deref().to_case(Case::Pascal));
+    let variant_fields = |ty: &AlgebraicTypeUse| match ty {
+        AlgebraicTypeUse::Unit => vec![],
+        ty => vec![(Identifier::new("value".into()).unwrap(), ty.clone())],
+    };
+
+    for (ident, ty) in variants {
+        write_dataclass(module, out, &variant_class(ident), &variant_fields(ty), this_type);
+        writeln!(out);
+        writeln!(out);
+    }
+
+    if variants.is_empty() {
+        writeln!(out, "{name} = NoReturn");
+    } else {
+        let names = variants
+            .iter()
+            .map(|(ident, _)| variant_class(ident))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(out, "{name} = Union[{names}]");
+    }
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(out, "def read_bsatn(r: {RUNTIME_MODULE}.BsatnReader) -> {name}:");
+    out.with_indent(|out| {
+        writeln!(out, "tag = r.read_u8()");
+        for (tag, (ident, ty)) in variants.iter().enumerate() {
+            writeln!(out, "if tag == {tag}:");
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "return {}({})",
+                    variant_class(ident),
+                    read_fields(module, &variant_fields(ty), this_type)
+                );
+            });
+        }
+        writeln!(
+            out,
+            "raise {RUNTIME_MODULE}.BsatnError(f\"unknown tag {{tag}} for sum type {name}\")"
+        );
+    });
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(
+        out,
+        "def write_bsatn(w: {RUNTIME_MODULE}.BsatnWriter, value: {name}) -> None:"
+    );
+    out.with_indent(|out| {
+        for (tag, (ident, ty)) in variants.iter().enumerate() {
+            let keyword = if tag == 0 { "if" } else { "elif" };
+            writeln!(out, "{keyword} isinstance(value, {}):", variant_class(ident));
+            out.with_indent(|out| {
+                writeln!(out, "w.write_u8({tag})");
+                if !matches!(ty, AlgebraicTypeUse::Unit) {
+                    writeln!(out, "{}", write_expr(module, ty, "value.value", this_type));
+                }
+            });
+        }
+        if variants.is_empty() {
+            writeln!(
+                out,
+                "raise TypeError(f\"expected a {name}, got {{type(value).__name__}}\")"
+            );
+        } else {
+            writeln!(out, "else:");
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "raise TypeError(f\"expected a {name}, got {{type(value).__name__}}\")"
+                );
+            });
+        }
+    });
+}
+
+/// Define a sum type named `name` whose variants carry no data as an `IntEnum`,
+/// along with the module-level `read_bsatn` and `write_bsatn` functions which encode it.
+fn define_plain_enum(out: &mut Indenter, name: &str, variants: &[Identifier]) {
+    writeln!(out, "class {name}(enum.IntEnum):");
+    out.with_indent(|out| {
+        for (tag, ident) in variants.iter().enumerate() {
+            writeln!(out, "{} = {tag}", python_ident(ident.deref().to_string()));
+        }
+        if variants.is_empty() {
+            writeln!(out, "pass");
+        }
+    });
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(out, "def read_bsatn(r: {RUNTIME_MODULE}.BsatnReader) -> {name}:");
+    out.with_indent(|out| {
+        writeln!(out, "tag = r.read_u8()");
+        writeln!(out, "try:");
+        out.with_indent(|out| writeln!(out, "return {name}(tag)"));
+        writeln!(out, "except ValueError:");
+        out.with_indent(|out| {
+            writeln!(
+                out,
+                "raise {RUNTIME_MODULE}.BsatnError(f\"unknown tag {{tag}} for enum {name}\") from None"
+            );
+        });
+    });
+
+    writeln!(out);
+    writeln!(out);
+    writeln!(
+        out,
+        "def write_bsatn(w: {RUNTIME_MODULE}.BsatnWriter, value: {name}) -> None:"
+    );
+    out.with_indent(|out| writeln!(out, "w.write_u8(int(value))"));
+}
+
+fn write_dataclass(
+    module: &ModuleDef,
+    out: &mut Indenter,
+    name: &str,
+    elements: &[(Identifier, AlgebraicTypeUse)],
+    this_type: Option<AlgebraicTypeRef>,
+) {
+    writeln!(out, "@dataclass");
+    writeln!(out, "class {name}:");
+    out.with_indent(|out| {
+        for (ident, ty) in elements {
+            writeln!(out, "{}: {}", field_name(ident), type_name(module, ty, this_type));
+        }
+        if elements.is_empty() {
+            writeln!(out, "pass");
+        }
+    });
+}
+
+/// The keyword arguments which read each of the `elements` from the reader `r`, in order.
+fn read_fields(
+    module: &ModuleDef,
+    elements: &[(Identifier, AlgebraicTypeUse)],
+    this_type: Option<AlgebraicTypeRef>,
+) -> String {
+    elements
+        .iter()
+        .map(|(ident, ty)| format!("{}={}", field_name(ident), read_expr(module, ty, this_type)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The Python type annotation for `ty`.
+///
+/// References to `this_type` are written unqualified,
+/// as they occur within the module which defines it.
+fn type_name(module: &ModuleDef, ty: &AlgebraicTypeUse, this_type: Option<AlgebraicTypeRef>) -> String {
+    match ty {
+        AlgebraicTypeUse::Unit => "None".into(),
+        AlgebraicTypeUse::Never => "NoReturn".into(),
+        AlgebraicTypeUse::Identity => format!("{RUNTIME_MODULE}.Identity"),
+        AlgebraicTypeUse::ConnectionId => format!("{RUNTIME_MODULE}.ConnectionId"),
+        AlgebraicTypeUse::Timestamp => format!("{RUNTIME_MODULE}.Timestamp"),
+        AlgebraicTypeUse::TimeDuration => format!("{RUNTIME_MODULE}.TimeDuration"),
+        AlgebraicTypeUse::ScheduleAt => format!("{RUNTIME_MODULE}.ScheduleAt"),
+        AlgebraicTypeUse::Option(inner_ty) => format!("Optional[{}]", type_name(module, inner_ty, this_type)),
+        AlgebraicTypeUse::Primitive(prim) => match prim {
+            PrimitiveType::Bool => "bool",
+            PrimitiveType::I8
+            | PrimitiveType::U8
+            | PrimitiveType::I16
+            | PrimitiveType::U16
+            | PrimitiveType::I32
+            | PrimitiveType::U32
+            | PrimitiveType::I64
+            | PrimitiveType::U64
+            | PrimitiveType::I128
+            | PrimitiveType::U128
+            | PrimitiveType::I256
+            | PrimitiveType::U256 => "int",
+            PrimitiveType::F32 | PrimitiveType::F64 => "float",
+        }
+        .into(),
+        AlgebraicTypeUse::String => "str".into(),
+        AlgebraicTypeUse::Array(elem_ty) => match &**elem_ty {
+            AlgebraicTypeUse::Primitive(PrimitiveType::U8) => "bytes".into(),
+            elem_ty => format!("List[{}]", type_name(module, elem_ty, this_type)),
+        },
+        AlgebraicTypeUse::Ref(r) if Some(*r) == this_type => type_ref_name(module, *r),
+        AlgebraicTypeUse::Ref(r) => format!("{}.{}", type_ref_module_name(module, *r), type_ref_name(module, *r)),
+    }
+}
+
+fn primitive_codec_suffix(prim: &PrimitiveType) -> &'static str {
+    match prim {
+        PrimitiveType::Bool => "bool",
+        PrimitiveType::I8 => "i8",
+        PrimitiveType::U8 => "u8",
+        PrimitiveType::I16 => "i16",
+        PrimitiveType::U16 => "u16",
+        PrimitiveType::I32 => "i32",
+        PrimitiveType::U32 => "u32",
+        PrimitiveType::I64 => "i64",
+        PrimitiveType::U64 => "u64",
+        PrimitiveType::I128 => "i128",
+        PrimitiveType::U128 => "u128",
+        PrimitiveType::I256 => "i256",
+        PrimitiveType::U256 => "u256",
+        PrimitiveType::F32 => "f32",
+        PrimitiveType::F64 => "f64",
+    }
+}
+
+/// A Python expression which reads a `ty` from the `BsatnReader` named `r`.
+fn read_expr(module: &ModuleDef, ty: &AlgebraicTypeUse, this_type: Option<AlgebraicTypeRef>) -> String {
+    match ty {
+        AlgebraicTypeUse::Unit => "r.read_unit()".into(),
+        AlgebraicTypeUse::Never => "r.read_never()".into(),
+        AlgebraicTypeUse::Identity => "r.read_identity()".into(),
+        AlgebraicTypeUse::ConnectionId => "r.read_connection_id()".into(),
+        AlgebraicTypeUse::Timestamp => "r.read_timestamp()".into(),
+        AlgebraicTypeUse::TimeDuration => "r.read_time_duration()".into(),
+        AlgebraicTypeUse::ScheduleAt => "r.read_schedule_at()".into(),
+        AlgebraicTypeUse::Option(inner_ty) => {
+            format!("r.read_option(lambda r: {})", read_expr(module, inner_ty, this_type))
+        }
+        AlgebraicTypeUse::Primitive(prim) => format!("r.read_{}()", primitive_codec_suffix(prim)),
+        AlgebraicTypeUse::String => "r.read_string()".into(),
+        AlgebraicTypeUse::Array(elem_ty) => match &**elem_ty {
+            AlgebraicTypeUse::Primitive(PrimitiveType::U8) => "r.read_bytes()".into(),
+            elem_ty => format!("r.read_array(lambda r: {})", read_expr(module, elem_ty, this_type)),
+        },
+        AlgebraicTypeUse::Ref(r) if Some(*r) == this_type => "read_bsatn(r)".into(),
+        AlgebraicTypeUse::Ref(r) => format!("{}.read_bsatn(r)", type_ref_module_name(module, *r)),
+    }
+}
+
+/// A Python expression which writes `value`, a `ty`, to the `BsatnWriter` named `w`.
+fn write_expr(module: &ModuleDef, ty: &AlgebraicTypeUse, value: &str, this_type: Option<AlgebraicTypeRef>) -> String {
+    match ty {
+        AlgebraicTypeUse::Unit => format!("w.write_unit({value})"),
+        AlgebraicTypeUse::Never => format!("w.write_never({value})"),
+        AlgebraicTypeUse::Identity => format!("w.write_identity({value})"),
+        AlgebraicTypeUse::ConnectionId => format!("w.write_connection_id({value})"),
+        AlgebraicTypeUse::Timestamp => format!("w.write_timestamp({value})"),
+        AlgebraicTypeUse::TimeDuration => format!("w.write_time_duration({value})"),
+        AlgebraicTypeUse::ScheduleAt => format!("w.write_schedule_at({value})"),
+        AlgebraicTypeUse::Option(inner_ty) => format!(
+            "w.write_option({value}, lambda w, v: {})",
+            write_expr(module, inner_ty, "v", this_type)
+        ),
+        AlgebraicTypeUse::Primitive(prim) => format!("w.write_{}({value})", primitive_codec_suffix(prim)),
+        AlgebraicTypeUse::String => format!("w.write_string({value})"),
+        AlgebraicTypeUse::Array(elem_ty) => match &**elem_ty {
+            AlgebraicTypeUse::Primitive(PrimitiveType::U8) => format!("w.write_bytes({value})"),
+            elem_ty => format!(
+                "w.write_array({value}, lambda w, v: {})",
+                write_expr(module, elem_ty, "v", this_type)
+            ),
+        },
+        AlgebraicTypeUse::Ref(r) if Some(*r) == this_type => format!("write_bsatn(w, {value})"),
+        AlgebraicTypeUse::Ref(r) => format!("{}.write_bsatn(w, {value})", type_ref_module_name(module, *r)),
+    }
+}
+
+fn type_ref_module_name(module: &ModuleDef, type_ref: AlgebraicTypeRef) -> String {
+    let (name, _) = module.type_def_from_ref(type_ref).unwrap();
+    type_module_name(name)
+}
+
+fn type_module_name(type_name: &ScopedTypeName) -> String {
+    collect_case(Case::Snake, type_name.name_segments()) + "_type"
+}
+
+fn table_module_name(table_name: &Identifier) -> String {
+    table_name.deref().to_case(Case::Snake) + "_table"
+}
+
+fn table_class_name(table_name: &Identifier) -> String {
+    table_name.deref().to_case(Case::Pascal) + "Table"
+}
+
+fn reducer_module_name(reducer_name: &Identifier) -> String {
+    reducer_name.deref().to_case(Case::Snake) + "_reducer"
+}
+
+fn reducer_args_type_name(reducer_name: &Identifier) -> String {
+    reducer_name.deref().to_case(Case::Pascal) + "Args"
+}
+
+fn reducer_function_name(reducer: &ReducerDef) -> String {
+    python_ident(reducer.name.deref().to_case(Case::Snake))
+}
+
+fn field_name(field: &Identifier) -> String {
+    python_ident(field.deref().to_case(Case::Snake))
+}
+
+const RESERVED_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+/// Append an underscore to `name` if it is a Python keyword, as PEP 8 recommends.
+fn python_ident(name: String) -> String {
+    if RESERVED_KEYWORDS.contains(&&*name) {
+        name + "_"
+    } else {
+        name
+    }
+}
+
+/// The contents of `_bsatn.py`, which the other generated modules depend on.
+const RUNTIME: &str = r#"# A BSATN codec and the SpacetimeDB special types, used by the generated bindings.
+
+import datetime
+import struct
+from dataclasses import dataclass
+from typing import Any, Callable, List, NoReturn, Optional, TypeVar, Union
+
+T = TypeVar("T")
+
+_UNIX_EPOCH = datetime.datetime(1970, 1, 1, tzinfo=datetime.timezone.utc)
+
+
+class BsatnError(ValueError):
+    """Raised when decoding malformed BSATN."""
+
+
+@dataclass(frozen=True)
+class Identity:
+    """A SpacetimeDB identity: a 256-bit unsigned integer, usually written as 64 hex digits."""
+
+    value: int
+
+    @staticmethod
+    def from_hex(hex_string: str) -> Identity:
+        return Identity(int(hex_string, 16))
+
+    def to_hex(self) -> str:
+        return f"{self.value:064x}"
+
+    def __str__(self) -> str:
+        return self.to_hex()
+
+
+@dataclass(frozen=True)
+class ConnectionId:
+    """A SpacetimeDB connection ID: a 128-bit unsigned integer, usually written as 32 hex digits."""
+
+    value: int
+
+    @staticmethod
+    def from_hex(hex_string: str) -> ConnectionId:
+        return ConnectionId(int(hex_string, 16))
+
+    def to_hex(self) -> str:
+        return f"{self.value:032x}"
+
+    def __str__(self) -> str:
+        return self.to_hex()
+
+
+@dataclass(frozen=True, order=True)
+class Timestamp:
+    """A point in time, as microseconds since the Unix epoch."""
+
+    micros_since_unix_epoch: int
+
+    @staticmethod
+    def from_datetime(dt: datetime.datetime) -> Timestamp:
+        return Timestamp(_timedelta_micros(dt - _UNIX_EPOCH))
+
+    def to_datetime(self) -> datetime.datetime:
+        return _UNIX_EPOCH + datetime.timedelta(microseconds=self.micros_since_unix_epoch)
+
+
+@dataclass(frozen=True, order=True)
+class TimeDuration:
+    """A signed span of time, in microseconds."""
+
+    micros: int
+
+    @staticmethod
+    def from_timedelta(td: datetime.timedelta) -> TimeDuration:
+        return TimeDuration(_timedelta_micros(td))
+
+    def to_timedelta(self) -> datetime.timedelta:
+        return datetime.timedelta(microseconds=self.micros)
+
+
+def _timedelta_micros(td: datetime.timedelta) -> int:
+    return (td.days * 86400 + td.seconds) * 1_000_000 + td.microseconds
+
+
+@dataclass
+class ScheduleAtInterval:
+    """Run a scheduled reducer repeatedly, every `value`."""
+
+    value: TimeDuration
+
+
+@dataclass
+class ScheduleAtTime:
+    """Run a scheduled reducer once, at `value`."""
+
+    value: Timestamp
+
+
+ScheduleAt = Union[ScheduleAtInterval, ScheduleAtTime]
+
+
+class BsatnReader:
+    """Reads BSATN-encoded values from a buffer, front to back."""
+
+    def __init__(self, data: bytes) -> None:
+        self._data = memoryview(data)
+        self._pos = 0
+
+    def remaining(self) -> int:
+        return len(self._data) - self._pos
+
+    def read_raw(self, n: int) -> bytes:
+        if self.remaining() < n:
+            raise BsatnError(f"unexpected end of input: wanted {n} bytes but only {self.remaining()} remain")
+        start = self._pos
+        self._pos += n
+        return bytes(self._data[start : self._pos])
+
+    def _unpack(self, fmt: str, size: int) -> Any:
+        return struct.unpack(fmt, self.read_raw(size))[0]
+
+    def _read_int(self, size: int, signed: bool) -> int:
+        return int.from_bytes(self.read_raw(size), "little", signed=signed)
+
+    def read_bool(self) -> bool:
+        byte = self.read_u8()
+        if byte > 1:
+            raise BsatnError(f"invalid bool {byte}")
+        return byte == 1
+
+    def read_i8(self) -> int:
+        return self._unpack("<b", 1)
+
+    def read_u8(self) -> int:
+        return self._unpack("<B", 1)
+
+    def read_i16(self) -> int:
+        return self._unpack("<h", 2)
+
+    def read_u16(self) -> int:
+        return self._unpack("<H", 2)
+
+    def read_i32(self) -> int:
+        return self._unpack("<i", 4)
+
+    def read_u32(self) -> int:
+        return self._unpack("<I", 4)
+
+    def read_i64(self) -> int:
+        return self._unpack("<q", 8)
+
+    def read_u64(self) -> int:
+        return self._unpack("<Q", 8)
+
+    def read_i128(self) -> int:
+        return self._read_int(16, signed=True)
+
+    def read_u128(self) -> int:
+        return self._read_int(16, signed=False)
+
+    def read_i256(self) -> int:
+        return self._read_int(32, signed=True)
+
+    def read_u256(self) -> int:
+        return self._read_int(32, signed=False)
+
+    def read_f32(self) -> float:
+        return self._unpack("<f", 4)
+
+    def read_f64(self) -> float:
+        return self._unpack("<d", 8)
+
+    def read_string(self) -> str:
+        try:
+            return self.read_bytes().decode("utf-8")
+        except UnicodeDecodeError as e:
+            raise BsatnError(f"invalid UTF-8 in string: {e}") from None
+
+    def read_bytes(self) -> bytes:
+        return self.read_raw(self.read_u32())
+
+    def read_array(self, read_elem: Callable[[BsatnReader], T]) -> List[T]:
+        return [read_elem(self) for _ in range(self.read_u32())]
+
+    def read_option(self, read_some: Callable[[BsatnReader], T]) -> Optional[T]:
+        tag = self.read_u8()
+        if tag == 0:
+            return read_some(self)
+        if tag == 1:
+            return None
+        raise BsatnError(f"invalid option tag {tag}")
+
+    def read_unit(self) -> None:
+        return None
+
+    def read_never(self) -> NoReturn:
+        raise BsatnError("cannot decode a value of an uninhabited type")
+
+    def read_identity(self) -> Identity:
+        return Identity(self.read_u256())
+
+    def read_connection_id(self) -> ConnectionId:
+        return ConnectionId(self.read_u128())
+
+    def read_timestamp(self) -> Timestamp:
+        return Timestamp(self.read_i64())
+
+    def read_time_duration(self) -> TimeDuration:
+        return TimeDuration(self.read_i64())
+
+    def read_schedule_at(self) -> ScheduleAt:
+        tag = self.read_u8()
+        if tag == 0:
+            return ScheduleAtInterval(self.read_time_duration())
+        if tag == 1:
+            return ScheduleAtTime(self.read_timestamp())
+        raise BsatnError(f"invalid ScheduleAt tag {tag}")
+
+
+class BsatnWriter:
+    """Accumulates BSATN-encoded values into a buffer."""
+
+    def __init__(self) -> None:
+        self._buf = bytearray()
+
+    def getvalue(self) -> bytes:
+        return bytes(self._buf)
+
+    def write_raw(self, data: bytes) -> None:
+        self._buf += data
+
+    def _pack(self, fmt: str, value: Any) -> None:
+        self._buf += struct.pack(fmt, value)
+
+    def _write_int(self, value: int, size: int, signed: bool) -> None:
+        self._buf += value.to_bytes(size, "little", signed=signed)
+
+    def write_bool(self, value: bool) -> None:
+        self.write_u8(1 if value else 0)
+
+    def write_i8(self, value: int) -> None:
+        self._pack("<b", value)
+
+    def write_u8(self, value: int) -> None:
+        self._pack("<B", value)
+
+    def write_i16(self, value: int) -> None:
+        self._pack("<h", value)
+
+    def write_u16(self, value: int) -> None:
+        self._pack("<H", value)
+
+    def write_i32(self, value: int) -> None:
+        self._pack("<i", value)
+
+    def write_u32(self, value: int) -> None:
+        self._pack("<I", value)
+
+    def write_i64(self, value: int) -> None:
+        self._pack("<q", value)
+
+    def write_u64(self, value: int) -> None:
+        self._pack("<Q", value)
+
+    def write_i128(self, value: int) -> None:
+        self._write_int(value, 16, signed=True)
+
+    def write_u128(self, value: int) -> None:
+        self._write_int(value, 16, signed=False)
+
+    def write_i256(self, value: int) -> None:
+        self._write_int(value, 32, signed=True)
+
+    def write_u256(self, value: int) -> None:
+        self._write_int(value, 32, signed=False)
+
+    def write_f32(self, value: float) -> None:
+        self._pack("<f", value)
+
+    def write_f64(self, value: float) -> None:
+        self._pack("<d", value)
+
+    def write_string(self, value: str) -> None:
+        self.write_bytes(value.encode("utf-8"))
+
+    def write_bytes(self, value: bytes) -> None:
+        self.write_u32(len(value))
+        self.write_raw(value)
+
+    def write_array(self, values: List[T], write_elem: Callable[[BsatnWriter, T], None]) -> None:
+        self.write_u32(len(values))
+        for value in values:
+            write_elem(self, value)
+
+    def write_option(self, value: Optional[T], write_some: Callable[[BsatnWriter, T], None]) -> None:
+        if value is None:
+            self.write_u8(1)
+        else:
+            self.write_u8(0)
+            write_some(self, value)
+
+    def write_unit(self, value: None) -> None:
+        pass
+
+    def write_never(self, value: NoReturn) -> None:
+        raise TypeError("cannot encode a value of an uninhabited type")
+
+    def write_identity(self, value: Identity) -> None:
+        self.write_u256(value.value)
+
+    def write_connection_id(self, value: ConnectionId) -> None:
+        self.write_u128(value.value)
+
+    def write_timestamp(self, value: Timestamp) -> None:
+        self.write_i64(value.micros_since_unix_epoch)
+
+    def write_time_duration(self, value: TimeDuration) -> None:
+        self.write_i64(value.micros)
+
+    def write_schedule_at(self, value: ScheduleAt) -> None:
+        if isinstance(value, ScheduleAtInterval):
+            self.write_u8(0)
+            self.write_time_duration(value.value)
+        elif isinstance(value, ScheduleAtTime):
+            self.write_u8(1)
+            self.write_timestamp(value.value)
+        else:
+            raise TypeError(f"expected a ScheduleAt, got {type(value).__name__}")
+
+
+def encode(write: Callable[[BsatnWriter, T], None], value: T) -> bytes:
+    """Encode `value` as BSATN using `write`, e.g. a generated module's `write_bsatn`."""
+    w = BsatnWriter()
+    write(w, value)
+    return w.getvalue()
+
+
+def decode(read: Callable[[BsatnReader], T], data: bytes) -> T:
+    """Decode `data` as BSATN using `read`, e.g. a generated module's `read_bsatn`."""
+    r = BsatnReader(data)
+    value = read(r)
+    if r.remaining():
+        raise BsatnError(f"{r.remaining()} trailing bytes after decoded value")
+    return value
+
+
+def read_row_list(r: BsatnReader) -> List[bytes]:
+    """Read a `BsatnRowList`, as found in table updates sent by the server, and split it into the BSATN of each row."""
+    tag = r.read_u8()
+    if tag == 0:
+        row_size = r.read_u16()
+        data = r.read_bytes()
+        if row_size == 0:
+            return []
+        return [data[start : start + row_size] for start in range(0, len(data), row_size)]
+    if tag == 1:
+        offsets = r.read_array(lambda r: r.read_u64())
+        data = r.read_bytes()
+        ends = offsets[1:] + [len(data)]
+        return [data[start:end] for start, end in zip(offsets, ends)]
+    raise BsatnError(f"invalid RowSizeHint tag {tag}")
+
+
+# Flags which may be passed to `CallReducer.encode`.
+CALL_REDUCER_FLAGS_FULL_UPDATE = 0
+CALL_REDUCER_FLAGS_NO_SUCCESS_NOTIFY = 1
+
+
+@dataclass
+class CallReducer:
+    """A request to call a reducer, with its arguments already encoded as BSATN."""
+
+    reducer: str
+    args: bytes
+
+    def encode(self, request_id: int = 0, flags: int = CALL_REDUCER_FLAGS_FULL_UPDATE) -> bytes:
+        """Encode this request as a BSATN `ClientMessage`, ready to send over the database's WebSocket.
+
+        The server includes `request_id` in its response to this call.
+        """
+        w = BsatnWriter()
+        # `CallReducer` is the first variant of `ClientMessage`.
+        w.write_u8(0)
+        w.write_string(self.reducer)
+        w.write_bytes(self.args)
+        w.write_u32(request_id)
+        w.write_u8(flags)
+        return w.getvalue()
+"#;
@@ -2,7 +2,8 @@ use super::code_indenter::{CodeIndenter, Indenter};
 use super::util::{collect_case, iter_reducers, print_lines, type_ref_name};
 use super::Lang;
 use crate::util::{
-    iter_tables, iter_types, iter_unique_cols, print_auto_generated_file_comment, print_auto_generated_version_comment,
+    iter_client_btree_indexes, iter_tables, iter_types, iter_unique_cols, print_auto_generated_file_comment,
+    print_auto_generated_version_comment,
 };
 use crate::OutputFile;
 use convert_case::{Case, Casing};
@@ -196,6 +197,14 @@ pub(super) fn register_table(client_cache: &mut __sdk::ClientCache<super::Remote
                         "_table.add_unique_constraint::<{unique_field_type}>({unique_field_name:?}, |row| &row.{unique_field_name});",
                     );
                 }
+                for (index_name, columns) in iter_client_btree_indexes(&schema, table, product_def) {
+                    let index_name = index_name.deref().to_case(Case::Snake);
+                    let (index_key_type, index_key_expr) = btree_index_key(module, &columns);
+                    writeln!(
+                        out,
+                        "_table.add_btree_index::<{index_key_type}>({index_name:?}, |row| {index_key_expr});",
+                    );
+                }
             },
             "}",
         );
@@ -289,7 +298,58 @@ pub(super) fn parse_table_update(
             );
         }
 
-        // TODO: expose non-unique indices.
+        for (index_name, columns) in iter_client_btree_indexes(&schema, table, product_def) {
+            let index_name = index_name.deref().to_case(Case::Snake);
+            let index_name_pascalcase = index_name.to_case(Case::Pascal);
+
+            let index_handle = table_name_pascalcase.clone() + &index_name_pascalcase + "Index";
+            let (index_key_type, _) = btree_index_key(module, &columns);
+            let index_columns = columns
+                .iter()
+                .map(|(name, _)| format!("`{}`", name.deref().to_case(Case::Snake)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            write!(
+                out,
+                "
+        /// Access to the `{index_name}` index on the table `{table_name}`,
+        /// which allows looking up rows by the column(s) {index_columns}
+        /// via the [`{index_handle}::filter`] and [`{index_handle}::range`] methods.
+        ///
+        /// Users are encouraged not to explicitly reference this type,
+        /// but to directly chain method calls,
+        /// like `ctx.db.{accessor_method}().{index_name}().filter(...)`.
+        pub struct {index_handle}<'ctx> {{
+            imp: __sdk::BTreeIndexHandle<{row_type}, {index_key_type}>,
+            phantom: std::marker::PhantomData<&'ctx super::RemoteTables>,
+        }}
+
+        impl<'ctx> {table_handle}<'ctx> {{
+            /// Get a handle on the `{index_name}` index on the table `{table_name}`.
+            pub fn {index_name}(&self) -> {index_handle}<'ctx> {{
+                {index_handle} {{
+                    imp: self.imp.get_btree_index::<{index_key_type}>({index_name:?}),
+                    phantom: std::marker::PhantomData,
+                }}
+            }}
+        }}
+
+        impl<'ctx> {index_handle}<'ctx> {{
+            /// Get the subscribed rows whose indexed column(s) are equal to `col_val`.
+            pub fn filter(&self, col_val: &{index_key_type}) -> impl Iterator<Item = {row_type}> {{
+                self.imp.filter(col_val)
+            }}
+
+            /// Get the subscribed rows whose indexed column(s) lie within `range`,
+            /// in ascending order.
+            pub fn range(&self, range: impl std::ops::RangeBounds<{index_key_type}>) -> impl Iterator<Item = {row_type}> {{
+                self.imp.range(range)
+            }}
+        }}
+        "
+            );
+        }
 
         OutputFile {
             filename: table_module_name(&table.name) + ".rs",
@@ -304,10 +364,24 @@ pub(super) fn parse_table_update(
 
         out.newline();
 
+        // Reducers which declare return types also need their types imported.
+        let return_types = reducer.has_typed_return().then(|| {
+            [
+                (reducer.name.clone(), reducer.ok_return_type_for_generate.clone()),
+                (reducer.name.clone(), reducer.err_return_type_for_generate.clone()),
+            ]
+        });
+        let import_roots = reducer
+            .params_for_generate
+            .elements
+            .iter()
+            .cloned()
+            .chain(return_types.into_iter().flatten())
+            .collect::<Vec<_>>();
         gen_and_print_imports(
             module,
             out,
-            &reducer.params_for_generate.elements,
+            &import_roots,
             // No need to skip any imports; we're not emitting a type that other modules can import.
             &[],
         );
@@ -481,6 +555,32 @@ impl {set_reducer_flags_trait} for super::SetReducerFlags {{
 "
         );
 
+        if reducer.has_typed_return() {
+            let mut ok_type = String::new();
+            write_type(module, &mut ok_type, &reducer.ok_return_type_for_generate).unwrap();
+            let mut err_type = String::new();
+            write_type(module, &mut err_type, &reducer.err_return_type_for_generate).unwrap();
+            let result_func_name = reducer_result_function_name(reducer);
+
+            writeln!(
+                out,
+                "
+/// Decodes the value returned by the reducer `{reducer_name}`, if it committed,
+/// or the error value it failed with, if it failed.
+///
+/// Returns `None` if `event` is not an invocation of `{reducer_name}`,
+/// or if the reducer did not run to completion.
+pub fn {result_func_name}(event: &__sdk::ReducerEvent<super::Reducer>) -> Option<__sdk::Result<Result<{ok_type}, {err_type}>>> {{
+    if matches!(event.reducer, super::Reducer::{enum_variant_name} {{ .. }}) {{
+        event.decode_return_value()
+    }} else {{
+        None
+    }}
+}}
+"
+            );
+        }
+
         OutputFile {
             filename: reducer_module_name(&reducer.name) + ".rs",
             code: output.into_inner(),
@@ -581,6 +681,25 @@ pub fn write_type<W: Write>(module: &ModuleDef, out: &mut W, ty: &AlgebraicTypeU
     Ok(())
 }
 
+/// Returns the Rust type of the key of a client-side B-tree index over `columns`,
+/// and an expression which computes that key from a `row`.
+///
+/// Single-column indexes are keyed by the column itself,
+/// and multi-column indexes by a tuple of their columns.
+fn btree_index_key(module: &ModuleDef, columns: &[&(Identifier, AlgebraicTypeUse)]) -> (String, String) {
+    let (types, exprs): (Vec<_>, Vec<_>) = columns
+        .iter()
+        .map(|(name, ty)| {
+            let name = name.deref().to_case(Case::Snake);
+            (type_name(module, ty), format!("row.{name}.clone()"))
+        })
+        .unzip();
+    match (&types[..], &exprs[..]) {
+        ([ty], [expr]) => (ty.clone(), expr.clone()),
+        _ => (format!("({})", types.join(", ")), format!("({})", exprs.join(", "))),
+    }
+}
+
 pub fn type_name(module: &ModuleDef, ty: &AlgebraicTypeUse) -> String {
     let mut s = String::new();
     write_type(module, &mut s, ty).unwrap();
@@ -803,6 +922,10 @@ fn reducer_flags_trait_name(reducer: &ReducerDef) -> String {
     format!("set_flags_for_{}", reducer_function_name(reducer))
 }
 
+fn reducer_result_function_name(reducer: &ReducerDef) -> String {
+    format!("{}_result", reducer_function_name(reducer))
+}
+
 /// Iterate over all of the Rust `mod`s for types, reducers and tables in the `module`.
 fn iter_module_names(module: &ModuleDef) -> impl Iterator<Item = String> + '_ {
     itertools::chain!(
@@ -840,10 +963,18 @@ fn print_module_reexports(module: &ModuleDef, out: &mut Indenter) {
         let reducer_trait_name = reducer_function_name(reducer);
         let flags_trait_name = reducer_flags_trait_name(reducer);
         let callback_id_name = reducer_callback_id_name(&reducer.name);
-        writeln!(
-            out,
-            "pub use {mod_name}::{{{reducer_trait_name}, {flags_trait_name}, {callback_id_name}}};"
-        );
+        if reducer.has_typed_return() {
+            let result_func_name = reducer_result_function_name(reducer);
+            writeln!(
+                out,
+                "pub use {mod_name}::{{{reducer_trait_name}, {flags_trait_name}, {callback_id_name}, {result_func_name}}};"
+            );
+        } else {
+            writeln!(
+                out,
+                "pub use {mod_name}::{{{reducer_trait_name}, {flags_trait_name}, {callback_id_name}}};"
+            );
+        }
     }
 }
 
@@ -1,5 +1,5 @@
 use crate::util::{
-    is_reducer_invokable, iter_reducers, iter_tables, iter_types, iter_unique_cols,
+    is_reducer_invokable, iter_client_btree_indexes, iter_reducers, iter_tables, iter_types, iter_unique_cols,
     print_auto_generated_version_comment,
 };
 use crate::{indent_scope, OutputFile};
@@ -241,9 +241,78 @@ export class {table_handle}<TableName extends string> implements __TableHandle<T
             writeln!(out, "}};");
         }
 
-        writeln!(out);
+        for (index_name, columns) in iter_client_btree_indexes(&schema, table, product_def) {
+            let index_name = index_name.deref().to_case(Case::Camel);
+            let column_names = columns
+                .iter()
+                .map(|(name, _)| name.deref().to_case(Case::Camel))
+                .collect::<Vec<_>>();
+            let index_columns = column_names
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let (index_key_type, matches_key) = match &columns[..] {
+                [(_, ty)] => (
+                    type_name(module, ty),
+                    format!("__deepEqual(row.{}, col_val)", column_names[0]),
+                ),
+                _ => (
+                    format!(
+                        "[{}]",
+                        columns
+                            .iter()
+                            .map(|(_, ty)| type_name(module, ty))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    column_names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| format!("__deepEqual(row.{name}, col_val[{i}])"))
+                        .collect::<Vec<_>>()
+                        .join(" && "),
+                ),
+            };
+
+            writeln!(
+                out,
+                "/**
+ * Access to the `{index_name}` index on the table `{table_name}`,
+ * which allows looking up rows by the column(s) {index_columns}
+ * via the [`{index_name}.filter`] method.
+ *
+ * Users are encouraged not to explicitly reference this type,
+ * but to directly chain method calls,
+ * like `ctx.db.{accessor_method}.{index_name}.filter(...)`.
+ */"
+            );
+            writeln!(out, "{index_name} = {{");
+            out.with_indent(|out| {
+                writeln!(
+                    out,
+                    "// Get the subscribed rows whose indexed column(s) are equal to `col_val`."
+                );
+                writeln!(out, "filter: (col_val: {index_key_type}): {row_type}[] => {{");
+                out.with_indent(|out| {
+                    writeln!(out, "const rows: {row_type}[] = [];");
+                    writeln!(out, "for (let row of this.tableCache.iter()) {{");
+                    out.with_indent(|out| {
+                        writeln!(out, "if ({matches_key}) {{");
+                        out.with_indent(|out| {
+                            writeln!(out, "rows.push(row);");
+                        });
+                        writeln!(out, "}}");
+                    });
+                    writeln!(out, "}}");
+                    writeln!(out, "return rows;");
+                });
+                writeln!(out, "}},");
+            });
+            writeln!(out, "}};");
+        }
 
-        // TODO: expose non-unique indices.
+        writeln!(out);
 
         writeln!(
             out,
@@ -295,10 +364,24 @@ removeOnUpdate = (cb: (ctx: EventContext, onRow: {row_type}, newRow: {row_type})
 
         out.newline();
 
+        // Reducers which declare return types also need their types imported.
+        let return_types = reducer.has_typed_return().then(|| {
+            [
+                (reducer.name.clone(), reducer.ok_return_type_for_generate.clone()),
+                (reducer.name.clone(), reducer.err_return_type_for_generate.clone()),
+            ]
+        });
+        let import_roots = reducer
+            .params_for_generate
+            .elements
+            .iter()
+            .cloned()
+            .chain(return_types.into_iter().flatten())
+            .collect::<Vec<_>>();
         gen_and_print_imports(
             module,
             out,
-            &reducer.params_for_generate.elements,
+            &import_roots,
             // No need to skip any imports; we're not emitting a type that other modules can import.
             &[],
             None,
@@ -308,6 +391,24 @@ removeOnUpdate = (cb: (ctx: EventContext, onRow: {row_type}, newRow: {row_type})
 
         define_body_for_product(module, out, &args_type, &reducer.params_for_generate.elements);
 
+        if reducer.has_typed_return() {
+            let result_type = reducer_result_type_name(&reducer.name);
+            writeln!(
+                out,
+                "// The value returned by the reducer, if it committed, or the error value it failed with."
+            );
+            writeln!(out, "export type {result_type} =");
+            out.indent(1);
+            write!(out, "| {{ tag: \"ok\", value: ");
+            write_type(module, out, &reducer.ok_return_type_for_generate, None, None).unwrap();
+            writeln!(out, " }}");
+            write!(out, "| {{ tag: \"err\", value: ");
+            write_type(module, out, &reducer.err_return_type_for_generate, None, None).unwrap();
+            writeln!(out, " }};");
+            out.dedent(1);
+            out.newline();
+        }
+
         OutputFile {
             filename: reducer_module_name(&reducer.name) + ".ts",
             code: output.into_inner(),
@@ -329,6 +430,11 @@ removeOnUpdate = (cb: (ctx: EventContext, onRow: {row_type}, newRow: {row_type})
             let args_type = reducer_args_type_name(&reducer.name);
             writeln!(out, "import {{ {args_type} }} from \"./{reducer_module_name}\";");
             writeln!(out, "export {{ {args_type} }};");
+            if reducer.has_typed_return() {
+                let result_type = reducer_result_type_name(&reducer.name);
+                writeln!(out, "import {{ type {result_type} }} from \"./{reducer_module_name}\";");
+                writeln!(out, "export {{ type {result_type} }};");
+            }
         }
 
         writeln!(out);
@@ -402,6 +508,14 @@ removeOnUpdate = (cb: (ctx: EventContext, onRow: {row_type}, newRow: {row_type})
                 "argsType: {args_type}.getTypeScriptAlgebraicType(),",
                 args_type = reducer_args_type_name(&reducer.name)
             );
+            if reducer.has_typed_return() {
+                write!(out, "okType: ");
+                convert_algebraic_type(module, out, &reducer.ok_return_type_for_generate, "");
+                writeln!(out, ",");
+                write!(out, "errType: ");
+                convert_algebraic_type(module, out, &reducer.err_return_type_for_generate, "");
+                writeln!(out, ",");
+            }
             out.dedent(1);
             writeln!(out, "}},");
         }
@@ -664,12 +778,22 @@ fn print_reducer_enum_defn(module: &ModuleDef, out: &mut Indenter) {
     writeln!(out, "// A type representing all the possible variants of a reducer.");
     writeln!(out, "export type Reducer = never");
     for reducer in iter_reducers(module) {
-        writeln!(
-            out,
-            "| {{ name: \"{}\", args: {} }}",
-            reducer_variant_name(&reducer.name),
-            reducer_args_type_name(&reducer.name)
-        );
+        if reducer.has_typed_return() {
+            writeln!(
+                out,
+                "| {{ name: \"{}\", args: {}, result?: {} }}",
+                reducer_variant_name(&reducer.name),
+                reducer_args_type_name(&reducer.name),
+                reducer_result_type_name(&reducer.name)
+            );
+        } else {
+            writeln!(
+                out,
+                "| {{ name: \"{}\", args: {} }}",
+                reducer_variant_name(&reducer.name),
+                reducer_args_type_name(&reducer.name)
+            );
+        }
     }
     writeln!(out, ";");
 }
@@ -1039,6 +1163,10 @@ fn reducer_args_type_name(reducer_name: &Identifier) -> String {
     reducer_name.deref().to_case(Case::Pascal)
 }
 
+fn reducer_result_type_name(reducer_name: &Identifier) -> String {
+    reducer_name.deref().to_case(Case::Pascal) + "Result"
+}
+
 fn reducer_variant_name(reducer_name: &Identifier) -> String {
     reducer_name.deref().to_case(Case::Pascal)
 }
@@ -15,7 +15,7 @@ use spacetimedb_primitives::ColList;
 use spacetimedb_schema::schema::TableSchema;
 use spacetimedb_schema::type_for_generate::ProductTypeDef;
 use spacetimedb_schema::{
-    def::{IndexDef, TableDef, TypeDef},
+    def::{BTreeAlgorithm, IndexAlgorithm, IndexDef, TableDef, TypeDef},
     type_for_generate::TypespaceForGenerate,
 };
 use spacetimedb_schema::{
@@ -126,6 +126,45 @@ pub(super) fn iter_indexes(table: &TableDef) -> impl Iterator<Item = &IndexDef>
     table.indexes.values().sorted_by_key(|index| &index.name)
 }
 
+/// Is `ty` totally ordered in every client language,
+/// so that clients can maintain a B-tree index over it?
+fn is_type_orderable(ty: &AlgebraicTypeUse) -> bool {
+    match ty {
+        AlgebraicTypeUse::Primitive(prim) => !matches!(prim, PrimitiveType::F32 | PrimitiveType::F64),
+        AlgebraicTypeUse::String | AlgebraicTypeUse::Identity | AlgebraicTypeUse::ConnectionId => true,
+        _ => false,
+    }
+}
+
+/// Iterate over the non-unique B-tree indexes on `table` for which clients get index handles,
+/// yielding each index's accessor name and its columns.
+///
+/// Unique indexes are skipped, as clients already get handles for them via [`iter_unique_cols`],
+/// as are indexes without an accessor name or over columns which aren't [orderable](is_type_orderable).
+pub(super) fn iter_client_btree_indexes<'a>(
+    schema: &'a TableSchema,
+    table: &'a TableDef,
+    product_def: &'a ProductTypeDef,
+) -> impl Iterator<Item = (&'a Identifier, Vec<&'a (Identifier, AlgebraicTypeUse)>)> + 'a {
+    iter_indexes(table).filter_map(move |index| {
+        let accessor_name = index.accessor_name.as_ref()?;
+        let IndexAlgorithm::BTree(BTreeAlgorithm { columns }) = &index.algorithm else {
+            return None;
+        };
+        if schema.is_unique(columns) {
+            return None;
+        }
+        let columns = columns
+            .iter()
+            .map(|col| &product_def.elements[col.idx()])
+            .collect::<Vec<_>>();
+        columns
+            .iter()
+            .all(|(_, ty)| is_type_orderable(ty))
+            .then_some((accessor_name, columns))
+    })
+}
+
 /// Iterate over all the [`TypeDef`]s defined by the module, in alphabetical order by name.
 ///
 /// Sorting is necessary to have deterministic reproducible codegen.
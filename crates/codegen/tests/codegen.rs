@@ -1,4 +1,4 @@
-use spacetimedb_codegen::{generate, Csharp, Rust, TypeScript};
+use spacetimedb_codegen::{generate, Csharp, Go, Python, Rust, TypeScript};
 use spacetimedb_data_structures::map::HashMap;
 use spacetimedb_schema::def::ModuleDef;
 use spacetimedb_testing::modules::{CompilationMode, CompiledModule};
@@ -23,7 +23,7 @@ macro_rules! declare_tests {
             settings.set_sort_maps(true);
             // Ignore the autogenerated comments with version info, since it changes with every
             // build.
-            settings.add_filter(r"// This was generated using spacetimedb cli version \d+\.\d+\.\d+ .*", "VERSION_COMMENT");
+            settings.add_filter(r"(?://|#) This was generated using spacetimedb cli version \d+\.\d+\.\d+ .*", "VERSION_COMMENT");
             // Ignore the place where the CLI version is put in the typescript REMOTE_MODULE info,
             // so it isn't constantly changing.
             settings.add_filter(r#"cliVersion: "\d+\.\d+\.\d+","#, r#"cliVersion: "X.Y.Z","#);
@@ -38,4 +38,6 @@ declare_tests! {
     test_codegen_csharp => Csharp { namespace: "SpacetimeDB" },
     test_codegen_typescript => TypeScript,
     test_codegen_rust => Rust,
+    test_codegen_python => Python,
+    test_codegen_go => Go { package_name: "module_bindings" },
 }
@@ -243,6 +243,12 @@ impl<T: Encode> Txdata<T> {
 }
 
 /// The inputs of a transaction, i.e. the name and arguments of a reducer call.
+///
+/// As written by the datastore, `reducer_args` is prefixed with the caller's
+/// identity and connection id and the timestamp the reducer observed.
+/// Since a reducer's random number generator is seeded from its timestamp,
+/// this is everything needed to re-run the reducer deterministically
+/// against the state preceding the transaction.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub struct Inputs {
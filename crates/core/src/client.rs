@@ -5,6 +5,7 @@ mod client_connection;
 mod client_connection_index;
 mod message_handlers;
 pub mod messages;
+pub mod rate_limit;
 
 pub use client_connection::{
     ClientConfig, ClientConnection, ClientConnectionReceiver, ClientConnectionSender, ClientSendError, DataMessage,
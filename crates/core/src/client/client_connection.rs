@@ -7,7 +7,8 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::{Instant, SystemTime};
 
-use super::messages::{OneOffQueryResponseMessage, SerializableMessage};
+use super::messages::{CaughtUpMessage, OneOffQueryResponseMessage, SerializableMessage};
+use super::rate_limit::{ConnectionRateLimiter, RateLimiter};
 use super::{message_handlers, ClientActorId, MessageHandleError};
 use crate::db::relational_db::RelationalDB;
 use crate::error::DBError;
@@ -24,8 +25,8 @@ use futures::prelude::*;
 use prometheus::{Histogram, IntCounter, IntGauge};
 use spacetimedb_auth::identity::{ConnectionAuthCtx, SpacetimeIdentityClaims};
 use spacetimedb_client_api_messages::websocket::{
-    BsatnFormat, CallReducerFlags, Compression, FormatSwitch, JsonFormat, SubscribeMulti, SubscribeSingle, Unsubscribe,
-    UnsubscribeMulti,
+    BsatnFormat, CallReducerFlags, Compression, FormatSwitch, JsonFormat, ResumeSubscriptions, SubscribeMulti,
+    SubscribeSingle, Unsubscribe, UnsubscribeMulti,
 };
 use spacetimedb_durability::{DurableOffset, TxOffset};
 use spacetimedb_lib::identity::RequestId;
@@ -72,6 +73,14 @@ pub struct ClientConfig {
     /// confirmed to be durable. If `false`, updates will be delivered
     /// immediately.
     pub confirmed_reads: bool,
+    /// If `true`, the client wants [`ReducerResult`]s carrying the return values
+    /// of the reducers it calls, rather than plain [`TransactionUpdate`]s.
+    pub reducer_results: bool,
+    /// If `true`, the client wants a [`CaughtUp`] after each message computed at a transaction offset,
+    /// so that it can later resume its subscriptions from that offset.
+    ///
+    /// [`CaughtUp`]: spacetimedb_client_api_messages::websocket::CaughtUp
+    pub tx_offsets: bool,
 }
 
 impl ClientConfig {
@@ -81,6 +90,8 @@ impl ClientConfig {
             compression: <_>::default(),
             tx_update_full: true,
             confirmed_reads: false,
+            reducer_results: false,
+            tx_offsets: false,
         }
     }
 }
@@ -151,11 +162,20 @@ pub struct ClientConnectionReceiver {
     channel: MeteredReceiver<ClientUpdate>,
     current: Option<ClientUpdate>,
     offset_supply: Box<dyn DurableOffsetSupply>,
+    /// The id of the database's [`TxHistory`], if the client asked for [`ClientConfig::tx_offsets`].
+    ///
+    /// [`TxHistory`]: crate::db::tx_history::TxHistory
+    tx_history_id: Option<u64>,
+    /// The [`CaughtUpMessage`] for the greatest offset of the messages received so far.
+    caught_up: Option<CaughtUpMessage>,
+    /// Whether `caught_up` is due to be sent before the next message.
+    caught_up_pending: bool,
 }
 
 impl ClientConnectionReceiver {
     fn new(
         confirmed_reads: bool,
+        tx_history_id: Option<u64>,
         channel: MeteredReceiver<ClientUpdate>,
         offset_supply: impl DurableOffsetSupply + 'static,
     ) -> Self {
@@ -164,10 +184,39 @@ impl ClientConnectionReceiver {
             channel,
             current: None,
             offset_supply: Box::new(offset_supply),
+            tx_history_id,
+            caught_up: None,
+            caught_up_pending: false,
         }
     }
 
-    /// Receive the next message from this channel.
+    /// Receive the next message for the client,
+    /// which is either the next message from this channel, as by [`Self::recv_update`],
+    /// or a [`CaughtUpMessage`] following one computed at a transaction offset.
+    ///
+    /// The [`CaughtUpMessage`] names the greatest offset received so far,
+    /// as messages aren't necessarily received in order of their offsets,
+    /// e.g. when a reducer fails while another commits.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, as is [`Self::recv_update`].
+    pub async fn recv(&mut self) -> Option<SerializableMessage> {
+        if let Some(caught_up) = self.caught_up.filter(|_| self.caught_up_pending) {
+            self.caught_up_pending = false;
+            return Some(caught_up.into());
+        }
+
+        let (tx_offset, message) = self.recv_update().await?;
+        if let (Some(history_id), Some(tx_offset)) = (self.tx_history_id, tx_offset) {
+            let tx_offset = self.caught_up.map_or(tx_offset, |prev| prev.tx_offset.max(tx_offset));
+            self.caught_up = Some(CaughtUpMessage { history_id, tx_offset });
+            self.caught_up_pending = true;
+        }
+        Some(message)
+    }
+
+    /// Receive the next message from this channel, along with the offset it was computed at.
     ///
     /// If this method returns `None`, the channel is closed and no more messages
     /// are in the internal buffers. No more messages can ever be received from
@@ -200,13 +249,13 @@ impl ClientConnectionReceiver {
     /// data.
     //
     // TODO: Can we make a cancel-safe `recv_many` with confirmed reads semantics?
-    pub async fn recv(&mut self) -> Option<SerializableMessage> {
+    async fn recv_update(&mut self) -> Option<(Option<TxOffset>, SerializableMessage)> {
         let ClientUpdate { tx_offset, message } = match self.current.take() {
             None => self.channel.recv().await?,
             Some(update) => update,
         };
         if !self.confirmed_reads {
-            return Some(message);
+            return Some((tx_offset, message));
         }
 
         if let Some(tx_offset) = tx_offset {
@@ -226,15 +275,15 @@ impl ClientConnectionReceiver {
                             warn!("database went away while waiting for durable offset");
                         })
                         .ok()?;
-                    self.current.take().map(|update| update.message)
+                    self.current.take().map(|update| (update.tx_offset, update.message))
                 }
                 // Database shut down or crashed.
                 Err(NoSuchModule) => None,
                 // In-memory database.
-                Ok(None) => Some(message),
+                Ok(None) => Some((Some(tx_offset), message)),
             }
         } else {
-            Some(message)
+            Some((None, message))
         }
     }
 
@@ -256,6 +305,8 @@ pub struct ClientConnectionSender {
     pub id: ClientActorId,
     pub auth: ConnectionAuthCtx,
     pub config: ClientConfig,
+    /// Enforces the rate limits on the requests of this client.
+    pub rate_limiter: Arc<ConnectionRateLimiter>,
     sendtx: mpsc::Sender<ClientUpdate>,
     abort_handle: AbortHandle,
     cancelled: AtomicBool,
@@ -325,7 +376,14 @@ impl ClientConnectionSender {
             Err(_) => tokio::runtime::Runtime::new().unwrap().spawn(async {}).abort_handle(),
         };
 
-        let receiver = ClientConnectionReceiver::new(config.confirmed_reads, MeteredReceiver::new(rx), offset_supply);
+        // There's no database whose history the client could resume from, so make up an id.
+        let tx_history_id = config.tx_offsets.then_some(0);
+        let receiver = ClientConnectionReceiver::new(
+            config.confirmed_reads,
+            tx_history_id,
+            MeteredReceiver::new(rx),
+            offset_supply,
+        );
         let cancelled = AtomicBool::new(false);
         let dummy_claims = SpacetimeIdentityClaims {
             identity: id.identity,
@@ -339,6 +397,7 @@ impl ClientConnectionSender {
             id,
             auth: ConnectionAuthCtx::try_from(dummy_claims).expect("dummy claims should always be valid"),
             config,
+            rate_limiter: Arc::new(ConnectionRateLimiter::unlimited(id.identity)),
             sendtx,
             abort_handle,
             cancelled,
@@ -674,10 +733,12 @@ impl ClientConnection {
     /// to verify that the database at `module_rx` approves of this connection,
     /// and should not invoke this method if that call returns an error,
     /// and pass the returned [`Connected`] as `_proof_of_client_connected_call`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn spawn<Fut>(
         id: ClientActorId,
         auth: ConnectionAuthCtx,
         config: ClientConfig,
+        rate_limiter: &Arc<RateLimiter>,
         replica_id: u64,
         mut module_rx: watch::Receiver<ModuleHost>,
         actor: impl FnOnce(ClientConnection, ClientConnectionReceiver) -> Fut,
@@ -715,16 +776,26 @@ impl ClientConnection {
         .abort_handle();
 
         let metrics = ClientConnectionMetrics::new(database_identity, config.protocol);
+        let tx_history_id = config
+            .tx_offsets
+            .then(|| module.replica_ctx().relational_db.tx_history_id());
         let receiver = ClientConnectionReceiver::new(
             config.confirmed_reads,
+            tx_history_id,
             MeteredReceiver::with_gauge(sendrx, metrics.sendtx_queue_size.clone()),
             module_rx.clone(),
         );
 
+        let rate_limiter = Arc::new(ConnectionRateLimiter::new(
+            rate_limiter.clone(),
+            database_identity,
+            id.identity,
+        ));
         let sender = Arc::new(ClientConnectionSender {
             id,
             auth,
             config,
+            rate_limiter,
             sendtx,
             abort_handle,
             cancelled: AtomicBool::new(false),
@@ -874,6 +945,21 @@ impl ClientConnection {
             .await?
     }
 
+    pub async fn resume_subscriptions(
+        &self,
+        request: ResumeSubscriptions,
+        timer: Instant,
+    ) -> Result<Option<ExecutionMetrics>, DBError> {
+        let me = self.clone();
+        self.module()
+            .on_module_thread("resume_subscriptions", move || {
+                me.module()
+                    .subscriptions()
+                    .resume_subscriptions(me.sender, request, timer)
+            })
+            .await?
+    }
+
     pub async fn unsubscribe_multi(
         &self,
         request: UnsubscribeMulti,
@@ -1022,6 +1108,7 @@ mod tests {
         TransactionUpdateMessage {
             event: None,
             database_update: SubscriptionUpdateMessage::default_for_protocol(Protocol::Binary, None),
+            return_value: None,
         }
     }
 
@@ -1161,6 +1248,31 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn client_connection_receiver_sends_caught_up_after_offset_messages() {
+        let (sender, mut receiver) = ClientConnectionSender::dummy_with_channel(
+            ClientActorId::for_test(Identity::ZERO),
+            ClientConfig {
+                tx_offsets: true,
+                ..ClientConfig::for_test()
+            },
+            NoneDurableOffset,
+        );
+        let assert_caught_up = |msg: Option<SerializableMessage>, expected: TxOffset| assert_matches!(msg, Some(SerializableMessage::CaughtUp(CaughtUpMessage { tx_offset, .. })) if tx_offset == expected);
+
+        sender.send_message(Some(5), empty_tx_update()).unwrap();
+        sender.send_message(None, empty_tx_update()).unwrap();
+        sender.send_message(Some(3), empty_tx_update()).unwrap();
+
+        assert_received_update(receiver.recv()).await;
+        assert_caught_up(receiver.recv().await, 5);
+        // Messages without an offset aren't followed by a `CaughtUp`.
+        assert_received_update(receiver.recv()).await;
+        assert_received_update(receiver.recv()).await;
+        // The offset never goes backwards.
+        assert_caught_up(receiver.recv().await, 5);
+    }
+
     #[tokio::test]
     async fn client_connection_receiver_cancel_safety() {
         let offset = FakeDurableOffset::new();
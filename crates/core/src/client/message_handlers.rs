@@ -1,4 +1,5 @@
 use super::messages::{SubscriptionUpdateMessage, SwitchedServerMessage, ToProtocol, TransactionUpdateMessage};
+use super::rate_limit::{RateLimitError, RateLimitKind};
 use super::{ClientConnection, DataMessage, Protocol};
 use crate::energy::EnergyQuanta;
 use crate::host::module_host::{EventStatus, ModuleEvent, ModuleFunctionCall};
@@ -61,6 +62,24 @@ pub async fn handle(client: &ClientConnection, message: DataMessage, timer: Inst
     let sub_metrics = record_metrics(WorkloadType::Subscribe);
     let unsub_metrics = record_metrics(WorkloadType::Unsubscribe);
 
+    if let Err(err) = check_rate_limits(client, &message) {
+        let reducer = match &message {
+            ClientMessage::CallReducer(CallReducer { reducer, .. }) => Some(reducer.clone()),
+            _ => None,
+        };
+        return Err(MessageExecutionError {
+            reducer_id: reducer
+                .as_deref()
+                .and_then(|reducer| mod_info.module_def.reducer_full(reducer))
+                .map(|(id, _)| id),
+            reducer,
+            caller_identity: client.id.identity,
+            caller_connection_id: Some(client.id.connection_id),
+            err: err.into(),
+        }
+        .into());
+    }
+
     let res = match message {
         ClientMessage::CallReducer(CallReducer {
             ref reducer,
@@ -88,6 +107,13 @@ pub async fn handle(client: &ClientConnection, message: DataMessage, timer: Inst
                 .observe(timer.elapsed().as_secs_f64());
             res.map_err(|e| (None, None, e.into()))
         }
+        ClientMessage::ResumeSubscriptions(request) => {
+            let res = client.resume_subscriptions(request, timer).await.map(sub_metrics);
+            mod_metrics
+                .request_round_trip_subscribe
+                .observe(timer.elapsed().as_secs_f64());
+            res.map_err(|e| (None, None, e.into()))
+        }
         ClientMessage::UnsubscribeMulti(request) => {
             let res = client.unsubscribe_multi(request, timer).await.map(unsub_metrics);
             mod_metrics
@@ -141,6 +167,27 @@ pub async fn handle(client: &ClientConnection, message: DataMessage, timer: Inst
     Ok(())
 }
 
+/// Charges `message` against the rate limits of `client`.
+fn check_rate_limits(client: &ClientConnection, message: &ClientMessage<FunctionArgs>) -> Result<(), RateLimitError> {
+    match message {
+        ClientMessage::CallReducer(_) => client.rate_limiter.check(RateLimitKind::ReducerCall),
+        ClientMessage::OneOffQuery(_) => client.rate_limiter.check(RateLimitKind::OneOffQuery),
+        // A client's messages are handled one at a time,
+        // so the query sets it holds can't change before this subscription is added.
+        ClientMessage::SubscribeSingle(_) | ClientMessage::SubscribeMulti(_) => client
+            .rate_limiter
+            .check_subscriptions(client.module().subscriptions().num_query_sets(client.id)),
+        // Resuming adds each of the query sets, so check that the last one will still fit.
+        ClientMessage::ResumeSubscriptions(request) => match request.subscriptions.len() {
+            0 => Ok(()),
+            n => client
+                .rate_limiter
+                .check_subscriptions(client.module().subscriptions().num_query_sets(client.id) + n - 1),
+        },
+        ClientMessage::Subscribe(_) | ClientMessage::Unsubscribe(_) | ClientMessage::UnsubscribeMulti(_) => Ok(()),
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("error executing message (reducer: {reducer:?}) (err: {err:#})")]
 pub struct MessageExecutionError {
@@ -168,6 +215,7 @@ impl MessageExecutionError {
             host_execution_duration: Duration::ZERO,
             request_id: Some(RequestId::default()),
             timer: None,
+            return_value: None,
         }
     }
 }
@@ -178,6 +226,7 @@ impl ToProtocol for MessageExecutionError {
         TransactionUpdateMessage {
             event: Some(Arc::new(self.into_event())),
             database_update: SubscriptionUpdateMessage::default_for_protocol(protocol, None),
+            return_value: None,
         }
         .to_protocol(protocol)
     }
@@ -167,6 +167,7 @@ pub enum SerializableMessage {
     Subscribe(SubscriptionUpdateMessage),
     Subscription(SubscriptionMessage),
     TxUpdate(TransactionUpdateMessage),
+    CaughtUp(CaughtUpMessage),
 }
 
 impl SerializableMessage {
@@ -177,7 +178,7 @@ impl SerializableMessage {
             Self::Subscribe(msg) => Some(msg.num_rows()),
             Self::Subscription(msg) => Some(msg.num_rows()),
             Self::TxUpdate(msg) => Some(msg.num_rows()),
-            Self::Identity(_) => None,
+            Self::Identity(_) | Self::CaughtUp(_) => None,
         }
     }
 
@@ -191,9 +192,10 @@ impl SerializableMessage {
                 SubscriptionResult::Error(_) => None,
                 SubscriptionResult::SubscribeMulti(_) => Some(WorkloadType::Subscribe),
                 SubscriptionResult::UnsubscribeMulti(_) => Some(WorkloadType::Unsubscribe),
+                SubscriptionResult::Resume(_) => Some(WorkloadType::Subscribe),
             },
             Self::TxUpdate(_) => Some(WorkloadType::Update),
-            Self::Identity(_) => None,
+            Self::Identity(_) | Self::CaughtUp(_) => None,
         }
     }
 }
@@ -208,12 +210,25 @@ impl ToProtocol for SerializableMessage {
             SerializableMessage::Subscribe(msg) => msg.to_protocol(protocol),
             SerializableMessage::TxUpdate(msg) => msg.to_protocol(protocol),
             SerializableMessage::Subscription(msg) => msg.to_protocol(protocol),
+            SerializableMessage::CaughtUp(msg) => msg.to_protocol(protocol),
         }
     }
 }
 
 pub type IdentityTokenMessage = ws::IdentityToken;
 
+pub type CaughtUpMessage = ws::CaughtUp;
+
+impl ToProtocol for CaughtUpMessage {
+    type Encoded = SwitchedServerMessage;
+    fn to_protocol(self, protocol: Protocol) -> Self::Encoded {
+        match protocol {
+            Protocol::Text => FormatSwitch::Json(ws::ServerMessage::CaughtUp(self)),
+            Protocol::Binary => FormatSwitch::Bsatn(ws::ServerMessage::CaughtUp(self)),
+        }
+    }
+}
+
 impl ToProtocol for IdentityTokenMessage {
     type Encoded = SwitchedServerMessage;
     fn to_protocol(self, protocol: Protocol) -> Self::Encoded {
@@ -230,6 +245,10 @@ pub struct TransactionUpdateMessage {
     /// When `None`, this is a light update.
     pub event: Option<Arc<ModuleEvent>>,
     pub database_update: SubscriptionUpdateMessage,
+    /// The value returned by the reducer,
+    /// set only for callers which asked for reducer results.
+    /// When `Some`, this is sent as a [`ws::ReducerResult`].
+    pub return_value: Option<Bytes>,
 }
 
 impl TransactionUpdateMessage {
@@ -243,6 +262,7 @@ impl ToProtocol for TransactionUpdateMessage {
     fn to_protocol(self, protocol: Protocol) -> Self::Encoded {
         fn convert<F: WebsocketFormat>(
             event: Option<Arc<ModuleEvent>>,
+            return_value: Option<Bytes>,
             request_id: u32,
             update: ws::DatabaseUpdate<F>,
             conv_args: impl FnOnce(&ArgsTuple) -> F::Single,
@@ -274,19 +294,33 @@ impl ToProtocol for TransactionUpdateMessage {
                 caller_connection_id: event.caller_connection_id.unwrap_or(ConnectionId::ZERO),
             };
 
-            ws::ServerMessage::TransactionUpdate(tx_update)
+            match return_value {
+                Some(return_value) => ws::ServerMessage::ReducerResult(ws::ReducerResult {
+                    update: tx_update,
+                    return_value,
+                }),
+                None => ws::ServerMessage::TransactionUpdate(tx_update),
+            }
         }
 
-        let TransactionUpdateMessage { event, database_update } = self;
+        let TransactionUpdateMessage {
+            event,
+            database_update,
+            return_value,
+        } = self;
         let update = database_update.database_update;
         protocol.assert_matches_format_switch(&update);
         let request_id = database_update.request_id.unwrap_or(0);
         match update {
-            FormatSwitch::Bsatn(update) => FormatSwitch::Bsatn(convert(event, request_id, update, |args| {
-                Vec::from(args.get_bsatn().clone()).into()
-            })),
+            FormatSwitch::Bsatn(update) => {
+                FormatSwitch::Bsatn(convert(event, return_value, request_id, update, |args| {
+                    Vec::from(args.get_bsatn().clone()).into()
+                }))
+            }
             FormatSwitch::Json(update) => {
-                FormatSwitch::Json(convert(event, request_id, update, |args| args.get_json().clone()))
+                FormatSwitch::Json(convert(event, return_value, request_id, update, |args| {
+                    args.get_json().clone()
+                }))
             }
         }
     }
@@ -371,6 +405,15 @@ pub struct SubscriptionError {
     pub message: Box<str>,
 }
 
+/// The query sets resumed by a [`ws::ResumeSubscriptions`] request.
+#[derive(Debug, Clone)]
+pub struct ResumedSubscriptionsData {
+    /// Whether each update holds the changes since the client's position,
+    /// rather than every matching row.
+    pub is_delta: bool,
+    pub subscriptions: Vec<(ws::QueryId, SubscriptionData)>,
+}
+
 #[derive(Debug, Clone)]
 pub enum SubscriptionResult {
     Subscribe(SubscriptionRows),
@@ -378,6 +421,7 @@ pub enum SubscriptionResult {
     Error(SubscriptionError),
     SubscribeMulti(SubscriptionData),
     UnsubscribeMulti(SubscriptionData),
+    Resume(ResumedSubscriptionsData),
 }
 
 #[derive(Debug, Clone)]
@@ -409,6 +453,7 @@ impl SubscriptionMessage {
             SubscriptionResult::SubscribeMulti(x) => subscription_data_rows(x),
             SubscriptionResult::UnsubscribeMulti(x) => subscription_data_rows(x),
             SubscriptionResult::Unsubscribe(x) => num_rows_in(x),
+            SubscriptionResult::Resume(x) => x.subscriptions.iter().map(|(_, x)| subscription_data_rows(x)).sum(),
             _ => 0,
         }
     }
@@ -543,6 +588,38 @@ impl ToProtocol for SubscriptionMessage {
                     ),
                 }
             }
+            SubscriptionResult::Resume(result) => {
+                let mut bsatn = Vec::new();
+                let mut json = Vec::new();
+                for (query_id, result) in result.subscriptions {
+                    protocol.assert_matches_format_switch(&result.data);
+                    match result.data {
+                        FormatSwitch::Bsatn(update) => bsatn.push(ws::ResumedSubscription { query_id, update }),
+                        FormatSwitch::Json(update) => json.push(ws::ResumedSubscription { query_id, update }),
+                    }
+                }
+                let is_delta = result.is_delta;
+                match protocol {
+                    Protocol::Binary => FormatSwitch::Bsatn(
+                        ws::SubscriptionsResumed {
+                            request_id,
+                            total_host_execution_duration_micros,
+                            is_delta,
+                            subscriptions: bsatn,
+                        }
+                        .into(),
+                    ),
+                    Protocol::Text => FormatSwitch::Json(
+                        ws::SubscriptionsResumed {
+                            request_id,
+                            total_host_execution_duration_micros,
+                            is_delta,
+                            subscriptions: json,
+                        }
+                        .into(),
+                    ),
+                }
+            }
         }
     }
 }
@@ -0,0 +1,553 @@
+//! Token-bucket rate limits on the requests clients make to a database.
+//!
+//! Limits are configured per server, and are enforced both per caller identity,
+//! across all of its connections and HTTP requests to a database,
+//! and per websocket connection.
+
+use crate::worker_metrics::WORKER_METRICS;
+use parking_lot::Mutex;
+use spacetimedb_lib::Identity;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A sustained rate of requests (or bytes), with an allowance for bursts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Rate {
+    /// The number of requests per second which may be sustained indefinitely.
+    pub per_second: NonZeroU32,
+    /// The number of requests which may be made at once after a quiet period.
+    ///
+    /// Default: `per_second`
+    #[serde(default)]
+    pub burst: Option<NonZeroU32>,
+}
+
+impl Rate {
+    fn burst(&self) -> f64 {
+        self.burst.unwrap_or(self.per_second).get().into()
+    }
+
+    fn per_second(&self) -> f64 {
+        self.per_second.get().into()
+    }
+}
+
+/// Limits shared by all requests a caller identity makes to a database,
+/// whether over websocket connections or HTTP.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IdentityRateLimits {
+    #[serde(default)]
+    pub reducer_calls: Option<Rate>,
+    #[serde(default)]
+    pub one_off_queries: Option<Rate>,
+}
+
+/// Limits on a single websocket connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConnectionRateLimits {
+    #[serde(default)]
+    pub reducer_calls: Option<Rate>,
+    #[serde(default)]
+    pub one_off_queries: Option<Rate>,
+    /// The maximum number of query sets subscribed to with `SubscribeSingle` or `SubscribeMulti`
+    /// which a connection may hold at once.
+    ///
+    /// The legacy `Subscribe` message replaces the previous query set, and is not counted.
+    #[serde(default)]
+    pub subscriptions: Option<NonZeroUsize>,
+    /// The rate at which messages are sent to the client.
+    ///
+    /// Rather than being dropped, messages exceeding the rate are delayed.
+    /// A client which keeps falling behind is eventually disconnected,
+    /// as happens to any client which doesn't keep up with its updates.
+    #[serde(default)]
+    pub outbound_bytes: Option<Rate>,
+}
+
+/// Rate limits on client requests, as read from the `[rate-limits]` section of `config.toml`.
+///
+/// By default, no limits are enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitOptions {
+    #[serde(default)]
+    pub per_identity: IdentityRateLimits,
+    #[serde(default)]
+    pub per_connection: ConnectionRateLimits,
+}
+
+/// The kinds of requests subject to a request rate limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RateLimitKind {
+    ReducerCall,
+    OneOffQuery,
+}
+
+impl RateLimitKind {
+    /// Returns the label of this kind in the `rate_limited_requests` metric.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReducerCall => "reducer_call",
+            Self::OneOffQuery => "one_off_query",
+        }
+    }
+}
+
+impl fmt::Display for RateLimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReducerCall => "reducer calls",
+            Self::OneOffQuery => "one-off queries",
+        })
+    }
+}
+
+/// A request was rejected because it exceeded a rate limit.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitError {
+    #[error("too many {kind}, retry in {}ms", retry_after.as_millis())]
+    TooManyRequests { kind: RateLimitKind, retry_after: Duration },
+    #[error("too many subscriptions, at most {max} may be active on a connection")]
+    TooManySubscriptions { max: NonZeroUsize },
+}
+
+impl RateLimitError {
+    /// Returns how long the client should wait before retrying, if retrying can succeed.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::TooManyRequests { retry_after, .. } => Some(*retry_after),
+            Self::TooManySubscriptions { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    rate: Rate,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: Rate, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate.burst(),
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate.per_second()).min(self.rate.burst());
+        self.last_refill = now;
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.rate.burst()
+    }
+
+    /// Checks that `n` tokens are available without taking them,
+    /// or returns how long until they will be.
+    fn check(&mut self, n: f64, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= n {
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((n - self.tokens) / self.rate.per_second()))
+        }
+    }
+
+    /// Takes `n` tokens, or returns how long until they will be available.
+    fn try_take(&mut self, n: f64, now: Instant) -> Result<(), Duration> {
+        self.check(n, now)?;
+        self.tokens -= n;
+        Ok(())
+    }
+
+    /// Takes `n` tokens, going into debt if they aren't available,
+    /// and returns how long until the debt is repaid.
+    ///
+    /// This allows taking more tokens than the bucket can ever hold.
+    fn take(&mut self, n: f64, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= n;
+        Duration::from_secs_f64((-self.tokens).max(0.0) / self.rate.per_second())
+    }
+}
+
+/// The per-identity buckets are split into this many shards,
+/// each behind its own lock, so that requests by different callers rarely contend.
+const IDENTITY_BUCKET_SHARDS: usize = 16;
+
+/// Once a shard holds this many buckets, full buckets are discarded,
+/// as they are indistinguishable from fresh ones.
+const IDENTITY_BUCKETS_PRUNE_THRESHOLD: usize = 1024;
+
+type IdentityBucketKey = (Identity, Identity, RateLimitKind);
+
+#[derive(Debug)]
+struct IdentityBucketShard {
+    buckets: HashMap<IdentityBucketKey, TokenBucket>,
+    /// The number of buckets at which the shard is next pruned.
+    ///
+    /// After pruning, this is at least twice the number of buckets that remain,
+    /// so that a shard of mostly busy buckets isn't scanned on every request.
+    prune_at: usize,
+}
+
+impl Default for IdentityBucketShard {
+    fn default() -> Self {
+        Self {
+            buckets: HashMap::new(),
+            prune_at: IDENTITY_BUCKETS_PRUNE_THRESHOLD,
+        }
+    }
+}
+
+impl IdentityBucketShard {
+    fn bucket(&mut self, key: IdentityBucketKey, rate: Rate, now: Instant) -> &mut TokenBucket {
+        if self.buckets.len() >= self.prune_at {
+            self.buckets.retain(|_, bucket| !bucket.is_full(now));
+            self.prune_at = (2 * self.buckets.len()).max(IDENTITY_BUCKETS_PRUNE_THRESHOLD);
+        }
+        self.buckets.entry(key).or_insert_with(|| TokenBucket::new(rate, now))
+    }
+}
+
+/// Enforces the [`RateLimitOptions`] of a server.
+///
+/// Holds the per-identity buckets, which are shared by all connections to the server,
+/// and hands out a [`ConnectionRateLimiter`] to every websocket connection.
+#[derive(Debug)]
+pub struct RateLimiter {
+    options: RateLimitOptions,
+    identity_buckets: Box<[Mutex<IdentityBucketShard>]>,
+    /// Picks the shard of [`Self::identity_buckets`] for a bucket.
+    shard_hasher: RandomState,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(<_>::default())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(options: RateLimitOptions) -> Self {
+        Self {
+            options,
+            identity_buckets: (0..IDENTITY_BUCKET_SHARDS).map(|_| <_>::default()).collect(),
+            shard_hasher: <_>::default(),
+        }
+    }
+
+    /// Returns a limiter which enforces no limits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn options(&self) -> &RateLimitOptions {
+        &self.options
+    }
+
+    /// Charges a request of the given `kind` by `caller` to `database`
+    /// against the per-identity limits.
+    pub fn check_identity(
+        &self,
+        database: Identity,
+        caller: Identity,
+        kind: RateLimitKind,
+    ) -> Result<(), RateLimitError> {
+        let rate = match kind {
+            RateLimitKind::ReducerCall => self.options.per_identity.reducer_calls,
+            RateLimitKind::OneOffQuery => self.options.per_identity.one_off_queries,
+        };
+        let Some(rate) = rate else { return Ok(()) };
+
+        let now = Instant::now();
+        let key = (database, caller, kind);
+        let shard = self.shard_hasher.hash_one(key) as usize % self.identity_buckets.len();
+        self.identity_buckets[shard]
+            .lock()
+            .bucket(key, rate, now)
+            .try_take(1.0, now)
+            .map_err(|retry_after| rejected(database, kind, retry_after))
+    }
+}
+
+fn rejected(database: Identity, kind: RateLimitKind, retry_after: Duration) -> RateLimitError {
+    WORKER_METRICS
+        .rate_limited_requests
+        .with_label_values(&database, kind.as_str())
+        .inc();
+    RateLimitError::TooManyRequests { kind, retry_after }
+}
+
+/// Enforces the per-connection and per-identity limits on the requests of a single websocket connection.
+#[derive(Debug)]
+pub struct ConnectionRateLimiter {
+    limiter: Arc<RateLimiter>,
+    database: Identity,
+    caller: Identity,
+    reducer_calls: Option<Mutex<TokenBucket>>,
+    one_off_queries: Option<Mutex<TokenBucket>>,
+    outbound_bytes: Option<Mutex<TokenBucket>>,
+}
+
+impl ConnectionRateLimiter {
+    pub fn new(limiter: Arc<RateLimiter>, database: Identity, caller: Identity) -> Self {
+        let now = Instant::now();
+        let ConnectionRateLimits {
+            reducer_calls,
+            one_off_queries,
+            subscriptions: _,
+            outbound_bytes,
+        } = limiter.options.per_connection;
+        let bucket = |rate: Option<Rate>| rate.map(|rate| Mutex::new(TokenBucket::new(rate, now)));
+        Self {
+            reducer_calls: bucket(reducer_calls),
+            one_off_queries: bucket(one_off_queries),
+            outbound_bytes: bucket(outbound_bytes),
+            limiter,
+            database,
+            caller,
+        }
+    }
+
+    /// Returns a limiter which enforces no limits, for connections not attached to a server.
+    pub fn unlimited(caller: Identity) -> Self {
+        Self::new(Arc::new(RateLimiter::unlimited()), Identity::ZERO, caller)
+    }
+
+    /// Charges a request of the given `kind` against the limits of this connection
+    /// and those of its caller identity.
+    ///
+    /// Neither limit is charged unless the request is within both.
+    pub fn check(&self, kind: RateLimitKind) -> Result<(), RateLimitError> {
+        let bucket = match kind {
+            RateLimitKind::ReducerCall => &self.reducer_calls,
+            RateLimitKind::OneOffQuery => &self.one_off_queries,
+        };
+        let Some(bucket) = bucket else {
+            return self.limiter.check_identity(self.database, self.caller, kind);
+        };
+        // Hold the connection's bucket while charging the identity,
+        // so that the token checked for here is still available afterwards.
+        let mut bucket = bucket.lock();
+        let now = Instant::now();
+        bucket
+            .check(1.0, now)
+            .map_err(|retry_after| rejected(self.database, kind, retry_after))?;
+        self.limiter.check_identity(self.database, self.caller, kind)?;
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+
+    /// Checks whether this connection, currently holding `active` query sets,
+    /// may subscribe to another one.
+    ///
+    /// The query sets are counted by the subscription manager,
+    /// so that only subscriptions which were actually added or removed count.
+    pub fn check_subscriptions(&self, active: usize) -> Result<(), RateLimitError> {
+        match self.limiter.options.per_connection.subscriptions {
+            Some(max) if active >= max.get() => {
+                WORKER_METRICS
+                    .rate_limited_requests
+                    .with_label_values(&self.database, "subscription")
+                    .inc();
+                Err(RateLimitError::TooManySubscriptions { max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Charges a message of `len` bytes sent to the client against the outbound limit,
+    /// and returns how long to wait before sending further messages.
+    pub fn throttle_outbound(&self, len: usize) -> Duration {
+        let Some(bucket) = &self.outbound_bytes else {
+            return Duration::ZERO;
+        };
+        let delay = bucket.lock().take(len as f64, Instant::now());
+        if !delay.is_zero() {
+            WORKER_METRICS
+                .rate_limited_requests
+                .with_label_values(&self.database, "outbound_bytes")
+                .inc();
+        }
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(per_second: u32, burst: u32) -> Rate {
+        Rate {
+            per_second: NonZeroU32::new(per_second).unwrap(),
+            burst: NonZeroU32::new(burst),
+        }
+    }
+
+    #[test]
+    fn bucket_allows_bursts_and_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(rate(10, 2), start);
+
+        assert_eq!(bucket.try_take(1.0, start), Ok(()));
+        assert_eq!(bucket.try_take(1.0, start), Ok(()));
+        assert_eq!(bucket.try_take(1.0, start), Err(Duration::from_millis(100)));
+
+        let later = start + Duration::from_millis(100);
+        assert_eq!(bucket.try_take(1.0, later), Ok(()));
+        assert!(bucket.try_take(1.0, later).is_err());
+
+        assert!(bucket.is_full(later + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn bucket_debt_delays() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(rate(1000, 1000), start);
+
+        assert_eq!(bucket.take(500.0, start), Duration::ZERO);
+        assert_eq!(bucket.take(1500.0, start), Duration::from_secs(1));
+        assert_eq!(bucket.take(0.0, start + Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[test]
+    fn identity_limit_is_shared_across_connections() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitOptions {
+            per_identity: IdentityRateLimits {
+                reducer_calls: Some(rate(1, 1)),
+                one_off_queries: None,
+            },
+            ..<_>::default()
+        }));
+        let db = Identity::from_claims("localhost", "db");
+        let alice = Identity::from_claims("localhost", "alice");
+        let bob = Identity::from_claims("localhost", "bob");
+
+        let first = ConnectionRateLimiter::new(limiter.clone(), db, alice);
+        let second = ConnectionRateLimiter::new(limiter.clone(), db, alice);
+        assert_eq!(first.check(RateLimitKind::ReducerCall), Ok(()));
+        assert!(matches!(
+            second.check(RateLimitKind::ReducerCall),
+            Err(RateLimitError::TooManyRequests {
+                kind: RateLimitKind::ReducerCall,
+                ..
+            })
+        ));
+        assert_eq!(second.check(RateLimitKind::OneOffQuery), Ok(()));
+        assert_eq!(limiter.check_identity(db, bob, RateLimitKind::ReducerCall), Ok(()));
+    }
+
+    #[test]
+    fn rejected_requests_are_not_charged() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitOptions {
+            per_identity: IdentityRateLimits {
+                reducer_calls: Some(rate(1, 1)),
+                one_off_queries: None,
+            },
+            per_connection: ConnectionRateLimits {
+                reducer_calls: Some(rate(1, 1)),
+                ..<_>::default()
+            },
+        }));
+        let db = Identity::from_claims("localhost", "db");
+        let alice = Identity::from_claims("localhost", "alice");
+
+        // The identity's only token is spent by another connection,
+        // so this one's request is rejected by the identity limit ...
+        assert_eq!(limiter.check_identity(db, alice, RateLimitKind::ReducerCall), Ok(()));
+        let conn = ConnectionRateLimiter::new(limiter, db, alice);
+        assert!(conn.check(RateLimitKind::ReducerCall).is_err());
+        // ... without spending the connection's token.
+        assert_eq!(conn.reducer_calls.as_ref().unwrap().lock().tokens, 1.0);
+    }
+
+    #[test]
+    fn identity_buckets_are_pruned() {
+        let mut shard = IdentityBucketShard::default();
+        let start = Instant::now();
+        let db = Identity::ZERO;
+        for i in 0..IDENTITY_BUCKETS_PRUNE_THRESHOLD as u64 {
+            let caller = Identity::from_u256(i.into());
+            shard
+                .bucket((db, caller, RateLimitKind::ReducerCall), rate(1, 1), start)
+                .try_take(1.0, start)
+                .unwrap();
+        }
+
+        // All buckets are busy, so none are discarded,
+        // and the shard isn't scanned again until it has doubled in size.
+        let busy = (db, Identity::ONE, RateLimitKind::OneOffQuery);
+        shard.bucket(busy, rate(1, 1), start);
+        assert_eq!(shard.buckets.len(), IDENTITY_BUCKETS_PRUNE_THRESHOLD + 1);
+        assert_eq!(shard.prune_at, 2 * IDENTITY_BUCKETS_PRUNE_THRESHOLD);
+
+        // Once the buckets have refilled, they are discarded.
+        let later = start + Duration::from_secs(1);
+        shard.prune_at = 0;
+        shard.bucket(busy, rate(1, 1), later);
+        assert_eq!(shard.buckets.len(), 1);
+        assert_eq!(shard.prune_at, IDENTITY_BUCKETS_PRUNE_THRESHOLD);
+    }
+
+    #[test]
+    fn subscriptions_are_counted() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitOptions {
+            per_connection: ConnectionRateLimits {
+                subscriptions: NonZeroUsize::new(1),
+                ..<_>::default()
+            },
+            ..<_>::default()
+        }));
+        let conn = ConnectionRateLimiter::new(limiter, Identity::ZERO, Identity::ZERO);
+
+        assert_eq!(conn.check_subscriptions(0), Ok(()));
+        assert!(matches!(
+            conn.check_subscriptions(1),
+            Err(RateLimitError::TooManySubscriptions { .. })
+        ));
+
+        let unlimited = ConnectionRateLimiter::unlimited(Identity::ZERO);
+        assert_eq!(unlimited.check_subscriptions(usize::MAX), Ok(()));
+    }
+
+    #[test]
+    fn options_from_toml() {
+        let options: RateLimitOptions = toml::from_str(
+            r#"
+            [per-identity]
+            reducer-calls = { per-second = 50, burst = 100 }
+
+            [per-connection]
+            subscriptions = 64
+            outbound-bytes = { per-second = 1048576 }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(options.per_identity.reducer_calls, Some(rate(50, 100)));
+        assert_eq!(options.per_identity.one_off_queries, None);
+        assert_eq!(options.per_connection.subscriptions, NonZeroUsize::new(64));
+        assert_eq!(
+            options.per_connection.outbound_bytes,
+            Some(Rate {
+                per_second: NonZeroU32::new(1048576).unwrap(),
+                burst: None,
+            })
+        );
+    }
+}
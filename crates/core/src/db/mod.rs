@@ -10,6 +10,7 @@ use spacetimedb_datastore::{locking_tx_datastore::datastore::TxMetrics, traits::
 pub mod persistence;
 pub mod relational_db;
 pub mod snapshot;
+pub mod tx_history;
 pub mod update;
 
 /// Whether SpacetimeDB is run in memory, or persists objects and
@@ -30,6 +31,10 @@ pub struct Config {
     pub storage: Storage,
     /// Specifies the page pool max size in bytes.
     pub page_pool_max_size: Option<usize>,
+    /// Specifies the maximum size in bytes of the table pages each database keeps in memory,
+    /// beyond which cold pages are evicted to the database's latest snapshot.
+    /// Only applies to [`Storage::Disk`].
+    pub table_memory_limit: Option<usize>,
 }
 
 /// A message that is processed by the [`spawn_metrics_recorder`] actor.
@@ -1,3 +1,4 @@
+use crate::db::tx_history::TxHistory;
 use crate::db::MetricsRecorderQueue;
 use crate::error::{DBError, DatabaseError, RestoreSnapshotError};
 use crate::messages::control_db::HostType;
@@ -7,19 +8,23 @@ use crate::worker_metrics::WORKER_METRICS;
 use anyhow::{anyhow, Context};
 use enum_map::EnumMap;
 use fs2::FileExt;
+use parking_lot::{Mutex, RwLock};
 use spacetimedb_commitlog as commitlog;
 use spacetimedb_commitlog::repo::OnNewSegmentFn;
 use spacetimedb_data_structures::map::IntSet;
 use spacetimedb_datastore::db_metrics::DB_METRICS;
 use spacetimedb_datastore::error::{DatastoreError, TableError};
 use spacetimedb_datastore::execution_context::{ReducerContext, Workload, WorkloadType};
-use spacetimedb_datastore::locking_tx_datastore::datastore::TxMetrics;
+use spacetimedb_datastore::locking_tx_datastore::datastore::{Replay, ReplayError, TxMetrics};
 use spacetimedb_datastore::locking_tx_datastore::state_view::{
     IterByColEqMutTx, IterByColRangeMutTx, IterMutTx, IterTx, StateView,
 };
 use spacetimedb_datastore::locking_tx_datastore::{MutTxId, TxId};
 use spacetimedb_datastore::system_tables::{system_tables, StModuleRow};
-use spacetimedb_datastore::system_tables::{StFields, StVarFields, StVarName, StVarRow, ST_MODULE_ID, ST_VAR_ID};
+use spacetimedb_datastore::system_tables::{
+    StFields, StSlowEventFields, StSlowEventRow, StVarFields, StVarName, StVarRow, ST_MODULE_ID,
+    ST_RESERVED_SEQUENCE_RANGE, ST_SLOW_EVENT_CAPACITY, ST_SLOW_EVENT_ID, ST_VAR_ID,
+};
 use spacetimedb_datastore::traits::{
     InsertFlags, IsolationLevel, Metadata, MutTx as _, MutTxDatastore, Program, RowTypeForTable, Tx as _, TxDatastore,
     UpdateFlags,
@@ -40,8 +45,10 @@ use spacetimedb_lib::Identity;
 use spacetimedb_paths::server::{CommitLogDir, ReplicaDir, SnapshotsPath};
 use spacetimedb_primitives::*;
 use spacetimedb_sats::algebraic_type::fmt::fmt_algebraic_type;
+use spacetimedb_sats::algebraic_value::de::ValueDeserializer;
+use spacetimedb_sats::buffer::{BufReader, DecodeError};
 use spacetimedb_sats::memory_usage::MemoryUsage;
-use spacetimedb_sats::{AlgebraicType, AlgebraicValue, ProductType, ProductValue};
+use spacetimedb_sats::{AlgebraicType, AlgebraicValue, Deserialize as _, ProductType, ProductValue};
 use spacetimedb_schema::def::{ModuleDef, TableDef, ViewDef};
 use spacetimedb_schema::schema::{
     ColumnSchema, IndexSchema, RowLevelSecuritySchema, Schema, SequenceSchema, TableSchema,
@@ -60,10 +67,11 @@ use std::io;
 use std::ops::RangeBounds;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::watch;
 
 pub use super::persistence::{DiskSizeFn, Durability, Persistence};
-pub use super::snapshot::SnapshotWorker;
+pub use super::snapshot::{SnapshotPolicy, SnapshotWorker};
 pub use durability::{DurableOffset, TxOffset};
 
 // NOTE(cloutiertyler): We should be using the associated types, but there is
@@ -82,6 +90,13 @@ pub type Txdata = commitlog::payload::Txdata<ProductValue>;
 /// relaxed post 1.0.
 pub const ONLY_MODULE_VERSION: &str = "0.0.1";
 
+/// The system variables making up the [`SnapshotPolicy`].
+const SNAPSHOT_POLICY_VARS: [StVarName; 3] = [
+    StVarName::SnapshotEveryTxs,
+    StVarName::SnapshotInterval,
+    StVarName::SnapshotCommitlogBytes,
+];
+
 /// The set of clients considered connected to the database.
 ///
 /// A client is considered connected if there exists a corresponding row in the
@@ -101,6 +116,9 @@ pub struct RelationalDB {
     inner: Locking,
     durability: Option<Arc<Durability>>,
     snapshot_worker: Option<SnapshotWorker>,
+    /// The [`SnapshotPolicy`] as configured in `st_var`.
+    /// Only maintained if `snapshot_worker` is `Some`.
+    snapshot_policy: Arc<RwLock<SnapshotPolicy>>,
 
     row_count_fn: RowCountFn,
     /// Function to determine the durable size on disk.
@@ -113,6 +131,16 @@ pub struct RelationalDB {
     /// An async queue for recording transaction metrics off the main thread
     metrics_recorder_queue: Option<MetricsRecorderQueue>,
 
+    /// Slow events waiting to be written to `st_slow_event`.
+    /// Shared between clones, like the datastore they're written to.
+    /// See [`Self::record_slow_event`].
+    slow_events: Arc<Mutex<SlowEventBuffer>>,
+
+    /// The rows changed by recently committed transactions,
+    /// from which resuming clients are sent the changes they missed.
+    /// See [`Self::changes_since`].
+    tx_history: Arc<Mutex<TxHistory>>,
+
     // DO NOT ADD FIELDS AFTER THIS.
     // By default, fields are dropped in declaration order.
     // We want to release the file lock last.
@@ -120,15 +148,39 @@ pub struct RelationalDB {
     _lock: LockFile,
 }
 
-/// Perform a snapshot every `SNAPSHOT_FREQUENCY` transactions.
-// TODO(config): Allow DBs to specify how frequently to snapshot.
-// TODO(bikeshedding): Snapshot based on number of bytes written to commitlog, not tx offsets.
+/// Perform a snapshot every `SNAPSHOT_FREQUENCY` transactions,
+/// unless the database configures a different [`SnapshotPolicy`] in `st_var`.
 //
-// NOTE: Replicas must agree on the snapshot frequency. By making them consult
-// this value, later introduction of dynamic configuration will allow the
-// compiler to find external dependencies.
+// NOTE: Replicas must agree on the snapshot frequency. This holds for the
+// configured frequency, too, as `st_var` is part of the replicated state.
 pub const SNAPSHOT_FREQUENCY: u64 = 1_000_000;
 
+/// Buffered slow events are written once this many have accumulated, ...
+const SLOW_EVENT_BATCH_SIZE: usize = 64;
+/// ... or once this much time has passed since they were last written.
+///
+/// The host checks for due events at this interval too,
+/// so buffered events are written even if no new ones are recorded.
+pub const SLOW_EVENT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Slow events which have not yet been written to `st_slow_event`.
+struct SlowEventBuffer {
+    events: Vec<StSlowEventRow>,
+    last_flush: Instant,
+}
+
+impl SlowEventBuffer {
+    fn is_due(&self) -> bool {
+        !self.events.is_empty()
+            && (self.events.len() >= SLOW_EVENT_BATCH_SIZE || self.last_flush.elapsed() >= SLOW_EVENT_FLUSH_INTERVAL)
+    }
+
+    fn take(&mut self) -> Vec<StSlowEventRow> {
+        self.last_flush = Instant::now();
+        std::mem::take(&mut self.events)
+    }
+}
+
 impl std::fmt::Debug for RelationalDB {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RelationalDB")
@@ -155,6 +207,7 @@ impl RelationalDB {
             inner,
             durability,
             snapshot_worker,
+            snapshot_policy: <_>::default(),
 
             database_identity,
             owner_identity,
@@ -165,6 +218,12 @@ impl RelationalDB {
             workload_type_to_exec_counters,
             metrics_recorder_queue,
 
+            slow_events: Arc::new(Mutex::new(SlowEventBuffer {
+                events: Vec::new(),
+                last_flush: Instant::now(),
+            })),
+            tx_history: <_>::default(),
+
             _lock: lock,
         }
     }
@@ -261,6 +320,7 @@ impl RelationalDB {
         mut persistence: Option<Persistence>,
         metrics_recorder_queue: Option<MetricsRecorderQueue>,
         page_pool: PagePool,
+        table_memory_limit: Option<usize>,
     ) -> Result<(Self, ConnectedClients), DBError> {
         log::trace!("[{database_identity}] DATABASE: OPEN");
 
@@ -286,6 +346,7 @@ impl RelationalDB {
             durable_tx_offset,
             min_commitlog_offset,
             page_pool,
+            table_memory_limit,
         )?;
         if let Some(persistence) = &mut persistence {
             // Sanity check because the snapshot worker could've been used before.
@@ -316,6 +377,7 @@ impl RelationalDB {
             metrics_recorder_queue,
         );
         db.migrate_system_tables()?;
+        db.load_snapshot_policy()?;
 
         if let Some(meta) = db.metadata()? {
             if meta.database_identity != database_identity {
@@ -340,6 +402,87 @@ impl RelationalDB {
         Ok((db, connected_clients))
     }
 
+    /// Open an in-memory copy of the database stored in `root`,
+    /// as it was just before the transaction at `tx_offset` was committed.
+    ///
+    /// The state is restored from the newest snapshot older than `tx_offset`,
+    /// or bootstrapped if there is none,
+    /// and the commitlog is replayed up to, but excluding, `tx_offset`.
+    ///
+    /// Unlike [`Self::open`], this never modifies the snapshots or commitlog in `root`,
+    /// so it is safe to call while the database is running.
+    /// The copy is locked via `scratch_dir` instead,
+    /// and hands the transactions committed to it to `persistence`, if any.
+    ///
+    /// Alongside `Self`, the transaction at `tx_offset` is returned as it was committed,
+    /// or `None` if the commitlog ends before `tx_offset`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_before_tx(
+        root: &ReplicaDir,
+        scratch_dir: &ReplicaDir,
+        database_identity: Identity,
+        owner_identity: Identity,
+        replica_id: u64,
+        tx_offset: TxOffset,
+        persistence: Option<Persistence>,
+        page_pool: PagePool,
+    ) -> Result<(Self, Option<Txdata>), DBError> {
+        log::trace!("[{database_identity}] DATABASE: OPEN BEFORE TX {tx_offset}");
+
+        let lock = LockFile::lock(scratch_dir)?;
+
+        let snapshot_repo = root
+            .snapshots()
+            .is_dir()
+            .then(|| SnapshotRepository::open(root.snapshots(), database_identity, replica_id))
+            .transpose()
+            .map_err(Box::new)?;
+        let snapshot_offset = match (snapshot_repo.as_ref(), tx_offset.checked_sub(1)) {
+            (Some(repo), Some(upper_bound)) => repo.latest_snapshot_older_than(upper_bound).map_err(Box::new)?,
+            _ => None,
+        };
+        let inner = match snapshot_repo.zip(snapshot_offset) {
+            Some((repo, snapshot_offset)) => {
+                log::info!("[{database_identity}] DATABASE: restoring snapshot of tx_offset {snapshot_offset}");
+                let snapshot = repo.read_snapshot(snapshot_offset, &page_pool).map_err(Box::new)?;
+                if snapshot.database_identity != database_identity {
+                    return Err(RestoreSnapshotError::IdentityMismatch {
+                        expected: database_identity,
+                        actual: snapshot.database_identity,
+                    }
+                    .into());
+                }
+                Locking::restore_from_snapshot(snapshot, page_pool, None)?
+            }
+            None => Locking::bootstrap(database_identity, page_pool)?,
+        };
+
+        let mut replay = inner.replay(|_| {});
+        let start_tx_offset = replay.next_tx_offset();
+        if start_tx_offset < tx_offset {
+            commitlog::fold_transaction_range(root.commit_log(), start_tx_offset..tx_offset, ReplayRange(&mut replay))
+                .map_err(anyhow::Error::from)?;
+        }
+        let end_tx_offset = replay.next_tx_offset();
+        if end_tx_offset != tx_offset {
+            return Err(anyhow!("commitlog ends at tx offset {end_tx_offset}, before {tx_offset}").into());
+        }
+        inner.rebuild_state_after_replay()?;
+
+        // NOTE: Unlike `open`, we don't migrate the system tables here,
+        // as that would commit a transaction the original history doesn't have.
+        let db = Self::new(lock, database_identity, owner_identity, inner, persistence, None);
+
+        let committed = commitlog::transactions_from(root.commit_log(), tx_offset, &InspectTx { db: &db })?
+            .next()
+            .transpose()
+            .map_err(anyhow::Error::from)?
+            .filter(|tx| tx.offset == tx_offset)
+            .map(|tx| tx.txdata);
+
+        Ok((db, committed))
+    }
+
     fn migrate_system_tables(&self) -> Result<(), DBError> {
         let mut tx = self.begin_mut_tx(IsolationLevel::Serializable, Workload::Internal);
         for schema in system_tables() {
@@ -441,20 +584,30 @@ impl RelationalDB {
         durable_tx_offset: Option<TxOffset>,
         min_commitlog_offset: TxOffset,
         page_pool: PagePool,
+        table_memory_limit: Option<usize>,
     ) -> Result<Locking, RestoreSnapshotError> {
         // Try to load the `ReconstructedSnapshot` at `snapshot_offset`.
+        //
+        // With a memory limit, only the system tables are read up front,
+        // leaving user tables on disk to be loaded on demand,
+        // so that a database larger than the limit can still be restored.
         fn try_load_snapshot(
             database_identity: &Identity,
             snapshot_repo: &SnapshotRepository,
             snapshot_offset: TxOffset,
             page_pool: &PagePool,
+            table_memory_limit: Option<usize>,
         ) -> Result<ReconstructedSnapshot, Box<SnapshotError>> {
             log::info!("[{database_identity}] DATABASE: restoring snapshot of tx_offset {snapshot_offset}");
             let start = std::time::Instant::now();
 
-            let snapshot = snapshot_repo
-                .read_snapshot(snapshot_offset, page_pool)
-                .map_err(Box::new)?;
+            let snapshot = match table_memory_limit {
+                None => snapshot_repo.read_snapshot(snapshot_offset, page_pool),
+                Some(_) => snapshot_repo.read_snapshot_lazy(snapshot_offset, page_pool, |table_id| {
+                    table_id.0 < ST_RESERVED_SEQUENCE_RANGE
+                }),
+            }
+            .map_err(Box::new)?;
 
             let elapsed_time = start.elapsed();
 
@@ -475,10 +628,11 @@ impl RelationalDB {
             database_identity: &Identity,
             snapshot: ReconstructedSnapshot,
             page_pool: PagePool,
+            table_memory_limit: Option<usize>,
         ) -> Result<Locking, Box<DBError>> {
             let start = std::time::Instant::now();
             let snapshot_offset = snapshot.tx_offset;
-            Locking::restore_from_snapshot(snapshot, page_pool)
+            Locking::restore_from_snapshot(snapshot, page_pool, table_memory_limit)
                 .inspect(|_| {
                     let elapsed_time = start.elapsed();
 
@@ -543,7 +697,13 @@ impl RelationalDB {
                     log::debug!("snapshot_offset={snapshot_offset} min_commitlog_offset={min_commitlog_offset}");
                     break;
                 }
-                match try_load_snapshot(&database_identity, snapshot_repo, snapshot_offset, &page_pool) {
+                match try_load_snapshot(
+                    &database_identity,
+                    snapshot_repo,
+                    snapshot_offset,
+                    &page_pool,
+                    table_memory_limit,
+                ) {
                     Ok(snapshot) if snapshot.database_identity != database_identity => {
                         return Err(RestoreSnapshotError::IdentityMismatch {
                             expected: database_identity,
@@ -551,7 +711,7 @@ impl RelationalDB {
                         });
                     }
                     Ok(snapshot) => {
-                        return restore_from_snapshot(&database_identity, snapshot, page_pool)
+                        return restore_from_snapshot(&database_identity, snapshot, page_pool, table_memory_limit)
                             .map_err(RestoreSnapshotError::Datastore);
                     }
                     Err(e) => {
@@ -640,7 +800,14 @@ impl RelationalDB {
     pub fn update_data_size_metrics(&self) {
         let cs = self.inner.committed_state.read();
 
-        cs.report_data_size(self.database_identity)
+        cs.report_data_size(self.database_identity);
+        cs.report_page_cache(self.database_identity);
+    }
+
+    /// Limits the memory used by table pages to roughly `limit` bytes.
+    /// See [`Locking::set_table_memory_limit`].
+    pub fn set_table_memory_limit(&self, limit: Option<usize>) {
+        self.inner.set_table_memory_limit(limit)
     }
 
     pub fn encode_row(row: &ProductValue, bytes: &mut Vec<u8>) {
@@ -731,6 +898,10 @@ impl RelationalDB {
         r
     }
 
+    /// Begin a read-only transaction over a snapshot of the committed state.
+    ///
+    /// The transaction does not block mutable transactions from committing,
+    /// nor does it observe their effects.
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn begin_tx(&self, workload: Workload) -> Tx {
         log::trace!("BEGIN TX");
@@ -739,6 +910,18 @@ impl RelationalDB {
         r
     }
 
+    /// Begin a read-only transaction which holds a shared lock on the committed state.
+    ///
+    /// No mutable transaction can commit until the returned transaction is released.
+    /// See [`Locking::begin_tx_locked`].
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn begin_tx_locked(&self, workload: Workload) -> Tx {
+        log::trace!("BEGIN LOCKED TX");
+        let r = self.inner.begin_tx_locked(workload);
+        log::trace!("ACQUIRED LOCKED TX");
+        r
+    }
+
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn rollback_mut_tx(&self, tx: MutTx) -> (TxOffset, TxMetrics, String) {
         log::trace!("ROLLBACK MUT TX");
@@ -768,6 +951,7 @@ impl RelationalDB {
         };
 
         self.maybe_do_snapshot(&tx_data);
+        self.tx_history.lock().record(&tx_data);
 
         if let Some(durability) = &self.durability {
             Self::do_durability(&**durability, reducer_context.as_ref(), &tx_data)
@@ -789,6 +973,7 @@ impl RelationalDB {
         };
 
         self.maybe_do_snapshot(&tx_data);
+        self.tx_history.lock().record(&tx_data);
 
         if let Some(durability) = &self.durability {
             Self::do_durability(&**durability, tx.ctx.reducer_context(), &tx_data)
@@ -797,6 +982,20 @@ impl RelationalDB {
         Ok(Some((tx_data, tx_metrics, tx)))
     }
 
+    /// Returns the id of the history of recent transactions kept by this database.
+    ///
+    /// Clients are told this id along with their tx offsets,
+    /// and must present it together with an offset to [`Self::changes_since`].
+    pub fn tx_history_id(&self) -> u64 {
+        self.tx_history.lock().id()
+    }
+
+    /// Returns the net changes made by the transactions after `since`, up to and including `up_to`,
+    /// or `None` if they are no longer known. See [`TxHistory::changes_since`].
+    pub fn changes_since(&self, history_id: u64, since: TxOffset, up_to: TxOffset) -> Option<TxData> {
+        self.tx_history.lock().changes_since(history_id, since, up_to)
+    }
+
     /// If `(tx_data, ctx)` should be appended to the commitlog, do so.
     ///
     /// Note that by this stage,
@@ -878,16 +1077,75 @@ impl RelationalDB {
     /// which stores the committed-not-yet-durable state.
     /// This requires a small amount of additional logic when restoring from a snapshot
     /// to ensure we don't restore a snapshot more recent than the durable TX offset.
+    ///
+    /// Transactions changing the [`SnapshotPolicy`] in `st_var` take effect
+    /// starting with the transaction itself.
     fn maybe_do_snapshot(&self, tx_data: &TxData) {
         if let Some(snapshot_worker) = &self.snapshot_worker {
+            let policy = self.update_snapshot_policy(snapshot_worker, tx_data);
             if let Some(tx_offset) = tx_data.tx_offset() {
-                if tx_offset % SNAPSHOT_FREQUENCY == 0 {
+                if policy.is_snapshot_tx(tx_offset) {
                     snapshot_worker.request_snapshot();
                 }
             }
         }
     }
 
+    /// Read the [`SnapshotPolicy`] from `st_var` and pass it on to the [`SnapshotWorker`].
+    fn load_snapshot_policy(&self) -> Result<(), DBError> {
+        let Some(snapshot_worker) = &self.snapshot_worker else {
+            return Ok(());
+        };
+        let policy = self.with_read_only(Workload::Internal, |tx| {
+            let mut policy = SnapshotPolicy::default();
+            for name in SNAPSHOT_POLICY_VARS {
+                let value = match self.read_var(tx, name)? {
+                    Some(StVarValue::U64(value)) => Some(value),
+                    _ => None,
+                };
+                policy.set_var(name, value);
+            }
+            Ok::<_, DBError>(policy)
+        })?;
+        *self.snapshot_policy.write() = policy;
+        snapshot_worker.set_policy(policy);
+        Ok(())
+    }
+
+    /// Apply the changes `tx_data` makes to the snapshot variables in `st_var`
+    /// to the cached [`SnapshotPolicy`], and return the resulting policy.
+    fn update_snapshot_policy(&self, snapshot_worker: &SnapshotWorker, tx_data: &TxData) -> SnapshotPolicy {
+        let old_policy = *self.snapshot_policy.read();
+        let mut policy = old_policy;
+
+        // `write_var` deletes the old row before inserting the new one,
+        // so apply deletes (resetting to the default) first.
+        let st_var_rows = |(table_id, rows): (&TableId, &Arc<[ProductValue]>)| {
+            (*table_id == ST_VAR_ID).then(|| rows.clone()).into_iter()
+        };
+        let deletes = tx_data.deletes().flat_map(st_var_rows).map(|rows| (rows, false));
+        let inserts = tx_data.inserts().flat_map(st_var_rows).map(|rows| (rows, true));
+        for (rows, inserted) in deletes.chain(inserts) {
+            for row in rows.iter() {
+                let Ok(var) = StVarRow::deserialize(ValueDeserializer::new(AlgebraicValue::Product(row.clone())))
+                else {
+                    continue;
+                };
+                let value = match var.value {
+                    StVarValue::U64(value) if inserted => Some(value),
+                    _ => None,
+                };
+                policy.set_var(var.name, value);
+            }
+        }
+
+        if policy != old_policy {
+            *self.snapshot_policy.write() = policy;
+            snapshot_worker.set_policy(policy);
+        }
+        policy
+    }
+
     /// Subscribe to a channel of snapshot offsets.
     ///
     /// If a `snapshot_repo` was provided when this database was opened, this method
@@ -897,6 +1155,12 @@ impl RelationalDB {
         self.snapshot_worker.as_ref().map(|snap| snap.subscribe())
     }
 
+    /// The [`SnapshotWorker`] of this database,
+    /// or `None` if snapshots are not enabled.
+    pub fn snapshot_worker(&self) -> Option<&SnapshotWorker> {
+        self.snapshot_worker.as_ref()
+    }
+
     /// Run a fallible function in a transaction.
     ///
     /// If the supplied function returns `Ok`, the transaction is automatically
@@ -1212,7 +1476,11 @@ impl RelationalDB {
     ) -> Result<Constraints, DBError> {
         let table = self.inner.schema_for_table_mut_tx(tx, table_id)?;
 
-        let index = table.indexes.iter().find(|i| i.index_algorithm.columns() == *cols);
+        let index = table.indexes.iter().find(|i| {
+            i.index_algorithm
+                .columns()
+                .is_some_and(|index_cols| index_cols == *cols)
+        });
         let cols_set = ColSet::from(cols);
         let unique_constraint = table
             .constraints
@@ -1430,7 +1698,6 @@ impl RelationalDB {
     }
 
     /// Read the value of [ST_VARNAME_SLOW_SUB] from `st_var`
-    #[allow(dead_code)]
     pub(crate) fn sub_limit(&self, tx: &Tx) -> Result<Option<u64>, DBError> {
         if let Some(StVarValue::U64(ms)) = self.read_var(tx, StVarName::SlowSubThreshold)? {
             return Ok(Some(ms));
@@ -1439,7 +1706,6 @@ impl RelationalDB {
     }
 
     /// Read the value of [ST_VARNAME_SLOW_INC] from `st_var`
-    #[allow(dead_code)]
     pub(crate) fn incr_limit(&self, tx: &Tx) -> Result<Option<u64>, DBError> {
         if let Some(StVarValue::U64(ms)) = self.read_var(tx, StVarName::SlowIncThreshold)? {
             return Ok(Some(ms));
@@ -1447,6 +1713,96 @@ impl RelationalDB {
         Ok(None)
     }
 
+    /// Read the value of [ST_VARNAME_SLOW_REDUCER] from `st_var`,
+    /// within the reducer's own transaction.
+    pub(crate) fn reducer_limit(&self, tx: &MutTx) -> Result<Option<u64>, DBError> {
+        let name: AlgebraicValue = StVarName::SlowReducerThreshold.into();
+        if let Some(row_ref) = self
+            .iter_by_col_eq_mut(tx, ST_VAR_ID, StVarFields::Name.col_id(), &name)?
+            .next()
+        {
+            if let StVarValue::U64(ms) = StVarRow::try_from(row_ref)?.value {
+                return Ok(Some(ms));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Record a slow query, subscription or reducer call in `st_slow_event`.
+    ///
+    /// Events are buffered and written in batches,
+    /// see [`Self::flush_slow_events`].
+    /// The buffer is flushed if it is full or hasn't been flushed recently,
+    /// so the caller must not be holding a transaction on this database.
+    /// Buffered events are also flushed periodically by the host,
+    /// so they become visible even if no further events are recorded.
+    pub fn record_slow_event(&self, row: StSlowEventRow) -> Result<(), DBError> {
+        self.buffer_slow_event(row);
+        self.flush_slow_events_if_due()
+    }
+
+    /// Buffer a slow event without writing it to `st_slow_event`.
+    ///
+    /// Unlike [`Self::record_slow_event`],
+    /// this may be called while holding a transaction on this database.
+    /// The event will be written by the next flush.
+    pub fn buffer_slow_event(&self, row: StSlowEventRow) {
+        self.slow_events.lock().events.push(row);
+    }
+
+    /// Write the buffered slow events to `st_slow_event`,
+    /// if there are enough of them or they haven't been written recently.
+    ///
+    /// The caller must not be holding a transaction on this database.
+    pub fn flush_slow_events_if_due(&self) -> Result<(), DBError> {
+        let events = {
+            let mut buffer = self.slow_events.lock();
+            if !buffer.is_due() {
+                return Ok(());
+            }
+            buffer.take()
+        };
+        self.write_slow_events(events)
+    }
+
+    /// Write all buffered slow events to `st_slow_event`.
+    ///
+    /// The caller must not be holding a transaction on this database.
+    pub fn flush_slow_events(&self) -> Result<(), DBError> {
+        let events = self.slow_events.lock().take();
+        self.write_slow_events(events)
+    }
+
+    /// Insert `events` into `st_slow_event` in a single transaction,
+    /// evicting the oldest events so that at most [ST_SLOW_EVENT_CAPACITY] remain.
+    ///
+    /// `st_slow_event` is a persistent system table,
+    /// so every flush commits a transaction that is written to the commitlog.
+    /// This is why events are batched, and why nothing is written while the buffer is empty.
+    fn write_slow_events(&self, events: Vec<StSlowEventRow>) -> Result<(), DBError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.with_auto_commit(Workload::Internal, |tx| {
+            for row in &events {
+                tx.insert_via_serialize_bsatn(ST_SLOW_EVENT_ID, row)?;
+            }
+
+            let len = self.table_row_count_mut(tx, ST_SLOW_EVENT_ID).unwrap_or(0) as usize;
+            if len > ST_SLOW_EVENT_CAPACITY {
+                // Event ids are allocated in increasing order,
+                // so the oldest events come first in the primary key index.
+                let oldest = self
+                    .iter_by_col_range_mut(tx, ST_SLOW_EVENT_ID, StSlowEventFields::EventId.col_id(), ..)?
+                    .take(len - ST_SLOW_EVENT_CAPACITY)
+                    .map(|row_ref| row_ref.pointer())
+                    .collect::<Vec<_>>();
+                self.delete(tx, ST_SLOW_EVENT_ID, oldest);
+            }
+            Ok(())
+        })
+    }
+
     /// Read the value of a system variable from `st_var`
     pub(crate) fn read_var(&self, tx: &Tx, name: StVarName) -> Result<Option<StVarValue>, DBError> {
         if let Some(row_ref) = self
@@ -1566,6 +1922,135 @@ where
     Ok(())
 }
 
+/// Adapts a [`Replay`] to commitlog traversals which can also fail with an [`io::Error`].
+struct ReplayRange<'a, F>(&'a mut Replay<F>);
+
+#[derive(Debug, thiserror::Error)]
+enum ReplayRangeError {
+    #[error(transparent)]
+    Replay(#[from] commitlog::payload::txdata::DecoderError<ReplayError>),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<DecodeError> for ReplayRangeError {
+    fn from(e: DecodeError) -> Self {
+        Self::Replay(e.into())
+    }
+}
+
+impl From<commitlog::error::Traversal> for ReplayRangeError {
+    fn from(e: commitlog::error::Traversal) -> Self {
+        Self::Replay(e.into())
+    }
+}
+
+impl<F: FnMut(u64)> commitlog::Decoder for ReplayRange<'_, F> {
+    type Record = Txdata;
+    type Error = ReplayRangeError;
+
+    fn decode_record<'a, R: BufReader<'a>>(
+        &self,
+        version: u8,
+        tx_offset: u64,
+        reader: &mut R,
+    ) -> Result<Self::Record, Self::Error> {
+        Ok(commitlog::Decoder::decode_record(&*self.0, version, tx_offset, reader)?)
+    }
+
+    fn consume_record<'a, R: BufReader<'a>>(
+        &self,
+        version: u8,
+        tx_offset: u64,
+        reader: &mut R,
+    ) -> Result<(), Self::Error> {
+        Ok(commitlog::Decoder::consume_record(
+            &*self.0, version, tx_offset, reader,
+        )?)
+    }
+
+    fn skip_record<'a, R: BufReader<'a>>(
+        &self,
+        version: u8,
+        tx_offset: u64,
+        reader: &mut R,
+    ) -> Result<(), Self::Error> {
+        Ok(commitlog::Decoder::skip_record(&*self.0, version, tx_offset, reader)?)
+    }
+}
+
+/// A [`commitlog::Decoder`] which decodes transactions against the schemas of `db`,
+/// without applying them.
+struct InspectTx<'a> {
+    db: &'a RelationalDB,
+}
+
+impl commitlog::Decoder for InspectTx<'_> {
+    type Record = Txdata;
+    type Error = commitlog::payload::txdata::DecoderError<DBError>;
+
+    fn decode_record<'a, R: BufReader<'a>>(
+        &self,
+        version: u8,
+        tx_offset: u64,
+        reader: &mut R,
+    ) -> Result<Self::Record, Self::Error> {
+        self.db.with_read_only(Workload::Internal, |tx| {
+            let mut visitor = InspectVisitor { db: self.db, tx };
+            commitlog::payload::txdata::decode_record_fn(&mut visitor, version, tx_offset, reader)
+        })
+    }
+
+    fn skip_record<'a, R: BufReader<'a>>(
+        &self,
+        version: u8,
+        _tx_offset: u64,
+        reader: &mut R,
+    ) -> Result<(), Self::Error> {
+        self.db.with_read_only(Workload::Internal, |tx| {
+            let mut visitor = InspectVisitor { db: self.db, tx };
+            commitlog::payload::txdata::skip_record_fn(&mut visitor, version, reader)
+        })
+    }
+}
+
+struct InspectVisitor<'a> {
+    db: &'a RelationalDB,
+    tx: &'a Tx,
+}
+
+impl InspectVisitor<'_> {
+    fn decode_row<'a, R: BufReader<'a>>(&self, table_id: TableId, reader: &mut R) -> Result<ProductValue, DBError> {
+        let schema = self.db.schema_for_table(self.tx, table_id)?;
+        Ok(ProductValue::decode(schema.get_row_type(), reader)?)
+    }
+}
+
+impl commitlog::payload::txdata::Visitor for InspectVisitor<'_> {
+    type Error = DBError;
+    type Row = ProductValue;
+
+    fn visit_insert<'a, R: BufReader<'a>>(
+        &mut self,
+        table_id: TableId,
+        reader: &mut R,
+    ) -> Result<Self::Row, Self::Error> {
+        self.decode_row(table_id, reader)
+    }
+
+    fn visit_delete<'a, R: BufReader<'a>>(
+        &mut self,
+        table_id: TableId,
+        reader: &mut R,
+    ) -> Result<Self::Row, Self::Error> {
+        self.decode_row(table_id, reader)
+    }
+
+    fn skip_row<'a, R: BufReader<'a>>(&mut self, table_id: TableId, reader: &mut R) -> Result<(), Self::Error> {
+        self.decode_row(table_id, reader).map(drop)
+    }
+}
+
 pub type LocalDurability = Arc<durability::Local<ProductValue>>;
 /// Initialize local durability with the default parameters.
 ///
@@ -1867,6 +2352,7 @@ pub mod tests_utils {
                 Some(persistence),
                 None,
                 PagePool::new_for_test(),
+                None,
             )?;
             let db = db.with_row_count(Self::row_count_fn());
             Ok((db, local))
@@ -2015,6 +2501,7 @@ pub mod tests_utils {
                 persistence,
                 metrics_recorder_queue,
                 PagePool::new_for_test(),
+                None,
             )?;
             assert_eq!(connected_clients.len(), expected_num_clients);
             let db = db.with_row_count(Self::row_count_fn());
@@ -2142,11 +2629,12 @@ mod tests {
     use std::fs::OpenOptions;
     use std::path::PathBuf;
     use std::rc::Rc;
+    use std::time::Duration;
 
     use super::tests_utils::begin_mut_tx;
     use super::*;
     use crate::db::relational_db::tests_utils::{
-        begin_tx, insert, make_snapshot, with_auto_commit, with_read_only, TestDB,
+        begin_tx, insert, make_snapshot, with_auto_commit, with_read_only, TempReplicaDir, TestDB,
     };
     use anyhow::bail;
     use bytes::Bytes;
@@ -2248,6 +2736,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_snapshot_policy_from_system_variables() -> ResultTest<()> {
+        let stdb = TestDB::durable()?;
+        let every_n_txs = |stdb: &TestDB| stdb.snapshot_policy.read().every_n_txs.map(|n| n.get());
+        assert_eq!(every_n_txs(&stdb), Some(SNAPSHOT_FREQUENCY));
+
+        with_auto_commit(&stdb, |tx| stdb.write_var(tx, StVarName::SnapshotEveryTxs, "4"))?;
+        assert_eq!(every_n_txs(&stdb), Some(4));
+
+        // Snapshots are now requested every 4 transactions.
+        let mut snapshots = stdb.subscribe_to_snapshots().expect("snapshots should be enabled");
+        let table_id = with_auto_commit(&stdb, |tx| stdb.create_table(tx, my_table(AlgebraicType::I32)))?;
+        for v in 0..4 {
+            with_auto_commit(&stdb, |tx| insert(&stdb, tx, table_id, &product![v]).map(drop))?;
+        }
+        let rt = stdb.runtime().expect("durable db should have a runtime").clone();
+        let snapshot_offset = rt.block_on(async {
+            tokio::time::timeout(
+                Duration::from_secs(10),
+                snapshots.wait_for(|offset| *offset > 0 && *offset % 4 == 0),
+            )
+            .await
+            .map(|offset| offset.map(|offset| *offset))
+        })??;
+        assert!(snapshot_offset > 0);
+
+        // Zero disables the trigger, and the setting survives a restart.
+        with_auto_commit(&stdb, |tx| stdb.write_var(tx, StVarName::SnapshotInterval, "60"))?;
+        with_auto_commit(&stdb, |tx| stdb.write_var(tx, StVarName::SnapshotEveryTxs, "0"))?;
+        let stdb = stdb.reopen()?;
+        let policy = *stdb.snapshot_policy.read();
+        assert_eq!(policy.every_n_txs, None);
+        assert_eq!(policy.interval, Some(Duration::from_secs(60)));
+        assert_eq!(policy.commitlog_bytes, None);
+
+        Ok(())
+    }
+
     #[test]
     fn test_open_twice() -> ResultTest<()> {
         let stdb = TestDB::durable()?;
@@ -2264,6 +2790,7 @@ mod tests {
             None,
             None,
             PagePool::new_for_test(),
+            None,
         ) {
             Ok(_) => {
                 panic!("Allowed to open database twice")
@@ -3065,6 +3592,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_open_before_tx() -> ResultTest<()> {
+        let stdb = TestDB::durable()?;
+
+        let schema = table(
+            "test_table",
+            ProductType::from([("x", AlgebraicType::I32)]),
+            |builder| builder,
+        );
+        let mut tx = begin_mut_tx(&stdb);
+        let table_id = stdb.create_table(&mut tx, schema)?;
+        stdb.commit_tx(tx)?;
+
+        let commit_insert = |x: i32| -> ResultTest<TxOffset> {
+            let mut tx = begin_mut_tx(&stdb);
+            insert(&stdb, &mut tx, table_id, &product![x])?;
+            Ok(stdb.commit_tx(tx)?.expect("commit should yield a tx offset").0)
+        };
+        commit_insert(1)?;
+        let tx_offset = commit_insert(2)?;
+        commit_insert(3)?;
+
+        // Ensure everything is flushed to disk.
+        let stdb = stdb.reopen()?;
+
+        let scratch_dir = TempReplicaDir::new()?;
+        let (db, committed) = RelationalDB::open_before_tx(
+            stdb.path(),
+            &scratch_dir,
+            TestDB::DATABASE_IDENTITY,
+            TestDB::OWNER,
+            0,
+            tx_offset,
+            None,
+            PagePool::new_for_test(),
+        )?;
+
+        let rows = db.with_read_only(Workload::ForTests, |tx| -> ResultTest<_> {
+            let tx = &*tx;
+            Ok(db
+                .iter(tx, table_id)?
+                .map(|row| row.to_product_value())
+                .collect::<Vec<_>>())
+        })?;
+        assert_eq!(rows, [product![1]]);
+
+        let mutations = committed
+            .expect("the commitlog should contain `tx_offset`")
+            .mutations
+            .expect("the tx should have mutations");
+        let inserts: Vec<_> = mutations
+            .inserts
+            .iter()
+            .filter(|ops| ops.table_id == table_id)
+            .flat_map(|ops| ops.rowdata.iter().cloned())
+            .collect();
+        assert_eq!(inserts, [product![2]]);
+
+        // The tx after the end of the commitlog doesn't exist yet.
+        let scratch_dir = TempReplicaDir::new()?;
+        let (_, committed) = RelationalDB::open_before_tx(
+            stdb.path(),
+            &scratch_dir,
+            TestDB::DATABASE_IDENTITY,
+            TestDB::OWNER,
+            0,
+            tx_offset + 2,
+            None,
+            PagePool::new_for_test(),
+        )?;
+        assert!(committed.is_none());
+
+        Ok(())
+    }
+
     /// This tests that we are able to correctly replay mutations to system tables,
     /// in this case specifically `st_client`.
     ///
@@ -3219,6 +3821,7 @@ mod tests {
             Some(last_compress),
             0,
             PagePool::new_for_test(),
+            None,
         )?;
 
         Ok(())
@@ -3243,8 +3846,14 @@ mod tests {
         );
 
         let last = repo.latest_snapshot()?;
-        let stdb =
-            RelationalDB::restore_from_snapshot_or_bootstrap(identity, Some(&repo), last, 0, PagePool::new_for_test())?;
+        let stdb = RelationalDB::restore_from_snapshot_or_bootstrap(
+            identity,
+            Some(&repo),
+            last,
+            0,
+            PagePool::new_for_test(),
+            None,
+        )?;
 
         let out = TempDir::with_prefix("snapshot_test")?;
         let dir = SnapshotsPath::from_path_unchecked(out.path());
@@ -3283,6 +3892,7 @@ mod tests {
                 Some(durable_tx_offset),
                 min_commitlog_offset,
                 PagePool::new_for_test(),
+                None,
             )
         };
 
@@ -2,25 +2,36 @@
 #![allow(clippy::uninlined_format_args)]
 
 use std::{
+    num::NonZeroU64,
     sync::{Arc, Weak},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use anyhow::Context as _;
-use futures::{channel::mpsc, StreamExt as _};
+use anyhow::{anyhow, Context as _};
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt as _,
+};
 use log::{info, warn};
 use parking_lot::RwLock;
 use prometheus::{Histogram, IntGauge};
 use spacetimedb_datastore::locking_tx_datastore::{committed_state::CommittedState, datastore::Locking};
+use spacetimedb_datastore::system_tables::StVarName;
 use spacetimedb_durability::TxOffset;
 use spacetimedb_lib::Identity;
 use spacetimedb_snapshot::{CompressionStats, SnapshotRepository};
-use tokio::sync::watch;
+use tokio::{sync::watch, time::MissedTickBehavior};
 
+use super::{persistence::DiskSizeFn, relational_db::SNAPSHOT_FREQUENCY};
 use crate::{util::asyncify, worker_metrics::WORKER_METRICS};
 
 pub type SnapshotDatabaseState = Arc<RwLock<CommittedState>>;
 
+/// A snapshot was requested of a database which has not committed any transactions yet.
+#[derive(Debug, thiserror::Error)]
+#[error("refusing to take snapshot of database {0} at TX offset -1: no transactions committed yet")]
+pub struct NoTransactionsYet(pub Identity);
+
 /// Whether the [SnapshotWorker] should compress historical snapshots.
 #[derive(Clone, Copy, Debug)]
 pub enum Compression {
@@ -34,6 +45,73 @@ impl Compression {
     }
 }
 
+/// How often the [SnapshotWorker] checks the time- and size-based triggers
+/// of its [SnapshotPolicy].
+const POLICY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When to take snapshots of a database, configured via `st_var`.
+///
+/// A snapshot is taken as soon as any of the configured triggers fires.
+/// Snapshots can additionally be requested at any time,
+/// see [SnapshotWorker::request_snapshot].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotPolicy {
+    /// Snapshot whenever the TX offset is a multiple of this number.
+    ///
+    /// Replicas agree on the offsets of these snapshots,
+    /// as the policy is part of the replicated database state.
+    pub every_n_txs: Option<NonZeroU64>,
+    /// Snapshot when this much time has passed since the previous snapshot,
+    /// and there were transactions since.
+    pub interval: Option<Duration>,
+    /// Snapshot when the commitlog has grown by this many bytes since the
+    /// previous snapshot.
+    pub commitlog_bytes: Option<NonZeroU64>,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            every_n_txs: NonZeroU64::new(SNAPSHOT_FREQUENCY),
+            interval: None,
+            commitlog_bytes: None,
+        }
+    }
+}
+
+impl SnapshotPolicy {
+    /// Update the trigger configured by the system variable `name`.
+    ///
+    /// A value of zero disables the trigger,
+    /// `None` (i.e. the variable is unset) restores the default.
+    /// Variables not related to snapshots are ignored.
+    pub fn set_var(&mut self, name: StVarName, value: Option<u64>) {
+        let default = Self::default();
+        match name {
+            StVarName::SnapshotEveryTxs => {
+                self.every_n_txs = value.map_or(default.every_n_txs, NonZeroU64::new);
+            }
+            StVarName::SnapshotInterval => {
+                self.interval = value.map_or(default.interval, |secs| (secs > 0).then(|| Duration::from_secs(secs)));
+            }
+            StVarName::SnapshotCommitlogBytes => {
+                self.commitlog_bytes = value.map_or(default.commitlog_bytes, NonZeroU64::new);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a snapshot is due after committing the transaction at `tx_offset`.
+    pub fn is_snapshot_tx(&self, tx_offset: TxOffset) -> bool {
+        self.every_n_txs.is_some_and(|n| tx_offset % n == 0)
+    }
+
+    /// Whether the policy has triggers the [SnapshotWorker] needs to check periodically.
+    fn is_periodic(&self) -> bool {
+        self.interval.is_some() || self.commitlog_bytes.is_some()
+    }
+}
+
 /// Represents a handle to a background task that takes snapshots of a
 /// [SnapshotDatabaseState] and stores them on disk.
 ///
@@ -48,6 +126,9 @@ impl Compression {
 /// Whenever a snapshot is complete, its [TxOffset] is published to a channel,
 /// to which one can [subscribe][Self::subscribe].
 ///
+/// The worker also takes snapshots on its own according to the time- and
+/// size-based triggers of its [SnapshotPolicy], see [Self::set_policy].
+///
 /// The [SnapshotWorker] handle is freely cloneable, so ownership can be shared
 /// between the database and control code.
 ///
@@ -98,10 +179,23 @@ impl SnapshotWorker {
     /// Finish the initialization of [Self] by passing a [SnapshotDatabaseState],
     /// or replace the current [SnapshotDatabaseState] with a new one.
     ///
+    /// The `disk_size` function reports the size of the commitlog,
+    /// and is used to evaluate [SnapshotPolicy::commitlog_bytes].
+    ///
     /// This is called during construction of a [super::relational_db::RelationalDB].
-    pub(crate) fn set_state(&self, state: SnapshotDatabaseState) {
+    pub(crate) fn set_state(&self, state: SnapshotDatabaseState, disk_size: DiskSizeFn) {
+        self.request_snapshot
+            .unbounded_send(Request::ReplaceState(state, disk_size))
+            .expect("snapshot worker panicked");
+    }
+
+    /// Replace the [SnapshotPolicy] of the worker.
+    ///
+    /// Only the time- and size-based triggers are evaluated by the worker,
+    /// the database requests snapshots according to [SnapshotPolicy::every_n_txs].
+    pub(crate) fn set_policy(&self, policy: SnapshotPolicy) {
         self.request_snapshot
-            .unbounded_send(Request::ReplaceState(state))
+            .unbounded_send(Request::SetPolicy(policy))
             .expect("snapshot worker panicked");
     }
 
@@ -119,16 +213,31 @@ impl SnapshotWorker {
     /// which is likely due to it having panicked.
     pub fn request_snapshot(&self) {
         self.request_snapshot
-            .unbounded_send(Request::TakeSnapshot)
+            .unbounded_send(Request::TakeSnapshot { reply: None })
             .expect("snapshot worker panicked");
     }
 
+    /// Like [`Self::request_snapshot`], but waits for the snapshot to be taken.
+    ///
+    /// Returns the [TxOffset] of the new snapshot, or an error if it could not
+    /// be taken. If the database has not committed any transactions yet,
+    /// the error is a [NoTransactionsYet].
+    pub async fn request_snapshot_and_wait(&self) -> anyhow::Result<TxOffset> {
+        let (reply, rx) = oneshot::channel();
+        self.request_snapshot
+            .unbounded_send(Request::TakeSnapshot { reply: Some(reply) })
+            .map_err(|_| anyhow!("snapshot worker panicked"))?;
+        rx.await.context("snapshot worker panicked")?
+    }
+
     /// Like [`Self::request_snapshot`], but doesn't propogate panics from the worker.
     ///
     /// Used by the durability to request snapshots on commitlog segment rotation,
     /// since the durability should continue writing queued TXes even if the snapshot worker panics.
     pub fn request_snapshot_ignore_closed(&self) {
-        let _ = self.request_snapshot.unbounded_send(Request::TakeSnapshot);
+        let _ = self
+            .request_snapshot
+            .unbounded_send(Request::TakeSnapshot { reply: None });
     }
 
     /// Subscribe to the [TxOffset]s of snapshots created by this worker.
@@ -158,8 +267,33 @@ impl SnapshotMetrics {
 type WeakDatabaseState = Weak<RwLock<CommittedState>>;
 
 enum Request {
-    TakeSnapshot,
-    ReplaceState(SnapshotDatabaseState),
+    TakeSnapshot {
+        reply: Option<oneshot::Sender<anyhow::Result<TxOffset>>>,
+    },
+    ReplaceState(SnapshotDatabaseState, DiskSizeFn),
+    SetPolicy(SnapshotPolicy),
+}
+
+/// Progress since the latest snapshot, for evaluating the periodic triggers
+/// of a [SnapshotPolicy].
+struct SinceLastSnapshot {
+    /// The offset of the latest snapshot, if any.
+    tx_offset: Option<TxOffset>,
+    /// When the latest snapshot was taken, or the worker started.
+    at: Instant,
+    /// The commitlog size at the latest snapshot,
+    /// `None` until first observed after the snapshot.
+    commitlog_size: Option<u64>,
+}
+
+impl SinceLastSnapshot {
+    fn new(tx_offset: Option<TxOffset>) -> Self {
+        Self {
+            tx_offset,
+            at: Instant::now(),
+            commitlog_size: None,
+        }
+    }
 }
 
 struct SnapshotWorkerActor {
@@ -175,7 +309,10 @@ impl SnapshotWorkerActor {
     ///
     /// For each [Request::TakeSnapshot] message, a snapshot of `database_state`
     /// is taken. The offset of each successfully created snapshot is sent to
-    /// the `snapshot_created` channel.
+    /// the `snapshot_created` channel, and to the requester if it asked for a reply.
+    ///
+    /// In between requests, the periodic triggers of the current [SnapshotPolicy]
+    /// are checked every [POLICY_CHECK_INTERVAL].
     ///
     /// If compression is enabled, it is run after successful creation of a
     /// snapshot.
@@ -186,26 +323,104 @@ impl SnapshotWorkerActor {
     /// message is received, unless a new snapshot request is already being
     /// processed.
     async fn run(mut self) {
-        let mut database_state: Option<WeakDatabaseState> = None;
-        while let Some(req) = self.snapshot_requests.next().await {
+        let mut database_state: Option<(WeakDatabaseState, DiskSizeFn)> = None;
+        let mut policy = SnapshotPolicy::default();
+        let mut since_last = SinceLastSnapshot::new(self.latest_snapshot());
+        let mut ticker = tokio::time::interval(POLICY_CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let req = tokio::select! {
+                req = self.snapshot_requests.next() => match req {
+                    Some(req) => req,
+                    None => break,
+                },
+                _ = ticker.tick(), if policy.is_periodic() => {
+                    let Some((state, disk_size)) = &database_state else {
+                        continue;
+                    };
+                    if !self.is_snapshot_due(&policy, &mut since_last, state, disk_size).await {
+                        continue;
+                    }
+                    Request::TakeSnapshot { reply: None }
+                }
+            };
             match req {
-                Request::TakeSnapshot => {
+                Request::TakeSnapshot { reply } => {
                     let res = self
-                        .maybe_take_snapshot(database_state.as_ref())
+                        .maybe_take_snapshot(database_state.as_ref().map(|(state, _)| state))
                         .await
                         .inspect_err(|e| warn!("SnapshotWorker: {e:#}"));
                     if let Ok(snapshot_offset) = res {
+                        since_last = SinceLastSnapshot::new(Some(snapshot_offset));
                         self.maybe_compress_snapshots(snapshot_offset).await;
                         self.snapshot_created.send_replace(snapshot_offset);
                     }
+                    if let Some(reply) = reply {
+                        let _ = reply.send(res);
+                    }
+                }
+                Request::ReplaceState(new_state, disk_size) => {
+                    database_state = Some((Arc::downgrade(&new_state), disk_size));
+                    since_last = SinceLastSnapshot::new(self.latest_snapshot());
                 }
-                Request::ReplaceState(new_state) => {
-                    database_state = Some(Arc::downgrade(&new_state));
+                Request::SetPolicy(new_policy) => {
+                    policy = new_policy;
                 }
             }
         }
     }
 
+    /// Check whether the time- or size-based trigger of `policy` has fired.
+    ///
+    /// Neither fires unless transactions were committed since the latest snapshot.
+    async fn is_snapshot_due(
+        &self,
+        policy: &SnapshotPolicy,
+        since_last: &mut SinceLastSnapshot,
+        state: &WeakDatabaseState,
+        disk_size: &DiskSizeFn,
+    ) -> bool {
+        let Some(state) = state.upgrade() else {
+            return false;
+        };
+        let Some(tx_offset) = state.read().next_tx_offset().checked_sub(1) else {
+            return false;
+        };
+        if since_last
+            .tx_offset
+            .is_some_and(|snapshot_offset| tx_offset <= snapshot_offset)
+        {
+            return false;
+        }
+
+        if policy
+            .interval
+            .is_some_and(|interval| since_last.at.elapsed() >= interval)
+        {
+            return true;
+        }
+        if let Some(max_bytes) = policy.commitlog_bytes {
+            let disk_size = disk_size.clone();
+            match asyncify(move || disk_size()).await {
+                Ok(size) => {
+                    let base = *since_last.commitlog_size.get_or_insert(size);
+                    return size.saturating_sub(base) >= max_bytes.get();
+                }
+                Err(e) => warn!("SnapshotWorker: error determining commitlog size: {e:#}"),
+            }
+        }
+        false
+    }
+
+    fn latest_snapshot(&self) -> Option<TxOffset> {
+        self.snapshot_repo
+            .latest_snapshot()
+            .inspect_err(|e| warn!("SnapshotWorker: error reading latest snapshot: {e:#}"))
+            .ok()
+            .flatten()
+    }
+
     async fn maybe_take_snapshot(&self, state: Option<&WeakDatabaseState>) -> anyhow::Result<TxOffset> {
         let state = state.context("database state not set, call `SnapshotWorker::set_state`")?;
         let state = Weak::upgrade(state).context("database state is already dropped")?;
@@ -235,12 +450,7 @@ impl SnapshotWorkerActor {
                     database_identity, snapshot_offset, elapsed,
                 );
             })
-            .with_context(|| {
-                format!(
-                    "refusing to take snapshot of database {} at TX offset -1",
-                    database_identity
-                )
-            })
+            .ok_or_else(|| NoTransactionsYet(database_identity).into())
     }
 
     async fn maybe_compress_snapshots(&mut self, latest_snapshot: TxOffset) {
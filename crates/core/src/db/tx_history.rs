@@ -0,0 +1,217 @@
+//! A bounded, in-memory record of the rows changed by recently committed transactions.
+//!
+//! Clients which persist their subscribed rows tell the host which transaction those rows reflect
+//! when they reconnect. If the history still covers every transaction since then,
+//! the host can send them just the changes they missed, rather than every subscribed row.
+
+use spacetimedb_datastore::system_tables::{ST_COLUMN_ID, ST_TABLE_ID};
+use spacetimedb_datastore::traits::TxData;
+use spacetimedb_durability::TxOffset;
+use spacetimedb_primitives::TableId;
+use spacetimedb_sats::ProductValue;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+/// The history keeps at most this many transactions, ...
+const MAX_TXS: usize = 10_000;
+/// ... which together change at most this many rows.
+const MAX_ROWS: usize = 100_000;
+
+/// The rows changed by recently committed transactions, oldest first.
+pub struct TxHistory {
+    /// Identifies this history.
+    ///
+    /// Offsets are only comparable within the same history.
+    /// A database which is restarted or recreated starts a new one,
+    /// so a client's offset into an earlier history is never mistaken for an offset into this one.
+    id: u64,
+    /// The transactions, in order of their offsets.
+    txs: VecDeque<(TxOffset, Arc<TxData>)>,
+    /// The number of rows inserted or deleted by the transactions in `txs`.
+    num_rows: usize,
+}
+
+impl Default for TxHistory {
+    fn default() -> Self {
+        Self {
+            id: rand::random(),
+            txs: VecDeque::new(),
+            num_rows: 0,
+        }
+    }
+}
+
+impl TxHistory {
+    /// Returns the id of this history.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Record the rows changed by a newly committed transaction,
+    /// forgetting the oldest transactions if the history grows too large.
+    ///
+    /// Transactions which don't consume a tx offset change no rows and are not recorded.
+    pub fn record(&mut self, tx_data: &TxData) {
+        let Some(tx_offset) = tx_data.tx_offset() else {
+            return;
+        };
+        // Commits record themselves after releasing the datastore's lock,
+        // so they may do so out of order.
+        let pos = self.txs.partition_point(|(offset, _)| *offset < tx_offset);
+        self.num_rows += num_rows(tx_data);
+        self.txs.insert(pos, (tx_offset, Arc::new(tx_data.clone())));
+
+        while self.txs.len() > MAX_TXS || (self.num_rows > MAX_ROWS && self.txs.len() > 1) {
+            let (_, oldest) = self.txs.pop_front().expect("history is not empty");
+            self.num_rows -= num_rows(&oldest);
+        }
+    }
+
+    /// Returns the net changes of the transactions after `since`, up to and including `up_to`,
+    /// as if they had been made by a single transaction.
+    ///
+    /// Returns `None` if `since` is an offset into a different history,
+    /// if this history doesn't record every one of those transactions,
+    /// or if one of them altered the schema of a table,
+    /// in which case the caller must fall back to sending every subscribed row.
+    pub fn changes_since(&self, history_id: u64, since: TxOffset, up_to: TxOffset) -> Option<TxData> {
+        if history_id != self.id || since > up_to {
+            return None;
+        }
+
+        let start = self.txs.partition_point(|(offset, _)| *offset <= since);
+        let txs = self.txs.range(start..).take_while(|(offset, _)| *offset <= up_to);
+        let mut expected = since + 1;
+        let mut net = BTreeMap::<TableId, (&str, BTreeMap<&ProductValue, i32>)>::new();
+        for (offset, tx_data) in txs {
+            if *offset != expected {
+                return None;
+            }
+            expected += 1;
+
+            for (table_id, table_name, rows) in tx_data.deletes_with_table_name() {
+                if is_schema_table(*table_id) {
+                    return None;
+                }
+                let (_, counts) = net.entry(*table_id).or_insert_with(|| (table_name, BTreeMap::new()));
+                for row in rows.iter() {
+                    *counts.entry(row).or_default() -= 1;
+                }
+            }
+            for (table_id, table_name, rows) in tx_data.inserts_with_table_name() {
+                if is_schema_table(*table_id) {
+                    return None;
+                }
+                let (_, counts) = net.entry(*table_id).or_insert_with(|| (table_name, BTreeMap::new()));
+                for row in rows.iter() {
+                    *counts.entry(row).or_default() += 1;
+                }
+            }
+        }
+        if expected != up_to + 1 {
+            return None;
+        }
+
+        // Tables are sets, so a row inserted and later deleted, or vice versa, cancels out.
+        let mut merged = TxData::default();
+        for (table_id, (table_name, counts)) in net {
+            let (inserts, deletes): (Vec<_>, Vec<_>) = counts
+                .into_iter()
+                .filter(|(_, count)| *count != 0)
+                .partition(|(_, count)| *count > 0);
+            if !inserts.is_empty() {
+                let rows = inserts.into_iter().map(|(row, _)| row.clone()).collect();
+                merged.set_inserts_for_table(table_id, table_name, rows);
+            }
+            if !deletes.is_empty() {
+                let rows = deletes.into_iter().map(|(row, _)| row.clone()).collect();
+                merged.set_deletes_for_table(table_id, table_name, rows);
+            }
+        }
+        merged.set_tx_offset(up_to);
+        Some(merged)
+    }
+}
+
+/// Changes to these tables may change the layout of the rows a client holds.
+fn is_schema_table(table_id: TableId) -> bool {
+    table_id == ST_TABLE_ID || table_id == ST_COLUMN_ID
+}
+
+fn num_rows(tx_data: &TxData) -> usize {
+    let inserted: usize = tx_data.inserts().map(|(_, rows)| rows.len()).sum();
+    let deleted: usize = tx_data.deletes().map(|(_, rows)| rows.len()).sum();
+    inserted + deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacetimedb_sats::product;
+
+    const TABLE: TableId = TableId(4096);
+
+    fn tx(offset: TxOffset, inserts: &[u64], deletes: &[u64]) -> TxData {
+        let rows = |ids: &[u64]| ids.iter().map(|id| product![*id]).collect::<Arc<[_]>>();
+        let mut tx_data = TxData::default();
+        if !inserts.is_empty() {
+            tx_data.set_inserts_for_table(TABLE, "t", rows(inserts));
+        }
+        if !deletes.is_empty() {
+            tx_data.set_deletes_for_table(TABLE, "t", rows(deletes));
+        }
+        tx_data.set_tx_offset(offset);
+        tx_data
+    }
+
+    fn rows(rows: Option<&Arc<[ProductValue]>>) -> Vec<ProductValue> {
+        rows.map(|rows| rows.to_vec()).unwrap_or_default()
+    }
+
+    #[test]
+    fn merges_transactions_since_offset() {
+        let mut history = TxHistory::default();
+        history.record(&tx(1, &[1, 2], &[]));
+        history.record(&tx(2, &[3], &[1]));
+        history.record(&tx(3, &[4], &[3]));
+
+        let merged = history.changes_since(history.id(), 1, 3).unwrap();
+        let inserts: BTreeMap<_, _> = merged.inserts().collect();
+        let deletes: BTreeMap<_, _> = merged.deletes().collect();
+        // Row 3 was inserted and deleted again, so it cancels out.
+        assert_eq!(rows(inserts.get(&TABLE).copied()), [product![4u64]]);
+        assert_eq!(rows(deletes.get(&TABLE).copied()), [product![1u64]]);
+        assert_eq!(merged.tx_offset(), Some(3));
+    }
+
+    #[test]
+    fn records_out_of_order_commits() {
+        let mut history = TxHistory::default();
+        history.record(&tx(2, &[2], &[]));
+        history.record(&tx(1, &[1], &[]));
+        assert!(history.changes_since(history.id(), 0, 2).is_some());
+    }
+
+    #[test]
+    fn rejects_gaps_and_other_histories() {
+        let mut history = TxHistory::default();
+        history.record(&tx(1, &[1], &[]));
+        history.record(&tx(3, &[3], &[]));
+
+        assert!(history.changes_since(history.id(), 0, 3).is_none());
+        assert!(history.changes_since(history.id(), 0, 4).is_none());
+        assert!(history.changes_since(history.id().wrapping_add(1), 0, 1).is_none());
+        assert!(history.changes_since(history.id(), 3, 3).is_some());
+    }
+
+    #[test]
+    fn forgets_oldest_transactions() {
+        let mut history = TxHistory::default();
+        let last = MAX_TXS as TxOffset + 1;
+        for offset in 1..=last {
+            history.record(&tx(offset, &[offset], &[]));
+        }
+        assert!(history.changes_since(history.id(), 0, last).is_none());
+        assert!(history.changes_since(history.id(), 1, last).is_some());
+    }
+}
@@ -152,13 +152,15 @@ fn auto_migrate_database(
                 let index_def = table_def.indexes.get(index_name).unwrap();
                 let table_id = table_schemas_by_name[&table_def.name[..]].table_id;
 
-                let index_cols = ColSet::from(index_def.algorithm.columns());
-
-                let is_unique = table_def
-                    .constraints
-                    .iter()
-                    .filter_map(|(_, c)| c.data.unique_columns())
-                    .any(|unique_cols| unique_cols == &index_cols);
+                // Only indexes over a projection of the row can back a unique constraint.
+                let is_unique = index_def.algorithm.columns().is_some_and(|cols| {
+                    let index_cols = ColSet::from(cols);
+                    table_def
+                        .constraints
+                        .iter()
+                        .filter_map(|(_, c)| c.data.unique_columns())
+                        .any(|unique_cols| unique_cols == &index_cols)
+                });
 
                 log!(logger, "Creating index `{}` on table `{}`", index_name, table_def.name);
 
@@ -7,7 +7,9 @@ use crate::messages::control_db::Database;
 pub use spacetimedb_client_api_messages::energy::*;
 pub struct ReducerFingerprint<'a> {
     pub module_hash: Hash,
+    /// The identity of the owner of the database.
     pub module_identity: Identity,
+    pub database_identity: Identity,
     pub caller_identity: Identity,
     pub reducer_name: &'a str,
 }
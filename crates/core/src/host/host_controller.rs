@@ -1,10 +1,11 @@
 use super::module_host::{EventStatus, ModuleHost, ModuleInfo, NoSuchModule};
+use super::replay::{diff_writes, CapturingDurability};
 use super::scheduler::SchedulerStarter;
 use super::wasmtime::WasmtimeRuntime;
-use super::{Scheduler, UpdateDatabaseResult};
+use super::{FunctionArgs, Scheduler, UpdateDatabaseResult};
 use crate::client::{ClientActorId, ClientName};
 use crate::database_logger::DatabaseLogger;
-use crate::db::persistence::PersistenceProvider;
+use crate::db::persistence::{Persistence, PersistenceProvider};
 use crate::db::relational_db::{self, DiskSizeFn, RelationalDB, Txdata};
 use crate::db::{self, spawn_tx_metrics_recorder};
 use crate::energy::{EnergyMonitor, EnergyQuanta, NullEnergyMonitor};
@@ -20,15 +21,18 @@ use crate::util::jobs::{JobCores, SingleCoreExecutor};
 use crate::worker_metrics::WORKER_METRICS;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
+use bytes::Bytes;
 use durability::{Durability, EmptyHistory};
 use log::{info, trace, warn};
 use parking_lot::Mutex;
+use spacetimedb_client_api_messages::replay::ReplayReport;
 use spacetimedb_data_structures::error_stream::ErrorStream;
 use spacetimedb_data_structures::map::IntMap;
 use spacetimedb_datastore::db_metrics::data_size::DATA_SIZE_METRICS;
 use spacetimedb_datastore::db_metrics::DB_METRICS;
+use spacetimedb_datastore::execution_context::ReducerContext;
 use spacetimedb_datastore::traits::Program;
-use spacetimedb_durability::{self as durability};
+use spacetimedb_durability::{self as durability, TxOffset};
 use spacetimedb_lib::{hash_bytes, Identity};
 use spacetimedb_paths::server::{ReplicaDir, ServerDataDir};
 use spacetimedb_paths::FromPathUnchecked;
@@ -119,6 +123,9 @@ impl HostRuntimes {
 #[derive(Clone, Debug)]
 pub struct ReducerCallResult {
     pub outcome: ReducerOutcome,
+    /// The BSATN-encoded value the reducer returned or failed with,
+    /// for reducers which declare return types.
+    pub return_value: Option<Bytes>,
     pub energy_used: EnergyQuanta,
     pub execution_duration: Duration,
 }
@@ -313,6 +320,122 @@ impl HostController {
         .await
     }
 
+    /// Re-run the reducer which committed the transaction at `tx_offset`
+    /// against a throwaway copy of the database as it was just before that transaction,
+    /// and report how the rows it writes differ from those originally committed.
+    ///
+    /// The reducer is called with the caller, connection id, timestamp and arguments
+    /// recorded in the commitlog, and the module stored in the database at that point.
+    /// As the reducer's random number generator is seeded from its timestamp,
+    /// a deterministic reducer will write exactly the same rows.
+    ///
+    /// The running database, if any, is not affected.
+    /// Only transactions committed by non-lifecycle reducers can be replayed,
+    /// and only if the database is stored on disk.
+    pub async fn replay_transaction(
+        &self,
+        database: Database,
+        replica_id: u64,
+        tx_offset: TxOffset,
+    ) -> anyhow::Result<ReplayReport> {
+        anyhow::ensure!(
+            matches!(self.default_config.storage, db::Storage::Disk),
+            "replaying transactions requires the database to be stored on disk"
+        );
+
+        // The copy acquires a lockfile, and the module logs of the replay go here.
+        let scratch_dir = TempDir::with_prefix("spacetimedb-replay")
+            .context("Error creating temporary directory to house the replayed database")?;
+        let scratch_replica_dir = ReplicaDir::from_path_unchecked(scratch_dir.path().to_owned());
+
+        let durability = Arc::new(CapturingDurability::default());
+        let persistence = Persistence {
+            durability: durability.clone(),
+            disk_size: Arc::new(|| Ok::<_, std::io::Error>(0)),
+            snapshots: None,
+        };
+        let (db, committed) = asyncify({
+            let replica_dir = self.data_dir.replica(replica_id);
+            let scratch_replica_dir = scratch_replica_dir.clone();
+            let page_pool = self.page_pool.clone();
+            move || {
+                RelationalDB::open_before_tx(
+                    &replica_dir,
+                    &scratch_replica_dir,
+                    database.database_identity,
+                    database.owner_identity,
+                    replica_id,
+                    tx_offset,
+                    Some(persistence),
+                    page_pool,
+                )
+            }
+        })
+        .await?;
+
+        let committed = committed.with_context(|| format!("no transaction at offset {tx_offset}"))?;
+        let ReducerContext {
+            name,
+            caller_identity,
+            caller_connection_id,
+            timestamp,
+            arg_bsatn,
+        } = committed
+            .inputs
+            .as_ref()
+            .with_context(|| format!("transaction {tx_offset} was not committed by a reducer"))
+            .and_then(|inputs| Ok(ReducerContext::try_from(inputs)?))?;
+        let program = db
+            .program()?
+            .with_context(|| format!("database was not initialized before transaction {tx_offset}"))?;
+
+        let (_, launched) = launch_module(
+            database,
+            replica_id,
+            program,
+            || log::error!("launch_module on_panic called for replay instance"),
+            Arc::new(db),
+            Arc::new(NullEnergyMonitor),
+            scratch_replica_dir,
+            self.runtimes.clone(),
+            self.db_cores.take(),
+        )
+        .await?;
+        let module_host = launched.module_host;
+
+        let result = module_host
+            .call_reducer_at(
+                caller_identity,
+                Some(caller_connection_id),
+                None,
+                None,
+                None,
+                timestamp,
+                &name,
+                FunctionArgs::Bsatn(arg_bsatn),
+            )
+            .await
+            .with_context(|| format!("cannot replay reducer `{name}`"));
+        module_host.exit().await;
+        let error = result?.outcome.into_result().err().map(|e| format!("{e:#}"));
+
+        // The replay database may commit other transactions besides the reducer's,
+        // e.g. a flush of buffered slow events,
+        // so pick the one recording the same reducer call as the original.
+        let replayed = durability.take().into_iter().rfind(|tx| tx.inputs == committed.inputs);
+        let differences = diff_writes(&launched.replica_ctx.relational_db, &committed, replayed.as_ref());
+
+        Ok(ReplayReport {
+            tx_offset,
+            reducer: name,
+            caller_identity,
+            caller_connection_id,
+            timestamp_micros: timestamp.to_micros_since_unix_epoch(),
+            error,
+            differences,
+        })
+    }
+
     /// Run a computation on the [`RelationalDB`] of a [`ModuleHost`] managed by
     /// this controller, launching the host if necessary.
     ///
@@ -337,6 +460,21 @@ impl HostController {
         Ok(result)
     }
 
+    /// Take a snapshot of the database `database` with replica `replica_id`,
+    /// and return the [`TxOffset`] of the snapshot once it is stored on disk.
+    ///
+    /// The host is launched if it is not already running.
+    pub async fn take_snapshot(&self, database: Database, replica_id: u64) -> anyhow::Result<TxOffset> {
+        let module = self.get_or_launch_module_host(database, replica_id).await?;
+        let snapshot_worker = module
+            .replica_ctx()
+            .relational_db
+            .snapshot_worker()
+            .cloned()
+            .context("snapshots are not enabled for this database")?;
+        snapshot_worker.request_snapshot_and_wait().await
+    }
+
     /// Update the [`ModuleHost`] identified by `replica_id` to the given
     /// program.
     ///
@@ -734,6 +872,10 @@ struct Host {
     /// Handle to the task responsible for recording metrics for each transaction.
     /// The task is aborted when [`Host`] is dropped.
     tx_metrics_recorder_task: AbortHandle,
+    /// Handle to the task started via [`slow_event_flusher`],
+    /// which writes buffered slow events even if no new ones are recorded.
+    /// The task is aborted when [`Host`] is dropped.
+    slow_event_flusher_task: AbortHandle,
 }
 
 impl Host {
@@ -766,6 +908,7 @@ impl Host {
                 None,
                 Some(tx_metrics_queue),
                 page_pool.clone(),
+                None,
             )?,
             db::Storage::Disk => {
                 // Open a read-only copy of the local durability to replay from.
@@ -784,6 +927,7 @@ impl Host {
                     Some(persistence),
                     Some(tx_metrics_queue),
                     page_pool.clone(),
+                    config.table_memory_limit,
                 )
                 // Make sure we log the source chain of the error
                 // as a single line, with the help of `anyhow`.
@@ -795,6 +939,7 @@ impl Host {
                         "Failed to open database: {e:#}"
                     );
                 })?;
+                db.set_table_memory_limit(config.table_memory_limit);
 
                 (db, clients)
             }
@@ -857,6 +1002,7 @@ impl Host {
 
         scheduler_starter.start(&module_host)?;
         let disk_metrics_recorder_task = tokio::spawn(metric_reporter(replica_ctx.clone())).abort_handle();
+        let slow_event_flusher_task = tokio::spawn(slow_event_flusher(replica_ctx.clone())).abort_handle();
 
         Ok(Host {
             module: watch::Sender::new(module_host),
@@ -864,6 +1010,7 @@ impl Host {
             scheduler,
             disk_metrics_recorder_task,
             tx_metrics_recorder_task,
+            slow_event_flusher_task,
         })
     }
 
@@ -900,6 +1047,7 @@ impl Host {
             None,
             None,
             page_pool,
+            None,
         )?;
 
         let (program, launched) = launch_module(
@@ -1040,6 +1188,7 @@ impl Drop for Host {
     fn drop(&mut self) {
         self.disk_metrics_recorder_task.abort();
         self.tx_metrics_recorder_task.abort();
+        self.slow_event_flusher_task.abort();
     }
 }
 
@@ -1084,6 +1233,25 @@ async fn metric_reporter(replica_ctx: Arc<ReplicaContext>) {
     }
 }
 
+/// Periodically write buffered slow events to `st_slow_event`.
+///
+/// Events are otherwise only written when the next one is recorded,
+/// so without this, the last events before a database goes idle would never become visible.
+/// Nothing is written while the buffer is empty,
+/// so an idle database does not append to its commitlog.
+async fn slow_event_flusher(replica_ctx: Arc<ReplicaContext>) {
+    let mut ticker = tokio::time::interval(relational_db::SLOW_EVENT_FLUSH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let ctx = replica_ctx.clone();
+        // We spawn a blocking task here because writing the events starts a transaction.
+        let flushed = tokio::task::spawn_blocking(move || ctx.relational_db.flush_slow_events_if_due()).await;
+        if let Ok(Err(e)) = flushed {
+            warn!("failed to write slow events: {e}");
+        }
+    }
+}
+
 /// Extracts the schema from a given module.
 ///
 /// Spins up a dummy host and returns the `ModuleDef` that it extracts.
@@ -1113,6 +1281,46 @@ pub async fn extract_schema(program_bytes: Box<[u8]>, host_type: HostType) -> an
     Ok(module_def)
 }
 
+/// Launch `program` against a fresh in-memory database whose lockfile and module logs live in `replica_dir`,
+/// then initialize it, running the module's `init` reducer if it has one.
+///
+/// Unlike a real host, the scheduler is never started:
+/// the returned module expects its caller to fire scheduled reducers itself.
+/// This is the entry point for [`crate::testing::ModuleTestHarness`].
+pub(crate) async fn launch_in_memory_for_testing(
+    database: Database,
+    program: Program,
+    replica_dir: ReplicaDir,
+) -> anyhow::Result<(Arc<ReplicaContext>, ModuleHost, Option<ReducerCallResult>)> {
+    let (db, _connected_clients) = RelationalDB::open(
+        &replica_dir,
+        database.database_identity,
+        database.owner_identity,
+        EmptyHistory::new(),
+        None,
+        None,
+        PagePool::new(None),
+        None,
+    )?;
+
+    let (program, launched) = launch_module(
+        database,
+        0,
+        program,
+        || log::error!("launch_module on_panic called for in-memory test instance"),
+        Arc::new(db),
+        Arc::new(NullEnergyMonitor),
+        replica_dir,
+        HostRuntimes::new(None),
+        SingleCoreExecutor::in_current_tokio_runtime(),
+    )
+    .await?;
+
+    let init_result = launched.module_host.init_database(program).await?;
+
+    Ok((launched.replica_ctx, launched.module_host, init_result))
+}
+
 // Remove all gauges associated with a database.
 // This is useful if a database is being deleted.
 pub fn remove_database_gauges<'a, I>(db: &Identity, table_names: I)
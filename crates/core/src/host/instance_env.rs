@@ -687,7 +687,7 @@ mod test {
                     schema
                         .index_algorithm
                         .columns()
-                        .as_singleton()
+                        .and_then(|cols| cols.as_singleton())
                         .is_some_and(|col_id| col_id.idx() == 0)
                 })
                 .map(|schema| schema.index_id)
@@ -718,7 +718,7 @@ mod test {
                     schema
                         .index_algorithm
                         .columns()
-                        .as_singleton()
+                        .and_then(|cols| cols.as_singleton())
                         .is_some_and(|col_id| col_id.idx() == 0)
                 })
                 .map(|schema| schema.index_id)
@@ -14,6 +14,7 @@ mod host_controller;
 mod module_common;
 #[allow(clippy::too_many_arguments)]
 pub mod module_host;
+mod replay;
 pub mod scheduler;
 pub mod wasmtime;
 
@@ -23,6 +24,7 @@ pub mod v8; // only pub for testing
 mod wasm_common;
 
 pub use disk_storage::DiskStorage;
+pub(crate) use host_controller::launch_in_memory_for_testing;
 pub use host_controller::{
     extract_schema, ExternalDurability, ExternalStorage, HostController, MigratePlanResult, ProgramStorage,
     ReducerCallResult, ReducerOutcome,
@@ -21,6 +21,7 @@ use crate::subscription::module_subscription_actor::ModuleSubscriptions;
 use crate::subscription::tx::DeltaTx;
 use crate::subscription::websocket_building::BuildableWebsocketFormat;
 use crate::util::jobs::{SingleCoreExecutor, WeakSingleCoreExecutor};
+use crate::util::slow::{self, record_slow_event, SlowQueryLogger};
 use crate::vm::check_row_limit;
 use crate::worker_metrics::WORKER_METRICS;
 use anyhow::Context;
@@ -46,6 +47,7 @@ use spacetimedb_lib::identity::{AuthCtx, RequestId};
 use spacetimedb_lib::metrics::ExecutionMetrics;
 use spacetimedb_lib::ConnectionId;
 use spacetimedb_lib::Timestamp;
+use spacetimedb_physical_plan::plan::ProjectListPlan;
 use spacetimedb_primitives::TableId;
 use spacetimedb_query::compile_subscription;
 use spacetimedb_sats::ProductValue;
@@ -195,6 +197,9 @@ pub struct ModuleEvent {
     pub host_execution_duration: Duration,
     pub request_id: Option<RequestId>,
     pub timer: Option<Instant>,
+    /// The BSATN-encoded value the reducer returned or failed with,
+    /// for reducers which declare return types.
+    pub return_value: Option<Bytes>,
 }
 
 /// Information about a running module.
@@ -861,11 +866,14 @@ impl ModuleHost {
         self.call("call_identity_connected", move |inst| {
             let reducer_lookup = me.info.module_def.lifecycle_reducer(Lifecycle::OnConnect);
             let stdb = &me.module.replica_ctx().relational_db;
+            // The transaction records the same timestamp the reducer sees,
+            // so that the call can be replayed deterministically.
+            let timestamp = Timestamp::now();
             let workload = Workload::Reducer(ReducerContext {
                 name: "call_identity_connected".to_owned(),
                 caller_identity: caller_auth.claims.identity,
                 caller_connection_id,
-                timestamp: Timestamp::now(),
+                timestamp,
                 arg_bsatn: Bytes::new(),
             });
             let mut_tx = stdb.begin_mut_tx(IsolationLevel::Serializable, workload);
@@ -897,6 +905,7 @@ impl ModuleHost {
                     None,
                     None,
                     None,
+                    timestamp,
                     reducer_id,
                     reducer_def,
                     FunctionArgs::Nullary,
@@ -961,12 +970,13 @@ impl ModuleHost {
 
         let is_client_exist = |mut_tx: &MutTxId| mut_tx.st_client_row(caller_identity, caller_connection_id).is_some();
 
+        let timestamp = Timestamp::now();
         let workload = || {
             Workload::Reducer(ReducerContext {
                 name: reducer_name.to_owned(),
                 caller_identity,
                 caller_connection_id,
-                timestamp: Timestamp::now(),
+                timestamp,
                 arg_bsatn: Bytes::new(),
             })
         };
@@ -1024,6 +1034,7 @@ impl ModuleHost {
                 None,
                 None,
                 None,
+                timestamp,
                 reducer_id,
                 reducer_def,
                 FunctionArgs::Nullary,
@@ -1110,6 +1121,7 @@ impl ModuleHost {
         client: Option<Arc<ClientConnectionSender>>,
         request_id: Option<RequestId>,
         timer: Option<Instant>,
+        timestamp: Timestamp,
         reducer_id: ReducerId,
         reducer_def: &ReducerDef,
         args: FunctionArgs,
@@ -1123,7 +1135,7 @@ impl ModuleHost {
                 inst.call_reducer(
                     None,
                     CallReducerParams {
-                        timestamp: Timestamp::now(),
+                        timestamp,
                         caller_identity,
                         caller_connection_id,
                         client,
@@ -1144,6 +1156,7 @@ impl ModuleHost {
         client: Option<Arc<ClientConnectionSender>>,
         request_id: Option<RequestId>,
         timer: Option<Instant>,
+        timestamp: Timestamp,
         reducer_id: ReducerId,
         reducer_def: &ReducerDef,
         args: FunctionArgs,
@@ -1156,7 +1169,7 @@ impl ModuleHost {
         Ok(module_instance.call_reducer(
             tx,
             CallReducerParams {
-                timestamp: Timestamp::now(),
+                timestamp,
                 caller_identity,
                 caller_connection_id,
                 client,
@@ -1177,6 +1190,34 @@ impl ModuleHost {
         timer: Option<Instant>,
         reducer_name: &str,
         args: FunctionArgs,
+    ) -> Result<ReducerCallResult, ReducerCallError> {
+        self.call_reducer_at(
+            caller_identity,
+            caller_connection_id,
+            client,
+            request_id,
+            timer,
+            Timestamp::now(),
+            reducer_name,
+            args,
+        )
+        .await
+    }
+
+    /// Like [`Self::call_reducer`], but tells the reducer that it is running at `timestamp`
+    /// rather than at the current wall-clock time.
+    ///
+    /// Used by the module testing harness to drive reducers with a virtual clock.
+    pub async fn call_reducer_at(
+        &self,
+        caller_identity: Identity,
+        caller_connection_id: Option<ConnectionId>,
+        client: Option<Arc<ClientConnectionSender>>,
+        request_id: Option<RequestId>,
+        timer: Option<Instant>,
+        timestamp: Timestamp,
+        reducer_name: &str,
+        args: FunctionArgs,
     ) -> Result<ReducerCallResult, ReducerCallError> {
         let res = async {
             let (reducer_id, reducer_def) = self
@@ -1193,6 +1234,7 @@ impl ModuleHost {
                 client,
                 request_id,
                 timer,
+                timestamp,
                 reducer_id,
                 reducer_def,
                 args,
@@ -1247,7 +1289,7 @@ impl ModuleHost {
                             name: reducer.into(),
                             caller_identity: params.caller_identity,
                             caller_connection_id: params.caller_connection_id,
-                            timestamp: Timestamp::now(),
+                            timestamp: params.timestamp,
                             arg_bsatn: params.args.get_bsatn().clone(),
                         }),
                     );
@@ -1342,9 +1384,14 @@ impl ModuleHost {
                     db.report_read_tx_metrics(reducer, tx_metrics);
                 });
 
+                let slow_query_threshold = db.query_limit(&tx)?.map(Duration::from_millis);
+                let slow_query = SlowQueryLogger::new(&query, slow_query_threshold, WorkloadType::Sql);
+                // The optimized plan, kept to identify the query if it turns out to be slow
+                let mut slow_plan = None;
+
                 // We wrap the actual query in a closure so we can use ? to handle errors without making
                 // the entire transaction abort with an error.
-                let result: Result<(OneOffTable<F>, ExecutionMetrics), anyhow::Error> = (|| {
+                let result: Result<(OneOffTable<F>, u64, ExecutionMetrics), anyhow::Error> = (|| {
                     let tx = SchemaViewer::new(&*tx, &auth);
 
                     let (
@@ -1360,7 +1407,7 @@ impl ModuleHost {
                     // Optimize each fragment
                     let optimized = plans
                         .into_iter()
-                        .map(|plan| plan.optimize())
+                        .map(|plan| plan.optimize_with(&tx))
                         .collect::<Result<Vec<_>, _>>()?;
 
                     check_row_limit(
@@ -1372,6 +1419,10 @@ impl ModuleHost {
                         &auth,
                     )?;
 
+                    if slow_query.is_enabled() {
+                        slow_plan = Some(ProjectListPlan::Name(optimized.clone()));
+                    }
+
                     let optimized = optimized
                         .into_iter()
                         // Convert into something we can execute
@@ -1380,13 +1431,21 @@ impl ModuleHost {
 
                     // Execute the union and return the results
                     execute_plan::<_, F>(&optimized, &DeltaTx::from(&*tx))
-                        .map(|(rows, _, metrics)| (OneOffTable { table_name, rows }, metrics))
+                        .map(|(rows, num_rows, metrics)| (OneOffTable { table_name, rows }, num_rows, metrics))
                         .context("One-off queries are not allowed to modify the database")
                 })();
 
                 let total_host_execution_duration = timer.elapsed().into();
+                let slow_event = result.as_ref().ok().and_then(|(_, num_rows, metrics)| {
+                    slow_query.event(
+                        caller_identity,
+                        || slow_plan.as_ref().map(slow::plan_hash),
+                        metrics.rows_scanned,
+                        *num_rows as usize,
+                    )
+                });
                 let (message, metrics): (SerializableMessage, Option<ExecutionMetrics>) = match result {
-                    Ok((rows, metrics)) => (
+                    Ok((rows, _, metrics)) => (
                         into_message(OneOffQueryResponseMessage {
                             message_id,
                             error: None,
@@ -1407,6 +1466,12 @@ impl ModuleHost {
                 };
 
                 subscriptions.send_client_message(client, message, (&*tx, tx_offset_receiver))?;
+
+                // Slow events are recorded in a separate tx,
+                // so we must release this one first.
+                drop(tx);
+                record_slow_event(&db, slow_event);
+
                 Ok::<Option<ExecutionMetrics>, anyhow::Error>(metrics)
             })
             .await??;
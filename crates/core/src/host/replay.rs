@@ -0,0 +1,120 @@
+//! Support for re-running the reducer which committed a transaction
+//! and comparing what it writes to what was originally committed.
+
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex;
+use spacetimedb_client_api_messages::replay::TableDiff;
+use spacetimedb_commitlog::payload::txdata::Ops;
+use spacetimedb_data_structures::map::HashMap;
+use spacetimedb_datastore::execution_context::Workload;
+use spacetimedb_durability::{Durability, DurableOffset, TxOffset};
+use spacetimedb_primitives::TableId;
+use spacetimedb_sats::satn::Satn as _;
+use spacetimedb_sats::ProductValue;
+use tokio::sync::watch;
+
+use crate::db::relational_db::{RelationalDB, Txdata};
+
+/// A [`Durability`] which keeps the transactions appended to it in memory,
+/// so that the writes of a replayed reducer can be inspected.
+pub(super) struct CapturingDurability {
+    txs: Mutex<Vec<Txdata>>,
+    durable_offset: watch::Sender<Option<TxOffset>>,
+}
+
+impl Default for CapturingDurability {
+    fn default() -> Self {
+        let (durable_offset, _) = watch::channel(None);
+        Self {
+            txs: Mutex::default(),
+            durable_offset,
+        }
+    }
+}
+
+impl CapturingDurability {
+    /// Take the transactions appended so far.
+    pub(super) fn take(&self) -> Vec<Txdata> {
+        std::mem::take(&mut *self.txs.lock())
+    }
+}
+
+impl Durability for CapturingDurability {
+    type TxData = Txdata;
+
+    fn append_tx(&self, tx: Self::TxData) {
+        self.txs.lock().push(tx);
+    }
+
+    fn durable_tx_offset(&self) -> DurableOffset {
+        self.durable_offset.subscribe().into()
+    }
+}
+
+/// Compare the rows written by the `committed` transaction to those written by its `replayed` counterpart.
+///
+/// Rows are compared per table, as multisets, since the order of rows within a transaction is not meaningful.
+/// Returns one [`TableDiff`] per table whose writes differ.
+pub(super) fn diff_writes(db: &RelationalDB, committed: &Txdata, replayed: Option<&Txdata>) -> Vec<TableDiff> {
+    let mut inserts = BTreeMap::<TableId, RowCounts>::new();
+    let mut deletes = BTreeMap::<TableId, RowCounts>::new();
+
+    let count = |counts: &mut BTreeMap<TableId, RowCounts>, ops: &[Ops<ProductValue>], delta| {
+        for ops in ops {
+            let counts = counts.entry(ops.table_id).or_default();
+            for row in ops.rowdata.iter() {
+                *counts.entry(row.clone()).or_default() += delta;
+            }
+        }
+    };
+    for (txdata, delta) in [(Some(committed), 1), (replayed, -1)] {
+        if let Some(mutations) = txdata.and_then(|txdata| txdata.mutations.as_ref()) {
+            count(&mut inserts, &mutations.inserts, delta);
+            count(&mut deletes, &mutations.deletes, delta);
+        }
+    }
+
+    let mut diffs = BTreeMap::<TableId, TableDiff>::new();
+    let mut record = |table_id, counts: RowCounts, field: fn(&mut TableDiff, bool) -> &mut Vec<String>| {
+        for (row, n) in counts.into_iter().filter(|(_, n)| *n != 0) {
+            let diff = diffs.entry(table_id).or_insert_with(|| TableDiff {
+                table_name: table_name(db, table_id),
+                ..<_>::default()
+            });
+            field(diff, n > 0).extend(std::iter::repeat_n(row.to_satn(), n.unsigned_abs()));
+        }
+    };
+    for (table_id, counts) in inserts {
+        record(table_id, counts, |diff, missing| {
+            if missing {
+                &mut diff.missing_inserts
+            } else {
+                &mut diff.extra_inserts
+            }
+        });
+    }
+    for (table_id, counts) in deletes {
+        record(table_id, counts, |diff, missing| {
+            if missing {
+                &mut diff.missing_deletes
+            } else {
+                &mut diff.extra_deletes
+            }
+        });
+    }
+
+    diffs.into_values().collect()
+}
+
+/// The number of times each row was written by the committed transaction,
+/// minus the number of times it was written by the replay.
+type RowCounts = HashMap<ProductValue, isize>;
+
+fn table_name(db: &RelationalDB, table_id: TableId) -> String {
+    db.with_read_only(Workload::Internal, |tx| {
+        let name = db.table_name_from_id(tx, table_id).ok().flatten();
+        name.map(|name| name.into_owned())
+    })
+    .unwrap_or_else(|| table_id.to_string())
+}
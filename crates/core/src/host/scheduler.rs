@@ -19,7 +19,7 @@ use crate::db::relational_db::RelationalDB;
 
 use super::module_host::ModuleEvent;
 use super::module_host::ModuleFunctionCall;
-use super::module_host::{CallReducerParams, WeakModuleHost};
+use super::module_host::{CallReducerParams, ModuleInfo, WeakModuleHost};
 use super::module_host::{DatabaseUpdate, EventStatus};
 use super::{FunctionArgs, ModuleHost, ReducerCallError};
 use spacetimedb_datastore::execution_context::Workload;
@@ -31,16 +31,16 @@ use spacetimedb_datastore::traits::IsolationLevel;
 pub struct ScheduledReducerId {
     /// The ID of the table whose rows hold the scheduled reducers.
     /// This table should have a entry in `ST_SCHEDULED`.
-    table_id: TableId,
+    pub(crate) table_id: TableId,
     /// The particular schedule row in the reducer scheduling table referred to by `self.table_id`.
-    schedule_id: u64,
+    pub(crate) schedule_id: u64,
     // These may seem redundant, but they're actually free - they fit in the struct padding.
     // `scheduled_id: u64, table_id: u32, id_column: u16, at_column: u16` == 16 bytes, same as
     // (`scheduled_id: u64, table_id: u32` == 12 bytes).pad_to_align() == 16 bytes
     /// The column that the primary key (`scheduled_id`) is in.
-    id_column: ColId,
+    pub(crate) id_column: ColId,
     /// The column that the `ScheduleAt` value is in.
-    at_column: ColId,
+    pub(crate) at_column: ColId,
 }
 
 spacetimedb_table::static_assert_size!(ScheduledReducerId, 16);
@@ -332,39 +332,9 @@ impl SchedulerActor {
 
         let call_reducer_params = move |tx: &MutTxId| match item {
             QueueItem::Id { id, at } => {
-                let Ok(schedule_row) = get_schedule_row_mut(tx, &db, id) else {
-                    // if the row is not found, it means the schedule is cancelled by the user
-                    log::debug!(
-                        "table row corresponding to yield scheduler id not found: tableid {}, schedulerId {}",
-                        id.table_id,
-                        id.schedule_id
-                    );
-                    return Ok(None);
-                };
-
-                let ScheduledReducer { reducer, bsatn_args } = process_schedule(tx, &db, id.table_id, &schedule_row)?;
-
-                let (reducer_id, reducer_seed) = module_info
-                    .module_def
-                    .reducer_arg_deserialize_seed(&reducer[..])
-                    .ok_or_else(|| anyhow!("Reducer not found: {reducer}"))?;
-
-                let reducer_args = FunctionArgs::Bsatn(bsatn_args.into()).into_tuple(reducer_seed)?;
-
                 // the timestamp we tell the reducer it's running at will be
                 // at least the timestamp it was scheduled to run at.
-                let timestamp = at.max(Timestamp::now());
-
-                Ok(Some(CallReducerParams {
-                    timestamp,
-                    caller_identity,
-                    caller_connection_id: ConnectionId::ZERO,
-                    client: None,
-                    request_id: None,
-                    timer: None,
-                    reducer_id,
-                    args: reducer_args,
-                }))
+                scheduled_reducer_params(tx, &db, &module_info, id, at.max(Timestamp::now()))
             }
             QueueItem::VolatileNonatomicImmediate { reducer_name, args } => {
                 let (reducer_id, reducer_seed) = module_info
@@ -420,36 +390,7 @@ impl SchedulerActor {
         let db = db.clone();
         let schedule_at = host_clone
             .on_module_thread("delete_scheduled_reducer_row", move || {
-                let mut tx = db.begin_mut_tx(IsolationLevel::Serializable, Workload::Internal);
-
-                match get_schedule_row_mut(&tx, &db, id) {
-                    Ok(schedule_row) => {
-                        if let Ok(schedule_at) = read_schedule_at(&schedule_row, id.at_column) {
-                            // If the schedule is an interval, we handle it as a repeated schedule
-                            if let ScheduleAt::Interval(_) = schedule_at {
-                                return Some(schedule_at);
-                            }
-                            let row_ptr = schedule_row.pointer();
-                            db.delete(&mut tx, id.table_id, [row_ptr]);
-
-                            commit_and_broadcast_deletion_event(tx, module_host);
-                        } else {
-                            log::debug!(
-                                "Failed to read 'scheduled_at' from row: table_id {}, schedule_id {}",
-                                id.table_id,
-                                id.schedule_id
-                            );
-                        }
-                    }
-                    Err(_) => {
-                        log::debug!(
-                            "Table row corresponding to yield scheduler ID not found: table_id {}, scheduler_id {}",
-                            id.table_id,
-                            id.schedule_id
-                        );
-                    }
-                }
-                None
+                finish_scheduled_row(&db, id, module_host)
             })
             .await?;
         // If this was repeated, we need to add it back to the queue.
@@ -467,6 +408,90 @@ impl SchedulerActor {
     }
 }
 
+/// Build the [`CallReducerParams`] for the scheduled row identified by `id`,
+/// telling the reducer that it is running at `timestamp`.
+///
+/// Returns `Ok(None)` if the row no longer exists, i.e. the schedule was cancelled.
+pub(crate) fn scheduled_reducer_params(
+    tx: &MutTxId,
+    db: &RelationalDB,
+    module_info: &ModuleInfo,
+    id: ScheduledReducerId,
+    timestamp: Timestamp,
+) -> anyhow::Result<Option<CallReducerParams>> {
+    let Ok(schedule_row) = get_schedule_row_mut(tx, db, id) else {
+        // if the row is not found, it means the schedule is cancelled by the user
+        log::debug!(
+            "table row corresponding to yield scheduler id not found: tableid {}, schedulerId {}",
+            id.table_id,
+            id.schedule_id
+        );
+        return Ok(None);
+    };
+
+    let ScheduledReducer { reducer, bsatn_args } = process_schedule(tx, db, id.table_id, &schedule_row)?;
+
+    let (reducer_id, reducer_seed) = module_info
+        .module_def
+        .reducer_arg_deserialize_seed(&reducer[..])
+        .ok_or_else(|| anyhow!("Reducer not found: {reducer}"))?;
+
+    let reducer_args = FunctionArgs::Bsatn(bsatn_args.into()).into_tuple(reducer_seed)?;
+
+    Ok(Some(CallReducerParams {
+        timestamp,
+        caller_identity: module_info.database_identity,
+        caller_connection_id: ConnectionId::ZERO,
+        client: None,
+        request_id: None,
+        timer: None,
+        reducer_id,
+        args: reducer_args,
+    }))
+}
+
+/// Delete the row of the schedule `id` once its reducer has run,
+/// unless it is a repeating schedule, in which case its `ScheduleAt` is returned
+/// so that the caller can queue the next run.
+///
+/// Must be called on the module thread.
+pub(crate) fn finish_scheduled_row(
+    db: &RelationalDB,
+    id: ScheduledReducerId,
+    module_host: ModuleHost,
+) -> Option<ScheduleAt> {
+    let mut tx = db.begin_mut_tx(IsolationLevel::Serializable, Workload::Internal);
+
+    match get_schedule_row_mut(&tx, db, id) {
+        Ok(schedule_row) => {
+            if let Ok(schedule_at) = read_schedule_at(&schedule_row, id.at_column) {
+                // If the schedule is an interval, we handle it as a repeated schedule
+                if let ScheduleAt::Interval(_) = schedule_at {
+                    return Some(schedule_at);
+                }
+                let row_ptr = schedule_row.pointer();
+                db.delete(&mut tx, id.table_id, [row_ptr]);
+
+                commit_and_broadcast_deletion_event(tx, module_host);
+            } else {
+                log::debug!(
+                    "Failed to read 'scheduled_at' from row: table_id {}, schedule_id {}",
+                    id.table_id,
+                    id.schedule_id
+                );
+            }
+        }
+        Err(_) => {
+            log::debug!(
+                "Table row corresponding to yield scheduler ID not found: table_id {}, scheduler_id {}",
+                id.table_id,
+                id.schedule_id
+            );
+        }
+    }
+    None
+}
+
 fn commit_and_broadcast_deletion_event(tx: MutTxId, module_host: ModuleHost) {
     let caller_identity = module_host.info().database_identity;
 
@@ -481,6 +506,7 @@ fn commit_and_broadcast_deletion_event(tx: MutTxId, module_host: ModuleHost) {
         host_execution_duration: Duration::from_millis(0),
         request_id: None,
         timer: None,
+        return_value: None,
     };
 
     if let Err(e) = module_host
@@ -16,6 +16,7 @@ use crate::host::wasm_common::instrumentation::span;
 use crate::host::wasm_common::module_host_actor::{ReducerOp, ReducerResult};
 use crate::host::wasm_common::{err_to_errno_and_log, RowIterIdx, TimingSpan, TimingSpanIdx};
 use crate::host::AbiCall;
+use bytes::Bytes;
 use spacetimedb_lib::{bsatn, Identity, RawModuleDef};
 use spacetimedb_primitives::{errno, ColId, IndexId, ReducerId, TableId};
 use spacetimedb_sats::Serialize;
@@ -352,9 +353,10 @@ pub(super) fn call_call_reducer<'scope>(
     let ret = call_free_fun(scope, fun, args)?;
 
     // Deserialize the user result.
-    let user_res = deserialize_js(scope, ret)?;
+    // JS reducers don't yet return values, so a successful call returns nothing.
+    let user_res: Result<(), Box<str>> = deserialize_js(scope, ret)?;
 
-    Ok(user_res)
+    Ok(user_res.map(|()| Bytes::new()).map_err(Into::into))
 }
 
 /// Gets a handle to the `__describe_module__` property on `object`.
@@ -1,3 +1,4 @@
+use bytes::Bytes;
 use prometheus::{Histogram, IntCounter, IntGauge};
 use spacetimedb_lib::db::raw_def::v9::Lifecycle;
 use spacetimedb_schema::auto_migrate::{MigratePlan, MigrationPolicy, MigrationPolicyError};
@@ -21,14 +22,15 @@ use crate::module_host_context::ModuleCreationContext;
 use crate::replica_context::ReplicaContext;
 use crate::subscription::module_subscription_actor::WriteConflict;
 use crate::util::prometheus_handle::{HistogramExt, TimerGuard};
+use crate::util::slow;
 use crate::worker_metrics::WORKER_METRICS;
 use spacetimedb_datastore::db_metrics::DB_METRICS;
-use spacetimedb_datastore::execution_context::{self, ReducerContext, Workload};
+use spacetimedb_datastore::execution_context::{self, ReducerContext, Workload, WorkloadType};
 use spacetimedb_datastore::locking_tx_datastore::MutTxId;
 use spacetimedb_datastore::traits::{IsolationLevel, Program};
 use spacetimedb_lib::buffer::DecodeError;
 use spacetimedb_lib::identity::AuthCtx;
-use spacetimedb_lib::{bsatn, ConnectionId, RawModuleDef, Timestamp};
+use spacetimedb_lib::{bsatn, hash_bytes, AlgebraicType, ConnectionId, RawModuleDef, Timestamp};
 
 use super::*;
 
@@ -76,7 +78,25 @@ pub struct ExecutionTimings {
 }
 
 /// The result that `__call_reducer__` produces during normal non-trap execution.
-pub type ReducerResult = Result<(), Box<str>>;
+///
+/// On success, this holds the BSATN-encoded value returned by the reducer,
+/// which is empty for reducers returning `()`.
+pub type ReducerResult = Result<Bytes, ReducerFailure>;
+
+/// The error that a reducer fails with during normal non-trap execution.
+#[derive(Debug)]
+pub struct ReducerFailure {
+    /// A message describing the error.
+    pub message: Box<str>,
+    /// The BSATN-encoded error value, for reducers declaring an error type.
+    pub value: Option<Bytes>,
+}
+
+impl From<Box<str>> for ReducerFailure {
+    fn from(message: Box<str>) -> Self {
+        Self { message, value: None }
+    }
+}
 
 pub struct ExecuteResult {
     pub energy: EnergyStats,
@@ -387,6 +407,7 @@ impl InstanceCommon {
         let energy_fingerprint = ReducerFingerprint {
             module_hash: self.info.module_hash,
             module_identity: self.info.owner_identity,
+            database_identity,
             caller_identity,
             reducer_name,
         };
@@ -438,6 +459,22 @@ impl InstanceCommon {
         maybe_log_long_running_reducer(reducer_name, timings.total_duration);
         reducer_span.exit();
 
+        let slow_event = match stdb.reducer_limit(&tx) {
+            Ok(Some(ms)) if timings.total_duration > Duration::from_millis(ms) => Some(slow::slow_event(
+                WorkloadType::Reducer,
+                reducer_name,
+                // Reducers have no query plan, so they are identified by name.
+                hash_bytes(reducer_name),
+                caller_identity,
+                timings.total_duration,
+                tx.metrics.rows_scanned,
+                0,
+            )),
+            _ => None,
+        };
+
+        // The BSATN-encoded value returned by the reducer, for reducers declaring return types.
+        let mut return_value = None;
         let status = match call_result {
             Err(err) => {
                 log_traceback("reducer", reducer_name, &err);
@@ -463,21 +500,35 @@ impl InstanceCommon {
             }
             // We haven't actually committed yet - `commit_and_broadcast_event` will commit
             // for us and replace this with the actual database update.
-            Ok(res) => match res.and_then(|()| {
+            Ok(res) => match res.and_then(|value| {
                 // If this is an OnDisconnect lifecycle event, remove the client from st_clients.
                 // We handle OnConnect events before running the reducer.
                 match reducer_def.lifecycle {
                     Some(Lifecycle::OnDisconnect) => tx
                         .delete_st_client(caller_identity, caller_connection_id, database_identity)
-                        .map_err(|e| e.to_string().into()),
+                        .map_err(|e| ReducerFailure::from(e.to_string().into_boxed_str())),
                     _ => Ok(()),
                 }
+                .map(|()| value)
             }) {
-                Ok(()) => EventStatus::Committed(DatabaseUpdate::default()),
-                Err(err) => {
-                    log::info!("reducer returned error: {err}");
-                    log_reducer_error(replica_ctx, timestamp, reducer_name, &err);
-                    EventStatus::Failed(err.into())
+                Ok(value) => {
+                    if reducer_def.has_typed_return() {
+                        return_value = Some(value);
+                    }
+                    EventStatus::Committed(DatabaseUpdate::default())
+                }
+                Err(ReducerFailure { message, value }) => {
+                    log::info!("reducer returned error: {message}");
+                    log_reducer_error(replica_ctx, timestamp, reducer_name, &message);
+                    if reducer_def.has_typed_return() {
+                        // Reducers whose error type is `String` fail with just a message,
+                        // which is sent to clients as the error value.
+                        return_value = value.or_else(|| {
+                            (reducer_def.err_return_type == AlgebraicType::String)
+                                .then(|| bsatn::to_vec(&*message).unwrap().into())
+                        });
+                    }
+                    EventStatus::Failed(message.into())
                 }
             },
         };
@@ -496,11 +547,14 @@ impl InstanceCommon {
             host_execution_duration: timings.total_duration,
             request_id,
             timer,
+            return_value,
         };
         let event = commit_and_broadcast_event(&self.info, client, event, tx);
+        slow::record_slow_event(stdb, slow_event);
 
         ReducerCallResult {
             outcome: ReducerOutcome::from(&event.status),
+            return_value: event.return_value.clone(),
             energy_used: energy_quanta_used,
             execution_duration: timings.total_duration,
         }
@@ -8,8 +8,10 @@ use crate::host::module_common::run_describer;
 use crate::host::wasm_common::module_host_actor::{DescribeError, InitializationError};
 use crate::host::wasm_common::*;
 use crate::util::string_from_utf8_lossy_owned;
+use bytes::Bytes;
 use futures_util::FutureExt;
-use spacetimedb_primitives::errno::HOST_CALL_FAILURE;
+use spacetimedb_lib::bsatn;
+use spacetimedb_primitives::errno::{HOST_CALL_FAILURE, HOST_CALL_FAILURE_VALUE};
 use wasmtime::{
     AsContext, AsContextMut, ExternType, Instance, InstancePre, Linker, Store, TypedFunc, WasmBacktrace, WasmParams,
     WasmResults,
@@ -89,6 +91,24 @@ fn handle_error_sink_code(code: i32, error: Vec<u8>) -> Result<(), Box<str>> {
 }
 
 const CALL_FAILURE: i32 = HOST_CALL_FAILURE.get() as i32;
+const CALL_FAILURE_VALUE: i32 = HOST_CALL_FAILURE_VALUE.get() as i32;
+
+/// Interprets the `code` returned by `__call_reducer__` along with the contents of its `result` sink.
+fn handle_result_sink_code(code: i32, result: Vec<u8>) -> module_host_actor::ReducerResult {
+    match code {
+        0 => Ok(result.into()),
+        CALL_FAILURE => Err(string_from_utf8_lossy_owned(result).into_boxed_str().into()),
+        CALL_FAILURE_VALUE => {
+            // The sink holds the error message as a BSATN string, followed by the BSATN-encoded value.
+            let mut rest = &result[..];
+            let message = bsatn::from_reader::<Box<str>>(&mut rest)
+                .map_err(|_| Box::<str>::from("malformed reducer error message"))?;
+            let value = Some(Bytes::copy_from_slice(rest));
+            Err(module_host_actor::ReducerFailure { message, value })
+        }
+        _ => Err(Box::<str>::from("unknown return code").into()),
+    }
+}
 
 /// Invoke `typed_func` and assert that it doesn't yield.
 ///
@@ -214,10 +234,10 @@ impl module_host_actor::WasmInstance for WasmtimeInstance {
         let [sender_0, sender_1, sender_2, sender_3] = bytemuck::must_cast(op.caller_identity.to_byte_array());
         let [conn_id_0, conn_id_1] = bytemuck::must_cast(op.caller_connection_id.as_le_byte_array());
 
-        // Prepare arguments to the reducer + the error sink & start timings.
+        // Prepare arguments to the reducer + the result sink & start timings.
         let args_bytes = op.args.get_bsatn().clone();
 
-        let (args_source, errors_sink) = store.data_mut().start_reducer(op.name, args_bytes, op.timestamp);
+        let (args_source, result_sink) = store.data_mut().start_reducer(op.name, args_bytes, op.timestamp);
 
         let call_result = call_sync_typed_func(
             &self.call_reducer,
@@ -232,16 +252,16 @@ impl module_host_actor::WasmInstance for WasmtimeInstance {
                 conn_id_1,
                 op.timestamp.to_micros_since_unix_epoch() as u64,
                 args_source.0,
-                errors_sink,
+                result_sink,
             ),
         );
 
         // Signal that this reducer call is finished. This gets us the timings
         // associated to our reducer call, and clears all of the instance state
         // associated to the call.
-        let (timings, error) = store.data_mut().finish_reducer();
+        let (timings, result) = store.data_mut().finish_reducer();
 
-        let call_result = call_result.map(|code| handle_error_sink_code(code, error));
+        let call_result = call_result.map(|code| handle_result_sink_code(code, result));
 
         // Compute fuel and heap usage.
         let remaining_fuel = get_store_fuel(store);
@@ -20,6 +20,7 @@ pub mod module_host_context;
 pub mod replica_context;
 pub mod startup;
 pub mod subscription;
+pub mod testing;
 pub mod util;
 pub mod vm;
 pub mod worker_metrics;
@@ -8,7 +8,8 @@ use spacetimedb_expr::check::SchemaView;
 use spacetimedb_expr::statement::compile_sql_stmt;
 use spacetimedb_lib::db::auth::StAccess;
 use spacetimedb_lib::identity::AuthCtx;
-use spacetimedb_primitives::{ColId, TableId};
+use spacetimedb_physical_plan::cost::Statistics;
+use spacetimedb_primitives::{ColId, ColList, TableId};
 use spacetimedb_sats::{AlgebraicType, AlgebraicValue};
 use spacetimedb_schema::def::error::RelationError;
 use spacetimedb_schema::relation::{ColExpr, FieldName};
@@ -539,6 +540,16 @@ impl<T: StateView> SchemaView for SchemaViewer<'_, T> {
     }
 }
 
+impl Statistics for SchemaViewer<'_, Tx> {
+    fn row_count(&self, table_id: TableId) -> Option<u64> {
+        self.tx.table_row_count(table_id)
+    }
+
+    fn num_distinct_values(&self, table_id: TableId, cols: &ColList) -> Option<u64> {
+        self.tx.num_distinct_values(table_id, cols).map(|n| n.get())
+    }
+}
+
 impl<'a, T> SchemaViewer<'a, T> {
     pub fn new(tx: &'a T, auth: &'a AuthCtx) -> Self {
         Self { tx, auth }
@@ -1,8 +1,9 @@
+use std::cell::Cell;
 use std::sync::Arc;
 use std::time::Duration;
 
 use super::ast::SchemaViewer;
-use crate::db::relational_db::{RelationalDB, Tx};
+use crate::db::relational_db::{MutTx, RelationalDB, Tx};
 use crate::energy::EnergyQuanta;
 use crate::error::DBError;
 use crate::estimation::estimate_rows_scanned;
@@ -11,19 +12,25 @@ use crate::host::ArgsTuple;
 use crate::subscription::module_subscription_actor::{ModuleSubscriptions, WriteConflict};
 use crate::subscription::module_subscription_manager::TransactionOffset;
 use crate::subscription::tx::DeltaTx;
-use crate::util::slow::SlowQueryLogger;
+use crate::util::slow::{plan_hash, record_slow_event, SlowQueryLogger};
 use crate::vm::{check_row_limit, DbProgram, TxMode};
 use anyhow::anyhow;
-use spacetimedb_datastore::execution_context::Workload;
+use spacetimedb_datastore::execution_context::{Workload, WorkloadType};
 use spacetimedb_datastore::locking_tx_datastore::state_view::StateView;
 use spacetimedb_datastore::traits::IsolationLevel;
-use spacetimedb_expr::statement::Statement;
+use spacetimedb_expr::expr::ProjectList;
+use spacetimedb_expr::statement::{Explain, Statement, DML};
 use spacetimedb_lib::identity::AuthCtx;
 use spacetimedb_lib::metrics::ExecutionMetrics;
 use spacetimedb_lib::Timestamp;
 use spacetimedb_lib::{AlgebraicType, ProductType, ProductValue};
-use spacetimedb_query::{compile_sql_stmt, execute_dml_stmt, execute_select_stmt};
+use spacetimedb_physical_plan::compile::compile_select_list;
+use spacetimedb_physical_plan::plan::{ProjectListPlan, ProjectPlan};
+use spacetimedb_primitives::TableId;
+use spacetimedb_query::{compile_sql_stmt, execute_dml_stmt, execute_select_stmt, explain_select_plan};
+use spacetimedb_sats::product;
 use spacetimedb_schema::relation::FieldName;
+use spacetimedb_subscription::{SubscriptionPlan, TableName};
 use spacetimedb_vm::eval::run_ast;
 use spacetimedb_vm::expr::{CodeResult, CrudExpr, Expr};
 use spacetimedb_vm::relation::MemTable;
@@ -142,6 +149,7 @@ pub fn execute_sql(
                 host_execution_duration: Duration::ZERO,
                 request_id: None,
                 timer: None,
+                return_value: None,
             };
             match subs.unwrap().commit_and_broadcast_event(None, event, tx).unwrap() {
                 Ok(_) => res,
@@ -173,6 +181,12 @@ pub fn execute_sql_tx<'a>(
     execute(&mut DbProgram::new(db, &mut tx, auth), ast, sql, &mut updates).map(Some)
 }
 
+/// A SQL statement which only reads from the database.
+enum Query {
+    Select(ProjectList),
+    Explain(Explain),
+}
+
 pub struct SqlResult {
     /// The offset of the SQL operation's transaction.
     ///
@@ -199,121 +213,189 @@ pub fn run(
         compile_sql_stmt(sql_text, &SchemaViewer::new(tx, &auth), &auth)
     })?;
 
+    let query = match stmt {
+        Statement::Select(stmt) => Query::Select(stmt),
+        Statement::Explain(stmt) => Query::Explain(stmt),
+        Statement::DML(stmt) => return run_dml(db, tx, stmt, auth, subs),
+    };
+
     let mut metrics = ExecutionMetrics::default();
 
-    match stmt {
-        Statement::Select(stmt) => {
-            // Up to this point, the tx has been read-only,
-            // and hence there are no deltas to process.
-            let (tx_data, tx_metrics_mut, tx) = tx.commit_downgrade(Workload::Sql);
-
-            let (tx_offset_send, tx_offset) = oneshot::channel();
-            // Release the tx on drop, so that we record metrics
-            // and set the transaction offset.
-            let mut tx = scopeguard::guard(tx, |tx| {
-                let (offset, tx_metrics_downgrade, reducer) = db.release_tx(tx);
-                let _ = tx_offset_send.send(offset);
-                db.report_tx_metrics(
-                    reducer,
-                    Some(Arc::new(tx_data)),
-                    Some(tx_metrics_mut),
-                    Some(tx_metrics_downgrade),
-                );
-            });
+    // Up to this point, the tx has been read-only,
+    // and hence there are no deltas to process.
+    let (tx_data, tx_metrics_mut, tx) = tx.commit_downgrade(Workload::Sql);
+
+    let (tx_offset_send, tx_offset) = oneshot::channel();
+    // Release the tx on drop, so that we record metrics
+    // and set the transaction offset.
+    let mut tx = scopeguard::guard(tx, |tx| {
+        let (offset, tx_metrics_downgrade, reducer) = db.release_tx(tx);
+        let _ = tx_offset_send.send(offset);
+        db.report_tx_metrics(
+            reducer,
+            Some(Arc::new(tx_data)),
+            Some(tx_metrics_mut),
+            Some(tx_metrics_downgrade),
+        );
+    });
 
+    let slow_query_threshold = db.query_limit(&tx)?.map(Duration::from_millis);
+    let slow_query = SlowQueryLogger::new(sql_text, slow_query_threshold, WorkloadType::Sql);
+    // The optimized plan, kept to identify the query if it turns out to be slow
+    let slow_plan = Cell::new(None);
+
+    let row_limit = |plan: ProjectListPlan| -> anyhow::Result<ProjectListPlan> {
+        if slow_query.is_enabled() {
+            slow_plan.set(Some(plan.clone()));
+        }
+        check_row_limit(
+            &[&plan],
+            db,
+            &tx,
+            |plan, tx| plan.plan_iter().map(|plan| estimate_rows_scanned(tx, plan)).sum(),
+            &auth,
+        )?;
+        Ok(plan)
+    };
+
+    let rows = match query {
+        Query::Select(stmt) => {
             // Compute the header for the result set
             stmt.for_each_return_field(|col_name, col_type| {
                 head.push((col_name.into(), col_type.clone()));
             });
 
             // Evaluate the query
-            let rows = execute_select_stmt(stmt, &DeltaTx::from(&*tx), &mut metrics, |plan| {
-                check_row_limit(
-                    &[&plan],
-                    db,
-                    &tx,
-                    |plan, tx| plan.plan_iter().map(|plan| estimate_rows_scanned(tx, plan)).sum(),
-                    &auth,
-                )?;
-                Ok(plan)
-            })?;
-
-            // Update transaction metrics
-            tx.metrics.merge(metrics);
-
-            Ok(SqlResult {
-                tx_offset,
-                rows,
-                metrics: tx.metrics,
-            })
+            execute_select_stmt(stmt, &DeltaTx::from(&*tx), &mut metrics, row_limit)?
         }
-        Statement::DML(stmt) => {
-            // An extra layer of auth is required for DML
-            if auth.caller != auth.owner {
-                return Err(anyhow!("Only owners are authorized to run SQL DML statements").into());
+        Query::Explain(Explain { analyze, select }) => {
+            // The plan is returned as text, one row per line
+            head.push(("QUERY PLAN".into(), AlgebraicType::String));
+            // A query that returns the rows of a single table can also be a subscription
+            let return_table = select
+                .return_table()
+                .map(|schema| (schema.table_id, TableName::from(schema.table_name.clone())));
+            let plan = compile_select_list(select);
+            let delta_tx = DeltaTx::from(&*tx);
+            let mut lines = explain_select_plan(plan.clone(), analyze, &delta_tx, &mut metrics, row_limit)?;
+            if let (Some((return_id, return_name)), ProjectListPlan::Name(plans)) = (return_table, plan) {
+                lines.extend(explain_subscription(plans, return_id, return_name, &delta_tx));
             }
+            lines.into_iter().map(|line| product![line]).collect()
+        }
+    };
 
-            // Evaluate the mutation
-            let (mut tx, _) = db.with_auto_rollback(tx, |tx| execute_dml_stmt(stmt, tx, &mut metrics))?;
+    // Update transaction metrics
+    tx.metrics.merge(metrics);
+    let metrics = tx.metrics;
+
+    // Slow events are recorded in a separate tx,
+    // so we must release this one first.
+    let slow_event = slow_query.event(
+        auth.caller,
+        || slow_plan.take().as_ref().map(plan_hash),
+        metrics.rows_scanned,
+        rows.len(),
+    );
+    drop(tx);
+    record_slow_event(db, slow_event);
+
+    Ok(SqlResult {
+        tx_offset,
+        rows,
+        metrics,
+    })
+}
 
-            // Update transaction metrics
-            tx.metrics.merge(metrics);
+/// Explain how a subscription to `plans` would maintain its view,
+/// or why the query cannot be used as a subscription.
+fn explain_subscription(
+    plans: Vec<ProjectPlan>,
+    return_id: TableId,
+    return_name: TableName,
+    tx: &DeltaTx,
+) -> Vec<String> {
+    match SubscriptionPlan::compile_from_plans(plans, return_id, return_name, tx) {
+        Ok(plans) => plans.iter().flat_map(SubscriptionPlan::explain).collect(),
+        Err(err) => vec![format!("Subscription: not supported: {err}")],
+    }
+}
 
-            // Commit the tx if there are no deltas to process
-            if subs.is_none() {
-                let metrics = tx.metrics;
-                return db.commit_tx(tx).map(|tx_opt| {
-                    let (tx_offset, tx_data, tx_metrics, reducer) = tx_opt.unwrap();
+/// Run the DML statement `stmt` in `tx`, the transaction in which [`run`] compiled it.
+fn run_dml(
+    db: &RelationalDB,
+    tx: MutTx,
+    stmt: DML,
+    auth: AuthCtx,
+    subs: Option<&ModuleSubscriptions>,
+) -> Result<SqlResult, DBError> {
+    let mut metrics = ExecutionMetrics::default();
 
-                    let (tx_offset_sender, tx_offset_receiver) = oneshot::channel();
-                    let _ = tx_offset_sender.send(tx_offset);
+    // An extra layer of auth is required for DML
+    if auth.caller != auth.owner {
+        return Err(anyhow!("Only owners are authorized to run SQL DML statements").into());
+    }
 
-                    db.report_mut_tx_metrics(reducer, tx_metrics, Some(tx_data));
-                    SqlResult {
-                        tx_offset: tx_offset_receiver,
-                        rows: vec![],
-                        metrics,
-                    }
-                });
-            }
+    // Evaluate the mutation
+    let (mut tx, _) = db.with_auto_rollback(tx, |tx| execute_dml_stmt(stmt, tx, &mut metrics))?;
 
-            // Otherwise downgrade the tx and process the deltas.
-            // Note, we get the delta by downgrading the tx.
-            // Hence we just pass a default `DatabaseUpdate` here.
-            // It will ultimately be replaced with the correct one.
-            match subs
-                .unwrap()
-                .commit_and_broadcast_event(
-                    None,
-                    ModuleEvent {
-                        timestamp: Timestamp::now(),
-                        caller_identity: auth.caller,
-                        caller_connection_id: None,
-                        function_call: ModuleFunctionCall {
-                            reducer: String::new(),
-                            reducer_id: u32::MAX.into(),
-                            args: ArgsTuple::default(),
-                        },
-                        status: EventStatus::Committed(DatabaseUpdate::default()),
-                        energy_quanta_used: EnergyQuanta::ZERO,
-                        host_execution_duration: Duration::ZERO,
-                        request_id: None,
-                        timer: None,
-                    },
-                    tx,
-                )
-                .unwrap()
-            {
-                Err(WriteConflict) => {
-                    todo!("See module_host_actor::call_reducer_with_tx")
-                }
-                Ok(res) => Ok(SqlResult {
-                    tx_offset: res.tx_offset,
-                    rows: vec![],
-                    metrics,
-                }),
+    // Update transaction metrics
+    tx.metrics.merge(metrics);
+
+    // Commit the tx if there are no deltas to process
+    if subs.is_none() {
+        let metrics = tx.metrics;
+        return db.commit_tx(tx).map(|tx_opt| {
+            let (tx_offset, tx_data, tx_metrics, reducer) = tx_opt.unwrap();
+
+            let (tx_offset_sender, tx_offset_receiver) = oneshot::channel();
+            let _ = tx_offset_sender.send(tx_offset);
+
+            db.report_mut_tx_metrics(reducer, tx_metrics, Some(tx_data));
+            SqlResult {
+                tx_offset: tx_offset_receiver,
+                rows: vec![],
+                metrics,
             }
+        });
+    }
+
+    // Otherwise downgrade the tx and process the deltas.
+    // Note, we get the delta by downgrading the tx.
+    // Hence we just pass a default `DatabaseUpdate` here.
+    // It will ultimately be replaced with the correct one.
+    match subs
+        .unwrap()
+        .commit_and_broadcast_event(
+            None,
+            ModuleEvent {
+                timestamp: Timestamp::now(),
+                caller_identity: auth.caller,
+                caller_connection_id: None,
+                function_call: ModuleFunctionCall {
+                    reducer: String::new(),
+                    reducer_id: u32::MAX.into(),
+                    args: ArgsTuple::default(),
+                },
+                status: EventStatus::Committed(DatabaseUpdate::default()),
+                energy_quanta_used: EnergyQuanta::ZERO,
+                host_execution_duration: Duration::ZERO,
+                request_id: None,
+                timer: None,
+                return_value: None,
+            },
+            tx,
+        )
+        .unwrap()
+    {
+        Err(WriteConflict) => {
+            todo!("See module_host_actor::call_reducer_with_tx")
         }
+        Ok(res) => Ok(SqlResult {
+            tx_offset: res.tx_offset,
+            rows: vec![],
+            metrics,
+        }),
     }
 }
 
@@ -432,6 +514,58 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_explain() -> ResultTest<()> {
+        let (db, _) = create_data(5)?;
+
+        let lines = |rows: Vec<ProductValue>| -> Vec<String> {
+            rows.into_iter()
+                .map(|row| row.elements[0].as_string().unwrap().to_string())
+                .collect()
+        };
+
+        // The query is also a valid subscription,
+        // so the plans for maintaining its view are explained too
+        let subscription = [
+            "Subscription to inventory:",
+            "  Initial rows:",
+            "    Filter: inventory.name = \"health3\"",
+            "    -> Seq Scan on inventory",
+            "  Insert fragment 1:",
+            "    Filter: inventory.name = \"health3\"",
+            "    -> Seq Scan on inventory (delta Inserts)",
+            "  Delete fragment 1:",
+            "    Filter: inventory.name = \"health3\"",
+            "    -> Seq Scan on inventory (delta Deletes)",
+        ];
+
+        let sql = "EXPLAIN SELECT * FROM inventory WHERE name = 'health3'";
+        let result = lines(run_for_testing(&db, sql)?);
+        assert_eq!(
+            result[..2],
+            ["Filter: inventory.name = \"health3\"", "-> Seq Scan on inventory"]
+        );
+        assert_eq!(result[2..], subscription);
+
+        let sql = "EXPLAIN ANALYZE SELECT * FROM inventory WHERE name = 'health3'";
+        let result = lines(run_for_testing(&db, sql)?);
+        assert_eq!(result.len(), 4 + subscription.len());
+        assert!(result[0].starts_with("Filter: inventory.name = \"health3\"  (actual rows=1 time="));
+        assert!(result[1].starts_with("-> Seq Scan on inventory  (actual rows=5 time="));
+        assert_eq!(result[2], "Rows returned: 1");
+        assert!(result[3].starts_with("Execution time: "));
+        assert_eq!(result[4..], subscription);
+
+        // A query with a limit is not a subscription
+        let sql = "EXPLAIN SELECT * FROM inventory LIMIT 1";
+        let result = lines(run_for_testing(&db, sql)?);
+        assert!(!result.iter().any(|line| line.starts_with("Subscription")));
+
+        // Only queries can be explained
+        assert!(run_for_testing(&db, "EXPLAIN DELETE FROM inventory").is_err());
+        Ok(())
+    }
+
     /// Test the evaluation of SELECT, UPDATE, and DELETE parameterized with `:sender`
     #[test]
     fn test_sender_param() -> ResultTest<()> {
@@ -1,4 +1,5 @@
 use crate::subscription::websocket_building::{BuildableWebsocketFormat, RowListBuilder as _};
+use crate::util::slow::{subscription_plan_hash, SlowQueryLogger};
 use crate::{error::DBError, worker_metrics::WORKER_METRICS};
 use anyhow::Result;
 use module_subscription_manager::Plan;
@@ -9,11 +10,13 @@ use spacetimedb_client_api_messages::websocket::{
 };
 use spacetimedb_datastore::{
     db_metrics::DB_METRICS, execution_context::WorkloadType, locking_tx_datastore::datastore::MetricsRecorder,
+    system_tables::StSlowEventRow,
 };
 use spacetimedb_execution::{pipelined::PipelinedProject, Datastore, DeltaStore};
 use spacetimedb_lib::{metrics::ExecutionMetrics, Identity};
 use spacetimedb_primitives::TableId;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub mod delta;
 pub mod execution_unit;
@@ -123,6 +126,16 @@ pub enum TableUpdateType {
     Unsubscribe,
 }
 
+impl TableUpdateType {
+    /// The kind of workload evaluating queries for this kind of update.
+    pub fn workload(self) -> WorkloadType {
+        match self {
+            Self::Subscribe => WorkloadType::Subscribe,
+            Self::Unsubscribe => WorkloadType::Unsubscribe,
+        }
+    }
+}
+
 /// Execute a subscription query and collect the results in a [TableUpdate]
 pub fn collect_table_update<Tx, F>(
     plan_fragments: &[PipelinedProject],
@@ -159,11 +172,16 @@ where
 }
 
 /// Execute a collection of subscription queries in parallel
+///
+/// Returns, alongside the results, an event for `st_slow_event`
+/// for each query fragment which took longer than `slow_query_threshold`.
 pub fn execute_plans<Tx, F>(
     plans: &[Arc<Plan>],
     tx: &Tx,
     update_type: TableUpdateType,
-) -> Result<(DatabaseUpdate<F>, ExecutionMetrics), DBError>
+    caller: Identity,
+    slow_query_threshold: Option<Duration>,
+) -> Result<(DatabaseUpdate<F>, ExecutionMetrics, Vec<StSlowEventRow>), DBError>
 where
     Tx: Datastore + DeltaStore + Sync,
     F: BuildableWebsocketFormat,
@@ -178,12 +196,23 @@ where
                 .all(|table_id| tx.table(table_id).is_some_and(|t| t.row_count > 0))
         })
         .map(|(sql, plan)| (sql, plan, plan.subscribed_table_id(), plan.subscribed_table_name()))
-        .map(|(sql, plan, table_id, table_name)| {
-            plan.optimized_physical_plan()
+        .map(|(sql, fragment, table_id, table_name)| {
+            let slow_query = SlowQueryLogger::new(sql, slow_query_threshold, update_type.workload());
+            fragment
+                .optimized_physical_plan()
                 .clone()
                 .optimize()
                 .map(|plan| (sql, PipelinedProject::from(plan)))
                 .and_then(|(_, plan)| collect_table_update(&[plan], table_id, (&**table_name).into(), tx, update_type))
+                .map(|(update, metrics)| {
+                    let slow_event = slow_query.event(
+                        caller,
+                        || Some(subscription_plan_hash([fragment])),
+                        metrics.rows_scanned,
+                        update.num_rows as usize,
+                    );
+                    (update, metrics, slow_event)
+                })
                 .map_err(|err| DBError::WithSql {
                     sql: sql.into(),
                     error: Box::new(DBError::Other(err)),
@@ -194,10 +223,12 @@ where
             let n = table_updates_with_metrics.len();
             let mut tables = Vec::with_capacity(n);
             let mut aggregated_metrics = ExecutionMetrics::default();
-            for (update, metrics) in table_updates_with_metrics {
+            let mut slow_events = Vec::new();
+            for (update, metrics, slow_event) in table_updates_with_metrics {
                 tables.push(update);
                 aggregated_metrics.merge(metrics);
+                slow_events.extend(slow_event);
             }
-            (DatabaseUpdate { tables }, aggregated_metrics)
+            (DatabaseUpdate { tables }, aggregated_metrics, slow_events)
         })
 }
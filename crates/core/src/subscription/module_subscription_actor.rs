@@ -7,8 +7,8 @@ use super::query::compile_query_with_hashes;
 use super::tx::DeltaTx;
 use super::{collect_table_update, TableUpdateType};
 use crate::client::messages::{
-    SerializableMessage, SubscriptionData, SubscriptionError, SubscriptionMessage, SubscriptionResult,
-    SubscriptionRows, SubscriptionUpdateMessage, TransactionUpdateMessage,
+    ResumedSubscriptionsData, SerializableMessage, SubscriptionData, SubscriptionError, SubscriptionMessage,
+    SubscriptionResult, SubscriptionRows, SubscriptionUpdateMessage, TransactionUpdateMessage,
 };
 use crate::client::{ClientActorId, ClientConnectionSender, Protocol};
 use crate::db::relational_db::{MutTx, RelationalDB, Tx};
@@ -16,33 +16,73 @@ use crate::error::DBError;
 use crate::estimation::estimate_rows_scanned;
 use crate::host::module_host::{DatabaseUpdate, EventStatus, ModuleEvent};
 use crate::messages::websocket::Subscribe;
-use crate::subscription::execute_plans;
 use crate::subscription::query::is_subscribe_to_all_tables;
+use crate::subscription::{execute_delta_plans, execute_plans};
 use crate::util::prometheus_handle::IntGaugeExt;
+use crate::util::slow::{record_slow_event, subscription_plan_hash, SlowQueryLogger};
 use crate::vm::check_row_limit;
 use crate::worker_metrics::WORKER_METRICS;
-use parking_lot::RwLock;
+use parking_lot::{RwLock, RwLockWriteGuard};
 use prometheus::{Histogram, HistogramTimer, IntCounter, IntGauge};
 use scopeguard::ScopeGuard;
 use spacetimedb_client_api_messages::websocket::{
-    self as ws, BsatnFormat, FormatSwitch, JsonFormat, SubscribeMulti, SubscribeSingle, TableUpdate, Unsubscribe,
-    UnsubscribeMulti,
+    self as ws, BsatnFormat, FormatSwitch, JsonFormat, ResumeSubscriptions, SubscribeMulti, SubscribeSingle,
+    TableUpdate, Unsubscribe, UnsubscribeMulti,
 };
 use spacetimedb_datastore::db_metrics::DB_METRICS;
 use spacetimedb_datastore::execution_context::{Workload, WorkloadType};
 use spacetimedb_datastore::locking_tx_datastore::datastore::TxMetrics;
 use spacetimedb_datastore::locking_tx_datastore::TxId;
+use spacetimedb_datastore::system_tables::StSlowEventRow;
 use spacetimedb_datastore::traits::TxData;
 use spacetimedb_durability::TxOffset;
 use spacetimedb_execution::pipelined::PipelinedProject;
 use spacetimedb_lib::identity::AuthCtx;
 use spacetimedb_lib::metrics::ExecutionMetrics;
 use spacetimedb_lib::Identity;
-use std::{sync::Arc, time::Instant};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 
 type Subscriptions = Arc<RwLock<SubscriptionManager>>;
 
+/// How a read transaction started by [`ModuleSubscriptions::begin_tx`] views the committed state.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReadView {
+    /// Read from a snapshot of the committed state, which doesn't hold up commits.
+    Snapshot,
+    /// Hold a shared lock on the committed state, so that no commit can happen in the meantime.
+    Locked,
+}
+
+/// The queries of a subscription request, hashed outside of a tx.
+/// See [`ModuleSubscriptions::compile_queries`].
+struct HashedQueries<'a> {
+    subscribe_to_all_tables: bool,
+    hashes: Vec<(&'a str, QueryHash, QueryHash)>,
+}
+
+impl<'a> HashedQueries<'a> {
+    fn new(sender: Identity, queries: &'a [Box<str>]) -> Self {
+        let mut subscribe_to_all_tables = false;
+        let mut hashes = Vec::with_capacity(queries.len());
+        for sql in queries {
+            let sql = sql.trim();
+            if is_subscribe_to_all_tables(sql) {
+                subscribe_to_all_tables = true;
+                continue;
+            }
+            let hash = QueryHash::from_string(sql, sender, false);
+            let hash_with_param = QueryHash::from_string(sql, sender, true);
+            hashes.push((sql, hash, hash_with_param));
+        }
+        Self {
+            subscribe_to_all_tables,
+            hashes,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ModuleSubscriptions {
     relational_db: Arc<RelationalDB>,
@@ -249,6 +289,8 @@ impl ModuleSubscriptions {
         let table_id = query.subscribed_table_id();
         let table_name = query.subscribed_table_name();
 
+        let slow_query = SlowQueryLogger::new(query.sql(), self.sub_limit(tx), update_type.workload());
+
         let plans = query
             .plans_fragments()
             .map(|fragment| fragment.optimized_physical_plan())
@@ -261,12 +303,55 @@ impl ModuleSubscriptions {
 
         let tx = DeltaTx::from(tx);
 
-        Ok(match sender.config.protocol {
+        let (table_update, metrics) = match sender.config.protocol {
             Protocol::Binary => collect_table_update(&plans, table_id, table_name.into(), &tx, update_type)
                 .map(|(table_update, metrics)| (FormatSwitch::Bsatn(table_update), metrics)),
             Protocol::Text => collect_table_update(&plans, table_id, table_name.into(), &tx, update_type)
                 .map(|(table_update, metrics)| (FormatSwitch::Json(table_update), metrics)),
-        }?)
+        }?;
+
+        let num_rows = match &table_update {
+            FormatSwitch::Bsatn(update) => update.num_rows,
+            FormatSwitch::Json(update) => update.num_rows,
+        };
+        if let Some(event) = slow_query.event(
+            auth.caller,
+            || Some(subscription_plan_hash(query.plans_fragments())),
+            metrics.rows_scanned,
+            num_rows as usize,
+        ) {
+            // We're holding a tx, so the event can only be buffered.
+            self.relational_db.buffer_slow_event(event);
+        }
+
+        Ok((table_update, metrics))
+    }
+
+    /// Read the [ST_VARNAME_SLOW_SUB] threshold for evaluating subscription queries.
+    ///
+    /// [ST_VARNAME_SLOW_SUB]: spacetimedb_datastore::system_tables::ST_VARNAME_SLOW_SUB
+    fn sub_limit(&self, tx: &TxId) -> Option<Duration> {
+        self.relational_db
+            .sub_limit(tx)
+            .ok()
+            .flatten()
+            .map(Duration::from_millis)
+    }
+
+    /// Buffer the `slow_events` recorded while evaluating subscriptions.
+    /// They are written to `st_slow_event` once we no longer hold a tx,
+    /// see [`Self::flush_slow_events`].
+    fn buffer_slow_events(&self, slow_events: Vec<StSlowEventRow>) {
+        for event in slow_events {
+            self.relational_db.buffer_slow_event(event);
+        }
+    }
+
+    /// Write the slow events buffered by earlier subscriptions to `st_slow_event`, if they're due.
+    ///
+    /// Must be called without holding a tx or the subscription lock.
+    fn flush_slow_events(&self) {
+        record_slow_event(&self.relational_db, None);
     }
 
     fn evaluate_queries(
@@ -289,17 +374,35 @@ impl ModuleSubscriptions {
             auth,
         )?;
 
+        let slow_query_threshold = self.sub_limit(tx);
         let tx = DeltaTx::from(tx);
-        match sender.config.protocol {
-            Protocol::Binary => {
-                let (update, metrics) = execute_plans(queries, &tx, update_type)?;
-                Ok((FormatSwitch::Bsatn(update), metrics))
-            }
-            Protocol::Text => {
-                let (update, metrics) = execute_plans(queries, &tx, update_type)?;
-                Ok((FormatSwitch::Json(update), metrics))
-            }
-        }
+        let (update, metrics, slow_events) = match sender.config.protocol {
+            Protocol::Binary => execute_plans(queries, &tx, update_type, auth.caller, slow_query_threshold)
+                .map(|(update, metrics, slow_events)| (FormatSwitch::Bsatn(update), metrics, slow_events))?,
+            Protocol::Text => execute_plans(queries, &tx, update_type, auth.caller, slow_query_threshold)
+                .map(|(update, metrics, slow_events)| (FormatSwitch::Json(update), metrics, slow_events))?,
+        };
+        self.buffer_slow_events(slow_events);
+        Ok((update, metrics))
+    }
+
+    /// Evaluates the changes to the results of `queries` made by the transaction `tx` was built from.
+    fn evaluate_delta_queries(
+        &self,
+        sender: &ClientConnectionSender,
+        queries: &[Arc<Plan>],
+        tx: &DeltaTx,
+        auth: &AuthCtx,
+    ) -> Result<(FullSubscriptionUpdate, ExecutionMetrics), DBError> {
+        let slow_query_threshold = self.sub_limit(tx);
+        let (update, metrics, slow_events) = match sender.config.protocol {
+            Protocol::Binary => execute_delta_plans(queries, tx, auth.caller, slow_query_threshold)
+                .map(|(update, metrics, slow_events)| (FormatSwitch::Bsatn(update), metrics, slow_events))?,
+            Protocol::Text => execute_delta_plans(queries, tx, auth.caller, slow_query_threshold)
+                .map(|(update, metrics, slow_events)| (FormatSwitch::Json(update), metrics, slow_events))?,
+        };
+        self.buffer_slow_events(slow_events);
+        Ok((update, metrics))
     }
 
     /// Add a subscription to a single query.
@@ -333,36 +436,52 @@ impl ModuleSubscriptions {
         let hash = QueryHash::from_string(&sql, auth.caller, false);
         let hash_with_param = QueryHash::from_string(&sql, auth.caller, true);
 
-        let (tx, tx_offset) = self.begin_tx(Workload::Subscribe);
+        // Write the slow events of earlier subscriptions before taking a tx.
+        self.flush_slow_events();
 
-        let existing_query = {
-            let guard = self.subscriptions.read();
-            guard.query(&hash)
-        };
+        let mut view = ReadView::Snapshot;
+        let (tx, tx_offset, query, table_rows, metrics, mut subscriptions) = loop {
+            let (tx, tx_offset) = self.begin_tx(Workload::Subscribe, view);
 
-        let query = return_on_err_with_sql!(
-            existing_query.map(Ok).unwrap_or_else(|| compile_query_with_hashes(
-                &auth,
-                &tx,
-                &sql,
-                hash,
-                hash_with_param
-            )
-            .map(Arc::new)),
-            sql,
-            send_err_msg
-        );
+            let existing_query = {
+                let guard = self.subscriptions.read();
+                guard.query(&hash)
+            };
 
-        let (table_rows, metrics) = return_on_err_with_sql!(
-            self.evaluate_initial_subscription(sender.clone(), query.clone(), &tx, &auth, TableUpdateType::Subscribe),
-            query.sql(),
-            send_err_msg
-        );
+            let query = return_on_err_with_sql!(
+                existing_query.map(Ok).unwrap_or_else(|| compile_query_with_hashes(
+                    &auth,
+                    &tx,
+                    &sql,
+                    hash,
+                    hash_with_param
+                )
+                .map(Arc::new)),
+                sql,
+                send_err_msg
+            );
 
-        // It acquires the subscription lock after `eval`, allowing `add_subscription` to run concurrently.
-        // This also makes it possible for `broadcast_event` to get scheduled before the subsequent part here
-        // but that should not pose an issue.
-        let mut subscriptions = self.subscriptions.write();
+            let (table_rows, metrics) = return_on_err_with_sql!(
+                self.evaluate_initial_subscription(
+                    sender.clone(),
+                    query.clone(),
+                    &tx,
+                    &auth,
+                    TableUpdateType::Subscribe
+                ),
+                query.sql(),
+                send_err_msg
+            );
+
+            // It acquires the subscription lock after `eval`, allowing `add_subscription` to run concurrently.
+            // This also makes it possible for `broadcast_event` to get scheduled before the subsequent part here,
+            // in which case the snapshot is stale and we evaluate again.
+            let subscriptions = self.subscriptions.write();
+            if Self::is_current(&subscriptions, &tx, view) {
+                break (tx, tx_offset, query, table_rows, metrics, subscriptions);
+            }
+            view = ReadView::Locked;
+        };
         subscriptions.add_subscription(sender.clone(), query.clone(), request.query_id)?;
 
         #[cfg(test)]
@@ -371,12 +490,11 @@ impl ModuleSubscriptions {
         }
 
         // Note: to make sure transaction updates are consistent, we need to put this in the broadcast
-        // queue while we are still holding a read-lock on the database.
-
-        // That will avoid race conditions because reducers first grab a write lock on the db, then
-        // grab a read lock on the subscriptions.
+        // queue while we are still holding a write lock on `self.subscriptions`.
 
-        // Holding a write lock on `self.subscriptions` would also be sufficient.
+        // That will avoid race conditions because reducers grab a read lock on the subscriptions
+        // before committing, so every commit is either visible to `tx` and broadcast before this,
+        // or broadcast after this with the subscriptions as updated here.
         let _ = self.broadcast_queue.send_client_message(
             sender.clone(),
             Some(tx_offset),
@@ -418,7 +536,9 @@ impl ModuleSubscriptions {
             )
         };
 
-        let mut subscriptions = self.subscriptions.write();
+        // Always lock the db before the subscription lock to avoid deadlocks.
+        let (tx, tx_offset, mut subscriptions) =
+            self.begin_tx_and_lock_subscriptions(Workload::Unsubscribe, || self.subscriptions.write());
 
         let queries = return_on_err!(
             subscriptions.remove_subscription((sender.id.identity, sender.id.connection_id), request.query_id),
@@ -434,7 +554,6 @@ impl ModuleSubscriptions {
             return Ok(None);
         };
 
-        let (tx, tx_offset) = self.begin_tx(Workload::Unsubscribe);
         let auth = AuthCtx::new(self.owner_identity, sender.id.identity);
         let (table_rows, metrics) = return_on_err_with_sql!(
             self.evaluate_initial_subscription(sender.clone(), query.clone(), &tx, &auth, TableUpdateType::Unsubscribe),
@@ -443,12 +562,11 @@ impl ModuleSubscriptions {
         );
 
         // Note: to make sure transaction updates are consistent, we need to put this in the broadcast
-        // queue while we are still holding a read-lock on the database.
+        // queue while we are still holding a write lock on `self.subscriptions`.
 
-        // That will avoid race conditions because reducers first grab a write lock on the db, then
-        // grab a read lock on the subscriptions.
-
-        // Holding a write lock on `self.subscriptions` would also be sufficient.
+        // That will avoid race conditions because reducers grab a read lock on the subscriptions
+        // before committing, so every commit is either visible to `tx` and broadcast before this,
+        // or broadcast after this with the subscriptions as updated here.
         let _ = self.broadcast_queue.send_client_message(
             sender.clone(),
             Some(tx_offset),
@@ -495,16 +613,15 @@ impl ModuleSubscriptions {
         let subscription_metrics = SubscriptionMetrics::new(&database_identity, &WorkloadType::Unsubscribe);
 
         // Always lock the db before the subscription lock to avoid deadlocks.
-        let (tx, tx_offset) = self.begin_tx(Workload::Unsubscribe);
+        let (tx, tx_offset, mut subscriptions) = self.begin_tx_and_lock_subscriptions(Workload::Unsubscribe, || {
+            // How contended is the lock?
+            let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
+            let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
+            self.subscriptions.write()
+        });
 
         let removed_queries = {
             let _compile_timer = subscription_metrics.compilation_time.start_timer();
-            let mut subscriptions = {
-                // How contended is the lock?
-                let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
-                let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
-                self.subscriptions.write()
-            };
 
             return_on_err!(
                 subscriptions.remove_subscription((sender.id.identity, sender.id.connection_id), request.query_id),
@@ -512,6 +629,7 @@ impl ModuleSubscriptions {
                 None
             )
         };
+        drop(subscriptions);
 
         let (update, metrics) = return_on_err!(
             self.evaluate_queries(
@@ -530,13 +648,8 @@ impl ModuleSubscriptions {
             .num_queries_evaluated
             .inc_by(removed_queries.len() as _);
 
-        // Note: to make sure transaction updates are consistent, we need to put this in the broadcast
-        // queue while we are still holding a read-lock on the database.
-
-        // That will avoid race conditions because reducers first grab a write lock on the db, then
-        // grab a read lock on the subscriptions.
-
-        // Holding a write lock on `self.subscriptions` would also be sufficient.
+        // Note: unlike when subscribing, this can go into the broadcast queue after later commits,
+        // as `self.subscriptions` no longer sends updates for `removed_queries` to this client.
         let _ = self.broadcast_queue.send_client_message(
             sender,
             Some(tx_offset),
@@ -570,28 +683,31 @@ impl ModuleSubscriptions {
         queries: &[Box<str>],
         num_queries: usize,
         metrics: &SubscriptionMetrics,
+        view: ReadView,
     ) -> Result<(Vec<Arc<Plan>>, AuthCtx, TxId, HistogramTimer), DBError> {
-        let mut subscribe_to_all_tables = false;
-        let mut plans = Vec::with_capacity(num_queries);
-        let mut query_hashes = Vec::with_capacity(num_queries);
-
-        for sql in queries {
-            let sql = sql.trim();
-            if is_subscribe_to_all_tables(sql) {
-                subscribe_to_all_tables = true;
-                continue;
-            }
-            let hash = QueryHash::from_string(sql, sender, false);
-            let hash_with_param = QueryHash::from_string(sql, sender, true);
-            query_hashes.push((sql, hash, hash_with_param));
-        }
-
+        let queries = HashedQueries::new(sender, queries);
         let auth = AuthCtx::new(self.owner_identity, sender);
 
         // We always get the db lock before the subscription lock to avoid deadlocks.
-        let (tx, _tx_offset) = self.begin_tx(Workload::Subscribe);
+        let (tx, _tx_offset) = self.begin_tx(Workload::Subscribe, view);
 
         let compile_timer = metrics.compilation_time.start_timer();
+        let plans = self.compile_hashed_queries(&auth, &tx, &queries, num_queries, metrics)?;
+
+        Ok((plans, auth, scopeguard::ScopeGuard::into_inner(tx), compile_timer))
+    }
+
+    /// Compiles `queries`, hashed by [`HashedQueries::new`], against `tx`,
+    /// reusing the plans of queries which are already tracked.
+    fn compile_hashed_queries(
+        &self,
+        auth: &AuthCtx,
+        tx: &TxId,
+        queries: &HashedQueries<'_>,
+        num_queries: usize,
+        metrics: &SubscriptionMetrics,
+    ) -> Result<Vec<Arc<Plan>>, DBError> {
+        let mut plans = Vec::with_capacity(num_queries);
 
         let guard = {
             // How contended is the lock?
@@ -600,9 +716,9 @@ impl ModuleSubscriptions {
             self.subscriptions.read()
         };
 
-        if subscribe_to_all_tables {
+        if queries.subscribe_to_all_tables {
             plans.extend(
-                super::subscription::get_all(&self.relational_db, &tx, &auth)?
+                super::subscription::get_all(&self.relational_db, tx, auth)?
                     .into_iter()
                     .map(Arc::new),
             );
@@ -610,14 +726,14 @@ impl ModuleSubscriptions {
 
         let mut new_queries = 0;
 
-        for (sql, hash, hash_with_param) in query_hashes {
+        for &(sql, hash, hash_with_param) in &queries.hashes {
             if let Some(unit) = guard.query(&hash) {
                 plans.push(unit);
             } else if let Some(unit) = guard.query(&hash_with_param) {
                 plans.push(unit);
             } else {
                 plans.push(Arc::new(
-                    compile_query_with_hashes(&auth, &tx, sql, hash, hash_with_param).map_err(|err| {
+                    compile_query_with_hashes(auth, tx, sql, hash, hash_with_param).map_err(|err| {
                         DBError::WithSql {
                             error: Box::new(DBError::Other(err.into())),
                             sql: sql.into(),
@@ -631,7 +747,7 @@ impl ModuleSubscriptions {
         // How many queries in this subscription are not cached?
         metrics.num_new_queries_subscribed.inc_by(new_queries);
 
-        Ok((plans, auth, scopeguard::ScopeGuard::into_inner(tx), compile_timer))
+        Ok(plans)
     }
 
     /// Send a message to a client connection.
@@ -680,33 +796,40 @@ impl ModuleSubscriptions {
         // How many queries make up this subscription?
         subscription_metrics.num_queries_subscribed.inc_by(num_queries as _);
 
-        let (queries, auth, tx, compile_timer) = return_on_err!(
-            self.compile_queries(
-                sender.id.identity,
-                &request.query_strings,
-                num_queries,
-                &subscription_metrics
-            ),
-            send_err_msg,
-            None
-        );
-        let (tx, tx_offset) = self.guard_tx(tx, <_>::default());
-
-        // We minimize locking so that other clients can add subscriptions concurrently.
-        // We are protected from race conditions with broadcasts, because we have the db lock,
-        // an `commit_and_broadcast_event` grabs a read lock on `subscriptions` while it still has a
-        // write lock on the db.
-        let queries = {
-            let mut subscriptions = {
+        // Write the slow events of earlier subscriptions before taking a tx.
+        self.flush_slow_events();
+
+        let mut view = ReadView::Snapshot;
+        let (queries, auth, tx, tx_offset, compile_timer, mut subscriptions) = loop {
+            let (queries, auth, tx, compile_timer) = return_on_err!(
+                self.compile_queries(
+                    sender.id.identity,
+                    &request.query_strings,
+                    num_queries,
+                    &subscription_metrics,
+                    view,
+                ),
+                send_err_msg,
+                None
+            );
+            let (tx, tx_offset) = self.guard_tx(tx, <_>::default());
+
+            let subscriptions = {
                 // How contended is the lock?
                 let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
                 let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
                 self.subscriptions.write()
             };
-
-            subscriptions.add_subscription_multi(sender.clone(), queries, request.query_id)?
+            if Self::is_current(&subscriptions, &tx, view) {
+                break (queries, auth, tx, tx_offset, compile_timer, subscriptions);
+            }
+            view = ReadView::Locked;
         };
 
+        // We hold the lock until the initial rows are enqueued,
+        // as `commit_and_broadcast_event` could otherwise broadcast an update to these queries ahead of them.
+        let queries = subscriptions.add_subscription_multi(sender.clone(), queries, request.query_id)?;
+
         // Record how long it took to compile the subscription
         drop(compile_timer);
 
@@ -714,12 +837,6 @@ impl ModuleSubscriptions {
             self.evaluate_queries(sender.clone(), &queries, &tx, &auth, TableUpdateType::Subscribe)
         else {
             // If we fail the query, we need to remove the subscription.
-            let mut subscriptions = {
-                // How contended is the lock?
-                let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
-                let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
-                self.subscriptions.write()
-            };
             {
                 let _compile_timer = subscription_metrics.compilation_time.start_timer();
                 subscriptions.remove_subscription((sender.id.identity, sender.id.connection_id), request.query_id)?;
@@ -738,13 +855,11 @@ impl ModuleSubscriptions {
         }
 
         // Note: to make sure transaction updates are consistent, we need to put this in the broadcast
-        // queue while we are still holding a read-lock on the database.
-
-        // That will avoid race conditions because reducers first grab a write lock on the db, then
-        // grab a read lock on the subscriptions.
-
-        // Holding a write lock on `self.subscriptions` would also be sufficient.
+        // queue while we are still holding a write lock on `self.subscriptions`.
 
+        // That will avoid race conditions because reducers grab a read lock on the subscriptions
+        // before committing, so every commit is either visible to `tx` and broadcast before this,
+        // or broadcast after this with the subscriptions as updated here.
         let _ = self.broadcast_queue.send_client_message(
             sender.clone(),
             Some(tx_offset),
@@ -759,6 +874,171 @@ impl ModuleSubscriptions {
         Ok(Some(metrics))
     }
 
+    /// Add the query sets of a [`ResumeSubscriptions`] request.
+    ///
+    /// If the database still knows every transaction since the position the client names,
+    /// the client is sent just the changes to each query set's rows since then.
+    /// Otherwise, or if any query set fails, the client is sent every row of the other query sets.
+    /// A query set which fails is reported to the client with a [`SubscriptionError`].
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn resume_subscriptions(
+        &self,
+        sender: Arc<ClientConnectionSender>,
+        request: ResumeSubscriptions,
+        timer: Instant,
+    ) -> Result<Option<ExecutionMetrics>, DBError> {
+        let num_queries = request
+            .subscriptions
+            .iter()
+            .map(|sub| sub.query_strings.len())
+            .sum::<usize>();
+
+        let database_identity = self.relational_db.database_identity();
+        let subscription_metrics = SubscriptionMetrics::new(&database_identity, &WorkloadType::Subscribe);
+
+        // How many queries make up these subscriptions?
+        subscription_metrics.num_queries_subscribed.inc_by(num_queries as _);
+
+        // Write the slow events of earlier subscriptions before taking a tx.
+        self.flush_slow_events();
+
+        let hashed = request
+            .subscriptions
+            .iter()
+            .map(|sub| (sub.query_id, HashedQueries::new(sender.id.identity, &sub.query_strings)))
+            .collect::<Vec<_>>();
+        let auth = AuthCtx::new(self.owner_identity, sender.id.identity);
+
+        // The query sets which failed, with the error to send for each.
+        let mut failed = Vec::new();
+
+        let mut view = ReadView::Snapshot;
+        let (compiled, tx, tx_offset, compile_timer, mut subscriptions) = loop {
+            // We always get the db lock before the subscription lock to avoid deadlocks.
+            let (tx, tx_offset) = self.begin_tx(Workload::Subscribe, view);
+            let compile_timer = subscription_metrics.compilation_time.start_timer();
+
+            failed.clear();
+            let mut compiled = Vec::with_capacity(hashed.len());
+            for (query_id, queries) in &hashed {
+                match self.compile_hashed_queries(&auth, &tx, queries, queries.hashes.len(), &subscription_metrics) {
+                    Ok(plans) => compiled.push((*query_id, plans)),
+                    Err(err) => failed.push((*query_id, err.to_string().into_boxed_str())),
+                }
+            }
+
+            let subscriptions = {
+                // How contended is the lock?
+                let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
+                let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
+                self.subscriptions.write()
+            };
+            if Self::is_current(&subscriptions, &tx, view) {
+                break (compiled, tx, tx_offset, compile_timer, subscriptions);
+            }
+            view = ReadView::Locked;
+        };
+
+        // We hold the lock until the rows are enqueued,
+        // as `commit_and_broadcast_event` could otherwise broadcast an update to these queries ahead of them.
+        let mut added = Vec::with_capacity(compiled.len());
+        for (query_id, plans) in compiled {
+            match subscriptions.add_subscription_multi(sender.clone(), plans, query_id) {
+                Ok(queries) => added.push((query_id, queries)),
+                Err(err) => failed.push((query_id, err.to_string().into_boxed_str())),
+            }
+        }
+
+        // Record how long it took to compile the subscriptions
+        drop(compile_timer);
+
+        // The client's rows for a failed query set would never be brought up to date,
+        // so in that case, the client must replace all of its rows.
+        let mut changes = request.since.filter(|_| failed.is_empty()).and_then(|since| {
+            let up_to = tx.next_tx_offset().saturating_sub(1);
+            self.relational_db
+                .changes_since(since.history_id, since.tx_offset, up_to)
+        });
+
+        let mut metrics = ExecutionMetrics::default();
+        let updates = loop {
+            let delta_tx = match &changes {
+                Some(changes) => DeltaTx::new(&tx, changes, subscriptions.index_ids_for_subscriptions()),
+                None => DeltaTx::from(&*tx),
+            };
+            let mut updates = Vec::with_capacity(added.len());
+            let mut failed_at = None;
+            for (i, (query_id, queries)) in added.iter().enumerate() {
+                let result = match changes {
+                    Some(_) => self.evaluate_delta_queries(&sender, queries, &delta_tx, &auth),
+                    None => self.evaluate_queries(sender.clone(), queries, &tx, &auth, TableUpdateType::Subscribe),
+                };
+                match result {
+                    Ok((update, query_metrics)) => {
+                        metrics.merge(query_metrics);
+                        updates.push((*query_id, SubscriptionData { data: update }));
+                    }
+                    Err(_) => {
+                        failed_at = Some(i);
+                        break;
+                    }
+                }
+            }
+
+            let Some(i) = failed_at else {
+                break updates;
+            };
+            // If we fail the query, we need to remove the subscription,
+            // and start over with every row of the remaining ones.
+            let (query_id, _) = added.remove(i);
+            {
+                let _compile_timer = subscription_metrics.compilation_time.start_timer();
+                subscriptions.remove_subscription((sender.id.identity, sender.id.connection_id), query_id)?;
+            }
+            failed.push((query_id, "Internal error evaluating queries".into()));
+            changes = None;
+        };
+
+        // How many queries did we actually evaluate?
+        subscription_metrics
+            .num_queries_evaluated
+            .inc_by(added.iter().map(|(_, queries)| queries.len()).sum::<usize>() as _);
+
+        for (query_id, message) in failed {
+            let _ = self.broadcast_queue.send_client_message(
+                sender.clone(),
+                None,
+                SubscriptionMessage {
+                    request_id: Some(request.request_id),
+                    query_id: Some(query_id),
+                    timer: Some(timer),
+                    result: SubscriptionResult::Error(SubscriptionError {
+                        table_id: None,
+                        message,
+                    }),
+                },
+            );
+        }
+
+        // Note: as in `add_multi_subscription`, we put this in the broadcast queue
+        // while we are still holding a write lock on `self.subscriptions`.
+        let _ = self.broadcast_queue.send_client_message(
+            sender,
+            Some(tx_offset),
+            SubscriptionMessage {
+                request_id: Some(request.request_id),
+                query_id: None,
+                timer: Some(timer),
+                result: SubscriptionResult::Resume(ResumedSubscriptionsData {
+                    is_delta: changes.is_some(),
+                    subscriptions: updates,
+                }),
+            },
+        );
+
+        Ok(Some(metrics))
+    }
+
     /// Add a subscriber to the module. NOTE: this function is blocking.
     /// This is used for the legacy subscription API which uses a set of queries.
     #[tracing::instrument(level = "trace", skip_all)]
@@ -776,50 +1056,70 @@ impl ModuleSubscriptions {
         // How many queries make up this subscription?
         subscription_metrics.num_queries_subscribed.inc_by(num_queries as _);
 
-        let (queries, auth, tx, compile_timer) = self.compile_queries(
-            sender.id.identity,
-            &subscription.query_strings,
-            num_queries,
-            &subscription_metrics,
-        )?;
-        let (tx, tx_offset) = self.guard_tx(tx, <_>::default());
+        // Write the slow events of earlier subscriptions before taking a tx.
+        self.flush_slow_events();
 
-        check_row_limit(
-            &queries,
-            &self.relational_db,
-            &tx,
-            |plan, tx| {
-                plan.plans_fragments()
-                    .map(|plan_fragment| estimate_rows_scanned(tx, plan_fragment.optimized_physical_plan()))
-                    .fold(0, |acc, rows_scanned| acc.saturating_add(rows_scanned))
-            },
-            &auth,
-        )?;
-
-        // Record how long it took to compile the subscription
-        drop(compile_timer);
+        let mut view = ReadView::Snapshot;
+        let (queries, tx, tx_offset, database_update, metrics, mut subscriptions) = loop {
+            let (queries, auth, tx, compile_timer) = self.compile_queries(
+                sender.id.identity,
+                &subscription.query_strings,
+                num_queries,
+                &subscription_metrics,
+                view,
+            )?;
+            let (tx, tx_offset) = self.guard_tx(tx, <_>::default());
 
-        let tx = DeltaTx::from(&*tx);
-        let (database_update, metrics) = match sender.config.protocol {
-            Protocol::Binary => execute_plans(&queries, &tx, TableUpdateType::Subscribe)
-                .map(|(table_update, metrics)| (FormatSwitch::Bsatn(table_update), metrics))?,
-            Protocol::Text => execute_plans(&queries, &tx, TableUpdateType::Subscribe)
-                .map(|(table_update, metrics)| (FormatSwitch::Json(table_update), metrics))?,
-        };
+            check_row_limit(
+                &queries,
+                &self.relational_db,
+                &tx,
+                |plan, tx| {
+                    plan.plans_fragments()
+                        .map(|plan_fragment| estimate_rows_scanned(tx, plan_fragment.optimized_physical_plan()))
+                        .fold(0, |acc, rows_scanned| acc.saturating_add(rows_scanned))
+                },
+                &auth,
+            )?;
 
-        // It acquires the subscription lock after `eval`, allowing `add_subscription` to run concurrently.
-        // This also makes it possible for `broadcast_event` to get scheduled before the subsequent part here
-        // but that should not pose an issue.
-        {
-            let _compile_timer = subscription_metrics.compilation_time.start_timer();
+            // Record how long it took to compile the subscription
+            drop(compile_timer);
+
+            let (database_update, metrics) = {
+                let slow_query_threshold = self.sub_limit(&tx);
+                let tx = DeltaTx::from(&*tx);
+                let caller = auth.caller;
+                let (database_update, metrics, slow_events) = match sender.config.protocol {
+                    Protocol::Binary => {
+                        execute_plans(&queries, &tx, TableUpdateType::Subscribe, caller, slow_query_threshold)
+                            .map(|(table_update, metrics, slow)| (FormatSwitch::Bsatn(table_update), metrics, slow))?
+                    }
+                    Protocol::Text => {
+                        execute_plans(&queries, &tx, TableUpdateType::Subscribe, caller, slow_query_threshold)
+                            .map(|(table_update, metrics, slow)| (FormatSwitch::Json(table_update), metrics, slow))?
+                    }
+                };
+                self.buffer_slow_events(slow_events);
+                (database_update, metrics)
+            };
 
-            let mut subscriptions = {
+            // It acquires the subscription lock after `eval`, allowing `add_subscription` to run concurrently.
+            // This also makes it possible for `broadcast_event` to get scheduled before the subsequent part here,
+            // in which case the snapshot is stale and we evaluate again.
+            let subscriptions = {
                 // How contended is the lock?
                 let _wait_guard = subscription_metrics.lock_waiters.inc_scope();
                 let _wait_timer = subscription_metrics.lock_wait_time.start_timer();
                 self.subscriptions.write()
             };
+            if Self::is_current(&subscriptions, &tx, view) {
+                break (queries, tx, tx_offset, database_update, metrics, subscriptions);
+            }
+            view = ReadView::Locked;
+        };
 
+        {
+            let _compile_timer = subscription_metrics.compilation_time.start_timer();
             subscriptions.set_legacy_subscription(sender.clone(), queries.into_iter());
         }
 
@@ -829,12 +1129,11 @@ impl ModuleSubscriptions {
         }
 
         // Note: to make sure transaction updates are consistent, we need to put this in the broadcast
-        // queue while we are still holding a read-lock on the database.
+        // queue while we are still holding a write lock on `self.subscriptions`.
 
-        // That will avoid race conditions because reducers first grab a write lock on the db, then
-        // grab a read lock on the subscriptions.
-
-        // Holding a write lock on `self.subscriptions` would also be sufficient.
+        // That will avoid race conditions because reducers grab a read lock on the subscriptions
+        // before committing, so every commit is either visible to `tx` and broadcast before this,
+        // or broadcast after this with the subscriptions as updated here.
         let _ = self.broadcast_queue.send_client_message(
             sender,
             Some(tx_offset),
@@ -848,6 +1147,16 @@ impl ModuleSubscriptions {
         Ok(metrics)
     }
 
+    /// Returns the number of query sets the client is subscribed to with `SubscribeSingle` or `SubscribeMulti`.
+    ///
+    /// Failed subscriptions, and unsubscriptions from query sets the client doesn't hold,
+    /// don't change this number.
+    pub fn num_query_sets(&self, client_id: ClientActorId) -> usize {
+        self.subscriptions
+            .read()
+            .num_query_sets(&(client_id.identity, client_id.connection_id))
+    }
+
     pub fn remove_subscriber(&self, client_id: ClientActorId) {
         let mut subscriptions = self.subscriptions.write();
         subscriptions.remove_all_subscriptions(&(client_id.identity, client_id.connection_id));
@@ -898,6 +1207,11 @@ impl ModuleSubscriptions {
                     let message = TransactionUpdateMessage {
                         event: Some(event.clone()),
                         database_update: SubscriptionUpdateMessage::default_for_protocol(client.config.protocol, None),
+                        return_value: client
+                            .config
+                            .reducer_results
+                            .then(|| event.return_value.clone())
+                            .flatten(),
                     };
 
                     let _ = self
@@ -915,6 +1229,9 @@ impl ModuleSubscriptions {
         };
         let event = Arc::new(event);
 
+        // Subscription requests evaluated against a snapshot without this commit are now stale.
+        subscriptions.record_broadcast(read_tx.next_tx_offset());
+
         // When we're done with this method, release the tx and report metrics.
         let (extra_tx_offset_sender, extra_tx_offset) = oneshot::channel();
         let (mut read_tx, tx_offset) = self.guard_tx(
@@ -923,8 +1240,17 @@ impl ModuleSubscriptions {
         );
         // Create the delta transaction we'll use to eval updates against.
         let delta_read_tx = DeltaTx::new(&read_tx, tx_data.as_ref(), subscriptions.index_ids_for_subscriptions());
-        let update_metrics = subscriptions.eval_updates_sequential((&delta_read_tx, tx_offset), event.clone(), caller);
+        let slow_query_threshold = stdb.incr_limit(&read_tx).ok().flatten().map(Duration::from_millis);
+        let (update_metrics, slow_events) = subscriptions.eval_updates_sequential(
+            (&delta_read_tx, tx_offset),
+            event.clone(),
+            caller,
+            slow_query_threshold,
+        );
         read_tx.metrics.merge(update_metrics);
+        // We're holding a tx, so the events can only be buffered.
+        // They are written by the next flush, e.g. once the calling reducer is done.
+        self.buffer_slow_events(slow_events);
         Ok(Ok(CommitAndBroadcastEventSuccess {
             tx_offset: extra_tx_offset,
             event,
@@ -934,8 +1260,55 @@ impl ModuleSubscriptions {
 
     /// Helper that starts a new read transaction, and guards it using
     /// [`Self::guard_tx`] with the default configuration.
-    fn begin_tx(&self, workload: Workload) -> (ScopeGuard<TxId, impl FnOnce(TxId) + '_>, TransactionOffset) {
-        self.guard_tx(self.relational_db.begin_tx(workload), <_>::default())
+    ///
+    /// Adding and removing subscriptions relies on no commit being broadcast
+    /// between the transaction's view of the committed state and updating `self.subscriptions`.
+    /// With [`ReadView::Snapshot`], the transaction doesn't hold up commits,
+    /// so callers must check [`Self::is_current`] once they hold the subscription lock,
+    /// and start over with [`ReadView::Locked`] if the snapshot is stale.
+    fn begin_tx(
+        &self,
+        workload: Workload,
+        view: ReadView,
+    ) -> (ScopeGuard<TxId, impl FnOnce(TxId) + '_>, TransactionOffset) {
+        let tx = match view {
+            ReadView::Snapshot => self.relational_db.begin_tx(workload),
+            ReadView::Locked => self.relational_db.begin_tx_locked(workload),
+        };
+        self.guard_tx(tx, <_>::default())
+    }
+
+    /// Starts a read transaction and then takes a write lock on `self.subscriptions` with `lock`,
+    /// such that the transaction's view of the committed state is current for the locked subscriptions.
+    ///
+    /// Used by requests which update `self.subscriptions` before evaluating their queries.
+    fn begin_tx_and_lock_subscriptions<'a>(
+        &'a self,
+        workload: Workload,
+        lock: impl Fn() -> RwLockWriteGuard<'a, SubscriptionManager>,
+    ) -> (
+        ScopeGuard<TxId, impl FnOnce(TxId) + 'a>,
+        TransactionOffset,
+        RwLockWriteGuard<'a, SubscriptionManager>,
+    ) {
+        let mut view = ReadView::Snapshot;
+        loop {
+            let (tx, tx_offset) = self.begin_tx(workload.clone(), view);
+            let subscriptions = lock();
+            if Self::is_current(&subscriptions, &tx, view) {
+                return (tx, tx_offset, subscriptions);
+            }
+            view = ReadView::Locked;
+        }
+    }
+
+    /// Returns whether a request evaluated against `tx` may update `subscriptions`,
+    /// i.e., whether every commit broadcast to `subscriptions` so far is visible to `tx`.
+    ///
+    /// The caller must hold a write lock on `subscriptions`,
+    /// so that no broadcast is in progress.
+    fn is_current(subscriptions: &SubscriptionManager, tx: &TxId, view: ReadView) -> bool {
+        view == ReadView::Locked || !subscriptions.has_broadcast_since(tx.next_tx_offset())
     }
 
     /// Helper wrapping `tx` in a scopegard, with a configurable drop fn.
@@ -1009,8 +1382,8 @@ pub struct WriteConflict;
 mod tests {
     use super::{AssertTxFn, ModuleSubscriptions};
     use crate::client::messages::{
-        SerializableMessage, SubscriptionData, SubscriptionError, SubscriptionMessage, SubscriptionResult,
-        SubscriptionUpdateMessage, TransactionUpdateMessage,
+        ResumedSubscriptionsData, SerializableMessage, SubscriptionData, SubscriptionError, SubscriptionMessage,
+        SubscriptionResult, SubscriptionUpdateMessage, TransactionUpdateMessage,
     };
     use crate::client::{
         ClientActorId, ClientConfig, ClientConnectionReceiver, ClientConnectionSender, ClientName, Protocol,
@@ -1032,11 +1405,13 @@ mod tests {
     use pretty_assertions::assert_matches;
     use spacetimedb_client_api_messages::energy::EnergyQuanta;
     use spacetimedb_client_api_messages::websocket::{
-        CompressableQueryUpdate, Compression, FormatSwitch, QueryId, Subscribe, SubscribeMulti, SubscribeSingle,
-        TableUpdate, Unsubscribe, UnsubscribeMulti,
+        CompressableQueryUpdate, Compression, FormatSwitch, QueryId, ResumeSubscriptions, Subscribe, SubscribeMulti,
+        SubscribeSingle, TableUpdate, Unsubscribe, UnsubscribeMulti,
     };
     use spacetimedb_commitlog::{commitlog, repo};
-    use spacetimedb_datastore::system_tables::{StRowLevelSecurityRow, ST_ROW_LEVEL_SECURITY_ID};
+    use spacetimedb_datastore::system_tables::{
+        StRowLevelSecurityRow, StSlowEventRow, StVarName, ST_ROW_LEVEL_SECURITY_ID, ST_SLOW_EVENT_ID,
+    };
     use spacetimedb_durability::{Durability, EmptyHistory, TxOffset};
     use spacetimedb_execution::dml::MutDatastore;
     use spacetimedb_lib::bsatn::ToBsatn;
@@ -1214,6 +1589,7 @@ mod tests {
             host_execution_duration: Duration::from_millis(0),
             request_id: None,
             timer: None,
+            return_value: None,
         }
     }
 
@@ -1260,6 +1636,7 @@ mod tests {
                 compression,
                 tx_update_full: true,
                 confirmed_reads: false,
+                reducer_results: false,
             },
         )
     }
@@ -1286,6 +1663,7 @@ mod tests {
                 compression: Compression::None,
                 tx_update_full: true,
                 confirmed_reads,
+                reducer_results: false,
             },
         )
     }
@@ -1513,6 +1891,54 @@ mod tests {
         Ok(())
     }
 
+    /// Test that slow subscriptions and slow incremental updates are recorded in `st_slow_event`.
+    #[tokio::test]
+    async fn test_record_slow_subscriptions() -> anyhow::Result<()> {
+        let db = relational_db()?;
+
+        let client_id = client_id_from_u8(1);
+        let (sender, _rx) = client_connection(client_id, &db);
+
+        let subs = ModuleSubscriptions::for_test_enclosing_runtime(db.clone());
+
+        let table_id = db.create_table_for_test("t", &[("id", AlgebraicType::U64)], &[0.into()])?;
+        commit_tx(&db, &subs, [], [(table_id, product![1_u64])])?;
+
+        // Record every subscription and every incremental update
+        with_auto_commit(&db, |tx| -> anyhow::Result<_> {
+            db.write_var(tx, StVarName::SlowSubThreshold, "0")?;
+            db.write_var(tx, StVarName::SlowIncThreshold, "0")?;
+            Ok(())
+        })?;
+
+        let sql = "select * from t";
+        let mut query_ids = 0;
+        subscribe_single(&subs, sql, sender, &mut query_ids)?;
+        commit_tx(&db, &subs, [], [(table_id, product![2_u64])])?;
+
+        db.flush_slow_events()?;
+        let events = with_read_only(&db, |tx| -> anyhow::Result<Vec<StSlowEventRow>> {
+            Ok(db
+                .iter(tx, ST_SLOW_EVENT_ID)?
+                .map(StSlowEventRow::try_from)
+                .collect::<Result<_, _>>()?)
+        })?;
+
+        let [sub, inc] = &events[..] else {
+            panic!("expected a subscribe and an update event, got {events:?}");
+        };
+        assert_eq!(&*sub.workload, "Subscribe");
+        assert_eq!(&*sub.query, sql);
+        assert_eq!(sub.caller_identity.0, identity_from_u8(1));
+        assert_eq!(sub.rows_returned, 1);
+        assert_eq!(&*inc.workload, "Update");
+        assert_eq!(&*inc.query, sql);
+        assert_eq!(inc.rows_returned, 1);
+        // Both evaluate the same compiled query
+        assert_eq!(sub.plan_hash, inc.plan_hash);
+        Ok(())
+    }
+
     fn check_subscription_err(sql: &str, result: Option<SerializableMessage>) {
         if let Some(SerializableMessage::Subscription(SubscriptionMessage {
             result: SubscriptionResult::Error(SubscriptionError { message, .. }),
@@ -1570,6 +1996,50 @@ mod tests {
         Ok(())
     }
 
+    /// Test that only subscriptions which were actually added or removed
+    /// change the number of query sets a client holds,
+    /// which its subscription limit is checked against.
+    ///
+    /// Needs a multi-threaded tokio runtime so that the module subscription worker can run in parallel.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn num_query_sets_only_counts_active_subscriptions() -> anyhow::Result<()> {
+        let db = relational_db()?;
+
+        let client_id = client_id_from_u8(1);
+        let (tx, mut rx) = client_connection(client_id, &db);
+
+        let subs = ModuleSubscriptions::for_test_enclosing_runtime(db.clone());
+
+        db.create_table_for_test("t", &[("x", AlgebraicType::U8)], &[])?;
+
+        let mut query_id = 0;
+
+        // Failed subscriptions don't hold a query set
+        let sql = "select r.* from t";
+        subscribe_single(&subs, sql, tx.clone(), &mut query_id)?;
+        check_subscription_err(sql, rx.recv().await);
+        subscribe_multi(&subs, &[sql], tx.clone(), &mut query_id)?;
+        check_subscription_err(sql, rx.recv().await);
+        assert_eq!(subs.num_query_sets(client_id), 0);
+
+        subscribe_single(&subs, "select * from t", tx.clone(), &mut query_id)?;
+        let single_id = query_id;
+        subscribe_multi(&subs, &["select * from t where x = 1"], tx.clone(), &mut query_id)?;
+        let multi_id = query_id;
+        assert_eq!(subs.num_query_sets(client_id), 2);
+
+        // Unsubscribing from query sets the client doesn't hold frees nothing
+        unsubscribe_single(&subs, tx.clone(), 100)?;
+        unsubscribe_multi(&subs, tx.clone(), 101)?;
+        assert_eq!(subs.num_query_sets(client_id), 2);
+
+        unsubscribe_single(&subs, tx.clone(), single_id)?;
+        unsubscribe_multi(&subs, tx, multi_id)?;
+        assert_eq!(subs.num_query_sets(client_id), 0);
+
+        Ok(())
+    }
+
     /// Test that clients receive error messages on unsubscribe
     #[tokio::test]
     async fn unsubscribe_single_error() -> anyhow::Result<()> {
@@ -2097,6 +2567,87 @@ mod tests {
         Ok(())
     }
 
+    /// Pull a message from receiver and assert that it resumes query set 1 with the expected rows
+    async fn assert_resumed(
+        rx: impl Future<Output = Option<SerializableMessage>>,
+        is_delta: bool,
+        schema: &ProductType,
+        inserts: impl IntoIterator<Item = ProductValue>,
+        deletes: impl IntoIterator<Item = ProductValue>,
+    ) {
+        let Some(SerializableMessage::Subscription(SubscriptionMessage {
+            result: SubscriptionResult::Resume(resumed),
+            ..
+        })) = rx.await
+        else {
+            panic!("expected a resumed subscription");
+        };
+        let ResumedSubscriptionsData {
+            is_delta: resumed_is_delta,
+            mut subscriptions,
+        } = resumed;
+        assert_eq!(resumed_is_delta, is_delta);
+        assert_eq!(subscriptions.len(), 1);
+        let (query_id, SubscriptionData { data }) = subscriptions.pop().unwrap();
+        assert_eq!(query_id, QueryId::new(1));
+        let FormatSwitch::Bsatn(ws::DatabaseUpdate { tables }) = data else {
+            panic!("expected a binary update");
+        };
+
+        let (mut rows_inserted, mut rows_deleted) = (Vec::new(), Vec::new());
+        for update in tables.into_iter().flat_map(|table| table.updates) {
+            let CompressableQueryUpdate::Uncompressed(update) = update else {
+                panic!("expected an uncompressed table update")
+            };
+            let decode = |bytes: bytes::Bytes| ProductValue::decode(schema, &mut &*bytes).unwrap();
+            rows_inserted.extend(update.inserts.into_iter().map(decode));
+            rows_deleted.extend(update.deletes.into_iter().map(decode));
+        }
+        rows_inserted.sort();
+        rows_deleted.sort();
+        assert_eq!(rows_inserted, inserts.into_iter().sorted().collect::<Vec<_>>());
+        assert_eq!(rows_deleted, deletes.into_iter().sorted().collect::<Vec<_>>());
+    }
+
+    /// Test that resuming from a recent position sends just the changes since then,
+    /// and that resuming from an unknown position sends every row.
+    #[tokio::test]
+    async fn resume_subscriptions_sends_changes_since_position() -> anyhow::Result<()> {
+        let db = relational_db()?;
+        let subs = ModuleSubscriptions::for_test_enclosing_runtime(db.clone());
+        let t_id = db.create_table_for_test("t", &[("x", AlgebraicType::U8)], &[])?;
+        let schema = ProductType::from([AlgebraicType::U8]);
+
+        commit_tx(&db, &subs, [], [(t_id, product![1_u8]), (t_id, product![2_u8])])?;
+        let since = ws::CaughtUp {
+            history_id: db.tx_history_id(),
+            tx_offset: with_read_only(&db, |tx| tx.next_tx_offset() - 1),
+        };
+        commit_tx(&db, &subs, [(t_id, product![1_u8])], [(t_id, product![3_u8])])?;
+
+        let resume = |since| ResumeSubscriptions {
+            request_id: 0,
+            since: Some(since),
+            subscriptions: [multi_subscribe(&["select * from t"], 1)].into(),
+        };
+
+        // The history covers every transaction since `since`.
+        let (tx, mut rx) = client_connection(client_id_from_u8(1), &db);
+        subs.resume_subscriptions(tx, resume(since), Instant::now())?;
+        assert_resumed(rx.recv(), true, &schema, [product![3_u8]], [product![1_u8]]).await;
+
+        // `since` is a position in some other history.
+        let (tx, mut rx) = client_connection(client_id_from_u8(2), &db);
+        let elsewhere = ws::CaughtUp {
+            history_id: since.history_id.wrapping_add(1),
+            ..since
+        };
+        subs.resume_subscriptions(tx, resume(elsewhere), Instant::now())?;
+        assert_resumed(rx.recv(), false, &schema, [product![2_u8], product![3_u8]], []).await;
+
+        Ok(())
+    }
+
     /// Test that we do not compress within a [TransactionUpdateMessage].
     /// The message itself is compressed before being sent over the wire,
     /// but we don't care about that for this test.
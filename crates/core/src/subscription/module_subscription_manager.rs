@@ -10,6 +10,7 @@ use crate::host::module_host::{DatabaseTableUpdate, ModuleEvent, UpdatesRelValue
 use crate::messages::websocket::{self as ws, TableUpdate};
 use crate::subscription::delta::eval_delta;
 use crate::subscription::websocket_building::BuildableWebsocketFormat;
+use crate::util::slow::{subscription_plan_hash, SlowQueryLogger};
 use crate::worker_metrics::WORKER_METRICS;
 use core::mem;
 use hashbrown::hash_map::OccupiedError;
@@ -20,7 +21,9 @@ use spacetimedb_client_api_messages::websocket::{
     BsatnFormat, CompressableQueryUpdate, FormatSwitch, JsonFormat, QueryId, QueryUpdate, SingleQueryUpdate,
 };
 use spacetimedb_data_structures::map::{Entry, IntMap};
+use spacetimedb_datastore::execution_context::WorkloadType;
 use spacetimedb_datastore::locking_tx_datastore::state_view::StateView;
+use spacetimedb_datastore::system_tables::StSlowEventRow;
 use spacetimedb_durability::TxOffset;
 use spacetimedb_lib::metrics::ExecutionMetrics;
 use spacetimedb_lib::{AlgebraicValue, ConnectionId, Identity, ProductValue};
@@ -28,8 +31,9 @@ use spacetimedb_primitives::{ColId, IndexId, TableId};
 use spacetimedb_subscription::{JoinEdge, SubscriptionPlan, TableName};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
 
 /// Clients are uniquely identified by their Identity and ConnectionId.
@@ -495,6 +499,14 @@ pub struct SubscriptionManager {
     /// as it imposes a delay between unlocking the datastore
     /// and waking the many per-client sender Tokio tasks.
     send_worker_queue: BroadcastQueue,
+
+    /// The offset following that of the latest transaction broadcast to subscribers,
+    /// see [`Self::record_broadcast`].
+    ///
+    /// Updated under a shared lock on the manager,
+    /// so an exclusive lock observes every broadcast which has started.
+    /// See [`Self::has_broadcast_since`].
+    next_broadcast_tx_offset: AtomicU64,
 }
 
 /// A single update for one client and one query.
@@ -640,9 +652,27 @@ impl SubscriptionManager {
             search_args: Default::default(),
             join_edges: Default::default(),
             send_worker_queue,
+            next_broadcast_tx_offset: Default::default(),
         }
     }
 
+    /// Records that the transaction preceding `next_tx_offset` is being broadcast.
+    pub fn record_broadcast(&self, next_tx_offset: TxOffset) {
+        self.next_broadcast_tx_offset
+            .fetch_max(next_tx_offset, Ordering::AcqRel);
+    }
+
+    /// Returns whether a transaction with an offset of at least `next_tx_offset` has been broadcast.
+    ///
+    /// A read transaction whose [`TxId::next_tx_offset`](spacetimedb_datastore::locking_tx_datastore::TxId::next_tx_offset) is `next_tx_offset`
+    /// is consistent with the current subscriptions if this returns `false`
+    /// while holding an exclusive lock on the manager:
+    /// every transaction it observes has been broadcast to them,
+    /// and every later transaction will be.
+    pub fn has_broadcast_since(&self, next_tx_offset: TxOffset) -> bool {
+        self.next_broadcast_tx_offset.load(Ordering::Acquire) > next_tx_offset
+    }
+
     pub fn query(&self, hash: &QueryHash) -> Option<Query> {
         self.queries.get(hash).map(|state| state.query.clone())
     }
@@ -835,6 +865,11 @@ impl SubscriptionManager {
         Ok(queries_to_return)
     }
 
+    /// Returns the number of query sets `client` is subscribed to with `SubscribeSingle` or `SubscribeMulti`.
+    pub fn num_query_sets(&self, client: &ClientId) -> usize {
+        self.clients.get(client).map_or(0, |ci| ci.subscriptions.len())
+    }
+
     /// Adds a single subscription for a client.
     pub fn add_subscription(&mut self, client: Client, query: Query, query_id: ClientQueryId) -> Result<(), DBError> {
         self.add_subscription_multi(client, vec![query], query_id).map(|_| ())
@@ -1126,16 +1161,21 @@ impl SubscriptionManager {
     /// However, in order to optimize for the common case of small updates,
     /// we removed rayon and switched to a single-threaded execution,
     /// which removed significant overhead associated with thread switching.
+    ///
+    /// Returns, alongside the metrics, an event for `st_slow_event`
+    /// for each query fragment whose evaluation took longer than `slow_query_threshold`.
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn eval_updates_sequential(
         &self,
         (tx, tx_offset): (&DeltaTx, TransactionOffset),
         event: Arc<ModuleEvent>,
         caller: Option<Arc<ClientConnectionSender>>,
-    ) -> ExecutionMetrics {
+        slow_query_threshold: Option<Duration>,
+    ) -> (ExecutionMetrics, Vec<StSlowEventRow>) {
         use FormatSwitch::{Bsatn, Json};
 
         let tables = &event.status.database_update().unwrap().tables;
+        let caller_identity = event.caller_identity;
 
         let span = tracing::info_span!("eval_incr").entered();
 
@@ -1144,6 +1184,7 @@ impl SubscriptionManager {
             updates: Vec<ClientUpdate>,
             errs: Vec<(ClientId, Box<str>)>,
             metrics: ExecutionMetrics,
+            slow_events: Vec<StSlowEventRow>,
         }
 
         /// Returns the value pointed to by this join edge
@@ -1167,7 +1208,12 @@ impl SubscriptionManager {
             })
         }
 
-        let FoldState { updates, errs, metrics } = tables
+        let FoldState {
+            updates,
+            errs,
+            metrics,
+            slow_events,
+        } = tables
             .iter()
             .filter(|table| !table.inserts.is_empty() || !table.deletes.is_empty())
             .flat_map(|table_update| {
@@ -1242,7 +1288,21 @@ impl SubscriptionManager {
 
                 let clients_for_query = qstate.all_clients();
 
-                match eval_delta(tx, &mut acc.metrics, plan) {
+                let slow_query = SlowQueryLogger::new(&qstate.query.sql, slow_query_threshold, WorkloadType::Update);
+                let rows_scanned = acc.metrics.rows_scanned;
+                let result = eval_delta(tx, &mut acc.metrics, plan);
+                let rows_returned = match &result {
+                    Ok(Some(updates)) => updates.inserts.len() + updates.deletes.len(),
+                    _ => 0,
+                };
+                acc.slow_events.extend(slow_query.event(
+                    caller_identity,
+                    || Some(subscription_plan_hash([plan])),
+                    acc.metrics.rows_scanned - rows_scanned,
+                    rows_returned,
+                ));
+
+                match result {
                     Err(err) => {
                         tracing::error!(
                             message = "Query errored during tx update",
@@ -1308,7 +1368,7 @@ impl SubscriptionManager {
 
         drop(span);
 
-        metrics
+        (metrics, slow_events)
     }
 }
 
@@ -1578,6 +1638,11 @@ impl SendWorker {
             let message = TransactionUpdateMessage {
                 event: Some(event.clone()),
                 database_update,
+                return_value: caller
+                    .config
+                    .reducer_results
+                    .then(|| event.return_value.clone())
+                    .flatten(),
             };
             send_to_client(&caller, Some(tx_offset), message);
         }
@@ -1588,7 +1653,11 @@ impl SendWorker {
             let client = self.clients[&id].outbound_ref.clone();
             // Conditionally send out a full update or a light one otherwise.
             let event = client.config.tx_update_full.then(|| event.clone());
-            let message = TransactionUpdateMessage { event, database_update };
+            let message = TransactionUpdateMessage {
+                event,
+                database_update,
+                return_value: None,
+            };
             send_to_client(&client, Some(tx_offset), message);
         }
 
@@ -2501,6 +2570,7 @@ mod tests {
             host_execution_duration: Duration::default(),
             request_id: None,
             timer: None,
+            return_value: None,
         });
 
         // This block ensures that the transaction is released before waiting
@@ -2515,7 +2585,7 @@ mod tests {
                 db.report_read_tx_metrics(reducer, tx_metrics);
             });
             let delta_tx = DeltaTx::from(&*tx);
-            subscriptions.eval_updates_sequential((&delta_tx, offset_rx), event, Some(Arc::new(client0)));
+            subscriptions.eval_updates_sequential((&delta_tx, offset_rx), event, Some(Arc::new(client0)), None);
         }
 
         runtime.block_on(async move {
@@ -9,7 +9,8 @@ use itertools::Either;
 use smallvec::SmallVec;
 use spacetimedb_execution::{Datastore, DeltaStore, Row};
 use spacetimedb_lib::{query::Delta, AlgebraicValue, ProductValue};
-use spacetimedb_primitives::{IndexId, TableId};
+use spacetimedb_physical_plan::cost::Statistics;
+use spacetimedb_primitives::{ColList, IndexId, TableId};
 use spacetimedb_table::{blob_store::BlobStore, table::Table};
 
 use spacetimedb_datastore::{
@@ -55,20 +56,22 @@ impl DeltaTableIndexes {
             for (table_id, rows) in rows {
                 if let Some(schema) = tx.get_schema(*table_id) {
                     // Fetch the column ids for each index
+                    // Indexes whose keys are not a projection of the row have no columns,
+                    // and are never built for delta tables.
                     let mut cols_for_index = vec![];
                     for index_id in meta.index_ids_for_table(*table_id) {
-                        cols_for_index.push((index_id, schema.col_list_for_index_id(index_id)));
+                        if let Some(col_list) = schema.col_list_for_index_id(index_id) {
+                            cols_for_index.push((index_id, col_list));
+                        }
                     }
                     for (i, row) in rows.iter().enumerate() {
                         for (index_id, col_list) in &cols_for_index {
-                            if !col_list.is_empty() {
-                                indexes
-                                    .entry((*table_id, *index_id))
-                                    .or_default()
-                                    .entry(row.project(col_list).unwrap())
-                                    .or_default()
-                                    .push(i);
-                            }
+                            indexes
+                                .entry((*table_id, *index_id))
+                                .or_default()
+                                .entry(row.project(col_list).unwrap())
+                                .or_default()
+                                .push(i);
                         }
                     }
                 }
@@ -128,6 +131,16 @@ impl Datastore for DeltaTx<'_> {
     }
 }
 
+impl Statistics for DeltaTx<'_> {
+    fn row_count(&self, table_id: TableId) -> Option<u64> {
+        self.tx.table_row_count(table_id)
+    }
+
+    fn num_distinct_values(&self, table_id: TableId, cols: &ColList) -> Option<u64> {
+        self.tx.num_distinct_values(table_id, cols).map(|n| n.get())
+    }
+}
+
 impl DeltaStore for DeltaTx<'_> {
     fn num_inserts(&self, table_id: TableId) -> usize {
         self.data
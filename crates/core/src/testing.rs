@@ -0,0 +1,301 @@
+//! An in-process harness for unit-testing modules.
+//!
+//! [`ModuleTestHarness`] loads a compiled module into a [`RelationalDB`] with in-memory durability,
+//! and lets tests call reducers as arbitrary identities at arbitrary timestamps,
+//! drive scheduled reducers with a virtual clock,
+//! and inspect the resulting table contents and module logs.
+//!
+//! ```ignore
+//! let mut harness = ModuleTestHarness::new(program_bytes, HostType::Wasm, Timestamp::UNIX_EPOCH).await?;
+//! harness.call_reducer(alice, "add", FunctionArgs::Json(r#"["Alice"]"#.into())).await?;
+//! harness.advance_time(Duration::from_secs(60)).await?;
+//! assert_eq!(harness.table_rows("person")?.len(), 1);
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use rustc_hash::FxHashMap;
+use spacetimedb_datastore::execution_context::Workload;
+use spacetimedb_datastore::system_tables::{StScheduledFields, ST_SCHEDULED_ID};
+use spacetimedb_datastore::traits::Program;
+use spacetimedb_lib::scheduler::ScheduleAt;
+use spacetimedb_lib::{Identity, Timestamp};
+use spacetimedb_paths::server::ReplicaDir;
+use spacetimedb_paths::FromPathUnchecked;
+use spacetimedb_sats::ProductValue;
+use tempfile::TempDir;
+
+use crate::database_logger::DatabaseLogger;
+use crate::db::relational_db::RelationalDB;
+use crate::host::scheduler::{
+    finish_scheduled_row, get_schedule_from_row, scheduled_reducer_params, ScheduledReducerId,
+};
+use crate::host::{launch_in_memory_for_testing, FunctionArgs, ModuleHost, ReducerCallError, ReducerCallResult};
+use crate::messages::control_db::{Database, HostType};
+use crate::replica_context::ReplicaContext;
+
+/// A module running against a throwaway in-memory database, driven by a virtual clock.
+///
+/// Scheduled reducers never fire on their own.
+/// Instead, [`Self::advance_time`] and [`Self::advance_to`] move the virtual clock forward
+/// and run every schedule which falls due along the way, in order,
+/// telling each reducer that it is running at the time it was due.
+pub struct ModuleTestHarness {
+    module_host: ModuleHost,
+    replica_ctx: Arc<ReplicaContext>,
+    /// The virtual time which reducers are told they are running at.
+    now: Timestamp,
+    /// Every row currently in a scheduled table, and when it next falls due.
+    schedules: FxHashMap<ScheduledReducerId, PendingSchedule>,
+    // Declared last so that it's dropped after the database which holds a lock inside it.
+    replica_dir: TempDir,
+}
+
+#[derive(Clone, Copy)]
+struct PendingSchedule {
+    schedule_at: ScheduleAt,
+    due: Timestamp,
+}
+
+impl ModuleTestHarness {
+    /// Load `program_bytes` into a fresh in-memory database with the virtual clock set to `start`,
+    /// and run the module's `init` reducer.
+    ///
+    /// The database and its owner are given fixed identities,
+    /// available via [`Self::database_identity`] and [`Self::owner_identity`].
+    pub async fn new(program_bytes: Box<[u8]>, host_type: HostType, start: Timestamp) -> anyhow::Result<Self> {
+        let owner_identity = Identity::from_u256(0xdcba_u32.into());
+        let database_identity = Identity::from_u256(0xabcd_u32.into());
+        let program = Program::from_bytes(program_bytes);
+
+        let database = Database {
+            id: 0,
+            database_identity,
+            owner_identity,
+            host_type,
+            initial_program: program.hash,
+        };
+
+        let replica_dir = TempDir::with_prefix("spacetimedb-module-test")
+            .context("Error creating temporary directory to house the test database")?;
+        let (replica_ctx, module_host, init_result) = launch_in_memory_for_testing(
+            database,
+            program,
+            ReplicaDir::from_path_unchecked(replica_dir.path().to_owned()),
+        )
+        .await?;
+        if let Some(init_result) = init_result {
+            Result::from(init_result).context("init reducer failed")?;
+        }
+
+        let mut harness = Self {
+            module_host,
+            replica_ctx,
+            now: start,
+            schedules: FxHashMap::default(),
+            replica_dir,
+        };
+        harness.sync_schedules()?;
+        Ok(harness)
+    }
+
+    /// The identity of the database the module is running in.
+    pub fn database_identity(&self) -> Identity {
+        self.replica_ctx.database.database_identity
+    }
+
+    /// The identity of the database's owner.
+    pub fn owner_identity(&self) -> Identity {
+        self.replica_ctx.database.owner_identity
+    }
+
+    /// The module under test.
+    pub fn module_host(&self) -> &ModuleHost {
+        &self.module_host
+    }
+
+    /// The current virtual time.
+    pub fn now(&self) -> Timestamp {
+        self.now
+    }
+
+    /// Call `reducer` as `sender` at the current virtual time.
+    ///
+    /// A reducer which runs but fails is reported through [`ReducerCallResult::outcome`],
+    /// not as an `Err`.
+    pub async fn call_reducer(
+        &mut self,
+        sender: Identity,
+        reducer: &str,
+        args: FunctionArgs,
+    ) -> anyhow::Result<ReducerCallResult> {
+        self.call_reducer_at(sender, self.now, reducer, args).await
+    }
+
+    /// Call `reducer` as `sender`, telling it that it is running at `timestamp`.
+    ///
+    /// This does not move the virtual clock.
+    pub async fn call_reducer_at(
+        &mut self,
+        sender: Identity,
+        timestamp: Timestamp,
+        reducer: &str,
+        args: FunctionArgs,
+    ) -> anyhow::Result<ReducerCallResult> {
+        let result = self
+            .module_host
+            .call_reducer_at(sender, None, None, None, None, timestamp, reducer, args)
+            .await?;
+        self.sync_schedules()?;
+        Ok(result)
+    }
+
+    /// Move the virtual clock forward by `by`, running every scheduled reducer which falls due.
+    ///
+    /// Returns the results of the scheduled reducers, in the order they ran.
+    pub async fn advance_time(&mut self, by: Duration) -> anyhow::Result<Vec<ReducerCallResult>> {
+        self.advance_to(self.now + by).await
+    }
+
+    /// Move the virtual clock forward to `until`, running every scheduled reducer which falls due.
+    ///
+    /// Schedules run in order of the time they fall due,
+    /// so a reducer which schedules another reducer within the window will see it run.
+    /// Schedules due in the past run immediately.
+    /// The clock never moves backwards.
+    ///
+    /// Returns the results of the scheduled reducers, in the order they ran.
+    pub async fn advance_to(&mut self, until: Timestamp) -> anyhow::Result<Vec<ReducerCallResult>> {
+        let mut results = Vec::new();
+        while let Some((id, due)) = self.next_due(until) {
+            self.now = self.now.max(due);
+            if let Some(result) = self.run_schedule(id, due).await? {
+                results.push(result);
+            }
+        }
+        self.now = self.now.max(until);
+        Ok(results)
+    }
+
+    /// Read every row of the table named `table_name`.
+    pub fn table_rows(&self, table_name: &str) -> anyhow::Result<Vec<ProductValue>> {
+        let db = &self.replica_ctx.relational_db;
+        db.with_read_only(Workload::Internal, |tx| {
+            let tx = &*tx;
+            let table_id = db
+                .table_id_from_name(tx, table_name)?
+                .ok_or_else(|| anyhow!("no such table: {table_name}"))?;
+            Ok(db.iter(tx, table_id)?.map(|row| row.to_product_value()).collect())
+        })
+    }
+
+    /// Read the module's logs, one JSON-encoded record per line,
+    /// limited to the last `num_lines` lines if given.
+    pub async fn logs(&self, num_lines: Option<u32>) -> String {
+        let replica_dir = ReplicaDir::from_path_unchecked(self.replica_dir.path().to_owned());
+        DatabaseLogger::read_latest(replica_dir.module_logs(), num_lines).await
+    }
+
+    /// The earliest schedule due at or before `until`, if any.
+    ///
+    /// Ties are broken by table and schedule ID, so that runs are deterministic.
+    fn next_due(&self, until: Timestamp) -> Option<(ScheduledReducerId, Timestamp)> {
+        self.schedules
+            .iter()
+            .filter(|(_, pending)| pending.due <= until)
+            .min_by_key(|(id, pending)| (pending.due, id.table_id, id.schedule_id))
+            .map(|(id, pending)| (*id, pending.due))
+    }
+
+    /// Run the schedule `id` as if it fired at `due`,
+    /// then delete its row or, for an interval, queue its next run.
+    ///
+    /// Returns `None` if the row disappeared before it could run.
+    async fn run_schedule(
+        &mut self,
+        id: ScheduledReducerId,
+        due: Timestamp,
+    ) -> anyhow::Result<Option<ReducerCallResult>> {
+        let db = self.replica_ctx.relational_db.clone();
+        let module_info = self.module_host.info.clone();
+        let res = self
+            .module_host
+            .call_scheduled_reducer(move |tx| scheduled_reducer_params(tx, &db, &module_info, id, due))
+            .await;
+        let result = match res {
+            Err(ReducerCallError::ScheduleReducerNotFound) => {
+                self.schedules.remove(&id);
+                return Ok(None);
+            }
+            res => res?,
+        };
+
+        let db = self.replica_ctx.relational_db.clone();
+        let module_host = self.module_host.clone();
+        let schedule_at = self
+            .module_host
+            .on_module_thread("finish_scheduled_row", move || {
+                finish_scheduled_row(&db, id, module_host)
+            })
+            .await?;
+        match schedule_at {
+            Some(ScheduleAt::Interval(interval)) => {
+                anyhow::ensure!(
+                    interval.to_micros() > 0,
+                    "schedule {} in table {} repeats with a non-positive interval and would never stop firing",
+                    id.schedule_id,
+                    id.table_id
+                );
+                if let Some(pending) = self.schedules.get_mut(&id) {
+                    pending.due = due + interval;
+                }
+            }
+            _ => {
+                self.schedules.remove(&id);
+            }
+        }
+
+        self.sync_schedules()?;
+        Ok(Some(result))
+    }
+
+    /// Rescan every scheduled table, picking up rows which were inserted, updated or deleted since the last scan.
+    ///
+    /// Rows whose `ScheduleAt` is unchanged keep their due time,
+    /// so that intervals stay anchored to when they were first scheduled.
+    fn sync_schedules(&mut self) -> anyhow::Result<()> {
+        let db: &RelationalDB = &self.replica_ctx.relational_db;
+        let now = self.now;
+        let old = &self.schedules;
+        let schedules = db.with_read_only(Workload::Internal, |tx| -> anyhow::Result<_> {
+            let tx = &*tx;
+            let mut schedules = FxHashMap::default();
+            for st_scheduled_row in db.iter(tx, ST_SCHEDULED_ID)? {
+                let table_id = st_scheduled_row.read_col(StScheduledFields::TableId)?;
+                let (id_column, at_column) = db
+                    .table_scheduled_id_and_at(tx, table_id)?
+                    .ok_or_else(|| anyhow!("scheduled table {table_id} doesn't have valid columns"))?;
+
+                for scheduled_row in db.iter(tx, table_id)? {
+                    let (schedule_id, schedule_at) = get_schedule_from_row(&scheduled_row, id_column, at_column)?;
+                    let id = ScheduledReducerId {
+                        table_id,
+                        schedule_id,
+                        id_column,
+                        at_column,
+                    };
+                    let due = match old.get(&id) {
+                        Some(pending) if pending.schedule_at == schedule_at => pending.due,
+                        _ => schedule_at.to_timestamp_from(now),
+                    };
+                    schedules.insert(id, PendingSchedule { schedule_at, due });
+                }
+            }
+            Ok(schedules)
+        })?;
+        self.schedules = schedules;
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
 use std::time::{Duration, Instant};
 
 use spacetimedb_datastore::execution_context::WorkloadType;
+use spacetimedb_datastore::system_tables::StSlowEventRow;
+use spacetimedb_lib::{Identity, Timestamp};
+use spacetimedb_physical_plan::explain::explain;
+use spacetimedb_physical_plan::plan::ProjectListPlan;
+use spacetimedb_sats::hash::{hash_bytes, Hash};
+use spacetimedb_subscription::SubscriptionPlan;
+
+use crate::db::relational_db::RelationalDB;
 
 /// Records the execution time of some `sql`
 /// and logs when the duration goes above a specific one.
@@ -42,6 +50,96 @@ impl<'a> SlowQueryLogger<'a> {
         }
         None
     }
+
+    /// Log the query if it exceeds the threshold,
+    /// and return the event to record in `st_slow_event`.
+    ///
+    /// `plan_hash` returns the [`plan_hash`] of the plan the query was executed with.
+    /// It is only called for slow queries.
+    /// If it returns `None`, e.g. because the query failed to compile,
+    /// the hash of the query text is used instead.
+    pub fn event(
+        &self,
+        caller: Identity,
+        plan_hash: impl FnOnce() -> Option<Hash>,
+        rows_scanned: usize,
+        rows_returned: usize,
+    ) -> Option<StSlowEventRow> {
+        self.log().map(|elapsed| {
+            let plan_hash = plan_hash().unwrap_or_else(|| hash_bytes(self.sql));
+            slow_event(
+                self.workload,
+                self.sql,
+                plan_hash,
+                caller,
+                elapsed,
+                rows_scanned,
+                rows_returned,
+            )
+        })
+    }
+
+    /// Returns whether this logger has a threshold,
+    /// i.e. whether [`Self::event`] may return an event.
+    pub fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+}
+
+/// Returns a hash identifying an optimized `plan`.
+///
+/// This is the hash of its `EXPLAIN` output,
+/// so queries which are written differently but executed the same way have the same hash.
+pub fn plan_hash(plan: &ProjectListPlan) -> Hash {
+    hash_bytes(explain(plan, |_| None).join("\n"))
+}
+
+/// Returns the [`plan_hash`] of a subscription query compiled to `fragments`.
+pub fn subscription_plan_hash<'a>(fragments: impl IntoIterator<Item = &'a SubscriptionPlan>) -> Hash {
+    let plans = fragments
+        .into_iter()
+        .map(|fragment| fragment.optimized_physical_plan().clone())
+        .collect();
+    plan_hash(&ProjectListPlan::Name(plans))
+}
+
+/// Returns a row for `st_slow_event` describing a `query` which took `elapsed` to run.
+pub fn slow_event(
+    workload: WorkloadType,
+    query: &str,
+    plan_hash: Hash,
+    caller: Identity,
+    elapsed: Duration,
+    rows_scanned: usize,
+    rows_returned: usize,
+) -> StSlowEventRow {
+    StSlowEventRow {
+        event_id: 0,
+        workload: workload.to_string().into(),
+        query: query.into(),
+        plan_hash,
+        caller_identity: caller.into(),
+        duration_micros: elapsed.as_micros() as u64,
+        rows_scanned: rows_scanned as u64,
+        rows_returned: rows_returned as u64,
+        timestamp: Timestamp::now(),
+    }
+}
+
+/// Record `event` in `st_slow_event`, if any,
+/// and write any buffered events that are due, see [`RelationalDB::record_slow_event`].
+///
+/// Failing to record an event is logged but otherwise ignored,
+/// as it should never fail the query or reducer that triggered it.
+/// Must not be called while holding a transaction on `db`.
+pub fn record_slow_event(db: &RelationalDB, event: Option<StSlowEventRow>) {
+    let result = match event {
+        Some(event) => db.record_slow_event(event),
+        None => db.flush_slow_events_if_due(),
+    };
+    if let Err(err) = result {
+        tracing::warn!("failed to record slow event: {err}");
+    }
 }
 
 #[cfg(test)]
@@ -49,9 +147,10 @@ mod tests {
     use super::*;
 
     use crate::sql::compiler::compile_sql;
-    use crate::sql::execute::tests::execute_for_testing;
+    use crate::sql::execute::tests::{execute_for_testing, run_for_testing};
     use spacetimedb_datastore::system_tables::ST_VARNAME_SLOW_QRY;
     use spacetimedb_datastore::system_tables::{StVarName, ST_VARNAME_SLOW_INC, ST_VARNAME_SLOW_SUB};
+    use spacetimedb_datastore::system_tables::{ST_SLOW_EVENT_CAPACITY, ST_SLOW_EVENT_ID};
     use spacetimedb_lib::error::ResultTest;
     use spacetimedb_lib::identity::AuthCtx;
     use spacetimedb_lib::st_var::StVarValue;
@@ -149,4 +248,122 @@ mod tests {
         assert_eq!(row3, None);
         Ok(())
     }
+
+    fn slow_events(db: &RelationalDB) -> ResultTest<Vec<StSlowEventRow>> {
+        db.flush_slow_events()?;
+        stored_slow_events(db)
+    }
+
+    /// Returns the events in `st_slow_event`, without flushing the buffered ones.
+    fn stored_slow_events(db: &RelationalDB) -> ResultTest<Vec<StSlowEventRow>> {
+        let tx = begin_tx(db);
+        let rows = db
+            .iter(&tx, ST_SLOW_EVENT_ID)?
+            .map(StSlowEventRow::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    // Verify slow queries are recorded in `st_slow_event`
+    #[test]
+    fn test_record_slow_queries() -> ResultTest<()> {
+        let db = TestDB::in_memory()?.db;
+
+        let table_id =
+            db.create_table_for_test("test", &[("x", AlgebraicType::I32), ("y", AlgebraicType::I32)], &[])?;
+
+        with_auto_commit(&db, |tx| -> ResultTest<_> {
+            for i in 0..1_000 {
+                insert(&db, tx, table_id, &product![i, i * 2])?;
+            }
+            Ok(())
+        })?;
+
+        // Nothing is recorded without a threshold
+        let sql = "select * from test where x > 0";
+        run_for_testing(&db, sql)?;
+        assert!(slow_events(&db)?.is_empty());
+
+        // The plan hash identifies the optimized plan, as rendered by `EXPLAIN`
+        let explain = run_for_testing(&db, &format!("EXPLAIN {sql}"))?
+            .into_iter()
+            .map(|row| row.elements[0].as_string().unwrap().to_string())
+            .collect::<Vec<_>>();
+        let expected_plan_hash = hash_bytes(explain.join("\n"));
+
+        run_query_write(&db, format!("SET {ST_VARNAME_SLOW_QRY} TO 0"))?;
+        let rows = run_for_testing(&db, sql)?;
+
+        let events = slow_events(&db)?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(&*events[0].workload, "Sql");
+        assert_eq!(&*events[0].query, sql);
+        assert_eq!(events[0].plan_hash, expected_plan_hash);
+        assert_eq!(events[0].rows_returned, rows.len() as u64);
+        assert!(events[0].rows_scanned >= rows.len() as u64);
+        Ok(())
+    }
+
+    // Verify `st_slow_event` only retains the most recent events
+    #[test]
+    fn test_slow_event_capacity() -> ResultTest<()> {
+        let db = TestDB::in_memory()?.db;
+
+        let total = ST_SLOW_EVENT_CAPACITY + 10;
+        for i in 0..total {
+            let query = format!("select * from t{i}");
+            let event = slow_event(
+                WorkloadType::Sql,
+                &query,
+                hash_bytes(&query),
+                Identity::ZERO,
+                Duration::from_millis(5),
+                0,
+                0,
+            );
+            db.record_slow_event(event)?;
+        }
+
+        let events = slow_events(&db)?;
+        assert_eq!(events.len(), ST_SLOW_EVENT_CAPACITY);
+        assert!(!events.iter().any(|event| &*event.query == "select * from t9"));
+        assert!(events.iter().any(|event| &*event.query == "select * from t10"));
+        Ok(())
+    }
+
+    // Verify slow events are buffered and written in batches
+    #[test]
+    fn test_slow_event_batching() -> ResultTest<()> {
+        let db = TestDB::in_memory()?.db;
+
+        let event = |i: usize| {
+            let query = format!("select * from t{i}");
+            slow_event(
+                WorkloadType::Sql,
+                &query,
+                hash_bytes(&query),
+                Identity::ZERO,
+                Duration::from_millis(5),
+                0,
+                0,
+            )
+        };
+
+        // Start from an empty buffer that was just flushed
+        db.flush_slow_events()?;
+
+        // A single event is buffered, not written
+        db.record_slow_event(event(0))?;
+        assert!(stored_slow_events(&db)?.is_empty());
+
+        // Events buffered while holding a tx are written by the next flush
+        let tx = begin_tx(&db);
+        db.buffer_slow_event(event(1));
+        drop(tx);
+        assert!(stored_slow_events(&db)?.is_empty());
+
+        db.flush_slow_events()?;
+        assert_eq!(stored_slow_events(&db)?.len(), 2);
+        Ok(())
+    }
 }
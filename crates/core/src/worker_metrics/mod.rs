@@ -42,6 +42,11 @@ metrics_group!(
         #[labels(database_identity: Identity, protocol: str)]
         pub websocket_request_msg_size: HistogramVec,
 
+        #[name = spacetime_worker_rate_limited_requests_total]
+        #[help = "The cumulative number of client requests rejected, or messages to clients delayed, by a rate limit"]
+        #[labels(database_identity: Identity, kind: str)]
+        pub rate_limited_requests: IntCounterVec,
+
         #[name = jemalloc_active_bytes]
         #[help = "Number of bytes in jemallocs heap"]
         #[labels(node_id: str)]
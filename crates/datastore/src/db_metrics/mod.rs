@@ -101,6 +101,21 @@ metrics_group!(
         #[labels(db: Identity, table_id: u32, table_name: str)]
         pub rdb_table_size: IntGaugeVec,
 
+        #[name = spacetime_num_resident_pages]
+        #[help = "The number of table pages held in memory"]
+        #[labels(db: Identity)]
+        pub rdb_num_resident_pages: IntGaugeVec,
+
+        #[name = spacetime_page_cache_hits]
+        #[help = "The number of accesses to evictable table pages that were held in memory"]
+        #[labels(db: Identity)]
+        pub rdb_page_cache_hits: IntCounterVec,
+
+        #[name = spacetime_page_cache_misses]
+        #[help = "The number of accesses to evicted table pages that had to be reloaded from a snapshot"]
+        #[labels(db: Identity)]
+        pub rdb_page_cache_misses: IntCounterVec,
+
         #[name = reducer_wasmtime_fuel_used]
         #[help = "The total wasmtime fuel used"]
         #[labels(db: Identity, reducer: str)]
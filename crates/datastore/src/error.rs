@@ -8,7 +8,9 @@ use spacetimedb_sats::{AlgebraicType, AlgebraicValue, ProductValue};
 use spacetimedb_schema::def::error::LibError;
 use spacetimedb_snapshot::SnapshotError;
 use spacetimedb_table::{
-    bflatn_to, read_column,
+    bflatn_to,
+    pages::PageReloadError,
+    read_column,
     table::{self, ReadViaBsatnError, UniqueConstraintViolation},
 };
 use thiserror::Error;
@@ -26,6 +28,8 @@ pub enum DatastoreError {
     #[error(transparent)]
     // Box the inner [`SnapshotError`] to keep Clippy quiet about large `Err` variants.
     Snapshot(#[from] Box<SnapshotError>),
+    #[error(transparent)]
+    PageReload(#[from] PageReloadError),
     // TODO(cloutiertyler): should this be a TableError? I couldn't get it to compile
     #[error("Error reading a value from a table through BSATN: {0}")]
     ReadViaBsatnError(#[from] ReadViaBsatnError),
@@ -31,6 +31,10 @@ pub struct ReducerContext {
     /// The [`ConnectionId`] of the caller.
     pub caller_connection_id: ConnectionId,
     /// The timestamp of the reducer invocation.
+    ///
+    /// This must be the same timestamp the reducer itself observes,
+    /// as it also seeds the reducer's random number generator,
+    /// and is what a replay of the transaction will run the reducer at.
     pub timestamp: Timestamp,
     /// The BSATN-encoded arguments given to the reducer.
     ///
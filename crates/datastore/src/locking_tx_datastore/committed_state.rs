@@ -7,8 +7,8 @@ use super::{
     IterByColEqTx,
 };
 use crate::system_tables::{
-    ST_CONNECTION_CREDENTIALS_ID, ST_CONNECTION_CREDENTIALS_IDX, ST_VIEW_COLUMN_ID, ST_VIEW_COLUMN_IDX, ST_VIEW_ID,
-    ST_VIEW_IDX, ST_VIEW_PARAM_ID, ST_VIEW_PARAM_IDX,
+    ST_CONNECTION_CREDENTIALS_ID, ST_CONNECTION_CREDENTIALS_IDX, ST_SLOW_EVENT_ID, ST_SLOW_EVENT_IDX,
+    ST_VIEW_COLUMN_ID, ST_VIEW_COLUMN_IDX, ST_VIEW_ID, ST_VIEW_IDX, ST_VIEW_PARAM_ID, ST_VIEW_PARAM_IDX,
 };
 use crate::{
     db_metrics::DB_METRICS,
@@ -51,10 +51,20 @@ use thin_vec::ThinVec;
 /// is exposed in order to support tools wanting to process the commit
 /// logs directly. For normal usage, see the RelationalDB struct instead.
 ///
+/// The tables are reference counted and the blob store is a persistent map,
+/// so that [`CommittedState::snapshot`] can hand out a consistent, read-only view
+/// to read-only transactions without copying any data.
+/// A table which is shared with a snapshot
+/// is copied the first time the live committed state modifies it.
+/// The copy shares its pages, indexes and pointer map with the snapshot,
+/// so only the pages and index nodes actually modified are copied,
+/// as are the blob store's map nodes, but never the blobs themselves.
+/// Old versions are freed once the last snapshot referring to them is dropped.
+///
 /// NOTE: unstable API, this may change at any point in the future.
 pub struct CommittedState {
     pub(crate) next_tx_offset: u64,
-    pub(crate) tables: IntMap<TableId, Table>,
+    pub(crate) tables: IntMap<TableId, Arc<Table>>,
     pub(crate) blob_store: HashMapBlobStore,
     /// Provides fast lookup for index id -> an index.
     pub(super) index_id_map: IndexIdMap,
@@ -72,8 +82,27 @@ pub struct CommittedState {
     /// We should split `CommittedState` into two types
     /// where one, e.g., `ReplayCommittedState`, has this field.
     table_dropped: IntSet<TableId>,
+    /// The tables which had rows deleted from them
+    /// and may have fragmented pages left to compact.
+    ///
+    /// See [`CommittedState::compact_tables`].
+    compaction_candidates: IntSet<TableId>,
+    /// The maximum number of table pages to keep in memory,
+    /// or `None` to keep all of them in memory.
+    ///
+    /// See [`CommittedState::evict_cold_pages`].
+    pub(super) max_resident_pages: Option<usize>,
 }
 
+/// A committed table is compacted while at least this fraction of the space in its pages is unused.
+///
+/// See [`Table::compact`].
+const COMPACTION_FRAGMENTATION_THRESHOLD: f64 = 0.5;
+
+/// The maximum number of rows which compaction may move after each commit,
+/// bounding the latency it adds to a transaction.
+const COMPACTION_ROWS_PER_TX: usize = 256;
+
 impl MemoryUsage for CommittedState {
     fn heap_usage(&self) -> usize {
         let Self {
@@ -83,6 +112,8 @@ impl MemoryUsage for CommittedState {
             index_id_map,
             page_pool: _,
             table_dropped,
+            compaction_candidates,
+            max_resident_pages: _,
         } = self;
         // NOTE(centril): We do not want to include the heap usage of `page_pool` as it's a shared resource.
         next_tx_offset.heap_usage()
@@ -90,6 +121,7 @@ impl MemoryUsage for CommittedState {
             + blob_store.heap_usage()
             + index_id_map.heap_usage()
             + table_dropped.heap_usage()
+            + compaction_candidates.heap_usage()
     }
 }
 
@@ -111,7 +143,7 @@ impl StateView for CommittedState {
 
     fn iter(&self, table_id: TableId) -> Result<Self::Iter<'_>> {
         if self.table_name(table_id).is_some() {
-            return Ok(IterTx::new(table_id, self));
+            return IterTx::new(table_id, self);
         }
         Err(TableError::IdNotFound(SystemTable::st_table, table_id.0).into())
     }
@@ -124,7 +156,7 @@ impl StateView for CommittedState {
         cols: ColList,
         range: R,
     ) -> Result<Self::IterByColRange<'_, R>> {
-        match self.index_seek(table_id, &cols, &range) {
+        match self.index_seek(table_id, &cols, &range)? {
             Some(iter) => Ok(IterByColRangeTx::Index(iter)),
             None => Ok(IterByColRangeTx::Scan(ScanIterByColRangeTx::new(
                 self.iter(table_id)?,
@@ -144,6 +176,18 @@ impl StateView for CommittedState {
     }
 }
 
+/// Returns mutable access to `table`,
+/// first replacing it with a private copy if it is shared with a snapshot.
+///
+/// The copy shares its pages, indexes and pointer map with the snapshot,
+/// so only the pages and index nodes the caller then modifies are copied.
+pub(super) fn make_table_mut<'a>(table: &'a mut Arc<Table>, pool: &PagePool) -> &'a mut Table {
+    if Arc::get_mut(table).is_none() {
+        *table = Arc::new(table.clone_with_pool(pool));
+    }
+    Arc::get_mut(table).expect("a freshly copied table should not be shared")
+}
+
 impl CommittedState {
     pub(super) fn new(page_pool: PagePool) -> Self {
         Self {
@@ -152,10 +196,31 @@ impl CommittedState {
             blob_store: <_>::default(),
             index_id_map: <_>::default(),
             table_dropped: <_>::default(),
+            compaction_candidates: <_>::default(),
+            max_resident_pages: None,
             page_pool,
         }
     }
 
+    /// Returns a consistent, read-only view of `self` as of now.
+    ///
+    /// This is cheap, as no tables are copied.
+    /// Instead, the snapshot shares all tables and the blob store with `self`,
+    /// and `self` copies the parts of them it modifies afterwards.
+    /// This way, the snapshot can be read from without holding any lock on `self`.
+    pub(super) fn snapshot(&self) -> Self {
+        Self {
+            next_tx_offset: self.next_tx_offset,
+            tables: self.tables.clone(),
+            blob_store: self.blob_store.clone(),
+            index_id_map: self.index_id_map.clone(),
+            page_pool: self.page_pool.clone(),
+            table_dropped: <_>::default(),
+            compaction_candidates: <_>::default(),
+            max_resident_pages: self.max_resident_pages,
+        }
+    }
+
     /// Extremely delicate function to bootstrap the system tables.
     /// Don't update this unless you know what you're doing.
     pub(super) fn bootstrap_system_tables(&mut self, database_identity: Identity) -> Result<()> {
@@ -260,6 +325,8 @@ impl CommittedState {
         self.create_table(ST_VIEW_PARAM_ID, schemas[ST_VIEW_PARAM_IDX].clone());
         self.create_table(ST_VIEW_COLUMN_ID, schemas[ST_VIEW_COLUMN_IDX].clone());
 
+        self.create_table(ST_SLOW_EVENT_ID, schemas[ST_SLOW_EVENT_IDX].clone());
+
         // Insert the sequences into `st_sequences`
         let (st_sequences, blob_store, pool) =
             self.get_table_and_blob_store_or_create(ST_SEQUENCE_ID, &schemas[ST_SEQUENCE_IDX]);
@@ -425,6 +492,7 @@ impl CommittedState {
 
         // Update the columns and layout of the the in-memory table.
         if let Some(table) = self.tables.get_mut(&target_table_id) {
+            let table = make_table_mut(table, &self.page_pool);
             table.change_columns_to(columns).map_err(TableError::from)?;
         }
 
@@ -483,16 +551,20 @@ impl CommittedState {
         for index_row in rows {
             let index_id = index_row.index_id;
             let table_id = index_row.table_id;
-            let (table, blob_store, index_id_map, _) = self
+            let (table, blob_store, index_id_map, page_pool) = self
                 .get_table_and_blob_store_mut(table_id)
                 .expect("index should exist in committed state; cannot create it");
             let algo: IndexAlgorithm = index_row.index_algorithm.into();
-            let columns: ColSet = algo.columns().into();
-            let is_unique = unique_constraints.contains(&(table_id, columns));
+            // Only indexes over a projection of the row can back a unique constraint.
+            let is_unique = algo
+                .columns()
+                .is_some_and(|columns| unique_constraints.contains(&(table_id, columns.into())));
 
             let index = table.new_index(&algo, is_unique)?;
+            // Pages evicted since restoring from a snapshot are evicted again once indexed,
+            // so building the index doesn't require the whole table in memory.
             // SAFETY: `index` was derived from `table`.
-            unsafe { table.insert_index(blob_store, index_id, index) };
+            unsafe { table.try_insert_index(page_pool, blob_store, index_id, index) }?;
             index_id_map.insert(index_id, table_id);
         }
         Ok(())
@@ -509,7 +581,7 @@ impl CommittedState {
             schemas.push(self.schema_for_table_raw(table_id)?);
         }
         for (table, schema) in self.tables.values_mut().zip(schemas) {
-            table.with_mut_schema(|s| *s = schema);
+            make_table_mut(table, &self.page_pool).with_mut_schema(|s| *s = schema);
         }
         Ok(())
     }
@@ -543,16 +615,23 @@ impl CommittedState {
     ///
     /// For a unique index this will always yield at most one `RowRef`.
     /// When there is no index this returns `None`.
+    ///
+    /// Fails if a page holding a matching row was evicted and cannot be reloaded.
     pub(super) fn index_seek<'a>(
         &'a self,
         table_id: TableId,
         cols: &ColList,
         range: &impl RangeBounds<AlgebraicValue>,
-    ) -> Option<IndexScanRangeIter<'a>> {
-        self.tables
-            .get(&table_id)?
-            .get_index_by_cols_with_table(&self.blob_store, cols)
-            .map(|i| i.seek_range(range))
+    ) -> Result<Option<IndexScanRangeIter<'a>>> {
+        let Some(index) = self
+            .tables
+            .get(&table_id)
+            .and_then(|table| table.get_index_by_cols_with_table(&self.blob_store, cols))
+        else {
+            return Ok(None);
+        };
+        index.load_range(range)?;
+        Ok(Some(index.seek_range(range)))
     }
 
     /// Returns the table associated with the given `index_id`, if any.
@@ -645,9 +724,94 @@ impl CommittedState {
             self.next_tx_offset += 1;
         }
 
+        // The transaction is now fully applied and no longer refers to any rows,
+        // so this is a good time to reclaim some of the space freed by deletions.
+        self.compact_tables(COMPACTION_ROWS_PER_TX);
+        // Pages reloaded or allocated by the transaction may have exceeded the memory limit.
+        self.evict_cold_pages();
+
         tx_data
     }
 
+    /// Returns the number of pages of all tables held in memory.
+    pub(super) fn num_resident_pages(&self) -> usize {
+        self.tables.values().map(|table| table.num_resident_pages()).sum()
+    }
+
+    /// Evicts cold pages which are unmodified since the last snapshot
+    /// until at most [`CommittedState::max_resident_pages`] pages remain in memory,
+    /// or no more pages can be evicted.
+    /// Evicted pages are reloaded from the latest snapshot when accessed.
+    ///
+    /// Pages are chosen per [`Table::evict_cold_pages`],
+    /// so pages accessed since they were last considered for eviction get a second chance.
+    /// Tables shared with a read-only transaction are skipped,
+    /// as evicting from them would require copying them first.
+    ///
+    /// As tables must be mutated, this must only be called between transactions.
+    pub(super) fn evict_cold_pages(&mut self) {
+        let Some(max_resident_pages) = self.max_resident_pages else {
+            return;
+        };
+        let mut excess = self.num_resident_pages().saturating_sub(max_resident_pages);
+
+        // The first sweep may only clear the referenced bits of the pages it passes,
+        // so sweep twice to evict pages which were not accessed in between.
+        for _ in 0..2 {
+            for table in self.tables.values_mut() {
+                if excess == 0 {
+                    return;
+                }
+                if let Some(table) = Arc::get_mut(table) {
+                    excess -= table.evict_cold_pages(&self.page_pool, excess);
+                }
+            }
+        }
+    }
+
+    /// Compacts the fragmented tables which had rows deleted from them,
+    /// moving at most `max_rows_moved` rows in total.
+    ///
+    /// Tables are compacted incrementally across calls,
+    /// and dropped from the candidates once they're no longer fragmented
+    /// or no further progress can be made.
+    /// Tables shared with another version of the committed state are skipped,
+    /// like in [`CommittedState::evict_cold_pages`].
+    ///
+    /// As compaction moves rows, invalidating their [`RowPointer`]s,
+    /// this must only be called between transactions.
+    fn compact_tables(&mut self, max_rows_moved: usize) {
+        let Self {
+            tables,
+            blob_store,
+            page_pool,
+            compaction_candidates,
+            ..
+        } = self;
+        let mut budget = max_rows_moved;
+        compaction_candidates.retain(|table_id| {
+            let Some(table) = tables.get_mut(table_id) else {
+                // The table was dropped.
+                return false;
+            };
+            if table.fragmentation() < COMPACTION_FRAGMENTATION_THRESHOLD {
+                return false;
+            }
+            if budget == 0 {
+                return true;
+            }
+            // Compacting a table shared with a snapshot or a read-only transaction
+            // would copy the table and every page it touches,
+            // so leave it for a later commit, once it's no longer shared.
+            let Some(table) = Arc::get_mut(table).filter(|table| table.num_shared_pages() == 0) else {
+                return true;
+            };
+            let compaction = table.compact(page_pool, blob_store, budget, COMPACTION_FRAGMENTATION_THRESHOLD);
+            budget -= compaction.rows_moved;
+            !compaction.done
+        });
+    }
+
     fn merge_apply_deletes(
         &mut self,
         tx_data: &mut TxData,
@@ -691,6 +855,9 @@ impl CommittedState {
         }
 
         for (table_id, row_ptrs) in delete_tables {
+            if !row_ptrs.is_empty() {
+                self.compaction_candidates.insert(table_id);
+            }
             match self.get_table_and_blob_store_mut(table_id) {
                 Ok((table, blob_store, ..)) => delete_rows(
                     tx_data,
@@ -716,7 +883,7 @@ impl CommittedState {
                 delete_rows(
                     tx_data,
                     table_id,
-                    &mut table,
+                    make_table_mut(&mut table, &self.page_pool),
                     &mut self.blob_store,
                     row_ptrs.len(),
                     row_ptrs.into_iter(),
@@ -798,7 +965,7 @@ impl CommittedState {
         match change {
             // An index was removed. Add it back.
             IndexRemoved(table_id, index_id, table_index, index_schema) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 // SAFETY: `table_index` was derived from `table`.
                 unsafe { table.add_index(index_id, table_index) };
                 table.with_mut_schema(|s| s.update_index(index_schema));
@@ -806,7 +973,7 @@ impl CommittedState {
             }
             // An index was added. Remove it.
             IndexAdded(table_id, index_id, pointer_map) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.delete_index(&self.blob_store, index_id, pointer_map);
                 table.with_mut_schema(|s| s.remove_index(index_id));
                 self.index_id_map.remove(&index_id);
@@ -827,14 +994,14 @@ impl CommittedState {
             }
             // A table's access was changed. Change back to the old one.
             TableAlterAccess(table_id, access) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.with_mut_schema(|s| s.table_access = access);
             }
             // A table's row type was changed. Change back to the old one.
             // The row representation of old rows hasn't changed,
             // so it's safe to not rewrite the rows and merely change the type back.
             TableAlterRowType(table_id, column_schemas) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 // SAFETY:
                 // Let the "old" type/schema be the one in `column_schemas`.
                 // Let the "new" type/schema be the one used by the table which we are rolling back.
@@ -850,23 +1017,23 @@ impl CommittedState {
             }
             // A constraint was removed. Add it back.
             ConstraintRemoved(table_id, constraint_schema) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.with_mut_schema(|s| s.update_constraint(constraint_schema));
             }
             // A constraint was added. Remove it.
             ConstraintAdded(table_id, constraint_id) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.with_mut_schema(|s| s.remove_constraint(constraint_id));
             }
             // A sequence was removed. Add it back.
             SequenceRemoved(table_id, seq, schema) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.with_mut_schema(|s| s.update_sequence(schema));
                 seq_state.insert(seq);
             }
             // A sequence was added. Remove it.
             SequenceAdded(table_id, sequence_id) => {
-                let table = self.tables.get_mut(&table_id)?;
+                let table = make_table_mut(self.tables.get_mut(&table_id)?, &self.page_pool);
                 table.with_mut_schema(|s| s.remove_sequence(sequence_id));
                 seq_state.remove(sequence_id);
             }
@@ -876,7 +1043,12 @@ impl CommittedState {
     }
 
     pub(super) fn get_table(&self, table_id: TableId) -> Option<&Table> {
-        self.tables.get(&table_id)
+        self.tables.get(&table_id).map(|table| &**table)
+    }
+
+    /// The offset the next committed transaction will be assigned.
+    pub fn next_tx_offset(&self) -> u64 {
+        self.next_tx_offset
     }
 
     #[allow(clippy::unnecessary_lazy_evaluations)]
@@ -900,15 +1072,15 @@ impl CommittedState {
             .get_mut(&table_id)
             .ok_or_else(|| TableError::IdNotFoundState(table_id))?;
         Ok((
-            table,
+            make_table_mut(table, &self.page_pool),
             &mut self.blob_store as &mut dyn BlobStore,
             &mut self.index_id_map,
             &self.page_pool,
         ))
     }
 
-    fn make_table(schema: Arc<TableSchema>) -> Table {
-        Table::new(schema, SquashedOffset::COMMITTED_STATE)
+    fn make_table(schema: Arc<TableSchema>) -> Arc<Table> {
+        Arc::new(Table::new(schema, SquashedOffset::COMMITTED_STATE))
     }
 
     fn create_table(&mut self, table_id: TableId, schema: Arc<TableSchema>) {
@@ -924,11 +1096,42 @@ impl CommittedState {
             .tables
             .entry(table_id)
             .or_insert_with(|| Self::make_table(schema.clone()));
-        let blob_store = &mut self.blob_store;
         let pool = &self.page_pool;
+        let table = make_table_mut(table, pool);
+        let blob_store = &mut self.blob_store;
         (table, blob_store, pool)
     }
 
+    /// Reports the number of pages held in memory
+    /// and the hits and misses of page accesses since the last report.
+    ///
+    /// Hits and misses are only counted for tables whose pages can be evicted,
+    /// see [`CommittedState::evict_cold_pages`].
+    pub fn report_page_cache(&self, database_identity: Identity) {
+        let mut num_resident_pages = 0;
+        let mut hits = 0;
+        let mut misses = 0;
+        for table in self.tables.values() {
+            num_resident_pages += table.num_resident_pages();
+            let stats = table.take_page_cache_stats();
+            hits += stats.hits;
+            misses += stats.misses;
+        }
+
+        DB_METRICS
+            .rdb_num_resident_pages
+            .with_label_values(&database_identity)
+            .set(num_resident_pages as _);
+        DB_METRICS
+            .rdb_page_cache_hits
+            .with_label_values(&database_identity)
+            .inc_by(hits);
+        DB_METRICS
+            .rdb_page_cache_misses
+            .with_label_values(&database_identity)
+            .inc_by(misses);
+    }
+
     pub fn report_data_size(&self, database_identity: Identity) {
         use crate::db_metrics::data_size::DATA_SIZE_METRICS;
 
@@ -1,9 +1,9 @@
 use super::{
-    committed_state::CommittedState,
+    committed_state::{make_table_mut, CommittedState},
     mut_tx::MutTxId,
     sequence::SequencesState,
     state_view::{IterByColRangeTx, StateView},
-    tx::TxId,
+    tx::{CommittedStateRead, TxId},
     tx_state::TxState,
 };
 use crate::{
@@ -42,7 +42,13 @@ use spacetimedb_sats::{
 use spacetimedb_sats::{memory_usage::MemoryUsage, Deserialize};
 use spacetimedb_schema::schema::{ColumnSchema, IndexSchema, SequenceSchema, TableSchema};
 use spacetimedb_snapshot::{ReconstructedSnapshot, SnapshotRepository};
-use spacetimedb_table::{indexes::RowPointer, page_pool::PagePool, table::RowRef};
+use spacetimedb_table::{
+    indexes::RowPointer,
+    page::Page,
+    page_pool::PagePool,
+    pages::PageStore,
+    table::{RowRef, Table},
+};
 use std::borrow::Cow;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -143,6 +149,8 @@ impl Locking {
         // See John Carmack's philosophy on this.
         committed_state.reschema_tables()?;
         committed_state.build_missing_tables()?;
+        // Replay may have reloaded many evicted pages; shed them before indexing.
+        committed_state.evict_cold_pages();
         committed_state.build_indexes()?;
         // Figure out where to pick up for each sequence.
         *self.sequence_state.lock() = committed_state.build_sequence_state()?;
@@ -173,18 +181,31 @@ impl Locking {
     /// - Notably, **do not** construct indexes or sequences.
     ///   This should be done by [`Self::rebuild_state_after_replay`],
     ///   after replaying the suffix of the commitlog.
-    pub fn restore_from_snapshot(snapshot: ReconstructedSnapshot, page_pool: PagePool) -> Result<Self> {
+    ///
+    /// The pages of tables which `snapshot` left on disk,
+    /// see [`ReconstructedSnapshot::lazy_tables`],
+    /// are restored as evicted pages to be loaded on demand,
+    /// keeping only as many in memory as `table_memory_limit` allows;
+    /// see [`Self::set_table_memory_limit`].
+    pub fn restore_from_snapshot(
+        snapshot: ReconstructedSnapshot,
+        page_pool: PagePool,
+        table_memory_limit: Option<usize>,
+    ) -> Result<Self> {
         let ReconstructedSnapshot {
             database_identity,
             tx_offset,
             blob_store,
             tables,
+            lazy_tables,
+            page_store,
             ..
         } = snapshot;
 
         let datastore = Self::new(database_identity, page_pool);
         let mut committed_state = datastore.committed_state.write_arc();
         committed_state.blob_store = blob_store;
+        committed_state.max_resident_pages = table_memory_limit.map(|limit| limit / size_of::<Page>());
 
         // Note that `tables` is a `BTreeMap`, and so iterates in increasing order.
         // This means that we will instantiate and populate the system tables before any user tables.
@@ -207,21 +228,25 @@ impl Locking {
                 //   so the layout used in the `pages` must be consistent with the schema.
                 table.set_pages(pages, blob_store);
             }
+            record_restored_table_metrics(&database_identity, table_id, &schema, table);
+        }
 
-            // Set the `rdb_num_table_rows` metric for the table.
-            // NOTE: the `rdb_num_table_rows` metric is used by the query optimizer,
-            // and therefore has performance implications and must not be disabled.
-            DB_METRICS
-                .rdb_num_table_rows
-                .with_label_values(&database_identity, &table_id.0, &schema.table_name)
-                .set(table.row_count as i64);
-
-            // Also set the `rdb_table_size` metric for the table.
-            let table_size = table.bytes_occupied_overestimate();
-            DB_METRICS
-                .rdb_table_size
-                .with_label_values(&database_identity, &table_id.into(), &schema.table_name)
-                .set(table_size as i64);
+        // The system tables are always read eagerly, so all schemas are known by now.
+        if let Some(page_store) = page_store {
+            let page_store: Arc<dyn PageStore> = Arc::new(page_store);
+            let mut budget = committed_state.max_resident_pages.unwrap_or(usize::MAX);
+            budget = budget.saturating_sub(committed_state.num_resident_pages());
+            for (table_id, hashes) in lazy_tables {
+                let schema = committed_state.schema_for_table(table_id)?;
+                let (table, blob_store, page_pool) =
+                    committed_state.get_table_and_blob_store_or_create(table_id, &schema);
+                let num_pages = hashes.len();
+                // Safety: As for `set_pages` above.
+                // The pages are verified against their hashes once loaded.
+                unsafe { table.set_evicted_pages(hashes, page_store.clone(), page_pool, blob_store, budget) }?;
+                budget = budget.saturating_sub(num_pages);
+                record_restored_table_metrics(&database_identity, table_id, &schema, table);
+            }
         }
 
         // Double check that our in-memory system table ids match the on-disk schemas.
@@ -283,13 +308,86 @@ impl Locking {
         let CommittedState {
             ref mut tables,
             ref blob_store,
+            ref page_pool,
+            max_resident_pages,
             ..
         } = *committed_state;
-        let snapshot_dir = repo.create_snapshot(tables.values_mut(), blob_store, tx_offset)?;
+        // Writing the snapshot saves the content hash of each page,
+        // so tables still shared with a read-only transaction are copied first.
+        let snapshot_dir = repo.create_snapshot(
+            tables.values_mut().map(|table| make_table_mut(table, page_pool)),
+            blob_store,
+            tx_offset,
+        )?;
+
+        // Every page now has its contents in the new snapshot,
+        // so pages can be evicted and reloaded from there.
+        if max_resident_pages.is_some() {
+            let page_store = Arc::new(repo.page_store(tx_offset, page_pool.clone())?);
+            for table in tables.values_mut() {
+                make_table_mut(table, page_pool).set_page_store(page_store.clone());
+            }
+            committed_state.evict_cold_pages();
+        }
 
         Ok(Some((tx_offset, snapshot_dir)))
     }
 
+    /// Limits the memory used by the pages of this datastore's tables to roughly `limit` bytes,
+    /// or lifts the limit if `None`.
+    ///
+    /// Once the limit is exceeded, cold pages which are unmodified since the latest snapshot
+    /// are evicted from memory, and transparently reloaded from the snapshot when accessed.
+    /// Hence, pages can only be evicted once a snapshot has been taken,
+    /// and the limit may be exceeded by pages modified since then.
+    pub fn set_table_memory_limit(&self, limit: Option<usize>) {
+        let max_resident_pages = limit.map(|limit| limit / size_of::<Page>());
+        let mut committed_state = self.committed_state.write();
+        committed_state.max_resident_pages = max_resident_pages;
+        committed_state.evict_cold_pages();
+    }
+
+    /// Begins a read-only transaction under the given `workload`,
+    /// holding a shared lock on the committed state until the transaction is released.
+    ///
+    /// While this transaction is pending,
+    /// other read-only transactions may be started,
+    /// but new mutable transactions will block until there are no locked read-only transactions left.
+    ///
+    /// Unlike [`Tx::begin_tx`], this guarantees that no commit happens
+    /// between the point this transaction reads at and its release.
+    /// This is used e.g., when adding a subscription whose evaluation against a snapshot
+    /// was superseded by a concurrent commit,
+    /// as its initial rows must be followed by the updates for every later commit.
+    ///
+    /// Blocks if a mutable transaction is pending.
+    pub fn begin_tx_locked(&self, workload: Workload) -> TxId {
+        self.begin_tx_with(workload, |committed_state| {
+            CommittedStateRead::Locked(committed_state.read_arc())
+        })
+    }
+
+    fn begin_tx_with(
+        &self,
+        workload: Workload,
+        read: impl FnOnce(&Arc<RwLock<CommittedState>>) -> CommittedStateRead,
+    ) -> TxId {
+        let metrics = ExecutionMetrics::default();
+        let ctx = ExecutionContext::with_workload(self.database_identity, workload);
+
+        let timer = Instant::now();
+        let committed_state = read(&self.committed_state);
+        let lock_wait_time = timer.elapsed();
+
+        TxId {
+            committed_state,
+            lock_wait_time,
+            timer,
+            ctx,
+            metrics,
+        }
+    }
+
     /// Returns a list over all the currently connected clients,
     /// reading from the `st_clients` system table.
     pub fn connected_clients<'a>(
@@ -332,6 +430,24 @@ impl Locking {
     }
 }
 
+/// Sets the metrics of a `table` restored from a snapshot.
+fn record_restored_table_metrics(database_identity: &Identity, table_id: TableId, schema: &TableSchema, table: &Table) {
+    // Set the `rdb_num_table_rows` metric for the table.
+    // NOTE: the `rdb_num_table_rows` metric is used by the query optimizer,
+    // and therefore has performance implications and must not be disabled.
+    DB_METRICS
+        .rdb_num_table_rows
+        .with_label_values(database_identity, &table_id.0, &schema.table_name)
+        .set(table.row_count as i64);
+
+    // Also set the `rdb_table_size` metric for the table.
+    let table_size = table.bytes_occupied_overestimate();
+    DB_METRICS
+        .rdb_table_size
+        .with_label_values(database_identity, &table_id.into(), &schema.table_name)
+        .set(table_size as i64);
+}
+
 impl DataRow for Locking {
     type RowId = RowPointer;
     type RowRef<'a> = RowRef<'a>;
@@ -346,26 +462,18 @@ impl Tx for Locking {
 
     /// Begins a read-only transaction under the given `workload`.
     ///
+    /// The transaction reads from a snapshot of the committed state
+    /// taken when it begins, see [`CommittedState::snapshot`].
     /// While this transaction is pending,
-    /// other read-only transactions may be started,
-    /// but new mutable transactions will block until there are no read-only transactions left.
+    /// other read-only and mutable transactions may be started and committed,
+    /// without this transaction observing their effects.
     ///
     /// Blocks if a mutable transaction is pending.
+    /// Use [`Locking::begin_tx_locked`] for a transaction that also excludes commits.
     fn begin_tx(&self, workload: Workload) -> Self::Tx {
-        let metrics = ExecutionMetrics::default();
-        let ctx = ExecutionContext::with_workload(self.database_identity, workload);
-
-        let timer = Instant::now();
-        let committed_state_shared_lock = self.committed_state.read_arc();
-        let lock_wait_time = timer.elapsed();
-
-        Self::Tx {
-            committed_state_shared_lock,
-            lock_wait_time,
-            timer,
-            ctx,
-            metrics,
-        }
+        self.begin_tx_with(workload, |committed_state| {
+            CommittedStateRead::Snapshot(committed_state.read().snapshot())
+        })
     }
 
     /// Release this read-only transaction,
@@ -1247,12 +1355,12 @@ mod tests {
     use crate::system_tables::{
         system_tables, StColumnRow, StConnectionCredentialsFields, StConstraintData, StConstraintFields,
         StConstraintRow, StIndexAlgorithm, StIndexFields, StIndexRow, StRowLevelSecurityFields, StScheduledFields,
-        StSequenceFields, StSequenceRow, StTableRow, StVarFields, StViewFields, ST_CLIENT_NAME, ST_COLUMN_ID,
-        ST_COLUMN_NAME, ST_CONNECTION_CREDENTIALS_ID, ST_CONNECTION_CREDENTIALS_NAME, ST_CONSTRAINT_ID,
+        StSequenceFields, StSequenceRow, StSlowEventFields, StTableRow, StVarFields, StViewFields, ST_CLIENT_NAME,
+        ST_COLUMN_ID, ST_COLUMN_NAME, ST_CONNECTION_CREDENTIALS_ID, ST_CONNECTION_CREDENTIALS_NAME, ST_CONSTRAINT_ID,
         ST_CONSTRAINT_NAME, ST_INDEX_ID, ST_INDEX_NAME, ST_MODULE_NAME, ST_RESERVED_SEQUENCE_RANGE,
         ST_ROW_LEVEL_SECURITY_ID, ST_ROW_LEVEL_SECURITY_NAME, ST_SCHEDULED_ID, ST_SCHEDULED_NAME, ST_SEQUENCE_ID,
-        ST_SEQUENCE_NAME, ST_TABLE_NAME, ST_VAR_ID, ST_VAR_NAME, ST_VIEW_COLUMN_ID, ST_VIEW_COLUMN_NAME, ST_VIEW_ID,
-        ST_VIEW_NAME, ST_VIEW_PARAM_ID, ST_VIEW_PARAM_NAME,
+        ST_SEQUENCE_NAME, ST_SLOW_EVENT_ID, ST_SLOW_EVENT_NAME, ST_TABLE_NAME, ST_VAR_ID, ST_VAR_NAME,
+        ST_VIEW_COLUMN_ID, ST_VIEW_COLUMN_NAME, ST_VIEW_ID, ST_VIEW_NAME, ST_VIEW_PARAM_ID, ST_VIEW_PARAM_NAME,
     };
     use crate::traits::{IsolationLevel, MutTx};
     use crate::Result;
@@ -1266,6 +1374,7 @@ mod tests {
     use spacetimedb_lib::error::ResultTest;
     use spacetimedb_lib::st_var::StVarValue;
     use spacetimedb_lib::{resolved_type_via_v9, ScheduleAt, TimeDuration};
+    use spacetimedb_paths::{server::SnapshotsPath, FromPathUnchecked};
     use spacetimedb_primitives::{col_list, ColId, ScheduleId, ViewId};
     use spacetimedb_sats::algebraic_value::ser::value_serialize;
     use spacetimedb_sats::bsatn::ToBsatn;
@@ -1708,6 +1817,7 @@ mod tests {
             TableRow { id: ST_VIEW_ID.into(), name: ST_VIEW_NAME, ty: StTableType::System, access: StAccess::Public, primary_key: Some(StViewFields::ViewId.into()) },
             TableRow { id: ST_VIEW_PARAM_ID.into(), name: ST_VIEW_PARAM_NAME, ty: StTableType::System, access: StAccess::Public, primary_key: None },
             TableRow { id: ST_VIEW_COLUMN_ID.into(), name: ST_VIEW_COLUMN_NAME, ty: StTableType::System, access: StAccess::Public, primary_key: None },
+            TableRow { id: ST_SLOW_EVENT_ID.into(), name: ST_SLOW_EVENT_NAME, ty: StTableType::System, access: StAccess::Private, primary_key: Some(StSlowEventFields::EventId.into()) },
 
         ]));
         #[rustfmt::skip]
@@ -1783,6 +1893,16 @@ mod tests {
             ColRow { table: ST_VIEW_COLUMN_ID.into(), pos: 1, name: "col_pos", ty: ColId::get_type() },
             ColRow { table: ST_VIEW_COLUMN_ID.into(), pos: 2, name: "col_name", ty: AlgebraicType::String },
             ColRow { table: ST_VIEW_COLUMN_ID.into(), pos: 3, name: "col_type", ty: AlgebraicType::bytes() },
+
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 0, name: "event_id", ty: AlgebraicType::U64 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 1, name: "workload", ty: AlgebraicType::String },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 2, name: "query", ty: AlgebraicType::String },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 3, name: "plan_hash", ty: AlgebraicType::U256 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 4, name: "caller_identity", ty: AlgebraicType::U256 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 5, name: "duration_micros", ty: AlgebraicType::U64 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 6, name: "rows_scanned", ty: AlgebraicType::U64 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 7, name: "rows_returned", ty: AlgebraicType::U64 },
+            ColRow { table: ST_SLOW_EVENT_ID.into(), pos: 8, name: "timestamp", ty: AlgebraicType::timestamp() },
         ]));
         #[rustfmt::skip]
         assert_eq!(query.scan_st_indexes()?, map_array([
@@ -1803,6 +1923,7 @@ mod tests {
             IndexRow { id: 15, table: ST_VIEW_ID.into(), col: col(1), name: "st_view_view_name_idx_btree", },
             IndexRow { id: 16, table: ST_VIEW_PARAM_ID.into(), col: col_list![0, 1], name: "st_view_param_view_id_param_pos_idx_btree", },
             IndexRow { id: 17, table: ST_VIEW_COLUMN_ID.into(), col: col_list![0, 1], name: "st_view_column_view_id_col_pos_idx_btree", },
+            IndexRow { id: 18, table: ST_SLOW_EVENT_ID.into(), col: col(0), name: "st_slow_event_event_id_idx_btree", },
         ]));
         let start = ST_RESERVED_SEQUENCE_RANGE as i128 + 1;
         #[rustfmt::skip]
@@ -1814,6 +1935,7 @@ mod tests {
                 SequenceRow { id: 3, table: ST_CONSTRAINT_ID.into(), col_pos: 0, name: "st_constraint_constraint_id_seq", start },
                 SequenceRow { id: 4, table: ST_SCHEDULED_ID.into(), col_pos: 0, name: "st_scheduled_schedule_id_seq", start },
                 SequenceRow { id: 6, table: ST_VIEW_ID.into(), col_pos: 0, name: "st_view_view_id_seq", start },
+                SequenceRow { id: 7, table: ST_SLOW_EVENT_ID.into(), col_pos: 0, name: "st_slow_event_event_id_seq", start },
             ],
             |row| StSequenceRow {
                 allocated: start - 1,
@@ -1838,6 +1960,7 @@ mod tests {
             ConstraintRow { constraint_id: 14, table_id: ST_VIEW_ID.into(), unique_columns: col(1), constraint_name: "st_view_view_name_key", },
             ConstraintRow { constraint_id: 15, table_id: ST_VIEW_PARAM_ID.into(), unique_columns: col_list![0, 1], constraint_name: "st_view_param_view_id_param_pos_key", },
             ConstraintRow { constraint_id: 16, table_id: ST_VIEW_COLUMN_ID.into(), unique_columns: col_list![0, 1], constraint_name: "st_view_column_view_id_col_pos_key", },
+            ConstraintRow { constraint_id: 17, table_id: ST_SLOW_EVENT_ID.into(), unique_columns: col(0), constraint_name: "st_slow_event_event_id_key", },
             ]));
 
         // Verify we get back the tables correctly with the proper ids...
@@ -2258,6 +2381,7 @@ mod tests {
             IndexRow { id: 15, table: ST_VIEW_ID.into(), col: col(1), name: "st_view_view_name_idx_btree", },
             IndexRow { id: 16, table: ST_VIEW_PARAM_ID.into(), col: col_list![0, 1], name: "st_view_param_view_id_param_pos_idx_btree", },
             IndexRow { id: 17, table: ST_VIEW_COLUMN_ID.into(), col: col_list![0, 1], name: "st_view_column_view_id_col_pos_idx_btree", },
+            IndexRow { id: 18, table: ST_SLOW_EVENT_ID.into(), col: col(0), name: "st_slow_event_event_id_idx_btree", },
             IndexRow { id: seq_start,     table: FIRST_NON_SYSTEM_ID, col: col(0), name: "Foo_id_idx_btree",  },
             IndexRow { id: seq_start + 1, table: FIRST_NON_SYSTEM_ID, col: col(1), name: "Foo_name_idx_btree",  },
             IndexRow { id: seq_start + 2, table: FIRST_NON_SYSTEM_ID, col: col(2), name: "Foo_age_idx_btree",  },
@@ -2996,6 +3120,143 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    /// Test that a read-only TX reads from a snapshot,
+    /// which doesn't block a mutable TX from committing,
+    /// and doesn't observe the effects of that commit.
+    fn test_read_only_tx_snapshot_does_not_block_commit() -> ResultTest<()> {
+        let (datastore, mut tx, table_id) = setup_table()?;
+        let row1 = u32_str_u32(1, "Foo", 18);
+        insert(&datastore, &mut tx, table_id, &row1)?;
+        datastore.commit_mut_tx(tx)?;
+
+        let read_tx = begin_tx(&datastore);
+        assert!(datastore.committed_state.try_write().is_some());
+
+        // Commit a mutable TX while `read_tx` is still alive.
+        let mut tx = begin_mut_tx(&datastore);
+        let row2 = u32_str_u32(2, "Bar", 20);
+        insert(&datastore, &mut tx, table_id, &row2)?;
+        assert_eq!(datastore.delete_by_rel_mut_tx(&mut tx, table_id, [row1.clone()]), 1);
+        datastore.commit_mut_tx(tx)?;
+
+        // `read_tx` still sees the state as of when it began,
+        // while a new read-only TX sees the commit.
+        let new_read_tx = begin_tx(&datastore);
+        assert_eq!(all_rows_tx(&read_tx, table_id), [row1]);
+        assert_eq!(all_rows_tx(&new_read_tx, table_id), [row2]);
+        let _ = new_read_tx.release();
+        let _ = read_tx.release();
+
+        // A locked read-only TX excludes commits until released.
+        let locked_tx = datastore.begin_tx_locked(Workload::ForTests);
+        assert!(datastore.committed_state.try_write().is_none());
+        let _ = locked_tx.release();
+        assert!(datastore.committed_state.try_write().is_some());
+        Ok(())
+    }
+
+    #[test]
+    /// Test that committing deletions compacts the committed table incrementally,
+    /// keeping all remaining rows reachable through the indices.
+    fn test_commit_compacts_fragmented_table() -> ResultTest<()> {
+        let (datastore, mut tx, table_id) = setup_table()?;
+        let row = |i: u32| u32_str_u32(i, &format!("Foo{i}"), i);
+        for i in 0..10_000 {
+            insert(&datastore, &mut tx, table_id, &row(i))?;
+        }
+        commit(&datastore, tx)?;
+        let table_stats = || {
+            let committed_state = datastore.committed_state.read();
+            let table = committed_state.get_table(table_id).unwrap();
+            (table.bytes_occupied_overestimate(), table.fragmentation())
+        };
+        let (bytes_before, _) = table_stats();
+
+        // Delete three out of every four rows.
+        let mut tx = begin_mut_tx(&datastore);
+        let deleted = (0..10_000).filter(|i| i % 4 != 0).map(row);
+        assert_eq!(datastore.delete_by_rel_mut_tx(&mut tx, table_id, deleted), 7_500);
+        commit(&datastore, tx)?;
+
+        // Compaction waits while the table is shared with a read-only transaction,
+        // rather than copying it.
+        let read_tx = begin_tx(&datastore);
+        let stats_while_shared = table_stats();
+        for _ in 0..20 {
+            commit(&datastore, begin_mut_tx(&datastore))?;
+        }
+        assert_eq!(table_stats(), stats_while_shared);
+        let _ = read_tx.release();
+
+        // Compaction continues over the following commits, even empty ones.
+        for _ in 0..100 {
+            commit(&datastore, begin_mut_tx(&datastore))?;
+        }
+        let (bytes_after, fragmentation) = table_stats();
+        assert!(bytes_after < bytes_before);
+        assert!(fragmentation < 0.5);
+
+        let tx = begin_tx(&datastore);
+        assert_eq!(all_rows_tx(&tx, table_id).len(), 2_500);
+        for i in (0..10_000).step_by(4) {
+            let rows = datastore
+                .iter_by_col_eq_tx(&tx, table_id, 0, &AlgebraicValue::U32(i))?
+                .map(|row_ref| row_ref.to_product_value())
+                .collect::<Vec<_>>();
+            assert_eq!(rows, [row(i)]);
+        }
+        let _ = tx.release();
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_evicts_cold_pages() -> ResultTest<()> {
+        let (datastore, mut tx, table_id) = setup_table()?;
+        let row = |i: u32| u32_str_u32(i, &format!("Foo{i}"), i);
+        for i in 0..10_000 {
+            insert(&datastore, &mut tx, table_id, &row(i))?;
+        }
+        commit(&datastore, tx)?;
+        let num_resident_pages = || {
+            let committed_state = datastore.committed_state.read();
+            committed_state.get_table(table_id).unwrap().num_resident_pages()
+        };
+        let num_pages = num_resident_pages();
+        assert!(num_pages > 1);
+
+        // Pages can't be evicted before there's a snapshot to reload them from.
+        datastore.set_table_memory_limit(Some(0));
+        assert_eq!(num_resident_pages(), num_pages);
+
+        let dir = tempfile::tempdir()?;
+        let snapshots = SnapshotsPath::from_path_unchecked(dir.path());
+        snapshots.create()?;
+        let repo = SnapshotRepository::open(snapshots, datastore.database_identity, 0)?;
+        datastore.take_snapshot(&repo)?.expect("failed to take snapshot");
+        assert_eq!(num_resident_pages(), 0);
+
+        // Reading evicted pages reloads them.
+        let tx = begin_tx(&datastore);
+        for i in [0, 5_000, 9_999] {
+            let rows = datastore
+                .iter_by_col_eq_tx(&tx, table_id, 0, &AlgebraicValue::U32(i))?
+                .map(|row_ref| row_ref.to_product_value())
+                .collect::<Vec<_>>();
+            assert_eq!(rows, [row(i)]);
+        }
+        let _ = tx.release();
+        assert!(num_resident_pages() > 0);
+
+        // Commits evict the reloaded pages again once they've gone cold.
+        commit(&datastore, begin_mut_tx(&datastore))?;
+        assert_eq!(num_resident_pages(), 0);
+        let tx = begin_tx(&datastore);
+        assert_eq!(all_rows_tx(&tx, table_id).len(), 10_000);
+        let _ = tx.release();
+        Ok(())
+    }
+
     #[test]
     fn test_scheduled_table_insert_and_update() -> ResultTest<()> {
         // Build the minimal schema that is a valid scheduler table.
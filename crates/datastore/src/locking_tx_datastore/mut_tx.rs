@@ -4,7 +4,7 @@ use super::{
     delete_table::DeleteTable,
     sequence::{Sequence, SequencesState},
     state_view::{IterByColEqMutTx, IterByColRangeMutTx, IterMutTx, ScanIterByColRangeMutTx, StateView},
-    tx::TxId,
+    tx::{CommittedStateRead, TxId},
     tx_state::{IndexIdMap, PendingSchemaChange, TxState, TxTableForInsertion},
     SharedMutexGuard, SharedWriteGuard,
 };
@@ -291,8 +291,11 @@ impl MutTxId {
 
         // Create the indexes for the table.
         for index in indices {
-            let col_set = ColSet::from(index.index_algorithm.columns());
-            let is_unique = constraints.iter().any(|c| c.data.unique_columns() == Some(&col_set));
+            // Only indexes over a projection of the row can back a unique constraint.
+            let is_unique = index.index_algorithm.columns().is_some_and(|cols| {
+                let col_set = ColSet::from(cols);
+                constraints.iter().any(|c| c.data.unique_columns() == Some(&col_set))
+            });
             self.create_index(index, is_unique)?;
         }
 
@@ -387,7 +390,9 @@ impl MutTxId {
         let tx_table = commit_table.clone_structure(SquashedOffset::TX_STATE);
 
         // Add them to the committed and tx states.
-        self.committed_state_write_lock.tables.insert(table_id, commit_table);
+        self.committed_state_write_lock
+            .tables
+            .insert(table_id, Arc::new(commit_table));
         self.tx_state.insert_tables.insert(table_id, tx_table);
 
         // Record that the committed state table is pending.
@@ -718,6 +723,7 @@ impl MutTxId {
     /// - `index.table_id != TableId::SENTINEL`
     /// - `is_unique` must be `true` if and only if a unique constraint will exist on
     ///   `ColSet::from(&index.index_algorithm.columns())` after this transaction is committed.
+    ///   It must be `false` if `index.index_algorithm.columns()` is `None`.
     ///
     /// Ensures:
     /// - The index metadata is inserted into the system tables (and other data structures reflecting them).
@@ -768,6 +774,7 @@ impl MutTxId {
         // Builds the index and ensures that `table`'s row won't cause a unique constraint violation
         // due to the existing rows having the same value for some column(s).
         let build_from_rows = |index: &mut TableIndex, table: &Table, bs: &dyn BlobStore| -> Result<()> {
+            table.load_pages()?;
             let rows = table.scan_rows(bs);
             // SAFETY: (1) `tx_index` / `commit_index` was derived from `table` / `commit_table`
             // which in turn was derived from `commit_table`.
@@ -883,6 +890,7 @@ impl MutTxId {
 
         // Get an index seek iterator for the tx and committed state.
         let tx_iter = tx_index.map(|i| i.seek_range(&bounds));
+        commit_index.load_range(&bounds)?;
         let commit_iter = commit_index.seek_range(&bounds);
 
         let dt = self.tx_state.get_delete_table(table_id);
@@ -1463,7 +1471,7 @@ impl MutTxId {
         // Update the workload type of the execution context
         self.ctx.workload = workload.workload_type();
         let tx = TxId {
-            committed_state_shared_lock: SharedWriteGuard::downgrade(self.committed_state_write_lock),
+            committed_state: CommittedStateRead::Locked(SharedWriteGuard::downgrade(self.committed_state_write_lock)),
             lock_wait_time: Duration::ZERO,
             timer: Instant::now(),
             ctx: self.ctx,
@@ -1527,7 +1535,7 @@ impl MutTxId {
         // Update the workload type of the execution context
         self.ctx.workload = workload.workload_type();
         let tx = TxId {
-            committed_state_shared_lock: SharedWriteGuard::downgrade(self.committed_state_write_lock),
+            committed_state: CommittedStateRead::Locked(SharedWriteGuard::downgrade(self.committed_state_write_lock)),
             lock_wait_time: Duration::ZERO,
             timer: Instant::now(),
             ctx: self.ctx,
@@ -1763,6 +1771,10 @@ fn insert_physically_maybe_generate<'a, const GENERATE: bool>(
     let commit_parts = committed_state.get_table_and_blob_store(table_id)?;
     let (commit_table, ..) = commit_parts;
 
+    // Committing the row will insert it into one of the non-full pages of `commit_table`,
+    // which must hence be in memory by then.
+    commit_table.load_non_full_pages()?;
+
     // Get the insert table, so we can write the row into it.
     let (tx_table, tx_blob_store, _) = tx_state.get_table_and_blob_store_or_create_from(table_id, commit_table);
 
@@ -1862,6 +1874,14 @@ pub(super) fn insert<'a, const GENERATE: bool>(
             // - `commit_table` and `tx_table` use the same schema
             //   because `tx_table` is derived from `commit_table`.
             // - `tx_row_ptr` is correct per post-condition of `tx_table.confirm_insertion(...)`.
+            let loaded = unsafe {
+                Table::load_pages_for_same_row(commit_table, tx_table, tx_blob_store, tx_row_ptr, tx_row_hash)
+            };
+            if let Err(e) = loaded {
+                tx_table.delete(tx_blob_store, tx_row_ptr, |_| {});
+                return Err(e.into());
+            }
+            // SAFETY: As above.
             if let (_, Some(commit_ptr)) =
                 unsafe { Table::find_same_row(commit_table, tx_table, tx_blob_store, tx_row_ptr, tx_row_hash) }
             {
@@ -2018,6 +2038,9 @@ impl MutTxId {
             let index_key = unsafe { tx_row_ref.project_unchecked(&commit_index.indexed_columns) };
 
             // Try to find the old row first in the committed state using the `index_key`.
+            if let Err(e) = commit_table.load_pages_of(commit_index.seek_point(&index_key)) {
+                throw!(e);
+            }
             let mut old_commit_del_ptr = None;
             let commit_old_ptr = commit_index.seek_point(&index_key).next().filter(|&ptr| {
                 // Was committed row previously deleted in this TX?
@@ -2163,6 +2186,7 @@ impl MutTxId {
         let mut rows_removed = tx_table.clear(tx_blob_store);
 
         // Mark every row in the committed state as deleted.
+        commit_table.load_pages()?;
         for row in commit_table.scan_rows(commit_bs) {
             delete_table.insert(row.pointer());
             rows_removed += 1;
@@ -2230,6 +2254,13 @@ impl MutTxId {
         // SAFETY:
         // - `commit_table` and `tx_table` use the same schema.
         // - `temp_ptr` is valid because we just inserted it.
+        if let Err(e) = unsafe { Table::load_pages_for_same_row(commit_table, tx_table, tx_blob_store, temp_ptr, None) }
+        {
+            // SAFETY: `temp_ptr` is valid because we just inserted it and haven't deleted it since.
+            unsafe { tx_table.delete_internal_skip_pointer_map(tx_blob_store, temp_ptr) };
+            return Err(e.into());
+        }
+        // SAFETY: As above.
         let (hash, to_delete) = unsafe { Table::find_same_row(commit_table, tx_table, tx_blob_store, temp_ptr, None) };
         let to_delete = to_delete
             // Not present in commit table? Check if present in the tx table.
@@ -2327,7 +2358,7 @@ fn iter_by_col_range<'a, R: RangeBounds<AlgebraicValue>>(
     // If there's an index, use that.
     // It's sufficient to check that the committed state has an index
     // as index schema changes are applied immediately.
-    if let Some(commit_iter) = committed_state.index_seek(table_id, &cols, &range) {
+    if let Some(commit_iter) = committed_state.index_seek(table_id, &cols, &range)? {
         let tx_iter = tx_state.index_seek_by_cols(table_id, &cols, &range);
         let delete_table = tx_state.get_delete_table(table_id);
         let iter = combine_range_index_iters(delete_table, tx_iter, commit_iter);
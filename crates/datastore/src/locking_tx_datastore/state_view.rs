@@ -197,6 +197,7 @@ impl<'a> IterMutTx<'a> {
             .get(&table_id)
             .map(|table| (table, &tx_state.blob_store));
 
+        commit_table.load_pages()?;
         let iter = commit_table.scan_rows(&committed_state.blob_store);
         let stage = if let Some(deletes) = tx_state.get_delete_table(table_id) {
             // There are deletes in the tx state
@@ -295,14 +296,15 @@ pub struct IterTx<'a> {
 }
 
 impl<'a> IterTx<'a> {
-    pub(super) fn new(table_id: TableId, committed_state: &'a CommittedState) -> Self {
+    pub(super) fn new(table_id: TableId, committed_state: &'a CommittedState) -> Result<Self> {
         // The table_id was validated to exist in the committed state.
         let table = committed_state
             .tables
             .get(&table_id)
             .expect("table_id must exist in committed state");
+        table.load_pages()?;
         let iter = table.scan_rows(&committed_state.blob_store);
-        Self { iter }
+        Ok(Self { iter })
     }
 }
 
@@ -14,6 +14,7 @@ use spacetimedb_sats::AlgebraicValue;
 use spacetimedb_schema::schema::TableSchema;
 use spacetimedb_table::blob_store::BlobStore;
 use spacetimedb_table::table::Table;
+use std::ops::Deref;
 use std::sync::Arc;
 use std::{future, num::NonZeroU64};
 use std::{
@@ -21,9 +22,36 @@ use std::{
     time::{Duration, Instant},
 };
 
-/// A read-only transaction with a shared lock on the committed state.
+/// The committed state as seen by a [`TxId`].
+pub(super) enum CommittedStateRead {
+    /// A shared lock on the live committed state.
+    ///
+    /// While held, no mutable transaction can commit.
+    /// Used by transactions downgraded from a committed [`MutTxId`](super::MutTxId),
+    /// and by those which must observe every later commit,
+    /// see [`Locking::begin_tx_locked`](super::datastore::Locking::begin_tx_locked).
+    Locked(SharedReadGuard<CommittedState>),
+    /// A copy-on-write snapshot of the committed state.
+    ///
+    /// Holds no lock, so mutable transactions may commit while this is alive.
+    Snapshot(CommittedState),
+}
+
+impl Deref for CommittedStateRead {
+    type Target = CommittedState;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Locked(guard) => guard,
+            Self::Snapshot(snapshot) => snapshot,
+        }
+    }
+}
+
+/// A read-only transaction over a consistent view of the committed state,
+/// either a shared lock on it or a copy-on-write snapshot of it.
 pub struct TxId {
-    pub(super) committed_state_shared_lock: SharedReadGuard<CommittedState>,
+    pub(super) committed_state: CommittedStateRead,
     pub(super) lock_wait_time: Duration,
     pub(super) timer: Instant,
     // TODO(cloutiertyler): The below were made `pub` for the datastore split. We should
@@ -34,11 +62,11 @@ pub struct TxId {
 
 impl Datastore for TxId {
     fn blob_store(&self) -> &dyn BlobStore {
-        &self.committed_state_shared_lock.blob_store
+        &self.committed_state.blob_store
     }
 
     fn table(&self, table_id: TableId) -> Option<&Table> {
-        self.committed_state_shared_lock.get_table(table_id)
+        self.committed_state.get_table(table_id)
     }
 }
 
@@ -51,15 +79,15 @@ impl StateView for TxId {
         Self: 'a;
 
     fn get_schema(&self, table_id: TableId) -> Option<&Arc<TableSchema>> {
-        self.committed_state_shared_lock.get_schema(table_id)
+        self.committed_state.get_schema(table_id)
     }
 
     fn table_row_count(&self, table_id: TableId) -> Option<u64> {
-        self.committed_state_shared_lock.table_row_count(table_id)
+        self.committed_state.table_row_count(table_id)
     }
 
     fn iter(&self, table_id: TableId) -> Result<Self::Iter<'_>> {
-        self.committed_state_shared_lock.iter(table_id)
+        self.committed_state.iter(table_id)
     }
 
     /// Returns an iterator,
@@ -71,8 +99,7 @@ impl StateView for TxId {
         cols: ColList,
         range: R,
     ) -> Result<Self::IterByColRange<'_, R>> {
-        self.committed_state_shared_lock
-            .iter_by_col_range(table_id, cols, range)
+        self.committed_state.iter_by_col_range(table_id, cols, range)
     }
 
     fn iter_by_col_eq<'a, 'r>(
@@ -101,7 +128,7 @@ impl TxId {
         // in which case we'd wrongly return zero (a non-existent transaction).
         // This doesn not happen in practice, however, as [RelationalDB::set_initialized]
         // creates a transaction.
-        let tx_offset = self.committed_state_shared_lock.next_tx_offset.saturating_sub(1);
+        let tx_offset = self.committed_state.next_tx_offset.saturating_sub(1);
         let tx_metrics = TxMetrics::new(
             &self.ctx,
             self.timer,
@@ -109,7 +136,7 @@ impl TxId {
             self.metrics,
             true,
             None,
-            &self.committed_state_shared_lock,
+            &self.committed_state,
         );
         let reducer = self.ctx.into_reducer_name();
         (tx_offset, tx_metrics, reducer)
@@ -126,12 +153,19 @@ impl TxId {
     // This method must never return 0, as it's used as the divisor in quotients.
     // Do not change its return type to a bare `u64`.
     pub fn num_distinct_values(&self, table_id: TableId, cols: &ColList) -> Option<NonZeroU64> {
-        let table = self.committed_state_shared_lock.get_table(table_id)?;
+        let table = self.committed_state.get_table(table_id)?;
         let (_, index) = table.get_index_by_cols(cols)?;
         NonZeroU64::new(index.num_keys() as u64)
     }
 
     pub fn tx_offset(&self) -> future::Ready<TxOffset> {
-        future::ready(self.committed_state_shared_lock.next_tx_offset)
+        future::ready(self.committed_state.next_tx_offset)
+    }
+
+    /// Returns the offset the next transaction committed after those visible to this one will have.
+    ///
+    /// Every transaction with a smaller offset is visible to this transaction.
+    pub fn next_tx_offset(&self) -> TxOffset {
+        self.committed_state.next_tx_offset
     }
 }
@@ -14,6 +14,7 @@ use spacetimedb_table::{
     table_index::TableIndex,
 };
 use std::collections::{btree_map, BTreeMap};
+use std::sync::Arc;
 use thin_vec::ThinVec;
 
 /// A mapping to find the actual index given an `IndexId`.
@@ -96,7 +97,7 @@ pub enum PendingSchemaChange {
     /// it will be present here.
     IndexAdded(TableId, IndexId, Option<PointerMap>),
     /// The [`Table`] with [`TableId`] was removed.
-    TableRemoved(TableId, Table),
+    TableRemoved(TableId, Arc<Table>),
     /// The table with [`TableId`] was added.
     TableAdded(TableId),
     /// The access of the table with [`TableId`] was changed.
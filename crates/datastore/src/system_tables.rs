@@ -17,14 +17,15 @@ use spacetimedb_lib::db::raw_def::*;
 use spacetimedb_lib::de::{Deserialize, DeserializeOwned, Error};
 use spacetimedb_lib::ser::Serialize;
 use spacetimedb_lib::st_var::StVarValue;
-use spacetimedb_lib::{ConnectionId, Identity, ProductValue, SpacetimeType};
+use spacetimedb_lib::{ConnectionId, Identity, ProductValue, SpacetimeType, Timestamp};
 use spacetimedb_primitives::*;
 use spacetimedb_sats::algebraic_value::ser::value_serialize;
 use spacetimedb_sats::hash::Hash;
 use spacetimedb_sats::product_value::InvalidFieldError;
 use spacetimedb_sats::{impl_deserialize, impl_serialize, impl_st, u256, AlgebraicType, AlgebraicValue, ArrayValue};
 use spacetimedb_schema::def::{
-    BTreeAlgorithm, ConstraintData, DirectAlgorithm, IndexAlgorithm, ModuleDef, UniqueConstraintData,
+    BTreeAlgorithm, BTreeExprAlgorithm, ConstraintData, DirectAlgorithm, FullTextAlgorithm, IndexAlgorithm, ModuleDef,
+    PartialBTreeAlgorithm, UniqueConstraintData,
 };
 use spacetimedb_schema::schema::{
     ColumnSchema, ConstraintSchema, IndexSchema, RowLevelSecuritySchema, ScheduleSchema, Schema, SequenceSchema,
@@ -71,6 +72,8 @@ pub const ST_VIEW_ID: TableId = TableId(12);
 pub const ST_VIEW_PARAM_ID: TableId = TableId(13);
 /// The static ID of the table that tracks view columns
 pub const ST_VIEW_COLUMN_ID: TableId = TableId(14);
+/// The static ID of the table that records slow queries and reducers
+pub const ST_SLOW_EVENT_ID: TableId = TableId(15);
 
 pub(crate) const ST_CONNECTION_CREDENTIALS_NAME: &str = "st_connection_credentials";
 pub const ST_TABLE_NAME: &str = "st_table";
@@ -86,6 +89,7 @@ pub(crate) const ST_ROW_LEVEL_SECURITY_NAME: &str = "st_row_level_security";
 pub(crate) const ST_VIEW_NAME: &str = "st_view";
 pub(crate) const ST_VIEW_PARAM_NAME: &str = "st_view_param";
 pub(crate) const ST_VIEW_COLUMN_NAME: &str = "st_view_column";
+pub(crate) const ST_SLOW_EVENT_NAME: &str = "st_slow_event";
 /// Reserved range of sequence values used for system tables.
 ///
 /// Ids for user-created tables will start at `ST_RESERVED_SEQUENCE_RANGE`.
@@ -114,7 +118,7 @@ pub enum SystemTable {
     st_row_level_security,
 }
 
-pub fn system_tables() -> [TableSchema; 14] {
+pub fn system_tables() -> [TableSchema; 15] {
     [
         // The order should match the `id` of the system table, that start with [ST_TABLE_IDX].
         st_table_schema(),
@@ -131,6 +135,7 @@ pub fn system_tables() -> [TableSchema; 14] {
         st_view_schema(),
         st_view_param_schema(),
         st_view_column_schema(),
+        st_slow_event_schema(),
     ]
 }
 
@@ -173,6 +178,7 @@ pub(crate) const ST_CONNECTION_CREDENTIALS_IDX: usize = 10;
 pub(crate) const ST_VIEW_IDX: usize = 11;
 pub(crate) const ST_VIEW_PARAM_IDX: usize = 12;
 pub(crate) const ST_VIEW_COLUMN_IDX: usize = 13;
+pub(crate) const ST_SLOW_EVENT_IDX: usize = 14;
 
 macro_rules! st_fields_enum {
     ($(#[$attr:meta])* enum $ty_name:ident { $($name:expr, $var:ident = $discr:expr,)* }) => {
@@ -305,6 +311,19 @@ st_fields_enum!(enum StVarFields {
     "value", Value = 1,
 });
 
+// WARNING: For a stable schema, don't change the field names and discriminants.
+st_fields_enum!(enum StSlowEventFields {
+    "event_id", EventId = 0,
+    "workload", Workload = 1,
+    "query", Query = 2,
+    "plan_hash", PlanHash = 3,
+    "caller_identity", CallerIdentity = 4,
+    "duration_micros", DurationMicros = 5,
+    "rows_scanned", RowsScanned = 6,
+    "rows_returned", RowsReturned = 7,
+    "timestamp", Timestamp = 8,
+});
+
 st_fields_enum!(enum StScheduledFields {
     "schedule_id", ScheduleId = 0,
     "table_id", TableId = 1,
@@ -455,6 +474,14 @@ fn system_module_def() -> ModuleDef {
         .with_index_no_accessor_name(btree(StVarFields::Name))
         .with_primary_key(StVarFields::Name);
 
+    let st_slow_event_type = builder.add_type::<StSlowEventRow>();
+    builder
+        .build_table(ST_SLOW_EVENT_NAME, *st_slow_event_type.as_ref().expect("should be ref"))
+        .with_type(TableType::System)
+        .with_auto_inc_primary_key(StSlowEventFields::EventId)
+        .with_index_no_accessor_name(btree(StSlowEventFields::EventId))
+        .with_access(v9::TableAccess::Private);
+
     let result = builder
         .finish()
         .try_into()
@@ -474,6 +501,7 @@ fn system_module_def() -> ModuleDef {
     validate_system_table::<StViewFields>(&result, ST_VIEW_NAME);
     validate_system_table::<StViewParamFields>(&result, ST_VIEW_PARAM_NAME);
     validate_system_table::<StViewColumnFields>(&result, ST_VIEW_COLUMN_NAME);
+    validate_system_table::<StSlowEventFields>(&result, ST_SLOW_EVENT_NAME);
 
     result
 }
@@ -513,6 +541,7 @@ lazy_static::lazy_static! {
         m.insert("st_view_view_name_key", ConstraintId(14));
         m.insert("st_view_param_view_id_param_pos_key", ConstraintId(15));
         m.insert("st_view_column_view_id_col_pos_key", ConstraintId(16));
+        m.insert("st_slow_event_event_id_key", ConstraintId(17));
         m
     };
 }
@@ -539,6 +568,7 @@ lazy_static::lazy_static! {
         m.insert("st_view_view_name_idx_btree", IndexId(15));
         m.insert("st_view_param_view_id_param_pos_idx_btree", IndexId(16));
         m.insert("st_view_column_view_id_col_pos_idx_btree", IndexId(17));
+        m.insert("st_slow_event_event_id_idx_btree", IndexId(18));
         m
     };
 }
@@ -554,6 +584,7 @@ lazy_static::lazy_static! {
         m.insert("st_scheduled_schedule_id_seq", SequenceId(4));
         m.insert("st_sequence_sequence_id_seq", SequenceId(5));
         m.insert("st_view_view_id_seq", SequenceId(6));
+        m.insert("st_slow_event_event_id_seq", SequenceId(7));
         m
     };
 }
@@ -666,6 +697,10 @@ pub fn st_view_column_schema() -> TableSchema {
     st_schema(ST_VIEW_COLUMN_NAME, ST_VIEW_COLUMN_ID)
 }
 
+pub fn st_slow_event_schema() -> TableSchema {
+    st_schema(ST_SLOW_EVENT_NAME, ST_SLOW_EVENT_ID)
+}
+
 /// If `table_id` refers to a known system table, return its schema.
 ///
 /// Used when restoring from a snapshot; system tables are reinstantiated with this schema,
@@ -688,6 +723,7 @@ pub(crate) fn system_table_schema(table_id: TableId) -> Option<TableSchema> {
         ST_VIEW_ID => Some(st_view_schema()),
         ST_VIEW_PARAM_ID => Some(st_view_param_schema()),
         ST_VIEW_COLUMN_ID => Some(st_view_column_schema()),
+        ST_SLOW_EVENT_ID => Some(st_slow_event_schema()),
         _ => None,
     }
 }
@@ -887,6 +923,15 @@ pub enum StIndexAlgorithm {
 
     /// A Direct index.
     Direct { column: ColId },
+
+    /// A BTree index over computed keys.
+    BTreeExpr { keys: Vec<v9::RawIndexKeyExprV9> },
+
+    /// A BTree index over the rows matching a SQL filter.
+    PartialBTree { columns: ColList, filter: Box<str> },
+
+    /// A full-text index.
+    FullText { column: ColId },
 }
 
 impl From<IndexAlgorithm> for StIndexAlgorithm {
@@ -894,6 +939,13 @@ impl From<IndexAlgorithm> for StIndexAlgorithm {
         match algorithm {
             IndexAlgorithm::BTree(BTreeAlgorithm { columns }) => Self::BTree { columns },
             IndexAlgorithm::Direct(DirectAlgorithm { column }) => Self::Direct { column },
+            IndexAlgorithm::BTreeExpr(BTreeExprAlgorithm { keys }) => Self::BTreeExpr {
+                keys: keys.into_vec().into_iter().map(Into::into).collect(),
+            },
+            IndexAlgorithm::PartialBTree(PartialBTreeAlgorithm { columns, filter }) => {
+                Self::PartialBTree { columns, filter }
+            }
+            IndexAlgorithm::FullText(FullTextAlgorithm { column }) => Self::FullText { column },
             algo => unreachable!("unexpected `{algo:?}`, did you add a new one?"),
         }
     }
@@ -904,6 +956,13 @@ impl From<StIndexAlgorithm> for IndexAlgorithm {
         match algorithm {
             StIndexAlgorithm::BTree { columns } => Self::BTree(BTreeAlgorithm { columns }),
             StIndexAlgorithm::Direct { column } => Self::Direct(DirectAlgorithm { column }),
+            StIndexAlgorithm::BTreeExpr { keys } => Self::BTreeExpr(BTreeExprAlgorithm {
+                keys: keys.into_iter().map(Into::into).collect(),
+            }),
+            StIndexAlgorithm::PartialBTree { columns, filter } => {
+                Self::PartialBTree(PartialBTreeAlgorithm { columns, filter })
+            }
+            StIndexAlgorithm::FullText { column } => Self::FullText(FullTextAlgorithm { column }),
             algo => unreachable!("unexpected `{algo:?}` in system table `st_indexes`"),
         }
     }
@@ -1288,6 +1347,17 @@ pub const ST_VARNAME_SLOW_QRY: &str = "slow_ad_hoc_query_ms";
 pub const ST_VARNAME_SLOW_SUB: &str = "slow_subscription_query_ms";
 /// A system variable that defines a threshold for logging slow tx updates.
 pub const ST_VARNAME_SLOW_INC: &str = "slow_tx_update_ms";
+/// A system variable that defines a threshold for recording slow reducers.
+pub const ST_VARNAME_SLOW_REDUCER: &str = "slow_reducer_ms";
+/// A system variable that defines after how many transactions a snapshot is taken.
+/// Zero disables transaction-count snapshots.
+pub const ST_VARNAME_SNAPSHOT_EVERY_TXS: &str = "snapshot_every_txs";
+/// A system variable that defines the time after which a snapshot is taken,
+/// if there were transactions since the previous one.
+pub const ST_VARNAME_SNAPSHOT_INTERVAL: &str = "snapshot_interval_secs";
+/// A system variable that defines how many bytes of commitlog may be written
+/// before a snapshot is taken.
+pub const ST_VARNAME_SNAPSHOT_COMMITLOG_BYTES: &str = "snapshot_commitlog_bytes";
 
 /// The name of a system variable in `st_var`
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1296,6 +1366,10 @@ pub enum StVarName {
     SlowQryThreshold,
     SlowSubThreshold,
     SlowIncThreshold,
+    SlowReducerThreshold,
+    SnapshotEveryTxs,
+    SnapshotInterval,
+    SnapshotCommitlogBytes,
 }
 impl From<StVarName> for &'static str {
     fn from(value: StVarName) -> Self {
@@ -1304,6 +1378,10 @@ impl From<StVarName> for &'static str {
             StVarName::SlowQryThreshold => ST_VARNAME_SLOW_QRY,
             StVarName::SlowSubThreshold => ST_VARNAME_SLOW_SUB,
             StVarName::SlowIncThreshold => ST_VARNAME_SLOW_INC,
+            StVarName::SlowReducerThreshold => ST_VARNAME_SLOW_REDUCER,
+            StVarName::SnapshotEveryTxs => ST_VARNAME_SNAPSHOT_EVERY_TXS,
+            StVarName::SnapshotInterval => ST_VARNAME_SNAPSHOT_INTERVAL,
+            StVarName::SnapshotCommitlogBytes => ST_VARNAME_SNAPSHOT_COMMITLOG_BYTES,
         }
     }
 }
@@ -1322,6 +1400,10 @@ impl FromStr for StVarName {
             ST_VARNAME_SLOW_QRY => Ok(StVarName::SlowQryThreshold),
             ST_VARNAME_SLOW_SUB => Ok(StVarName::SlowSubThreshold),
             ST_VARNAME_SLOW_INC => Ok(StVarName::SlowIncThreshold),
+            ST_VARNAME_SLOW_REDUCER => Ok(StVarName::SlowReducerThreshold),
+            ST_VARNAME_SNAPSHOT_EVERY_TXS => Ok(StVarName::SnapshotEveryTxs),
+            ST_VARNAME_SNAPSHOT_INTERVAL => Ok(StVarName::SnapshotInterval),
+            ST_VARNAME_SNAPSHOT_COMMITLOG_BYTES => Ok(StVarName::SnapshotCommitlogBytes),
             _ => Err(anyhow::anyhow!("Invalid system variable {s}")),
         }
     }
@@ -1341,7 +1423,11 @@ impl StVarName {
             StVarName::RowLimit
             | StVarName::SlowQryThreshold
             | StVarName::SlowSubThreshold
-            | StVarName::SlowIncThreshold => AlgebraicType::U64,
+            | StVarName::SlowIncThreshold
+            | StVarName::SlowReducerThreshold
+            | StVarName::SnapshotEveryTxs
+            | StVarName::SnapshotInterval
+            | StVarName::SnapshotCommitlogBytes => AlgebraicType::U64,
         }
     }
 }
@@ -1371,6 +1457,49 @@ impl TryFrom<RowRef<'_>> for StVarRow {
     }
 }
 
+/// System table [ST_SLOW_EVENT_NAME]
+///
+/// A bounded log of queries and reducers that exceeded their `st_var` threshold.
+/// Only the most recent [`ST_SLOW_EVENT_CAPACITY`] events are retained.
+///
+/// | event_id | workload | query               | plan_hash | caller_identity | duration_micros | rows_scanned | rows_returned | timestamp |
+/// |----------|----------|---------------------|-----------|-----------------|-----------------|--------------|---------------|-----------|
+/// | 4097     | "sql"    | "SELECT * FROM foo" | 0x5e6f... | 0xc200...       | 150000          | 100000       | 12            | ...       |
+#[derive(Clone, Debug, Eq, PartialEq, SpacetimeType)]
+#[sats(crate = spacetimedb_lib)]
+pub struct StSlowEventRow {
+    pub event_id: u64,
+    /// The kind of workload, e.g. `Sql`, `Subscribe` or `Reducer`.
+    pub workload: Box<str>,
+    /// The SQL text of the query, or the name of the reducer.
+    pub query: Box<str>,
+    /// The [`Hash`] of the optimized query plan, used to group events for queries executed the same way.
+    /// For reducers, the [`Hash`] of the reducer name.
+    pub plan_hash: Hash,
+    pub caller_identity: IdentityViaU256,
+    pub duration_micros: u64,
+    pub rows_scanned: u64,
+    pub rows_returned: u64,
+    pub timestamp: Timestamp,
+}
+
+/// The maximum number of rows retained in [ST_SLOW_EVENT_NAME].
+/// When a new event is recorded, the oldest events beyond this count are deleted.
+pub const ST_SLOW_EVENT_CAPACITY: usize = 1000;
+
+impl TryFrom<RowRef<'_>> for StSlowEventRow {
+    type Error = DatastoreError;
+    fn try_from(row: RowRef<'_>) -> Result<Self, DatastoreError> {
+        read_via_bsatn(row)
+    }
+}
+
+impl From<StSlowEventRow> for ProductValue {
+    fn from(x: StSlowEventRow) -> Self {
+        to_product_value(&x)
+    }
+}
+
 /// System table [ST_SCHEDULED_NAME]
 /// | schedule_id | table_id | reducer_name | schedule_name |
 #[derive(Clone, Debug, Eq, PartialEq, SpacetimeType)]
@@ -307,6 +307,15 @@ impl<'a> RowRefIter<'a> {
                     ),
                 )
                 .map(Self::IndexScanRange),
+            PhysicalPlan::IxScan(
+                scan @ IxScan {
+                    arg: Sarg::Key(lower, upper),
+                    ..
+                },
+                _,
+            ) => tx
+                .index_scan_range(scan.schema.table_id, scan.index_id, &(lower.as_ref(), upper.as_ref()))
+                .map(Self::IndexScanRange),
             PhysicalPlan::Filter(input, expr) => Self::build(input, tx)
                 .map(Box::new)
                 .map(|input| Filter { input, expr })
@@ -395,13 +404,21 @@ pub(super) fn get_index(tx: &impl Datastore, table_id: TableId, index_id: IndexI
         .ok_or_else(|| anyhow!("IndexId `{index_id}` does not exist"))
 }
 
+/// Like [`get_index`], but also reloads every evicted page of the table,
+/// as the probes of an iterator-based index join cannot fail.
+fn get_loaded_index(tx: &impl Datastore, table_id: TableId, index_id: IndexId) -> Result<TableAndIndex<'_>> {
+    let index = get_index(tx, table_id, index_id)?;
+    index.table().load_pages()?;
+    Ok(index)
+}
+
 impl<'a> UniqueIxJoin<'a> {
     fn build_from<Tx>(join: &'a IxJoin, tx: &'a Tx) -> Result<Self>
     where
         Tx: Datastore + DeltaStore,
     {
         let lhs = Iter::build(&join.lhs, tx)?;
-        let rhs_index = get_index(tx, join.rhs.table_id, join.rhs_index)?;
+        let rhs_index = get_loaded_index(tx, join.rhs.table_id, join.rhs_index)?;
         Ok(Self {
             lhs: Box::new(lhs),
             rhs_index,
@@ -476,7 +493,7 @@ impl<'a> UniqueIxJoinRhs<'a> {
         Tx: Datastore + DeltaStore,
     {
         let lhs = Iter::build(&join.lhs, tx)?;
-        let rhs_index = get_index(tx, join.rhs.table_id, join.rhs_index)?;
+        let rhs_index = get_loaded_index(tx, join.rhs.table_id, join.rhs_index)?;
         Ok(Self {
             lhs: Box::new(lhs),
             rhs_index,
@@ -518,7 +535,7 @@ impl<'a> IxJoinIter<'a> {
         Tx: Datastore + DeltaStore,
     {
         let lhs = Iter::build(&join.lhs, tx)?;
-        let rhs_index = get_index(tx, join.rhs.table_id, join.rhs_index)?;
+        let rhs_index = get_loaded_index(tx, join.rhs.table_id, join.rhs_index)?;
         Ok(Self {
             lhs: Box::new(lhs),
             lhs_tuple: None,
@@ -631,7 +648,7 @@ impl<'a> IxJoinRhs<'a> {
         Tx: Datastore + DeltaStore,
     {
         let lhs = Iter::build(&join.lhs, tx)?;
-        let rhs_index = get_index(tx, join.rhs.table_id, join.rhs_index)?;
+        let rhs_index = get_loaded_index(tx, join.rhs.table_id, join.rhs_index)?;
         Ok(Self {
             lhs: Box::new(lhs),
             rhs_index,
@@ -34,9 +34,10 @@ pub trait Datastore {
     }
 
     fn table_scan(&self, table_id: TableId) -> Result<TableScanIter<'_>> {
-        self.table(table_id)
-            .map(|table| table.scan_rows(self.blob_store()))
-            .ok_or_else(|| anyhow!("TableId `{table_id}` does not exist"))
+        let table = self.table_or_err(table_id)?;
+        // Reload evicted pages up front, as the iterator cannot fail.
+        table.load_pages()?;
+        Ok(table.scan_rows(self.blob_store()))
     }
 
     fn index_scan_point(
@@ -48,10 +49,11 @@ pub trait Datastore {
         self.table(table_id)
             .ok_or_else(|| anyhow!("TableId `{table_id}` does not exist"))
             .and_then(|table| {
-                table
+                let index = table
                     .get_index_by_id_with_table(self.blob_store(), index_id)
-                    .map(|i| i.seek_point(key))
-                    .ok_or_else(|| anyhow!("IndexId `{index_id}` does not exist"))
+                    .ok_or_else(|| anyhow!("IndexId `{index_id}` does not exist"))?;
+                index.load_point(key)?;
+                Ok(index.seek_point(key))
             })
     }
 
@@ -64,10 +66,11 @@ pub trait Datastore {
         self.table(table_id)
             .ok_or_else(|| anyhow!("TableId `{table_id}` does not exist"))
             .and_then(|table| {
-                table
+                let index = table
                     .get_index_by_id_with_table(self.blob_store(), index_id)
-                    .map(|i| i.seek_range(range))
-                    .ok_or_else(|| anyhow!("IndexId `{index_id}` does not exist"))
+                    .ok_or_else(|| anyhow!("IndexId `{index_id}` does not exist"))?;
+                index.load_range(range)?;
+                Ok(index.seek_range(range))
             })
     }
 }
@@ -1,6 +1,8 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Bound,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
@@ -43,6 +45,37 @@ impl From<ProjectListPlan> for ProjectListExecutor {
 }
 
 impl ProjectListExecutor {
+    /// Wraps every operator in a [PipelinedAnalyze] for `EXPLAIN ANALYZE`
+    pub fn analyze(self) -> Self {
+        match self {
+            Self::Name(plans) => Self::Name(plans.into_iter().map(PipelinedProject::analyze).collect()),
+            Self::List(plans, fields) => {
+                Self::List(plans.into_iter().map(PipelinedExecutor::analyze).collect(), fields)
+            }
+            Self::Limit(plan, n) => Self::Limit(Box::new(plan.analyze()), n),
+            Self::Agg(plans, agg_type) => {
+                Self::Agg(plans.into_iter().map(PipelinedExecutor::analyze).collect(), agg_type)
+            }
+        }
+    }
+
+    /// Walks and visits each executor in the tree
+    pub fn visit(&self, f: &mut impl FnMut(&PipelinedExecutor)) {
+        match self {
+            Self::Name(plans) => {
+                for plan in plans {
+                    plan.visit(f);
+                }
+            }
+            Self::List(plans, _) | Self::Agg(plans, _) => {
+                for plan in plans {
+                    plan.visit(f);
+                }
+            }
+            Self::Limit(plan, _) => plan.visit(f),
+        }
+    }
+
     pub fn execute<Tx: Datastore + DeltaStore>(
         &self,
         tx: &Tx,
@@ -127,6 +160,14 @@ impl From<ProjectPlan> for PipelinedProject {
 }
 
 impl PipelinedProject {
+    /// Wraps every operator in a [PipelinedAnalyze] for `EXPLAIN ANALYZE`
+    pub fn analyze(self) -> Self {
+        match self {
+            Self::None(plan) => Self::None(plan.analyze()),
+            Self::Some(plan, i) => Self::Some(plan.analyze(), i),
+        }
+    }
+
     /// Walks and visits each executor in the tree
     pub fn visit(&self, f: &mut impl FnMut(&PipelinedExecutor)) {
         match self {
@@ -195,6 +236,7 @@ pub enum PipelinedExecutor {
     NLJoin(BlockingNLJoin),
     Filter(PipelinedFilter),
     Limit(PipelinedLimit),
+    Analyze(PipelinedAnalyze),
 }
 
 impl From<PhysicalPlan> for PipelinedExecutor {
@@ -280,6 +322,42 @@ impl From<PhysicalPlan> for PipelinedExecutor {
 }
 
 impl PipelinedExecutor {
+    /// Wraps this operator, and each of its inputs, in a [PipelinedAnalyze] for `EXPLAIN ANALYZE`
+    pub fn analyze(self) -> Self {
+        let analyze = |input: Box<Self>| Box::new(input.analyze());
+        let op = match self {
+            Self::IxJoin(join) => Self::IxJoin(PipelinedIxJoin {
+                lhs: analyze(join.lhs),
+                ..join
+            }),
+            Self::IxDeltaJoin(join) => Self::IxDeltaJoin(PipelinedIxDeltaJoin {
+                lhs: analyze(join.lhs),
+                ..join
+            }),
+            Self::HashJoin(join) => Self::HashJoin(BlockingHashJoin {
+                lhs: analyze(join.lhs),
+                rhs: analyze(join.rhs),
+                ..join
+            }),
+            Self::NLJoin(join) => Self::NLJoin(BlockingNLJoin {
+                lhs: analyze(join.lhs),
+                rhs: analyze(join.rhs),
+            }),
+            Self::Filter(filter) => Self::Filter(PipelinedFilter {
+                input: analyze(filter.input),
+                ..filter
+            }),
+            Self::Limit(limit) => Self::Limit(PipelinedLimit {
+                input: analyze(limit.input),
+                ..limit
+            }),
+            // Already instrumented
+            op @ Self::Analyze(_) => return op,
+            op @ (Self::TableScan(..) | Self::IxScan(..) | Self::IxDeltaScan(..)) => op,
+        };
+        Self::Analyze(PipelinedAnalyze::new(op))
+    }
+
     /// Walks and visits each executor in the tree
     pub fn visit(&self, f: &mut impl FnMut(&Self)) {
         f(self);
@@ -287,7 +365,8 @@ impl PipelinedExecutor {
             Self::IxJoin(PipelinedIxJoin { lhs: input, .. })
             | Self::IxDeltaJoin(PipelinedIxDeltaJoin { lhs: input, .. })
             | Self::Filter(PipelinedFilter { input, .. })
-            | Self::Limit(PipelinedLimit { input, .. }) => {
+            | Self::Limit(PipelinedLimit { input, .. })
+            | Self::Analyze(PipelinedAnalyze { input, .. }) => {
                 input.visit(f);
             }
             Self::NLJoin(BlockingNLJoin { lhs, rhs }) | Self::HashJoin(BlockingHashJoin { lhs, rhs, .. }) => {
@@ -310,6 +389,7 @@ impl PipelinedExecutor {
             Self::NLJoin(join) => join.is_empty(tx),
             Self::Filter(filter) => filter.is_empty(tx),
             Self::Limit(limit) => limit.is_empty(tx),
+            Self::Analyze(analyze) => analyze.is_empty(tx),
         }
     }
 
@@ -329,6 +409,7 @@ impl PipelinedExecutor {
             Self::NLJoin(join) => join.execute(tx, metrics, f),
             Self::Filter(filter) => filter.execute(tx, metrics, f),
             Self::Limit(limit) => limit.execute(tx, metrics, f),
+            Self::Analyze(analyze) => analyze.execute(tx, metrics, f),
         }
     }
 }
@@ -489,6 +570,20 @@ impl From<IxScan> for PipelinedIxDeltaScan {
                 upper,
                 delta: Delta::Deletes,
             },
+            IxScan {
+                schema,
+                index_id,
+                arg: Sarg::Key(lower, upper),
+                delta,
+                ..
+            } => Self {
+                table_id: schema.table_id,
+                index_id,
+                prefix: vec![],
+                lower,
+                upper,
+                delta: delta.unwrap_or(Delta::Deletes),
+            },
         }
     }
 }
@@ -611,6 +706,21 @@ impl From<IxScan> for PipelinedIxScan {
                 lower,
                 upper,
             },
+            IxScan {
+                schema,
+                limit,
+                delta: _,
+                index_id,
+                prefix: _,
+                arg: Sarg::Key(lower, upper),
+            } => Self {
+                table_id: schema.table_id,
+                index_id,
+                limit,
+                prefix: vec![],
+                lower,
+                upper,
+            },
         }
     }
 }
@@ -767,12 +877,9 @@ impl PipelinedIxJoin {
                 lhs.execute(tx, metrics, &mut |u| {
                     n += 1;
                     index_seeks += 1;
-                    if let Some(v) = rhs_index
-                        .seek_point(&project(&u, lhs_field, &mut bytes_scanned))
-                        .next()
-                        .map(Row::Ptr)
-                        .map(Tuple::Row)
-                    {
+                    let key = project(&u, lhs_field, &mut bytes_scanned);
+                    rhs_index.load_point(&key)?;
+                    if let Some(v) = rhs_index.seek_point(&key).next().map(Row::Ptr).map(Tuple::Row) {
                         f(v)?;
                     }
                     Ok(())
@@ -789,12 +896,9 @@ impl PipelinedIxJoin {
                 lhs.execute(tx, metrics, &mut |u| {
                     n += 1;
                     index_seeks += 1;
-                    if let Some(v) = rhs_index
-                        .seek_point(&project(&u, lhs_field, &mut bytes_scanned))
-                        .next()
-                        .map(Row::Ptr)
-                        .map(Tuple::Row)
-                    {
+                    let key = project(&u, lhs_field, &mut bytes_scanned);
+                    rhs_index.load_point(&key)?;
+                    if let Some(v) = rhs_index.seek_point(&key).next().map(Row::Ptr).map(Tuple::Row) {
                         f(u.join(v))?;
                     }
                     Ok(())
@@ -831,11 +935,9 @@ impl PipelinedIxJoin {
                 lhs.execute(tx, metrics, &mut |u| {
                     n += 1;
                     index_seeks += 1;
-                    for v in rhs_index
-                        .seek_point(&project(&u, lhs_field, &mut bytes_scanned))
-                        .map(Row::Ptr)
-                        .map(Tuple::Row)
-                    {
+                    let key = project(&u, lhs_field, &mut bytes_scanned);
+                    rhs_index.load_point(&key)?;
+                    for v in rhs_index.seek_point(&key).map(Row::Ptr).map(Tuple::Row) {
                         f(v)?;
                     }
                     Ok(())
@@ -852,11 +954,9 @@ impl PipelinedIxJoin {
                 lhs.execute(tx, metrics, &mut |u| {
                     n += 1;
                     index_seeks += 1;
-                    for v in rhs_index
-                        .seek_point(&project(&u, lhs_field, &mut bytes_scanned))
-                        .map(Row::Ptr)
-                        .map(Tuple::Row)
-                    {
+                    let key = project(&u, lhs_field, &mut bytes_scanned);
+                    rhs_index.load_point(&key)?;
+                    for v in rhs_index.seek_point(&key).map(Row::Ptr).map(Tuple::Row) {
                         f(u.clone().join(v))?;
                     }
                     Ok(())
@@ -1383,6 +1483,65 @@ impl PipelinedLimit {
     }
 }
 
+/// Records the number of rows returned by its input, and the time spent computing them, for `EXPLAIN ANALYZE`.
+///
+/// The time includes the time spent in the input's own inputs,
+/// but not the time its consumer spends processing the rows.
+/// Both accumulate if the plan is executed more than once.
+#[derive(Debug)]
+pub struct PipelinedAnalyze {
+    pub input: Box<PipelinedExecutor>,
+    rows: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl PipelinedAnalyze {
+    pub fn new(input: PipelinedExecutor) -> Self {
+        Self {
+            input: Box::new(input),
+            rows: AtomicU64::new(0),
+            nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// The number of rows returned by the input
+    pub fn rows(&self) -> u64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// The time spent computing the rows returned by the input
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Does this operation contain an empty delta scan?
+    pub fn is_empty(&self, tx: &impl DeltaStore) -> bool {
+        self.input.is_empty(tx)
+    }
+
+    pub fn execute<'a, Tx: Datastore + DeltaStore>(
+        &self,
+        tx: &'a Tx,
+        metrics: &mut ExecutionMetrics,
+        f: &mut dyn FnMut(Tuple<'a>) -> Result<()>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let mut n = 0;
+        let mut downstream = Duration::ZERO;
+        let result = self.input.execute(tx, metrics, &mut |t| {
+            n += 1;
+            let start = Instant::now();
+            let result = f(t);
+            downstream += start.elapsed();
+            result
+        });
+        let elapsed = start.elapsed().saturating_sub(downstream);
+        self.rows.fetch_add(n, Ordering::Relaxed);
+        self.nanos.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+}
+
 /// A wrapper around [ProjectField] that increments a counter by the size of the projected value
 fn project(row: &impl ProjectField, field: &TupleField, bytes_scanned: &mut usize) -> AlgebraicValue {
     let value = row.project(field);
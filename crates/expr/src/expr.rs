@@ -141,7 +141,7 @@ pub enum ProjectList {
     Agg(Vec<RelExpr>, AggType, Box<str>, AlgebraicType),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum AggType {
     Count,
 }
@@ -324,6 +324,11 @@ pub enum Expr {
     Value(AlgebraicValue, AlgebraicType),
     /// A field projection
     Field(FieldProject),
+    /// A projection of a nested field of a product value,
+    /// given by the positions of the fields to descend into, e.g. `t.position.x`
+    Path(Box<Expr>, Box<[usize]>, AlgebraicType),
+    /// A full-text search of a string for the tokens of a query, e.g. `match(t.name, 'red sword')`
+    Match(Box<Expr>, Box<str>),
 }
 
 impl Expr {
@@ -335,6 +340,7 @@ impl Expr {
                 a.visit(f);
                 b.visit(f);
             }
+            Self::Path(expr, ..) | Self::Match(expr, _) => expr.visit(f),
             Self::Value(..) | Self::Field(..) => {}
         }
     }
@@ -347,6 +353,7 @@ impl Expr {
                 a.visit_mut(f);
                 b.visit_mut(f);
             }
+            Self::Path(expr, ..) | Self::Match(expr, _) => expr.visit_mut(f),
             Self::Value(..) | Self::Field(..) => {}
         }
     }
@@ -364,8 +371,8 @@ impl Expr {
     /// The [AlgebraicType] of this scalar expression
     pub fn ty(&self) -> &AlgebraicType {
         match self {
-            Self::BinOp(..) | Self::LogOp(..) => &AlgebraicType::Bool,
-            Self::Value(_, ty) | Self::Field(FieldProject { ty, .. }) => ty,
+            Self::BinOp(..) | Self::LogOp(..) | Self::Match(..) => &AlgebraicType::Bool,
+            Self::Value(_, ty) | Self::Field(FieldProject { ty, .. }) | Self::Path(_, _, ty) => ty,
         }
     }
 }
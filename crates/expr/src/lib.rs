@@ -123,6 +123,33 @@ fn _type_expr(vars: &Relvars, expr: SqlExpr, expected: Option<&AlgebraicType>, d
                 ty: col_type.clone(),
             }))
         }
+        (SqlExpr::Path(SqlIdent(table), path), expected) => {
+            let table_type = vars.deref().get(&table).ok_or_else(|| Unresolved::var(&table))?;
+            let mut path = path.into_vec().into_iter().map(|SqlIdent(name)| name);
+            let column = path.next().ok_or_else(|| Unresolved::var(&table))?;
+            let ColumnSchema { col_pos, col_type, .. } = table_type
+                .get_column_by_name(&column)
+                .ok_or_else(|| Unresolved::var(&column))?;
+            let mut ty = col_type;
+            let mut fields = vec![];
+            for name in path {
+                let (pos, elem) = ty
+                    .as_product()
+                    .and_then(|ty| ty.elements.iter().enumerate().find(|(_, elem)| elem.has_name(&name)))
+                    .ok_or_else(|| Unresolved::field(&column, &name))?;
+                fields.push(pos);
+                ty = &elem.algebraic_type;
+            }
+            if let Some(expected) = expected.filter(|expected| *expected != ty) {
+                return Err(UnexpectedType::new(expected, ty).into());
+            }
+            let field = Expr::Field(FieldProject {
+                table,
+                field: col_pos.idx(),
+                ty: col_type.clone(),
+            });
+            Ok(Expr::Path(Box::new(field), fields.into(), ty.clone()))
+        }
         (SqlExpr::Log(a, b, op), None | Some(AlgebraicType::Bool)) => {
             let a = _type_expr(vars, *a, Some(&AlgebraicType::Bool), depth + 1)?;
             let b = _type_expr(vars, *b, Some(&AlgebraicType::Bool), depth + 1)?;
@@ -144,7 +171,13 @@ fn _type_expr(vars: &Relvars, expr: SqlExpr, expected: Option<&AlgebraicType>, d
             }
             Ok(Expr::BinOp(op, Box::new(a), Box::new(b)))
         }
-        (SqlExpr::Bin(..) | SqlExpr::Log(..), Some(ty)) => Err(UnexpectedType::new(&AlgebraicType::Bool, ty).into()),
+        (SqlExpr::Match(a, query), None | Some(AlgebraicType::Bool)) => {
+            let a = _type_expr(vars, *a, Some(&AlgebraicType::String), depth + 1)?;
+            Ok(Expr::Match(Box::new(a), query))
+        }
+        (SqlExpr::Bin(..) | SqlExpr::Log(..) | SqlExpr::Match(..), Some(ty)) => {
+            Err(UnexpectedType::new(&AlgebraicType::Bool, ty).into())
+        }
         // Both unqualified names as well as parameters are syntactic constructs.
         // Unqualified names are qualified and parameters are resolved before type checking.
         (SqlExpr::Var(_) | SqlExpr::Param(_), _) => unreachable!(),
@@ -5,7 +5,7 @@ use spacetimedb_primitives::{ColId, TableId};
 use spacetimedb_schema::schema::{ColumnSchema, TableSchema};
 use spacetimedb_sql_parser::{
     ast::{
-        sql::{SqlAst, SqlDelete, SqlInsert, SqlSelect, SqlSet, SqlShow, SqlUpdate},
+        sql::{SqlAst, SqlDelete, SqlExplain, SqlInsert, SqlSelect, SqlSet, SqlShow, SqlUpdate},
         BinOp, SqlIdent, SqlLiteral,
     },
     parser::sql::parse_sql,
@@ -29,6 +29,7 @@ use super::{
 pub enum Statement {
     Select(ProjectList),
     DML(DML),
+    Explain(Explain),
 }
 
 pub enum DML {
@@ -74,6 +75,13 @@ pub struct TableUpdate {
     pub filter: Option<Expr>,
 }
 
+/// EXPLAIN [ ANALYZE ] SELECT ...
+pub struct Explain {
+    /// Should the query be executed, so that row counts and timings can be reported?
+    pub analyze: bool,
+    pub select: ProjectList,
+}
+
 pub struct SetVar {
     pub name: String,
     pub value: AlgebraicValue,
@@ -435,6 +443,10 @@ pub fn parse_and_type_sql(sql: &str, tx: &impl SchemaView, auth: &AuthCtx) -> Ty
         SqlAst::Update(update) => Ok(Statement::DML(DML::Update(type_update(update, tx)?))),
         SqlAst::Set(set) => Ok(Statement::DML(DML::Insert(type_and_rewrite_set(set, tx)?))),
         SqlAst::Show(show) => Ok(Statement::Select(type_and_rewrite_show(show, tx)?)),
+        SqlAst::Explain(SqlExplain { analyze, select }) => Ok(Statement::Explain(Explain {
+            analyze,
+            select: SqlChecker::type_ast(select, tx)?,
+        })),
     }
 }
 
@@ -498,6 +510,8 @@ mod tests {
             "select str, arr from t",
             "select t.str, arr from t",
             "select * from t limit 5",
+            "explain select * from t limit 5",
+            "explain analyze select t.str from t join s on t.u32 = s.u32",
         ] {
             let result = parse_and_type_sql(sql, &tx);
             assert!(result.is_ok());
@@ -515,6 +529,8 @@ mod tests {
             "select * from t limit '5'",
             // Unqualified name in join expression
             "select t.* from t join s on t.u32 = s.u32 where bytes = 0xABCD",
+            // Explaining a query that doesn't type check
+            "explain select * from t limit '5'",
         ] {
             let result = parse_and_type_sql(sql, &tx);
             assert!(result.is_err());
@@ -289,6 +289,64 @@ pub enum RawIndexAlgorithm {
         /// Only one is allowed, as direct indexing with more is nonsensical.
         column: ColId,
     },
+    /// Implemented using a B-Tree over keys computed from each row,
+    /// rather than over a projection of the row's columns.
+    ///
+    /// Each key is a path into a column, possibly descending into nested product fields,
+    /// optionally followed by a deterministic function applied to the value found there.
+    BTreeExpr {
+        /// The key expressions to index on. These are ordered.
+        keys: Vec<RawIndexKeyExprV9>,
+    },
+    /// A B-Tree index which only contains the rows matching `filter`.
+    PartialBTree {
+        /// The columns to index on. These are ordered.
+        columns: ColList,
+        /// A SQL boolean expression over the unqualified column names of the table,
+        /// e.g. `status = 'active'`.
+        filter: Box<str>,
+    },
+    /// An inverted index from the tokens of a `String` column to the rows containing them.
+    ///
+    /// See [`crate::full_text`] for how text is split into tokens.
+    FullText {
+        /// The column to index on. It must be of type `String`.
+        column: ColId,
+    },
+}
+
+/// A key expression of a [`RawIndexAlgorithm::BTreeExpr`] index.
+#[derive(Debug, Clone, SpacetimeType)]
+#[sats(crate = crate)]
+#[cfg_attr(feature = "test", derive(PartialEq, Eq, PartialOrd, Ord))]
+pub struct RawIndexKeyExprV9 {
+    /// The column the path starts at.
+    pub column: ColId,
+    /// Positions of nested product fields to descend into, starting at `column`.
+    ///
+    /// An empty path indexes the column itself.
+    pub fields: Vec<u16>,
+    /// A function to apply to the value found at the end of the path, if any.
+    pub function: Option<IndexKeyFunction>,
+}
+
+/// A deterministic function that may be applied to an index key.
+/// New variants MUST be added to the END of this enum, to maintain ABI compatibility.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
+#[sats(crate = crate)]
+pub enum IndexKeyFunction {
+    /// Lowercases a `String` key.
+    Lower,
+}
+
+impl IndexKeyFunction {
+    /// Returns the name of this function, as written in index definitions and SQL.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lower => "lower",
+        }
+    }
 }
 
 /// Returns a btree index algorithm for the columns `cols`.
@@ -301,6 +359,27 @@ pub fn direct(col: impl Into<ColId>) -> RawIndexAlgorithm {
     RawIndexAlgorithm::Direct { column: col.into() }
 }
 
+/// Returns a btree index algorithm over the key expressions `keys`.
+pub fn btree_expr(keys: impl IntoIterator<Item = RawIndexKeyExprV9>) -> RawIndexAlgorithm {
+    RawIndexAlgorithm::BTreeExpr {
+        keys: keys.into_iter().collect(),
+    }
+}
+
+/// Returns a btree index algorithm for the columns `cols`,
+/// containing only the rows that match the SQL predicate `filter`.
+pub fn partial_btree(cols: impl Into<ColList>, filter: impl Into<Box<str>>) -> RawIndexAlgorithm {
+    RawIndexAlgorithm::PartialBTree {
+        columns: cols.into(),
+        filter: filter.into(),
+    }
+}
+
+/// Returns a full-text index algorithm for the column `col`.
+pub fn full_text(col: impl Into<ColId>) -> RawIndexAlgorithm {
+    RawIndexAlgorithm::FullText { column: col.into() }
+}
+
 /// Marks a table as a timer table for a scheduled reducer or procedure.
 ///
 /// The table must have columns:
@@ -381,6 +460,8 @@ pub enum RawMiscModuleExportV9 {
     Procedure(RawProcedureDefV9),
     /// A view definition.
     View(RawViewDefV9),
+    /// The return and error types of a reducer.
+    ReducerReturnType(RawReducerReturnTypeV9),
 }
 
 /// Marks a particular table's column as having a particular default.
@@ -497,6 +578,32 @@ pub struct RawReducerDefV9 {
     pub lifecycle: Option<Lifecycle>,
 }
 
+/// The types of the values returned by a reducer.
+///
+/// Will be wrapped in [`RawMiscModuleExportV9`] and included in the [`RawModuleDefV9`]'s `misc_exports` vec.
+/// A reducer without a `RawReducerReturnTypeV9` returns `()` on success
+/// and a `String` error message on failure.
+#[derive(Debug, Clone, SpacetimeType)]
+#[sats(crate = crate)]
+#[cfg_attr(feature = "test", derive(PartialEq, Eq, PartialOrd, Ord))]
+pub struct RawReducerReturnTypeV9 {
+    /// The name of the reducer.
+    /// This corresponds to `name` in `RawReducerDefV9`.
+    pub reducer: RawIdentifier,
+
+    /// The type of the value returned when the reducer succeeds.
+    ///
+    /// If this is a user-defined product or sum type,
+    /// it should be registered in the typespace and indirected through an [`AlgebraicType::Ref`].
+    pub ok_return_type: AlgebraicType,
+
+    /// The type of the error returned when the reducer fails.
+    ///
+    /// If this is a user-defined product or sum type,
+    /// it should be registered in the typespace and indirected through an [`AlgebraicType::Ref`].
+    pub err_return_type: AlgebraicType,
+}
+
 /// Special roles a reducer can play in the module lifecycle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SpacetimeType)]
 #[cfg_attr(feature = "enum-map", derive(enum_map::Enum))]
@@ -704,6 +811,27 @@ impl RawModuleDefV9Builder {
         });
     }
 
+    /// Declare the types of the values returned by the reducer `reducer`.
+    ///
+    /// Reducers without a declared return type return `()` on success
+    /// and a `String` error message on failure.
+    /// If either type is a user-defined product or sum type,
+    /// it should be registered in the typespace and indirected through an `AlgebraicType::Ref`.
+    pub fn add_reducer_return_type(
+        &mut self,
+        reducer: impl Into<RawIdentifier>,
+        ok_return_type: AlgebraicType,
+        err_return_type: AlgebraicType,
+    ) {
+        self.module
+            .misc_exports
+            .push(RawMiscModuleExportV9::ReducerReturnType(RawReducerReturnTypeV9 {
+                reducer: reducer.into(),
+                ok_return_type,
+                err_return_type,
+            }))
+    }
+
     /// Add a procedure to the in-progress module.
     ///
     /// Accepts a `ProductType` of arguments.
@@ -0,0 +1,57 @@
+//! Tokenization for full-text indexes.
+//!
+//! The host maintains full-text indexes and evaluates `match(...)` predicates with these functions,
+//! and modules use them to check the rows fetched by `filter_text`,
+//! so both sides must agree on what the tokens of a text are.
+
+/// Returns the distinct tokens of `text`, sorted.
+///
+/// A token is a maximal run of alphanumeric characters, lowercased.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    tokens.sort_unstable();
+    tokens.dedup();
+    tokens
+}
+
+/// Does `text` contain every token of `query`, i.e., every one of `terms`?
+///
+/// The `terms` must be the tokens of the query, as returned by [`tokenize`].
+/// A query without tokens matches nothing.
+pub fn matches(text: &str, terms: &[impl AsRef<str>]) -> bool {
+    if terms.is_empty() {
+        return false;
+    }
+    let tokens = tokenize(text);
+    terms
+        .iter()
+        .all(|term| tokens.binary_search_by(|token| (**token).cmp(term.as_ref())).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize("The red sword, the RED shield!"),
+            ["red", "shield", "sword", "the"]
+        );
+        assert_eq!(tokenize("épée_2h"), ["2h", "épée"]);
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn matching() {
+        let text = "A red sword of fire";
+        assert!(matches(text, &tokenize("Red SWORD")));
+        assert!(matches(text, &tokenize("fire")));
+        assert!(!matches(text, &tokenize("red shield")));
+        assert!(!matches(text, &tokenize("")));
+    }
+}
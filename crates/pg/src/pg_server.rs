@@ -22,7 +22,7 @@ use pgwire::messages::data::DataRow;
 use pgwire::messages::startup::Authentication;
 use pgwire::messages::{PgWireBackendMessage, PgWireFrontendMessage};
 use pgwire::tokio::process_socket;
-use spacetimedb_client_api::auth::validate_token;
+use spacetimedb_client_api::auth::{validate_token, SpacetimeIdentityClaims};
 use spacetimedb_client_api::routes::database;
 use spacetimedb_client_api::routes::database::{SqlParams, SqlQueryParams};
 use spacetimedb_client_api::{ControlStateReadAccess, ControlStateWriteAccess, NodeDelegate};
@@ -31,7 +31,7 @@ use spacetimedb_client_api_messages::name::DatabaseName;
 use spacetimedb_lib::sats::satn::{PsqlClient, TypedSerializer};
 use spacetimedb_lib::sats::{satn, Serialize, Typespace};
 use spacetimedb_lib::version::spacetimedb_lib_version;
-use spacetimedb_lib::{Identity, ProductValue};
+use spacetimedb_lib::ProductValue;
 use thiserror::Error;
 use tokio::net::TcpListener;
 use tokio::sync::{Mutex, Notify};
@@ -65,7 +65,7 @@ impl From<PgError> for PgWireError {
 #[derive(Clone)]
 struct Metadata {
     database: String,
-    caller_identity: Identity,
+    claims: SpacetimeIdentityClaims,
 }
 
 pub(crate) fn to_rows(
@@ -161,7 +161,8 @@ impl<T: ControlStateReadAccess + ControlStateWriteAccess + NodeDelegate + Clone>
                 self.ctx.clone(),
                 db,
                 SqlQueryParams { confirmed: true },
-                params.caller_identity,
+                &params.claims,
+                None,
                 query.to_string(),
             )
             .await,
@@ -261,8 +262,8 @@ impl<T: Sync + Send + ControlStateReadAccess + ControlStateWriteAccess + NodeDel
                     }
                 };
 
-                let caller_identity = match validate_token(&self.ctx, &pwd.password).await {
-                    Ok(claims) => claims.identity,
+                let claims = match validate_token(&self.ctx, &pwd.password).await {
+                    Ok(claims) => claims,
                     Err(err) => {
                         log::error!(
                             "PG: Authentication failed for identity `{}` on database {database}: {err}",
@@ -273,12 +274,12 @@ impl<T: Sync + Send + ControlStateReadAccess + ControlStateWriteAccess + NodeDel
                     }
                 };
 
-                log::info!("PG: Connected to database: {database} using identity `{caller_identity}`");
+                log::info!(
+                    "PG: Connected to database: {database} using identity `{}`",
+                    claims.identity
+                );
 
-                let metadata = Metadata {
-                    database,
-                    caller_identity,
-                };
+                let metadata = Metadata { database, claims };
                 self.cached.lock().await.clone_from(&Some(metadata));
                 finish_authentication(client, &self.parameter_provider).await?;
             }
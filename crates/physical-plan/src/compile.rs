@@ -9,6 +9,7 @@ use crate::plan::{
 
 use spacetimedb_expr::expr::{Expr, FieldProject, LeftDeepJoin, ProjectList, ProjectName, RelExpr, Relvar};
 use spacetimedb_expr::statement::DML;
+use spacetimedb_lib::full_text;
 
 pub trait VarLabel {
     fn label(&mut self, name: &str) -> Label;
@@ -24,6 +25,10 @@ fn compile_expr(expr: Expr, var: &mut impl VarLabel) -> PhysicalExpr {
         }
         Expr::Value(v, _) => PhysicalExpr::Value(v),
         Expr::Field(proj) => PhysicalExpr::Field(compile_field_project(var, proj)),
+        Expr::Path(expr, path, _) => PhysicalExpr::Path(Box::new(compile_expr(*expr, var)), path),
+        Expr::Match(expr, query) => {
+            PhysicalExpr::Match(Box::new(compile_expr(*expr, var)), full_text::tokenize(&query))
+        }
     }
 }
 
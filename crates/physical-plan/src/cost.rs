@@ -0,0 +1,435 @@
+//! Cost-based join ordering.
+//!
+//! The rewrite rules in [crate::rules] order joins and choose join algorithms using heuristics alone.
+//! When [Statistics] are available for the tables in a join,
+//! the optimizer instead enumerates the left deep join orders,
+//! estimates the cost of each using the number of rows in each table,
+//! and the number of distinct keys in each index,
+//! and chooses between an index join and a hash join for each step.
+use std::{ops::Bound, sync::Arc};
+
+use spacetimedb_primitives::{ColId, ColList, IndexId, TableId};
+use spacetimedb_schema::schema::TableSchema;
+use spacetimedb_sql_parser::ast::{BinOp, LogOp};
+
+use crate::plan::{HashJoin, IxJoin, IxScan, Label, PhysicalExpr, PhysicalPlan, Sarg, Semi, TableScan, TupleField};
+
+/// Table and index statistics used to estimate the cost of a plan
+pub trait Statistics {
+    /// The number of rows in a table
+    fn row_count(&self, table_id: TableId) -> Option<u64>;
+
+    /// The number of distinct values of `cols` in a table.
+    /// Only available if there is an index on `cols`.
+    fn num_distinct_values(&self, table_id: TableId, cols: &ColList) -> Option<u64>;
+}
+
+/// No statistics are available,
+/// so the optimizer falls back to its heuristic join rules.
+pub struct NoStatistics;
+
+impl Statistics for NoStatistics {
+    fn row_count(&self, _: TableId) -> Option<u64> {
+        None
+    }
+
+    fn num_distinct_values(&self, _: TableId, _: &ColList) -> Option<u64> {
+        None
+    }
+}
+
+/// The selectivity of an equality predicate on a column without an index
+const EQ_SELECTIVITY: f64 = 0.1;
+
+/// The selectivity of a range predicate
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// Inserting a row into a hash table costs more than probing it
+const HASH_BUILD_COST: f64 = 2.0;
+
+/// The number of join orders grows exponentially with the number of relations.
+/// Beyond this many, we fall back to the heuristic join rules.
+const MAX_JOIN_RELATIONS: usize = 10;
+
+/// Choose a join order for the hash joins in `plan` based on their estimated cost.
+///
+/// Returns [None] if `plan` doesn't join any tables,
+/// or if it can't be costed, because:
+///
+/// 1. Statistics are missing for one of its tables
+/// 2. It reads a delta table
+/// 3. It has a join that isn't an equijoin of base tables
+///
+/// in which case the heuristic join rules should be applied instead.
+pub(crate) fn reorder_joins(plan: &PhysicalPlan, stats: &impl Statistics) -> Option<PhysicalPlan> {
+    match plan {
+        PhysicalPlan::Filter(input, expr) => {
+            reorder_joins(input, stats).map(|input| PhysicalPlan::Filter(Box::new(input), expr.clone()))
+        }
+        PhysicalPlan::HashJoin(_, Semi::All) => JoinGraph::new(plan, stats)?.best_plan(),
+        _ => None,
+    }
+}
+
+/// A relation in a join graph, i.e. a table scan with optional filters
+struct Relation {
+    plan: PhysicalPlan,
+    schema: Arc<TableSchema>,
+    label: Label,
+    /// The number of rows in the table
+    table_rows: f64,
+    /// The estimated number of rows after applying any filters
+    rows: f64,
+    /// The estimated cost of scanning this relation
+    cost: f64,
+    /// Can rows be fetched from this relation using an index join?
+    /// If so, these filters must be applied after the join.
+    probe: Option<Option<PhysicalExpr>>,
+}
+
+impl Relation {
+    fn new(plan: &PhysicalPlan, stats: &impl Statistics) -> Option<Self> {
+        match plan {
+            PhysicalPlan::TableScan(
+                TableScan {
+                    schema,
+                    limit: None,
+                    delta: None,
+                },
+                label,
+            ) => {
+                let rows = stats.row_count(schema.table_id)? as f64;
+                Some(Self {
+                    plan: plan.clone(),
+                    schema: schema.clone(),
+                    label: *label,
+                    table_rows: rows,
+                    rows,
+                    cost: rows,
+                    probe: Some(None),
+                })
+            }
+            PhysicalPlan::IxScan(
+                IxScan {
+                    schema,
+                    limit: None,
+                    delta: None,
+                    prefix,
+                    arg,
+                    ..
+                },
+                label,
+            ) => {
+                let table_rows = stats.row_count(schema.table_id)? as f64;
+                let mut cols = prefix.iter().map(|(col, _)| *col).collect::<Vec<_>>();
+                let selectivity = match arg {
+                    Sarg::Eq(col, _) => {
+                        cols.push(*col);
+                        eq_selectivity(stats, schema.table_id, &cols)
+                    }
+                    Sarg::Range(..) if cols.is_empty() => RANGE_SELECTIVITY,
+                    Sarg::Range(..) => eq_selectivity(stats, schema.table_id, &cols) * RANGE_SELECTIVITY,
+                    // We don't keep statistics for computed keys
+                    Sarg::Key(Bound::Included(lower), Bound::Included(upper)) if lower == upper => EQ_SELECTIVITY,
+                    Sarg::Key(..) => RANGE_SELECTIVITY,
+                };
+                let rows = table_rows * selectivity;
+                Some(Self {
+                    plan: plan.clone(),
+                    schema: schema.clone(),
+                    label: *label,
+                    table_rows,
+                    rows,
+                    cost: rows + probe_cost(table_rows),
+                    probe: None,
+                })
+            }
+            PhysicalPlan::Filter(input, expr) => {
+                let input = Self::new(input, stats)?;
+                Some(Self {
+                    plan: plan.clone(),
+                    rows: input.rows * selectivity(stats, &input.schema, expr),
+                    probe: input.probe.map(|_| Some(expr.clone())),
+                    ..input
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The estimated number of distinct values in a column of this relation
+    fn ndv(&self, stats: &impl Statistics, col: ColId) -> f64 {
+        stats
+            .num_distinct_values(self.schema.table_id, &ColList::from(col))
+            .map_or(self.table_rows, |n| n as f64)
+            .min(self.rows)
+            .max(1.0)
+    }
+
+    /// Returns the single column index on `col` if this relation can be the rhs of an index join
+    fn index_on(&self, col: ColId) -> Option<IndexId> {
+        self.probe.as_ref()?;
+        self.schema.indexes.iter().find_map(|ix| {
+            ix.index_algorithm
+                .columns()?
+                .as_singleton()
+                .filter(|col_id| *col_id == col)
+                .map(|_| ix.index_id)
+        })
+    }
+}
+
+/// An equijoin condition between two relations
+struct Edge {
+    /// The fields being compared, with the index of the relation for each
+    fields: [(usize, TupleField); 2],
+    /// The estimated number of distinct values for each field
+    ndv: [f64; 2],
+}
+
+impl Edge {
+    /// Orient this edge so that the field from relation `rhs` is second
+    fn oriented(&self, rhs: usize) -> (&(usize, TupleField), &(usize, TupleField), f64, f64) {
+        let [a, b] = &self.fields;
+        if b.0 == rhs {
+            (a, b, self.ndv[0], self.ndv[1])
+        } else {
+            (b, a, self.ndv[1], self.ndv[0])
+        }
+    }
+}
+
+/// How a relation is joined to the relations before it
+#[derive(Clone, Copy)]
+enum JoinMethod {
+    Hash,
+    Index(IndexId, ColId),
+}
+
+/// A left deep join order with its estimated cost
+#[derive(Clone)]
+struct JoinOrder {
+    cost: f64,
+    rows: f64,
+    /// The relation on the far left
+    first: usize,
+    /// The relation, edge, and method for each join
+    joins: Vec<(usize, usize, JoinMethod)>,
+}
+
+/// A tree of equijoins flattened into its relations and join conditions
+struct JoinGraph {
+    relations: Vec<Relation>,
+    edges: Vec<Edge>,
+}
+
+impl JoinGraph {
+    fn new(plan: &PhysicalPlan, stats: &impl Statistics) -> Option<Self> {
+        fn flatten(
+            plan: &PhysicalPlan,
+            stats: &impl Statistics,
+            relations: &mut Vec<Relation>,
+            conditions: &mut Vec<(TupleField, TupleField)>,
+        ) -> Option<()> {
+            match plan {
+                PhysicalPlan::HashJoin(
+                    HashJoin {
+                        lhs,
+                        rhs,
+                        lhs_field,
+                        rhs_field,
+                        ..
+                    },
+                    Semi::All,
+                ) => {
+                    flatten(lhs, stats, relations, conditions)?;
+                    flatten(rhs, stats, relations, conditions)?;
+                    conditions.push((lhs_field.clone(), rhs_field.clone()));
+                    Some(())
+                }
+                _ => {
+                    relations.push(Relation::new(plan, stats)?);
+                    Some(())
+                }
+            }
+        }
+
+        let mut relations = vec![];
+        let mut conditions = vec![];
+        flatten(plan, stats, &mut relations, &mut conditions)?;
+
+        // Without any rows, the statistics don't tell us anything
+        if relations.len() > MAX_JOIN_RELATIONS || relations.iter().all(|rel| rel.table_rows == 0.0) {
+            return None;
+        }
+
+        let relation = |field: &TupleField| relations.iter().position(|rel| rel.label == field.label);
+        let edges = conditions
+            .into_iter()
+            .map(|(a, b)| {
+                let (i, j) = (relation(&a)?, relation(&b)?);
+                let ndv = [
+                    relations[i].ndv(stats, a.field_pos.into()),
+                    relations[j].ndv(stats, b.field_pos.into()),
+                ];
+                Some(Edge {
+                    fields: [(i, a), (j, b)],
+                    ndv,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { relations, edges })
+    }
+
+    /// Find the cheapest left deep join order that avoids cross products,
+    /// using dynamic programming over the subsets of relations.
+    fn best_plan(self) -> Option<PhysicalPlan> {
+        let n = self.relations.len();
+        let mut best: Vec<Option<JoinOrder>> = vec![None; 1 << n];
+
+        for (i, rel) in self.relations.iter().enumerate() {
+            best[1 << i] = Some(JoinOrder {
+                cost: rel.cost,
+                rows: rel.rows,
+                first: i,
+                joins: vec![],
+            });
+        }
+
+        for set in 1..(1usize << n) {
+            let Some(order) = best[set].clone() else {
+                continue;
+            };
+            for (j, rel) in self.relations.iter().enumerate() {
+                if set & (1 << j) != 0 {
+                    continue;
+                }
+                let in_set = |i: usize| set & (1 << i) != 0;
+                // Because the join graph is a tree,
+                // at most one edge connects the new relation to the set.
+                let Some((k, edge)) = self.edges.iter().enumerate().find(|(_, edge)| {
+                    let [(a, _), (b, _)] = &edge.fields;
+                    (*a == j && in_set(*b)) || (*b == j && in_set(*a))
+                }) else {
+                    continue;
+                };
+
+                let ((_, _), (_, rhs_field), lhs_ndv, rhs_ndv) = edge.oriented(j);
+                let lhs_ndv = lhs_ndv.min(order.rows).max(1.0);
+                let rows = order.rows * rel.rows / lhs_ndv.max(rhs_ndv);
+
+                // Build a hash table over the rhs and probe it with each lhs row
+                let hash_cost = rel.cost + HASH_BUILD_COST * rel.rows + order.rows + rows;
+                let mut join = (hash_cost, JoinMethod::Hash);
+
+                // Or probe an index on the rhs with each lhs row
+                let rhs_col = ColId::from(rhs_field.field_pos);
+                if let Some(index_id) = rel.index_on(rhs_col) {
+                    let unfiltered = order.rows * rel.table_rows / lhs_ndv.max(rhs_ndv);
+                    let index_cost = order.rows * probe_cost(rel.table_rows) + unfiltered;
+                    if index_cost <= hash_cost {
+                        join = (index_cost, JoinMethod::Index(index_id, rhs_col));
+                    }
+                }
+
+                let (cost, method) = join;
+                let cost = order.cost + cost;
+                let next = set | (1 << j);
+                if best[next].as_ref().is_none_or(|best| cost < best.cost) {
+                    let mut joins = order.joins.clone();
+                    joins.push((j, k, method));
+                    best[next] = Some(JoinOrder {
+                        cost,
+                        rows,
+                        first: order.first,
+                        joins,
+                    });
+                }
+            }
+        }
+
+        let order = best.pop()??;
+        Some(self.build(order))
+    }
+
+    fn build(self, order: JoinOrder) -> PhysicalPlan {
+        let mut plan = self.relations[order.first].plan.clone();
+        for (j, k, method) in order.joins {
+            let rel = &self.relations[j];
+            let ((_, lhs_field), (_, rhs_field), ..) = self.edges[k].oriented(j);
+            plan = match method {
+                JoinMethod::Hash => PhysicalPlan::HashJoin(
+                    HashJoin {
+                        lhs: Box::new(plan),
+                        rhs: Box::new(rel.plan.clone()),
+                        lhs_field: lhs_field.clone(),
+                        rhs_field: rhs_field.clone(),
+                        unique: false,
+                    },
+                    Semi::All,
+                ),
+                JoinMethod::Index(rhs_index, rhs_field) => {
+                    let join = PhysicalPlan::IxJoin(
+                        IxJoin {
+                            lhs: Box::new(plan),
+                            rhs: rel.schema.clone(),
+                            rhs_label: rel.label,
+                            rhs_index,
+                            rhs_field,
+                            unique: false,
+                            lhs_field: lhs_field.clone(),
+                            rhs_delta: None,
+                        },
+                        Semi::All,
+                    );
+                    // An index join reads the rhs table directly,
+                    // so its filters are applied to the joined rows instead.
+                    match &rel.probe {
+                        Some(Some(expr)) => PhysicalPlan::Filter(Box::new(join), expr.clone()),
+                        _ => join,
+                    }
+                }
+            };
+        }
+        plan
+    }
+}
+
+/// The estimated cost of probing an index on a table with this many rows
+fn probe_cost(rows: f64) -> f64 {
+    (rows + 2.0).log2()
+}
+
+/// The selectivity of an equality predicate on `cols`.
+/// Assumes a uniform distribution of values, which implies a selectivity of 1 / NDV.
+fn eq_selectivity(stats: &impl Statistics, table_id: TableId, cols: &[ColId]) -> f64 {
+    stats
+        .num_distinct_values(table_id, &ColList::from_iter(cols.iter().copied()))
+        .filter(|n| *n > 0)
+        .map_or(EQ_SELECTIVITY.powi(cols.len() as i32), |n| 1.0 / n as f64)
+}
+
+/// The estimated fraction of rows that satisfy a filter
+fn selectivity(stats: &impl Statistics, schema: &TableSchema, expr: &PhysicalExpr) -> f64 {
+    match expr {
+        PhysicalExpr::LogOp(LogOp::And, exprs) => exprs.iter().map(|expr| selectivity(stats, schema, expr)).product(),
+        PhysicalExpr::LogOp(LogOp::Or, exprs) => {
+            1.0 - exprs
+                .iter()
+                .map(|expr| 1.0 - selectivity(stats, schema, expr))
+                .product::<f64>()
+        }
+        PhysicalExpr::BinOp(BinOp::Eq, a, b) => match (&**a, &**b) {
+            (PhysicalExpr::Field(TupleField { field_pos, .. }), PhysicalExpr::Value(_)) => {
+                eq_selectivity(stats, schema.table_id, &[ColId::from(*field_pos)])
+            }
+            _ => EQ_SELECTIVITY,
+        },
+        PhysicalExpr::BinOp(BinOp::Ne, ..) => 1.0 - EQ_SELECTIVITY,
+        PhysicalExpr::BinOp(..) => RANGE_SELECTIVITY,
+        // Each token of a full-text query is like an equality predicate
+        PhysicalExpr::Match(_, terms) => EQ_SELECTIVITY.powi(terms.len() as i32),
+        PhysicalExpr::Value(_) | PhysicalExpr::Field(_) | PhysicalExpr::Path(..) => 1.0,
+    }
+}
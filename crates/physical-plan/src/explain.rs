@@ -0,0 +1,380 @@
+//! Rendering of optimized plans for `EXPLAIN`
+
+use std::{ops::Bound, sync::Arc};
+
+use spacetimedb_expr::expr::AggType;
+use spacetimedb_lib::{sats::satn::Satn, AlgebraicType, AlgebraicValue};
+use spacetimedb_primitives::{ColId, IndexId};
+use spacetimedb_schema::schema::TableSchema;
+
+use crate::plan::{
+    HashJoin, IxJoin, IxScan, Label, PhysicalExpr, PhysicalPlan, ProjectListPlan, ProjectPlan, Sarg, Semi, TableScan,
+    TupleField,
+};
+
+/// Render `plan` as text for `EXPLAIN`, one line per operator.
+///
+/// `annotate` is called once for each [PhysicalPlan] operator,
+/// with its position in the order in which [ProjectListPlan::plan_iter] and [PhysicalPlan::visit] walk the plan.
+/// The text it returns, if any, is appended to the operator's line.
+/// `EXPLAIN ANALYZE` uses it to report row counts and timings.
+pub fn explain(plan: &ProjectListPlan, annotate: impl FnMut(usize) -> Option<String>) -> Vec<String> {
+    let mut labels: Vec<(Label, Arc<TableSchema>)> = vec![];
+    for plan in plan.plan_iter() {
+        plan.visit(&mut |op| match op {
+            PhysicalPlan::TableScan(TableScan { schema, .. }, label)
+            | PhysicalPlan::IxScan(IxScan { schema, .. }, label)
+            | PhysicalPlan::IxJoin(
+                IxJoin {
+                    rhs: schema,
+                    rhs_label: label,
+                    ..
+                },
+                _,
+            ) => {
+                if !labels.iter().any(|(l, _)| l == label) {
+                    labels.push((*label, schema.clone()));
+                }
+            }
+            _ => {}
+        });
+    }
+    // Disambiguate self joins by suffixing the table name with the relvar label
+    let relvars = labels
+        .iter()
+        .map(|(label, schema)| {
+            let name = &schema.table_name;
+            let is_self_join = labels.iter().filter(|(_, s)| &s.table_name == name).count() > 1;
+            let name = if is_self_join {
+                format!("{name}#{}", label.0)
+            } else {
+                name.to_string()
+            };
+            (*label, schema.clone(), name)
+        })
+        .collect();
+
+    let mut explainer = Explainer {
+        relvars,
+        annotate,
+        next_op: 0,
+        lines: vec![],
+    };
+    explainer.project_list(plan, 0);
+    explainer.lines
+}
+
+struct Explainer<F> {
+    /// The label, schema and display name of each relvar in the plan
+    relvars: Vec<(Label, Arc<TableSchema>, String)>,
+    annotate: F,
+    /// The position of the next [PhysicalPlan] operator to be rendered
+    next_op: usize,
+    lines: Vec<String>,
+}
+
+impl<F: FnMut(usize) -> Option<String>> Explainer<F> {
+    fn line(&mut self, depth: usize, text: String, annotation: Option<String>) {
+        let indent = match depth {
+            0 => String::new(),
+            _ => format!("{}-> ", "  ".repeat(depth - 1)),
+        };
+        self.lines.push(match annotation {
+            Some(annotation) => format!("{indent}{text}  ({annotation})"),
+            None => format!("{indent}{text}"),
+        });
+    }
+
+    /// Plans that consist of multiple fragments return the union of their rows
+    fn union(&mut self, n: usize, depth: usize) -> usize {
+        if n > 1 {
+            self.line(depth, "Union All".to_owned(), None);
+            depth + 1
+        } else {
+            depth
+        }
+    }
+
+    fn project_list(&mut self, plan: &ProjectListPlan, depth: usize) {
+        match plan {
+            ProjectListPlan::Name(plans) => {
+                let depth = self.union(plans.len(), depth);
+                for plan in plans {
+                    match plan {
+                        ProjectPlan::None(plan) => self.plan(plan, depth),
+                        ProjectPlan::Name(plan, label, _) => {
+                            self.line(depth, format!("Project: {}.*", self.relvar(label)), None);
+                            self.plan(plan, depth + 1);
+                        }
+                    }
+                }
+            }
+            ProjectListPlan::List(plans, fields) => {
+                let fields = fields.iter().map(|field| self.field(field)).collect::<Vec<_>>();
+                self.line(depth, format!("Project: {}", fields.join(", ")), None);
+                let depth = self.union(plans.len(), depth + 1);
+                for plan in plans {
+                    self.plan(plan, depth);
+                }
+            }
+            ProjectListPlan::Limit(plan, n) => {
+                self.line(depth, format!("Limit: {n}"), None);
+                self.project_list(plan, depth + 1);
+            }
+            ProjectListPlan::Agg(plans, AggType::Count) => {
+                self.line(depth, "Aggregate: count(*)".to_owned(), None);
+                let depth = self.union(plans.len(), depth + 1);
+                for plan in plans {
+                    self.plan(plan, depth);
+                }
+            }
+        }
+    }
+
+    fn plan(&mut self, plan: &PhysicalPlan, depth: usize) {
+        let annotation = (self.annotate)(self.next_op);
+        self.next_op += 1;
+
+        match plan {
+            PhysicalPlan::TableScan(TableScan { limit, delta, .. }, label) => {
+                let mut text = format!("Seq Scan on {}", self.relvar(label));
+                if let Some(delta) = delta {
+                    text.push_str(&format!(" (delta {delta:?})"));
+                }
+                if let Some(n) = limit {
+                    text.push_str(&format!(" limit {n}"));
+                }
+                self.line(depth, text, annotation);
+            }
+            PhysicalPlan::IxScan(
+                IxScan {
+                    schema,
+                    limit,
+                    delta,
+                    index_id,
+                    prefix,
+                    arg,
+                },
+                label,
+            ) => {
+                let relvar = self.relvar(label);
+                let column = |col: &ColId| format!("{relvar}.{}", column_name(schema, *col));
+                let mut conds = prefix
+                    .iter()
+                    .map(|(col, value)| format!("{} = {}", column(col), literal(value)))
+                    .collect::<Vec<_>>();
+                match arg {
+                    Sarg::Eq(col, value) => conds.push(format!("{} = {}", column(col), literal(value))),
+                    Sarg::Range(col, lower, upper) => push_bounds(&mut conds, &column(col), lower, upper),
+                    Sarg::Key(Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+                        conds.push(format!("{} = {}", key_name(schema, &relvar, *index_id), literal(lower)))
+                    }
+                    Sarg::Key(lower, upper) => {
+                        push_bounds(&mut conds, &key_name(schema, &relvar, *index_id), lower, upper)
+                    }
+                }
+                let mut text = format!("Index Scan using {} on {relvar}", index_name(schema, *index_id));
+                // A scan of a whole (partial) index has no conditions.
+                if !conds.is_empty() {
+                    text.push_str(&format!(": {}", conds.join(" AND ")));
+                }
+                if let Some(delta) = delta {
+                    text.push_str(&format!(" (delta {delta:?})"));
+                }
+                if let Some(n) = limit {
+                    text.push_str(&format!(" limit {n}"));
+                }
+                self.line(depth, text, annotation);
+            }
+            PhysicalPlan::IxJoin(
+                IxJoin {
+                    lhs,
+                    rhs,
+                    rhs_label,
+                    rhs_index,
+                    rhs_field,
+                    unique,
+                    lhs_field,
+                    rhs_delta,
+                },
+                semi,
+            ) => {
+                let relvar = self.relvar(rhs_label);
+                let mut text = format!(
+                    "Index Join using {} on {relvar}: {relvar}.{} = {}",
+                    index_name(rhs, *rhs_index),
+                    column_name(rhs, *rhs_field),
+                    self.field(lhs_field),
+                );
+                if let Some(delta) = rhs_delta {
+                    text.push_str(&format!(" (delta {delta:?})"));
+                }
+                text.push_str(&join_properties(*unique, semi));
+                self.line(depth, text, annotation);
+                self.plan(lhs, depth + 1);
+            }
+            PhysicalPlan::HashJoin(
+                HashJoin {
+                    lhs,
+                    rhs,
+                    lhs_field,
+                    rhs_field,
+                    unique,
+                },
+                semi,
+            ) => {
+                let mut text = format!("Hash Join: {} = {}", self.field(lhs_field), self.field(rhs_field));
+                text.push_str(&join_properties(*unique, semi));
+                self.line(depth, text, annotation);
+                self.plan(lhs, depth + 1);
+                self.plan(rhs, depth + 1);
+            }
+            PhysicalPlan::NLJoin(lhs, rhs) => {
+                self.line(depth, "Nested Loop Join".to_owned(), annotation);
+                self.plan(lhs, depth + 1);
+                self.plan(rhs, depth + 1);
+            }
+            PhysicalPlan::Filter(input, expr) => {
+                let text = format!("Filter: {}", self.expr(expr));
+                self.line(depth, text, annotation);
+                self.plan(input, depth + 1);
+            }
+        }
+    }
+
+    fn expr(&self, expr: &PhysicalExpr) -> String {
+        match expr {
+            PhysicalExpr::LogOp(op, exprs) => exprs
+                .iter()
+                .map(|expr| match expr {
+                    PhysicalExpr::LogOp(..) => format!("({})", self.expr(expr)),
+                    _ => self.expr(expr),
+                })
+                .collect::<Vec<_>>()
+                .join(&format!(" {op} ")),
+            PhysicalExpr::BinOp(op, a, b) => format!("{} {op} {}", self.expr(a), self.expr(b)),
+            PhysicalExpr::Value(value) => literal(value),
+            PhysicalExpr::Field(field) => self.field(field),
+            PhysicalExpr::Path(expr, path) => {
+                let ty = match &**expr {
+                    PhysicalExpr::Field(field) => self
+                        .relvars
+                        .iter()
+                        .find(|(label, _, _)| *label == field.label)
+                        .and_then(|(_, schema, _)| schema.get_column(field.field_pos))
+                        .map(|column| &column.col_type),
+                    _ => None,
+                };
+                let mut text = self.expr(expr);
+                push_path(&mut text, ty, path.iter().copied());
+                text
+            }
+            PhysicalExpr::Match(expr, terms) => format!("match({}, '{}')", self.expr(expr), terms.join(" ")),
+        }
+    }
+
+    fn field(&self, field: &TupleField) -> String {
+        match self.relvars.iter().find(|(label, _, _)| *label == field.label) {
+            Some((_, schema, name)) => format!("{name}.{}", column_name(schema, field.field_pos.into())),
+            None => format!("#{}.{}", field.label.0, field.field_pos),
+        }
+    }
+
+    fn relvar(&self, label: &Label) -> String {
+        self.relvars
+            .iter()
+            .find(|(l, _, _)| l == label)
+            .map(|(_, _, name)| name.clone())
+            .unwrap_or_else(|| format!("#{}", label.0))
+    }
+}
+
+fn column_name(schema: &TableSchema, col: ColId) -> String {
+    schema
+        .get_column(col.idx())
+        .map(|column| column.col_name.to_string())
+        .unwrap_or_else(|| col.to_string())
+}
+
+/// Append `.field` for each step of `path` into a value of type `ty`,
+/// falling back to positions where field names are unknown.
+fn push_path(text: &mut String, mut ty: Option<&AlgebraicType>, path: impl Iterator<Item = usize>) {
+    for i in path {
+        let field = ty.and_then(|ty| ty.as_product()).and_then(|ty| ty.elements.get(i));
+        match field.and_then(|field| field.name()) {
+            Some(name) => text.push_str(&format!(".{name}")),
+            None => text.push_str(&format!(".{i}")),
+        }
+        ty = field.map(|field| &field.algebraic_type);
+    }
+}
+
+/// Render the key expressions of an expression or full-text index
+fn key_name(schema: &TableSchema, relvar: &str, index_id: IndexId) -> String {
+    let index = schema.indexes.iter().find(|index| index.index_id == index_id);
+    // The keys of a full-text index are the tokens of a column
+    if let Some(col) = index.and_then(|index| index.index_algorithm.full_text()) {
+        return format!("tokens({relvar}.{})", column_name(schema, col));
+    }
+    let keys = index
+        .and_then(|index| index.index_algorithm.key_exprs())
+        .unwrap_or_default()
+        .iter()
+        .map(|key| {
+            let mut text = format!("{relvar}.{}", column_name(schema, key.column));
+            let ty = schema.get_column(key.column.idx()).map(|column| &column.col_type);
+            push_path(&mut text, ty, key.fields.iter().map(|&i| i as usize));
+            match key.function {
+                Some(function) => format!("{}({text})", function.name()),
+                None => text,
+            }
+        })
+        .collect::<Vec<_>>();
+    match &*keys {
+        [key] => key.clone(),
+        _ => format!("({})", keys.join(", ")),
+    }
+}
+
+fn push_bounds(conds: &mut Vec<String>, lhs: &str, lower: &Bound<AlgebraicValue>, upper: &Bound<AlgebraicValue>) {
+    match lower {
+        Bound::Included(value) => conds.push(format!("{lhs} >= {}", literal(value))),
+        Bound::Excluded(value) => conds.push(format!("{lhs} > {}", literal(value))),
+        Bound::Unbounded => {}
+    }
+    match upper {
+        Bound::Included(value) => conds.push(format!("{lhs} <= {}", literal(value))),
+        Bound::Excluded(value) => conds.push(format!("{lhs} < {}", literal(value))),
+        Bound::Unbounded => {}
+    }
+}
+
+fn index_name(schema: &TableSchema, index_id: IndexId) -> String {
+    schema
+        .indexes
+        .iter()
+        .find(|index| index.index_id == index_id)
+        .map(|index| index.index_name.to_string())
+        .unwrap_or_else(|| index_id.to_string())
+}
+
+fn literal(value: &AlgebraicValue) -> String {
+    value.to_satn()
+}
+
+fn join_properties(unique: bool, semi: &Semi) -> String {
+    let mut props = vec![];
+    if unique {
+        props.push("unique");
+    }
+    match semi {
+        Semi::Lhs => props.push("semijoin returning lhs"),
+        Semi::Rhs => props.push("semijoin returning rhs"),
+        Semi::All => {}
+    }
+    if props.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", props.join(", "))
+    }
+}
@@ -1,4 +1,6 @@
 pub mod compile;
+pub mod cost;
 pub mod dml;
+pub mod explain;
 pub mod plan;
 pub mod rules;
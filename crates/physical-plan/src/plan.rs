@@ -8,16 +8,17 @@ use anyhow::{bail, Result};
 use derive_more::From;
 use either::Either;
 use spacetimedb_expr::{expr::AggType, StatementSource};
-use spacetimedb_lib::{query::Delta, sats::size_of::SizeOf, AlgebraicValue, ProductValue};
+use spacetimedb_lib::{full_text, query::Delta, sats::size_of::SizeOf, AlgebraicValue, ProductValue};
 use spacetimedb_primitives::{ColId, ColSet, IndexId, TableId};
 use spacetimedb_schema::schema::{IndexSchema, TableSchema};
 use spacetimedb_sql_parser::ast::{BinOp, LogOp};
 use spacetimedb_table::table::RowRef;
 
+use crate::cost::{reorder_joins, NoStatistics, Statistics};
 use crate::rules::{
-    ComputePositions, HashToIxJoin, IxScanAnd, IxScanEq, IxScanEq2Col, IxScanEq3Col, PullFilterAboveHashJoin,
-    PushConstAnd, PushConstEq, PushLimit, ReorderDeltaJoinRhs, ReorderHashJoin, RewriteRule, UniqueHashJoinRule,
-    UniqueIxJoinRule,
+    ComputePositions, HashToIxJoin, IxScanAnd, IxScanEq, IxScanEq2Col, IxScanEq3Col, IxScanFullText, IxScanKeyEq,
+    IxScanPartial, PullFilterAboveHashJoin, PushConstAnd, PushConstEq, PushLimit, ReorderDeltaJoinRhs, ReorderHashJoin,
+    RewriteRule, UniqueHashJoinRule, UniqueIxJoinRule,
 };
 
 /// Table aliases are replaced with labels in the physical plan
@@ -70,10 +71,15 @@ impl DerefMut for ProjectPlan {
 
 impl ProjectPlan {
     pub fn optimize(self) -> Result<Self> {
+        self.optimize_with(&NoStatistics)
+    }
+
+    /// Optimize using table and index statistics to choose a join order
+    pub fn optimize_with(self, stats: &impl Statistics) -> Result<Self> {
         match self {
-            Self::None(plan) => Ok(Self::None(plan.optimize(vec![])?)),
+            Self::None(plan) => Ok(Self::None(plan.optimize_with(vec![], stats)?)),
             Self::Name(plan, label, _) => {
-                let plan = plan.optimize(vec![label])?;
+                let plan = plan.optimize_with(vec![label], stats)?;
                 let n = plan.nfields();
                 let pos = plan.position(&label);
                 Ok(match n {
@@ -114,7 +120,7 @@ impl ProjectPlan {
 /// Note that RLS takes a single expression and produces a list of expressions.
 /// Hence why these variants take lists rather than single expressions.
 /// See [spacetimedb_expr::ProjectList] for details.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ProjectListPlan {
     /// A plan that returns physical rows
     Name(Vec<ProjectPlan>),
@@ -128,12 +134,19 @@ pub enum ProjectListPlan {
 
 impl ProjectListPlan {
     pub fn optimize(self) -> Result<Self> {
+        self.optimize_with(&NoStatistics)
+    }
+
+    /// Optimize using table and index statistics to choose a join order
+    pub fn optimize_with(self, stats: &impl Statistics) -> Result<Self> {
         match self {
             Self::Name(plan) => Ok(Self::Name(
-                plan.into_iter().map(|plan| plan.optimize()).collect::<Result<_>>()?,
+                plan.into_iter()
+                    .map(|plan| plan.optimize_with(stats))
+                    .collect::<Result<_>>()?,
             )),
             Self::Limit(plan, n) => {
-                let mut limit = Self::Limit(Box::new(plan.optimize()?), n);
+                let mut limit = Self::Limit(Box::new(plan.optimize_with(stats)?), n);
                 // Merge a limit with a scan if possible
                 if PushLimit::matches(&limit).is_some() {
                     limit = PushLimit::rewrite(limit, ())?;
@@ -142,7 +155,7 @@ impl ProjectListPlan {
             }
             Self::Agg(plan, agg_type) => Ok(Self::Agg(
                 plan.into_iter()
-                    .map(|plan| plan.optimize(vec![]))
+                    .map(|plan| plan.optimize_with(vec![], stats))
                     .collect::<Result<_>>()?,
                 agg_type,
             )),
@@ -152,7 +165,7 @@ impl ProjectListPlan {
                     // Collect the names of the relvars
                     let labels = fields.iter().map(|field| field.label).collect();
                     // Optimize each plan
-                    let optimized_plan = plan.optimize(labels)?;
+                    let optimized_plan = plan.optimize_with(labels, stats)?;
                     // Compute the position of each relvar referenced in the projection
                     for TupleField { label, label_pos, .. } in &mut fields {
                         *label_pos = optimized_plan.position(label);
@@ -387,7 +400,15 @@ impl PhysicalPlan {
     /// 4. Determine index and semijoins
     /// 5. Compute positions for tuple labels
     pub fn optimize(self, reqs: Vec<Label>) -> Result<Self> {
-        let optimized = self
+        self.optimize_with(reqs, &NoStatistics)
+    }
+
+    /// Optimize a plan like [Self::optimize],
+    /// but if `stats` are available for the tables being joined,
+    /// choose the join order and the join algorithms based on their estimated cost.
+    /// See [crate::cost] for details.
+    pub fn optimize_with(self, reqs: Vec<Label>, stats: &impl Statistics) -> Result<Self> {
+        let plan = self
             .map(&Self::canonicalize)
             .apply_rec::<PushConstAnd>()?
             .apply_rec::<PushConstEq>()?
@@ -397,8 +418,16 @@ impl PhysicalPlan {
             .apply_rec::<IxScanEq2Col>()?
             .apply_rec::<IxScanEq>()?
             .apply_rec::<IxScanAnd>()?
-            .apply_rec::<ReorderHashJoin>()?
-            .apply_rec::<HashToIxJoin>()?
+            .apply_rec::<IxScanKeyEq>()?
+            .apply_rec::<IxScanPartial>()?
+            .apply_rec::<IxScanFullText>()?;
+
+        let plan = match reorder_joins(&plan, stats) {
+            Some(plan) => plan,
+            None => plan.apply_rec::<ReorderHashJoin>()?.apply_rec::<HashToIxJoin>()?,
+        };
+
+        let optimized = plan
             .apply_rec::<UniqueIxJoinRule>()?
             .apply_rec::<UniqueHashJoinRule>()?
             .introduce_semijoins(reqs)
@@ -515,7 +544,8 @@ impl PhysicalPlan {
             Self::Filter(input, expr) => {
                 let move_value_to_rhs = |expr| match expr {
                     PhysicalExpr::BinOp(op, value, expr)
-                        if matches!(&*value, PhysicalExpr::Value(_)) && matches!(&*expr, PhysicalExpr::Field(..)) =>
+                        if matches!(&*value, PhysicalExpr::Value(_))
+                            && matches!(&*expr, PhysicalExpr::Field(..) | PhysicalExpr::Path(..)) =>
                     {
                         match op {
                             BinOp::Eq => PhysicalExpr::BinOp(BinOp::Eq, expr, value),
@@ -810,7 +840,7 @@ impl PhysicalPlan {
             ) if alias == label => schema.indexes.iter().any(|IndexSchema { index_algorithm, .. }| {
                 index_algorithm
                     .columns()
-                    .as_singleton()
+                    .and_then(|cols| cols.as_singleton())
                     .is_some_and(|col_id| col_id.idx() == field)
             }),
             _ => false,
@@ -980,6 +1010,9 @@ pub struct IxScan {
 pub enum Sarg {
     Eq(ColId, AlgebraicValue),
     Range(ColId, Bound<AlgebraicValue>, Bound<AlgebraicValue>),
+    /// A range over the computed keys of an expression index.
+    /// Equality is a range whose bounds are the same inclusive value.
+    Key(Bound<AlgebraicValue>, Bound<AlgebraicValue>),
 }
 
 /// A join of two relations on a single equality condition.
@@ -1039,6 +1072,12 @@ pub enum PhysicalExpr {
     Value(AlgebraicValue),
     /// A field projection expression
     Field(TupleField),
+    /// A projection of a nested field,
+    /// given by its position in each enclosing product
+    Path(Box<PhysicalExpr>, Box<[usize]>),
+    /// A full-text search of a string,
+    /// given the tokens of the query, all of which it must contain
+    Match(Box<PhysicalExpr>, Vec<String>),
 }
 
 /// A trait for projecting values from a tuple.
@@ -1075,6 +1114,9 @@ impl PhysicalExpr {
                     expr.visit(f);
                 }
             }
+            Self::Path(expr, _) | Self::Match(expr, _) => {
+                expr.visit(f);
+            }
             _ => {}
         }
     }
@@ -1092,6 +1134,9 @@ impl PhysicalExpr {
                     expr.visit_mut(f);
                 }
             }
+            Self::Path(expr, _) | Self::Match(expr, _) => {
+                expr.visit_mut(f);
+            }
             _ => {}
         }
     }
@@ -1103,6 +1148,8 @@ impl PhysicalExpr {
             field @ Self::Field(..) => field,
             Self::BinOp(op, a, b) => Self::BinOp(op, Box::new(a.map(f)), Box::new(b.map(f))),
             Self::LogOp(op, exprs) => Self::LogOp(op, exprs.into_iter().map(|expr| expr.map(f)).collect()),
+            Self::Path(expr, path) => Self::Path(Box::new(expr.map(f)), path),
+            Self::Match(expr, terms) => Self::Match(Box::new(expr.map(f)), terms),
         }
     }
 
@@ -1160,6 +1207,21 @@ impl PhysicalExpr {
                 *bytes_scanned += value.size_of();
                 Cow::Owned(value)
             }
+            Self::Path(expr, path) => Cow::Owned(path.iter().fold(
+                expr.eval_with_metrics(row, bytes_scanned).into_owned(),
+                |value, &i| {
+                    value
+                        .into_product()
+                        .ok()
+                        .and_then(|product| product.elements.into_vec().into_iter().nth(i))
+                        .expect("path was type checked against the column type")
+                },
+            )),
+            Self::Match(expr, terms) => into(
+                expr.eval_with_metrics(row, bytes_scanned)
+                    .as_string()
+                    .is_some_and(|text| full_text::matches(text, terms)),
+            ),
             Self::Value(v) => Cow::Borrowed(v),
         }
     }
@@ -1179,6 +1241,8 @@ impl PhysicalExpr {
                     .collect(),
             ),
             Self::BinOp(op, a, b) => Self::BinOp(op, Box::new(a.flatten()), Box::new(b.flatten())),
+            Self::Path(expr, path) => Self::Path(Box::new(expr.flatten()), path),
+            Self::Match(expr, terms) => Self::Match(Box::new(expr.flatten()), terms),
             Self::Field(..) | Self::Value(..) => self,
         }
     }
@@ -1193,7 +1257,7 @@ pub struct PhysicalCtx<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{ops::Bound, sync::Arc};
 
     use pretty_assertions::assert_eq;
     use spacetimedb_expr::{
@@ -1206,15 +1270,19 @@ mod tests {
         identity::AuthCtx,
         AlgebraicType, AlgebraicValue,
     };
-    use spacetimedb_primitives::{ColId, ColList, ColSet, TableId};
+    use spacetimedb_primitives::{ColId, ColList, ColSet, IndexId, TableId};
     use spacetimedb_schema::{
-        def::{BTreeAlgorithm, ConstraintData, IndexAlgorithm, UniqueConstraintData},
+        def::{
+            BTreeAlgorithm, BTreeExprAlgorithm, ConstraintData, FullTextAlgorithm, IndexAlgorithm, IndexKeyExpr,
+            PartialBTreeAlgorithm, UniqueConstraintData,
+        },
         schema::{ColumnSchema, ConstraintSchema, IndexSchema, TableSchema},
     };
     use spacetimedb_sql_parser::ast::BinOp;
 
     use crate::{
         compile::{compile_select, compile_select_list},
+        cost::Statistics,
         plan::{HashJoin, IxJoin, IxScan, PhysicalPlan, ProjectListPlan, Sarg, Semi, TupleField},
     };
 
@@ -1369,6 +1437,245 @@ mod tests {
         };
     }
 
+    /// Index scan on a nested field with an expression index
+    #[test]
+    fn filter_nested_field_ix_scan() {
+        let t_id = TableId(1);
+
+        let mut t = schema(
+            t_id,
+            "t",
+            &[
+                ("id", AlgebraicType::U64),
+                (
+                    "position",
+                    AlgebraicType::product([("x", AlgebraicType::U32), ("y", AlgebraicType::U32)]),
+                ),
+            ],
+            &[&[0]],
+            &[&[0]],
+            Some(0),
+        );
+        t.indexes.push(IndexSchema {
+            table_id: t_id,
+            index_id: IndexId(1),
+            index_name: "t_position_x_idx_btree".into(),
+            index_algorithm: IndexAlgorithm::BTreeExpr(BTreeExprAlgorithm {
+                keys: [IndexKeyExpr {
+                    column: ColId(1),
+                    fields: [0].into(),
+                    function: None,
+                }]
+                .into(),
+            }),
+        });
+        let t = Arc::new(t);
+
+        let db = SchemaViewer {
+            schemas: vec![t.clone()],
+        };
+
+        let sql = "select * from t where t.position.x = 5";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::IxScan(
+                IxScan {
+                    index_id, prefix, arg, ..
+                },
+                _,
+            )) => {
+                assert_eq!(index_id, IndexId(1));
+                assert!(prefix.is_empty());
+                assert_eq!(
+                    arg,
+                    Sarg::Key(
+                        Bound::Included(AlgebraicValue::U32(5)),
+                        Bound::Included(AlgebraicValue::U32(5))
+                    )
+                );
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+
+        // The field `y` is not indexed
+        let sql = "select * from t where t.position.y = 5";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, PhysicalExpr::BinOp(BinOp::Eq, path, value))) => {
+                assert!(matches!(*path, PhysicalExpr::Path(_, ref path) if **path == [1]));
+                assert!(matches!(*value, PhysicalExpr::Value(AlgebraicValue::U32(5))));
+                assert!(matches!(*input, PhysicalPlan::TableScan(..)));
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+    }
+
+    /// Index scan with a partial index whose filter is implied by the query
+    #[test]
+    fn filter_partial_ix_scan() {
+        let t_id = TableId(1);
+
+        let mut t = schema(
+            t_id,
+            "t",
+            &[
+                ("id", AlgebraicType::U64),
+                ("status", AlgebraicType::String),
+                ("level", AlgebraicType::U32),
+            ],
+            &[&[0]],
+            &[&[0]],
+            Some(0),
+        );
+        t.indexes.push(IndexSchema {
+            table_id: t_id,
+            index_id: IndexId(1),
+            index_name: "t_level_idx_partial".into(),
+            index_algorithm: IndexAlgorithm::PartialBTree(PartialBTreeAlgorithm {
+                columns: ColId(2).into(),
+                filter: "status = 'active'".into(),
+            }),
+        });
+        let t = Arc::new(t);
+
+        let db = SchemaViewer {
+            schemas: vec![t.clone()],
+        };
+
+        let sql = "select * from t where status = 'active' and level = 5";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, PhysicalExpr::BinOp(BinOp::Eq, field, value))) => {
+                assert!(matches!(*field, PhysicalExpr::Field(TupleField { field_pos: 1, .. })));
+                assert!(matches!(*value, PhysicalExpr::Value(AlgebraicValue::String(ref s)) if &**s == "active"));
+                match *input {
+                    PhysicalPlan::IxScan(
+                        IxScan {
+                            index_id, prefix, arg, ..
+                        },
+                        _,
+                    ) => {
+                        assert_eq!(index_id, IndexId(1));
+                        assert!(prefix.is_empty());
+                        assert_eq!(arg, Sarg::Eq(ColId(2), AlgebraicValue::U32(5)));
+                    }
+                    plan => panic!("unexpected plan: {plan:#?}"),
+                }
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+
+        // Without equality on `level`, scan the whole index
+        let sql = "select * from t where status = 'active'";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, _)) => match *input {
+                PhysicalPlan::IxScan(IxScan { index_id, arg, .. }, _) => {
+                    assert_eq!(index_id, IndexId(1));
+                    assert_eq!(arg, Sarg::Range(ColId(2), Bound::Unbounded, Bound::Unbounded));
+                }
+                plan => panic!("unexpected plan: {plan:#?}"),
+            },
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+
+        // The filter of the index is not implied
+        let sql = "select * from t where status = 'idle' and level = 5";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, _)) => {
+                assert!(matches!(*input, PhysicalPlan::TableScan(..)));
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+    }
+
+    /// Index scan with a full-text index for the longest token of the query
+    #[test]
+    fn filter_full_text_ix_scan() {
+        let t_id = TableId(1);
+
+        let mut t = schema(
+            t_id,
+            "t",
+            &[("id", AlgebraicType::U64), ("name", AlgebraicType::String)],
+            &[&[0]],
+            &[&[0]],
+            Some(0),
+        );
+        t.indexes.push(IndexSchema {
+            table_id: t_id,
+            index_id: IndexId(1),
+            index_name: "t_name_idx_fulltext".into(),
+            index_algorithm: IndexAlgorithm::FullText(FullTextAlgorithm { column: ColId(1) }),
+        });
+        let t = Arc::new(t);
+
+        let db = SchemaViewer {
+            schemas: vec![t.clone()],
+        };
+
+        let sql = "select * from t where match(name, 'Red sword')";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, PhysicalExpr::Match(field, terms))) => {
+                assert!(matches!(*field, PhysicalExpr::Field(TupleField { field_pos: 1, .. })));
+                assert_eq!(terms, ["red", "sword"]);
+                match *input {
+                    PhysicalPlan::IxScan(
+                        IxScan {
+                            index_id, prefix, arg, ..
+                        },
+                        _,
+                    ) => {
+                        assert_eq!(index_id, IndexId(1));
+                        assert!(prefix.is_empty());
+                        assert_eq!(
+                            arg,
+                            Sarg::Key(
+                                Bound::Included(AlgebraicValue::String("sword".into())),
+                                Bound::Included(AlgebraicValue::String("sword".into()))
+                            )
+                        );
+                    }
+                    plan => panic!("unexpected plan: {plan:#?}"),
+                }
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+
+        // A query without tokens can't use the index
+        let sql = "select * from t where match(name, '--')";
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let pp = compile_select(lp).optimize().unwrap();
+
+        match pp {
+            ProjectPlan::None(PhysicalPlan::Filter(input, _)) => {
+                assert!(matches!(*input, PhysicalPlan::TableScan(..)));
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+    }
+
     /// Given the following operator notation:
     ///
     /// x:  join  
@@ -2052,4 +2359,205 @@ mod tests {
         assert!(plan.plan_iter().any(|plan| plan.has_filter()));
         assert!(plan.plan_iter().any(|plan| plan.has_table_scan(None)));
     }
+
+    #[test]
+    fn explain() {
+        let t_id = TableId(1);
+
+        let t = Arc::new(schema(
+            t_id,
+            "t",
+            &[("x", AlgebraicType::U8), ("y", AlgebraicType::U8)],
+            &[&[0]],
+            &[],
+            None,
+        ));
+
+        let db = SchemaViewer {
+            schemas: vec![t.clone()],
+        };
+
+        let stmt = parse_and_type_sql(
+            "explain select * from t where y = 1 limit 5",
+            &db,
+            &AuthCtx::for_testing(),
+        )
+        .unwrap();
+        let Statement::Explain(explain) = stmt else {
+            panic!("expected an EXPLAIN statement")
+        };
+        let plan = compile_select_list(explain.select).optimize().unwrap();
+
+        assert_eq!(
+            crate::explain::explain(&plan, |i| Some(format!("op {i}"))),
+            ["Limit: 5", "-> Filter: t.y = 1  (op 0)", "  -> Seq Scan on t  (op 1)"],
+        );
+        assert_eq!(
+            crate::explain::explain(&plan, |_| None),
+            ["Limit: 5", "-> Filter: t.y = 1", "  -> Seq Scan on t"],
+        );
+    }
+
+    /// Table and index statistics for testing the cost-based join rules
+    struct Stats {
+        rows: Vec<(TableId, u64)>,
+        ndv: Vec<(TableId, ColId, u64)>,
+    }
+
+    impl Statistics for Stats {
+        fn row_count(&self, table_id: TableId) -> Option<u64> {
+            self.rows.iter().find(|(id, _)| *id == table_id).map(|(_, n)| *n)
+        }
+
+        fn num_distinct_values(&self, table_id: TableId, cols: &ColList) -> Option<u64> {
+            self.ndv
+                .iter()
+                .find(|(id, col, _)| *id == table_id && cols.as_singleton() == Some(*col))
+                .map(|(.., n)| *n)
+        }
+    }
+
+    /// Given a large table `a` and a small table `b`,
+    /// both with an index on `id`:
+    ///
+    /// ```sql
+    /// select a.* from a join b on a.id = b.id
+    /// ```
+    ///
+    /// Without statistics, we probe the index on `b` once for each row in `a`.
+    /// With statistics, we scan `b` and probe the index on `a` instead.
+    #[test]
+    fn cost_based_index_join() {
+        let a_id = TableId(1);
+        let b_id = TableId(2);
+
+        let a = Arc::new(schema(
+            a_id,
+            "a",
+            &[("id", AlgebraicType::U64), ("x", AlgebraicType::U64)],
+            &[&[0]],
+            &[&[0]],
+            Some(0),
+        ));
+
+        let b = Arc::new(schema(
+            b_id,
+            "b",
+            &[("id", AlgebraicType::U64), ("y", AlgebraicType::U64)],
+            &[&[0]],
+            &[],
+            None,
+        ));
+
+        let db = SchemaViewer {
+            schemas: vec![a.clone(), b.clone()],
+        };
+
+        let stats = Stats {
+            rows: vec![(a_id, 1_000_000), (b_id, 10)],
+            ndv: vec![(a_id, 0.into(), 1_000_000), (b_id, 0.into(), 10)],
+        };
+
+        let sql = "select a.* from a join b on a.id = b.id";
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+
+        match compile_select(lp).optimize().unwrap() {
+            ProjectPlan::None(PhysicalPlan::IxJoin(IxJoin { lhs, rhs, .. }, Semi::Lhs)) => {
+                assert!(lhs.has_table_scan(None));
+                assert_eq!(rhs.table_id, b_id);
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        match compile_select(lp).optimize_with(&stats).unwrap() {
+            ProjectPlan::None(PhysicalPlan::IxJoin(
+                IxJoin {
+                    lhs, rhs, unique: true, ..
+                },
+                Semi::Rhs,
+            )) => {
+                assert!(
+                    matches!(&*lhs, PhysicalPlan::TableScan(TableScan { schema, .. }, _) if schema.table_id == b_id)
+                );
+                assert_eq!(rhs.table_id, a_id);
+            }
+            proj => panic!("unexpected project: {proj:#?}"),
+        };
+    }
+
+    /// Given a large table `a` without an index on `x`,
+    /// and a small table `b` with an index on `id`:
+    ///
+    /// ```sql
+    /// select b.* from a join b on a.x = b.id
+    /// ```
+    ///
+    /// Without statistics, we always prefer an index join.
+    /// With statistics, we build a hash table over `b`,
+    /// since probing its index for every row in `a` costs more.
+    #[test]
+    fn cost_based_hash_join() {
+        let a_id = TableId(1);
+        let b_id = TableId(2);
+
+        let a = Arc::new(schema(
+            a_id,
+            "a",
+            &[("id", AlgebraicType::U64), ("x", AlgebraicType::U64)],
+            &[],
+            &[],
+            None,
+        ));
+
+        let b = Arc::new(schema(
+            b_id,
+            "b",
+            &[("id", AlgebraicType::U64), ("y", AlgebraicType::U64)],
+            &[&[0]],
+            &[],
+            None,
+        ));
+
+        let db = SchemaViewer {
+            schemas: vec![a.clone(), b.clone()],
+        };
+
+        let stats = Stats {
+            rows: vec![(a_id, 1_000_000), (b_id, 10)],
+            ndv: vec![(b_id, 0.into(), 10)],
+        };
+
+        let sql = "select b.* from a join b on a.x = b.id";
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+
+        let plan = compile_select(lp).optimize().unwrap();
+        assert!(matches!(
+            plan.physical_plan(),
+            PhysicalPlan::IxJoin(IxJoin { rhs, .. }, _) if rhs.table_id == b_id
+        ));
+
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let plan = compile_select(lp).optimize_with(&stats).unwrap();
+        match plan.physical_plan() {
+            PhysicalPlan::HashJoin(HashJoin { lhs, rhs, .. }, _) => {
+                assert!(
+                    matches!(&**lhs, PhysicalPlan::TableScan(TableScan { schema, .. }, _) if schema.table_id == a_id)
+                );
+                assert!(
+                    matches!(&**rhs, PhysicalPlan::TableScan(TableScan { schema, .. }, _) if schema.table_id == b_id)
+                );
+            }
+            plan => panic!("unexpected plan: {plan:#?}"),
+        }
+
+        // Without any rows, fall back to the heuristic rules
+        let stats = Stats {
+            rows: vec![(a_id, 0), (b_id, 0)],
+            ndv: vec![],
+        };
+        let lp = parse_and_type_sub(sql, &db).unwrap();
+        let plan = compile_select(lp).optimize_with(&stats).unwrap();
+        assert!(matches!(plan.physical_plan(), PhysicalPlan::IxJoin(..)));
+    }
 }
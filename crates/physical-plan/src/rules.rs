@@ -14,6 +14,12 @@
 //!   Generate 2-column index scan
 //! * [IxScanEq3Col]  
 //!   Generate 3-column index scan
+//! * [IxScanKeyEq]  
+//!   Generate expression index scan for `t.a.b=1`
+//! * [IxScanPartial]  
+//!   Generate partial index scan when the filter implies the index's filter
+//! * [IxScanFullText]  
+//!   Generate full-text index scan for `match(t.a, 'query')`
 //! * [ReorderHashJoin]  
 //!   Reorder the sides of a hash join
 //! * [ReorderDeltaJoinRhs]
@@ -26,9 +32,12 @@
 //!   Mark index join as unique
 //! * [UniqueHashJoinRule]  
 //!   Mark hash join as unique
+use std::ops::Bound;
+
 use anyhow::{bail, Result};
-use spacetimedb_primitives::{ColId, ColSet, IndexId};
-use spacetimedb_schema::schema::IndexSchema;
+use spacetimedb_lib::AlgebraicValue;
+use spacetimedb_primitives::{ColId, ColList, ColSet, IndexId};
+use spacetimedb_schema::schema::{IndexSchema, TableSchema};
 use spacetimedb_sql_parser::ast::{BinOp, LogOp};
 
 use crate::plan::{
@@ -441,7 +450,7 @@ impl RewriteRule for IxScanEq {
                              ..
                          }| {
                             // TODO: Support prefix scans
-                            if index_algorithm.columns().len() == 1 {
+                            if index_algorithm.columns()?.len() == 1 {
                                 Some((*index_id, index_algorithm.find_col_index(*pos)?))
                             } else {
                                 None
@@ -514,7 +523,7 @@ impl RewriteRule for IxScanAnd {
                                      ..
                                  }| {
                                     index_algorithm
-                                        .columns()
+                                        .columns()?
                                         // TODO: Support prefix scans
                                         .as_singleton()
                                         .filter(|col_id| col_id.idx() == *pos)
@@ -560,6 +569,311 @@ impl RewriteRule for IxScanAnd {
     }
 }
 
+/// Match equality predicates on nested fields such as:
+///
+/// ```sql
+/// select * from t where t.position.x = 1
+/// select * from t where t.position.x = 1 and y = 2
+/// ```
+///
+/// Rewrite as a scan of an index whose key is that nested field.
+///
+/// NOTE: Delta tables don't maintain expression indexes,
+/// so this rule only applies to scans of committed tables.
+pub(crate) struct IxScanKeyEq;
+
+impl IxScanKeyEq {
+    /// Find an expression index whose key is the path in `expr`
+    fn find_index(schema: &TableSchema, expr: &PhysicalExpr) -> Option<IndexId> {
+        let PhysicalExpr::BinOp(BinOp::Eq, lhs, value) = expr else {
+            return None;
+        };
+        let (PhysicalExpr::Path(field, path), PhysicalExpr::Value(_)) = (&**lhs, &**value) else {
+            return None;
+        };
+        let PhysicalExpr::Field(TupleField { field_pos: pos, .. }) = &**field else {
+            return None;
+        };
+        schema
+            .indexes
+            .iter()
+            .find_map(|index| match index.index_algorithm.key_exprs()? {
+                [key]
+                    if key.function.is_none()
+                        && key.column.idx() == *pos
+                        && key.fields.iter().map(|&i| i as usize).eq(path.iter().copied()) =>
+                {
+                    Some(index.index_id)
+                }
+                _ => None,
+            })
+    }
+}
+
+impl RewriteRule for IxScanKeyEq {
+    type Plan = PhysicalPlan;
+    type Info = (IndexId, Option<usize>);
+
+    fn matches(plan: &PhysicalPlan) -> Option<Self::Info> {
+        if let PhysicalPlan::Filter(input, expr) = plan {
+            if let PhysicalPlan::TableScan(
+                TableScan {
+                    schema,
+                    limit: None,
+                    delta: None,
+                },
+                _,
+            ) = &**input
+            {
+                return match expr {
+                    PhysicalExpr::LogOp(LogOp::And, exprs) => exprs
+                        .iter()
+                        .enumerate()
+                        .find_map(|(i, expr)| Some((Self::find_index(schema, expr)?, Some(i)))),
+                    _ => Some((Self::find_index(schema, expr)?, None)),
+                };
+            }
+        }
+        None
+    }
+
+    fn rewrite(plan: PhysicalPlan, (index_id, i): Self::Info) -> Result<PhysicalPlan> {
+        if let PhysicalPlan::Filter(input, expr) = plan {
+            if let PhysicalPlan::TableScan(TableScan { schema, limit, delta }, label) = *input {
+                let (expr, rest) = match (expr, i) {
+                    (PhysicalExpr::LogOp(LogOp::And, mut exprs), Some(i)) => {
+                        let expr = exprs.swap_remove(i);
+                        let rest = match exprs.len() {
+                            1 => exprs.swap_remove(0),
+                            _ => PhysicalExpr::LogOp(LogOp::And, exprs),
+                        };
+                        (expr, Some(rest))
+                    }
+                    (expr, _) => (expr, None),
+                };
+                if let PhysicalExpr::BinOp(BinOp::Eq, _, value) = expr {
+                    if let PhysicalExpr::Value(v) = *value {
+                        let scan = PhysicalPlan::IxScan(
+                            IxScan {
+                                schema,
+                                limit,
+                                delta,
+                                index_id,
+                                prefix: vec![],
+                                arg: Sarg::Key(Bound::Included(v.clone()), Bound::Included(v)),
+                            },
+                            label,
+                        );
+                        return Ok(match rest {
+                            Some(expr) => PhysicalPlan::Filter(Box::new(scan), expr),
+                            None => scan,
+                        });
+                    }
+                }
+            }
+        }
+        bail!("{INVARIANT_VIOLATION}: Failed to create expression index scan from equality condition")
+    }
+}
+
+/// Match predicates implying the filter of a partial index such as:
+///
+/// ```sql
+/// -- Given an index on `t(x)` with the filter `status = 'active'`
+/// select * from t where status = 'active' and x = 1
+/// select * from t where status = 'active'
+/// ```
+///
+/// Rewrite as a scan of that index,
+/// by equality if there are equality predicates on all of its columns,
+/// and over the whole index otherwise.
+/// Predicates that aren't used as the index key are kept as a filter.
+///
+/// NOTE: Delta tables don't maintain partial indexes,
+/// so this rule only applies to scans of committed tables.
+/// Only the top-level conjuncts of the filter are considered.
+pub(crate) struct IxScanPartial;
+
+/// The conjuncts of `expr`
+fn conjuncts(expr: &PhysicalExpr) -> &[PhysicalExpr] {
+    match expr {
+        PhysicalExpr::LogOp(LogOp::And, exprs) => exprs,
+        expr => std::slice::from_ref(expr),
+    }
+}
+
+impl IxScanPartial {
+    /// Returns the column, operator, and value of a comparison `t.x op v`
+    fn as_fact(expr: &PhysicalExpr) -> Option<(ColId, BinOp, &AlgebraicValue)> {
+        let PhysicalExpr::BinOp(op, lhs, rhs) = expr else {
+            return None;
+        };
+        match (&**lhs, &**rhs) {
+            (PhysicalExpr::Field(TupleField { field_pos, .. }), PhysicalExpr::Value(v)) => {
+                Some((ColId::from(*field_pos), *op, v))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl RewriteRule for IxScanPartial {
+    type Plan = PhysicalPlan;
+    /// The index, its columns,
+    /// and the positions of the equality conjuncts on those columns if there are any.
+    type Info = (IndexId, ColList, Vec<usize>);
+
+    fn matches(plan: &PhysicalPlan) -> Option<Self::Info> {
+        let PhysicalPlan::Filter(input, expr) = plan else {
+            return None;
+        };
+        let PhysicalPlan::TableScan(
+            TableScan {
+                schema,
+                limit: None,
+                delta: None,
+            },
+            _,
+        ) = &**input
+        else {
+            return None;
+        };
+        let exprs = conjuncts(expr);
+        let facts = exprs.iter().filter_map(Self::as_fact).collect::<Vec<_>>();
+        schema.indexes.iter().find_map(|index| {
+            let partial = index.index_algorithm.partial()?;
+            let filter = partial.resolve_filter(schema.get_row_type()).ok()?;
+            if !filter.is_implied_by(&facts) {
+                return None;
+            }
+            let eqs = partial
+                .columns
+                .iter()
+                .map(|col| {
+                    exprs
+                        .iter()
+                        .position(|expr| matches!(Self::as_fact(expr), Some((c, BinOp::Eq, _)) if c == col))
+                })
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default();
+            Some((index.index_id, partial.columns.clone(), eqs))
+        })
+    }
+
+    fn rewrite(plan: PhysicalPlan, (index_id, columns, eqs): Self::Info) -> Result<PhysicalPlan> {
+        if let PhysicalPlan::Filter(input, expr) = plan {
+            if let PhysicalPlan::TableScan(TableScan { schema, limit, delta }, label) = *input {
+                let exprs = conjuncts(&expr);
+                let mut prefix = columns
+                    .iter()
+                    .zip(&eqs)
+                    .filter_map(|(col, &i)| exprs.get(i).and_then(Self::as_fact).map(|(_, _, v)| (col, v.clone())))
+                    .collect::<Vec<_>>();
+                let arg = match (prefix.pop(), columns.head()) {
+                    (Some((col, v)), _) => Sarg::Eq(col, v),
+                    (None, Some(col)) => Sarg::Range(col, Bound::Unbounded, Bound::Unbounded),
+                    (None, None) => bail!("{INVARIANT_VIOLATION}: Partial index without columns"),
+                };
+                let mut rest = exprs
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !eqs.contains(i))
+                    .map(|(_, expr)| expr.clone())
+                    .collect::<Vec<_>>();
+                let scan = PhysicalPlan::IxScan(
+                    IxScan {
+                        schema,
+                        limit,
+                        delta,
+                        index_id,
+                        prefix,
+                        arg,
+                    },
+                    label,
+                );
+                return Ok(match rest.len() {
+                    0 => scan,
+                    1 => PhysicalPlan::Filter(Box::new(scan), rest.swap_remove(0)),
+                    _ => PhysicalPlan::Filter(Box::new(scan), PhysicalExpr::LogOp(LogOp::And, rest)),
+                });
+            }
+        }
+        bail!("{INVARIANT_VIOLATION}: Failed to create partial index scan from filter")
+    }
+}
+
+/// Match full-text searches such as:
+///
+/// ```sql
+/// select * from t where match(name, 'red sword')
+/// select * from t where match(name, 'red sword') and x = 1
+/// ```
+///
+/// Rewrite as a scan of a full-text index on the column for one of the query's tokens.
+/// The filter is kept, since that scan returns every row containing the token,
+/// and not just those containing all of them.
+///
+/// NOTE: Delta tables don't maintain full-text indexes,
+/// so this rule only applies to scans of committed tables.
+pub(crate) struct IxScanFullText;
+
+impl RewriteRule for IxScanFullText {
+    type Plan = PhysicalPlan;
+    type Info = (IndexId, AlgebraicValue);
+
+    fn matches(plan: &PhysicalPlan) -> Option<Self::Info> {
+        let PhysicalPlan::Filter(input, expr) = plan else {
+            return None;
+        };
+        let PhysicalPlan::TableScan(
+            TableScan {
+                schema,
+                limit: None,
+                delta: None,
+            },
+            _,
+        ) = &**input
+        else {
+            return None;
+        };
+        conjuncts(expr).iter().find_map(|expr| {
+            let PhysicalExpr::Match(field, terms) = expr else {
+                return None;
+            };
+            let PhysicalExpr::Field(TupleField { field_pos, .. }) = &**field else {
+                return None;
+            };
+            let index = schema
+                .indexes
+                .iter()
+                .find(|index| index.index_algorithm.full_text() == Some(ColId::from(*field_pos)))?;
+            // Scan for the longest token, as it's likely the rarest
+            let term = terms.iter().max_by_key(|term| term.len())?;
+            Some((index.index_id, AlgebraicValue::String(term.as_str().into())))
+        })
+    }
+
+    fn rewrite(plan: PhysicalPlan, (index_id, token): Self::Info) -> Result<PhysicalPlan> {
+        if let PhysicalPlan::Filter(input, expr) = plan {
+            if let PhysicalPlan::TableScan(TableScan { schema, limit, delta }, label) = *input {
+                let scan = PhysicalPlan::IxScan(
+                    IxScan {
+                        schema,
+                        limit,
+                        delta,
+                        index_id,
+                        prefix: vec![],
+                        arg: Sarg::Key(Bound::Included(token.clone()), Bound::Included(token)),
+                    },
+                    label,
+                );
+                return Ok(PhysicalPlan::Filter(Box::new(scan), expr));
+            }
+        }
+        bail!("{INVARIANT_VIOLATION}: Failed to create full-text index scan from match condition")
+    }
+}
+
 /// Match multi-field equality predicates such as:
 ///
 /// ```sql
@@ -607,8 +921,8 @@ impl RewriteRule for IxScanEq2Col {
                 if let Some(scan) = schema
                     .indexes
                     .iter()
-                    .filter(|idx| idx.index_algorithm.columns().len() == 2) // TODO: Support prefix scans
-                    .map(|idx| (idx.index_id, idx.index_algorithm.columns()))
+                    .filter_map(|idx| Some((idx.index_id, idx.index_algorithm.columns()?)))
+                    .filter(|(_, columns)| columns.len() == 2) // TODO: Support prefix scans
                     .find_map(|(index_id, columns)| {
                         let mut columns = columns.iter();
                         let x = columns.next()?;
@@ -782,8 +1096,8 @@ impl RewriteRule for IxScanEq3Col {
                     if let Some(scan) = schema
                         .indexes
                         .iter()
-                        .filter(|idx| idx.index_algorithm.columns().len() == 3)
-                        .map(|idx| (idx.index_id, idx.index_algorithm.columns()))
+                        .filter_map(|idx| Some((idx.index_id, idx.index_algorithm.columns()?)))
+                        .filter(|(_, columns)| columns.len() == 3)
                         .find_map(|(index_id, columns)| {
                             let mut columns = columns.iter();
                             let x = columns.next()?;
@@ -1050,7 +1364,7 @@ impl RewriteRule for PullFilterAboveHashJoin {
                             schema
                                 .index_algorithm
                                 .columns()
-                                .as_singleton()
+                                .and_then(|cols| cols.as_singleton())
                                 .is_some_and(|col_id| col_id.idx() == rhs_field.field_pos)
                         }))
                     .then_some(());
@@ -1109,7 +1423,7 @@ impl RewriteRule for HashToIxJoin {
                     // Is there a single column index on this field?
                     schema.indexes.iter().find_map(|ix| {
                         ix.index_algorithm
-                            .columns()
+                            .columns()?
                             .as_singleton()
                             .filter(|col_id| col_id.idx() == *field_pos)
                             .map(|col_id| (ix.index_id, col_id))
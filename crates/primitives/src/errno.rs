@@ -23,6 +23,10 @@ macro_rules! errnos {
             INDEX_NOT_UNIQUE(14, "The index was not unique"),
             NO_SUCH_ROW(15, "The row was not found, e.g., in an update call"),
             AUTO_INC_OVERFLOW(16, "The auto-increment sequence overflowed"),
+            HOST_CALL_FAILURE_VALUE(
+                17,
+                "ABI called by host returned an error with a BSATN-encoded value"
+            ),
         );
     };
 }
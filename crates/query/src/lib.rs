@@ -1,18 +1,22 @@
+use std::time::Instant;
+
 use anyhow::{bail, Result};
 use spacetimedb_execution::{
     dml::{MutDatastore, MutExecutor},
-    pipelined::ProjectListExecutor,
+    pipelined::{PipelinedExecutor, ProjectListExecutor},
     Datastore, DeltaStore,
 };
 use spacetimedb_expr::{
     check::{parse_and_type_sub, SchemaView},
     expr::ProjectList,
     rls::{resolve_views_for_sql, resolve_views_for_sub},
-    statement::{parse_and_type_sql, Statement, DML},
+    statement::{parse_and_type_sql, Explain, Statement, DML},
 };
 use spacetimedb_lib::{identity::AuthCtx, metrics::ExecutionMetrics, ProductValue};
 use spacetimedb_physical_plan::{
     compile::{compile_dml_plan, compile_select, compile_select_list},
+    cost::Statistics,
+    explain::explain,
     plan::{ProjectListPlan, ProjectPlan},
 };
 use spacetimedb_primitives::TableId;
@@ -59,17 +63,21 @@ pub fn compile_sql_stmt(sql: &str, tx: &impl SchemaView, auth: &AuthCtx) -> Resu
     match parse_and_type_sql(sql, tx, auth)? {
         stmt @ Statement::DML(_) => Ok(stmt),
         Statement::Select(expr) => Ok(Statement::Select(resolve_views_for_sql(tx, expr, auth)?)),
+        Statement::Explain(Explain { analyze, select }) => Ok(Statement::Explain(Explain {
+            analyze,
+            select: resolve_views_for_sql(tx, select, auth)?,
+        })),
     }
 }
 
 /// A utility for executing a sql select statement
-pub fn execute_select_stmt<Tx: Datastore + DeltaStore>(
+pub fn execute_select_stmt<Tx: Datastore + DeltaStore + Statistics>(
     stmt: ProjectList,
     tx: &Tx,
     metrics: &mut ExecutionMetrics,
     check_row_limit: impl Fn(ProjectListPlan) -> Result<ProjectListPlan>,
 ) -> Result<Vec<ProductValue>> {
-    let plan = compile_select_list(stmt).optimize()?;
+    let plan = compile_select_list(stmt).optimize_with(tx)?;
     let plan = check_row_limit(plan)?;
     let plan = ProjectListExecutor::from(plan);
     let mut rows = vec![];
@@ -80,6 +88,66 @@ pub fn execute_select_stmt<Tx: Datastore + DeltaStore>(
     Ok(rows)
 }
 
+/// A utility for explaining a sql select statement.
+/// Returns the rendered plan, one line per operator.
+///
+/// With `ANALYZE`, the plan is also executed,
+/// and each operator is annotated with the rows it returned and the time it took.
+pub fn explain_select_stmt<Tx: Datastore + DeltaStore + Statistics>(
+    stmt: Explain,
+    tx: &Tx,
+    metrics: &mut ExecutionMetrics,
+    check_row_limit: impl Fn(ProjectListPlan) -> Result<ProjectListPlan>,
+) -> Result<Vec<String>> {
+    explain_select_plan(
+        compile_select_list(stmt.select),
+        stmt.analyze,
+        tx,
+        metrics,
+        check_row_limit,
+    )
+}
+
+/// Like [explain_select_stmt], but for a select statement that has already been compiled.
+/// The plan is optimized before it is explained.
+pub fn explain_select_plan<Tx: Datastore + DeltaStore + Statistics>(
+    plan: ProjectListPlan,
+    analyze: bool,
+    tx: &Tx,
+    metrics: &mut ExecutionMetrics,
+    check_row_limit: impl Fn(ProjectListPlan) -> Result<ProjectListPlan>,
+) -> Result<Vec<String>> {
+    let plan = plan.optimize_with(tx)?;
+    if !analyze {
+        return Ok(explain(&plan, |_| None));
+    }
+
+    let plan = check_row_limit(plan)?;
+    let executor = ProjectListExecutor::from(plan.clone()).analyze();
+    let mut rows = 0;
+    let start = Instant::now();
+    executor.execute(tx, metrics, &mut |_| {
+        rows += 1;
+        Ok(())
+    })?;
+    let elapsed = start.elapsed();
+
+    // Operators are visited in the same order in which they are rendered
+    let mut ops = vec![];
+    executor.visit(&mut |op| {
+        if let PipelinedExecutor::Analyze(op) = op {
+            ops.push((op.rows(), op.elapsed()));
+        }
+    });
+    let mut lines = explain(&plan, |i| {
+        ops.get(i)
+            .map(|(rows, elapsed)| format!("actual rows={rows} time={:.3}ms", elapsed.as_secs_f64() * 1e3))
+    });
+    lines.push(format!("Rows returned: {rows}"));
+    lines.push(format!("Execution time: {:.3}ms", elapsed.as_secs_f64() * 1e3));
+    Ok(lines)
+}
+
 /// A utility for executing a sql dml statement
 pub fn execute_dml_stmt<Tx: MutDatastore>(stmt: DML, tx: &mut Tx, metrics: &mut ExecutionMetrics) -> Result<()> {
     let plan = compile_dml_plan(stmt).optimize()?;
@@ -5,7 +5,10 @@ use std::io;
 use super::{AutoMigratePlan, IndexAlgorithm, ModuleDefLookup, TableDef};
 use crate::{
     auto_migrate::AutoMigrateStep,
-    def::{ConstraintData, FunctionKind, ModuleDef, ScheduleDef},
+    def::{
+        BTreeAlgorithm, ConstraintData, DirectAlgorithm, FullTextAlgorithm, FunctionKind, ModuleDef,
+        PartialBTreeAlgorithm, ScheduleDef,
+    },
     identifier::Identifier,
 };
 use itertools::Itertools;
@@ -273,20 +276,29 @@ fn extract_table_info(
         .sorted_by_key(|c| c.name.clone())
         .map(|index| {
             let columns = match &index.algorithm {
-                IndexAlgorithm::BTree(btree) => btree
-                    .columns
+                IndexAlgorithm::BTree(BTreeAlgorithm { columns })
+                | IndexAlgorithm::PartialBTree(PartialBTreeAlgorithm { columns, .. }) => columns
                     .iter()
                     .map(|col_id| {
                         let column = table_def.get_column(col_id).ok_or(FormattingErrors::ColumnNotFound)?;
                         Ok(column.name.clone())
                     })
                     .collect::<Result<Vec<_>, FormattingErrors>>()?,
-                IndexAlgorithm::Direct(direct) => {
-                    let column = table_def
-                        .get_column(direct.column)
-                        .ok_or(FormattingErrors::ColumnNotFound)?;
+                IndexAlgorithm::Direct(DirectAlgorithm { column })
+                | IndexAlgorithm::FullText(FullTextAlgorithm { column }) => {
+                    let column = table_def.get_column(*column).ok_or(FormattingErrors::ColumnNotFound)?;
                     vec![column.name.clone()]
                 }
+                IndexAlgorithm::BTreeExpr(btree_expr) => btree_expr
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        let column = table_def
+                            .get_column(key.column)
+                            .ok_or(FormattingErrors::ColumnNotFound)?;
+                        Ok(column.name.clone())
+                    })
+                    .collect::<Result<Vec<_>, FormattingErrors>>()?,
             };
 
             Ok(IndexInfo {
@@ -341,20 +353,28 @@ fn extract_index_info(
     let index_def = table_def.indexes.get(index).ok_or(FormattingErrors::IndexNotFound)?;
 
     let columns = match &index_def.algorithm {
-        IndexAlgorithm::BTree(btree) => btree
-            .columns
+        IndexAlgorithm::BTree(BTreeAlgorithm { columns })
+        | IndexAlgorithm::PartialBTree(PartialBTreeAlgorithm { columns, .. }) => columns
             .iter()
             .map(|col_id| {
                 let column = table_def.get_column(col_id).ok_or(FormattingErrors::ColumnNotFound)?;
                 Ok(column.name.clone())
             })
             .collect::<Result<Vec<_>, FormattingErrors>>()?,
-        IndexAlgorithm::Direct(direct) => {
-            let column = table_def
-                .get_column(direct.column)
-                .ok_or(FormattingErrors::ColumnNotFound)?;
+        IndexAlgorithm::Direct(DirectAlgorithm { column }) | IndexAlgorithm::FullText(FullTextAlgorithm { column }) => {
+            let column = table_def.get_column(*column).ok_or(FormattingErrors::ColumnNotFound)?;
             vec![column.name.clone()]
         }
+        IndexAlgorithm::BTreeExpr(btree_expr) => btree_expr
+            .keys
+            .iter()
+            .map(|key| {
+                let column = table_def
+                    .get_column(key.column)
+                    .ok_or(FormattingErrors::ColumnNotFound)?;
+                Ok(column.name.clone())
+            })
+            .collect::<Result<Vec<_>, FormattingErrors>>()?,
     };
 
     Ok(IndexInfo {
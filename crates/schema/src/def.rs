@@ -26,16 +26,18 @@ use crate::type_for_generate::{AlgebraicTypeUse, ProductTypeDef, TypespaceForGen
 use deserialize::ArgsSeed;
 use enum_map::EnumMap;
 use hashbrown::Equivalent;
+use index_filter::{IndexFilter, IndexFilterError};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use spacetimedb_data_structures::error_stream::{CollectAllErrors, CombineErrors, ErrorStream};
 use spacetimedb_data_structures::map::HashMap;
 use spacetimedb_lib::db::raw_def;
 use spacetimedb_lib::db::raw_def::v9::{
-    Lifecycle, RawColumnDefaultValueV9, RawConstraintDataV9, RawConstraintDefV9, RawIdentifier, RawIndexAlgorithm,
-    RawIndexDefV9, RawMiscModuleExportV9, RawModuleDefV9, RawProcedureDefV9, RawReducerDefV9, RawRowLevelSecurityDefV9,
-    RawScheduleDefV9, RawScopedTypeNameV9, RawSequenceDefV9, RawSql, RawTableDefV9, RawTypeDefV9,
-    RawUniqueConstraintDataV9, RawViewDefV9, TableAccess, TableType,
+    IndexKeyFunction, Lifecycle, RawColumnDefaultValueV9, RawConstraintDataV9, RawConstraintDefV9, RawIdentifier,
+    RawIndexAlgorithm, RawIndexDefV9, RawIndexKeyExprV9, RawMiscModuleExportV9, RawModuleDefV9, RawProcedureDefV9,
+    RawReducerDefV9, RawReducerReturnTypeV9, RawRowLevelSecurityDefV9, RawScheduleDefV9, RawScopedTypeNameV9,
+    RawSequenceDefV9, RawSql, RawTableDefV9, RawTypeDefV9, RawUniqueConstraintDataV9, RawViewDefV9, TableAccess,
+    TableType,
 };
 use spacetimedb_lib::{ProductType, RawModuleDef};
 use spacetimedb_primitives::{ColId, ColList, ColOrCols, ColSet, ProcedureId, ReducerId, TableId};
@@ -44,6 +46,7 @@ use spacetimedb_sats::{AlgebraicTypeRef, Typespace};
 
 pub mod deserialize;
 pub mod error;
+pub mod index_filter;
 pub mod validate;
 
 /// A map from `Identifier`s to values of type `T`.
@@ -387,6 +390,12 @@ impl From<ModuleDef> for RawModuleDefV9 {
             procedures,
         } = val;
 
+        let reducer_return_types = reducers
+            .values()
+            .filter_map(ReducerDef::raw_return_type)
+            .map(RawMiscModuleExportV9::ReducerReturnType)
+            .collect::<Vec<_>>();
+
         RawModuleDefV9 {
             tables: to_raw(tables),
             reducers: reducers.into_iter().map(|(_, def)| def.into()).collect(),
@@ -396,6 +405,7 @@ impl From<ModuleDef> for RawModuleDefV9 {
                 .into_iter()
                 .map(|(_, def)| def.into())
                 .chain(views.into_iter().map(|(_, def)| def.into()))
+                .chain(reducer_return_types)
                 .collect(),
             typespace,
             row_level_security: row_level_security_raw.into_iter().map(|(_, def)| def).collect(),
@@ -599,6 +609,9 @@ impl From<IndexDef> for RawIndexDefV9 {
             algorithm: match val.algorithm {
                 IndexAlgorithm::BTree(BTreeAlgorithm { columns }) => RawIndexAlgorithm::BTree { columns },
                 IndexAlgorithm::Direct(DirectAlgorithm { column }) => RawIndexAlgorithm::Direct { column },
+                IndexAlgorithm::BTreeExpr(btree_expr) => btree_expr.into(),
+                IndexAlgorithm::PartialBTree(partial) => partial.into(),
+                IndexAlgorithm::FullText(FullTextAlgorithm { column }) => RawIndexAlgorithm::FullText { column },
             },
             accessor_name: val.accessor_name.map(Into::into),
         }
@@ -613,21 +626,63 @@ pub enum IndexAlgorithm {
     BTree(BTreeAlgorithm),
     /// Implemented using `DirectUniqueIndex`.
     Direct(DirectAlgorithm),
+    /// Implemented using a rust `std::collections::BTreeMap` over computed keys.
+    BTreeExpr(BTreeExprAlgorithm),
+    /// Implemented using a rust `std::collections::BTreeMap` over the rows matching a filter.
+    PartialBTree(PartialBTreeAlgorithm),
+    /// Implemented using a rust `std::collections::BTreeMap` from tokens to the rows containing them.
+    FullText(FullTextAlgorithm),
 }
 
 impl IndexAlgorithm {
-    /// Get the columns of the index.
-    pub fn columns(&self) -> ColOrCols<'_> {
+    /// Get the columns of the index,
+    /// if its keys are a projection of the row's columns.
+    ///
+    /// Returns `None` for [`IndexAlgorithm::BTreeExpr`],
+    /// as its keys are computed from the row,
+    /// for [`IndexAlgorithm::PartialBTree`],
+    /// as it only contains the rows matching its filter,
+    /// and for [`IndexAlgorithm::FullText`],
+    /// as its keys are the tokens of a column rather than its values.
+    /// Such an index can neither serve lookups by column values nor back a unique constraint.
+    /// See [`Self::key_exprs`], [`Self::partial`] and [`Self::full_text`] for how they are keyed.
+    pub fn columns(&self) -> Option<ColOrCols<'_>> {
+        match self {
+            Self::BTree(btree) => Some(ColOrCols::ColList(&btree.columns)),
+            Self::Direct(direct) => Some(ColOrCols::Col(direct.column)),
+            Self::BTreeExpr(_) | Self::PartialBTree(_) | Self::FullText(_) => None,
+        }
+    }
+
+    /// Returns the key expressions of the index, if its keys are computed.
+    pub fn key_exprs(&self) -> Option<&[IndexKeyExpr]> {
         match self {
-            Self::BTree(btree) => ColOrCols::ColList(&btree.columns),
-            Self::Direct(direct) => ColOrCols::Col(direct.column),
+            Self::BTreeExpr(btree_expr) => Some(&btree_expr.keys),
+            _ => None,
         }
     }
+
+    /// Returns the columns and filter of the index, if it is partial.
+    pub fn partial(&self) -> Option<&PartialBTreeAlgorithm> {
+        match self {
+            Self::PartialBTree(partial) => Some(partial),
+            _ => None,
+        }
+    }
+
+    /// Returns the column of the index, if it is a full-text index.
+    pub fn full_text(&self) -> Option<ColId> {
+        match self {
+            Self::FullText(full_text) => Some(full_text.column),
+            _ => None,
+        }
+    }
+
     /// Find the column index for a given field.
     ///
     /// *NOTE*: This take in account the possibility of permutations.
     pub fn find_col_index(&self, pos: usize) -> Option<ColId> {
-        self.columns().iter().find(|col_id| col_id.idx() == pos)
+        self.columns()?.iter().find(|col_id| col_id.idx() == pos)
     }
 }
 
@@ -636,6 +691,9 @@ impl From<IndexAlgorithm> for RawIndexAlgorithm {
         match val {
             IndexAlgorithm::BTree(BTreeAlgorithm { columns }) => Self::BTree { columns },
             IndexAlgorithm::Direct(DirectAlgorithm { column }) => Self::Direct { column },
+            IndexAlgorithm::BTreeExpr(btree_expr) => btree_expr.into(),
+            IndexAlgorithm::PartialBTree(partial) => partial.into(),
+            IndexAlgorithm::FullText(FullTextAlgorithm { column }) => Self::FullText { column },
         }
     }
 }
@@ -680,6 +738,162 @@ impl From<DirectAlgorithm> for IndexAlgorithm {
     }
 }
 
+/// Data specifying a BTree index over computed keys.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BTreeExprAlgorithm {
+    /// The key expressions to index. Never empty.
+    pub keys: Box<[IndexKeyExpr]>,
+}
+
+impl BTreeExprAlgorithm {
+    /// Returns the type of the keys stored in the index for rows of `row_type`.
+    ///
+    /// A single key expression is stored as is,
+    /// while several are stored as a product of the individual keys.
+    pub fn key_type(&self, row_type: &ProductType) -> Option<AlgebraicType> {
+        match &*self.keys {
+            [key] => key.key_type(row_type),
+            keys => keys
+                .iter()
+                .map(|key| key.key_type(row_type))
+                .collect::<Option<ProductType>>()
+                .map(AlgebraicType::Product),
+        }
+    }
+}
+
+impl From<BTreeExprAlgorithm> for IndexAlgorithm {
+    fn from(val: BTreeExprAlgorithm) -> Self {
+        IndexAlgorithm::BTreeExpr(val)
+    }
+}
+
+impl From<BTreeExprAlgorithm> for RawIndexAlgorithm {
+    fn from(val: BTreeExprAlgorithm) -> Self {
+        RawIndexAlgorithm::BTreeExpr {
+            keys: val.keys.into_vec().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Data specifying a BTree index over the rows matching a filter.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PartialBTreeAlgorithm {
+    /// The columns to index.
+    pub columns: ColList,
+    /// The filter, as SQL text over the unqualified column names of the table.
+    pub filter: Box<str>,
+}
+
+impl PartialBTreeAlgorithm {
+    /// Resolves the filter against `row_type`, whose column types must already be resolved.
+    pub fn resolve_filter(&self, row_type: &ProductType) -> Result<IndexFilter, IndexFilterError> {
+        IndexFilter::parse(&self.filter, row_type)
+    }
+}
+
+impl From<PartialBTreeAlgorithm> for IndexAlgorithm {
+    fn from(val: PartialBTreeAlgorithm) -> Self {
+        IndexAlgorithm::PartialBTree(val)
+    }
+}
+
+impl From<PartialBTreeAlgorithm> for RawIndexAlgorithm {
+    fn from(val: PartialBTreeAlgorithm) -> Self {
+        RawIndexAlgorithm::PartialBTree {
+            columns: val.columns,
+            filter: val.filter,
+        }
+    }
+}
+
+/// Data specifying a full-text index.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FullTextAlgorithm {
+    /// The `String` column to index.
+    pub column: ColId,
+}
+
+impl From<FullTextAlgorithm> for IndexAlgorithm {
+    fn from(val: FullTextAlgorithm) -> Self {
+        IndexAlgorithm::FullText(val)
+    }
+}
+
+/// A key expression of an index: a path into a column, possibly through nested product fields,
+/// followed by an optional function applied to the value found at the end of the path.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IndexKeyExpr {
+    /// The column the path starts at.
+    pub column: ColId,
+    /// Positions of the nested product fields to descend into, starting at `column`.
+    pub fields: Box<[u16]>,
+    /// The function applied to the value at the end of the path.
+    pub function: Option<IndexKeyFunction>,
+}
+
+impl IndexKeyExpr {
+    /// Returns the type of the value at the end of the path,
+    /// for a row of `row_type` whose types have been resolved.
+    fn path_type<'a>(&self, row_type: &'a ProductType) -> Option<&'a AlgebraicType> {
+        let column = &row_type.elements.get(self.column.idx())?.algebraic_type;
+        self.fields.iter().try_fold(column, |ty, &field| {
+            Some(&ty.as_product()?.elements.get(field as usize)?.algebraic_type)
+        })
+    }
+
+    /// Returns the type of the key computed by this expression,
+    /// for a row of `row_type` whose types have been resolved.
+    pub fn key_type(&self, row_type: &ProductType) -> Option<AlgebraicType> {
+        let ty = self.path_type(row_type)?;
+        match self.function {
+            None => Some(ty.clone()),
+            Some(IndexKeyFunction::Lower) => ty.is_string().then_some(AlgebraicType::String),
+            Some(_) => None,
+        }
+    }
+
+    /// Computes the key of this expression from the value of its `column`.
+    ///
+    /// Returns `None` if `value` does not have the shape expected by the expression.
+    pub fn eval(&self, value: AlgebraicValue) -> Option<AlgebraicValue> {
+        let value = self.fields.iter().try_fold(value, |value, &field| {
+            value
+                .into_product()
+                .ok()?
+                .elements
+                .into_vec()
+                .into_iter()
+                .nth(field as usize)
+        })?;
+        match self.function {
+            None => Some(value),
+            Some(IndexKeyFunction::Lower) => Some(AlgebraicValue::String(value.as_string()?.to_lowercase().into())),
+            Some(_) => None,
+        }
+    }
+}
+
+impl From<RawIndexKeyExprV9> for IndexKeyExpr {
+    fn from(raw: RawIndexKeyExprV9) -> Self {
+        Self {
+            column: raw.column,
+            fields: raw.fields.into(),
+            function: raw.function,
+        }
+    }
+}
+
+impl From<IndexKeyExpr> for RawIndexKeyExprV9 {
+    fn from(val: IndexKeyExpr) -> Self {
+        Self {
+            column: val.column,
+            fields: val.fields.into(),
+            function: val.function,
+        }
+    }
+}
+
 /// A struct representing the validated definition of a database column.
 ///
 /// Cannot be created directly. Construct a [`ModuleDef`] by validating a [`RawModuleDef`] instead,
@@ -1064,6 +1278,42 @@ pub struct ReducerDef {
 
     /// The special role of this reducer in the module lifecycle, if any.
     pub lifecycle: Option<Lifecycle>,
+
+    /// The type of the value returned when the reducer succeeds.
+    ///
+    /// This is `()` unless the module declares a return type for the reducer.
+    /// If this is a non-special compound type, it should be registered in the module's `Typespace`
+    /// and indirected through an [`AlgebraicType::Ref`].
+    pub ok_return_type: AlgebraicType,
+
+    /// The type of the value returned when the reducer succeeds, formatted for client codegen.
+    pub ok_return_type_for_generate: AlgebraicTypeUse,
+
+    /// The type of the error returned when the reducer fails.
+    ///
+    /// This is `String`, the error message, unless the module declares an error type for the reducer.
+    /// If this is a non-special compound type, it should be registered in the module's `Typespace`
+    /// and indirected through an [`AlgebraicType::Ref`].
+    pub err_return_type: AlgebraicType,
+
+    /// The type of the error returned when the reducer fails, formatted for client codegen.
+    pub err_return_type_for_generate: AlgebraicTypeUse,
+}
+
+impl ReducerDef {
+    /// Does this reducer return something other than `()` on success or a `String` message on failure?
+    pub fn has_typed_return(&self) -> bool {
+        !self.ok_return_type.is_unit() || self.err_return_type != AlgebraicType::String
+    }
+
+    /// The declaration of this reducer's return types, if they aren't the defaults.
+    fn raw_return_type(&self) -> Option<RawReducerReturnTypeV9> {
+        self.has_typed_return().then(|| RawReducerReturnTypeV9 {
+            reducer: self.name.clone().into(),
+            ok_return_type: self.ok_return_type.clone(),
+            err_return_type: self.err_return_type.clone(),
+        })
+    }
 }
 
 impl From<ReducerDef> for RawReducerDefV9 {
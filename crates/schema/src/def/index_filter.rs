@@ -0,0 +1,267 @@
+//! The filters of partial indexes.
+//!
+//! A filter is stored as SQL text in the module definition and the system tables,
+//! and is resolved against the row type of its table whenever it is used.
+
+use spacetimedb_lib::{AlgebraicType, AlgebraicValue, ProductType};
+use spacetimedb_primitives::ColId;
+use spacetimedb_sql_parser::ast::{BinOp, LogOp, SqlExpr, SqlIdent, SqlLiteral};
+use spacetimedb_sql_parser::parser::errors::SqlParseError;
+use spacetimedb_sql_parser::parser::sub::parse_predicate;
+
+use crate::error::PrettyAlgebraicType;
+
+/// The filter of a partial index, resolved against the row type of its table.
+///
+/// Filters are conjunctions and disjunctions of comparisons between a column and a literal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexFilter {
+    /// `column op value`
+    Cmp(ColId, BinOp, AlgebraicValue),
+    /// All of the filters must hold
+    And(Box<[IndexFilter]>),
+    /// Any of the filters must hold
+    Or(Box<[IndexFilter]>),
+}
+
+/// An error resolving the filter of a partial index.
+#[derive(thiserror::Error, Debug)]
+pub enum IndexFilterError {
+    #[error(transparent)]
+    Parse(#[from] SqlParseError),
+    #[error("`{0}` is not a column of the table")]
+    UnknownColumn(Box<str>),
+    #[error("`{literal}` is not a valid literal of type `{ty}`")]
+    InvalidLiteral { literal: Box<str>, ty: PrettyAlgebraicType },
+    #[error("expected a comparison between a column and a literal")]
+    Unsupported,
+}
+
+impl IndexFilter {
+    /// Parses the SQL predicate `sql` and resolves it against `row_type`,
+    /// whose column types must already be resolved.
+    pub fn parse(sql: &str, row_type: &ProductType) -> Result<Self, IndexFilterError> {
+        Self::resolve(parse_predicate(sql)?, row_type)
+    }
+
+    fn resolve(expr: SqlExpr, row_type: &ProductType) -> Result<Self, IndexFilterError> {
+        let column = |SqlIdent(name): SqlIdent| {
+            row_type
+                .elements
+                .iter()
+                .position(|field| field.has_name(&name))
+                .map(|pos| (ColId::from(pos), &row_type.elements[pos].algebraic_type))
+                .ok_or(IndexFilterError::UnknownColumn(name))
+        };
+        let cmp = |name, op, literal| {
+            let (col, ty) = column(name)?;
+            let value = parse_literal(literal, ty)?;
+            Ok(Self::Cmp(col, op, value))
+        };
+        match expr {
+            SqlExpr::Log(a, b, op) => {
+                let a = Self::resolve(*a, row_type)?;
+                let b = Self::resolve(*b, row_type)?;
+                Ok(match op {
+                    LogOp::And => Self::And(flatten(a, b, |filter| filter.as_and())),
+                    LogOp::Or => Self::Or(flatten(a, b, |filter| filter.as_or())),
+                })
+            }
+            SqlExpr::Bin(a, b, op) => match (*a, *b) {
+                (SqlExpr::Var(name), SqlExpr::Lit(literal)) => cmp(name, op, literal),
+                (SqlExpr::Lit(literal), SqlExpr::Var(name)) => cmp(name, flip(op), literal),
+                _ => Err(IndexFilterError::Unsupported),
+            },
+            // A bare boolean column, e.g. `WHERE active`
+            SqlExpr::Var(name) => cmp(name, BinOp::Eq, SqlLiteral::Bool(true)),
+            _ => Err(IndexFilterError::Unsupported),
+        }
+    }
+
+    fn as_and(self) -> Result<Box<[Self]>, Self> {
+        match self {
+            Self::And(filters) => Ok(filters),
+            filter => Err(filter),
+        }
+    }
+
+    fn as_or(self) -> Result<Box<[Self]>, Self> {
+        match self {
+            Self::Or(filters) => Ok(filters),
+            filter => Err(filter),
+        }
+    }
+
+    /// Does the row whose columns are read by `read` match this filter?
+    pub fn eval(&self, read: &mut impl FnMut(ColId) -> AlgebraicValue) -> bool {
+        match self {
+            Self::Cmp(col, op, value) => compare(*op, &read(*col), value),
+            Self::And(filters) => filters.iter().all(|filter| filter.eval(read)),
+            Self::Or(filters) => filters.iter().any(|filter| filter.eval(read)),
+        }
+    }
+
+    /// Does every row satisfying all of the comparisons in `facts` also satisfy this filter?
+    ///
+    /// The facts are typically the conjuncts of a query's WHERE clause.
+    /// This is a conservative check: it may return `false` for filters that are in fact implied.
+    pub fn is_implied_by(&self, facts: &[(ColId, BinOp, &AlgebraicValue)]) -> bool {
+        match self {
+            Self::Cmp(col, op, value) => facts
+                .iter()
+                .any(|&(fact_col, fact_op, fact_value)| fact_col == *col && implies(fact_op, fact_value, *op, value)),
+            Self::And(filters) => filters.iter().all(|filter| filter.is_implied_by(facts)),
+            Self::Or(filters) => filters.iter().any(|filter| filter.is_implied_by(facts)),
+        }
+    }
+}
+
+/// Combine `a` and `b` into a flat list, splicing in the children of either that `split` accepts.
+fn flatten(
+    a: IndexFilter,
+    b: IndexFilter,
+    split: impl Fn(IndexFilter) -> Result<Box<[IndexFilter]>, IndexFilter>,
+) -> Box<[IndexFilter]> {
+    [a, b]
+        .into_iter()
+        .flat_map(|filter| match split(filter) {
+            Ok(filters) => filters.into_vec(),
+            Err(filter) => vec![filter],
+        })
+        .collect()
+}
+
+/// Returns the operator `op'` such that `a op b` iff `b op' a`.
+fn flip(op: BinOp) -> BinOp {
+    match op {
+        BinOp::Eq | BinOp::Ne => op,
+        BinOp::Lt => BinOp::Gt,
+        BinOp::Gt => BinOp::Lt,
+        BinOp::Lte => BinOp::Gte,
+        BinOp::Gte => BinOp::Lte,
+    }
+}
+
+/// Evaluates `a op b`.
+fn compare(op: BinOp, a: &AlgebraicValue, b: &AlgebraicValue) -> bool {
+    match op {
+        BinOp::Eq => a == b,
+        BinOp::Ne => a != b,
+        BinOp::Lt => a < b,
+        BinOp::Gt => a > b,
+        BinOp::Lte => a <= b,
+        BinOp::Gte => a >= b,
+    }
+}
+
+/// Does `x fact_op a` imply `x op b` for every `x`?
+fn implies(fact_op: BinOp, a: &AlgebraicValue, op: BinOp, b: &AlgebraicValue) -> bool {
+    use BinOp::*;
+    match (fact_op, op) {
+        (Eq, _) => compare(op, a, b),
+        (Ne, Ne) => a == b,
+        (Lt, Lt | Lte | Ne) | (Lte, Lte) => a <= b,
+        (Lte, Lt | Ne) => a < b,
+        (Gt, Gt | Gte | Ne) | (Gte, Gte) => a >= b,
+        (Gte, Gt | Ne) => a > b,
+        _ => false,
+    }
+}
+
+/// Converts `literal` to a value of type `ty`.
+fn parse_literal(literal: SqlLiteral, ty: &AlgebraicType) -> Result<AlgebraicValue, IndexFilterError> {
+    let value = match (&literal, ty) {
+        (SqlLiteral::Bool(b), AlgebraicType::Bool) => Some(AlgebraicValue::Bool(*b)),
+        (SqlLiteral::Str(s), AlgebraicType::String) => Some(AlgebraicValue::String(s.clone())),
+        (SqlLiteral::Num(n), _) => parse_num(n, ty),
+        _ => None,
+    };
+    value.ok_or_else(|| IndexFilterError::InvalidLiteral {
+        literal: match literal {
+            SqlLiteral::Bool(b) => b.to_string().into(),
+            SqlLiteral::Hex(s) | SqlLiteral::Num(s) | SqlLiteral::Str(s) => s,
+        },
+        ty: ty.clone().into(),
+    })
+}
+
+fn parse_num(n: &str, ty: &AlgebraicType) -> Option<AlgebraicValue> {
+    Some(match ty {
+        AlgebraicType::I8 => AlgebraicValue::I8(n.parse().ok()?),
+        AlgebraicType::U8 => AlgebraicValue::U8(n.parse().ok()?),
+        AlgebraicType::I16 => AlgebraicValue::I16(n.parse().ok()?),
+        AlgebraicType::U16 => AlgebraicValue::U16(n.parse().ok()?),
+        AlgebraicType::I32 => AlgebraicValue::I32(n.parse().ok()?),
+        AlgebraicType::U32 => AlgebraicValue::U32(n.parse().ok()?),
+        AlgebraicType::I64 => AlgebraicValue::I64(n.parse().ok()?),
+        AlgebraicType::U64 => AlgebraicValue::U64(n.parse().ok()?),
+        AlgebraicType::I128 => n.parse::<i128>().ok()?.into(),
+        AlgebraicType::U128 => n.parse::<u128>().ok()?.into(),
+        AlgebraicType::F32 => n.parse::<f32>().ok()?.into(),
+        AlgebraicType::F64 => n.parse::<f64>().ok()?.into(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_type() -> ProductType {
+        ProductType::from([
+            ("status", AlgebraicType::String),
+            ("level", AlgebraicType::U32),
+            ("active", AlgebraicType::Bool),
+        ])
+    }
+
+    #[test]
+    fn parse_and_eval() {
+        let filter = IndexFilter::parse("status = 'active' and (level >= 10 or active)", &row_type()).unwrap();
+        let row = |status: &str, level: u32, active: bool| {
+            move |col: ColId| match col.idx() {
+                0 => status.into(),
+                1 => level.into(),
+                _ => active.into(),
+            }
+        };
+        assert!(filter.eval(&mut row("active", 10, false)));
+        assert!(filter.eval(&mut row("active", 1, true)));
+        assert!(!filter.eval(&mut row("active", 1, false)));
+        assert!(!filter.eval(&mut row("idle", 10, true)));
+
+        // Literals on the left are flipped
+        assert_eq!(
+            IndexFilter::parse("10 < level", &row_type()).unwrap(),
+            IndexFilter::Cmp(ColId(1), BinOp::Gt, AlgebraicValue::U32(10))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for sql in [
+            "name = 'x'",
+            "level = 'x'",
+            "level = -1",
+            "status = level",
+            "t.level = 1",
+        ] {
+            assert!(IndexFilter::parse(sql, &row_type()).is_err(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn implication() {
+        let active = AlgebraicValue::String("active".into());
+        let filter = IndexFilter::parse("status = 'active' and level > 5", &row_type()).unwrap();
+
+        let eq = |col, value| (ColId(col), BinOp::Eq, value);
+        assert!(filter.is_implied_by(&[eq(0, &active), eq(1, &AlgebraicValue::U32(6))]));
+        assert!(filter.is_implied_by(&[eq(0, &active), (ColId(1), BinOp::Gte, &AlgebraicValue::U32(6))]));
+        assert!(!filter.is_implied_by(&[eq(0, &active), (ColId(1), BinOp::Gte, &AlgebraicValue::U32(5))]));
+        assert!(!filter.is_implied_by(&[eq(0, &active)]));
+
+        let filter = IndexFilter::parse("status = 'active' or status = 'idle'", &row_type()).unwrap();
+        assert!(filter.is_implied_by(&[eq(0, &active)]));
+    }
+}
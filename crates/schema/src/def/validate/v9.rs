@@ -1,3 +1,4 @@
+use crate::def::index_filter::IndexFilter;
 use crate::def::*;
 use crate::error::{RawColumnName, ValidationError};
 use crate::type_for_generate::{ClientCodegenError, ProductTypeDef, TypespaceForGenerateBuilder};
@@ -43,18 +44,35 @@ pub fn validate(def: RawModuleDefV9) -> Result<ModuleDef> {
     // `combine_errors` or `collect_all_errors` on all the things we need to validate.
     // Sometimes it is unavoidable to use `?` early and this should be commented on.
 
+    // Reducer return types are declared as misc exports,
+    // since they were added after the format of `RawReducerDefV9` was stabilized.
+    let (reducer_return_types, misc_exports) =
+        misc_exports
+            .into_iter()
+            .partition::<Vec<RawMiscModuleExportV9>, _>(|misc_export| {
+                matches!(misc_export, RawMiscModuleExportV9::ReducerReturnType(_))
+            });
+    let (mut reducer_return_types, duplicate_reducer_return_types) = collect_reducer_return_types(reducer_return_types);
+
     let reducers = reducers
         .into_iter()
         .enumerate()
         .map(|(idx, reducer)| {
+            let return_type = reducer_return_types.remove(&reducer.name);
             validator
-                .validate_reducer_def(reducer, ReducerId(idx as u32))
+                .validate_reducer_def(reducer, return_type, ReducerId(idx as u32))
                 .map(|reducer_def| (reducer_def.name.clone(), reducer_def))
         })
         // Collect into a `Vec` first to preserve duplicate names.
         // Later on, in `check_function_names_are_unique`, we'll transform this into an `IndexMap`.
         .collect_all_errors::<Vec<_>>();
 
+    // Any return types left over were declared for reducers which don't exist.
+    let reducer_return_types = reducer_return_types
+        .into_keys()
+        .map(|reducer| -> Result<()> { Err(ValidationError::ReducerReturnTypeForMissingReducer { reducer }.into()) })
+        .collect_all_errors::<()>();
+
     let (procedures, misc_exports) =
         misc_exports
             .into_iter()
@@ -125,9 +143,17 @@ pub fn validate(def: RawModuleDefV9) -> Result<ModuleDef> {
         })
         .collect_all_errors::<HashMap<_, _>>();
 
-    let tables_types_reducers_procedures_views = (tables, types, reducers, procedures, views)
+    let tables_types_reducers_procedures_views = (
+        tables,
+        types,
+        reducers,
+        procedures,
+        views,
+        reducer_return_types,
+        duplicate_reducer_return_types,
+    )
         .combine_errors()
-        .and_then(|(mut tables, types, reducers, procedures, views)| {
+        .and_then(|(mut tables, types, reducers, procedures, views, (), ())| {
             let ((reducers, procedures, views), ()) = (
                 check_function_names_are_unique(reducers, procedures, views),
                 check_non_procedure_misc_exports(misc_exports, &validator, &mut tables),
@@ -279,7 +305,8 @@ impl ModuleValidator<'_> {
                     .filter(|(_, unique_cols)| {
                         !indexes
                             .values()
-                            .any(|i| ColSet::from(i.algorithm.columns()) == **unique_cols)
+                            .filter_map(|i| i.algorithm.columns())
+                            .any(|cols| ColSet::from(cols) == **unique_cols)
                     })
                     .map(|(c, cols)| {
                         let constraint = c.name.clone();
@@ -369,14 +396,36 @@ impl ModuleValidator<'_> {
             .collect_all_errors()
     }
 
-    /// Validate a reducer definition.
-    fn validate_reducer_def(&mut self, reducer_def: RawReducerDefV9, reducer_id: ReducerId) -> Result<ReducerDef> {
+    /// Validate a reducer definition, along with its declared return types, if any.
+    fn validate_reducer_def(
+        &mut self,
+        reducer_def: RawReducerDefV9,
+        return_type: Option<RawReducerReturnTypeV9>,
+        reducer_id: ReducerId,
+    ) -> Result<ReducerDef> {
         let RawReducerDefV9 {
             name,
             params,
             lifecycle,
         } = reducer_def;
 
+        // Without a declaration, reducers return `()` or a `String` error message.
+        let (ok_return_type, err_return_type) = return_type
+            .map(|ty| (ty.ok_return_type, ty.err_return_type))
+            .unwrap_or_else(|| (AlgebraicType::unit(), AlgebraicType::String));
+        let ok_return_type_for_generate = self.validate_for_type_use(
+            &TypeLocation::ReducerReturn {
+                reducer_name: Cow::Borrowed(&name),
+            },
+            &ok_return_type,
+        );
+        let err_return_type_for_generate = self.validate_for_type_use(
+            &TypeLocation::ReducerError {
+                reducer_name: Cow::Borrowed(&name),
+            },
+            &err_return_type,
+        );
+
         let params_for_generate: Result<_> =
             self.params_for_generate(&params, |position, arg_name| TypeLocation::ReducerArg {
                 reducer_name: (&*name).into(),
@@ -397,7 +446,14 @@ impl ModuleValidator<'_> {
                 Some(_) => Err(ValidationError::DuplicateLifecycle { lifecycle }.into()),
             })
             .transpose();
-        let (name, params_for_generate, lifecycle) = (name, params_for_generate, lifecycle).combine_errors()?;
+        let (name, params_for_generate, lifecycle, ok_return_type_for_generate, err_return_type_for_generate) = (
+            name,
+            params_for_generate,
+            lifecycle,
+            ok_return_type_for_generate,
+            err_return_type_for_generate,
+        )
+            .combine_errors()?;
         Ok(ReducerDef {
             name,
             params: params.clone(),
@@ -406,6 +462,10 @@ impl ModuleValidator<'_> {
                 recursive: false, // A ProductTypeDef not stored in a Typespace cannot be recursive.
             },
             lifecycle,
+            ok_return_type,
+            ok_return_type_for_generate,
+            err_return_type,
+            err_return_type_for_generate,
         })
     }
 
@@ -883,7 +943,14 @@ impl TableValidator<'_, '_> {
             accessor_name,
         } = index;
 
-        let name = name.unwrap_or_else(|| generate_index_name(&self.raw_name, self.product_type, &algorithm));
+        let name = name.unwrap_or_else(|| {
+            generate_index_name(
+                &self.raw_name,
+                self.product_type,
+                self.module_validator.typespace,
+                &algorithm,
+            )
+        });
 
         let algorithm: Result<IndexAlgorithm> = match algorithm {
             RawIndexAlgorithm::BTree { columns } => self
@@ -910,6 +977,40 @@ impl TableValidator<'_, '_> {
                 }
                 Ok(DirectAlgorithm { column }.into())
             }),
+            RawIndexAlgorithm::BTreeExpr { keys } if keys.is_empty() => {
+                Err(ValidationError::IndexWithoutKeys { index: name.clone() }.into())
+            }
+            RawIndexAlgorithm::BTreeExpr { keys } => keys
+                .into_iter()
+                .map(|key| self.validate_index_key_expr(&name, key))
+                .collect_all_errors::<Vec<_>>()
+                .map(|keys| BTreeExprAlgorithm { keys: keys.into() }.into()),
+            RawIndexAlgorithm::PartialBTree { columns, filter } => {
+                let columns = self.validate_col_ids(&name, columns);
+                let resolved: Result<_> = IndexFilter::parse(&filter, self.product_type).map_err(|error| {
+                    ValidationError::InvalidIndexFilter {
+                        index: name.clone(),
+                        filter: filter.clone(),
+                        error: error.to_string().into(),
+                    }
+                    .into()
+                });
+                (columns, resolved)
+                    .combine_errors()
+                    .map(|(columns, _)| PartialBTreeAlgorithm { columns, filter }.into())
+            }
+            RawIndexAlgorithm::FullText { column } => self.validate_col_id(&name, column).and_then(|column| {
+                let field = &self.product_type.elements[column.idx()];
+                if !field.algebraic_type.is_string() {
+                    return Err(ValidationError::FullTextIndexOnBadType {
+                        index: name.clone(),
+                        column: field.name.clone().unwrap_or_else(|| column.idx().to_string().into()),
+                        ty: field.algebraic_type.clone().into(),
+                    }
+                    .into());
+                }
+                Ok(FullTextAlgorithm { column }.into())
+            }),
             _ => Err(ValidationError::HashIndexUnsupported { index: name.clone() }.into()),
         };
         let name = self.add_to_global_namespace(name);
@@ -924,6 +1025,48 @@ impl TableValidator<'_, '_> {
         })
     }
 
+    /// Validate a key expression of the index `index_name`.
+    ///
+    /// The path must descend through product types only,
+    /// and the function, if any, must accept the type found at its end.
+    fn validate_index_key_expr(&self, index_name: &RawIdentifier, key: RawIndexKeyExprV9) -> Result<IndexKeyExpr> {
+        let column = self.validate_col_id(index_name, key.column)?;
+        let column_name = self.raw_column_name(column).column;
+        let typespace = self.module_validator.typespace;
+
+        let mut ty = resolve_ref(typespace, &self.product_type.elements[column.idx()].algebraic_type);
+        for &field in &key.fields {
+            ty = match ty.as_product().and_then(|p| p.elements.get(field as usize)) {
+                Some(element) => resolve_ref(typespace, &element.algebraic_type),
+                None => {
+                    return Err(ValidationError::IndexKeyFieldNotFound {
+                        index: index_name.clone(),
+                        column: column_name,
+                        field,
+                        ty: ty.clone().into(),
+                    }
+                    .into())
+                }
+            };
+        }
+
+        let accepts = match key.function {
+            None => true,
+            Some(IndexKeyFunction::Lower) => ty.is_string(),
+            Some(_) => false,
+        };
+        if let (Some(function), false) = (key.function, accepts) {
+            return Err(ValidationError::IndexKeyFunctionOnBadType {
+                index: index_name.clone(),
+                function: function.name().into(),
+                ty: ty.clone().into(),
+            }
+            .into());
+        }
+
+        Ok(key.into())
+    }
+
     /// Validate a unique constraint definition.
     fn validate_constraint_def(&mut self, constraint: RawConstraintDefV9) -> Result<ConstraintDef> {
         let RawConstraintDefV9 { name, data } = constraint;
@@ -1096,15 +1239,58 @@ fn concat_column_names(table_type: &ProductType, selected: &ColList) -> String {
     selected.iter().map(|col| column_name(table_type, col)).join("_")
 }
 
+/// Resolve `ty` in `typespace` if it is a reference, or return it unchanged.
+fn resolve_ref<'a>(typespace: &'a Typespace, ty: &'a AlgebraicType) -> &'a AlgebraicType {
+    match ty {
+        AlgebraicType::Ref(r) => typespace.get(*r).unwrap_or(ty),
+        _ => ty,
+    }
+}
+
+/// Name a key expression of an index, e.g., `position_x` or `name_lower`.
+///
+/// Nested fields without a name, or which cannot be found, are named `field_{position}`.
+fn key_expr_name(table_type: &ProductType, typespace: &Typespace, key: &RawIndexKeyExprV9) -> String {
+    let mut names = vec![column_name(table_type, key.column)];
+    let mut ty = table_type
+        .elements
+        .get(key.column.idx())
+        .map(|column| resolve_ref(typespace, &column.algebraic_type));
+    for &field in &key.fields {
+        let element = ty
+            .and_then(AlgebraicType::as_product)
+            .and_then(|ty| ty.elements.get(field as usize));
+        names.push(match element.and_then(|element| element.name()) {
+            Some(field_name) => field_name.to_string(),
+            None => format!("field_{field}"),
+        });
+        ty = element.map(|element| resolve_ref(typespace, &element.algebraic_type));
+    }
+    names.extend(key.function.map(|function| function.name().to_string()));
+    names.join("_")
+}
+
 /// All indexes have this name format.
-pub fn generate_index_name(table_name: &str, table_type: &ProductType, algorithm: &RawIndexAlgorithm) -> RawIdentifier {
-    let (label, columns) = match algorithm {
-        RawIndexAlgorithm::BTree { columns } => ("btree", columns),
-        RawIndexAlgorithm::Direct { column } => ("direct", &col_list![*column]),
-        RawIndexAlgorithm::Hash { columns } => ("hash", columns),
+pub fn generate_index_name(
+    table_name: &str,
+    table_type: &ProductType,
+    typespace: &Typespace,
+    algorithm: &RawIndexAlgorithm,
+) -> RawIdentifier {
+    let (label, column_names) = match algorithm {
+        RawIndexAlgorithm::BTree { columns } => ("btree", concat_column_names(table_type, columns)),
+        RawIndexAlgorithm::Direct { column } => ("direct", concat_column_names(table_type, &col_list![*column])),
+        RawIndexAlgorithm::Hash { columns } => ("hash", concat_column_names(table_type, columns)),
+        RawIndexAlgorithm::PartialBTree { columns, .. } => ("partial", concat_column_names(table_type, columns)),
+        RawIndexAlgorithm::FullText { column } => ("fulltext", concat_column_names(table_type, &col_list![*column])),
+        RawIndexAlgorithm::BTreeExpr { keys } => (
+            "btree",
+            keys.iter()
+                .map(|key| key_expr_name(table_type, typespace, key))
+                .join("_"),
+        ),
         _ => unimplemented!("Unknown index algorithm {:?}", algorithm),
     };
-    let column_names = concat_column_names(table_type, columns);
     format!("{table_name}_{column_names}_idx_{label}").into()
 }
 
@@ -1243,11 +1429,38 @@ fn check_non_procedure_misc_exports(
             RawMiscModuleExportV9::Procedure(_proc) => {
                 unreachable!("Procedure defs should already have been sorted out of `misc_exports`")
             }
+            RawMiscModuleExportV9::ReducerReturnType(_ret) => {
+                unreachable!("Reducer return types should already have been sorted out of `misc_exports`")
+            }
             _ => unimplemented!("unknown misc export"),
         })
         .collect_all_errors::<()>()
 }
 
+/// Index the reducer return type declarations by reducer name,
+/// also returning an error for each reducer with more than one declaration.
+fn collect_reducer_return_types(
+    misc_exports: Vec<RawMiscModuleExportV9>,
+) -> (HashMap<RawIdentifier, RawReducerReturnTypeV9>, Result<()>) {
+    let mut return_types = HashMap::default();
+    let mut duplicates = Vec::new();
+    for misc_export in misc_exports {
+        let RawMiscModuleExportV9::ReducerReturnType(return_type) = misc_export else {
+            unreachable!(
+                "Already partitioned reducer return types separate from other `RawMiscModuleExportV9` variants"
+            );
+        };
+        if let Some(duplicate) = return_types.insert(return_type.reducer.clone(), return_type) {
+            duplicates.push(duplicate.reducer);
+        }
+    }
+    let duplicates = duplicates
+        .into_iter()
+        .map(|reducer| -> Result<()> { Err(ValidationError::DuplicateReducerReturnType { reducer }.into()) })
+        .collect_all_errors::<()>();
+    (return_types, duplicates)
+}
+
 fn process_column_default_value(
     cdv: &RawColumnDefaultValueV9,
     validator: &ModuleValidator,
@@ -1294,11 +1507,11 @@ mod tests {
     };
     use crate::def::{validate::Result, ModuleDef};
     use crate::def::{
-        BTreeAlgorithm, ConstraintData, ConstraintDef, DirectAlgorithm, FunctionKind, IndexDef, SequenceDef,
-        UniqueConstraintData,
+        BTreeAlgorithm, ConstraintData, ConstraintDef, DirectAlgorithm, FunctionKind, IndexDef, IndexKeyExpr,
+        SequenceDef, UniqueConstraintData,
     };
     use crate::error::*;
-    use crate::type_for_generate::ClientCodegenError;
+    use crate::type_for_generate::{AlgebraicTypeUse, ClientCodegenError};
 
     use itertools::Itertools;
     use spacetimedb_data_structures::expect_error_matching;
@@ -1306,8 +1519,12 @@ mod tests {
     use spacetimedb_lib::db::raw_def::*;
     use spacetimedb_lib::ScheduleAt;
     use spacetimedb_primitives::{ColId, ColList, ColSet};
-    use spacetimedb_sats::{AlgebraicType, AlgebraicTypeRef, AlgebraicValue, ProductType, SumValue};
-    use v9::{Lifecycle, RawIndexAlgorithm, RawModuleDefV9Builder, TableAccess, TableType};
+    use spacetimedb_sats::ser::Serialize;
+    use spacetimedb_sats::{bsatn, AlgebraicType, AlgebraicTypeRef, AlgebraicValue, ProductType, SumValue, Typespace};
+    use v9::{
+        IndexKeyFunction, Lifecycle, RawIndexAlgorithm, RawIndexKeyExprV9, RawModuleDefV9Builder, TableAccess,
+        TableType,
+    };
 
     /// This test attempts to exercise every successful path in the validation code.
     #[test]
@@ -1854,6 +2071,146 @@ mod tests {
         });
     }
 
+    #[test]
+    fn expression_index_on_nested_field() {
+        let mut builder = RawModuleDefV9Builder::new();
+        let position = AlgebraicType::product([("x", AlgebraicType::U32), ("y", AlgebraicType::U32)]);
+        builder
+            .build_table_with_new_type(
+                "Bananas",
+                ProductType::from([("position", position), ("name", AlgebraicType::String)]),
+                false,
+            )
+            .with_index(
+                v9::btree_expr([RawIndexKeyExprV9 {
+                    column: 0.into(),
+                    fields: vec![0],
+                    function: None,
+                }]),
+                "bananas_x",
+            )
+            .with_index(
+                v9::btree_expr([RawIndexKeyExprV9 {
+                    column: 1.into(),
+                    fields: vec![],
+                    function: Some(IndexKeyFunction::Lower),
+                }]),
+                "bananas_name",
+            )
+            .finish();
+        let def: ModuleDef = builder.finish().try_into().unwrap();
+        let table = def.table("Bananas").unwrap();
+
+        let index = &table.indexes["Bananas_position_x_idx_btree"];
+        assert!(index.index_algorithm.columns().is_none());
+        assert_eq!(
+            index.index_algorithm.key_exprs(),
+            Some(
+                &[IndexKeyExpr {
+                    column: 0.into(),
+                    fields: [0].into(),
+                    function: None,
+                }][..]
+            )
+        );
+        assert!(table.indexes.contains_key("Bananas_name_lower_idx_btree"));
+    }
+
+    #[test]
+    fn invalid_expression_index() {
+        let mut builder = RawModuleDefV9Builder::new();
+        let position = AlgebraicType::product([("x", AlgebraicType::U32), ("y", AlgebraicType::U32)]);
+        builder
+            .build_table_with_new_type(
+                "Bananas",
+                ProductType::from([("position", position), ("name", AlgebraicType::String)]),
+                false,
+            )
+            .with_index(
+                v9::btree_expr([RawIndexKeyExprV9 {
+                    column: 0.into(),
+                    fields: vec![2],
+                    function: None,
+                }]),
+                "bananas_z",
+            )
+            .with_index(
+                v9::btree_expr([RawIndexKeyExprV9 {
+                    column: 0.into(),
+                    fields: vec![0],
+                    function: Some(IndexKeyFunction::Lower),
+                }]),
+                "bananas_x",
+            )
+            .finish();
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(result, ValidationError::IndexKeyFieldNotFound { column, field, .. } => {
+            &column[..] == "position" && *field == 2
+        });
+        expect_error_matching!(result, ValidationError::IndexKeyFunctionOnBadType { function, .. } => {
+            &function[..] == "lower"
+        });
+    }
+
+    #[test]
+    fn partial_index() {
+        let mut builder = RawModuleDefV9Builder::new();
+        builder
+            .build_table_with_new_type(
+                "Bananas",
+                ProductType::from([("status", AlgebraicType::String), ("count", AlgebraicType::U32)]),
+                false,
+            )
+            .with_index(v9::partial_btree(1, "status = 'ripe'"), "bananas_ripe")
+            .finish();
+        let def: ModuleDef = builder.finish().try_into().unwrap();
+        let table = def.table("Bananas").unwrap();
+
+        let index = &table.indexes["Bananas_count_idx_partial"];
+        assert!(index.index_algorithm.columns().is_none());
+        let partial = index.index_algorithm.partial().unwrap();
+        assert_eq!(partial.columns, ColList::from(1));
+        assert_eq!(&*partial.filter, "status = 'ripe'");
+    }
+
+    #[test]
+    fn invalid_partial_index_filter() {
+        let mut builder = RawModuleDefV9Builder::new();
+        builder
+            .build_table_with_new_type(
+                "Bananas",
+                ProductType::from([("status", AlgebraicType::String), ("count", AlgebraicType::U32)]),
+                false,
+            )
+            .with_index(v9::partial_btree(1, "count = 'ripe'"), "bananas_ripe")
+            .finish();
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(result, ValidationError::InvalidIndexFilter { index, filter, .. } => {
+            &index[..] == "Bananas_count_idx_partial" && &filter[..] == "count = 'ripe'"
+        });
+    }
+
+    #[test]
+    fn full_text_index_only_on_strings() {
+        let mut builder = RawModuleDefV9Builder::new();
+        builder
+            .build_table_with_new_type(
+                "Bananas",
+                ProductType::from([("name", AlgebraicType::String), ("count", AlgebraicType::U32)]),
+                false,
+            )
+            .with_index(v9::full_text(0), "bananas_name")
+            .with_index(v9::full_text(1), "bananas_count")
+            .finish();
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(result, ValidationError::FullTextIndexOnBadType { index, column, .. } => {
+            &index[..] == "Bananas_count_idx_fulltext" && &column[..] == "count"
+        });
+    }
+
     #[test]
     fn one_auto_inc() {
         let mut builder = RawModuleDefV9Builder::new();
@@ -2086,4 +2443,125 @@ mod tests {
             &name[..] == "foo"
         });
     }
+
+    #[test]
+    fn reducer_return_types() {
+        let mut builder = RawModuleDefV9Builder::new();
+
+        let error_ref = builder.add_algebraic_type(
+            [],
+            "PlaceError",
+            AlgebraicType::simple_enum(["Occupied", "OutOfBounds"].into_iter()),
+            false,
+        );
+        builder.add_reducer("place", [("x", AlgebraicType::U32)].into(), None);
+        builder.add_reducer("reset", ProductType::unit(), None);
+        builder.add_reducer_return_type("place", AlgebraicType::U64, error_ref.into());
+
+        let def: ModuleDef = builder.finish().try_into().unwrap();
+
+        let place = def.reducer("place").unwrap();
+        assert!(place.has_typed_return());
+        assert_eq!(place.ok_return_type, AlgebraicType::U64);
+        assert_eq!(place.err_return_type, AlgebraicType::Ref(error_ref));
+        assert_eq!(place.err_return_type_for_generate, AlgebraicTypeUse::Ref(error_ref));
+
+        let reset = def.reducer("reset").unwrap();
+        assert!(!reset.has_typed_return());
+        assert_eq!(reset.ok_return_type, AlgebraicType::unit());
+        assert_eq!(reset.err_return_type, AlgebraicType::String);
+
+        // Only the declared return type survives the round trip.
+        let raw = v9::RawModuleDefV9::from(def);
+        assert_eq!(raw.misc_exports.len(), 1);
+        let def: ModuleDef = raw.try_into().unwrap();
+        assert_eq!(def.reducer("place").unwrap().ok_return_type, AlgebraicType::U64);
+        assert_eq!(
+            def.reducer("place").unwrap().err_return_type,
+            AlgebraicType::Ref(error_ref)
+        );
+    }
+
+    #[test]
+    fn decode_module_without_reducer_return_types() {
+        // Encode a `RawModuleDefV9` field by field, in the layout published by modules
+        // built before reducers could declare return types.
+        fn encode<T: Serialize + ?Sized>(bytes: &mut Vec<u8>, value: &T) {
+            bsatn::to_writer(bytes, value).unwrap();
+        }
+        let mut bytes = Vec::new();
+        encode(&mut bytes, Typespace::EMPTY);
+        encode(&mut bytes, &Vec::<v9::RawTableDefV9>::new());
+        // `reducers`: the `name`, `params` and `lifecycle` of each reducer, and nothing else.
+        encode(&mut bytes, &2u32);
+        encode(&mut bytes, "init");
+        encode(&mut bytes, &ProductType::unit());
+        encode(&mut bytes, &Some(Lifecycle::Init));
+        encode(&mut bytes, "place");
+        encode(&mut bytes, &ProductType::from([("x", AlgebraicType::U32)]));
+        encode(&mut bytes, &None::<Lifecycle>);
+        encode(&mut bytes, &Vec::<v9::RawTypeDefV9>::new());
+        encode(&mut bytes, &Vec::<v9::RawMiscModuleExportV9>::new());
+        encode(&mut bytes, &Vec::<v9::RawRowLevelSecurityDefV9>::new());
+
+        let raw: v9::RawModuleDefV9 = bsatn::from_slice(&bytes).unwrap();
+        assert_eq!(raw.reducers.len(), 2);
+        let def: ModuleDef = raw.try_into().unwrap();
+
+        let place = def.reducer("place").unwrap();
+        assert!(!place.has_typed_return());
+        assert_eq!(place.params, ProductType::from([("x", AlgebraicType::U32)]));
+        assert_eq!(place.ok_return_type, AlgebraicType::unit());
+        assert_eq!(place.err_return_type, AlgebraicType::String);
+        assert_eq!(def.reducer("init").unwrap().lifecycle, Some(Lifecycle::Init));
+    }
+
+    #[test]
+    fn reducer_return_type_not_valid_for_client_code_generation() {
+        let invalid_type = AlgebraicType::product([("a", AlgebraicType::U32)]);
+        let mut builder = RawModuleDefV9Builder::new();
+        builder.add_reducer("silly", ProductType::unit(), None);
+        builder.add_reducer_return_type("silly", invalid_type.clone(), AlgebraicType::String);
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(
+            result,
+            ValidationError::ClientCodegenError {
+                location,
+                error: ClientCodegenError::NonSpecialTypeNotAUse { ty }
+            } => {
+                location == &TypeLocation::ReducerReturn { reducer_name: "silly".into() } &&
+                ty.0 == invalid_type
+            }
+        );
+    }
+
+    #[test]
+    fn reducer_return_type_for_missing_reducer() {
+        let mut builder = RawModuleDefV9Builder::new();
+
+        builder.add_reducer("foo", ProductType::unit(), None);
+        builder.add_reducer_return_type("bar", AlgebraicType::U64, AlgebraicType::String);
+
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(result, ValidationError::ReducerReturnTypeForMissingReducer { reducer } => {
+            &reducer[..] == "bar"
+        });
+    }
+
+    #[test]
+    fn duplicate_reducer_return_types() {
+        let mut builder = RawModuleDefV9Builder::new();
+
+        builder.add_reducer("foo", ProductType::unit(), None);
+        builder.add_reducer_return_type("foo", AlgebraicType::U64, AlgebraicType::String);
+        builder.add_reducer_return_type("foo", AlgebraicType::U32, AlgebraicType::String);
+
+        let result: Result<ModuleDef> = builder.finish().try_into();
+
+        expect_error_matching!(result, ValidationError::DuplicateReducerReturnType { reducer } => {
+            &reducer[..] == "foo"
+        });
+    }
 }
@@ -67,6 +67,33 @@ pub enum ValidationError {
         column: RawIdentifier,
         ty: PrettyAlgebraicType,
     },
+    #[error("Full-text index does not support type `{ty}` in column `{column}` in index `{index}`")]
+    FullTextIndexOnBadType {
+        index: RawIdentifier,
+        column: RawIdentifier,
+        ty: PrettyAlgebraicType,
+    },
+    #[error("Index `{index}` has no key expressions")]
+    IndexWithoutKeys { index: RawIdentifier },
+    #[error("Key path of index `{index}` on column `{column}` has no field at position {field} of type `{ty}`")]
+    IndexKeyFieldNotFound {
+        index: RawIdentifier,
+        column: RawIdentifier,
+        field: u16,
+        ty: PrettyAlgebraicType,
+    },
+    #[error("Key function `{function}` of index `{index}` does not support type `{ty}`")]
+    IndexKeyFunctionOnBadType {
+        index: RawIdentifier,
+        function: Box<str>,
+        ty: PrettyAlgebraicType,
+    },
+    #[error("Filter `{filter}` of index `{index}` is invalid: {error}")]
+    InvalidIndexFilter {
+        index: RawIdentifier,
+        filter: Box<str>,
+        error: Box<str>,
+    },
     #[error("def `{def}` has duplicate columns: {columns:?}")]
     DuplicateColumns { def: RawIdentifier, columns: ColList },
     #[error("invalid sequence column type: {column} with type `{column_type:?}` in sequence `{sequence}`")]
@@ -138,6 +165,10 @@ pub enum ValidationError {
     TableNotFound { table: RawIdentifier },
     #[error("Name {name} is used for multiple reducers, procedures and/or views")]
     DuplicateFunctionName { name: Identifier },
+    #[error("Return types declared for reducer {reducer}, which does not exist")]
+    ReducerReturnTypeForMissingReducer { reducer: RawIdentifier },
+    #[error("Multiple return types declared for reducer {reducer}")]
+    DuplicateReducerReturnType { reducer: RawIdentifier },
 }
 
 /// A wrapper around an `AlgebraicType` that implements `fmt::Display`.
@@ -193,6 +224,10 @@ pub enum TypeLocation<'a> {
         position: usize,
         arg_name: Option<Cow<'a, str>>,
     },
+    /// A reducer return type.
+    ReducerReturn { reducer_name: Cow<'a, str> },
+    /// A reducer error type.
+    ReducerError { reducer_name: Cow<'a, str> },
     /// A procedure return type.
     ProcedureReturn { procedure_name: Cow<'a, str> },
     /// A view return type.
@@ -235,6 +270,12 @@ impl TypeLocation<'_> {
                 position,
                 arg_name: arg_name.map(|s| s.to_string().into()),
             },
+            Self::ReducerReturn { reducer_name } => TypeLocation::ReducerReturn {
+                reducer_name: reducer_name.to_string().into(),
+            },
+            Self::ReducerError { reducer_name } => TypeLocation::ReducerError {
+                reducer_name: reducer_name.to_string().into(),
+            },
             Self::ProcedureReturn { procedure_name } => TypeLocation::ProcedureReturn {
                 procedure_name: procedure_name.to_string().into(),
             },
@@ -283,6 +324,12 @@ impl fmt::Display for TypeLocation<'_> {
                 }
                 Ok(())
             }
+            TypeLocation::ReducerReturn { reducer_name } => {
+                write!(f, "reducer `{reducer_name}` return value")
+            }
+            TypeLocation::ReducerError { reducer_name } => {
+                write!(f, "reducer `{reducer_name}` error value")
+            }
             TypeLocation::ProcedureReturn { procedure_name } => {
                 write!(f, "procedure `{procedure_name}` return value")
             }
@@ -6,6 +6,7 @@ pub mod auto_migrate;
 pub mod def;
 pub mod error;
 pub mod identifier;
+pub mod openapi;
 pub mod relation;
 pub mod schema;
 pub mod type_for_generate;
@@ -0,0 +1,390 @@
+//! Machine-readable descriptions of a database's HTTP API.
+//!
+//! [`openapi_document`] describes the per-database routes of the HTTP API as an OpenAPI 3.1 document,
+//! with a JSON Schema for the arguments of each reducer and the row type of each table.
+//! The schemas match the SATS-JSON encoding, i.e., the JSON which [`spacetimedb_sats::serde::SerdeWrapper`]
+//! produces and accepts for values of those types.
+
+use crate::def::ModuleDef;
+use serde_json::{json, Map, Value};
+use spacetimedb_data_structures::map::HashMap;
+use spacetimedb_lib::version;
+use spacetimedb_sats::{AlgebraicType, AlgebraicTypeRef, ProductType, SumType};
+
+/// The media type of JSON request and response bodies.
+const JSON: &str = "application/json";
+
+/// Describes the HTTP API of `database`, a database running `module`, as an OpenAPI 3.1 document.
+///
+/// The paths of the document are rooted at the host, e.g., `/v1/database/{database}/call/{reducer}`.
+/// The schemas of the module's types are defined under `components/schemas`,
+/// and the extension `x-spacetimedb-tables` maps the name of each table to the schema of its rows.
+pub fn openapi_document(module: &ModuleDef, database: &str) -> Value {
+    let schemas = JsonSchemas::new(module);
+    let root = format!("/v1/database/{database}");
+
+    let mut paths = Map::new();
+    for reducer in module.reducers() {
+        // Lifecycle reducers can't be called over HTTP.
+        if reducer.lifecycle.is_some() {
+            continue;
+        }
+        let name = &*reducer.name;
+        let mut responses = json!({
+            "200": { "description": "The reducer committed." },
+            "400": { "description": "The arguments did not match the reducer's parameters." },
+            "402": { "description": "The database's energy budget was exhausted." },
+            "404": { "description": "No such database or reducer." },
+            "530": {
+                "description": "The reducer failed, and its transaction was rolled back.",
+                "content": { "text/plain": { "schema": { "type": "string" } } },
+            },
+        });
+        // Reducers which declare return types respond with their return or error value as JSON.
+        if !reducer.ok_return_type.is_unit() {
+            responses["200"]["content"] = json!({ JSON: { "schema": schemas.for_type(&reducer.ok_return_type) } });
+        }
+        if reducer.err_return_type != AlgebraicType::String {
+            responses["530"]["content"] = json!({ JSON: { "schema": schemas.for_type(&reducer.err_return_type) } });
+        }
+        paths.insert(
+            format!("{root}/call/{name}"),
+            json!({
+                "post": {
+                    "operationId": format!("call_{name}"),
+                    "summary": format!("Call the reducer `{name}`."),
+                    "requestBody": {
+                        "required": true,
+                        "content": { JSON: { "schema": schemas.for_args(&reducer.params) } },
+                    },
+                    "responses": responses,
+                },
+            }),
+        );
+    }
+
+    paths.insert(
+        format!("{root}/sql"),
+        json!({
+            "post": {
+                "operationId": "sql",
+                "summary": "Run SQL statements against the database.",
+                "requestBody": {
+                    "required": true,
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                },
+                "responses": {
+                    "200": {
+                        "description": "The result of each statement.",
+                        "content": { JSON: { "schema": sql_response_schema() } },
+                    },
+                    "400": { "description": "A statement was invalid." },
+                    "404": { "description": "No such database." },
+                },
+            },
+        }),
+    );
+    paths.insert(
+        format!("{root}/schema"),
+        json!({
+            "get": {
+                "operationId": "schema",
+                "summary": "Get the module's definition.",
+                "parameters": [{
+                    "name": "version",
+                    "in": "query",
+                    "required": true,
+                    "schema": { "type": "string", "enum": ["9"] },
+                }],
+                "responses": {
+                    "200": {
+                        "description": "The module's `RawModuleDefV9`.",
+                        "content": { JSON: { "schema": { "type": "object" } } },
+                    },
+                    "404": { "description": "No such database." },
+                },
+            },
+        }),
+    );
+    paths.insert(
+        format!("{root}/openapi.json"),
+        json!({
+            "get": {
+                "operationId": "openapi",
+                "summary": "Get this document.",
+                "responses": {
+                    "200": {
+                        "description": "An OpenAPI document describing the database's HTTP API.",
+                        "content": { JSON: { "schema": { "type": "object" } } },
+                    },
+                    "404": { "description": "No such database." },
+                },
+            },
+        }),
+    );
+
+    let tables = module
+        .tables()
+        .map(|table| (table.name.to_string(), schemas.for_ref(table.product_type_ref)))
+        .collect::<Map<_, _>>();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": format!("SpacetimeDB database {database}"),
+            "version": version::spacetimedb_lib_version(),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas.components(),
+            "securitySchemes": {
+                "token": { "type": "http", "scheme": "bearer" },
+            },
+        },
+        // Requests without a token are assigned a new identity.
+        "security": [{ "token": [] }, {}],
+        "x-spacetimedb-tables": tables,
+    })
+}
+
+/// The schema of the response to a `POST /sql` request, an array of `SqlStmtResult`s.
+fn sql_response_schema() -> Value {
+    let count = json!({ "type": "integer", "minimum": 0 });
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "schema": { "type": "object", "description": "The `ProductType` of the rows." },
+                "rows": { "type": "array", "items": { "type": "array" } },
+                "total_duration_micros": count,
+                "stats": {
+                    "type": "object",
+                    "properties": {
+                        "rows_inserted": count,
+                        "rows_deleted": count,
+                        "rows_updated": count,
+                    },
+                },
+            },
+            "required": ["schema", "rows", "total_duration_micros"],
+        },
+    })
+}
+
+/// Converts the types of a module into JSON Schemas.
+struct JsonSchemas<'a> {
+    module: &'a ModuleDef,
+    /// The name under `components/schemas` of each type in the typespace.
+    ref_names: HashMap<AlgebraicTypeRef, String>,
+}
+
+impl<'a> JsonSchemas<'a> {
+    fn new(module: &'a ModuleDef) -> Self {
+        let ref_names = (0..module.typespace().types.len() as u32)
+            .map(AlgebraicTypeRef)
+            .map(|r| {
+                let name = match module.type_def_from_ref(r) {
+                    // Component names may not contain `:`.
+                    Some((name, _)) => name.to_string().replace("::", "."),
+                    None => format!("_type{}", r.idx()),
+                };
+                (r, name)
+            })
+            .collect();
+        Self { module, ref_names }
+    }
+
+    /// The schemas of every type in the typespace, keyed by name.
+    fn components(&self) -> Map<String, Value> {
+        let mut components = self
+            .module
+            .typespace()
+            .types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| (self.ref_names[&AlgebraicTypeRef(i as u32)].clone(), self.for_type(ty)))
+            .collect::<Vec<_>>();
+        components.sort_by(|(a, _), (b, _)| a.cmp(b));
+        components.into_iter().collect()
+    }
+
+    fn for_ref(&self, r: AlgebraicTypeRef) -> Value {
+        json!({ "$ref": format!("#/components/schemas/{}", self.ref_names[&r]) })
+    }
+
+    /// The schema of the arguments to a reducer with `params`.
+    ///
+    /// Arguments may be passed positionally, as an array, or by name, as an object.
+    fn for_args(&self, params: &ProductType) -> Value {
+        let positional = self.for_tuple(params);
+        if params.elements.iter().all(|elem| elem.name.is_some()) && !params.elements.is_empty() {
+            json!({ "oneOf": [positional, self.for_product(params)] })
+        } else {
+            positional
+        }
+    }
+
+    fn for_type(&self, ty: &AlgebraicType) -> Value {
+        match ty {
+            AlgebraicType::Ref(r) => self.for_ref(*r),
+            AlgebraicType::Sum(sum) => self.for_sum(sum),
+            AlgebraicType::Product(product) => self.for_product(product),
+            AlgebraicType::Array(array) if array.elem_ty.is_u8() => json!({
+                "type": "string",
+                "contentEncoding": "base16",
+                "pattern": "^([0-9a-fA-F]{2})*$",
+            }),
+            AlgebraicType::Array(array) => json!({ "type": "array", "items": self.for_type(&array.elem_ty) }),
+            AlgebraicType::String => json!({ "type": "string" }),
+            AlgebraicType::Bool => json!({ "type": "boolean" }),
+            AlgebraicType::I8 => int_schema(i8::MIN, i8::MAX),
+            AlgebraicType::U8 => int_schema(u8::MIN, u8::MAX),
+            AlgebraicType::I16 => int_schema(i16::MIN, i16::MAX),
+            AlgebraicType::U16 => int_schema(u16::MIN, u16::MAX),
+            AlgebraicType::I32 => int_schema(i32::MIN, i32::MAX),
+            AlgebraicType::U32 => int_schema(u32::MIN, u32::MAX),
+            AlgebraicType::I64 => int_schema(i64::MIN, i64::MAX),
+            AlgebraicType::U64 => int_schema(u64::MIN, u64::MAX),
+            // Bounds this wide don't round-trip through most JSON parsers, so are left out.
+            AlgebraicType::I128 | AlgebraicType::U128 => json!({ "type": "integer" }),
+            // 256-bit integers are written as `0x`-prefixed hex strings, and may also be read from numbers.
+            AlgebraicType::I256 | AlgebraicType::U256 => json!({
+                "anyOf": [
+                    { "type": "string", "pattern": "^-?(0x[0-9a-fA-F]+|[0-9]+)$" },
+                    { "type": "integer" },
+                ],
+            }),
+            AlgebraicType::F32 | AlgebraicType::F64 => json!({ "type": "number" }),
+        }
+    }
+
+    /// Products with named elements are objects, e.g., `{"x": 1, "y": 2}`,
+    /// including the special types, e.g., `{"__identity__": "0x..."}`.
+    /// Products with unnamed elements are arrays.
+    fn for_product(&self, product: &ProductType) -> Value {
+        if product.elements.is_empty() {
+            // The unit value is written as `{}`, but `[]` is accepted too.
+            return json!({ "type": ["object", "array"], "maxProperties": 0, "maxItems": 0 });
+        }
+        if product.elements.iter().any(|elem| elem.name.is_none()) {
+            return self.for_tuple(product);
+        }
+        let properties = product
+            .elements
+            .iter()
+            .map(|elem| (elem.name().unwrap().to_owned(), self.for_type(&elem.algebraic_type)))
+            .collect::<Map<_, _>>();
+        let required = product.elements.iter().map(|elem| elem.name()).collect::<Vec<_>>();
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+
+    fn for_tuple(&self, product: &ProductType) -> Value {
+        let items = product
+            .elements
+            .iter()
+            .map(|elem| self.for_type(&elem.algebraic_type))
+            .collect::<Vec<_>>();
+        json!({
+            "type": "array",
+            "prefixItems": items,
+            "minItems": items.len(),
+            "maxItems": items.len(),
+        })
+    }
+
+    /// Sums are objects with a single property, named after the variant, whose value is the variant's payload,
+    /// e.g., `{"some": 5}` or `{"none": {}}`.
+    fn for_sum(&self, sum: &SumType) -> Value {
+        let mut variants = sum
+            .variants
+            .iter()
+            .enumerate()
+            .map(|(tag, variant)| {
+                // Variants without names are identified by their tag.
+                let name = variant.name().map_or_else(|| tag.to_string(), str::to_owned);
+                json!({
+                    "type": "object",
+                    "properties": { &name: self.for_type(&variant.algebraic_type) },
+                    "required": [name],
+                    "additionalProperties": false,
+                })
+            })
+            .collect::<Vec<_>>();
+        // `null` is also accepted for `none`.
+        if sum.is_option() {
+            variants.push(json!({ "type": "null" }));
+        }
+        json!({ "oneOf": variants })
+    }
+}
+
+fn int_schema(min: impl Into<Value>, max: impl Into<Value>) -> Value {
+    json!({ "type": "integer", "minimum": min.into(), "maximum": max.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacetimedb_lib::db::raw_def::v9::RawModuleDefV9Builder;
+    use spacetimedb_lib::Identity;
+
+    fn module_def() -> ModuleDef {
+        let mut builder = RawModuleDefV9Builder::new();
+        let identity = builder.add_type::<Identity>();
+        let person = builder
+            .build_table_with_new_type(
+                "person",
+                ProductType::from([
+                    ("id", AlgebraicType::U32),
+                    ("owner", identity.clone()),
+                    ("nickname", AlgebraicType::option(AlgebraicType::String)),
+                ]),
+                true,
+            )
+            .finish();
+        builder.add_reducer(
+            "add_person",
+            ProductType::from([
+                ("person", AlgebraicType::Ref(person)),
+                ("avatar", AlgebraicType::bytes()),
+            ]),
+            None,
+        );
+        builder
+            .finish()
+            .try_into()
+            .expect("should be a valid module definition")
+    }
+
+    #[test]
+    fn describes_reducers_and_tables() {
+        let doc = openapi_document(&module_def(), "people");
+
+        let call = &doc["paths"]["/v1/database/people/call/add_person"]["post"];
+        let args = &call["requestBody"]["content"][JSON]["schema"]["oneOf"];
+        assert_eq!(
+            args[0]["prefixItems"][0],
+            json!({ "$ref": "#/components/schemas/person" })
+        );
+        assert_eq!(args[0]["prefixItems"][1]["type"], "string");
+        assert_eq!(args[1]["required"], json!(["person", "avatar"]));
+
+        assert_eq!(
+            doc["x-spacetimedb-tables"]["person"],
+            json!({ "$ref": "#/components/schemas/person" })
+        );
+        let person = &doc["components"]["schemas"]["person"]["properties"];
+        assert_eq!(person["id"], int_schema(0, u32::MAX));
+        assert_eq!(person["owner"]["required"], json!(["__identity__"]));
+        let nickname = person["nickname"]["oneOf"].as_array().unwrap();
+        assert_eq!(nickname.len(), 3);
+        assert_eq!(nickname[0]["properties"]["some"], json!({ "type": "string" }));
+        assert_eq!(nickname[2], json!({ "type": "null" }));
+    }
+}
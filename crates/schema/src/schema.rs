@@ -304,14 +304,17 @@ impl TableSchema {
             .map(|x| x.into())
     }
 
-    /// Retrieve the column ids for this index id
-    pub fn col_list_for_index_id(&self, index_id: IndexId) -> ColList {
+    /// Retrieve the column ids for this index id.
+    ///
+    /// Returns `None` if there is no such index,
+    /// or if its keys are not a projection of the row's columns,
+    /// see [`IndexAlgorithm::columns`](crate::def::IndexAlgorithm::columns).
+    pub fn col_list_for_index_id(&self, index_id: IndexId) -> Option<ColList> {
         self.indexes
             .iter()
             .find(|schema| schema.index_id == index_id)
-            .map(|schema| schema.index_algorithm.columns())
+            .and_then(|schema| schema.index_algorithm.columns())
             .map(|cols| ColList::from_iter(cols.iter()))
-            .unwrap_or_else(ColList::empty)
     }
 
     /// Is there a unique constraint for this set of columns?
@@ -354,9 +357,12 @@ impl TableSchema {
                     ConstraintData::Unique(unique) => (unique.columns.clone().into(), Constraints::unique()),
                 }
             })
-            .chain(self.indexes.iter().map(|x| match &x.index_algorithm {
-                IndexAlgorithm::BTree(btree) => (btree.columns.clone(), Constraints::indexed()),
-                IndexAlgorithm::Direct(direct) => (direct.column.into(), Constraints::indexed()),
+            .chain(self.indexes.iter().filter_map(|x| match &x.index_algorithm {
+                IndexAlgorithm::BTree(btree) => Some((btree.columns.clone(), Constraints::indexed())),
+                IndexAlgorithm::Direct(direct) => Some((direct.column.into(), Constraints::indexed())),
+                // Computed keys and tokens don't index the columns themselves,
+                // and partial indexes don't index every row.
+                IndexAlgorithm::BTreeExpr(_) | IndexAlgorithm::PartialBTree(_) | IndexAlgorithm::FullText(_) => None,
             }))
             .chain(
                 self.sequences
@@ -419,6 +425,9 @@ impl TableSchema {
                 let cols = match &x.index_algorithm {
                     IndexAlgorithm::BTree(btree) => btree.columns.clone(),
                     IndexAlgorithm::Direct(direct) => direct.column.into(),
+                    IndexAlgorithm::BTreeExpr(btree_expr) => btree_expr.keys.iter().map(|key| key.column).collect(),
+                    IndexAlgorithm::PartialBTree(partial) => partial.columns.clone(),
+                    IndexAlgorithm::FullText(full_text) => full_text.column.into(),
                 };
                 (DefType::Index, x.index_name.clone(), cols)
             }))
@@ -40,6 +40,7 @@ use spacetimedb_table::{
     blob_store::{BlobHash, BlobStore, HashMapBlobStore},
     page::Page,
     page_pool::PagePool,
+    pages::PageStore,
     table::Table,
 };
 use std::fs;
@@ -248,6 +249,8 @@ impl Snapshot {
     /// Write a single `page` into the on-disk object repository `object_repo`.
     ///
     /// `hash` must be the content hash of `page`, and must be stored in `page.unmodified_hash()`.
+    /// A `page` of `None` has been evicted from memory,
+    /// and is only reloaded from the `page_store` if it cannot be hardlinked from the `prev_snapshot`.
     ///
     /// Returns the `hash` for convenient use with [`Iter::map`] in [`Self::write_table`].
     ///
@@ -255,15 +258,26 @@ impl Snapshot {
     /// from that previous snapshot into `object_repo` rather than creating a fresh object.
     fn write_page(
         object_repo: &DirTrie,
-        page: &Page,
+        page: Option<&Page>,
+        page_store: Option<&dyn PageStore>,
         hash: blake3::Hash,
         prev_snapshot: Option<&DirTrie>,
         counter: &mut CountCreated,
     ) -> Result<blake3::Hash, SnapshotError> {
-        debug_assert!(page.unmodified_hash().copied() == Some(hash));
+        debug_assert!(page.is_none_or(|page| page.unmodified_hash().copied() == Some(hash)));
+
+        let can_hardlink = |repo: &DirTrie| repo.contains_entry(hash.as_bytes());
+        let reloaded = match (page, page_store) {
+            (None, Some(page_store)) if !can_hardlink(object_repo) && !prev_snapshot.is_some_and(can_hardlink) => {
+                Some(page_store.read_page(&hash)?)
+            }
+            _ => None,
+        };
+        let page = page.or(reloaded.as_deref());
+        let contents = || bsatn::to_vec(page.expect("evicted page should be reloaded or hardlinked")).unwrap();
 
         object_repo
-            .hardlink_or_write(prev_snapshot, hash.as_bytes(), || bsatn::to_vec(page).unwrap(), counter)
+            .hardlink_or_write(prev_snapshot, hash.as_bytes(), contents, counter)
             .map_err(|cause| SnapshotError::WriteObject {
                 ty: ObjectType::Page(hash),
                 dest_repo: object_repo.root().to_path_buf(),
@@ -284,9 +298,12 @@ impl Snapshot {
         prev_snapshot: Option<&DirTrie>,
         counter: &mut CountCreated,
     ) -> Result<(), SnapshotError> {
+        let page_store = table.page_store().cloned();
         let pages = table
             .iter_pages_with_hashes()
-            .map(|(hash, page)| Self::write_page(object_repo, page, hash, prev_snapshot, counter))
+            .map(|(hash, page)| {
+                Self::write_page(object_repo, page, page_store.as_deref(), hash, prev_snapshot, counter)
+            })
             .collect::<Result<Vec<blake3::Hash>, SnapshotError>>()?;
 
         self.tables.push(TableEntry {
@@ -400,6 +417,46 @@ impl Snapshot {
         Ok(blob_store)
     }
 
+    /// Read the page with content hash `hash` from the `object_repo`.
+    ///
+    /// Fails if the page file is missing or corrupted,
+    /// as detected by comparing the hash of its bytes to `hash`.
+    fn read_page(object_repo: &DirTrie, hash: &blake3::Hash, page_pool: &PagePool) -> Result<Box<Page>, SnapshotError> {
+        // Read the BSATN bytes of the on-disk page object.
+        let buf = object_repo
+            .read_entry(hash.as_bytes())
+            .map_err(|cause| SnapshotError::ReadObject {
+                ty: ObjectType::Page(*hash),
+                source_repo: object_repo.root().to_path_buf(),
+                cause,
+            })?;
+
+        // Deserialize the bytes into a `Page`.
+        let page = page_pool.take_deserialize_from(&buf);
+        let mut page = page.map_err(|cause| SnapshotError::Deserialize {
+            ty: ObjectType::Page(*hash),
+            source_repo: object_repo.root().to_path_buf(),
+            cause,
+        })?;
+
+        // Compute the hash of the page,
+        // saving it so that the page can be evicted again until it's modified.
+        let computed_hash = page.save_or_get_content_hash();
+
+        // Compare the computed hash to the one recorded in the `Snapshot`,
+        // and fail if they do not match.
+        if *hash != computed_hash {
+            return Err(SnapshotError::HashMismatch {
+                ty: ObjectType::Page(*hash),
+                expected: *hash.as_bytes(),
+                computed: *computed_hash.as_bytes(),
+                source_repo: object_repo.root().to_path_buf(),
+            });
+        }
+
+        Ok(page)
+    }
+
     /// Read all the pages referenced by `pages` from the `object_repo`.
     ///
     /// Fails if any of the pages files is missing or corrupted,
@@ -411,40 +468,7 @@ impl Snapshot {
     ) -> Result<Vec<Box<Page>>, SnapshotError> {
         pages
             .iter()
-            .map(|hash| {
-                // Read the BSATN bytes of the on-disk page object.
-                let buf = object_repo
-                    .read_entry(hash.as_bytes())
-                    .map_err(|cause| SnapshotError::ReadObject {
-                        ty: ObjectType::Page(*hash),
-                        source_repo: object_repo.root().to_path_buf(),
-                        cause,
-                    })?;
-
-                // Deserialize the bytes into a `Page`.
-                let page = page_pool.take_deserialize_from(&buf);
-                let page = page.map_err(|cause| SnapshotError::Deserialize {
-                    ty: ObjectType::Page(*hash),
-                    source_repo: object_repo.root().to_path_buf(),
-                    cause,
-                })?;
-
-                // Compute the hash of the page.
-                let computed_hash = page.content_hash();
-
-                // Compare the computed hash to the one recorded in the `Snapshot`,
-                // and fail if they do not match.
-                if *hash != computed_hash {
-                    return Err(SnapshotError::HashMismatch {
-                        ty: ObjectType::Page(*hash),
-                        expected: *hash.as_bytes(),
-                        computed: *computed_hash.as_bytes(),
-                        source_repo: object_repo.root().to_path_buf(),
-                    });
-                }
-
-                Ok::<Box<Page>, SnapshotError>(page)
-            })
+            .map(|hash| Self::read_page(object_repo, hash, page_pool))
             .collect()
     }
 
@@ -459,27 +483,6 @@ impl Snapshot {
         ))
     }
 
-    /// Reconstruct all the table data from `self`,
-    /// reading pages from files in the `object_repo`.
-    ///
-    /// This method cannot construct [`Table`] objects
-    /// because doing so requires knowledge of the system tables' schemas
-    /// to compute the schemas of the user-defined tables
-    ///
-    /// Fails if any object file referenced in `self` (as a page or large blob)
-    /// is missing or corrupted,
-    /// as detected by comparing the hash of its bytes to the hash recorded in `self`.
-    fn reconstruct_tables(
-        &self,
-        object_repo: &DirTrie,
-        page_pool: &PagePool,
-    ) -> Result<BTreeMap<TableId, Vec<Box<Page>>>, SnapshotError> {
-        self.tables
-            .iter()
-            .map(|tbl| Self::reconstruct_one_table(object_repo, tbl, page_pool))
-            .collect()
-    }
-
     /// The number of objects in this snapshot, both blobs and pages.
     pub fn total_objects(&self) -> usize {
         self.blobs.len() + self.tables.iter().map(|table| table.pages.len()).sum::<usize>()
@@ -503,6 +506,27 @@ impl Snapshot {
     }
 }
 
+/// A [`PageStore`] reading evicted pages from the object repository of a snapshot,
+/// obtained from [`SnapshotRepository::page_store`].
+pub struct SnapshotPageStore {
+    object_repo: DirTrie,
+    page_pool: PagePool,
+}
+
+impl fmt::Debug for SnapshotPageStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotPageStore")
+            .field("object_repo", &self.object_repo.root())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PageStore for SnapshotPageStore {
+    fn read_page(&self, hash: &blake3::Hash) -> std::io::Result<Box<Page>> {
+        Snapshot::read_page(&self.object_repo, hash, &self.page_pool).map_err(std::io::Error::other)
+    }
+}
+
 /// Collect the size of the snapshot and the number of objects in it.
 #[derive(Clone, Default)]
 pub struct SnapshotSize {
@@ -790,6 +814,19 @@ impl SnapshotRepository {
         DirTrie::open(snapshot_dir.objects().0)
     }
 
+    /// Open the objects of the snapshot referring to `tx_offset` as a [`PageStore`],
+    /// from which pages evicted from memory can be reloaded.
+    ///
+    /// Reloaded pages are taken from `page_pool`.
+    ///
+    /// The snapshot must have been created by this process, e.g. by [`Self::create_snapshot`],
+    /// or have been restored from, e.g. by [`Self::read_snapshot_lazy`],
+    /// and must not be removed while the store is in use.
+    pub fn page_store(&self, tx_offset: TxOffset, page_pool: PagePool) -> Result<SnapshotPageStore, SnapshotError> {
+        let object_repo = Self::object_repo(&self.snapshot_dir_path(tx_offset))?;
+        Ok(SnapshotPageStore { object_repo, page_pool })
+    }
+
     /// Read a snapshot contained in self referring to `tx_offset`,
     /// verify its hashes,
     /// and parse it into an in-memory structure [`ReconstructedSnapshot`]
@@ -819,6 +856,31 @@ impl SnapshotRepository {
         &self,
         tx_offset: TxOffset,
         page_pool: &PagePool,
+    ) -> Result<ReconstructedSnapshot, SnapshotError> {
+        self.read_snapshot_with(tx_offset, page_pool, |_| true)
+    }
+
+    /// Like [`Self::read_snapshot`],
+    /// but only reads the pages of tables for which `load_eagerly` returns `true`.
+    ///
+    /// The pages of the other tables are left in the object repository,
+    /// with their hashes in [`ReconstructedSnapshot::lazy_tables`],
+    /// to be loaded on demand from [`ReconstructedSnapshot::page_store`].
+    /// Their integrity is hence only verified once they're loaded.
+    pub fn read_snapshot_lazy(
+        &self,
+        tx_offset: TxOffset,
+        page_pool: &PagePool,
+        load_eagerly: impl Fn(TableId) -> bool,
+    ) -> Result<ReconstructedSnapshot, SnapshotError> {
+        self.read_snapshot_with(tx_offset, page_pool, load_eagerly)
+    }
+
+    fn read_snapshot_with(
+        &self,
+        tx_offset: TxOffset,
+        page_pool: &PagePool,
+        load_eagerly: impl Fn(TableId) -> bool,
     ) -> Result<ReconstructedSnapshot, SnapshotError> {
         let snapshot_dir = self.snapshot_dir_path(tx_offset);
         let lockfile = Lockfile::lock_path(&snapshot_dir);
@@ -848,7 +910,17 @@ impl SnapshotRepository {
 
         let blob_store = snapshot.reconstruct_blob_store(&object_repo)?;
 
-        let tables = snapshot.reconstruct_tables(&object_repo, page_pool)?;
+        let (eager, lazy): (Vec<_>, Vec<_>) = snapshot.tables.iter().partition(|tbl| load_eagerly(tbl.table_id));
+        let tables = eager
+            .into_iter()
+            .map(|tbl| Snapshot::reconstruct_one_table(&object_repo, tbl, page_pool))
+            .collect::<Result<_, _>>()?;
+        let lazy_tables: BTreeMap<_, _> = lazy.into_iter().map(|tbl| (tbl.table_id, tbl.pages.clone())).collect();
+        let page_store = if lazy_tables.is_empty() {
+            None
+        } else {
+            Some(self.page_store(tx_offset, page_pool.clone())?)
+        };
 
         Ok(ReconstructedSnapshot {
             database_identity: snapshot.database_identity,
@@ -857,6 +929,8 @@ impl SnapshotRepository {
             module_abi_version: snapshot.module_abi_version,
             blob_store,
             tables,
+            lazy_tables,
+            page_store,
             compress_type,
         })
     }
@@ -1235,6 +1309,14 @@ pub struct ReconstructedSnapshot {
     /// so the schema of user-defined tables can be recovered
     /// given knowledge of the schema of `st_table` and `st_column`.
     pub tables: BTreeMap<TableId, Vec<Box<Page>>>,
+    /// The content hashes of the pages of tables which were not loaded into memory,
+    /// when read by [`SnapshotRepository::read_snapshot_lazy`].
+    ///
+    /// Disjoint from `tables`.
+    pub lazy_tables: BTreeMap<TableId, Vec<blake3::Hash>>,
+    /// The store from which to load the pages in `lazy_tables`,
+    /// present iff `lazy_tables` is non-empty.
+    pub page_store: Option<SnapshotPageStore>,
     /// If the snapshot was compressed or not.
     pub compress_type: CompressType,
 }
@@ -63,7 +63,7 @@ async fn can_sync_a_snapshot() -> anyhow::Result<()> {
     // Assert that the copied snapshot is valid.
     let pool = PagePool::new_for_test();
     let dst_snapshot_full = dst_repo.read_snapshot(src.offset, &pool)?;
-    Locking::restore_from_snapshot(dst_snapshot_full, pool)?;
+    Locking::restore_from_snapshot(dst_snapshot_full, pool, None)?;
 
     // Let's also check that running `synchronize_snapshot` again does nothing.
     let stats = synchronize_snapshot(blob_provider.clone(), dst_path.clone(), src_snapshot.clone()).await?;
@@ -113,17 +113,23 @@ pub enum SqlExpr {
     Param(Parameter),
     /// Qualified column ref
     Field(SqlIdent, SqlIdent),
+    /// Qualified ref to a nested field of a column, e.g. `t.position.x`.
+    /// The path starts with the column name.
+    Path(SqlIdent, Box<[SqlIdent]>),
     /// A binary infix expression
     Bin(Box<SqlExpr>, Box<SqlExpr>, BinOp),
     /// A binary logic expression
     Log(Box<SqlExpr>, Box<SqlExpr>, LogOp),
+    /// A full-text search `match(t.a, 'query')`,
+    /// true if the string `t.a` contains every token of the query
+    Match(Box<SqlExpr>, Box<str>),
 }
 
 impl SqlExpr {
     pub fn qualify_vars(self, with: SqlIdent) -> Self {
         match self {
             Self::Var(name) => Self::Field(with, name),
-            Self::Lit(..) | Self::Field(..) | Self::Param(..) => self,
+            Self::Lit(..) | Self::Field(..) | Self::Path(..) | Self::Param(..) => self,
             Self::Bin(a, b, op) => Self::Bin(
                 Box::new(a.qualify_vars(with.clone())),
                 Box::new(b.qualify_vars(with)),
@@ -134,6 +140,7 @@ impl SqlExpr {
                 Box::new(b.qualify_vars(with)),
                 op,
             ),
+            Self::Match(a, query) => Self::Match(Box::new(a.qualify_vars(with)), query),
         }
     }
 
@@ -141,6 +148,7 @@ impl SqlExpr {
         match self {
             Self::Var(_) => true,
             Self::Bin(a, b, _) | Self::Log(a, b, _) => a.has_unqualified_vars() || b.has_unqualified_vars(),
+            Self::Match(a, _) => a.has_unqualified_vars(),
             _ => false,
         }
     }
@@ -149,16 +157,17 @@ impl SqlExpr {
     /// We need to know in order to hash subscription queries correctly.
     pub fn has_parameter(&self) -> bool {
         match self {
-            Self::Lit(_) | Self::Var(_) | Self::Field(..) => false,
+            Self::Lit(_) | Self::Var(_) | Self::Field(..) | Self::Path(..) => false,
             Self::Param(Parameter::Sender) => true,
             Self::Bin(a, b, _) | Self::Log(a, b, _) => a.has_parameter() || b.has_parameter(),
+            Self::Match(a, _) => a.has_parameter(),
         }
     }
 
     /// Replace the `:sender` parameter with the [Identity] it represents
     pub fn resolve_sender(self, sender_identity: Identity) -> Self {
         match self {
-            Self::Lit(_) | Self::Var(_) | Self::Field(..) => self,
+            Self::Lit(_) | Self::Var(_) | Self::Field(..) | Self::Path(..) => self,
             Self::Param(Parameter::Sender) => {
                 Self::Lit(SqlLiteral::Hex(String::from(sender_identity.to_hex()).into_boxed_str()))
             }
@@ -173,6 +182,7 @@ impl SqlExpr {
                 Box::new(b.resolve_sender(sender_identity)),
                 op,
             ),
+            Self::Match(a, query) => Self::Match(Box::new(a.resolve_sender(sender_identity)), query),
         }
     }
 }
@@ -19,6 +19,8 @@ pub enum SqlAst {
     Set(SqlSet),
     /// SHOW var
     Show(SqlShow),
+    /// EXPLAIN [ ANALYZE ] SELECT ...
+    Explain(SqlExplain),
 }
 
 impl SqlAst {
@@ -38,6 +40,10 @@ impl SqlAst {
                 table: with.clone(),
                 filter: filter.map(|expr| expr.qualify_vars(with)),
             }),
+            Self::Explain(SqlExplain { analyze, select }) => Self::Explain(SqlExplain {
+                analyze,
+                select: select.qualify_vars(),
+            }),
             _ => self,
         }
     }
@@ -45,6 +51,9 @@ impl SqlAst {
     pub fn find_unqualified_vars(self) -> SqlParseResult<Self> {
         match self {
             Self::Select(select) => select.find_unqualified_vars().map(Self::Select),
+            Self::Explain(SqlExplain { analyze, select }) => select
+                .find_unqualified_vars()
+                .map(|select| Self::Explain(SqlExplain { analyze, select })),
             _ => Ok(self),
         }
     }
@@ -55,6 +64,10 @@ impl SqlAst {
             Self::Select(select) => Self::Select(select.resolve_sender(sender_identity)),
             Self::Update(update) => Self::Update(update.resolve_sender(sender_identity)),
             Self::Delete(delete) => Self::Delete(delete.resolve_sender(sender_identity)),
+            Self::Explain(SqlExplain { analyze, select }) => Self::Explain(SqlExplain {
+                analyze,
+                select: select.resolve_sender(sender_identity),
+            }),
             _ => self,
         }
     }
@@ -159,3 +172,11 @@ pub struct SqlSet(pub SqlIdent, pub SqlLiteral);
 /// SHOW var
 #[derive(Debug)]
 pub struct SqlShow(pub SqlIdent);
+
+/// EXPLAIN [ ANALYZE ] select
+#[derive(Debug)]
+pub struct SqlExplain {
+    /// Should the query be executed, so that row counts and timings can be reported?
+    pub analyze: bool,
+    pub select: SqlSelect,
+}
@@ -2,8 +2,8 @@ use std::fmt::Display;
 
 use sqlparser::{
     ast::{
-        BinaryOperator, Expr, Function, ObjectName, Query, Select, SelectItem, SetExpr, TableFactor, TableWithJoins,
-        Value,
+        BinaryOperator, Expr, Function, ObjectName, Query, Select, SelectItem, SetExpr, Statement, TableFactor,
+        TableWithJoins, Value,
     },
     parser::ParserError,
 };
@@ -77,6 +77,8 @@ pub enum SqlUnsupported {
     Empty,
     #[error("Names must be qualified when using joins")]
     UnqualifiedNames,
+    #[error("Only SELECT statements can be explained: {0}")]
+    Explain(Box<Statement>),
 }
 
 impl SqlUnsupported {
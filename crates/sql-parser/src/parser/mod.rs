@@ -56,22 +56,18 @@ trait RelParser {
                 left,
                 op: BinaryOperator::Eq,
                 right,
-            })) if matches!(*left, Expr::Identifier(..) | Expr::CompoundIdentifier(..))
-                && matches!(*right, Expr::Identifier(..) | Expr::CompoundIdentifier(..)) =>
-            {
-                Ok(SqlJoin {
-                    var,
-                    alias,
-                    on: Some(parse_expr(
-                        Expr::BinaryOp {
-                            left,
-                            op: BinaryOperator::Eq,
-                            right,
-                        },
-                        0,
-                    )?),
-                })
-            }
+            })) if is_column_ref(&left) && is_column_ref(&right) => Ok(SqlJoin {
+                var,
+                alias,
+                on: Some(parse_expr(
+                    Expr::BinaryOp {
+                        left,
+                        op: BinaryOperator::Eq,
+                        right,
+                    },
+                    0,
+                )?),
+            }),
             _ => Err(SqlUnsupported::JoinType.into()),
         }
     }
@@ -194,6 +190,16 @@ pub(crate) fn parse_project_elem(item: SelectItem) -> SqlParseResult<ProjectElem
     }
 }
 
+/// Is this expression a possibly qualified reference to a column,
+/// as opposed to a nested field of one?
+fn is_column_ref(expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(..) => true,
+        Expr::CompoundIdentifier(idents) => idents.len() == 2,
+        _ => false,
+    }
+}
+
 /// Parse a column projection
 pub(crate) fn parse_proj(expr: Expr) -> SqlParseResult<ProjectExpr> {
     match expr {
@@ -243,6 +249,11 @@ fn parse_expr(expr: Expr, depth: usize) -> SqlParseResult<SqlExpr> {
             let field = idents.swap_remove(0).into();
             Ok(SqlExpr::Field(table, field))
         }
+        Expr::CompoundIdentifier(idents) if idents.len() > 2 => {
+            let mut idents = idents.into_iter().map(SqlIdent::from);
+            let table = idents.next().unwrap();
+            Ok(SqlExpr::Path(table, idents.collect()))
+        }
         Expr::BinaryOp {
             left,
             op: BinaryOperator::And,
@@ -266,10 +277,39 @@ fn parse_expr(expr: Expr, depth: usize) -> SqlParseResult<SqlExpr> {
             let r = parse_expr(*right, depth + 1)?;
             Ok(SqlExpr::Bin(Box::new(l), Box::new(r), parse_binop(op)?))
         }
+        Expr::Function(function) => match as_match(&function) {
+            Some((field, query)) => {
+                let field = parse_expr(field.clone(), depth + 1)?;
+                Ok(SqlExpr::Match(Box::new(field), query.into()))
+            }
+            None => Err(SqlUnsupported::Expr(Expr::Function(function)).into()),
+        },
         _ => Err(SqlUnsupported::Expr(expr).into()),
     }
 }
 
+/// Returns the field and query of a full-text search `match(field, 'query')`
+fn as_match(function: &Function) -> Option<(&Expr, &str)> {
+    let Function {
+        name: ObjectName(name),
+        args,
+        over: None,
+        distinct: false,
+        special: false,
+        order_by,
+    } = function
+    else {
+        return None;
+    };
+    match (&name[..], &args[..]) {
+        (
+            [Ident { value, .. }],
+            [FunctionArg::Unnamed(FunctionArgExpr::Expr(field)), FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Value(Value::SingleQuotedString(query))))],
+        ) if value.eq_ignore_ascii_case("match") && order_by.is_empty() => Some((field, query)),
+        _ => None,
+    }
+}
+
 /// Parse an optional scalar expression
 pub(crate) fn parse_expr_opt(opt: Option<Expr>) -> SqlParseResult<Option<SqlExpr>> {
     opt.map(|expr| parse_expr(expr, 0)).transpose()
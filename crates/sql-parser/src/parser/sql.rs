@@ -8,6 +8,11 @@
 //!     | update
 //!     | set
 //!     | show
+//!     | explain
+//!     ;
+//!
+//! explain
+//!     = EXPLAIN [ ANALYZE ] select
 //!     ;
 //!
 //! insert
@@ -137,7 +142,7 @@ use sqlparser::{
 };
 
 use crate::ast::{
-    sql::{SqlAst, SqlDelete, SqlInsert, SqlSelect, SqlSet, SqlShow, SqlUpdate, SqlValues},
+    sql::{SqlAst, SqlDelete, SqlExplain, SqlInsert, SqlSelect, SqlSet, SqlShow, SqlUpdate, SqlValues},
     SqlIdent,
 };
 
@@ -218,6 +223,19 @@ fn parse_statement(stmt: Statement) -> SqlParseResult<SqlAst> {
             value,
         } => Ok(SqlAst::Set(parse_set_var(variable, value)?)),
         Statement::ShowVariable { variable } => Ok(SqlAst::Show(SqlShow(parse_parts(variable)?))),
+        Statement::Explain {
+            describe_alias: false,
+            analyze,
+            verbose: false,
+            statement,
+            format: None,
+        } => match *statement {
+            Statement::Query(query) => Ok(SqlAst::Explain(SqlExplain {
+                analyze,
+                select: SqlParser::parse_query(*query)?,
+            })),
+            statement => Err(SqlUnsupported::Explain(Box::new(statement)).into()),
+        },
         _ => Err(SqlUnsupported::feature(stmt).into()),
     }
 }
@@ -430,6 +448,18 @@ mod tests {
             "select a.* from t as a, s as b where a.id = b.id and b.c = 1",
             // Joins require qualified vars
             "select t.* from t join s on int = u32",
+            // Only queries can be explained
+            "explain delete from t",
+            // EXPLAIN options
+            "explain verbose select * from t",
+            // Projections of nested fields
+            "select t.position.x from t",
+            // Joins on nested fields
+            "select t.* from t join s on t.position.x = s.x",
+            // Full-text queries must be string literals
+            "select * from t where match(name, query)",
+            // Other functions
+            "select * from t where lower(name) = 'x'",
         ] {
             assert!(parse_sql(sql).is_err());
         }
@@ -449,6 +479,12 @@ mod tests {
             "update t set a = 1, b = 2",
             "update t set a = 1, b = 2 where c = 3",
             "update t set a = 1, b = 2 where x = :sender",
+            "explain select a from t",
+            "explain analyze select t.* from t join s on t.id = s.id where s.x = :sender",
+            "select * from t where t.position.x = 1",
+            "select t.* from t join s on t.id = s.id where s.position.x = 1 and s.position.y < 2",
+            "select * from t where match(name, 'red sword')",
+            "select * from t where MATCH(t.name, 'red') and price < 10",
         ] {
             assert!(parse_sql(sql).is_ok());
         }
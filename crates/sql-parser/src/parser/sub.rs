@@ -57,13 +57,14 @@ use sqlparser::{
     ast::{GroupByExpr, Query, Select, SetExpr, Statement},
     dialect::PostgreSqlDialect,
     parser::Parser,
+    tokenizer::Token,
 };
 
-use crate::ast::sub::SqlSelect;
+use crate::ast::{sub::SqlSelect, SqlExpr};
 
 use super::{
     errors::{SqlUnsupported, SubscriptionUnsupported},
-    parse_expr_opt, parse_projection, RelParser, SqlParseResult,
+    parse_expr, parse_expr_opt, parse_projection, RelParser, SqlParseResult,
 };
 
 /// Parse a SQL string
@@ -78,6 +79,15 @@ pub fn parse_subscription(sql: &str) -> SqlParseResult<SqlSelect> {
     }
 }
 
+/// Parse a standalone `predicate`, e.g. the filter of a partial index.
+/// Unlike a WHERE clause, its column references are left unqualified.
+pub fn parse_predicate(sql: &str) -> SqlParseResult<SqlExpr> {
+    let mut parser = Parser::new(&PostgreSqlDialect {}).try_with_sql(sql)?;
+    let expr = parser.parse_expr()?;
+    parser.expect_token(&Token::EOF)?;
+    parse_expr(expr, 0)
+}
+
 /// Parse a SQL query
 fn parse_statement(stmt: Statement) -> SqlParseResult<SqlSelect> {
     match stmt {
@@ -152,7 +162,7 @@ fn parse_select(select: Select) -> SqlParseResult<SqlSelect> {
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::sub::parse_subscription;
+    use crate::parser::sub::{parse_predicate, parse_subscription};
 
     #[test]
     fn unsupported() {
@@ -182,4 +192,14 @@ mod tests {
             assert!(parse_subscription(sql).is_ok());
         }
     }
+
+    #[test]
+    fn predicates() {
+        for sql in ["a = 1", "a = 1 and (b > 2 or c <> 'x')", "active"] {
+            assert!(parse_predicate(sql).is_ok());
+        }
+        for sql in ["", "a = 1 b", "select * from t", "a in (1, 2)"] {
+            assert!(parse_predicate(sql).is_err());
+        }
+    }
 }
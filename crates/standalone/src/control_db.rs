@@ -6,12 +6,18 @@ use sled::transaction::{
 use spacetimedb::energy;
 use spacetimedb::identity::Identity;
 use spacetimedb::messages::control_db::{Database, EnergyBalance, Node, Replica};
+use spacetimedb_client_api::auth::RevocationList;
 
+use spacetimedb_client_api_messages::auth_policy::AuthPolicy;
+use spacetimedb_client_api_messages::collaborators::{Collaborator, DatabaseRole};
+use spacetimedb_client_api_messages::energy::EnergyUsageRecord;
 use spacetimedb_client_api_messages::name::{
     DomainName, DomainParsingError, InsertDomainResult, RegisterTldResult, SetDomainsResult, Tld, TldRef,
 };
+use spacetimedb_client_api_messages::tokens::ServiceToken;
 use spacetimedb_lib::bsatn;
 use spacetimedb_paths::standalone::ControlDbDir;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(test)]
 mod tests;
@@ -393,6 +399,179 @@ impl ControlDb {
         Ok(None)
     }
 
+    /// Returns the collaborators of the database with identity `database_identity`.
+    ///
+    /// The owner of the database is not included.
+    pub fn get_collaborators(&self, database_identity: &Identity) -> Result<Vec<Collaborator>> {
+        let tree = self.db.open_tree("collaborators")?;
+        let value = tree.get(database_identity.to_byte_array())?;
+        if let Some(value) = value {
+            let vec: Vec<Collaborator> = serde_json::from_slice(&value[..])?;
+            return Ok(vec);
+        }
+        Ok(vec![])
+    }
+
+    /// Returns the role `identity` was granted on the database with identity
+    /// `database_identity`, if any.
+    pub fn get_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        identity: &Identity,
+    ) -> Result<Option<DatabaseRole>> {
+        Ok(self
+            .get_collaborators(database_identity)?
+            .into_iter()
+            .find(|c| &c.identity == identity)
+            .map(|c| c.role))
+    }
+
+    /// Grant `role` to `identity` on the database with identity `database_identity`,
+    /// replacing any role previously granted to `identity`.
+    pub fn set_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        identity: Identity,
+        role: DatabaseRole,
+    ) -> Result<()> {
+        let tree = self.db.open_tree("collaborators")?;
+        let key = database_identity.to_byte_array();
+        tree.transaction(|tx| {
+            let mut collaborators: Vec<Collaborator> = match tx.get(key)? {
+                Some(value) => serde_json::from_slice(&value[..]).map_err(|e| {
+                    log::error!("Control database corruption: invalid collaborators in `collaborators` tree: {e}");
+                    ConflictableTransactionError::Abort(Error::from(e))
+                })?,
+                None => vec![],
+            };
+            match collaborators.iter_mut().find(|c| c.identity == identity) {
+                Some(existing) => existing.role = role,
+                None => collaborators.push(Collaborator { identity, role }),
+            }
+            tx.insert(&key, serde_json::to_vec(&collaborators).unwrap())?;
+            Ok::<_, ConflictableTransactionError<Error>>(())
+        })
+        .map_err(|e| match e {
+            TransactionError::Storage(e) => Error::Database(e),
+            TransactionError::Abort(e) => e,
+        })
+    }
+
+    /// Revoke any role granted to `identity` on the database with identity
+    /// `database_identity`.
+    ///
+    /// Returns `true` if `identity` was a collaborator.
+    pub fn remove_collaborator(&self, database_identity: &Identity, identity: &Identity) -> Result<bool> {
+        let tree = self.db.open_tree("collaborators")?;
+        let key = database_identity.to_byte_array();
+        tree.transaction(|tx| {
+            let Some(value) = tx.get(key)? else {
+                return Ok::<_, ConflictableTransactionError<Error>>(false);
+            };
+            let mut collaborators: Vec<Collaborator> = serde_json::from_slice(&value[..]).map_err(|e| {
+                log::error!("Control database corruption: invalid collaborators in `collaborators` tree: {e}");
+                ConflictableTransactionError::Abort(Error::from(e))
+            })?;
+            let len = collaborators.len();
+            collaborators.retain(|c| &c.identity != identity);
+            if collaborators.is_empty() {
+                tx.remove(&key)?;
+            } else {
+                tx.insert(&key, serde_json::to_vec(&collaborators).unwrap())?;
+            }
+            Ok(collaborators.len() != len)
+        })
+        .map_err(|e| match e {
+            TransactionError::Storage(e) => Error::Database(e),
+            TransactionError::Abort(e) => e,
+        })
+    }
+
+    /// Revoke all roles granted on the database with identity `database_identity`.
+    pub fn delete_collaborators(&self, database_identity: &Identity) -> Result<()> {
+        let tree = self.db.open_tree("collaborators")?;
+        tree.remove(database_identity.to_byte_array())?;
+        Ok(())
+    }
+
+    /// Returns the auth policy of the database with identity `database_identity`.
+    pub fn get_auth_policy(&self, database_identity: &Identity) -> Result<AuthPolicy> {
+        let tree = self.db.open_tree("auth_policies")?;
+        let value = tree.get(database_identity.to_byte_array())?;
+        if let Some(value) = value {
+            let policy: AuthPolicy = serde_json::from_slice(&value[..])?;
+            return Ok(policy);
+        }
+        Ok(AuthPolicy::default())
+    }
+
+    /// Replace the auth policy of the database with identity `database_identity`.
+    pub fn set_auth_policy(&self, database_identity: &Identity, policy: &AuthPolicy) -> Result<()> {
+        let tree = self.db.open_tree("auth_policies")?;
+        let key = database_identity.to_byte_array();
+        if policy.is_unrestricted() {
+            tree.remove(key)?;
+        } else {
+            tree.insert(key, serde_json::to_vec(policy)?)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the service token with the given `id`, if any.
+    pub fn get_service_token(&self, id: &str) -> Result<Option<ServiceToken>> {
+        let tree = self.db.open_tree("service_tokens")?;
+        let value = tree.get(id.as_bytes())?;
+        if let Some(value) = value {
+            let token: ServiceToken = serde_json::from_slice(&value[..])?;
+            return Ok(Some(token));
+        }
+        Ok(None)
+    }
+
+    /// Returns all service tokens created on behalf of `owner_identity`,
+    /// including revoked and expired ones.
+    pub fn get_service_tokens_by_owner(&self, owner_identity: &Identity) -> Result<Vec<ServiceToken>> {
+        let tree = self.db.open_tree("service_tokens")?;
+        let mut tokens = Vec::new();
+        for entry in tree.iter() {
+            let (_, value) = entry?;
+            let token: ServiceToken = serde_json::from_slice(&value[..])?;
+            if &token.owner_identity == owner_identity {
+                tokens.push(token);
+            }
+        }
+        Ok(tokens)
+    }
+
+    pub fn insert_service_token(&self, token: &ServiceToken) -> Result<()> {
+        let tree = self.db.open_tree("service_tokens")?;
+        tree.insert(token.id.as_bytes(), serde_json::to_vec(token)?)?;
+        Ok(())
+    }
+
+    /// Mark the service token with the given `id` as revoked.
+    ///
+    /// Returns `false` if no such token exists.
+    pub fn revoke_service_token(&self, id: &str) -> Result<bool> {
+        let tree = self.db.open_tree("service_tokens")?;
+        tree.transaction(|tx| {
+            let Some(value) = tx.get(id.as_bytes())? else {
+                return Ok::<_, ConflictableTransactionError<Error>>(false);
+            };
+            let mut token: ServiceToken = serde_json::from_slice(&value[..]).map_err(|e| {
+                log::error!("Control database corruption: invalid token in `service_tokens` tree: {e}");
+                ConflictableTransactionError::Abort(Error::from(e))
+            })?;
+            token.revoked = true;
+            tx.insert(id.as_bytes(), serde_json::to_vec(&token).unwrap())?;
+            Ok(true)
+        })
+        .map_err(|e| match e {
+            TransactionError::Storage(e) => Error::Database(e),
+            TransactionError::Abort(e) => e,
+        })
+    }
+
     pub fn get_replicas(&self) -> Result<Vec<Replica>> {
         let tree = self.db.open_tree("replica")?;
         let mut replicas = Vec::new();
@@ -544,17 +723,7 @@ impl ControlDb {
     pub fn get_energy_balance(&self, identity: &Identity) -> Result<Option<energy::EnergyBalance>> {
         let tree = self.db.open_tree("energy_budget")?;
         let value = tree.get(identity.to_byte_array())?;
-        if let Some(value) = value {
-            let arr = <[u8; 16]>::try_from(value.as_ref()).map_err(|_| bsatn::DecodeError::BufferLength {
-                for_type: "Identity",
-                expected: 16,
-                given: value.as_ref().len(),
-            })?;
-            let balance = i128::from_be_bytes(arr);
-            Ok(Some(energy::EnergyBalance::new(balance)))
-        } else {
-            Ok(None)
-        }
+        value.map(|value| decode_energy_balance(&value)).transpose()
     }
 
     /// Update the stored current budget for a identity.
@@ -566,6 +735,141 @@ impl ControlDb {
 
         Ok(())
     }
+
+    /// Subtract `amount` from the stored balance of `identity`, if it has one.
+    ///
+    /// Returns the new balance, or `None` if `identity` has no stored balance,
+    /// in which case its energy use is not metered.
+    pub fn debit_energy_balance(
+        &self,
+        identity: &Identity,
+        amount: energy::EnergyQuanta,
+    ) -> Result<Option<energy::EnergyBalance>> {
+        let tree = self.db.open_tree("energy_budget")?;
+        let value = tree.update_and_fetch(identity.to_byte_array(), |value| {
+            let value = value?;
+            let Ok(balance) = decode_energy_balance(value) else {
+                // Leave corrupt entries alone, for `get_energy_balance` to report.
+                return Some(value.to_vec());
+            };
+            Some(balance.saturating_sub_energy(amount).get().to_be_bytes().to_vec())
+        })?;
+        value.map(|value| decode_energy_balance(&value)).transpose()
+    }
+
+    /// Add `usage`, the aggregated energy use of one of the reducers of
+    /// the database `usage.database_identity`, to the ledger of its owner, `owner_identity`.
+    ///
+    /// Calls are aggregated per reducer over periods of [`ENERGY_LEDGER_PERIOD`],
+    /// so `usage.period_start` is rounded down to the start of its period.
+    pub fn record_energy_usage(&self, owner_identity: &Identity, usage: &EnergyUsageRecord) -> Result<()> {
+        let tree = self.db.open_tree("energy_ledger")?;
+        let period_start = energy_ledger_period_start(usage.period_start);
+        let mut key = energy_ledger_key_prefix(owner_identity, period_start);
+        key.extend_from_slice(&usage.database_identity.to_byte_array());
+        key.extend_from_slice(usage.reducer.as_bytes());
+
+        let mut error = None;
+        tree.update_and_fetch(key, |value| {
+            let mut record = match value.map(serde_json::from_slice::<EnergyUsageRecord>).transpose() {
+                Ok(record) => record.unwrap_or_else(|| EnergyUsageRecord {
+                    database_identity: usage.database_identity,
+                    reducer: usage.reducer.clone(),
+                    period_start,
+                    calls: 0,
+                    energy_used: 0,
+                    execution_duration: Duration::ZERO,
+                }),
+                Err(e) => {
+                    error = Some(e);
+                    return value.map(<[u8]>::to_vec);
+                }
+            };
+            record.calls = record.calls.saturating_add(usage.calls);
+            record.energy_used = record.energy_used.saturating_add(usage.energy_used);
+            record.execution_duration = record.execution_duration.saturating_add(usage.execution_duration);
+            Some(serde_json::to_vec(&record).unwrap())
+        })?;
+        match error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the records of all periods which ended before `before` from the energy ledger.
+    ///
+    /// Returns the number of records removed.
+    pub fn prune_energy_ledger(&self, before: SystemTime) -> Result<usize> {
+        let tree = self.db.open_tree("energy_ledger")?;
+        let before = before.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut batch = sled::Batch::default();
+        let mut removed = 0;
+        for key in tree.iter().keys() {
+            let key = key?;
+            let Some(secs) = key.get(32..40) else { continue };
+            let period_start = u64::from_be_bytes(secs.try_into().unwrap());
+            if period_start.saturating_add(ENERGY_LEDGER_PERIOD.as_secs()) <= before {
+                batch.remove(key);
+                removed += 1;
+            }
+        }
+        tree.apply_batch(batch)?;
+        Ok(removed)
+    }
+
+    /// Returns the energy ledger of `owner_identity`, oldest first,
+    /// starting with the period which includes `since`.
+    pub fn get_energy_usage(&self, owner_identity: &Identity, since: SystemTime) -> Result<Vec<EnergyUsageRecord>> {
+        let tree = self.db.open_tree("energy_ledger")?;
+        let owner_prefix = owner_identity.to_byte_array();
+        let start = energy_ledger_key_prefix(owner_identity, energy_ledger_period_start(since));
+        let mut records = Vec::new();
+        for entry in tree.range(start..) {
+            let (key, value) = entry?;
+            if !key.starts_with(&owner_prefix) {
+                break;
+            }
+            records.push(serde_json::from_slice(&value[..])?);
+        }
+        Ok(records)
+    }
+}
+
+/// The length of the periods over which the energy ledger aggregates usage.
+pub const ENERGY_LEDGER_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// How long records are kept in the energy ledger before being pruned.
+pub const ENERGY_LEDGER_RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+pub(crate) fn energy_ledger_period_start(at: SystemTime) -> SystemTime {
+    let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    UNIX_EPOCH + Duration::from_secs(secs - secs % ENERGY_LEDGER_PERIOD.as_secs())
+}
+
+/// Keys of the `energy_ledger` tree are the owner identity, followed by the start of the period,
+/// so that the ledger of an owner can be scanned in chronological order,
+/// followed by the database identity and the reducer name.
+fn energy_ledger_key_prefix(owner_identity: &Identity, period_start: SystemTime) -> Vec<u8> {
+    let secs = period_start.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let mut key = owner_identity.to_byte_array().to_vec();
+    key.extend_from_slice(&secs.to_be_bytes());
+    key
+}
+
+fn decode_energy_balance(value: &[u8]) -> Result<energy::EnergyBalance> {
+    let arr = <[u8; 16]>::try_from(value).map_err(|_| bsatn::DecodeError::BufferLength {
+        for_type: "EnergyBalance",
+        expected: 16,
+        given: value.len(),
+    })?;
+    Ok(energy::EnergyBalance::new(i128::from_be_bytes(arr)))
+}
+
+impl RevocationList for ControlDb {
+    fn is_revoked(&self, token_id: &str) -> anyhow::Result<bool> {
+        // Tokens we have no record of are treated as revoked.
+        Ok(self.get_service_token(token_id)?.is_none_or(|token| token.revoked))
+    }
 }
 
 mod compat {
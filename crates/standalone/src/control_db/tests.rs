@@ -152,3 +152,161 @@ fn test_decode() -> ResultTest<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_collaborators() -> anyhow::Result<()> {
+    let tmp = TempDir::with_prefix("collaborators")?;
+    let cdb = ControlDb::at(tmp.path())?;
+
+    let db = Identity::ZERO;
+    assert_eq!(cdb.get_collaborators(&db)?, vec![]);
+    assert_eq!(cdb.get_collaborator_role(&db, &ALICE)?, None);
+
+    cdb.set_collaborator_role(&db, *ALICE, DatabaseRole::ReadOnly)?;
+    cdb.set_collaborator_role(&db, *BOB, DatabaseRole::Developer)?;
+    assert_eq!(cdb.get_collaborator_role(&db, &ALICE)?, Some(DatabaseRole::ReadOnly));
+    assert_eq!(cdb.get_collaborator_role(&db, &BOB)?, Some(DatabaseRole::Developer));
+
+    // Granting again replaces the previous role.
+    cdb.set_collaborator_role(&db, *ALICE, DatabaseRole::Admin)?;
+    assert_eq!(cdb.get_collaborators(&db)?.len(), 2);
+    assert_eq!(cdb.get_collaborator_role(&db, &ALICE)?, Some(DatabaseRole::Admin));
+
+    assert!(cdb.remove_collaborator(&db, &ALICE)?);
+    assert!(!cdb.remove_collaborator(&db, &ALICE)?);
+    assert_eq!(cdb.get_collaborator_role(&db, &ALICE)?, None);
+    assert_eq!(cdb.get_collaborator_role(&db, &BOB)?, Some(DatabaseRole::Developer));
+
+    cdb.delete_collaborators(&db)?;
+    assert_eq!(cdb.get_collaborators(&db)?, vec![]);
+
+    let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+    Ok(())
+}
+
+#[test]
+fn test_service_tokens() -> anyhow::Result<()> {
+    use spacetimedb_client_api_messages::collaborators::DatabaseAction;
+    use spacetimedb_client_api_messages::tokens::TokenScope;
+
+    let tmp = TempDir::with_prefix("service-tokens")?;
+    let cdb = ControlDb::at(tmp.path())?;
+
+    let token = ServiceToken {
+        id: "ci".into(),
+        owner_identity: *ALICE,
+        description: Some("publish from CI".into()),
+        scope: TokenScope {
+            databases: vec![Identity::ZERO],
+            actions: vec![DatabaseAction::Publish],
+        },
+        created_at: std::time::SystemTime::UNIX_EPOCH,
+        expires_at: None,
+        revoked: false,
+    };
+    assert!(cdb.get_service_token("ci")?.is_none());
+    assert!(!cdb.revoke_service_token("ci")?);
+
+    cdb.insert_service_token(&token)?;
+    assert_eq!(cdb.get_service_token("ci")?.map(|t| t.scope), Some(token.scope.clone()));
+    assert_eq!(cdb.get_service_tokens_by_owner(&ALICE)?.len(), 1);
+    assert!(cdb.get_service_tokens_by_owner(&BOB)?.is_empty());
+
+    assert!(cdb.revoke_service_token("ci")?);
+    assert!(cdb.get_service_token("ci")?.is_some_and(|t| t.revoked));
+
+    let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+    Ok(())
+}
+
+#[test]
+fn test_auth_policy() -> anyhow::Result<()> {
+    let tmp = TempDir::with_prefix("auth-policy")?;
+    let cdb = ControlDb::at(tmp.path())?;
+
+    let db = Identity::ZERO;
+    assert_eq!(cdb.get_auth_policy(&db)?, AuthPolicy::default());
+
+    let policy = AuthPolicy {
+        issuers: vec!["https://auth.example.com".into()],
+        audiences: vec![],
+    };
+    cdb.set_auth_policy(&db, &policy)?;
+    assert_eq!(cdb.get_auth_policy(&db)?, policy);
+
+    cdb.set_auth_policy(&db, &AuthPolicy::default())?;
+    assert_eq!(cdb.get_auth_policy(&db)?, AuthPolicy::default());
+
+    let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+    Ok(())
+}
+
+#[test]
+fn test_energy_ledger() -> anyhow::Result<()> {
+    use spacetimedb::energy::{EnergyBalance, EnergyQuanta};
+    use std::time::Duration;
+
+    let tmp = TempDir::with_prefix("energy-ledger")?;
+    let cdb = ControlDb::at(tmp.path())?;
+
+    // Identities without a balance are not metered.
+    assert!(cdb.debit_energy_balance(&ALICE, EnergyQuanta::new(10))?.is_none());
+    assert!(cdb.get_energy_balance(&ALICE)?.is_none());
+
+    cdb.set_energy_balance(*ALICE, EnergyBalance::new(15))?;
+    let balance = cdb.debit_energy_balance(&ALICE, EnergyQuanta::new(10))?;
+    assert_eq!(balance.map(|b| b.get()), Some(5));
+    let balance = cdb.debit_energy_balance(&ALICE, EnergyQuanta::new(10))?;
+    assert_eq!(balance.map(|b| b.get()), Some(-5));
+
+    let db = Identity::ZERO;
+    let start = std::time::UNIX_EPOCH + ENERGY_LEDGER_PERIOD * 1000;
+    let call = |reducer: &str, at| {
+        let usage = EnergyUsageRecord {
+            database_identity: db,
+            reducer: reducer.to_owned(),
+            period_start: at,
+            calls: 1,
+            energy_used: 100,
+            execution_duration: Duration::from_millis(1),
+        };
+        cdb.record_energy_usage(&ALICE, &usage)
+    };
+    call("say_hello", start)?;
+    call("say_hello", start + Duration::from_secs(1))?;
+    call("add", start)?;
+    call("add", start + ENERGY_LEDGER_PERIOD)?;
+
+    let usage = cdb.get_energy_usage(&ALICE, std::time::UNIX_EPOCH)?;
+    let summary = usage
+        .iter()
+        .map(|r| (r.period_start, r.reducer.as_str(), r.calls, r.energy_used))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            (start, "add", 1, 100),
+            (start, "say_hello", 2, 200),
+            (start + ENERGY_LEDGER_PERIOD, "add", 1, 100),
+        ]
+    );
+    assert_eq!(usage[1].execution_duration, Duration::from_millis(2));
+
+    // Periods before `since` are skipped, but the period including it is not.
+    let usage = cdb.get_energy_usage(&ALICE, start + ENERGY_LEDGER_PERIOD + Duration::from_secs(1))?;
+    assert_eq!(usage.len(), 1);
+    assert!(cdb.get_energy_usage(&BOB, std::time::UNIX_EPOCH)?.is_empty());
+
+    // Only periods which ended before the cutoff are pruned.
+    assert_eq!(cdb.prune_energy_ledger(start + ENERGY_LEDGER_PERIOD)?, 2);
+    let usage = cdb.get_energy_usage(&ALICE, std::time::UNIX_EPOCH)?;
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].period_start, start + ENERGY_LEDGER_PERIOD);
+
+    let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use crate::control_db::{energy_ledger_period_start, ControlDb, ENERGY_LEDGER_RETENTION};
+use spacetimedb::energy::{EnergyBalance, EnergyMonitor, EnergyQuanta, ReducerBudget, ReducerFingerprint};
+use spacetimedb::identity::Identity;
+use spacetimedb::messages::control_db::Database;
+use spacetimedb_client_api_messages::energy::EnergyUsageRecord;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime};
+
+/// How often the energy used by reducer calls is written to the control database.
+const ENERGY_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often records older than [`ENERGY_LEDGER_RETENTION`] are pruned from the energy ledger.
+const ENERGY_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// An [`EnergyMonitor`] which bills reducer calls to the owner of the database.
+///
+/// The usage of every reducer call is recorded in the owner's energy ledger.
+/// Owners without a stored energy balance, which is the default, are not metered.
+/// Once an owner has a balance, each reducer call is debited from it,
+/// and calls are refused as out of energy once it is exhausted.
+///
+/// Reducer calls only update in-memory totals, which are written to the control database
+/// by [`StandaloneEnergyMonitor::flush`], periodically once [`StandaloneEnergyMonitor::spawn_flush_task`]
+/// has been called, and when the monitor is dropped.
+pub struct StandaloneEnergyMonitor {
+    control_db: ControlDb,
+    pending: Mutex<PendingUsage>,
+}
+
+#[derive(Default)]
+struct PendingUsage {
+    /// The energy used by each owner which has not yet been debited from their balance.
+    debits: HashMap<Identity, EnergyQuanta>,
+    /// The usage not yet written to the ledger,
+    /// keyed by owner, database and period, and then by reducer.
+    ledger: HashMap<(Identity, Identity, SystemTime), HashMap<String, EnergyUsageRecord>>,
+}
+
+impl StandaloneEnergyMonitor {
+    pub fn new(control_db: ControlDb) -> Self {
+        Self {
+            control_db,
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Spawn a task which flushes `monitor` every [`ENERGY_FLUSH_INTERVAL`]
+    /// and prunes the energy ledger every [`ENERGY_PRUNE_INTERVAL`],
+    /// until `monitor` is dropped.
+    pub fn spawn_flush_task(monitor: &Arc<Self>) {
+        let monitor = Arc::downgrade(monitor);
+        tokio::spawn(async move {
+            let mut flush_interval = tokio::time::interval(ENERGY_FLUSH_INTERVAL);
+            let mut last_pruned = None::<tokio::time::Instant>;
+            loop {
+                flush_interval.tick().await;
+                let prune = last_pruned.is_none_or(|at| at.elapsed() >= ENERGY_PRUNE_INTERVAL);
+                if prune {
+                    last_pruned = Some(tokio::time::Instant::now());
+                }
+                let monitor = monitor.clone();
+                let alive = tokio::task::spawn_blocking(move || Self::flush_weak(&monitor, prune))
+                    .await
+                    .unwrap_or(true);
+                if !alive {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Flush the monitor behind `monitor`, if it is still alive, and returns whether it was.
+    fn flush_weak(monitor: &Weak<Self>, prune: bool) -> bool {
+        let Some(monitor) = monitor.upgrade() else {
+            return false;
+        };
+        monitor.flush();
+        if prune {
+            monitor.prune();
+        }
+        true
+    }
+
+    /// Write the energy used since the last flush to the control database,
+    /// recording it in the energy ledger and debiting it from the owners' balances.
+    ///
+    /// Usage which fails to be written stays pending, and is retried by the next flush.
+    pub fn flush(&self) {
+        let (debits, ledger) = {
+            let mut pending = self.pending.lock().unwrap();
+            // Debits stay pending until they are written,
+            // so that `reducer_budget` never sees a balance which is missing them.
+            (pending.debits.clone(), std::mem::take(&mut pending.ledger))
+        };
+
+        for (key @ (owner, _, _), records) in ledger {
+            for (reducer, record) in records {
+                if let Err(e) = self.control_db.record_energy_usage(&owner, &record) {
+                    log::error!("failed to record energy usage of {}: {e}", record.database_identity);
+                    // Put the record back, to be retried by the next flush.
+                    let mut pending = self.pending.lock().unwrap();
+                    let records = pending.ledger.entry(key).or_default();
+                    match records.get_mut(&reducer) {
+                        Some(newer) => {
+                            newer.calls += record.calls;
+                            newer.energy_used = newer.energy_used.saturating_add(record.energy_used);
+                            newer.execution_duration =
+                                newer.execution_duration.saturating_add(record.execution_duration);
+                        }
+                        None => {
+                            records.insert(reducer, record);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (owner, amount) in debits {
+            if let Err(e) = self.control_db.debit_energy_balance(&owner, amount) {
+                // The amount stays pending, to be retried by the next flush.
+                log::error!("failed to debit the energy balance of {owner}: {e}");
+                continue;
+            }
+            let mut pending = self.pending.lock().unwrap();
+            if let Some(remaining) = pending.debits.get_mut(&owner) {
+                *remaining -= amount;
+                if *remaining == EnergyQuanta::ZERO {
+                    pending.debits.remove(&owner);
+                }
+            }
+        }
+    }
+
+    /// Remove the records older than [`ENERGY_LEDGER_RETENTION`] from the energy ledger.
+    fn prune(&self) {
+        let Some(before) = SystemTime::now().checked_sub(ENERGY_LEDGER_RETENTION) else {
+            return;
+        };
+        match self.control_db.prune_energy_ledger(before) {
+            Ok(0) => {}
+            Ok(removed) => log::debug!("pruned {removed} records from the energy ledger"),
+            Err(e) => log::error!("failed to prune the energy ledger: {e}"),
+        }
+    }
+
+    /// Returns the energy balance of `owner`, less the energy it used since the last flush.
+    fn balance(&self, owner: &Identity) -> anyhow::Result<Option<EnergyBalance>> {
+        let Some(balance) = self.control_db.get_energy_balance(owner)? else {
+            return Ok(None);
+        };
+        let pending = self.pending.lock().unwrap().debits.get(owner).copied();
+        Ok(Some(match pending {
+            Some(pending) => balance.saturating_sub_energy(pending),
+            None => balance,
+        }))
+    }
+}
+
+impl Drop for StandaloneEnergyMonitor {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl EnergyMonitor for StandaloneEnergyMonitor {
+    fn reducer_budget(&self, fingerprint: &ReducerFingerprint<'_>) -> ReducerBudget {
+        let balance = match self.balance(&fingerprint.module_identity) {
+            Ok(Some(balance)) => balance,
+            Ok(None) => return ReducerBudget::DEFAULT_BUDGET,
+            Err(e) => {
+                log::error!(
+                    "failed to read the energy balance of {}: {e}",
+                    fingerprint.module_identity
+                );
+                return ReducerBudget::DEFAULT_BUDGET;
+            }
+        };
+        // A reducer may not use more energy than remains in the balance.
+        match balance.to_energy_quanta() {
+            Ok(remaining) => {
+                let remaining = ReducerBudget::from_energy(remaining).unwrap_or(ReducerBudget::MAX);
+                ReducerBudget::new(remaining.get().min(ReducerBudget::DEFAULT_BUDGET.get()))
+            }
+            Err(_overdrawn) => ReducerBudget::ZERO,
+        }
+    }
+
+    fn record_reducer(
+        &self,
+        fingerprint: &ReducerFingerprint<'_>,
+        energy_used: EnergyQuanta,
+        execution_duration: Duration,
+    ) {
+        let owner = fingerprint.module_identity;
+        let database_identity = fingerprint.database_identity;
+        let period_start = energy_ledger_period_start(SystemTime::now());
+
+        let mut pending = self.pending.lock().unwrap();
+        *pending.debits.entry(owner).or_insert(EnergyQuanta::ZERO) += energy_used;
+
+        let records = pending
+            .ledger
+            .entry((owner, database_identity, period_start))
+            .or_default();
+        if !records.contains_key(fingerprint.reducer_name) {
+            let record = EnergyUsageRecord {
+                database_identity,
+                reducer: fingerprint.reducer_name.to_owned(),
+                period_start,
+                calls: 0,
+                energy_used: 0,
+                execution_duration: Duration::ZERO,
+            };
+            records.insert(fingerprint.reducer_name.to_owned(), record);
+        }
+        let record = records.get_mut(fingerprint.reducer_name).unwrap();
+        record.calls += 1;
+        record.energy_used = record.energy_used.saturating_add(energy_used.get());
+        record.execution_duration = record.execution_duration.saturating_add(execution_duration);
+    }
+
+    // Storage is not billed by standalone.
+
+    fn record_disk_usage(&self, _database: &Database, _replica_id: u64, _disk_usage: u64, _period: Duration) {}
+
+    fn record_memory_usage(&self, _database: &Database, _replica_id: u64, _mem_usage: u64, _period: Duration) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacetimedb_client_api::auth::LOCALHOST;
+    use spacetimedb_lib::Hash;
+    use tempfile::TempDir;
+
+    fn fingerprint(owner: Identity) -> ReducerFingerprint<'static> {
+        ReducerFingerprint {
+            module_hash: Hash::ZERO,
+            module_identity: owner,
+            database_identity: Identity::ZERO,
+            caller_identity: owner,
+            reducer_name: "say_hello",
+        }
+    }
+
+    #[test]
+    fn test_reducer_budget() -> anyhow::Result<()> {
+        let tmp = TempDir::with_prefix("energy-budget")?;
+        let control_db = ControlDb::at(tmp.path())?;
+        let monitor = StandaloneEnergyMonitor::new(control_db.clone());
+        let owner = Identity::from_claims(LOCALHOST, "alice");
+        let fingerprint = fingerprint(owner);
+
+        // Owners without a balance are not metered.
+        assert_eq!(
+            monitor.reducer_budget(&fingerprint).get(),
+            ReducerBudget::DEFAULT_BUDGET.get()
+        );
+
+        // A large balance is capped to the default budget.
+        control_db.set_energy_balance(owner, EnergyBalance::new(i128::MAX))?;
+        assert_eq!(
+            monitor.reducer_budget(&fingerprint).get(),
+            ReducerBudget::DEFAULT_BUDGET.get()
+        );
+
+        // A small balance limits the budget.
+        control_db.set_energy_balance(owner, EnergyBalance::new(1000))?;
+        assert_eq!(monitor.reducer_budget(&fingerprint).get(), 1000);
+
+        // Usage which has not been flushed yet is taken into account.
+        monitor.record_reducer(&fingerprint, EnergyQuanta::new(400), Duration::from_millis(1));
+        assert_eq!(monitor.reducer_budget(&fingerprint).get(), 600);
+
+        // An exhausted or overdrawn balance leaves no budget.
+        monitor.record_reducer(&fingerprint, EnergyQuanta::new(700), Duration::from_millis(1));
+        assert_eq!(monitor.reducer_budget(&fingerprint).get(), 0);
+        monitor.flush();
+        assert_eq!(control_db.get_energy_balance(&owner)?.map(|b| b.get()), Some(-100));
+        assert_eq!(monitor.reducer_budget(&fingerprint).get(), 0);
+
+        drop(monitor);
+        let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush() -> anyhow::Result<()> {
+        let tmp = TempDir::with_prefix("energy-flush")?;
+        let control_db = ControlDb::at(tmp.path())?;
+        let monitor = StandaloneEnergyMonitor::new(control_db.clone());
+        let owner = Identity::from_claims(LOCALHOST, "alice");
+        let fingerprint = fingerprint(owner);
+        control_db.set_energy_balance(owner, EnergyBalance::new(1000))?;
+
+        for _ in 0..3 {
+            monitor.record_reducer(&fingerprint, EnergyQuanta::new(100), Duration::from_millis(2));
+        }
+
+        // Nothing is written until the monitor is flushed.
+        assert!(control_db.get_energy_usage(&owner, std::time::UNIX_EPOCH)?.is_empty());
+        assert_eq!(control_db.get_energy_balance(&owner)?.map(|b| b.get()), Some(1000));
+
+        monitor.flush();
+        let usage = control_db.get_energy_usage(&owner, std::time::UNIX_EPOCH)?;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].reducer, "say_hello");
+        assert_eq!(usage[0].calls, 3);
+        assert_eq!(usage[0].energy_used, 300);
+        assert_eq!(usage[0].execution_duration, Duration::from_millis(6));
+        assert_eq!(control_db.get_energy_balance(&owner)?.map(|b| b.get()), Some(700));
+        assert_eq!(monitor.reducer_budget(&fingerprint).get(), 700);
+
+        // Flushing again does not bill the same calls twice.
+        monitor.flush();
+        assert_eq!(control_db.get_energy_balance(&owner)?.map(|b| b.get()), Some(700));
+
+        // Usage is also flushed when the monitor is dropped.
+        monitor.record_reducer(&fingerprint, EnergyQuanta::new(100), Duration::from_millis(2));
+        drop(monitor);
+        let usage = control_db.get_energy_usage(&owner, std::time::UNIX_EPOCH)?;
+        assert_eq!(usage.iter().map(|r| r.calls).sum::<u64>(), 4);
+        assert_eq!(control_db.get_energy_balance(&owner)?.map(|b| b.get()), Some(600));
+
+        let _ = tmp.close().ok(); // force tmp to not be dropped until here
+
+        Ok(())
+    }
+}
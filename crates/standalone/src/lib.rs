@@ -1,18 +1,21 @@
 mod control_db;
+mod energy_monitor;
 pub mod subcommands;
 pub mod util;
 pub mod version;
 
 use crate::control_db::ControlDb;
+use crate::energy_monitor::StandaloneEnergyMonitor;
 use crate::subcommands::{extract_schema, start};
 use anyhow::{ensure, Context as _, Ok};
 use async_trait::async_trait;
 use clap::{ArgMatches, Command};
+use spacetimedb::client::rate_limit::{RateLimitOptions, RateLimiter};
 use spacetimedb::client::ClientActorIndex;
 use spacetimedb::config::{CertificateAuthority, MetadataFile};
 use spacetimedb::db;
 use spacetimedb::db::persistence::LocalPersistenceProvider;
-use spacetimedb::energy::{EnergyBalance, EnergyQuanta, NullEnergyMonitor};
+use spacetimedb::energy::{EnergyBalance, EnergyQuanta};
 use spacetimedb::host::{DiskStorage, HostController, MigratePlanResult, UpdateDatabaseResult};
 use spacetimedb::identity::Identity;
 use spacetimedb::messages::control_db::{Database, Node, Replica};
@@ -20,8 +23,12 @@ use spacetimedb::util::jobs::JobCores;
 use spacetimedb::worker_metrics::WORKER_METRICS;
 use spacetimedb_client_api::auth::{self, LOCALHOST};
 use spacetimedb_client_api::routes::subscribe::{HasWebSocketOptions, WebSocketOptions};
-use spacetimedb_client_api::{Host, NodeDelegate};
+use spacetimedb_client_api::{ControlStateReadAccess, Host, NodeDelegate};
+use spacetimedb_client_api_messages::auth_policy::AuthPolicy;
+use spacetimedb_client_api_messages::collaborators::{Collaborator, DatabaseAction, DatabaseRole};
+use spacetimedb_client_api_messages::energy::EnergyUsageRecord;
 use spacetimedb_client_api_messages::name::{DomainName, InsertDomainResult, RegisterTldResult, SetDomainsResult, Tld};
+use spacetimedb_client_api_messages::tokens::ServiceToken;
 use spacetimedb_datastore::db_metrics::data_size::DATA_SIZE_METRICS;
 use spacetimedb_datastore::db_metrics::DB_METRICS;
 use spacetimedb_datastore::traits::Program;
@@ -30,6 +37,7 @@ use spacetimedb_paths::standalone::StandaloneDataDirExt;
 use spacetimedb_schema::auto_migrate::{MigrationPolicy, PrettyPrintStyle};
 use spacetimedb_table::page_pool::PagePool;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 pub use spacetimedb_client_api::routes::subscribe::{BIN_PROTOCOL, TEXT_PROTOCOL};
 
@@ -37,10 +45,12 @@ pub use spacetimedb_client_api::routes::subscribe::{BIN_PROTOCOL, TEXT_PROTOCOL}
 pub struct StandaloneOptions {
     pub db_config: db::Config,
     pub websocket: WebSocketOptions,
+    pub rate_limits: RateLimitOptions,
 }
 
 pub struct StandaloneEnv {
     control_db: ControlDb,
+    energy_monitor: Arc<StandaloneEnergyMonitor>,
     program_store: Arc<DiskStorage>,
     host_controller: HostController,
     client_actor_index: ClientActorIndex,
@@ -48,6 +58,7 @@ pub struct StandaloneEnv {
     _pid_file: PidFile,
     auth_provider: auth::DefaultJwtAuthProvider,
     websocket_options: WebSocketOptions,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl StandaloneEnv {
@@ -66,7 +77,8 @@ impl StandaloneEnv {
         meta.write(&meta_path).context("failed writing metadata.toml")?;
 
         let control_db = ControlDb::new(&data_dir.control_db()).context("failed to initialize control db")?;
-        let energy_monitor = Arc::new(NullEnergyMonitor);
+        let energy_monitor = Arc::new(StandaloneEnergyMonitor::new(control_db.clone()));
+        StandaloneEnergyMonitor::spawn_flush_task(&energy_monitor);
         let program_store = Arc::new(DiskStorage::new(data_dir.program_bytes().0).await?);
 
         let persistence_provider = Arc::new(LocalPersistenceProvider::new(data_dir.clone()));
@@ -74,14 +86,15 @@ impl StandaloneEnv {
             data_dir,
             config.db_config,
             program_store.clone(),
-            energy_monitor,
+            energy_monitor.clone(),
             persistence_provider,
             db_cores,
         );
         let client_actor_index = ClientActorIndex::new();
         let jwt_keys = certs.get_or_create_keys()?;
 
-        let auth_env = auth::default_auth_environment(jwt_keys, LOCALHOST.to_owned());
+        let auth_env = auth::default_auth_environment(jwt_keys, LOCALHOST.to_owned())
+            .with_revocation_list(Arc::new(control_db.clone()));
 
         let metrics_registry = prometheus::Registry::new();
         metrics_registry.register(Box::new(&*WORKER_METRICS)).unwrap();
@@ -90,6 +103,7 @@ impl StandaloneEnv {
 
         Ok(Arc::new(Self {
             control_db,
+            energy_monitor,
             program_store,
             host_controller,
             client_actor_index,
@@ -97,6 +111,7 @@ impl StandaloneEnv {
             _pid_file,
             auth_provider: auth_env,
             websocket_options: config.websocket,
+            rate_limiter: Arc::new(RateLimiter::new(config.rate_limits)),
         }))
     }
 
@@ -119,6 +134,10 @@ impl NodeDelegate for StandaloneEnv {
         &self.client_actor_index
     }
 
+    fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
     type JwtAuthProviderT = auth::DefaultJwtAuthProvider;
 
     fn jwt_auth_provider(&self) -> &Self::JwtAuthProviderT {
@@ -148,7 +167,7 @@ impl NodeDelegate for StandaloneEnv {
     }
 }
 
-impl spacetimedb_client_api::ControlStateReadAccess for StandaloneEnv {
+impl ControlStateReadAccess for StandaloneEnv {
     // Nodes
     fn get_node_id(&self) -> Option<u64> {
         Some(0)
@@ -197,8 +216,13 @@ impl spacetimedb_client_api::ControlStateReadAccess for StandaloneEnv {
     }
     // Energy
     fn get_energy_balance(&self, identity: &Identity) -> anyhow::Result<Option<EnergyBalance>> {
+        self.energy_monitor.flush();
         Ok(self.control_db.get_energy_balance(identity)?)
     }
+    fn get_energy_usage(&self, owner_identity: &Identity, since: SystemTime) -> anyhow::Result<Vec<EnergyUsageRecord>> {
+        self.energy_monitor.flush();
+        Ok(self.control_db.get_energy_usage(owner_identity, since)?)
+    }
 
     // DNS
     fn lookup_identity(&self, domain: &str) -> anyhow::Result<Option<Identity>> {
@@ -208,6 +232,33 @@ impl spacetimedb_client_api::ControlStateReadAccess for StandaloneEnv {
     fn reverse_lookup(&self, database_identity: &Identity) -> anyhow::Result<Vec<DomainName>> {
         Ok(self.control_db.spacetime_reverse_dns(database_identity)?)
     }
+
+    // Collaborators
+    fn get_collaborators(&self, database_identity: &Identity) -> anyhow::Result<Vec<Collaborator>> {
+        Ok(self.control_db.get_collaborators(database_identity)?)
+    }
+
+    fn get_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        identity: &Identity,
+    ) -> anyhow::Result<Option<DatabaseRole>> {
+        Ok(self.control_db.get_collaborator_role(database_identity, identity)?)
+    }
+
+    // Auth policies
+    fn get_auth_policy(&self, database_identity: &Identity) -> anyhow::Result<AuthPolicy> {
+        Ok(self.control_db.get_auth_policy(database_identity)?)
+    }
+
+    // Service tokens
+    fn get_service_token(&self, id: &str) -> anyhow::Result<Option<ServiceToken>> {
+        Ok(self.control_db.get_service_token(id)?)
+    }
+
+    fn get_service_tokens(&self, owner_identity: &Identity) -> anyhow::Result<Vec<ServiceToken>> {
+        Ok(self.control_db.get_service_tokens_by_owner(owner_identity)?)
+    }
 }
 
 #[async_trait]
@@ -259,8 +310,8 @@ impl spacetimedb_client_api::ControlStateWriteAccess for StandaloneEnv {
             // If that fails, we'll keep the old one.
             Some(database) => {
                 ensure!(
-                    &database.owner_identity == publisher,
-                    "Permission denied: `{}` does not own database `{}`",
+                    self.is_permitted(&database, publisher, DatabaseAction::Publish)?,
+                    "Permission denied: `{}` may not publish to database `{}`",
                     publisher,
                     spec.database_identity.to_abbreviated_hex()
                 );
@@ -352,15 +403,18 @@ impl spacetimedb_client_api::ControlStateWriteAccess for StandaloneEnv {
             return Ok(());
         };
         anyhow::ensure!(
-            &database.owner_identity == caller_identity,
+            self.is_permitted(&database, caller_identity, DatabaseAction::Delete)?,
             // TODO: `PermissionDenied` should be a variant of `Error`,
             //       so we can match on it and return better error responses
             //       from HTTP endpoints.
-            "Permission denied: `{caller_identity}` does not own database `{}`",
+            "Permission denied: `{caller_identity}` may not delete database `{}`",
             database_identity.to_abbreviated_hex()
         );
 
         self.control_db.delete_database(database.id)?;
+        self.control_db.delete_collaborators(database_identity)?;
+        self.control_db
+            .set_auth_policy(database_identity, &AuthPolicy::default())?;
 
         for instance in self.control_db.get_replicas_by_database(database.id)? {
             self.delete_replica(instance.id).await?;
@@ -370,6 +424,7 @@ impl spacetimedb_client_api::ControlStateWriteAccess for StandaloneEnv {
     }
 
     async fn add_energy(&self, identity: &Identity, amount: EnergyQuanta) -> anyhow::Result<()> {
+        self.energy_monitor.flush();
         let balance = self
             .control_db
             .get_energy_balance(identity)?
@@ -380,8 +435,16 @@ impl spacetimedb_client_api::ControlStateWriteAccess for StandaloneEnv {
         self.control_db.set_energy_balance(*identity, balance)?;
         Ok(())
     }
-    async fn withdraw_energy(&self, _identity: &Identity, _amount: EnergyQuanta) -> anyhow::Result<()> {
-        // The energy balance code is obsolete.
+    async fn withdraw_energy(&self, identity: &Identity, amount: EnergyQuanta) -> anyhow::Result<()> {
+        self.energy_monitor.flush();
+        let balance = self
+            .control_db
+            .get_energy_balance(identity)?
+            .unwrap_or(EnergyBalance::ZERO);
+
+        let balance = balance.saturating_sub_energy(amount);
+
+        self.control_db.set_energy_balance(*identity, balance)?;
         Ok(())
     }
 
@@ -410,9 +473,43 @@ impl spacetimedb_client_api::ControlStateWriteAccess for StandaloneEnv {
             .control_db
             .spacetime_replace_domains(database_identity, owner_identity, domain_names)?)
     }
+
+    async fn set_collaborator_role(
+        &self,
+        database_identity: &Identity,
+        collaborator: &Identity,
+        role: DatabaseRole,
+    ) -> anyhow::Result<()> {
+        Ok(self
+            .control_db
+            .set_collaborator_role(database_identity, *collaborator, role)?)
+    }
+
+    async fn remove_collaborator(&self, database_identity: &Identity, collaborator: &Identity) -> anyhow::Result<bool> {
+        Ok(self.control_db.remove_collaborator(database_identity, collaborator)?)
+    }
+
+    async fn set_auth_policy(&self, database_identity: &Identity, policy: &AuthPolicy) -> anyhow::Result<()> {
+        Ok(self.control_db.set_auth_policy(database_identity, policy)?)
+    }
+
+    async fn create_service_token(&self, token: &ServiceToken) -> anyhow::Result<()> {
+        Ok(self.control_db.insert_service_token(token)?)
+    }
+
+    async fn revoke_service_token(&self, id: &str) -> anyhow::Result<bool> {
+        Ok(self.control_db.revoke_service_token(id)?)
+    }
 }
 
 impl StandaloneEnv {
+    /// Whether `identity` holds a role on `database` which permits `action`.
+    fn is_permitted(&self, database: &Database, identity: &Identity, action: DatabaseAction) -> anyhow::Result<bool> {
+        Ok(self
+            .get_database_role(database, identity)?
+            .is_some_and(|role| role.permits(action)))
+    }
+
     async fn insert_replica(&self, replica: Replica) -> Result<(), anyhow::Error> {
         let mut new_replica = replica.clone();
         let id = self.control_db.insert_replica(replica)?;
@@ -533,8 +630,10 @@ mod tests {
             db_config: db::Config {
                 storage: Storage::Memory,
                 page_pool_max_size: None,
+                table_memory_limit: None,
             },
             websocket: WebSocketOptions::default(),
+            rate_limits: RateLimitOptions::default(),
         };
 
         let _env = StandaloneEnv::init(
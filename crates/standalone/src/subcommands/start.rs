@@ -6,6 +6,7 @@ use anyhow::Context;
 use axum::extract::DefaultBodyLimit;
 use clap::ArgAction::SetTrue;
 use clap::{Arg, ArgMatches};
+use spacetimedb::client::rate_limit::RateLimitOptions;
 use spacetimedb::config::{parse_config, CertificateAuthority};
 use spacetimedb::db::{self, Storage};
 use spacetimedb::startup::{self, TracingOptions};
@@ -75,6 +76,11 @@ pub fn cli() -> clap::Command {
                 "The maximum size of the page pool in bytes. Should be a multiple of 64KiB. The default is 8GiB.",
             ),
         )
+        .arg(Arg::new("table_memory_limit").long("table-memory-limit").help(
+            "The maximum size in bytes of table pages each database keeps in memory. \
+                 Cold pages beyond this limit are evicted to the database's latest snapshot and reloaded on access. \
+                 By default, all pages are kept in memory.",
+        ))
         .arg(
             Arg::new("pg_port")
                 .long("pg-port")
@@ -90,6 +96,8 @@ struct ConfigFile {
     common: spacetimedb::config::ConfigFile,
     #[serde(default)]
     websocket: WebSocketOptions,
+    #[serde(default, rename = "rate-limits")]
+    rate_limits: RateLimitOptions,
 }
 
 impl ConfigFile {
@@ -123,9 +131,16 @@ pub async fn exec(args: &ArgMatches, db_cores: JobCores) -> anyhow::Result<()> {
         .transpose()
         .context("unrecognized format in `page_pool_max_size`")?
         .map(|size| size as usize);
+    let table_memory_limit = args
+        .get_one::<&str>("table_memory_limit")
+        .map(|size| parse_size::Config::new().with_binary().parse_size(size))
+        .transpose()
+        .context("unrecognized format in `table_memory_limit`")?
+        .map(|size| size as usize);
     let db_config = db::Config {
         storage,
         page_pool_max_size,
+        table_memory_limit,
     };
 
     banner();
@@ -171,6 +186,7 @@ pub async fn exec(args: &ArgMatches, db_cores: JobCores) -> anyhow::Result<()> {
         StandaloneOptions {
             db_config,
             websocket: config.websocket,
+            rate_limits: config.rate_limits,
         },
         &certs,
         data_dir,
@@ -273,6 +289,7 @@ fn banner() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::num::NonZeroUsize;
     use std::time::Duration;
 
     #[test]
@@ -286,6 +303,9 @@ mod tests {
             [websocket]
             idle-timeout = "1min"
             close-handshake-timeout = "500ms"
+
+            [rate-limits.per-connection]
+            subscriptions = 16
 "#;
 
         let config: ConfigFile = toml::from_str(toml).unwrap();
@@ -303,5 +323,8 @@ mod tests {
                 ..<_>::default()
             }
         );
+
+        assert_eq!(config.rate_limits.per_connection.subscriptions, NonZeroUsize::new(16));
+        assert_eq!(config.rate_limits.per_identity, <_>::default());
     }
 }
@@ -8,7 +8,11 @@ use spacetimedb_execution::{
 };
 use spacetimedb_expr::check::SchemaView;
 use spacetimedb_lib::{identity::AuthCtx, metrics::ExecutionMetrics, query::Delta, AlgebraicValue};
-use spacetimedb_physical_plan::plan::{IxJoin, IxScan, Label, PhysicalPlan, ProjectPlan, Sarg, TableScan, TupleField};
+use spacetimedb_physical_plan::cost::Statistics;
+use spacetimedb_physical_plan::explain::explain;
+use spacetimedb_physical_plan::plan::{
+    IxJoin, IxScan, Label, PhysicalPlan, ProjectListPlan, ProjectPlan, Sarg, TableScan, TupleField,
+};
 use spacetimedb_primitives::{ColId, ColList, IndexId, TableId};
 use spacetimedb_query::compile_subscription;
 use std::sync::Arc;
@@ -28,9 +32,25 @@ struct Fragments {
     /// For joins there will be 4 fragments,
     /// but for selects only one.
     delete_plans: Vec<PipelinedProject>,
+    /// The optimized plans from which [Self::insert_plans] were built.
+    /// Kept for `EXPLAIN`.
+    insert_plans_opt: Vec<ProjectPlan>,
+    /// The optimized plans from which [Self::delete_plans] were built.
+    /// Kept for `EXPLAIN`.
+    delete_plans_opt: Vec<ProjectPlan>,
 }
 
 impl Fragments {
+    /// Build the executable fragments from their optimized plans
+    fn new(insert_plans_opt: Vec<ProjectPlan>, delete_plans_opt: Vec<ProjectPlan>) -> Self {
+        Self {
+            insert_plans: insert_plans_opt.iter().cloned().map(PipelinedProject::from).collect(),
+            delete_plans: delete_plans_opt.iter().cloned().map(PipelinedProject::from).collect(),
+            insert_plans_opt,
+            delete_plans_opt,
+        }
+    }
+
     /// Returns the index ids from which this fragment reads.
     fn index_ids(&self) -> impl Iterator<Item = (TableId, IndexId)> {
         let mut index_ids = HashSet::new();
@@ -178,21 +198,21 @@ impl Fragments {
         }
 
         /// Return a new plan with delta scans for the given tables
-        fn new_plan(plan: &ProjectPlan, tables: &[(Label, Delta)]) -> Result<PipelinedProject> {
+        fn new_plan(plan: &ProjectPlan, tables: &[(Label, Delta)]) -> Result<ProjectPlan> {
             let mut plan = plan.clone();
             for (alias, delta) in tables {
                 mut_plan(&mut plan, *alias, *delta);
             }
-            plan.optimize().map(PipelinedProject::from)
+            plan.optimize()
         }
 
         match tables {
-            [dr] => Ok(Fragments {
-                insert_plans: vec![new_plan(plan, &[(*dr, Delta::Inserts)])?],
-                delete_plans: vec![new_plan(plan, &[(*dr, Delta::Deletes)])?],
-            }),
-            [dr, ds] => Ok(Fragments {
-                insert_plans: vec![
+            [dr] => Ok(Fragments::new(
+                vec![new_plan(plan, &[(*dr, Delta::Inserts)])?],
+                vec![new_plan(plan, &[(*dr, Delta::Deletes)])?],
+            )),
+            [dr, ds] => Ok(Fragments::new(
+                vec![
                     new_plan(
                         // dr(+)S'
                         plan,
@@ -214,7 +234,7 @@ impl Fragments {
                         &[(*dr, Delta::Deletes), (*ds, Delta::Inserts)],
                     )?,
                 ],
-                delete_plans: vec![
+                vec![
                     new_plan(
                         // dr(-)S'
                         plan,
@@ -236,7 +256,7 @@ impl Fragments {
                         &[(*dr, Delta::Deletes), (*ds, Delta::Deletes)],
                     )?,
                 ],
-            }),
+            )),
             _ => bail!("Invalid number of tables in subscription: {}", tables.len()),
         }
     }
@@ -471,9 +491,20 @@ impl SubscriptionPlan {
     }
 
     /// Generate a plan for incrementally maintaining a subscription
-    pub fn compile(sql: &str, tx: &impl SchemaView, auth: &AuthCtx) -> Result<(Vec<Self>, bool)> {
+    pub fn compile(sql: &str, tx: &(impl SchemaView + Statistics), auth: &AuthCtx) -> Result<(Vec<Self>, bool)> {
         let (plans, return_id, return_name, has_param) = compile_subscription(sql, tx, auth)?;
+        let subscriptions = Self::compile_from_plans(plans, return_id, return_name.into(), tx)?;
+        Ok((subscriptions, has_param))
+    }
 
+    /// Generate a plan for incrementally maintaining a subscription,
+    /// given the unoptimized plans for the view and any RLS filters that apply to it.
+    pub fn compile_from_plans(
+        plans: Vec<ProjectPlan>,
+        return_id: TableId,
+        return_name: TableName,
+        tx: &impl Statistics,
+    ) -> Result<Vec<Self>> {
         /// Does this plan have any non-index joins?
         fn has_non_index_join(plan: &PhysicalPlan) -> bool {
             plan.any(&|op| matches!(op, PhysicalPlan::HashJoin(..) | PhysicalPlan::NLJoin(..)))
@@ -510,10 +541,14 @@ impl SubscriptionPlan {
 
         let mut subscriptions = vec![];
 
-        let return_name = TableName::from(return_name);
-
         for plan in plans {
-            let plan_opt = plan.clone().optimize()?;
+            // Subscriptions require index joins,
+            // so if the cheapest plan uses a hash join,
+            // fall back to the heuristic join order.
+            let plan_opt = match plan.clone().optimize_with(tx)? {
+                plan_opt if !has_non_index_join(&plan_opt) => plan_opt,
+                _ => plan.clone().optimize()?,
+            };
 
             if has_non_index_join(&plan_opt) {
                 bail!("Subscriptions require indexes on join columns")
@@ -532,6 +567,31 @@ impl SubscriptionPlan {
             });
         }
 
-        Ok((subscriptions, has_param))
+        Ok(subscriptions)
+    }
+
+    /// Render this plan as text for `EXPLAIN`.
+    ///
+    /// This includes the plan for the initial rows of the view,
+    /// followed by the delta plans that compute the rows to insert into and delete from it,
+    /// each time a transaction modifies one of the tables from which it reads.
+    pub fn explain(&self) -> Vec<String> {
+        let mut lines = vec![format!("Subscription to {}:", self.return_name)];
+        let mut section = |header: String, plan: &ProjectPlan| {
+            lines.push(format!("  {header}:"));
+            lines.extend(
+                explain(&ProjectListPlan::Name(vec![plan.clone()]), |_| None)
+                    .into_iter()
+                    .map(|line| format!("    {line}")),
+            );
+        };
+        section("Initial rows".into(), &self.plan_opt);
+        for (i, plan) in self.fragments.insert_plans_opt.iter().enumerate() {
+            section(format!("Insert fragment {}", i + 1), plan);
+        }
+        for (i, plan) in self.fragments.delete_plans_opt.iter().enumerate() {
+            section(format!("Delete fragment {}", i + 1), plan);
+        }
+        lines
     }
 }
@@ -8,13 +8,15 @@
 //! primarily for tests and benchmarking.
 //! - [`NullBlobStore`], a blob store that always panics.
 //!   Used when ensuring that the blob store is unreachable in a scenario.
-//! - [`HashMapBlobStore`], a blob store backed by a `HashMap` that refcounts blob objects.
-//!   It is not optimize and is mainly intended for testing purposes.
+//! - [`HashMapBlobStore`], a blob store backed by a persistent `HashMap` that refcounts blob objects.
 
 use blake3::hash;
-use spacetimedb_data_structures::map::{Entry, HashMap};
+use core::mem;
+use imbl::hashmap::Entry;
+use spacetimedb_data_structures::map::DefaultHashBuilder;
 use spacetimedb_lib::{de::Deserialize, ser::Serialize};
 use spacetimedb_memory_usage::MemoryUsage;
+use std::sync::Arc;
 
 /// The content address of a blob-stored object.
 #[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Hash, Debug, Serialize, Deserialize)]
@@ -158,28 +160,35 @@ impl BlobStore for NullBlobStore {
 }
 
 /// A blob store that is backed by a hash map with a reference counted value.
-/// Used for tests when you need an actual blob store.
-#[derive(Default, PartialEq, Eq, Debug)]
+///
+/// The map is persistent and each blob's data is reference counted,
+/// so cloning the store is cheap, and modifying a clone
+/// only copies the map nodes on the path to the modified blob, but never blob data.
+/// This lets the committed state share its blob store with read snapshots.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
 pub struct HashMapBlobStore {
-    /// For testing, we use a hash map with a reference count
+    /// A hash map with a reference count
     /// to handle freeing and cloning correctly.
-    map: HashMap<BlobHash, BlobObject>,
+    map: imbl::HashMap<BlobHash, BlobObject, DefaultHashBuilder>,
 }
 
 impl MemoryUsage for HashMapBlobStore {
     fn heap_usage(&self) -> usize {
         let Self { map } = self;
-        map.heap_usage()
+        // Nodes and blobs shared with clones of the store are counted in full by each of them.
+        map.values()
+            .map(|obj| mem::size_of::<(BlobHash, BlobObject)>() + obj.heap_usage())
+            .sum()
     }
 }
 
 /// A blob object including a reference count and the data.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 struct BlobObject {
     /// Reference count of the blob.
     uses: usize,
-    /// The blob data.
-    blob: Box<[u8]>,
+    /// The blob data, shared with clones of the blob store.
+    blob: Arc<[u8]>,
 }
 
 impl MemoryUsage for BlobObject {
@@ -197,22 +206,29 @@ impl BlobStore for HashMapBlobStore {
 
     fn insert_blob(&mut self, bytes: &[u8]) -> BlobHash {
         let hash = BlobHash::hash_from_bytes(bytes);
-        self.map
-            .entry(hash)
-            .and_modify(|v| v.uses += 1)
-            .or_insert_with(|| BlobObject {
-                blob: bytes.into(),
-                uses: 1,
-            });
+        match self.map.entry(hash) {
+            Entry::Occupied(mut entry) => entry.get_mut().uses += 1,
+            Entry::Vacant(entry) => {
+                entry.insert(BlobObject {
+                    blob: bytes.into(),
+                    uses: 1,
+                });
+            }
+        }
         hash
     }
 
     fn insert_with_uses(&mut self, hash: &BlobHash, uses: usize, bytes: Box<[u8]>) {
         debug_assert_eq!(hash, &BlobHash::hash_from_bytes(&bytes));
-        self.map
-            .entry(*hash)
-            .and_modify(|v| v.uses += uses)
-            .or_insert_with(|| BlobObject { blob: bytes, uses });
+        match self.map.entry(*hash) {
+            Entry::Occupied(mut entry) => entry.get_mut().uses += uses,
+            Entry::Vacant(entry) => {
+                entry.insert(BlobObject {
+                    blob: bytes.into(),
+                    uses,
+                });
+            }
+        }
     }
 
     fn retrieve_blob(&self, hash: &BlobHash) -> Result<&[u8], NoSuchBlobError> {
@@ -241,7 +257,7 @@ impl HashMapBlobStore {
     }
 
     /// Returns a map relating blob hashes to the usage count in this blob store.
-    pub fn usage_counter(&self) -> HashMap<BlobHash, usize> {
+    pub fn usage_counter(&self) -> spacetimedb_data_structures::map::HashMap<BlobHash, usize> {
         self.iter().map(|(hash, uses, _)| (*hash, uses)).collect()
     }
 }
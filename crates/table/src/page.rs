@@ -134,7 +134,7 @@ impl FreeCellRef {
 
 /// All the fixed size header information.
 #[repr(C)] // Required for a stable ABI.
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)] // So we can dump and restore pages during snapshotting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)] // So we can dump and restore pages during snapshotting.
 struct FixedHeader {
     /// A pointer to the head of the freelist which stores
     /// all the unused (freed) fixed row cells.
@@ -309,7 +309,7 @@ impl VarHeader {
 /// as the whole [`Page`] is `Box`ed.
 #[repr(C)] // Required for a stable ABI.
 #[repr(align(64))] // Alignment must be same as `VarLenGranule::SIZE`.
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)] // So we can dump and restore pages during snapshotting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)] // So we can dump and restore pages during snapshotting.
 pub(super) struct PageHeader {
     /// The header data relating to the fixed component of a row.
     fixed: FixedHeader,
@@ -1652,7 +1652,7 @@ impl Page {
             // - `starting_from` points to a valid row and thus `row_offset` also does.
             // - `var_len_visitor` will visit the right `VarLenRef`s and is consistent with other calls.
             // - `fixed_row_size` is consistent with `var_len_visitor` and `self`.
-            if !unsafe { self.copy_row_into(row_offset, dst, fixed_row_size, var_len_visitor, blob_store) } {
+            if unsafe { self.copy_row_into(row_offset, dst, fixed_row_size, var_len_visitor, blob_store) }.is_none() {
                 // Target doesn't have enough space for row;
                 // stop here and return the offset of the uncopied row
                 // so a later call to `copy_filter_into` can start there.
@@ -1666,8 +1666,11 @@ impl Page {
         ControlFlow::Break(())
     }
 
-    /// Copies the row at `row_offset` from `self` into `dst`
-    /// or returns `false` otherwise if `dst` has no space for the row.
+    /// Copies the row at `row_offset` from `self` into `dst`,
+    /// returning the offset of the copy in `dst`,
+    /// or returns `None` otherwise if `dst` has no space for the row.
+    ///
+    /// Large blobs referenced by the row are cloned in the `blob_store`.
     ///
     /// # Safety
     ///
@@ -1678,21 +1681,21 @@ impl Page {
     ///
     /// - `fixed_row_size` must be consistent with the `var_len_visitor`,
     ///   and be equal to the value provided to all other methods on `self` and `dst`.
-    unsafe fn copy_row_into(
+    pub(crate) unsafe fn copy_row_into(
         &self,
         row_offset: PageOffset,
         dst: &mut Page,
         fixed_row_size: Size,
         var_len_visitor: &impl VarLenMembers,
         blob_store: &mut dyn BlobStore,
-    ) -> bool {
+    ) -> Option<PageOffset> {
         // SAFETY: Caller promised that `starting_from` points to a valid row
         // consistent with `fixed_row_size` which was also
         // claimed to be consistent with `var_len_visitor` and `self`.
         let required_granules = unsafe { self.row_total_granules(row_offset, fixed_row_size, var_len_visitor) };
         if !dst.has_space_for_row(fixed_row_size, required_granules) {
             // Target doesn't have enough space for row.
-            return false;
+            return None;
         };
 
         let src_row = self.get_row_data(row_offset, fixed_row_size);
@@ -1730,7 +1733,7 @@ impl Page {
             *target_vlr_slot = target_vlr_fixup;
         }
 
-        true
+        Some(inserted_offset)
     }
 
     /// Copy a var-len object `src_vlr` from `self` into `dst_var`,
@@ -1851,6 +1854,22 @@ impl Page {
         // unsafe { self.zero_data() };
     }
 
+    /// Returns the maximum number of rows this page can hold.
+    pub(super) fn max_rows_in_page(&self) -> usize {
+        self.header.max_rows_in_page()
+    }
+
+    /// Overwrites `self` with an exact copy of `other`,
+    /// reusing the allocation of `self`.
+    ///
+    /// The `unmodified_hash` is copied as well,
+    /// so a copy of a page that was unmodified since the last snapshot
+    /// is still recognized as such and need not be rewritten.
+    pub fn copy_from(&mut self, other: &Page) {
+        self.header.clone_from(&other.header);
+        self.row_data.copy_from_slice(&other.row_data);
+    }
+
     /// Sets the header and the row data.
     ///
     /// # Safety
@@ -1899,6 +1918,12 @@ impl Page {
         self.header.unmodified_hash = Some(hash);
     }
 
+    /// Saves `hash`, known to be the content hash of this page, to [`PageHeader::unmodified_hash`].
+    pub(crate) fn set_content_hash(&mut self, hash: blake3::Hash) {
+        debug_assert_eq!(hash, self.content_hash());
+        self.header.unmodified_hash = Some(hash);
+    }
+
     /// Return the page's content hash, computing and saving it if it is not already stored.
     pub fn save_or_get_content_hash(&mut self) -> blake3::Hash {
         self.unmodified_hash().copied().unwrap_or_else(|| {
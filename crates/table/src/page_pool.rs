@@ -2,6 +2,7 @@ use super::{
     indexes::max_rows_in_page,
     page::{Page, PageHeader},
 };
+use core::fmt;
 use derive_more::Deref;
 use spacetimedb_data_structures::object_pool::{Pool, PooledObject};
 use spacetimedb_sats::bsatn::{self, DecodeError};
@@ -27,6 +28,12 @@ pub struct PagePool {
     pool: Pool<Box<Page>>,
 }
 
+impl fmt::Debug for PagePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PagePool").finish_non_exhaustive()
+    }
+}
+
 impl MemoryUsage for PagePool {
     fn heap_usage(&self) -> usize {
         self.pool.heap_usage()
@@ -75,6 +82,14 @@ impl PagePool {
         )
     }
 
+    /// Takes a [`Page`] from the pool or creates a new one,
+    /// and makes it an exact copy of `page`.
+    pub fn take_clone_of(&self, page: &Page) -> Box<Page> {
+        let mut new = self.take_with_max_row_count(page.max_rows_in_page());
+        new.copy_from(page);
+        new
+    }
+
     /// Deserialize a page from `buf` but reuse the allocations in the pool.
     pub fn take_deserialize_from(&self, buf: &[u8]) -> Result<Box<Page>, DecodeError> {
         self.deserialize(bsatn::Deserializer::new(&mut &*buf))
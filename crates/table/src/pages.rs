@@ -1,4 +1,22 @@
 //! Provides [`Pages`], a page manager dealing with [`Page`]s as a collection.
+//!
+//! When a [`PageStore`] is attached, [`Pages`] acts as a buffer pool:
+//! cold pages which are unmodified since the last snapshot
+//! can be evicted from memory with [`Pages::evict_cold_pages`],
+//! and are reloaded from the store the next time they are accessed.
+//!
+//! Reloading can fail, e.g., when the store's files are unreadable.
+//! Callers which can report errors should therefore load the pages they'll access
+//! up front with [`Pages::load`] and friends,
+//! as indexing into [`Pages`] panics when an evicted page cannot be reloaded.
+//! Pages are only evicted through `&mut Pages`,
+//! so a loaded page stays resident for as long as the [`Pages`] are borrowed shared.
+//!
+//! Pages are reference counted, so that [`Pages::clone_with_pool`] can share them
+//! between versions of a table rather than copying them.
+//! A shared page is copied the first time it is accessed mutably,
+//! keeping its `unmodified_hash`, so that only the pages a writer actually modifies
+//! are duplicated and rewritten by the next snapshot.
 
 use super::blob_store::BlobStore;
 use super::indexes::{Bytes, PageIndex, PageOffset, RowPointer};
@@ -6,10 +24,15 @@ use super::page::Page;
 use super::page_pool::PagePool;
 use super::table::BlobNumBytes;
 use super::var_len::VarLenMembers;
-use core::ops::{ControlFlow, Deref, Index, IndexMut};
+use core::cell::Cell;
+use core::fmt;
+use core::mem;
+use core::ops::{ControlFlow, Index, IndexMut};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use spacetimedb_sats::layout::Size;
 use spacetimedb_sats::memory_usage::MemoryUsage;
-use std::ops::DerefMut;
+use std::io;
+use std::sync::{Arc, OnceLock};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -18,35 +41,318 @@ pub enum Error {
     TooManyPages,
     #[error(transparent)]
     Page(#[from] super::page::Error),
+    #[error(transparent)]
+    Reload(#[from] PageReloadError),
+}
+
+/// An evicted page could not be reloaded from the [`PageStore`].
+#[derive(Error, Debug, Clone)]
+#[error("failed to reload evicted page {hash}: {source}")]
+pub struct PageReloadError {
+    /// The content hash of the page.
+    pub hash: blake3::Hash,
+    /// The error returned by [`PageStore::read_page`].
+    pub source: Arc<io::Error>,
 }
 
+impl PartialEq for PageReloadError {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.source.kind() == other.source.kind()
+    }
+}
+
+impl Eq for PageReloadError {}
+
 impl Index<PageIndex> for Pages {
     type Output = Page;
 
     fn index(&self, pi: PageIndex) -> &Self::Output {
-        &self.pages[pi.idx()]
+        self.cache.get(&self.pages[pi.idx()])
     }
 }
 
 impl IndexMut<PageIndex> for Pages {
     fn index_mut(&mut self, pi: PageIndex) -> &mut Self::Output {
-        &mut self.pages[pi.idx()]
+        self.cache.get_mut(&mut self.pages[pi.idx()])
+    }
+}
+
+/// A store from which evicted pages can be reloaded,
+/// e.g., the object repository of the latest snapshot.
+pub trait PageStore: fmt::Debug + Send + Sync {
+    /// Reads the page whose [`Page::content_hash`] is `hash`.
+    ///
+    /// The returned page must have its `unmodified_hash` set to `hash`.
+    fn read_page(&self, hash: &blake3::Hash) -> io::Result<Box<Page>>;
+}
+
+/// Hit and miss counts of accesses to the pages of a [`Pages`]
+/// since the last call to [`Pages::take_cache_stats`].
+///
+/// Only counted while a [`PageStore`] is attached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PageCacheStats {
+    /// The number of accesses to a page that was resident in memory.
+    ///
+    /// As hits are frequent, they're sampled rather than counted exactly:
+    /// every [`HIT_SAMPLE_INTERVAL`]th hit on a thread counts as that many hits.
+    pub hits: u64,
+    /// The number of accesses to a page that had to be reloaded from the [`PageStore`].
+    pub misses: u64,
+}
+
+/// A page which may be shared between several [`Pages`].
+type SharedPage = Arc<Box<Page>>;
+
+/// A page in a [`Pages`], which may have been evicted to a [`PageStore`].
+#[derive(Debug)]
+struct PageSlot {
+    /// The page, or empty if it has been evicted.
+    ///
+    /// An evicted page is reloaded on access through `&self`,
+    /// so reads of evicted pages don't require exclusive access to the [`Pages`].
+    /// The page may be shared with copies of the [`Pages`], see [`Pages::clone_with_pool`].
+    page: OnceLock<SharedPage>,
+    /// What is known about the page when it was last evicted.
+    ///
+    /// Only meaningful while `page` is empty.
+    evicted: Option<EvictedPage>,
+    /// Whether the page has been accessed since the last eviction sweep passed it.
+    referenced: AtomicBool,
+}
+
+/// What is known about an evicted page without reloading it.
+#[derive(Clone, Copy, Debug)]
+struct EvictedPage {
+    /// The content hash by which the page is reloaded.
+    hash: blake3::Hash,
+    /// See [`Page::bytes_used_by_rows`].
+    bytes_used_by_rows: usize,
+}
+
+impl PageSlot {
+    fn resident(page: Box<Page>) -> Self {
+        Self::shared(Arc::new(page))
+    }
+
+    fn shared(page: SharedPage) -> Self {
+        Self {
+            page: OnceLock::from(page),
+            evicted: None,
+            referenced: AtomicBool::new(false),
+        }
+    }
+
+    fn evicted(evicted: EvictedPage) -> Self {
+        Self {
+            page: OnceLock::new(),
+            evicted: Some(evicted),
+            referenced: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the page, if it is resident.
+    fn get(&self) -> Option<&Page> {
+        self.page.get().map(|page| &***page)
+    }
+
+    /// Returns whether the page holds no rows, without reloading it.
+    fn is_empty(&self) -> bool {
+        match self.get() {
+            Some(page) => page.num_rows() == 0,
+            None => self.evicted.unwrap().bytes_used_by_rows == 0,
+        }
+    }
+
+    /// Evicts the page in this slot, returning it to `pool`,
+    /// if it is resident and unmodified since the last snapshot.
+    ///
+    /// Returns whether the page was evicted.
+    fn evict(&mut self, pool: &PagePool, fixed_row_size: Size) -> bool {
+        let Some(page) = self.get() else {
+            return false;
+        };
+        let Some(&hash) = page.unmodified_hash() else {
+            return false;
+        };
+        self.evicted = Some(EvictedPage {
+            hash,
+            bytes_used_by_rows: page.bytes_used_by_rows(fixed_row_size),
+        });
+        // A page still shared with another version of the table is freed by the last one to drop it.
+        if let Ok(page) = Arc::try_unwrap(self.page.take().unwrap()) {
+            pool.put(page);
+        }
+        true
+    }
+}
+
+/// Page cache hits are counted once every this many hits on a thread.
+pub const HIT_SAMPLE_INTERVAL: u64 = 64;
+
+thread_local! {
+    /// The number of page cache hits on this thread, across all [`Pages`].
+    static THREAD_HITS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The bookkeeping of a [`Pages`] for evicting and reloading pages.
+#[derive(Debug, Default)]
+struct PageCache {
+    /// The store evicted pages are reloaded from.
+    ///
+    /// Pages are only ever evicted while this is set.
+    store: Option<Arc<dyn PageStore>>,
+    /// The number of pages currently evicted.
+    num_evicted: AtomicUsize,
+    /// The position at which the next eviction sweep starts.
+    clock_hand: usize,
+    /// See [`PageCacheStats::hits`].
+    hits: AtomicU64,
+    /// See [`PageCacheStats::misses`].
+    misses: AtomicU64,
+    /// The pool from which copies of shared pages are taken, if any.
+    pool: Option<PagePool>,
+}
+
+impl PageCache {
+    /// Returns the page in `slot`, reloading it if it was evicted.
+    ///
+    /// Panics if an evicted page cannot be read from the store.
+    /// Callers which can handle that should [`PageCache::load`] the page beforehand.
+    fn get<'a>(&self, slot: &'a PageSlot) -> &'a Page {
+        self.load(slot).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the page in `slot` mutably, reloading it if it was evicted.
+    ///
+    /// Panics like [`PageCache::get`].
+    fn get_mut<'a>(&self, slot: &'a mut PageSlot) -> &'a mut Page {
+        self.load_mut(slot).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the page in `slot`, reloading it if it was evicted.
+    fn load<'a>(&self, slot: &'a PageSlot) -> Result<&'a Page, PageReloadError> {
+        if let Some(page) = slot.get() {
+            if self.store.is_some() {
+                self.record_hit(slot);
+            }
+            return Ok(page);
+        }
+        let page = self.read(slot)?;
+        // Another reader may have reloaded the page in the meantime,
+        // in which case that copy is kept and ours is dropped.
+        if slot.page.set(Arc::new(page)).is_ok() {
+            self.num_evicted.fetch_sub(1, Ordering::Relaxed);
+        }
+        Ok(slot.get().unwrap())
+    }
+
+    /// Returns the page in `slot` mutably, reloading it if it was evicted,
+    /// and copying it first if it is shared with another [`Pages`].
+    fn load_mut<'a>(&self, slot: &'a mut PageSlot) -> Result<&'a mut Page, PageReloadError> {
+        if slot.page.get().is_some() {
+            if self.store.is_some() {
+                self.record_hit(slot);
+            }
+        } else {
+            let page = self.read(slot)?;
+            let _ = slot.page.set(Arc::new(page));
+            self.num_evicted.fetch_sub(1, Ordering::Relaxed);
+        }
+        let page = slot.page.get_mut().unwrap();
+        if Arc::get_mut(page).is_none() {
+            *page = Arc::new(self.copy(page));
+        }
+        Ok(Arc::get_mut(page).unwrap())
+    }
+
+    /// Returns a private copy of `page`, `unmodified_hash` included.
+    fn copy(&self, page: &Page) -> Box<Page> {
+        match &self.pool {
+            Some(pool) => pool.take_clone_of(page),
+            None => {
+                let mut copy = Page::new_with_max_row_count(page.max_rows_in_page());
+                copy.copy_from(page);
+                copy
+            }
+        }
+    }
+
+    /// Takes the page out of `slot`, or returns `None` if it was evicted.
+    ///
+    /// A page shared with another [`Pages`] is copied.
+    fn into_page(&self, slot: PageSlot) -> Option<Box<Page>> {
+        let Some(page) = slot.page.into_inner() else {
+            self.num_evicted.fetch_sub(1, Ordering::Relaxed);
+            return None;
+        };
+        Some(Arc::try_unwrap(page).unwrap_or_else(|page| self.copy(&page)))
+    }
+
+    fn record_hit(&self, slot: &PageSlot) {
+        // Avoid writing to the slot's cache line on every access.
+        if !slot.referenced.load(Ordering::Relaxed) {
+            slot.referenced.store(true, Ordering::Relaxed);
+        }
+        // Likewise, avoid contending on `self.hits` by sampling.
+        let sampled = THREAD_HITS.with(|hits| {
+            let n = hits.get().wrapping_add(1);
+            hits.set(n);
+            n % HIT_SAMPLE_INTERVAL == 0
+        });
+        if sampled {
+            self.hits.fetch_add(HIT_SAMPLE_INTERVAL, Ordering::Relaxed);
+        }
+    }
+
+    /// Reads the evicted page of `slot` from the store.
+    fn read(&self, slot: &PageSlot) -> Result<Box<Page>, PageReloadError> {
+        let hash = slot.evicted.expect("evicted page should have a content hash").hash;
+        let store = self.store.as_deref().expect("evicted page should have a page store");
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mut page = store.read_page(&hash).map_err(|e| PageReloadError {
+            hash,
+            source: Arc::new(e),
+        })?;
+        // The page is unmodified since it was evicted, so it may be evicted again.
+        page.set_content_hash(hash);
+        slot.referenced.store(true, Ordering::Relaxed);
+        Ok(page)
     }
 }
 
 /// A manager of [`Page`]s.
-#[derive(Default, Debug, PartialEq, Eq)]
+#[derive(Default, Debug)]
 pub struct Pages {
     /// The collection of pages under management.
-    pages: Vec<Box<Page>>,
+    pages: Vec<PageSlot>,
     /// The set of pages that aren't yet full.
     non_full_pages: Vec<PageIndex>,
+    /// Bookkeeping for evicting and reloading pages.
+    cache: PageCache,
 }
 
+impl PartialEq for Pages {
+    fn eq(&self, other: &Self) -> bool {
+        self.non_full_pages == other.non_full_pages && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Pages {}
+
 impl MemoryUsage for Pages {
     fn heap_usage(&self) -> usize {
-        let Self { pages, non_full_pages } = self;
-        pages.heap_usage() + non_full_pages.heap_usage()
+        let Self {
+            pages,
+            non_full_pages,
+            cache: _,
+        } = self;
+        let resident = pages
+            .iter()
+            .filter_map(PageSlot::get)
+            .map(|page| page.heap_usage())
+            .sum::<usize>();
+        pages.capacity() * mem::size_of::<PageSlot>() + resident + non_full_pages.heap_usage()
     }
 }
 
@@ -66,7 +372,7 @@ impl Pages {
     /// Used in benchmarks. Internal operators will prefer directly indexing into `self.pages`,
     /// as that allows split borrows.
     pub fn get_page_mut(&mut self, page: PageIndex) -> &mut Page {
-        &mut self.pages[page.idx()]
+        &mut self[page]
     }
 
     /// Make all pages within `self` clear,
@@ -74,7 +380,7 @@ impl Pages {
     #[doc(hidden)] // Used in benchmarks.
     pub fn clear(&mut self) {
         // Clear every page.
-        for page in &mut self.pages {
+        for page in self.iter_mut() {
             page.clear();
         }
         // Mark every page non-full.
@@ -99,7 +405,7 @@ impl Pages {
         let new_idx = self.can_allocate_new_page()?;
 
         let page = pool.take_with_fixed_row_size(fixed_row_size);
-        self.pages.push(page);
+        self.pages.push(PageSlot::resident(page));
 
         Ok(new_idx)
     }
@@ -116,6 +422,116 @@ impl Pages {
         self.non_full_pages.push(idx);
     }
 
+    /// Remove the page at `idx` from the non-full set,
+    /// so that later insertions will not access it.
+    pub fn unmark_page_non_full(&mut self, idx: PageIndex) {
+        self.non_full_pages.retain(|&pi| pi != idx);
+    }
+
+    /// Copies the bytes of the row at `src` into a page in the non-full set other than its own,
+    /// without allocating a new page,
+    /// and returns the location of the copy,
+    /// or `None` if no such page has room for the row.
+    ///
+    /// The row at `src` is left in place.
+    /// Large blobs referenced by the row are cloned in the `blob_store`,
+    /// so deleting the original afterwards leaves their reference counts unchanged.
+    ///
+    /// Pages already modified since the last snapshot are preferred as the destination,
+    /// so as not to dirty pages which could otherwise be evicted
+    /// and need not be written by the next snapshot.
+    ///
+    /// Fails if the page of `src` or a candidate page was evicted and cannot be reloaded.
+    ///
+    /// # Safety
+    ///
+    /// - `src` must point to a valid, live row in `self`.
+    ///
+    /// - `var_len_visitor` must visit the same set of `VarLenRef`s in the row
+    ///   as the visitor provided to all other methods on `self`.
+    ///
+    /// - `fixed_row_size` must be consistent with the `var_len_visitor`,
+    ///   and be equal to the value provided to all other methods on `self`.
+    pub unsafe fn copy_row_to_non_full_page(
+        &mut self,
+        var_len_visitor: &impl VarLenMembers,
+        fixed_row_size: Size,
+        src: RowPointer,
+        blob_store: &mut dyn BlobStore,
+    ) -> Result<Option<(PageIndex, PageOffset)>, PageReloadError> {
+        let src_pi = src.page_index();
+        // SAFETY: Caller promised that `src` is a valid row
+        // and that `fixed_row_size` and `var_len_visitor` are consistent with `self`.
+        let num_granules = unsafe {
+            self.load(src_pi)?
+                .row_total_granules(src.page_offset(), fixed_row_size, var_len_visitor)
+        };
+
+        let mut candidates = Vec::new();
+        for &pi in &self.non_full_pages {
+            if pi == src_pi {
+                continue;
+            }
+            let page = self.load(pi)?;
+            if page.has_space_for_row(fixed_row_size, num_granules) {
+                candidates.push((page.unmodified_hash().is_some(), pi));
+            }
+        }
+        // `false < true`, so this finds a modified page, if any.
+        let Some((_, dst_pi)) = candidates.into_iter().min() else {
+            return Ok(None);
+        };
+
+        // Split the borrow of `self.pages` to read from `src_pi` while writing to `dst_pi`.
+        let (src_slot, dst_slot) = if src_pi.idx() < dst_pi.idx() {
+            let (head, tail) = self.pages.split_at_mut(dst_pi.idx());
+            (&head[src_pi.idx()], &mut tail[0])
+        } else {
+            let (head, tail) = self.pages.split_at_mut(src_pi.idx());
+            (&tail[0], &mut head[dst_pi.idx()])
+        };
+        let src_page = self.cache.get(src_slot);
+        let dst_page = self.cache.get_mut(dst_slot);
+        // SAFETY:
+        // - Caller promised that `src` points to a valid row.
+        // - Caller promised that `var_len_visitor` and `fixed_row_size`
+        //   are consistent with each other and with all pages in `self`.
+        let dst_offset =
+            unsafe { src_page.copy_row_into(src.page_offset(), dst_page, fixed_row_size, var_len_visitor, blob_store) }
+                .expect("the destination page was checked to have space for the row");
+
+        // The destination page may have become full.
+        self.unmark_page_non_full(dst_pi);
+        self.maybe_mark_page_non_full(dst_pi, fixed_row_size);
+
+        Ok(Some((dst_pi, dst_offset)))
+    }
+
+    /// Removes all empty pages at the end of `self`, returning them to `pool`.
+    ///
+    /// Pages that aren't at the end are kept even when empty,
+    /// as removing them would invalidate the [`RowPointer`]s into later pages.
+    ///
+    /// Returns the number of pages removed.
+    pub fn free_empty_tail_pages(&mut self, pool: &PagePool) -> usize {
+        let mut freed = 0;
+        while self.pages.last().is_some_and(PageSlot::is_empty) {
+            let slot = self.pages.pop().unwrap();
+            self.unmark_page_non_full(PageIndex(self.pages.len() as u64));
+            match slot.page.into_inner() {
+                // A page still shared with another version of the table is freed by the last one to drop it.
+                Some(page) => {
+                    if let Ok(page) = Arc::try_unwrap(page) {
+                        pool.put(page);
+                    }
+                }
+                None => *self.cache.num_evicted.get_mut() -= 1,
+            }
+            freed += 1;
+        }
+        freed
+    }
+
     /// If the page at `page_index` is not full,
     /// add it to the non-full set so that later insertions can access it.
     pub fn maybe_mark_page_non_full(&mut self, page_index: PageIndex, fixed_row_size: Size) {
@@ -126,6 +542,8 @@ impl Pages {
 
     /// Call `f` with a reference to a page which satisfies
     /// `page.has_space_for_row(fixed_row_size, num_var_len_granules)`.
+    ///
+    /// Fails if a candidate page was evicted and cannot be reloaded.
     pub fn with_page_to_insert_row<Res>(
         &mut self,
         pool: &PagePool,
@@ -151,13 +569,17 @@ impl Pages {
         fixed_row_size: Size,
         num_var_len_granules: usize,
     ) -> Result<PageIndex, Error> {
-        if let Some((page_idx_idx, page_idx)) = self
-            .non_full_pages
-            .iter()
-            .copied()
-            .enumerate()
-            .find(|(_, page_idx)| self[*page_idx].has_space_for_row(fixed_row_size, num_var_len_granules))
-        {
+        let mut found = None;
+        for (page_idx_idx, &page_idx) in self.non_full_pages.iter().enumerate() {
+            if self
+                .load(page_idx)?
+                .has_space_for_row(fixed_row_size, num_var_len_granules)
+            {
+                found = Some((page_idx_idx, page_idx));
+                break;
+            }
+        }
+        if let Some((page_idx_idx, page_idx)) = found {
             self.non_full_pages.swap_remove(page_idx_idx);
             return Ok(page_idx);
         }
@@ -276,7 +698,7 @@ impl Pages {
         let mut partial_page = None;
 
         // Copy each page.
-        for from_page in &self.pages {
+        for from_page in self.iter() {
             // You may require multiple calls to `Page::copy_starting_from`
             // if `partial_page` fills up;
             // the first call starts from 0.
@@ -335,7 +757,7 @@ impl Pages {
                 if copy_starting_from.is_none() {
                     partial_page = Some(to_page);
                 } else {
-                    partial_copied_pages.pages.push(to_page);
+                    partial_copied_pages.pages.push(PageSlot::resident(to_page));
                 }
             }
         }
@@ -360,25 +782,269 @@ impl Pages {
             .enumerate()
             .filter_map(|(idx, page)| (!page.is_full(fixed_row_size)).then_some(PageIndex(idx as _)))
             .collect();
-        self.pages = pages;
+        self.pages = pages.into_iter().map(PageSlot::resident).collect();
+    }
+
+    /// Set this [`Pages`]' contents to be the pages with content hashes `hashes`,
+    /// all of which are evicted to `store`.
+    ///
+    /// Used when restoring from a snapshot without reading it into memory.
+    /// Until each page has been loaded once, nothing is known about it,
+    /// so the caller must visit every page with [`Pages::load`]
+    /// and [`Pages::maybe_mark_page_non_full`] before using `self`.
+    ///
+    /// Should only ever be called when `self.is_empty()`.
+    pub fn set_evicted_contents(&mut self, hashes: Vec<blake3::Hash>, store: Arc<dyn PageStore>) {
+        debug_assert!(self.is_empty());
+        self.pages = hashes
+            .into_iter()
+            .map(|hash| {
+                PageSlot::evicted(EvictedPage {
+                    hash,
+                    bytes_used_by_rows: 0,
+                })
+            })
+            .collect();
+        *self.cache.num_evicted.get_mut() = self.pages.len();
+        self.cache.store = Some(store);
+    }
+
+    /// Returns a copy of this page manager which shares its resident pages with `self`.
+    ///
+    /// A shared page is only copied when either manager accesses it mutably,
+    /// taking the allocation for the copy from `pool`.
+    /// Each copied page retains its `unmodified_hash`,
+    /// so the copy shares snapshot contents with `self`.
+    ///
+    /// Evicted pages stay evicted in the copy,
+    /// which shares the [`PageStore`] of `self`.
+    pub fn clone_with_pool(&self, pool: &PagePool) -> Self {
+        let pages = self
+            .pages
+            .iter()
+            .map(|slot| match slot.page.get() {
+                Some(page) => PageSlot::shared(page.clone()),
+                None => PageSlot::evicted(slot.evicted.unwrap()),
+            })
+            .collect();
+        let non_full_pages = self.non_full_pages.clone();
+        let cache = PageCache {
+            store: self.cache.store.clone(),
+            num_evicted: AtomicUsize::new(self.cache.num_evicted.load(Ordering::Relaxed)),
+            pool: Some(pool.clone()),
+            ..PageCache::default()
+        };
+        Self {
+            pages,
+            non_full_pages,
+            cache,
+        }
     }
 
     /// Consumes the page manager, returning all the pages it held.
+    ///
+    /// Must not have any evicted pages, e.g. by not having a [`PageStore`] attached.
     pub fn into_page_iter(self) -> impl Iterator<Item = Box<Page>> {
-        self.pages.into_iter()
+        debug_assert!(!self.has_evicted_pages());
+        let Self { pages, cache, .. } = self;
+        pages.into_iter().filter_map(move |slot| cache.into_page(slot))
     }
-}
 
-impl Deref for Pages {
-    type Target = [Box<Page>];
+    /// Returns the number of pages under management, including evicted ones.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.pages
+    /// Returns whether there are no pages under management.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Returns the page at `idx`, if any, reloading it if it was evicted.
+    ///
+    /// Panics if the page cannot be reloaded.
+    pub fn get(&self, idx: usize) -> Option<&Page> {
+        self.pages.get(idx).map(|slot| self.cache.get(slot))
+    }
+
+    /// Returns the page at `pi`, reloading it if it was evicted.
+    ///
+    /// The page then stays resident for as long as `self` is borrowed,
+    /// so later accesses to it by indexing won't fail.
+    pub fn load(&self, pi: PageIndex) -> Result<&Page, PageReloadError> {
+        self.cache.load(&self.pages[pi.idx()])
+    }
+
+    /// Reloads every evicted page, as per [`Pages::load`].
+    pub fn load_all(&self) -> Result<(), PageReloadError> {
+        if !self.has_evicted_pages() {
+            return Ok(());
+        }
+        self.pages.iter().try_for_each(|slot| self.cache.load(slot).map(drop))
+    }
+
+    /// Reloads every evicted page in the non-full set, as per [`Pages::load`].
+    ///
+    /// These are the pages that inserting a row may access.
+    pub fn load_non_full(&self) -> Result<(), PageReloadError> {
+        if !self.has_evicted_pages() {
+            return Ok(());
+        }
+        self.non_full_pages.iter().try_for_each(|&pi| self.load(pi).map(drop))
+    }
+
+    /// Returns whether any page is currently evicted.
+    pub fn has_evicted_pages(&self) -> bool {
+        self.cache.num_evicted.load(Ordering::Relaxed) != 0
+    }
+
+    /// Returns whether the page at `pi` is held in memory.
+    pub fn is_resident(&self, pi: PageIndex) -> bool {
+        self.pages[pi.idx()].page.get().is_some()
+    }
+
+    /// Returns the number of resident pages shared with another [`Pages`],
+    /// see [`Pages::clone_with_pool`].
+    pub fn num_shared_pages(&self) -> usize {
+        self.pages
+            .iter()
+            .filter(|slot| slot.page.get().is_some_and(|page| Arc::strong_count(page) > 1))
+            .count()
+    }
+
+    /// Iterates over all pages, reloading evicted ones.
+    ///
+    /// Panics if an evicted page cannot be reloaded.
+    pub fn iter(&self) -> impl Iterator<Item = &Page> {
+        self.pages.iter().map(|slot| self.cache.get(slot))
+    }
+
+    /// Iterates mutably over all pages, reloading evicted ones.
+    ///
+    /// Panics if an evicted page cannot be reloaded.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Page> {
+        let cache = &self.cache;
+        self.pages.iter_mut().map(move |slot| cache.get_mut(slot))
+    }
+
+    /// Iterates over all pages along with their content hashes,
+    /// computing and saving the hash of each resident page which was modified.
+    ///
+    /// The hash of a modified page shared with another [`Pages`] is computed but not saved,
+    /// so as not to copy the page.
+    ///
+    /// Evicted pages are not reloaded, and are yielded as `None`.
+    /// Their contents can be read from [`Pages::page_store`] instead.
+    pub fn iter_with_hashes(&mut self) -> impl Iterator<Item = (blake3::Hash, Option<&Page>)> {
+        self.pages.iter_mut().map(|slot| match slot.page.get_mut() {
+            Some(page) => {
+                let hash = match Arc::get_mut(page) {
+                    Some(page) => page.save_or_get_content_hash(),
+                    None => page.unmodified_hash().copied().unwrap_or_else(|| page.content_hash()),
+                };
+                (hash, Some(&***page))
+            }
+            None => (slot.evicted.unwrap().hash, None),
+        })
+    }
+
+    /// Returns the store evicted pages are reloaded from, if any.
+    pub fn page_store(&self) -> Option<&Arc<dyn PageStore>> {
+        self.cache.store.as_ref()
+    }
+
+    /// Attaches `store` as the store to reload evicted pages from,
+    /// enabling [`Pages::evict_cold_pages`].
+    ///
+    /// The `store` must contain every page which is currently evicted,
+    /// as well as every page whose `unmodified_hash` is set.
+    pub fn set_page_store(&mut self, store: Arc<dyn PageStore>) {
+        self.cache.store = Some(store);
     }
-}
 
-impl DerefMut for Pages {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.pages
+    /// Returns the number of bytes used by rows in all pages,
+    /// as per [`Page::bytes_used_by_rows`].
+    ///
+    /// Evicted pages are not reloaded,
+    /// as their usage is recorded when they are evicted.
+    pub fn bytes_used_by_rows(&self, fixed_row_size: Size) -> u64 {
+        self.pages
+            .iter()
+            .map(|slot| match slot.get() {
+                Some(page) => page.bytes_used_by_rows(fixed_row_size),
+                None => slot.evicted.unwrap().bytes_used_by_rows,
+            })
+            .sum::<usize>() as u64
+    }
+
+    /// Returns the number of pages currently held in memory.
+    pub fn num_resident_pages(&self) -> usize {
+        self.len() - self.cache.num_evicted.load(Ordering::Relaxed)
+    }
+
+    /// Evicts up to `max_pages` cold pages from memory, returning them to `pool`.
+    ///
+    /// A page is cold when it hasn't been accessed since the previous sweep passed it.
+    /// Only pages which are unmodified since the last snapshot are evicted,
+    /// as those can be reloaded from the [`PageStore`] by their `unmodified_hash`.
+    /// Nothing is evicted if no store is attached.
+    ///
+    /// This implements the CLOCK algorithm:
+    /// the sweep continues where the previous one stopped,
+    /// and clears the referenced bit of the pages it passes but doesn't evict.
+    /// A single call visits each page at most once.
+    ///
+    /// The `fixed_row_size` must be the one the pages were made with.
+    ///
+    /// Returns the number of pages evicted.
+    pub fn evict_cold_pages(&mut self, pool: &PagePool, fixed_row_size: Size, max_pages: usize) -> usize {
+        if self.cache.store.is_none() || self.pages.is_empty() {
+            return 0;
+        }
+
+        let len = self.pages.len();
+        let mut evicted = 0;
+        let mut visited = 0;
+        let mut hand = self.cache.clock_hand % len;
+        while evicted < max_pages && visited < len {
+            let slot = &mut self.pages[hand];
+            hand = (hand + 1) % len;
+            visited += 1;
+
+            if mem::take(slot.referenced.get_mut()) {
+                continue;
+            }
+            if slot.evict(pool, fixed_row_size) {
+                evicted += 1;
+            }
+        }
+        self.cache.clock_hand = hand;
+        *self.cache.num_evicted.get_mut() += evicted;
+        evicted
+    }
+
+    /// Evicts the page at `pi`, returning it to `pool`,
+    /// if it is resident and unmodified since the last snapshot,
+    /// regardless of whether it was accessed recently.
+    ///
+    /// The `fixed_row_size` must be the one the pages were made with.
+    ///
+    /// Returns whether the page was evicted.
+    pub fn evict_page(&mut self, pi: PageIndex, pool: &PagePool, fixed_row_size: Size) -> bool {
+        if self.cache.store.is_none() {
+            return false;
+        }
+        let evicted = self.pages[pi.idx()].evict(pool, fixed_row_size);
+        *self.cache.num_evicted.get_mut() += evicted as usize;
+        evicted
+    }
+
+    /// Returns the hits and misses of page accesses since the last call,
+    /// resetting the counts.
+    pub fn take_cache_stats(&self) -> PageCacheStats {
+        PageCacheStats {
+            hits: self.cache.hits.swap(0, Ordering::Relaxed),
+            misses: self.cache.misses.swap(0, Ordering::Relaxed),
+        }
     }
 }
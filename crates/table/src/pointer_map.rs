@@ -15,11 +15,9 @@
 
 use super::indexes::{PageIndex, PageOffset, RowHash, RowPointer, SquashedOffset};
 use crate::static_assert_size;
-use core::{hint, slice};
-use spacetimedb_data_structures::map::{
-    Entry,
-    IntMap, // No need to hash a hash.
-};
+use core::{hint, mem, slice};
+use imbl::hashmap::{Entry, HashMap};
+use nohash_hasher::BuildNoHashHasher; // No need to hash a hash.
 use spacetimedb_sats::memory_usage::MemoryUsage;
 
 /// An index to the outer layer of `colliders` in `PointerMap`.
@@ -132,8 +130,13 @@ impl From<ColliderSlotIndex> for PtrOrCollider {
 pub struct PointerMap {
     /// The pointer map from row hashes to row pointer(s).
     ///
+    /// This is a persistent map, so that cloning the pointer map is cheap
+    /// and modifying a clone only copies the nodes on the path to the modified hash.
+    /// This lets a table shared with a read snapshot be copied and then written to
+    /// without copying its pointer map.
+    ///
     /// Invariant: `self.maintains_map_invariant()`.
-    map: IntMap<RowHash, PtrOrCollider>,
+    map: HashMap<RowHash, PtrOrCollider, BuildNoHashHasher<RowHash>>,
     /// The inner vector is a list ("slot") of row pointers that share a row hash.
     /// The outer is indexed by [`ColliderSlotIndex`].
     ///
@@ -161,11 +164,13 @@ impl MemoryUsage for PointerMap {
             colliders,
             emptied_collider_slots,
         } = self;
-        map.heap_usage() + colliders.heap_usage() + emptied_collider_slots.heap_usage()
+        // Nodes shared with clones of the map are counted in full by each of them.
+        let map = map.len() * mem::size_of::<(RowHash, PtrOrCollider)>();
+        map + colliders.heap_usage() + emptied_collider_slots.heap_usage()
     }
 }
 
-static_assert_size!(PointerMap, 80);
+static_assert_size!(PointerMap, 72);
 
 // Provides the public API.
 impl PointerMap {
@@ -216,62 +221,62 @@ impl PointerMap {
     ///
     /// Handles any hash conflicts for `hash`.
     pub fn insert(&mut self, hash: RowHash, ptr: RowPointer) -> bool {
-        let mut was_in_map = false;
-
-        self.map
-            .entry(hash)
-            .and_modify(|v| match v.unpack() {
-                // Already in map; bail for idempotence.
-                MapSlotRef::Pointer(existing) if *existing == ptr => was_in_map = true,
-                // Stored inline => colliders list.
-                MapSlotRef::Pointer(existing) => {
-                    let ptrs = [*existing, ptr].map(ensure_ptr);
-                    let ci = match self.emptied_collider_slots.pop() {
-                        // Allocate a new colliders slot.
-                        None => {
-                            let ci = ColliderSlotIndex::new(self.colliders.len());
-                            self.colliders.push(ptrs.into());
-                            ci
-                        }
-                        // Reuse an empty slot.
-                        Some(ci) => {
-                            self.colliders[ci.idx()].extend(ptrs);
-                            ci
-                        }
-                    };
-                    *v = PtrOrCollider::collider(ci);
-                }
-                // Already using a list; add to it.
-                MapSlotRef::Collider(ci) => {
-                    let ptr = ensure_ptr(ptr);
-                    let colliders = &mut self.colliders[ci.idx()];
-                    if colliders.contains(&ptr) {
-                        // Already in map; bail for idempotence.
-                        //
-                        // O(n) check, but that's OK,
-                        // as we only regress perf in case we have > 5_000
-                        // collisions for this `hash`.
-                        //
-                        // Let `n` be the number of bits (`64`)
-                        // and `k` be the number of hashes.
-                        // The average number of collisions, `avg`,
-                        // according to the birthday problem is:
-                        // `avg = 2^(-n) * combinations(k, 2)`.
-                        // (Caveat: our hash function is not truly random.)
-                        //
-                        // Solving for `avg = 5000`, we get `k ≈ 5 * 10^11`.
-                        // That is, we need around half a trillion hashes before,
-                        // on average, getting 5_000 collisions.
-                        // So we can safely ignore this in terms of perf.
-                        return was_in_map = true;
+        let Some(v) = self.map.get_mut(&hash) else {
+            // 0 hashes so far.
+            self.map.insert(hash, PtrOrCollider::ptr(ptr));
+            return false;
+        };
+
+        match v.unpack() {
+            // Already in map; bail for idempotence.
+            MapSlotRef::Pointer(existing) if *existing == ptr => return true,
+            // Stored inline => colliders list.
+            MapSlotRef::Pointer(existing) => {
+                let ptrs = [*existing, ptr].map(ensure_ptr);
+                let ci = match self.emptied_collider_slots.pop() {
+                    // Allocate a new colliders slot.
+                    None => {
+                        let ci = ColliderSlotIndex::new(self.colliders.len());
+                        self.colliders.push(ptrs.into());
+                        ci
+                    }
+                    // Reuse an empty slot.
+                    Some(ci) => {
+                        self.colliders[ci.idx()].extend(ptrs);
+                        ci
                     }
-                    colliders.push(ptr);
+                };
+                *v = PtrOrCollider::collider(ci);
+            }
+            // Already using a list; add to it.
+            MapSlotRef::Collider(ci) => {
+                let ptr = ensure_ptr(ptr);
+                let colliders = &mut self.colliders[ci.idx()];
+                if colliders.contains(&ptr) {
+                    // Already in map; bail for idempotence.
+                    //
+                    // O(n) check, but that's OK,
+                    // as we only regress perf in case we have > 5_000
+                    // collisions for this `hash`.
+                    //
+                    // Let `n` be the number of bits (`64`)
+                    // and `k` be the number of hashes.
+                    // The average number of collisions, `avg`,
+                    // according to the birthday problem is:
+                    // `avg = 2^(-n) * combinations(k, 2)`.
+                    // (Caveat: our hash function is not truly random.)
+                    //
+                    // Solving for `avg = 5000`, we get `k ≈ 5 * 10^11`.
+                    // That is, we need around half a trillion hashes before,
+                    // on average, getting 5_000 collisions.
+                    // So we can safely ignore this in terms of perf.
+                    return true;
                 }
-            })
-            // 0 hashes so far.
-            .or_insert(PtrOrCollider::ptr(ptr));
+                colliders.push(ptr);
+            }
+        }
 
-        was_in_map
+        false
     }
 
     /// Removes the association `hash -> ptr`.
@@ -649,5 +654,30 @@ mod tests {
             prop_assert_eq!(sorted(&map.colliders[0]), sorted(&ptrs));
             prop_assert_eq!(map.emptied_collider_slots, []);
         }
+
+        #[test]
+        fn modifying_clone_leaves_original_intact(
+            (hashes, ptr) in (vec(any::<RowHash>(), 2..10), gen_row_pointer())
+        ) {
+            ensure_unique(&hashes)?;
+
+            let mut map = PointerMap::default();
+            for hash in &hashes {
+                prop_assert_eq!(map.insert(*hash, ptr), false);
+            }
+            let old_map = map.clone(); // Snapshot.
+
+            // Remove everything from the clone.
+            for hash in &hashes {
+                prop_assert_eq!(map.remove(*hash, ptr), true);
+            }
+            prop_assert!(map.is_empty());
+
+            // The snapshot still has every entry.
+            prop_assert_eq!(old_map.len(), hashes.len());
+            for hash in &hashes {
+                prop_assert_eq!(old_map.pointers_for(*hash), [ptr]);
+            }
+        }
     }
 }
@@ -9,7 +9,7 @@ use super::{
     indexes::{Bytes, PageIndex, PageOffset, RowHash, RowPointer, SquashedOffset, PAGE_DATA_SIZE},
     page::{FixedLenRowsIter, Page},
     page_pool::PagePool,
-    pages::Pages,
+    pages::{PageCacheStats, PageReloadError, PageStore, Pages},
     pointer_map::PointerMap,
     read_column::{ReadColumn, TypeError},
     row_hash::hash_row_in_page,
@@ -525,8 +525,8 @@ impl Table {
         let schema = self.get_schema().clone();
         let row_type = schema.get_row_type();
         for index in self.indexes.values_mut() {
-            index.key_type = row_type
-                .project(&index.indexed_columns)
+            index
+                .recompute_key_type(row_type)
                 .expect("new row type should have as many columns as before")
         }
     }
@@ -592,6 +592,16 @@ impl Table {
         let (row_ref, blob_bytes) = self.insert_physically_pv(pool, blob_store, row)?;
         let row_ptr = row_ref.pointer();
 
+        // Checking for a duplicate reads the rows with the same hash, which may have been evicted.
+        // SAFETY:
+        // - `self` trivially has the same `row_layout` as `self`.
+        // - We just inserted `row_ptr`, so it's valid.
+        if let Err(e) = unsafe { Self::load_pages_for_same_row(self, self, blob_store, row_ptr, None) } {
+            // SAFETY: `row_ptr` is valid as we just inserted it.
+            unsafe { self.delete_internal_skip_pointer_map(blob_store, row_ptr) };
+            return Err(Error::PagesError(e.into()).into());
+        }
+
         // Confirm the insertion, checking any constraints, removing the physical row on error.
         // SAFETY: We just inserted `ptr`, so it must be present.
         // Re. `CHECK_SAME_ROW = true`,
@@ -1142,6 +1152,42 @@ impl Table {
         }
     }
 
+    /// Reloads the evicted pages of `target_table`
+    /// which [`Table::find_same_row`] may access for the same arguments.
+    ///
+    /// # Safety
+    ///
+    /// - `target_table` and `needle_table` must have the same `row_layout`.
+    /// - `needle_table.is_row_present(needle_ptr)` must hold.
+    pub unsafe fn load_pages_for_same_row(
+        target_table: &Table,
+        needle_table: &Table,
+        needle_bs: &dyn BlobStore,
+        needle_ptr: RowPointer,
+        row_hash: Option<RowHash>,
+    ) -> Result<(), PageReloadError> {
+        if !target_table.has_evicted_pages() {
+            return Ok(());
+        }
+        // SAFETY: Caller promised that `needle_table.is_row_present(needle_ptr)`.
+        let needle_row = unsafe { needle_table.get_row_ref_unchecked(needle_bs, needle_ptr) };
+        if target_table.pointer_map.is_some() {
+            let row_hash = row_hash.unwrap_or_else(|| needle_row.row_hash());
+            target_table.load_pages_of(target_table.pointers_for(row_hash).iter().copied())
+        } else {
+            // Same index as `find_same_row_via_unique_index` uses.
+            let target_index = target_table
+                .indexes
+                .values()
+                .find(|idx| idx.is_unique())
+                .expect("there should be at least one unique index");
+            let key = needle_row
+                .project(&target_index.indexed_columns)
+                .expect("needle row should be valid");
+            target_table.load_pages_of(target_index.seek_point(&key))
+        }
+    }
+
     /// Returns a [`RowRef`] for `ptr` or `None` if the row isn't present.
     pub fn get_row_ref<'a>(&'a self, blob_store: &'a dyn BlobStore, ptr: RowPointer) -> Option<RowRef<'a>> {
         self.is_row_present(ptr)
@@ -1314,6 +1360,16 @@ impl Table {
         let (temp_row, _) = self.insert_physically_pv(pool, blob_store, row)?;
         let temp_ptr = temp_row.pointer();
 
+        // The row equal to `row`, if any, may sit on an evicted page.
+        // SAFETY:
+        // - `self` trivially has the same `row_layout` as `self`.
+        // - We just inserted `temp_ptr`, so it's valid.
+        if let Err(e) = unsafe { Self::load_pages_for_same_row(self, self, blob_store, temp_ptr, None) } {
+            // SAFETY: `temp_ptr` is valid as we just inserted it.
+            unsafe { self.delete_internal_skip_pointer_map(blob_store, temp_ptr) };
+            return Err(Error::PagesError(e.into()));
+        }
+
         // Find the row equal to the passed-in `row`.
         // This uses one of two approaches.
         // Either there is a pointer map, so we use that,
@@ -1415,6 +1471,47 @@ impl Table {
         unsafe { self.add_index(index_id, index) };
     }
 
+    /// Inserts a new `index` into the table, like [`Table::insert_index`],
+    /// but reloads evicted pages one at a time and evicts them again once indexed,
+    /// so that the table needn't fit in memory.
+    ///
+    /// Fails if an evicted page cannot be reloaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any row would violate `index`'s unique constraint, if it has one.
+    ///
+    /// # Safety
+    ///
+    /// Caller must promise that `index` was constructed with the same row type/layout as this table.
+    pub unsafe fn try_insert_index(
+        &mut self,
+        pool: &PagePool,
+        blob_store: &dyn BlobStore,
+        index_id: IndexId,
+        mut index: TableIndex,
+    ) -> Result<(), PageReloadError> {
+        let fixed_row_size = self.row_size();
+        for idx in 0..self.num_pages() {
+            let pi = PageIndex(idx as _);
+            let was_resident = self.inner.pages.is_resident(pi);
+            let rows = self.page_rows(blob_store, pi)?;
+            // SAFETY: Caller promised that table's row type/layout
+            // matches that which `index` was constructed with.
+            // It follows that this applies to any `rows`, as required.
+            let violation = unsafe { index.build_from_rows(rows) };
+            violation.unwrap_or_else(|ptr| {
+                panic!("adding `index` should cause no unique constraint violations, but {ptr:?} would")
+            });
+            if !was_resident {
+                self.inner.pages.evict_page(pi, pool, fixed_row_size);
+            }
+        }
+        // SAFETY: Forward caller requirement.
+        unsafe { self.add_index(index_id, index) };
+        Ok(())
+    }
+
     /// Adds an index to the table without populating.
     ///
     /// # Safety
@@ -1461,6 +1558,21 @@ impl Table {
         }
     }
 
+    /// Returns an iterator over the rows in the page at `pi`,
+    /// reloading the page if it was evicted.
+    fn page_rows<'a>(
+        &'a self,
+        blob_store: &'a dyn BlobStore,
+        pi: PageIndex,
+    ) -> Result<impl Iterator<Item = RowRef<'a>>, PageReloadError> {
+        let page = self.inner.pages.load(pi)?;
+        Ok(page.iter_fixed_len(self.row_size()).map(move |offset| {
+            let ptr = RowPointer::new(false, pi, offset, self.squashed_offset);
+            // SAFETY: `offset` came from `iter_fixed_len`, so it must point to a valid row.
+            unsafe { self.get_row_ref_unchecked(blob_store, ptr) }
+        }))
+    }
+
     /// Returns a list of all present row pointers.
     pub fn scan_all_row_ptrs(&self) -> Vec<RowPointer> {
         let mut ptrs = Vec::with_capacity(self.row_count as usize);
@@ -1501,10 +1613,12 @@ impl Table {
     }
 
     /// Returns the first [`TableIndex`] with the given [`ColList`].
+    ///
+    /// Partial indexes are skipped, as they don't contain every row.
     pub fn get_index_by_cols(&self, cols: &ColList) -> Option<(IndexId, &TableIndex)> {
         self.indexes
             .iter()
-            .find(|(_, index)| &index.indexed_columns == cols)
+            .find(|(_, index)| index.filter.is_none() && &index.indexed_columns == cols)
             .map(|(id, idx)| (*id, idx))
     }
 
@@ -1534,6 +1648,36 @@ impl Table {
         new
     }
 
+    /// Returns a copy of this table, rows and indices included.
+    ///
+    /// This is cheap, as nothing is copied eagerly.
+    /// The pages are shared with `self` and only copied once either table modifies them,
+    /// taking the allocations for the copied pages from `pool`.
+    /// The indices and the pointer map are persistent data structures,
+    /// so either table modifying them only copies the nodes on the path to the modified entry.
+    ///
+    /// The copy keeps the `squashed_offset` of `self`,
+    /// so every [`RowPointer`] valid in `self` is valid in the copy and refers to the same row.
+    /// Blobs referenced by the rows are not cloned in any [`BlobStore`];
+    /// the copy is expected to be used with a copy of the blob store `self` is used with.
+    pub fn clone_with_pool(&self, pool: &PagePool) -> Self {
+        Self {
+            inner: TableInner {
+                row_layout: self.inner.row_layout.clone(),
+                static_layout: self.inner.static_layout.clone(),
+                visitor_prog: self.inner.visitor_prog.clone(),
+                pages: self.inner.pages.clone_with_pool(pool),
+            },
+            pointer_map: self.pointer_map.clone(),
+            indexes: self.indexes.clone(),
+            schema: self.schema.clone(),
+            squashed_offset: self.squashed_offset,
+            row_count: self.row_count,
+            blob_store_bytes: self.blob_store_bytes,
+            is_scheduler: self.is_scheduler,
+        }
+    }
+
     /// Returns the number of bytes occupied by the pages and the blob store.
     /// Note that result can be more than the actual physical size occupied by the table
     /// because the blob store implementation can do internal optimizations.
@@ -1542,6 +1686,146 @@ impl Table {
         (self.num_pages() * PAGE_DATA_SIZE) + (self.blob_store_bytes.0)
     }
 
+    /// Returns the fraction, in `0.0..=1.0`, of the space in this table's pages
+    /// which is not occupied by rows.
+    ///
+    /// A table without any pages is not fragmented.
+    pub fn fragmentation(&self) -> f64 {
+        let capacity = self.num_pages() * PAGE_DATA_SIZE;
+        if capacity == 0 {
+            return 0.0;
+        }
+        1.0 - (self.bytes_used_by_rows() as f64 / capacity as f64).min(1.0)
+    }
+
+    /// Incrementally compacts this table,
+    /// reclaiming the space in sparse pages left behind by deletions.
+    ///
+    /// Rows are moved out of the last page into free space in earlier pages,
+    /// and empty pages at the end of the table are returned to the `pool`.
+    /// Moving a row updates every index and the pointer map,
+    /// so all [`RowPointer`]s into the moved rows are invalidated.
+    /// Hence, this must only be called between transactions.
+    ///
+    /// Compaction stops after moving `max_rows_moved` rows,
+    /// once the [`Table::fragmentation`] is below `fragmentation_threshold`,
+    /// or when no earlier page has room for a row from the last page.
+    pub fn compact(
+        &mut self,
+        pool: &PagePool,
+        blob_store: &mut dyn BlobStore,
+        max_rows_moved: usize,
+        fragmentation_threshold: f64,
+    ) -> Compaction {
+        let fixed_row_size = self.row_size();
+        let mut compaction = Compaction::default();
+        loop {
+            compaction.pages_freed += self.inner.pages.free_empty_tail_pages(pool);
+            if self.num_pages() <= 1 || self.fragmentation() < fragmentation_threshold {
+                compaction.done = true;
+                break;
+            }
+            if compaction.rows_moved >= max_rows_moved {
+                break;
+            }
+
+            let tail = PageIndex(self.num_pages() as u64 - 1);
+            let Ok(tail_page) = self.inner.pages.load(tail) else {
+                // The tail page was evicted and can't be reloaded, so give up for now.
+                break;
+            };
+            let offsets = tail_page
+                .iter_fixed_len(fixed_row_size)
+                .take(max_rows_moved - compaction.rows_moved)
+                .collect::<Vec<_>>();
+            for offset in offsets {
+                let ptr = RowPointer::new(false, tail, offset, self.squashed_offset);
+                // SAFETY: `offset` was just yielded by `iter_fixed_len`, so `ptr` is present.
+                match unsafe { self.move_row_out_of_page(blob_store, ptr) } {
+                    Ok(true) => compaction.rows_moved += 1,
+                    Ok(false) => {
+                        compaction.done = true;
+                        break;
+                    }
+                    // A candidate page was evicted and can't be reloaded, so give up for now.
+                    Err(_) => return compaction,
+                }
+            }
+            if compaction.done {
+                break;
+            }
+        }
+        compaction
+    }
+
+    /// Moves the row at `ptr` to another non-full page than the one it is in,
+    /// without allocating a new page,
+    /// by copying its bytes and pointing every index and the pointer map at the copy.
+    ///
+    /// Returns `Ok(false)`, leaving the row in place, if no other page has room for it,
+    /// or an error if a page was evicted and can't be reloaded.
+    ///
+    /// # Safety
+    ///
+    /// `self.is_row_present(ptr)` must hold.
+    unsafe fn move_row_out_of_page(
+        &mut self,
+        blob_store: &mut dyn BlobStore,
+        ptr: RowPointer,
+    ) -> Result<bool, PageReloadError> {
+        let fixed_row_size = self.row_size();
+        // SAFETY:
+        // - Caller promised that `ptr` points to a live row.
+        // - `visitor_prog` and `row_size` are consistent with `pages`,
+        //    as they are tied together in `Table::new`.
+        let copy = unsafe {
+            self.inner
+                .pages
+                .copy_row_to_non_full_page(&self.inner.visitor_prog, fixed_row_size, ptr, blob_store)?
+        };
+        let Some((page_index, page_offset)) = copy else {
+            return Ok(false);
+        };
+        let new = RowPointer::new(false, page_index, page_offset, self.squashed_offset);
+
+        // The copy is equal to the original, so it has the same index keys and row hash,
+        // and can't violate any unique constraint once the original is removed.
+        for index in self.indexes.values_mut() {
+            // SAFETY: Caller promised that `ptr` is present, and we just copied it to `new`.
+            let (old_row, new_row) = unsafe {
+                (
+                    self.inner.get_row_ref_unchecked(blob_store, self.squashed_offset, ptr),
+                    self.inner.get_row_ref_unchecked(blob_store, self.squashed_offset, new),
+                )
+            };
+            index.delete(old_row).unwrap();
+            // SAFETY: Any index in this table was constructed with the same row type as this table.
+            unsafe { index.check_and_insert(new_row) }.expect("moving a row should not violate a unique constraint");
+        }
+        if let Some(pointer_map) = &mut self.pointer_map {
+            // SAFETY: Caller promised that `ptr` is present.
+            let row = unsafe { self.inner.get_row_ref_unchecked(blob_store, self.squashed_offset, ptr) };
+            let hash = row.row_hash();
+            let _remove_result = pointer_map.remove(hash, ptr);
+            debug_assert!(_remove_result);
+            pointer_map.insert(hash, new);
+        }
+
+        // Freeing the original drops the references to its large blobs taken by the copy,
+        // so the blob store and the table's statistics are unchanged.
+        // SAFETY: Caller promised that `ptr` is present.
+        unsafe { self.delete_internal_skip_pointer_map(blob_store, ptr) };
+        Ok(true)
+    }
+
+    /// Returns the number of resident pages shared with another version of this table,
+    /// see [`Table::clone_with_pool`].
+    ///
+    /// Modifying a shared page copies it first.
+    pub fn num_shared_pages(&self) -> usize {
+        self.inner.pages.num_shared_pages()
+    }
+
     /// Reset the internal storage of `self` to be `pages`.
     ///
     /// This recomputes the pointer map based on the `pages`,
@@ -1561,6 +1845,53 @@ impl Table {
         self.pointer_map = Some(self.rebuild_pointer_map(blob_store));
     }
 
+    /// Reset the internal storage of `self` to be the pages with content hashes `hashes`,
+    /// which are read from `store` when accessed.
+    ///
+    /// Like [`Table::set_pages`], this recomputes the pointer map, but not indexes.
+    /// To do so, every page is loaded once,
+    /// and all but the first `max_resident_pages` are evicted again right after,
+    /// so that the table needn't fit in memory.
+    ///
+    /// Used when restoring from a snapshot without reading it into memory.
+    ///
+    /// Fails if a page cannot be read from `store`.
+    ///
+    /// # Safety
+    ///
+    /// The schema of rows stored in the pages must exactly match `self.schema` and `self.inner.row_layout`.
+    pub unsafe fn set_evicted_pages(
+        &mut self,
+        hashes: Vec<blake3::Hash>,
+        store: Arc<dyn PageStore>,
+        pool: &PagePool,
+        blob_store: &dyn BlobStore,
+        max_resident_pages: usize,
+    ) -> Result<(), PageReloadError> {
+        let fixed_row_size = self.row_size();
+        self.inner.pages.set_evicted_contents(hashes, store);
+
+        let mut row_count = 0;
+        let mut blob_store_bytes = 0;
+        let mut pointer_map = PointerMap::default();
+        for idx in 0..self.num_pages() {
+            let pi = PageIndex(idx as _);
+            for row in self.page_rows(blob_store, pi)? {
+                row_count += 1;
+                blob_store_bytes += row.blob_store_bytes();
+                pointer_map.insert(row.row_hash(), row.pointer());
+            }
+            self.inner.pages.maybe_mark_page_non_full(pi, fixed_row_size);
+            if idx >= max_resident_pages {
+                self.inner.pages.evict_page(pi, pool, fixed_row_size);
+            }
+        }
+        self.row_count = row_count;
+        self.blob_store_bytes = blob_store_bytes.into();
+        self.pointer_map = Some(pointer_map);
+        Ok(())
+    }
+
     /// Consumes the table, returning some constituents needed for merge.
     pub fn consume_for_merge(
         self,
@@ -1603,10 +1934,7 @@ impl Table {
     // TODO(perf, centril): consider storing the total number of granules in the table instead
     // so that this runs in constant time rather than O(|Pages|).
     pub fn bytes_used_by_rows(&self) -> u64 {
-        self.pages()
-            .iter()
-            .map(|page| page.bytes_used_by_rows(self.inner.row_layout.size()) as u64)
-            .sum()
+        self.pages().bytes_used_by_rows(self.inner.row_layout.size())
     }
 
     #[cfg(test)]
@@ -1650,6 +1978,18 @@ impl Table {
     }
 }
 
+/// The outcome of a call to [`Table::compact`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Compaction {
+    /// The number of rows moved into another page.
+    pub rows_moved: usize,
+    /// The number of empty pages returned to the page pool.
+    pub pages_freed: usize,
+    /// Whether compaction ran to completion,
+    /// i.e., whether calling [`Table::compact`] again right away would not make progress.
+    pub done: bool,
+}
+
 /// A reference to a single row within a table.
 ///
 /// # Safety
@@ -2065,6 +2405,20 @@ impl<'a> TableAndIndex<'a> {
             btree_index_iter: self.index.seek_range(range),
         }
     }
+
+    /// Reloads the evicted pages holding the rows [`Self::seek_point`] would yield for `key`.
+    ///
+    /// See [`Table::load_pages`].
+    pub fn load_point(&self, key: &AlgebraicValue) -> Result<(), PageReloadError> {
+        self.table.load_pages_of(self.index.seek_point(key))
+    }
+
+    /// Reloads the evicted pages holding the rows [`Self::seek_range`] would yield for `range`.
+    ///
+    /// See [`Table::load_pages`].
+    pub fn load_range(&self, range: &impl RangeBounds<AlgebraicValue>) -> Result<(), PageReloadError> {
+        self.table.load_pages_of(self.index.seek_range(range))
+    }
 }
 
 /// An iterator using a [`TableIndex`] to scan a `table`
@@ -2261,12 +2615,84 @@ impl Table {
 
     /// Iterates over each [`Page`] in this table, ensuring that its hash is computed before yielding it.
     ///
+    /// Evicted pages are yielded as `None` without being reloaded;
+    /// their contents are available from [`Table::page_store`].
+    ///
     /// Used when capturing a snapshot.
-    pub fn iter_pages_with_hashes(&mut self) -> impl Iterator<Item = (blake3::Hash, &Page)> {
-        self.inner.pages.iter_mut().map(|page| {
-            let hash = page.save_or_get_content_hash();
-            (hash, &**page)
-        })
+    pub fn iter_pages_with_hashes(&mut self) -> impl Iterator<Item = (blake3::Hash, Option<&Page>)> {
+        self.inner.pages.iter_with_hashes()
+    }
+
+    /// Returns the store evicted pages of this table are reloaded from, if any.
+    pub fn page_store(&self) -> Option<&Arc<dyn PageStore>> {
+        self.inner.pages.page_store()
+    }
+
+    /// Attaches `store` as the store to reload evicted pages of this table from,
+    /// enabling [`Table::evict_cold_pages`].
+    ///
+    /// The `store` must contain every page of this table which is evicted
+    /// or unmodified since the last snapshot,
+    /// e.g., by being the object repository of a snapshot just taken.
+    pub fn set_page_store(&mut self, store: Arc<dyn PageStore>) {
+        self.inner.pages.set_page_store(store);
+    }
+
+    /// Returns whether any page of this table is evicted.
+    pub fn has_evicted_pages(&self) -> bool {
+        self.inner.pages.has_evicted_pages()
+    }
+
+    /// Reloads every evicted page of this table.
+    ///
+    /// Reloaded pages stay in memory while the table is borrowed shared,
+    /// so scanning it afterwards won't have to reload, and hence can't fail to reload, any page.
+    pub fn load_pages(&self) -> Result<(), PageReloadError> {
+        self.inner.pages.load_all()
+    }
+
+    /// Reloads the page holding the row at `ptr`, if it was evicted.
+    ///
+    /// See [`Table::load_pages`].
+    pub fn load_page(&self, ptr: RowPointer) -> Result<(), PageReloadError> {
+        self.inner.pages.load(ptr.page_index()).map(drop)
+    }
+
+    /// Reloads the pages holding the rows at `ptrs` which were evicted.
+    ///
+    /// See [`Table::load_pages`].
+    pub fn load_pages_of(&self, ptrs: impl IntoIterator<Item = RowPointer>) -> Result<(), PageReloadError> {
+        if !self.has_evicted_pages() {
+            return Ok(());
+        }
+        ptrs.into_iter().try_for_each(|ptr| self.load_page(ptr))
+    }
+
+    /// Reloads the evicted pages which inserting a row into this table may access.
+    ///
+    /// See [`Table::load_pages`].
+    pub fn load_non_full_pages(&self) -> Result<(), PageReloadError> {
+        self.inner.pages.load_non_full()
+    }
+
+    /// Returns the number of pages of this table held in memory.
+    pub fn num_resident_pages(&self) -> usize {
+        self.inner.pages.num_resident_pages()
+    }
+
+    /// Evicts up to `max_pages` cold and clean pages of this table, returning them to the `pool`.
+    ///
+    /// See [`Pages::evict_cold_pages`] for which pages are evicted.
+    /// Evicted pages are transparently reloaded from the [`Table::page_store`] when accessed.
+    pub fn evict_cold_pages(&mut self, pool: &PagePool, max_pages: usize) -> usize {
+        let fixed_row_size = self.row_size();
+        self.inner.pages.evict_cold_pages(pool, fixed_row_size, max_pages)
+    }
+
+    /// Returns the page cache hits and misses of this table since the last call,
+    /// resetting the counts.
+    pub fn take_page_cache_stats(&self) -> PageCacheStats {
+        self.inner.pages.take_cache_stats()
     }
 
     /// Returns the number of pages storing the physical rows of this table.
@@ -2620,6 +3046,46 @@ pub(crate) mod test {
             prop_assert_eq!(table_pv, table_bsatn);
         }
 
+        #[test]
+        fn clone_with_pool_is_independent_copy((ty, vals) in generate_typed_row_vec(1, 256)) {
+            let pool = PagePool::new_for_test();
+            let mut blob_store = HashMapBlobStore::default();
+            let mut table = table(ty);
+
+            let mut ptrs = Vec::new();
+            for row in &vals {
+                if let Ok((_, row_ref)) = table.insert(&pool, &mut blob_store, row) {
+                    ptrs.push(row_ref.pointer());
+                }
+            }
+            for page in table.inner.pages.iter_mut() {
+                page.save_content_hash();
+            }
+
+            // The copy is equal to the original, content hashes included,
+            // and shares all of its pages.
+            let copy = table.clone_with_pool(&pool);
+            prop_assert_eq!(&copy, &table);
+            prop_assert_eq!(copy.inner.pages.num_shared_pages(), table.num_pages());
+            let copy_blob_store = blob_store.clone();
+
+            // Deleting from the original leaves the copy untouched.
+            for ptr in ptrs.iter().copied() {
+                table.delete(&mut blob_store, ptr, |_| ());
+            }
+            prop_assert_eq!(table.num_rows(), 0);
+            prop_assert_eq!(copy.num_rows(), ptrs.len() as u64);
+            let mut copied_ptrs = copy.scan_rows(&copy_blob_store).map(|r| r.pointer()).collect::<Vec<_>>();
+            copied_ptrs.sort();
+            ptrs.sort();
+            prop_assert_eq!(copied_ptrs, ptrs);
+
+            // Only the pages written to were copied, and the copy's pages kept their hashes.
+            let num_empty_pages = copy.inner.pages.iter().filter(|page| page.num_rows() == 0).count();
+            prop_assert_eq!(copy.inner.pages.num_shared_pages(), num_empty_pages);
+            prop_assert!(copy.inner.pages.iter().all(|page| page.unmodified_hash().is_some()));
+        }
+
         #[test]
         fn row_size_reporting_matches_slow_implementations((ty, vals) in generate_typed_row_vec(128, 2048)) {
             let pool = PagePool::new_for_test();
@@ -2719,6 +3185,242 @@ pub(crate) mod test {
         table.get_row_ref(&NullBlobStore, ptr).unwrap().to_product_value();
     }
 
+    #[test]
+    fn compact_moves_rows_out_of_sparse_pages() {
+        for with_index in [false, true] {
+            let pool = PagePool::new_for_test();
+            let mut blob_store = HashMapBlobStore::default();
+            let mut table = table([AlgebraicType::U64, AlgebraicType::String].into());
+            let index_id = IndexId(0);
+            if with_index {
+                let algo = BTreeAlgorithm { columns: col_list![0] }.into();
+                let index = table.new_index(&algo, true).unwrap();
+                // SAFETY: Index was derived from `table`.
+                unsafe { table.insert_index(&NullBlobStore, index_id, index) };
+            }
+
+            let row = |i: u64| product![i, format!("row {i}")];
+            let ptrs = (0..10_000)
+                .map(|i| table.insert(&pool, &mut blob_store, &row(i)).unwrap().1.pointer())
+                .collect::<Vec<_>>();
+            let num_pages_before = table.num_pages();
+            assert!(num_pages_before > 2);
+
+            // Delete three out of every four rows.
+            for (i, ptr) in ptrs.into_iter().enumerate() {
+                if i % 4 != 0 {
+                    table.delete(&mut blob_store, ptr, |_| ());
+                }
+            }
+            assert_eq!(table.num_pages(), num_pages_before);
+            assert!(table.fragmentation() > 0.5);
+
+            // Compact in small increments, as done between transactions.
+            let mut pages_freed = 0;
+            loop {
+                let compaction = table.compact(&pool, &mut blob_store, 100, 0.5);
+                assert!(compaction.rows_moved <= 100);
+                pages_freed += compaction.pages_freed;
+                if compaction.done {
+                    break;
+                }
+            }
+            assert!(pages_freed > 0);
+            assert_eq!(table.num_pages(), num_pages_before - pages_freed);
+            assert!(table.num_pages() == 1 || table.fragmentation() < 0.5);
+
+            // All the remaining rows survived, and the index and pointer map agree with their new locations.
+            assert_eq!(table.num_rows(), 2_500);
+            assert_eq!(table.reconstruct_num_rows(), 2_500);
+            let mut rows = table
+                .scan_rows(&blob_store)
+                .map(|row_ref| {
+                    if with_index {
+                        let index = table.get_index_by_id_with_table(&blob_store, index_id).unwrap();
+                        let key = row_ref.read_col::<u64>(ColId(0)).unwrap().into();
+                        let found = index.seek_point(&key).map(|r| r.pointer()).collect::<Vec<_>>();
+                        assert_eq!(found, [row_ref.pointer()]);
+                    } else {
+                        assert_eq!(table.pointers_for(row_ref.row_hash()), [row_ref.pointer()]);
+                    }
+                    row_ref.to_product_value()
+                })
+                .collect::<Vec<_>>();
+            rows.sort();
+            let expected = (0..10_000).step_by(4).map(row).collect::<Vec<_>>();
+            assert_eq!(rows, expected);
+        }
+    }
+
+    #[test]
+    fn compact_moves_large_blobs_without_changing_their_uses() {
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut table = table([AlgebraicType::U64, AlgebraicType::bytes()].into());
+
+        // Each row holds its own large blob.
+        let row = |i: u64| product![i, AlgebraicValue::Bytes(vec![i as u8; 4096].into())];
+        let ptrs = (0..2_000)
+            .map(|i| table.insert(&pool, &mut blob_store, &row(i)).unwrap().1.pointer())
+            .collect::<Vec<_>>();
+        for (i, ptr) in ptrs.into_iter().enumerate() {
+            if i % 4 != 0 {
+                table.delete(&mut blob_store, ptr, |_| ());
+            }
+        }
+        let uses_before = blob_store.usage_counter();
+        let blob_bytes_before = table.blob_store_bytes;
+
+        let compaction = table.compact(&pool, &mut blob_store, usize::MAX, 0.5);
+        assert!(compaction.done);
+        assert!(compaction.rows_moved > 0);
+
+        assert_eq!(blob_store.usage_counter(), uses_before);
+        assert_eq!(table.blob_store_bytes, blob_bytes_before);
+        let mut rows = table
+            .scan_rows(&blob_store)
+            .map(|row_ref| row_ref.to_product_value())
+            .collect::<Vec<_>>();
+        rows.sort();
+        assert_eq!(rows, (0..2_000).step_by(4).map(row).collect::<Vec<_>>());
+    }
+
+    /// A [`PageStore`] keeping serialized pages in memory, like the object repository of a snapshot.
+    #[derive(Debug, Default)]
+    struct MemPageStore(std::sync::Mutex<std::collections::HashMap<blake3::Hash, Vec<u8>>>);
+
+    impl PageStore for MemPageStore {
+        fn read_page(&self, hash: &blake3::Hash) -> std::io::Result<Box<Page>> {
+            let objects = self.0.lock().unwrap();
+            let bytes = objects.get(hash).ok_or(std::io::ErrorKind::NotFound)?;
+            Ok(PagePool::new_for_test().take_deserialize_from(bytes).unwrap())
+        }
+    }
+
+    #[test]
+    fn evicted_pages_are_reloaded_on_access() {
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut table = table([AlgebraicType::U64, AlgebraicType::String].into());
+        let row = |i: u64| product![i, format!("row {i}")];
+        for i in 0..10_000 {
+            table.insert(&pool, &mut blob_store, &row(i)).unwrap();
+        }
+        let num_pages = table.num_pages();
+        let bytes_used_by_rows = table.bytes_used_by_rows();
+
+        // Nothing is evicted without a store to reload from.
+        assert_eq!(table.evict_cold_pages(&pool, usize::MAX), 0);
+
+        // "Snapshot" every page into the store.
+        let store = Arc::new(MemPageStore::default());
+        for (hash, page) in table.iter_pages_with_hashes() {
+            let bytes = to_vec(page.unwrap()).unwrap();
+            store.0.lock().unwrap().insert(hash, bytes);
+        }
+        table.set_page_store(store);
+
+        // All pages are clean and unreferenced, so all of them are evicted.
+        assert_eq!(table.evict_cold_pages(&pool, usize::MAX), num_pages);
+        assert_eq!(table.num_resident_pages(), 0);
+        assert_eq!(table.bytes_used_by_rows(), bytes_used_by_rows);
+        assert!(table.iter_pages_with_hashes().all(|(_, page)| page.is_none()));
+
+        // Scanning reloads every page exactly once.
+        let mut rows = table
+            .scan_rows(&blob_store)
+            .map(|r| r.to_product_value())
+            .collect::<Vec<_>>();
+        rows.sort();
+        assert_eq!(rows, (0..10_000).map(row).collect::<Vec<_>>());
+        assert_eq!(table.num_resident_pages(), num_pages);
+        let stats = table.take_page_cache_stats();
+        assert_eq!(stats.misses, num_pages as u64);
+        assert!(stats.hits > 0);
+        assert_eq!(table.take_page_cache_stats(), PageCacheStats::default());
+
+        // Recently accessed pages get a second chance.
+        assert_eq!(table.evict_cold_pages(&pool, usize::MAX), 0);
+        // Modified pages are never evicted, as the store doesn't have their contents.
+        table.insert(&pool, &mut blob_store, &row(10_000)).unwrap();
+        table.evict_cold_pages(&pool, usize::MAX);
+        table.evict_cold_pages(&pool, usize::MAX);
+        assert_eq!(table.num_resident_pages(), 1);
+        assert_eq!(table.num_rows(), 10_001);
+    }
+
+    #[test]
+    fn unreadable_evicted_pages_are_errors() {
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut table = table([AlgebraicType::U64].into());
+        for i in 0..10_000u64 {
+            table.insert(&pool, &mut blob_store, &product![i]).unwrap();
+        }
+        let store = Arc::new(MemPageStore::default());
+        let hashes = table.iter_pages_with_hashes().map(|(hash, _)| hash).collect::<Vec<_>>();
+        table.set_page_store(store);
+        assert_eq!(table.evict_cold_pages(&pool, usize::MAX), hashes.len());
+
+        // The store doesn't have the pages, so they can't be reloaded.
+        let err = table.load_pages().unwrap_err();
+        assert_eq!(err.hash, hashes[0]);
+        assert_eq!(err.source.kind(), std::io::ErrorKind::NotFound);
+        assert!(table
+            .load_page(RowPointer::new(
+                false,
+                PageIndex(1),
+                PageOffset(0),
+                SquashedOffset::COMMITTED_STATE
+            ))
+            .is_err());
+        assert!(table.load_non_full_pages().is_err());
+        assert!(table.insert(&pool, &mut blob_store, &product![10_000u64]).is_err());
+        assert_eq!(table.num_resident_pages(), 0);
+    }
+
+    #[test]
+    fn restored_evicted_pages_stay_within_max_resident_pages() {
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let ty: ProductType = [AlgebraicType::U64, AlgebraicType::String].into();
+        let row = |i: u64| product![i, format!("row {i}")];
+        let mut src = table(ty.clone());
+        for i in 0..10_000 {
+            src.insert(&pool, &mut blob_store, &row(i)).unwrap();
+        }
+        let store = Arc::new(MemPageStore::default());
+        let mut hashes = Vec::new();
+        for (hash, page) in src.iter_pages_with_hashes() {
+            store.0.lock().unwrap().insert(hash, to_vec(page.unwrap()).unwrap());
+            hashes.push(hash);
+        }
+
+        let mut table = table(ty);
+        // SAFETY: The pages were made by a table of the same type.
+        unsafe { table.set_evicted_pages(hashes, store, &pool, &blob_store, 2) }.unwrap();
+        assert_eq!(table.num_pages(), src.num_pages());
+        assert_eq!(table.num_resident_pages(), 2);
+        assert_eq!(table.num_rows(), src.num_rows());
+        assert_eq!(table.bytes_used_by_rows(), src.bytes_used_by_rows());
+        assert_eq!(table.pointer_map, src.pointer_map);
+
+        // Building an index doesn't keep the pages it reloads.
+        let index_id = IndexId(0);
+        let algo = BTreeAlgorithm { columns: col_list![0] }.into();
+        let index = table.new_index(&algo, true).unwrap();
+        // SAFETY: The `index` was made for `table`.
+        unsafe { table.try_insert_index(&pool, &blob_store, index_id, index) }.unwrap();
+        assert_eq!(table.num_resident_pages(), 2);
+
+        let index = table.get_index_by_id_with_table(&blob_store, index_id).unwrap();
+        let key = 9_999u64.into();
+        index.load_point(&key).unwrap();
+        let rows = index.seek_point(&key).map(|r| r.to_product_value()).collect::<Vec<_>>();
+        assert_eq!(rows, [row(9_999)]);
+        assert_eq!(table.num_resident_pages(), 3);
+    }
+
     #[test]
     fn test_blob_store_bytes() {
         let pt: ProductType = [AlgebraicType::String, AlgebraicType::I32].into();
@@ -28,11 +28,12 @@ use super::indexes::RowPointer;
 use super::table::RowRef;
 use crate::{read_column::ReadColumn, static_assert_size};
 use core::ops::RangeBounds;
-use spacetimedb_primitives::ColList;
+use spacetimedb_lib::full_text;
+use spacetimedb_primitives::{ColId, ColList};
 use spacetimedb_sats::memory_usage::MemoryUsage;
 use spacetimedb_sats::{
     algebraic_value::Packed, i256, product_value::InvalidFieldError, sum_value::SumTag, u256, AlgebraicType,
-    AlgebraicValue, ProductType, F32, F64,
+    AlgebraicValue, ProductType, ProductValue, F32, F64,
 };
 
 mod key_size;
@@ -42,7 +43,8 @@ pub mod unique_direct_index;
 pub mod uniquemap;
 
 pub use key_size::KeySize;
-use spacetimedb_schema::def::IndexAlgorithm;
+use spacetimedb_schema::def::index_filter::IndexFilter;
+use spacetimedb_schema::def::{IndexAlgorithm, IndexKeyExpr};
 use unique_direct_fixed_cap_index::{UniqueDirectFixedCapIndex, UniqueDirectFixedCapIndexRangeIter};
 use unique_direct_index::{UniqueDirectIndex, UniqueDirectIndexPointIter, UniqueDirectIndexRangeIter};
 
@@ -196,7 +198,7 @@ impl Iterator for TableIndexRangeIter<'_> {
 /// An index from a key type determined at runtime to `RowPointer`(s).
 ///
 /// See module docs for info about specialization.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum TypedIndex {
     // All the non-unique btree index types.
     BtreeBool(BtreeIndex<bool>),
@@ -303,6 +305,12 @@ impl TypedIndex {
     fn new(key_type: &AlgebraicType, index_algo: &IndexAlgorithm, is_unique: bool) -> Self {
         use TypedIndex::*;
 
+        // Computed keys and tokens are not specialized on their type.
+        if let IndexAlgorithm::BTreeExpr(_) | IndexAlgorithm::FullText(_) = index_algo {
+            assert!(!is_unique);
+            return BtreeAV(<_>::default());
+        }
+
         if let IndexAlgorithm::Direct(_) = index_algo {
             assert!(is_unique);
             return match key_type {
@@ -505,7 +513,7 @@ impl TypedIndex {
             unsafe { T::unchecked_read_column(row_ref, col_layout) }
         }
 
-        fn mm_insert_at_type<T: Ord + ReadColumn + KeySize>(
+        fn mm_insert_at_type<T: Ord + Clone + ReadColumn + KeySize>(
             this: &mut BtreeIndex<T>,
             cols: &ColList,
             row_ref: RowRef<'_>,
@@ -515,7 +523,7 @@ impl TypedIndex {
             this.insert(key, row_ref.pointer());
             Ok(key_size)
         }
-        fn um_insert_at_type<T: Ord + ReadColumn + KeySize>(
+        fn um_insert_at_type<T: Ord + Clone + ReadColumn + KeySize>(
             this: &mut BtreeUniqueIndex<T>,
             cols: &ColList,
             row_ref: RowRef<'_>,
@@ -633,7 +641,7 @@ impl TypedIndex {
     /// so we have to return the size across this boundary.
     // TODO(centril): make this unsafe and use unchecked conversions.
     fn delete(&mut self, cols: &ColList, row_ref: RowRef<'_>) -> Result<Option<usize>, InvalidFieldError> {
-        fn mm_delete_at_type<T: Ord + ReadColumn + KeySize>(
+        fn mm_delete_at_type<T: Ord + Clone + ReadColumn + KeySize>(
             this: &mut BtreeIndex<T>,
             cols: &ColList,
             row_ref: RowRef<'_>,
@@ -643,7 +651,7 @@ impl TypedIndex {
             let key_size = key.key_size_in_bytes();
             Ok(this.delete(&key, &row_ref.pointer()).then_some(key_size))
         }
-        fn um_delete_at_type<T: Ord + ReadColumn + KeySize>(
+        fn um_delete_at_type<T: Ord + Clone + ReadColumn + KeySize>(
             this: &mut BtreeUniqueIndex<T>,
             cols: &ColList,
             row_ref: RowRef<'_>,
@@ -741,15 +749,49 @@ impl TypedIndex {
         }
     }
 
+    /// Add `ptr` to the index `self` under the computed `key`.
+    ///
+    /// The returned `usize` is the number of bytes used by the key.
+    ///
+    /// Panics if `self` is not keyed on `AlgebraicValue`s,
+    /// which is the case for all indices with computed keys.
+    fn insert_computed(&mut self, key: AlgebraicValue, ptr: RowPointer) -> Result<usize, RowPointer> {
+        let key_size = key.key_size_in_bytes();
+        match self {
+            Self::BtreeAV(this) => {
+                this.insert(key, ptr);
+                Ok(key_size)
+            }
+            Self::UniqueBtreeAV(this) => this.insert(key, ptr).map_err(|ptr| *ptr).map(|_| key_size),
+            _ => unreachable!("computed keys are only stored in `AlgebraicValue` indices"),
+        }
+    }
+
+    /// Remove `ptr` under the computed `key` from the index `self`.
+    ///
+    /// If the row was present and has been deleted, returns `Some(key_size_in_bytes)`.
+    ///
+    /// Panics if `self` is not keyed on `AlgebraicValue`s,
+    /// which is the case for all indices with computed keys.
+    fn delete_computed(&mut self, key: &AlgebraicValue, ptr: RowPointer) -> Option<usize> {
+        let key_size = key.key_size_in_bytes();
+        let deleted = match self {
+            Self::BtreeAV(this) => this.delete(key, &ptr),
+            Self::UniqueBtreeAV(this) => this.delete(key),
+            _ => unreachable!("computed keys are only stored in `AlgebraicValue` indices"),
+        };
+        deleted.then_some(key_size)
+    }
+
     fn seek_point(&self, key: &AlgebraicValue) -> TypedIndexPointIter<'_> {
-        fn mm_iter_at_type<'a, T: Ord>(
+        fn mm_iter_at_type<'a, T: Ord + Clone>(
             this: &'a BtreeIndex<T>,
             key: &AlgebraicValue,
             av_as_t: impl Fn(&AlgebraicValue) -> Option<&T>,
         ) -> BtreeIndexPointIter<'a> {
             this.values_in_point(av_as_t(key).expect("key does not conform to key type of index"))
         }
-        fn um_iter_at_type<'a, T: Ord>(
+        fn um_iter_at_type<'a, T: Ord + Clone>(
             this: &'a BtreeUniqueIndex<T>,
             key: &AlgebraicValue,
             av_as_t: impl Fn(&AlgebraicValue) -> Option<&T>,
@@ -827,7 +869,7 @@ impl TypedIndex {
     }
 
     fn seek_range(&self, range: &impl RangeBounds<AlgebraicValue>) -> TypedIndexRangeIter<'_> {
-        fn mm_iter_at_type<'a, T: Ord>(
+        fn mm_iter_at_type<'a, T: Ord + Clone>(
             this: &'a BtreeIndex<T>,
             range: &impl RangeBounds<AlgebraicValue>,
             av_as_t: impl Fn(&AlgebraicValue) -> Option<&T>,
@@ -837,7 +879,7 @@ impl TypedIndex {
             let end = range.end_bound().map(av_as_t);
             this.values_in_range(&(start, end))
         }
-        fn um_iter_at_type<'a, T: Ord>(
+        fn um_iter_at_type<'a, T: Ord + Clone>(
             this: &'a BtreeUniqueIndex<T>,
             range: &impl RangeBounds<AlgebraicValue>,
             av_as_t: impl Fn(&AlgebraicValue) -> Option<&T>,
@@ -1076,8 +1118,9 @@ impl TypedIndex {
     }
 }
 
-/// An index on a set of [`ColId`]s of a table.
-#[derive(Debug, PartialEq, Eq)]
+/// An index on a set of [`ColId`]s of a table,
+/// or on keys computed from its rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TableIndex {
     /// The actual index, specialized for the appropriate key type.
     idx: TypedIndex,
@@ -1087,6 +1130,7 @@ pub struct TableIndex {
     pub key_type: AlgebraicType,
 
     /// The number of rows in this index.
+    /// For a full-text index, this is the number of (token, row) entries.
     ///
     /// Memoized counter for [`Self::num_rows`].
     num_rows: u64,
@@ -1100,7 +1144,19 @@ pub struct TableIndex {
     /// Given a full row, typed at some `ty: ProductType`,
     /// these columns are the ones that this index indexes.
     /// Projecting the `ty` to `self.indexed_columns` yields the index's type `self.key_type`.
+    ///
+    /// This is empty when the index is keyed on `self.key_exprs` or `self.full_text` instead.
     pub indexed_columns: ColList,
+
+    /// The expressions computing the keys of this index from a full row,
+    /// or empty if the keys are a projection to `self.indexed_columns`.
+    pub key_exprs: Box<[IndexKeyExpr]>,
+
+    /// The filter a row must match to be in this index, if this is a partial index.
+    pub filter: Option<Box<IndexFilter>>,
+
+    /// The `String` column whose tokens are the keys of this index, if this is a full-text index.
+    pub full_text: Option<ColId>,
 }
 
 impl MemoryUsage for TableIndex {
@@ -1111,16 +1167,27 @@ impl MemoryUsage for TableIndex {
             num_rows,
             num_key_bytes,
             indexed_columns,
+            key_exprs,
+            filter,
+            full_text,
         } = self;
+        let key_exprs = key_exprs.len() * size_of::<IndexKeyExpr>()
+            + key_exprs
+                .iter()
+                .map(|key| key.fields.len() * size_of::<u16>())
+                .sum::<usize>();
         idx.heap_usage()
             + key_type.heap_usage()
             + num_rows.heap_usage()
             + num_key_bytes.heap_usage()
             + indexed_columns.heap_usage()
+            + key_exprs
+            + filter.as_ref().map_or(0, |_| size_of::<IndexFilter>())
+            + full_text.heap_usage()
     }
 }
 
-static_assert_size!(TableIndex, 88);
+static_assert_size!(TableIndex, 120);
 
 impl TableIndex {
     /// Returns a new possibly unique index, with `index_id` for a choice of indexing algorithm.
@@ -1129,8 +1196,23 @@ impl TableIndex {
         index_algo: &IndexAlgorithm,
         is_unique: bool,
     ) -> Result<Self, InvalidFieldError> {
-        let indexed_columns = index_algo.columns().to_owned();
-        let key_type = row_type.project(&indexed_columns)?;
+        let (indexed_columns, filter) = match index_algo.partial() {
+            Some(partial) => {
+                let filter = partial
+                    .resolve_filter(row_type)
+                    .expect("filter of partial index should have been validated");
+                (partial.columns.clone(), Some(Box::new(filter)))
+            }
+            // Indexes over computed keys or tokens have no indexed columns,
+            // see `key_exprs` and `full_text` below.
+            None => (
+                index_algo.columns().map_or_else(ColList::empty, |cols| cols.to_owned()),
+                None,
+            ),
+        };
+        let key_exprs: Box<[_]> = index_algo.key_exprs().unwrap_or_default().into();
+        let full_text = index_algo.full_text();
+        let key_type = Self::compute_key_type(row_type, &indexed_columns, &key_exprs, full_text)?;
         let typed_index = TypedIndex::new(&key_type, index_algo, is_unique);
         Ok(Self {
             idx: typed_index,
@@ -1138,21 +1220,113 @@ impl TableIndex {
             num_rows: 0,
             num_key_bytes: 0,
             indexed_columns,
+            key_exprs,
+            filter,
+            full_text,
         })
     }
 
+    /// Returns the key type of an index on `indexed_columns`, `key_exprs`, or the tokens of `full_text`
+    /// over rows of `row_type`.
+    fn compute_key_type(
+        row_type: &ProductType,
+        indexed_columns: &ColList,
+        key_exprs: &[IndexKeyExpr],
+        full_text: Option<ColId>,
+    ) -> Result<AlgebraicType, InvalidFieldError> {
+        if let Some(col) = full_text {
+            return row_type
+                .elements
+                .get(col.idx())
+                .filter(|elem| elem.algebraic_type.is_string())
+                .map(|_| AlgebraicType::String)
+                .ok_or(col.into());
+        }
+        let key_type = |key: &IndexKeyExpr| key.key_type(row_type).ok_or(InvalidFieldError::from(key.column));
+        match key_exprs {
+            [] => row_type.project(indexed_columns),
+            [key] => key_type(key),
+            keys => keys
+                .iter()
+                .map(key_type)
+                .collect::<Result<ProductType, _>>()
+                .map(AlgebraicType::Product),
+        }
+    }
+
+    /// Re-computes the key type of this index for rows of `row_type`.
+    pub fn recompute_key_type(&mut self, row_type: &ProductType) -> Result<(), InvalidFieldError> {
+        self.key_type = Self::compute_key_type(row_type, &self.indexed_columns, &self.key_exprs, self.full_text)?;
+        Ok(())
+    }
+
+    /// Computes the key of `row_ref` in this index, which must be keyed on `self.key_exprs`.
+    fn compute_key(&self, row_ref: RowRef<'_>) -> Result<AlgebraicValue, InvalidFieldError> {
+        let eval = |key: &IndexKeyExpr| -> Result<AlgebraicValue, InvalidFieldError> {
+            let value = row_ref.read_col(key.column).map_err(|_| key.column)?;
+            key.eval(value).ok_or_else(|| key.column.into())
+        };
+        match &*self.key_exprs {
+            [key] => eval(key),
+            keys => keys
+                .iter()
+                .map(eval)
+                .collect::<Result<ProductValue, _>>()
+                .map(Into::into),
+        }
+    }
+
+    /// Returns the tokens of the `String` column `col` of `row_ref`, as keys of a full-text index.
+    fn tokens_of(row_ref: RowRef<'_>, col: ColId) -> Result<Vec<AlgebraicValue>, InvalidFieldError> {
+        let text: Box<str> = row_ref.read_col(col).map_err(|_| col)?;
+        Ok(full_text::tokenize(&text)
+            .into_iter()
+            .map(|token| AlgebraicValue::String(token.into()))
+            .collect())
+    }
+
+    /// Returns whether `row_ref` belongs in this index,
+    /// i.e., whether it matches the filter of a partial index.
+    pub fn matches_filter(&self, row_ref: RowRef<'_>) -> bool {
+        self.filter.as_ref().is_none_or(|filter| {
+            filter.eval(&mut |col| {
+                row_ref
+                    .read_col(col)
+                    .expect("row should conform to the filter of the index")
+            })
+        })
+    }
+
+    /// Returns the key of `row_ref` in this index.
+    ///
+    /// This is the projection of `row_ref` to `self.indexed_columns`,
+    /// or the value of `self.key_exprs` evaluated on `row_ref`.
+    pub fn key_of(&self, row_ref: RowRef<'_>) -> Result<AlgebraicValue, InvalidFieldError> {
+        if self.key_exprs.is_empty() {
+            row_ref.project(&self.indexed_columns)
+        } else {
+            self.compute_key(row_ref)
+        }
+    }
+
     /// Clones the structure of this index but not the indexed elements,
     /// so the returned index is empty.
     pub fn clone_structure(&self) -> Self {
         let key_type = self.key_type.clone();
         let idx = self.idx.clone_structure();
         let indexed_columns = self.indexed_columns.clone();
+        let key_exprs = self.key_exprs.clone();
+        let filter = self.filter.clone();
+        let full_text = self.full_text;
         Self {
             idx,
             key_type,
             num_rows: 0,
             num_key_bytes: 0,
             indexed_columns,
+            key_exprs,
+            filter,
+            full_text,
         }
     }
 
@@ -1164,6 +1338,8 @@ impl TableIndex {
     /// Inserts `ptr` with the value `row` to this index.
     /// This index will extract the necessary values from `row` based on `self.indexed_columns`.
     ///
+    /// Rows not matching the filter of a partial index are not inserted.
+    ///
     /// Returns `Err(existing_row)` if this insertion would violate a unique constraint.
     ///
     /// # Safety
@@ -1174,10 +1350,30 @@ impl TableIndex {
     /// It also follows from `row_ref`'s type/layout
     /// being the same as passed in on `self`'s construction.
     pub unsafe fn check_and_insert(&mut self, row_ref: RowRef<'_>) -> Result<(), RowPointer> {
-        // SAFETY:
-        // 1. We're passing the same `ColList` that was provided during construction.
-        // 2. Forward the caller's proof obligation.
-        let res = unsafe { self.idx.insert(&self.indexed_columns, row_ref) };
+        if !self.matches_filter(row_ref) {
+            return Ok(());
+        }
+        if let Some(col) = self.full_text {
+            // A full-text index is never unique, so this can't fail.
+            let tokens = Self::tokens_of(row_ref, col).expect("row should have a `String` column for the index");
+            for token in tokens {
+                let key_size = self.idx.insert_computed(token, row_ref.pointer())?;
+                self.num_rows += 1;
+                self.num_key_bytes += key_size as u64;
+            }
+            return Ok(());
+        }
+        let res = if self.key_exprs.is_empty() {
+            // SAFETY:
+            // 1. We're passing the same `ColList` that was provided during construction.
+            // 2. Forward the caller's proof obligation.
+            unsafe { self.idx.insert(&self.indexed_columns, row_ref) }
+        } else {
+            let key = self
+                .compute_key(row_ref)
+                .expect("row should conform to the key expressions of the index");
+            self.idx.insert_computed(key, row_ref.pointer())
+        };
         match res {
             Ok(key_size) => {
                 // No existing row; the new row was inserted.
@@ -1191,11 +1387,31 @@ impl TableIndex {
         }
     }
 
-    /// Deletes `row_ref` with its indexed value `row_ref.project(&self.indexed_columns)` from this index.
+    /// Deletes `row_ref` with its indexed value `self.key_of(row_ref)` from this index.
     ///
     /// Returns whether `ptr` was present.
     pub fn delete(&mut self, row_ref: RowRef<'_>) -> Result<bool, InvalidFieldError> {
-        if let Some(size_in_bytes) = self.idx.delete(&self.indexed_columns, row_ref)? {
+        if !self.matches_filter(row_ref) {
+            return Ok(false);
+        }
+        if let Some(col) = self.full_text {
+            let mut deleted = false;
+            for token in Self::tokens_of(row_ref, col)? {
+                if let Some(size_in_bytes) = self.idx.delete_computed(&token, row_ref.pointer()) {
+                    self.num_rows -= 1;
+                    self.num_key_bytes -= size_in_bytes as u64;
+                    deleted = true;
+                }
+            }
+            return Ok(deleted);
+        }
+        let deleted = if self.key_exprs.is_empty() {
+            self.idx.delete(&self.indexed_columns, row_ref)?
+        } else {
+            let key = self.compute_key(row_ref)?;
+            self.idx.delete_computed(&key, row_ref.pointer())
+        };
+        if let Some(size_in_bytes) = deleted {
             // Was present, and deleted: update the `num_rows` and `num_key_bytes` counters.
             self.num_rows -= 1;
             self.num_key_bytes -= size_in_bytes as u64;
@@ -1358,13 +1574,14 @@ mod test {
     use proptest::prelude::*;
     use proptest::{collection::vec, test_runner::TestCaseResult};
     use spacetimedb_data_structures::map::HashMap;
+    use spacetimedb_lib::db::raw_def::v9::IndexKeyFunction;
     use spacetimedb_primitives::ColId;
     use spacetimedb_sats::{
         product,
         proptest::{generate_product_value, generate_row_type},
         AlgebraicType, ProductType, ProductValue,
     };
-    use spacetimedb_schema::def::BTreeAlgorithm;
+    use spacetimedb_schema::def::{BTreeAlgorithm, BTreeExprAlgorithm, FullTextAlgorithm, PartialBTreeAlgorithm};
 
     fn gen_cols(ty_len: usize) -> impl Strategy<Value = ColList> {
         vec((0..ty_len as u16).prop_map_into::<ColId>(), 1..=ty_len)
@@ -1561,4 +1778,129 @@ mod test {
             test_seek(&index, &val_to_ptr, (Excluded(V(prev)), Excluded(V(next))), [needle])?;
         }
     }
+
+    #[test]
+    fn computed_keys_on_nested_fields_and_functions() {
+        let position = AlgebraicType::product([("x", AlgebraicType::I32), ("y", AlgebraicType::I32)]);
+        let ty = ProductType::from([("position", position), ("name", AlgebraicType::String)]);
+        let by_x = BTreeExprAlgorithm {
+            keys: [IndexKeyExpr {
+                column: 0.into(),
+                fields: [0].into(),
+                function: None,
+            }]
+            .into(),
+        };
+        let by_lower_name = BTreeExprAlgorithm {
+            keys: [IndexKeyExpr {
+                column: 1.into(),
+                fields: [].into(),
+                function: Some(IndexKeyFunction::Lower),
+            }]
+            .into(),
+        };
+        let mut by_x = TableIndex::new(&ty, &by_x.into(), false).unwrap();
+        let mut by_lower_name = TableIndex::new(&ty, &by_lower_name.into(), false).unwrap();
+        assert_eq!(by_x.key_type, AlgebraicType::I32);
+        assert_eq!(by_lower_name.key_type, AlgebraicType::String);
+
+        let mut table = table(ty);
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut insert = |row: ProductValue| {
+            let row_ref = table.insert(&pool, &mut blob_store, &row).unwrap().1;
+            // SAFETY: `row_ref` has the same type as was passed in when constructing the indices.
+            unsafe {
+                by_x.check_and_insert(row_ref).unwrap();
+                by_lower_name.check_and_insert(row_ref).unwrap();
+            }
+            row_ref.pointer()
+        };
+        let alice = insert(product![product![5i32, 1i32], "Alice"]);
+        let bob = insert(product![product![7i32, 5i32], "BOB"]);
+
+        let seek = |index: &TableIndex, key: AlgebraicValue| index.seek_point(&key).collect::<Vec<_>>();
+        assert_eq!(seek(&by_x, 5i32.into()), [alice]);
+        assert_eq!(seek(&by_x, 1i32.into()), []);
+        assert_eq!(seek(&by_lower_name, "bob".into()), [bob]);
+        assert_eq!(seek(&by_lower_name, "BOB".into()), []);
+
+        let bob = table.get_row_ref(&blob_store, bob).unwrap();
+        assert_eq!(by_x.key_of(bob).unwrap(), 7i32.into());
+        assert!(by_x.delete(bob).unwrap());
+        assert!(by_lower_name.delete(bob).unwrap());
+        assert_eq!(by_x.num_rows(), 1);
+        assert_eq!(seek(&by_lower_name, "bob".into()), []);
+    }
+
+    #[test]
+    fn partial_index_only_contains_matching_rows() {
+        let ty = ProductType::from([("status", AlgebraicType::String), ("level", AlgebraicType::U32)]);
+        let algo = PartialBTreeAlgorithm {
+            columns: 1.into(),
+            filter: "status = 'active'".into(),
+        };
+        let mut index = TableIndex::new(&ty, &algo.into(), false).unwrap();
+        assert_eq!(index.key_type, AlgebraicType::U32);
+
+        let mut table = table(ty);
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut insert = |row: ProductValue| {
+            let row_ref = table.insert(&pool, &mut blob_store, &row).unwrap().1;
+            // SAFETY: `row_ref` has the same type as was passed in when constructing the index.
+            unsafe { index.check_and_insert(row_ref).unwrap() };
+            row_ref.pointer()
+        };
+        let active = insert(product!["active", 5u32]);
+        let idle = insert(product!["idle", 5u32]);
+
+        assert_eq!(index.num_rows(), 1);
+        assert_eq!(index.seek_point(&5u32.into()).collect::<Vec<_>>(), [active]);
+
+        let idle = table.get_row_ref(&blob_store, idle).unwrap();
+        assert!(!index.delete(idle).unwrap());
+        let active = table.get_row_ref(&blob_store, active).unwrap();
+        assert!(index.delete(active).unwrap());
+        assert_eq!(index.num_rows(), 0);
+    }
+
+    #[test]
+    fn full_text_index_maps_tokens_to_rows() {
+        let ty = ProductType::from([("id", AlgebraicType::U32), ("name", AlgebraicType::String)]);
+        let algo = FullTextAlgorithm { column: 1.into() };
+        let mut index = TableIndex::new(&ty, &algo.into(), false).unwrap();
+        assert_eq!(index.key_type, AlgebraicType::String);
+
+        let mut table = table(ty);
+        let pool = PagePool::new_for_test();
+        let mut blob_store = HashMapBlobStore::default();
+        let mut insert = |row: ProductValue| {
+            let row_ref = table.insert(&pool, &mut blob_store, &row).unwrap().1;
+            // SAFETY: `row_ref` has the same type as was passed in when constructing the index.
+            unsafe { index.check_and_insert(row_ref).unwrap() };
+            row_ref.pointer()
+        };
+        let sword = insert(product![1u32, "Red Sword of the red dragon"]);
+        let shield = insert(product![2u32, "red shield"]);
+
+        // Each distinct token of a row is a separate entry.
+        assert_eq!(index.num_rows(), 7);
+        let seek = |index: &TableIndex, token: &str| {
+            let mut ptrs = index.seek_point(&token.into()).collect::<Vec<_>>();
+            ptrs.sort();
+            ptrs
+        };
+        let mut both = vec![sword, shield];
+        both.sort();
+        assert_eq!(seek(&index, "red"), both);
+        assert_eq!(seek(&index, "dragon"), [sword]);
+        assert_eq!(seek(&index, "Red"), []);
+
+        let sword = table.get_row_ref(&blob_store, sword).unwrap();
+        assert!(index.delete(sword).unwrap());
+        assert_eq!(index.num_rows(), 2);
+        assert_eq!(seek(&index, "red"), [shield]);
+        assert_eq!(seek(&index, "dragon"), []);
+    }
 }
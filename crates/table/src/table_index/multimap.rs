@@ -1,40 +1,53 @@
+use core::mem;
 use core::ops::RangeBounds;
 use core::slice;
-use smallvec::SmallVec;
+use imbl::ordmap::{OrdMap, RangedIter};
+use smallvec::{smallvec, SmallVec};
 use spacetimedb_sats::memory_usage::MemoryUsage;
-use std::collections::btree_map::{BTreeMap, Range};
 
 /// A multi map that relates a `K` to a *set* of `V`s.
-#[derive(Debug, PartialEq, Eq)]
-pub struct MultiMap<K, V> {
-    /// The map is backed by a `BTreeMap` for relating keys to values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiMap<K: Ord + Clone, V: Clone> {
+    /// The map is backed by a persistent `OrdMap` for relating keys to values,
+    /// so that cloning the map is cheap
+    /// and modifying a clone only copies the nodes on the path to the modified key.
+    /// This lets a table shared with a read snapshot be copied and then written to
+    /// without copying its indexes.
     ///
     /// A value set is stored as a `SmallVec`.
     /// This is an optimization over a `Vec<_>`
     /// as we allow a single element to be stored inline
     /// to improve performance for the common case of one element.
-    map: BTreeMap<K, SmallVec<[V; 1]>>,
+    map: OrdMap<K, SmallVec<[V; 1]>>,
 }
 
-impl<K, V> Default for MultiMap<K, V> {
+impl<K: Ord + Clone, V: Clone> Default for MultiMap<K, V> {
     fn default() -> Self {
-        Self { map: BTreeMap::new() }
+        Self { map: OrdMap::new() }
     }
 }
 
-impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for MultiMap<K, V> {
+impl<K: Ord + Clone + MemoryUsage, V: Clone + MemoryUsage> MemoryUsage for MultiMap<K, V> {
     fn heap_usage(&self) -> usize {
         let Self { map } = self;
-        map.heap_usage()
+        // Nodes shared with clones of the map are counted in full by each of them.
+        map.iter()
+            .map(|(key, vals)| mem::size_of::<(K, SmallVec<[V; 1]>)>() + key.heap_usage() + vals.heap_usage())
+            .sum()
     }
 }
 
-impl<K: Ord, V: Ord> MultiMap<K, V> {
+impl<K: Ord + Clone, V: Ord + Clone> MultiMap<K, V> {
     /// Inserts the relation `key -> val` to this multimap.
     ///
     /// The map does not check whether `key -> val` was already in the map.
     pub fn insert(&mut self, key: K, val: V) {
-        self.map.entry(key).or_default().push(val);
+        match self.map.get_mut(&key) {
+            Some(vset) => vset.push(val),
+            None => {
+                self.map.insert(key, smallvec![val]);
+            }
+        }
     }
 
     /// Deletes `key -> val` from this multimap.
@@ -106,14 +119,14 @@ impl<'a, V> Iterator for MultiMapPointIter<'a, V> {
 }
 
 /// An iterator over values in a [`MultiMap`] where the keys are in a certain range.
-pub struct MultiMapRangeIter<'a, K, V> {
+pub struct MultiMapRangeIter<'a, K: Ord + Clone, V: Clone> {
     /// The outer iterator seeking for matching keys in the range.
-    outer: Range<'a, K, SmallVec<[V; 1]>>,
+    outer: RangedIter<'a, K, SmallVec<[V; 1]>>,
     /// The inner iterator for the value set for a found key.
     inner: Option<slice::Iter<'a, V>>,
 }
 
-impl<'a, K, V> Iterator for MultiMapRangeIter<'a, K, V> {
+impl<'a, K: Ord + Clone, V: Clone> Iterator for MultiMapRangeIter<'a, K, V> {
     type Item = &'a V;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -3,6 +3,7 @@ use core::mem;
 use core::ops::{Bound, RangeBounds};
 use core::option::IntoIter;
 use spacetimedb_sats::memory_usage::MemoryUsage;
+use std::sync::Arc;
 
 /// A direct index for relating unsigned integer keys [`u8`..`u64`] to [`RowPointer`].
 ///
@@ -33,9 +34,12 @@ const KEYS_PER_INNER: usize = PAGE_SIZE / size_of::<RowPointer>();
 type InnerIndexArray = [RowPointer; KEYS_PER_INNER];
 
 /// An inner index. Either it is empty, or it has `KEYS_PER_INNER` elements.
+///
+/// The array is reference counted, so that cloning the index only clones the outer index,
+/// and an inner index shared with a clone is copied the first time it is modified.
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct InnerIndex {
-    inner: Box<InnerIndexArray>,
+    inner: Arc<InnerIndexArray>,
 }
 
 impl MemoryUsage for InnerIndex {
@@ -76,7 +80,7 @@ impl InnerIndex {
         //         which makes each element a valid `RowPointer` (`u64`).
         let inner = unsafe { Box::from_raw(raw) };
 
-        Self { inner }
+        Self { inner: inner.into() }
     }
 
     /// Returns the pointer at `key`.
@@ -85,10 +89,11 @@ impl InnerIndex {
         *unsafe { self.inner.get_unchecked(key.0) }
     }
 
-    /// Returns the mutable slot at `key`.
+    /// Returns the mutable slot at `key`,
+    /// first copying the inner index if it is shared with a clone.
     fn get_mut(&mut self, key: InnerIndexKey) -> &mut RowPointer {
         // SAFETY: `self.inner.len() = KEYS_PER_INNER` and `key.0 < KEYS_PER_INNER`.
-        unsafe { self.inner.get_unchecked_mut(key.0) }
+        unsafe { Arc::make_mut(&mut self.inner).get_unchecked_mut(key.0) }
     }
 }
 
@@ -1,42 +1,46 @@
+use core::mem;
 use core::{ops::RangeBounds, option::IntoIter};
+use imbl::ordmap::{OrdMap, RangedIter};
 use spacetimedb_sats::memory_usage::MemoryUsage;
-use std::collections::btree_map::{BTreeMap, Entry, Range};
 
 /// A "unique map" that relates a `K` to a `V`.
 ///
-/// (This is just a `BTreeMap<K, V>`) with a slightly modified interface.
+/// (This is just an `OrdMap<K, V>`) with a slightly modified interface.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct UniqueMap<K, V> {
-    /// The map is backed by a `BTreeMap` for relating a key to a value.
-    map: BTreeMap<K, V>,
+pub struct UniqueMap<K: Ord + Clone, V: Clone> {
+    /// The map is backed by a persistent `OrdMap` for relating a key to a value,
+    /// so that, as for [`MultiMap`](super::multimap::MultiMap),
+    /// cloning the map is cheap and modifying a clone only copies the modified path.
+    map: OrdMap<K, V>,
 }
 
-impl<K, V> Default for UniqueMap<K, V> {
+impl<K: Ord + Clone, V: Clone> Default for UniqueMap<K, V> {
     fn default() -> Self {
-        Self { map: BTreeMap::new() }
+        Self { map: OrdMap::new() }
     }
 }
 
-impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for UniqueMap<K, V> {
+impl<K: Ord + Clone + MemoryUsage, V: Clone + MemoryUsage> MemoryUsage for UniqueMap<K, V> {
     fn heap_usage(&self) -> usize {
         let Self { map } = self;
-        map.heap_usage()
+        // Nodes shared with clones of the map are counted in full by each of them.
+        map.iter()
+            .map(|(key, val)| mem::size_of::<(K, V)>() + key.heap_usage() + val.heap_usage())
+            .sum()
     }
 }
 
-impl<K: Ord, V: Ord> UniqueMap<K, V> {
+impl<K: Ord + Clone, V: Ord + Clone> UniqueMap<K, V> {
     /// Inserts the relation `key -> val` to this map.
     ///
     /// If `key` was already present in the map, does not add an association with `val`.
     /// Returns the existing associated value instead.
     pub fn insert(&mut self, key: K, val: V) -> Result<(), &V> {
-        match self.map.entry(key) {
-            Entry::Vacant(e) => {
-                e.insert(val);
-                Ok(())
-            }
-            Entry::Occupied(e) => Err(e.into_mut()),
+        if self.map.contains_key(&key) {
+            return Err(&self.map[&key]);
         }
+        self.map.insert(key, val);
+        Ok(())
     }
 
     /// Deletes `key` from this map.
@@ -113,12 +117,12 @@ impl<'a, V> Iterator for UniqueMapPointIter<'a, V> {
 }
 
 /// An iterator over values in a [`UniqueMap`] where the keys are in a certain range.
-pub struct UniqueMapRangeIter<'a, K, V> {
+pub struct UniqueMapRangeIter<'a, K: Ord + Clone, V: Clone> {
     /// The iterator seeking for matching keys in the range.
-    iter: Range<'a, K, V>,
+    iter: RangedIter<'a, K, V>,
 }
 
-impl<'a, K, V> Iterator for UniqueMapRangeIter<'a, K, V> {
+impl<'a, K: Ord + Clone, V: Clone> Iterator for UniqueMapRangeIter<'a, K, V> {
     type Item = &'a V;
 
     fn next(&mut self) -> Option<Self::Item> {
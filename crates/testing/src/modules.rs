@@ -21,8 +21,9 @@ use spacetimedb::database_logger::DatabaseLogger;
 use spacetimedb::db::{Config, Storage};
 use spacetimedb::host::FunctionArgs;
 use spacetimedb::messages::websocket::CallReducerFlags;
+use spacetimedb::testing::ModuleTestHarness;
 use spacetimedb_client_api::{ControlStateReadAccess, ControlStateWriteAccess, DatabaseDef, NodeDelegate};
-use spacetimedb_lib::{bsatn, sats};
+use spacetimedb_lib::{bsatn, sats, Timestamp};
 
 pub use spacetimedb::database_logger::LogLevel;
 
@@ -163,6 +164,14 @@ impl CompiledModule {
         });
     }
 
+    /// Load the module into an in-process, in-memory [`ModuleTestHarness`],
+    /// with its virtual clock starting at `start`.
+    pub async fn load_test_harness(&self, start: Timestamp) -> ModuleTestHarness {
+        ModuleTestHarness::new(self.program_bytes().into(), self.host_type, start)
+            .await
+            .unwrap()
+    }
+
     /// Load a module with the given config.
     /// If "reuse_db_path" is set, the module will be loaded in the given path,
     /// without resetting the database.
@@ -186,6 +195,7 @@ impl CompiledModule {
             spacetimedb_standalone::StandaloneOptions {
                 db_config: config,
                 websocket: WebSocketOptions::default(),
+                rate_limits: <_>::default(),
             },
             &certs,
             paths.data_dir.into(),
@@ -246,6 +256,7 @@ impl CompiledModule {
 pub static DEFAULT_CONFIG: Config = Config {
     storage: Storage::Disk,
     page_pool_max_size: None,
+    table_memory_limit: None,
 };
 
 /// For performance tests, do not persist to disk.
@@ -256,6 +267,7 @@ pub static IN_MEMORY_CONFIG: Config = Config {
     // The threshold for failure on i7-7700K, 64GB RAM seems to be at 1 << 26.
     // TODO(centril): investigate further why this size affects the benchmark.
     page_pool_max_size: Some(1 << 16),
+    table_memory_limit: None,
 };
 
 /// Used to parse output from module logs.
@@ -1,7 +1,9 @@
 use serial_test::serial;
+use spacetimedb::host::FunctionArgs;
 use spacetimedb_lib::sats::{product, AlgebraicValue};
+use spacetimedb_lib::{Identity, Timestamp};
 use spacetimedb_testing::modules::{
-    CompilationMode, CompiledModule, Csharp, LogLevel, LoggerRecord, ModuleHandle, ModuleLanguage, Rust,
+    start_runtime, CompilationMode, CompiledModule, Csharp, LogLevel, LoggerRecord, ModuleHandle, ModuleLanguage, Rust,
     DEFAULT_CONFIG, IN_MEMORY_CONFIG,
 };
 use std::{
@@ -130,6 +132,34 @@ fn test_calling_a_reducer_with_private_table() {
     );
 }
 
+#[test]
+#[serial]
+fn test_module_test_harness() {
+    init();
+
+    let module = CompiledModule::compile("module-test", CompilationMode::Debug);
+    start_runtime().block_on(async {
+        // `init` stamps its repeating schedule with the wall-clock time,
+        // so start the virtual clock from there to keep `repeating_test` happy.
+        let start = Timestamp::now();
+        let mut harness = module.load_test_harness(start).await;
+
+        let result = harness
+            .call_reducer(Identity::ZERO, "add", FunctionArgs::Json(r#"["Tyrion", 24]"#.into()))
+            .await
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(harness.table_rows("person").unwrap().len(), 1);
+
+        // `repeating_test` fires every second, so three runs fall within 3.5s.
+        let results = harness.advance_time(Duration::from_millis(3500)).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(harness.now(), start + Duration::from_millis(3500));
+        assert_eq!(harness.table_rows("repeating_test_arg").unwrap().len(), 1);
+    });
+}
+
 /// Invoke the `module-test` module,
 /// use `caller` to invoke its `test` reducer,
 /// and assert that its logs look right.
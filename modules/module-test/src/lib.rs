@@ -334,6 +334,14 @@ pub fn delete_players_by_name(ctx: &ReducerContext, name: String) -> Result<(),
     }
 }
 
+#[spacetimedb::reducer]
+pub fn count_players_by_name(ctx: &ReducerContext, name: String) -> Result<u64, String> {
+    match ctx.db.test_e().name().filter(&name).count() {
+        0 => Err(format!("No TestE row with name {name:?}")),
+        count => Ok(count as u64),
+    }
+}
+
 #[spacetimedb::reducer(client_connected)]
 fn client_connected(_ctx: &ReducerContext) {}
 
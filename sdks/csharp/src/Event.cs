@@ -110,8 +110,46 @@ namespace SpacetimeDB
         Identity CallerIdentity,
         ConnectionId? CallerConnectionId,
         U128? EnergyConsumed,
-        R Reducer
-    );
+        R Reducer,
+        byte[]? ReturnValue = null
+    )
+    {
+        /// <summary>
+        /// Decode the value returned by the reducer, if it committed, or the error value it failed with, if it failed,
+        /// as a tagged enum <c>T</c> whose first variant is the value type and whose second variant is the error type.
+        ///
+        /// Returns <c>null</c> if the reducer does not declare return types, if it did not run to completion,
+        /// or if it was called by another client, as return values are only sent to the caller.
+        /// </summary>
+        public T? DecodeReturnValue<T, TRW>()
+            where T : class
+            where TRW : struct, BSATN.IReadWrite<T>
+        {
+            if (ReturnValue == null)
+            {
+                return null;
+            }
+            byte tag;
+            switch (Status)
+            {
+                case Status.Committed:
+                    tag = 0;
+                    break;
+                case Status.Failed:
+                    tag = 1;
+                    break;
+                default:
+                    return null;
+            }
+            // The return value is sent without a tag, as its type is determined by the status.
+            using var stream = new System.IO.MemoryStream(ReturnValue.Length + 1);
+            stream.WriteByte(tag);
+            stream.Write(ReturnValue, 0, ReturnValue.Length);
+            stream.Position = 0;
+            using var reader = new System.IO.BinaryReader(stream);
+            return new TRW().Read(reader);
+        }
+    }
 
     public record Event<R>
     {
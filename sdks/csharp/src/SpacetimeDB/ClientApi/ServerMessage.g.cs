@@ -18,6 +18,9 @@ namespace SpacetimeDB.ClientApi
         UnsubscribeApplied UnsubscribeApplied,
         SubscriptionError SubscriptionError,
         SubscribeMultiApplied SubscribeMultiApplied,
-        UnsubscribeMultiApplied UnsubscribeMultiApplied
+        UnsubscribeMultiApplied UnsubscribeMultiApplied,
+        ReducerResult ReducerResult,
+        SubscriptionsResumed SubscriptionsResumed,
+        CaughtUp CaughtUp
     )>;
 }
@@ -364,6 +364,16 @@ namespace SpacetimeDB
             {
                 var dbOps = ParsedDatabaseUpdate.New();
                 var message = CompressionHelpers.DecompressDecodeMessage(unparsed.bytes);
+
+                // A ReducerResult is the update for one of our own reducer calls,
+                // along with the value the reducer returned.
+                byte[]? returnValue = null;
+                if (message is ServerMessage.ReducerResult(var reducerResult))
+                {
+                    message = new ServerMessage.TransactionUpdate(reducerResult.Update);
+                    returnValue = reducerResult.ReturnValue.ToArray();
+                }
+
                 var trackerMetadata = TrackerMetadataForMessage(message);
 
                 stats.ParseMessageQueueTracker.FinishTrackingRequest(unparsed.parseQueueTrackerId, trackerMetadata);
@@ -416,7 +426,8 @@ namespace SpacetimeDB
                                 transactionUpdate.CallerIdentity,
                                 transactionUpdate.CallerConnectionId,
                                 transactionUpdate.EnergyQuantaUsed.Quanta,
-                                ToReducer(transactionUpdate));
+                                ToReducer(transactionUpdate),
+                                returnValue);
                         }
                         catch (Exception)
                         {
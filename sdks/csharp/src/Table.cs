@@ -122,8 +122,9 @@ namespace SpacetimeDB
         public abstract class BTreeIndexBase<Column> : IndexBase<Column>
             where Column : IEquatable<Column>, IComparable<Column>
         {
-            // TODO: change to SortedDictionary when adding support for range queries.
             private readonly Dictionary<Column, HashSet<Row>> cache = new();
+            // The keys of `cache`, in order, for range queries.
+            private readonly SortedSet<Column> keys = new();
 
             public BTreeIndexBase(RemoteTableHandle<EventContext, Row> table)
             {
@@ -134,6 +135,7 @@ namespace SpacetimeDB
                     {
                         rows = new();
                         cache.Add(key, rows);
+                        keys.Add(key);
                     }
                     rows.Add(row);
                 };
@@ -146,12 +148,22 @@ namespace SpacetimeDB
                     if (keyCache.Count == 0)
                     {
                         cache.Remove(key);
+                        keys.Remove(key);
                     }
                 };
             }
 
             public IEnumerable<Row> Filter(Column value) =>
                 cache.TryGetValue(value, out var rows) ? rows : Enumerable.Empty<Row>();
+
+            /// <summary>
+            /// Get the rows whose key lies between <paramref name="min"/> and <paramref name="max"/>, inclusive,
+            /// in ascending order of key.
+            /// </summary>
+            public IEnumerable<Row> Range(Column min, Column max) =>
+                min.CompareTo(max) > 0
+                    ? Enumerable.Empty<Row>()
+                    : keys.GetViewBetween(min, max).SelectMany(key => cache[key]);
         }
 
         /// <summary>
@@ -149,7 +149,7 @@ namespace SpacetimeDB
             _isConnecting = true;
             try
             {
-                var uri = $"{host}/v1/database/{nameOrAddress}/subscribe?connection_id={connectionId}&compression={compression}";
+                var uri = $"{host}/v1/database/{nameOrAddress}/subscribe?connection_id={connectionId}&compression={compression}&reducer_results=true";
                 if (light) uri += "&light=true";
         
                 _socketId = new TaskCompletionSource<int>();
@@ -172,7 +172,7 @@ namespace SpacetimeDB
             }
         // Events will be handled via UnitySendMessage callbacks
 #else
-            var uri = $"{host}/v1/database/{nameOrAddress}/subscribe?connection_id={connectionId}&compression={compression}";
+            var uri = $"{host}/v1/database/{nameOrAddress}/subscribe?connection_id={connectionId}&compression={compression}&reducer_results=true";
             if (light)
             {
                 uri += "&light=true";
@@ -0,0 +1,308 @@
+package spacetimedb
+
+import (
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"math"
+	"unicode/utf8"
+)
+
+// ErrUnexpectedEOF is reported by a Reader which runs out of input mid-value.
+var ErrUnexpectedEOF = errors.New("bsatn: unexpected end of input")
+
+// Encoder is implemented by types which can be encoded as BSATN,
+// including all the types in generated module bindings.
+type Encoder interface {
+	EncodeBSATN(w *Writer)
+}
+
+// Decoder is implemented by pointers to types which can be decoded from BSATN.
+type Decoder interface {
+	DecodeBSATN(r *Reader)
+}
+
+// Reader decodes BSATN values from a byte slice, front to back.
+//
+// Rather than returning an error from every method,
+// a Reader remembers the first error it encounters and returns zero values after it;
+// check Err once decoding is done.
+type Reader struct {
+	data []byte
+	err  error
+}
+
+// NewReader returns a Reader over data.
+func NewReader(data []byte) *Reader {
+	return &Reader{data: data}
+}
+
+// Err returns the first error encountered while decoding, if any.
+func (r *Reader) Err() error {
+	return r.err
+}
+
+// Fail records err as the reader's error, unless it has already failed.
+func (r *Reader) Fail(err error) {
+	if r.err == nil {
+		r.err = err
+	}
+}
+
+// Remaining returns the number of bytes which have not yet been read.
+func (r *Reader) Remaining() int {
+	return len(r.data)
+}
+
+// ReadRaw reads the next n bytes verbatim.
+// The returned slice aliases the reader's input.
+func (r *Reader) ReadRaw(n int) []byte {
+	if r.err != nil {
+		return nil
+	}
+	if n < 0 || n > len(r.data) {
+		r.Fail(ErrUnexpectedEOF)
+		r.data = nil
+		return nil
+	}
+	out := r.data[:n:n]
+	r.data = r.data[n:]
+	return out
+}
+
+func (r *Reader) fixed(n int) []byte {
+	if b := r.ReadRaw(n); b != nil {
+		return b
+	}
+	return make([]byte, n)
+}
+
+func (r *Reader) ReadBool() bool {
+	switch b := r.ReadU8(); b {
+	case 0:
+		return false
+	case 1:
+		return true
+	default:
+		r.Fail(fmt.Errorf("bsatn: invalid bool %d", b))
+		return false
+	}
+}
+
+func (r *Reader) ReadU8() uint8   { return r.fixed(1)[0] }
+func (r *Reader) ReadI8() int8    { return int8(r.ReadU8()) }
+func (r *Reader) ReadU16() uint16 { return binary.LittleEndian.Uint16(r.fixed(2)) }
+func (r *Reader) ReadI16() int16  { return int16(r.ReadU16()) }
+func (r *Reader) ReadU32() uint32 { return binary.LittleEndian.Uint32(r.fixed(4)) }
+func (r *Reader) ReadI32() int32  { return int32(r.ReadU32()) }
+func (r *Reader) ReadU64() uint64 { return binary.LittleEndian.Uint64(r.fixed(8)) }
+func (r *Reader) ReadI64() int64  { return int64(r.ReadU64()) }
+
+func (r *Reader) ReadU128() (v U128) { copy(v[:], r.fixed(16)); return }
+func (r *Reader) ReadI128() (v I128) { copy(v[:], r.fixed(16)); return }
+func (r *Reader) ReadU256() (v U256) { copy(v[:], r.fixed(32)); return }
+func (r *Reader) ReadI256() (v I256) { copy(v[:], r.fixed(32)); return }
+
+func (r *Reader) ReadF32() float32 { return math.Float32frombits(r.ReadU32()) }
+func (r *Reader) ReadF64() float64 { return math.Float64frombits(r.ReadU64()) }
+
+// ReadBytes reads a length-prefixed byte array, copying it out of the reader's input.
+func (r *Reader) ReadBytes() []byte {
+	n := r.ReadU32()
+	b := r.ReadRaw(int(n))
+	if b == nil {
+		return nil
+	}
+	return append([]byte{}, b...)
+}
+
+func (r *Reader) ReadString() string {
+	n := r.ReadU32()
+	b := r.ReadRaw(int(n))
+	if !utf8.Valid(b) {
+		r.Fail(errors.New("bsatn: invalid UTF-8 in string"))
+		return ""
+	}
+	return string(b)
+}
+
+func (r *Reader) ReadUnit() Unit { return Unit{} }
+
+func (r *Reader) ReadNever() Never {
+	r.Fail(errors.New("bsatn: cannot decode a value of an uninhabited type"))
+	return Never{}
+}
+
+// ReadTag reads the tag which precedes the payload of a sum type.
+func (r *Reader) ReadTag() uint8 { return r.ReadU8() }
+
+func (r *Reader) ReadIdentity() Identity         { return Identity(r.ReadU256()) }
+func (r *Reader) ReadConnectionId() ConnectionId { return ConnectionId(r.ReadU128()) }
+func (r *Reader) ReadTimestamp() Timestamp       { return Timestamp{MicrosSinceUnixEpoch: r.ReadI64()} }
+func (r *Reader) ReadTimeDuration() TimeDuration { return TimeDuration{Micros: r.ReadI64()} }
+
+func (r *Reader) ReadScheduleAt() ScheduleAt {
+	switch tag := r.ReadTag(); tag {
+	case 0:
+		return ScheduleAtInterval{Value: r.ReadTimeDuration()}
+	case 1:
+		return ScheduleAtTime{Value: r.ReadTimestamp()}
+	default:
+		r.Fail(fmt.Errorf("bsatn: invalid ScheduleAt tag %d", tag))
+		return nil
+	}
+}
+
+// ReadArray reads a length-prefixed array whose elements are read by readElem.
+func ReadArray[T any](r *Reader, readElem func(r *Reader) T) []T {
+	n := r.ReadU32()
+	// Don't trust the length prefix to size the allocation up front:
+	// every element takes at least one byte, except for zero-sized ones.
+	out := make([]T, 0, min(int(n), r.Remaining()))
+	for i := uint32(0); i < n && r.err == nil; i++ {
+		out = append(out, readElem(r))
+	}
+	return out
+}
+
+// ReadOption reads an optional value, returning nil for `none`.
+func ReadOption[T any](r *Reader, readSome func(r *Reader) T) *T {
+	switch tag := r.ReadTag(); tag {
+	case 0:
+		v := readSome(r)
+		return &v
+	case 1:
+		return nil
+	default:
+		r.Fail(fmt.Errorf("bsatn: invalid option tag %d", tag))
+		return nil
+	}
+}
+
+// ReadValue reads a T using its DecodeBSATN method.
+func ReadValue[T any, PT interface {
+	*T
+	Decoder
+}](r *Reader) T {
+	var v T
+	PT(&v).DecodeBSATN(r)
+	return v
+}
+
+// Writer accumulates BSATN-encoded values.
+type Writer struct {
+	buf []byte
+}
+
+// NewWriter returns an empty Writer.
+func NewWriter() *Writer {
+	return &Writer{}
+}
+
+// Bytes returns the values written so far.
+func (w *Writer) Bytes() []byte {
+	return w.buf
+}
+
+// WriteRaw appends b verbatim.
+func (w *Writer) WriteRaw(b []byte) { w.buf = append(w.buf, b...) }
+
+func (w *Writer) WriteBool(v bool) {
+	if v {
+		w.WriteU8(1)
+	} else {
+		w.WriteU8(0)
+	}
+}
+
+func (w *Writer) WriteU8(v uint8)   { w.buf = append(w.buf, v) }
+func (w *Writer) WriteI8(v int8)    { w.WriteU8(uint8(v)) }
+func (w *Writer) WriteU16(v uint16) { w.buf = binary.LittleEndian.AppendUint16(w.buf, v) }
+func (w *Writer) WriteI16(v int16)  { w.WriteU16(uint16(v)) }
+func (w *Writer) WriteU32(v uint32) { w.buf = binary.LittleEndian.AppendUint32(w.buf, v) }
+func (w *Writer) WriteI32(v int32)  { w.WriteU32(uint32(v)) }
+func (w *Writer) WriteU64(v uint64) { w.buf = binary.LittleEndian.AppendUint64(w.buf, v) }
+func (w *Writer) WriteI64(v int64)  { w.WriteU64(uint64(v)) }
+
+func (w *Writer) WriteU128(v U128) { w.WriteRaw(v[:]) }
+func (w *Writer) WriteI128(v I128) { w.WriteRaw(v[:]) }
+func (w *Writer) WriteU256(v U256) { w.WriteRaw(v[:]) }
+func (w *Writer) WriteI256(v I256) { w.WriteRaw(v[:]) }
+
+func (w *Writer) WriteF32(v float32) { w.WriteU32(math.Float32bits(v)) }
+func (w *Writer) WriteF64(v float64) { w.WriteU64(math.Float64bits(v)) }
+
+// WriteBytes writes a length-prefixed byte array.
+func (w *Writer) WriteBytes(v []byte) {
+	w.WriteU32(uint32(len(v)))
+	w.WriteRaw(v)
+}
+
+func (w *Writer) WriteString(v string) {
+	w.WriteU32(uint32(len(v)))
+	w.buf = append(w.buf, v...)
+}
+
+func (w *Writer) WriteUnit(Unit) {}
+
+func (w *Writer) WriteNever(Never) {
+	panic("bsatn: cannot encode a value of an uninhabited type")
+}
+
+// WriteTag writes the tag which precedes the payload of a sum type.
+func (w *Writer) WriteTag(tag uint8) { w.WriteU8(tag) }
+
+func (w *Writer) WriteIdentity(v Identity)         { w.WriteU256(U256(v)) }
+func (w *Writer) WriteConnectionId(v ConnectionId) { w.WriteU128(U128(v)) }
+func (w *Writer) WriteTimestamp(v Timestamp)       { w.WriteI64(v.MicrosSinceUnixEpoch) }
+func (w *Writer) WriteTimeDuration(v TimeDuration) { w.WriteI64(v.Micros) }
+func (w *Writer) WriteScheduleAt(v ScheduleAt)     { v.EncodeBSATN(w) }
+
+// WriteArray writes a length-prefixed array whose elements are written by writeElem.
+func WriteArray[T any](w *Writer, vs []T, writeElem func(w *Writer, v T)) {
+	w.WriteU32(uint32(len(vs)))
+	for _, v := range vs {
+		writeElem(w, v)
+	}
+}
+
+// WriteOption writes an optional value, with nil as `none`.
+func WriteOption[T any](w *Writer, v *T, writeSome func(w *Writer, v T)) {
+	if v == nil {
+		w.WriteTag(1)
+		return
+	}
+	w.WriteTag(0)
+	writeSome(w, *v)
+}
+
+// Encode returns the BSATN encoding of v.
+func Encode(v Encoder) []byte {
+	w := NewWriter()
+	v.EncodeBSATN(w)
+	return w.Bytes()
+}
+
+// Decode decodes data into v, which must consume all of data.
+func Decode(data []byte, v Decoder) error {
+	r := NewReader(data)
+	v.DecodeBSATN(r)
+	return r.finish()
+}
+
+// DecodeWith decodes data using read, which must consume all of data.
+func DecodeWith[T any](data []byte, read func(r *Reader) T) (T, error) {
+	r := NewReader(data)
+	v := read(r)
+	return v, r.finish()
+}
+
+func (r *Reader) finish() error {
+	if r.err != nil {
+		return r.err
+	}
+	if len(r.data) != 0 {
+		return fmt.Errorf("bsatn: %d trailing bytes after decoded value", len(r.data))
+	}
+	return nil
+}
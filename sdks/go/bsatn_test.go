@@ -0,0 +1,169 @@
+package spacetimedb
+
+import (
+	"bytes"
+	"math/big"
+	"testing"
+)
+
+func TestRoundTripPrimitives(t *testing.T) {
+	w := NewWriter()
+	w.WriteBool(true)
+	w.WriteI8(-3)
+	w.WriteU16(0xBEEF)
+	w.WriteI64(-1 << 40)
+	w.WriteF64(2.5)
+	w.WriteString("héllo")
+	WriteOption(w, nil, (*Writer).WriteU32)
+	seven := uint32(7)
+	WriteOption(w, &seven, (*Writer).WriteU32)
+	WriteArray(w, []string{"a", "bc"}, (*Writer).WriteString)
+
+	r := NewReader(w.Bytes())
+	if got := r.ReadBool(); !got {
+		t.Errorf("ReadBool() = %v", got)
+	}
+	if got := r.ReadI8(); got != -3 {
+		t.Errorf("ReadI8() = %v", got)
+	}
+	if got := r.ReadU16(); got != 0xBEEF {
+		t.Errorf("ReadU16() = %v", got)
+	}
+	if got := r.ReadI64(); got != -1<<40 {
+		t.Errorf("ReadI64() = %v", got)
+	}
+	if got := r.ReadF64(); got != 2.5 {
+		t.Errorf("ReadF64() = %v", got)
+	}
+	if got := r.ReadString(); got != "héllo" {
+		t.Errorf("ReadString() = %q", got)
+	}
+	if got := ReadOption(r, (*Reader).ReadU32); got != nil {
+		t.Errorf("ReadOption() = %v, want nil", *got)
+	}
+	if got := ReadOption(r, (*Reader).ReadU32); got == nil || *got != 7 {
+		t.Errorf("ReadOption() = %v, want 7", got)
+	}
+	if got := ReadArray(r, (*Reader).ReadString); len(got) != 2 || got[0] != "a" || got[1] != "bc" {
+		t.Errorf("ReadArray() = %q", got)
+	}
+	if err := r.finish(); err != nil {
+		t.Fatal(err)
+	}
+}
+
+func TestReaderReportsTruncatedInput(t *testing.T) {
+	r := NewReader([]byte{1, 2})
+	r.ReadU32()
+	if r.Err() != ErrUnexpectedEOF {
+		t.Fatalf("Err() = %v, want ErrUnexpectedEOF", r.Err())
+	}
+	if _, err := DecodeWith([]byte{1, 2, 3}, (*Reader).ReadU16); err == nil {
+		t.Fatal("expected an error for trailing bytes")
+	}
+}
+
+func TestWideIntegers(t *testing.T) {
+	minusTwo, err := I128FromBig(big.NewInt(-2))
+	if err != nil {
+		t.Fatal(err)
+	}
+	if minusTwo[0] != 0xFE || minusTwo[15] != 0xFF {
+		t.Errorf("I128FromBig(-2) = %x", minusTwo)
+	}
+	if got := minusTwo.Big(); got.Cmp(big.NewInt(-2)) != 0 {
+		t.Errorf("Big() = %v, want -2", got)
+	}
+	if _, err := U128FromBig(big.NewInt(-1)); err == nil {
+		t.Error("expected U128FromBig(-1) to fail")
+	}
+	if _, err := U256FromBig(new(big.Int).Lsh(big.NewInt(1), 256)); err == nil {
+		t.Error("expected U256FromBig(2^256) to fail")
+	}
+}
+
+func TestIdentityHexIsBigEndian(t *testing.T) {
+	id, err := IdentityFromHex("00000000000000000000000000000000000000000000000000000000000001ff")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if id[0] != 0xFF || id[1] != 0x01 {
+		t.Errorf("IdentityFromHex stored %x, want little-endian bytes", id[:])
+	}
+	if got := id.String(); got != "00000000000000000000000000000000000000000000000000000000000001ff" {
+		t.Errorf("String() = %s", got)
+	}
+}
+
+func TestReadRowList(t *testing.T) {
+	w := NewWriter()
+	// RowSizeHint::FixedSize(2)
+	w.WriteTag(0)
+	w.WriteU16(2)
+	w.WriteBytes([]byte{1, 2, 3, 4})
+	// RowSizeHint::RowOffsets([0, 1])
+	w.WriteTag(1)
+	WriteArray(w, []uint64{0, 1}, (*Writer).WriteU64)
+	w.WriteBytes([]byte{5, 6, 7})
+
+	r := NewReader(w.Bytes())
+	fixed := readRowList(r)
+	offsets := readRowList(r)
+	if err := r.finish(); err != nil {
+		t.Fatal(err)
+	}
+	if len(fixed) != 2 || !bytes.Equal(fixed[0], []byte{1, 2}) || !bytes.Equal(fixed[1], []byte{3, 4}) {
+		t.Errorf("fixed-size rows = %v", fixed)
+	}
+	if len(offsets) != 2 || !bytes.Equal(offsets[0], []byte{5}) || !bytes.Equal(offsets[1], []byte{6, 7}) {
+		t.Errorf("offset rows = %v", offsets)
+	}
+}
+
+func TestEncodeCallReducer(t *testing.T) {
+	got := encodeCallReducer("add", []byte{0x2A}, 7, NoSuccessNotify)
+	want := []byte{
+		0,          // ClientMessage::CallReducer
+		3, 0, 0, 0, // reducer name length
+		'a', 'd', 'd',
+		1, 0, 0, 0, // args length
+		0x2A,
+		7, 0, 0, 0, // request_id
+		1, // flags
+	}
+	if !bytes.Equal(got, want) {
+		t.Errorf("encodeCallReducer() = %v, want %v", got, want)
+	}
+}
+
+func TestTableCacheReportsUpdatesByPrimaryKey(t *testing.T) {
+	type row struct{ id, value uint8 }
+	encode := func(r row) []byte { return []byte{r.id, r.value} }
+	cache := NewTableCache("t", func(r *Reader) row {
+		return row{id: r.ReadU8(), value: r.ReadU8()}
+	}, func(r row) any { return r.id })
+
+	var inserts, deletes int
+	var updates []row
+	cache.OnInsert(func(*Event, row) { inserts++ })
+	cache.OnDelete(func(*Event, row) { deletes++ })
+	cache.OnUpdate(func(_ *Event, _, newRow row) { updates = append(updates, newRow) })
+
+	run, err := cache.applyUpdate(nil, [][]byte{encode(row{1, 10}), encode(row{2, 20})})
+	if err != nil {
+		t.Fatal(err)
+	}
+	run(&Event{})
+	run, err = cache.applyUpdate([][]byte{encode(row{1, 10}), encode(row{2, 20})}, [][]byte{encode(row{1, 11})})
+	if err != nil {
+		t.Fatal(err)
+	}
+	run(&Event{})
+
+	if inserts != 2 || deletes != 1 || len(updates) != 1 || updates[0] != (row{1, 11}) {
+		t.Errorf("inserts = %d, deletes = %d, updates = %v", inserts, deletes, updates)
+	}
+	if cache.Count() != 1 {
+		t.Errorf("Count() = %d, want 1", cache.Count())
+	}
+}
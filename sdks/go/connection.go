@@ -0,0 +1,516 @@
+package spacetimedb
+
+import (
+	"context"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"net/http"
+	"net/url"
+	"strings"
+	"sync"
+)
+
+// ErrClosed is returned by methods of a Conn which has disconnected.
+var ErrClosed = errors.New("spacetimedb: connection closed")
+
+// Compression is the compression the server should apply to the messages it sends.
+type Compression int
+
+const (
+	// CompressionNone asks the server not to compress messages. This is the default.
+	CompressionNone Compression = iota
+	// CompressionGzip asks the server to gzip large messages.
+	CompressionGzip
+)
+
+// ConnectOptions configure a connection to a database.
+type ConnectOptions struct {
+	// Host is the URI of the SpacetimeDB host, like "http://localhost:3000".
+	Host string
+	// DatabaseName is the name or identity of the database to connect to.
+	DatabaseName string
+	// Token authenticates the connection, if set.
+	// Otherwise, the host issues a new identity and token, available from Conn.Token once connected.
+	Token string
+	// Compression is the compression the server should apply to its messages.
+	Compression Compression
+	// Light asks the server to describe other clients' transactions without their reducer calls,
+	// saving bandwidth for clients which don't need them.
+	Light bool
+	// OnDisconnect, if set, runs once the connection has closed,
+	// with the error which closed it, or nil if it was closed by Conn.Close.
+	OnDisconnect func(conn *Conn, err error)
+}
+
+// ReducerStatus is the outcome of a reducer call.
+type ReducerStatus int
+
+const (
+	// Committed means the reducer ran to completion and its transaction was committed.
+	Committed ReducerStatus = iota
+	// Failed means the reducer returned an error or panicked, and its transaction was rolled back.
+	Failed
+	// OutOfEnergy means the reducer was aborted because the database's owner ran out of energy.
+	OutOfEnergy
+)
+
+// ReducerEvent describes a reducer call whose transaction the server reported to this connection.
+type ReducerEvent struct {
+	ReducerName string
+	// Args are the BSATN-encoded arguments to the reducer.
+	Args []byte
+	// RequestID is the ID returned by Conn.CallReducer, if this connection made the call.
+	RequestID          uint32
+	CallerIdentity     Identity
+	CallerConnectionId ConnectionId
+	Timestamp          Timestamp
+	Status             ReducerStatus
+	// Error is the error message the reducer failed with, if its Status is Failed.
+	Error string
+	// ReturnValue is the BSATN-encoded value the reducer returned, for reducers with typed returns.
+	ReturnValue                *[]byte
+	EnergyQuantaUsed           U128
+	TotalHostExecutionDuration TimeDuration
+}
+
+// OneOffTable is one table's rows in the result of Conn.OneOffQuery.
+type OneOffTable struct {
+	TableName string
+	// Rows are the BSATN of each row.
+	Rows [][]byte
+}
+
+// SubscriptionCallbacks are notified about the progress of a subscription.
+// Any of them may be nil.
+type SubscriptionCallbacks struct {
+	// OnApplied runs once the subscription's initial rows are in the client cache.
+	OnApplied func()
+	// OnError runs if the server rejects the subscription, or fails to keep it up to date.
+	OnError func(err error)
+	// OnEnded runs once the subscription's rows have been removed after unsubscribing.
+	OnEnded func()
+}
+
+// SubscriptionHandle refers to a set of queries subscribed to with Conn.Subscribe.
+type SubscriptionHandle struct {
+	conn      *Conn
+	queryID   uint32
+	callbacks SubscriptionCallbacks
+	// active and ended are guarded by conn.mu.
+	active, ended bool
+}
+
+// IsActive reports whether the subscription has been applied, and has not since ended.
+func (h *SubscriptionHandle) IsActive() bool {
+	h.conn.mu.Lock()
+	defer h.conn.mu.Unlock()
+	return h.active && !h.ended
+}
+
+// Unsubscribe ends the subscription, removing any rows it alone was keeping in the client cache.
+func (h *SubscriptionHandle) Unsubscribe() error {
+	c := h.conn
+	c.mu.Lock()
+	if h.ended {
+		c.mu.Unlock()
+		return errors.New("spacetimedb: subscription has already ended")
+	}
+	c.nextRequestID++
+	requestID := c.nextRequestID
+	c.mu.Unlock()
+	return c.send(encodeUnsubscribeMulti(requestID, h.queryID))
+}
+
+type oneOffResult struct {
+	tables []OneOffTable
+	err    error
+}
+
+// Conn is a connection to a database.
+//
+// Messages from the server are processed in order on a single goroutine,
+// which also runs all callbacks;
+// callbacks may call methods of the Conn, but should not block for long.
+type Conn struct {
+	ws     *wsConn
+	opts   ConnectOptions
+	tables map[string]TableHandle
+
+	// identified is closed once the server has sent the connection's identity.
+	identified chan struct{}
+	// done is closed once the connection has closed.
+	done      chan struct{}
+	closeOnce sync.Once
+	closeErr  error
+
+	mu               sync.Mutex
+	identity         Identity
+	connectionID     ConnectionId
+	token            string
+	nextRequestID    uint32
+	nextQueryID      uint32
+	nextCallbackID   CallbackID
+	subscriptions    map[uint32]*SubscriptionHandle
+	oneOffQueries    map[string]chan oneOffResult
+	reducerCallbacks map[string]map[CallbackID]func(ev *ReducerEvent)
+}
+
+// Connect opens a connection to a database, keeping the given tables' caches up to date,
+// and waits until the server has identified the connection.
+//
+// Generated module bindings provide a Connect function which passes all of the module's tables.
+func Connect(ctx context.Context, opts ConnectOptions, tables ...TableHandle) (*Conn, error) {
+	u, err := subscribeURL(opts)
+	if err != nil {
+		return nil, err
+	}
+	header := http.Header{}
+	header.Set("Sec-WebSocket-Protocol", BinaryProtocol)
+	if opts.Token != "" {
+		header.Set("Authorization", "Bearer "+opts.Token)
+	}
+	ws, err := dialWebSocket(ctx, u, header)
+	if err != nil {
+		return nil, fmt.Errorf("spacetimedb: connecting to %s: %w", u, err)
+	}
+
+	c := &Conn{
+		ws:               ws,
+		opts:             opts,
+		tables:           make(map[string]TableHandle, len(tables)),
+		identified:       make(chan struct{}),
+		done:             make(chan struct{}),
+		subscriptions:    make(map[uint32]*SubscriptionHandle),
+		oneOffQueries:    make(map[string]chan oneOffResult),
+		reducerCallbacks: make(map[string]map[CallbackID]func(ev *ReducerEvent)),
+	}
+	for _, table := range tables {
+		c.tables[table.TableName()] = table
+	}
+	go c.run()
+
+	select {
+	case <-c.identified:
+		return c, nil
+	case <-c.done:
+		return nil, c.closeErr
+	case <-ctx.Done():
+		c.Close()
+		return nil, ctx.Err()
+	}
+}
+
+func subscribeURL(opts ConnectOptions) (*url.URL, error) {
+	u, err := url.Parse(opts.Host)
+	if err != nil {
+		return nil, fmt.Errorf("spacetimedb: invalid host URI: %w", err)
+	}
+	if u.RawQuery != "" {
+		return nil, fmt.Errorf("spacetimedb: host URI %q must not have a query", opts.Host)
+	}
+	u.Path = strings.TrimSuffix(u.Path, "/") + "/v1/database/" + url.PathEscape(opts.DatabaseName) + "/subscribe"
+	query := url.Values{}
+	switch opts.Compression {
+	case CompressionNone:
+		query.Set("compression", "None")
+	case CompressionGzip:
+		query.Set("compression", "Gzip")
+	}
+	if opts.Light {
+		query.Set("light", "true")
+	}
+	u.RawQuery = query.Encode()
+	return u, nil
+}
+
+// Identity returns the identity the server assigned to this connection.
+func (c *Conn) Identity() Identity {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	return c.identity
+}
+
+// ConnectionId returns the ID the server assigned to this connection.
+func (c *Conn) ConnectionId() ConnectionId {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	return c.connectionID
+}
+
+// Token returns the token which authenticates this connection's identity,
+// which can be passed as ConnectOptions.Token to reconnect with the same identity.
+func (c *Conn) Token() string {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	return c.token
+}
+
+// Done returns a channel which is closed once the connection has closed.
+func (c *Conn) Done() <-chan struct{} {
+	return c.done
+}
+
+// Err returns the error which closed the connection,
+// or nil if it is still open or was closed by Close.
+func (c *Conn) Err() error {
+	select {
+	case <-c.done:
+		return c.closeErr
+	default:
+		return nil
+	}
+}
+
+// Close closes the connection.
+func (c *Conn) Close() error {
+	c.disconnect(nil)
+	return nil
+}
+
+// CallReducer requests that the database run the reducer called name with the BSATN-encoded args,
+// returning the request ID which will be reported in the ReducerEvent for the call.
+//
+// Generated module bindings provide a typed method for each reducer.
+func (c *Conn) CallReducer(name string, args []byte, flags CallReducerFlags) (uint32, error) {
+	c.mu.Lock()
+	c.nextRequestID++
+	requestID := c.nextRequestID
+	c.mu.Unlock()
+	return requestID, c.send(encodeCallReducer(name, args, requestID, flags))
+}
+
+// OnReducer registers cb to run whenever the server reports a call to the reducer called name,
+// whether or not it succeeded.
+//
+// The server reports calls made by this connection,
+// and calls by other clients whose transactions touched rows this connection subscribes to.
+func (c *Conn) OnReducer(name string, cb func(ev *ReducerEvent)) CallbackID {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	c.nextCallbackID++
+	callbacks := c.reducerCallbacks[name]
+	if callbacks == nil {
+		callbacks = make(map[CallbackID]func(ev *ReducerEvent))
+		c.reducerCallbacks[name] = callbacks
+	}
+	callbacks[c.nextCallbackID] = cb
+	return c.nextCallbackID
+}
+
+// RemoveOnReducer removes a callback registered with OnReducer.
+func (c *Conn) RemoveOnReducer(name string, id CallbackID) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	delete(c.reducerCallbacks[name], id)
+}
+
+// Subscribe subscribes to the rows matching the SQL queries,
+// which are added to the client cache once the server applies the subscription.
+func (c *Conn) Subscribe(queries []string, callbacks SubscriptionCallbacks) (*SubscriptionHandle, error) {
+	c.mu.Lock()
+	c.nextRequestID++
+	c.nextQueryID++
+	requestID, queryID := c.nextRequestID, c.nextQueryID
+	handle := &SubscriptionHandle{conn: c, queryID: queryID, callbacks: callbacks}
+	c.subscriptions[queryID] = handle
+	c.mu.Unlock()
+
+	if err := c.send(encodeSubscribeMulti(queries, requestID, queryID)); err != nil {
+		c.mu.Lock()
+		delete(c.subscriptions, queryID)
+		c.mu.Unlock()
+		return nil, err
+	}
+	return handle, nil
+}
+
+// OneOffQuery runs the SQL query against the database once, without subscribing to it,
+// and returns the BSATN of the matching rows.
+func (c *Conn) OneOffQuery(ctx context.Context, query string) ([]OneOffTable, error) {
+	c.mu.Lock()
+	c.nextRequestID++
+	messageID := binary.LittleEndian.AppendUint32(nil, c.nextRequestID)
+	result := make(chan oneOffResult, 1)
+	c.oneOffQueries[string(messageID)] = result
+	c.mu.Unlock()
+
+	defer func() {
+		c.mu.Lock()
+		delete(c.oneOffQueries, string(messageID))
+		c.mu.Unlock()
+	}()
+	if err := c.send(encodeOneOffQuery(messageID, query)); err != nil {
+		return nil, err
+	}
+	select {
+	case res := <-result:
+		return res.tables, res.err
+	case <-c.done:
+		return nil, ErrClosed
+	case <-ctx.Done():
+		return nil, ctx.Err()
+	}
+}
+
+func (c *Conn) send(message []byte) error {
+	select {
+	case <-c.done:
+		return ErrClosed
+	default:
+	}
+	if err := c.ws.writeMessage(message); err != nil {
+		c.disconnect(err)
+		return err
+	}
+	return nil
+}
+
+func (c *Conn) disconnect(err error) {
+	c.closeOnce.Do(func() {
+		c.closeErr = err
+		c.ws.close()
+		close(c.done)
+	})
+}
+
+// run processes messages from the server until the connection closes.
+func (c *Conn) run() {
+	var err error
+	for err == nil {
+		var raw []byte
+		raw, err = c.ws.readMessage()
+		if err != nil {
+			break
+		}
+		var msg *serverMessage
+		msg, err = parseServerMessage(raw)
+		if err != nil {
+			break
+		}
+		err = c.handle(msg)
+	}
+
+	select {
+	case <-c.done:
+		// Closed by Close, so the read error is expected.
+	default:
+		c.disconnect(err)
+	}
+	if c.opts.OnDisconnect != nil {
+		c.opts.OnDisconnect(c, c.closeErr)
+	}
+}
+
+func (c *Conn) handle(msg *serverMessage) error {
+	switch {
+	case msg.identityToken != nil:
+		c.mu.Lock()
+		c.identity = msg.identityToken.identity
+		c.connectionID = msg.identityToken.connectionID
+		c.token = msg.identityToken.token
+		c.mu.Unlock()
+		close(c.identified)
+
+	case msg.transactionUpdate != nil:
+		tx := msg.transactionUpdate
+		ev := &Event{Reducer: &tx.reducer}
+		if err := c.applyUpdate(ev, tx.update); err != nil {
+			return err
+		}
+		c.mu.Lock()
+		var callbacks []func(ev *ReducerEvent)
+		for _, cb := range c.reducerCallbacks[tx.reducer.ReducerName] {
+			callbacks = append(callbacks, cb)
+		}
+		c.mu.Unlock()
+		for _, cb := range callbacks {
+			cb(&tx.reducer)
+		}
+
+	case msg.lightUpdate != nil:
+		return c.applyUpdate(&Event{}, msg.lightUpdate)
+
+	case msg.oneOffResponse != nil:
+		resp := msg.oneOffResponse
+		c.mu.Lock()
+		result := c.oneOffQueries[string(resp.messageID)]
+		c.mu.Unlock()
+		if result != nil {
+			res := oneOffResult{tables: resp.tables}
+			if resp.err != nil {
+				res.err = errors.New(*resp.err)
+			}
+			result <- res
+		}
+
+	case msg.subscriptionError != nil:
+		subErr := msg.subscriptionError
+		if subErr.queryID == nil {
+			// An error which isn't tied to one subscription leaves the client cache in an unknown state.
+			return fmt.Errorf("spacetimedb: subscription error: %s", subErr.error)
+		}
+		c.mu.Lock()
+		handle := c.subscriptions[*subErr.queryID]
+		delete(c.subscriptions, *subErr.queryID)
+		if handle != nil {
+			handle.ended = true
+		}
+		c.mu.Unlock()
+		if handle != nil && handle.callbacks.OnError != nil {
+			handle.callbacks.OnError(errors.New(subErr.error))
+		}
+
+	case msg.multiApplied != nil:
+		if err := c.applyUpdate(&Event{}, msg.multiApplied.update); err != nil {
+			return err
+		}
+		c.mu.Lock()
+		handle := c.subscriptions[msg.multiApplied.queryID]
+		if handle != nil {
+			handle.active = true
+		}
+		c.mu.Unlock()
+		if handle != nil && handle.callbacks.OnApplied != nil {
+			handle.callbacks.OnApplied()
+		}
+
+	case msg.multiUnsubApplied != nil:
+		if err := c.applyUpdate(&Event{}, msg.multiUnsubApplied.update); err != nil {
+			return err
+		}
+		c.mu.Lock()
+		handle := c.subscriptions[msg.multiUnsubApplied.queryID]
+		delete(c.subscriptions, msg.multiUnsubApplied.queryID)
+		if handle != nil {
+			handle.ended = true
+		}
+		c.mu.Unlock()
+		if handle != nil && handle.callbacks.OnEnded != nil {
+			handle.callbacks.OnEnded()
+		}
+	}
+	return nil
+}
+
+// applyUpdate applies updates to the table caches,
+// then runs the row callbacks once every table is up to date.
+func (c *Conn) applyUpdate(ev *Event, updates []tableUpdate) error {
+	var pending []func(ev *Event)
+	for _, update := range updates {
+		table := c.tables[update.tableName]
+		if table == nil {
+			// A table the bindings don't know about, likely added since they were generated.
+			continue
+		}
+		runCallbacks, err := table.applyUpdate(update.deletes, update.inserts)
+		if err != nil {
+			return fmt.Errorf("spacetimedb: %w", err)
+		}
+		pending = append(pending, runCallbacks)
+	}
+	for _, runCallbacks := range pending {
+		runCallbacks(ev)
+	}
+	return nil
+}
@@ -0,0 +1,3 @@
+module github.com/clockworklabs/SpacetimeDB/sdks/go
+
+go 1.21
@@ -0,0 +1,346 @@
+package spacetimedb
+
+import (
+	"bytes"
+	"compress/gzip"
+	"errors"
+	"fmt"
+	"io"
+)
+
+// The messages of the `v1.bsatn.spacetimedb` WebSocket protocol,
+// as defined by `client-api-messages/src/websocket.rs`.
+//
+// Only the parts of the protocol this package uses are modeled here.
+
+// BinaryProtocol is the WebSocket subprotocol spoken by this package.
+const BinaryProtocol = "v1.bsatn.spacetimedb"
+
+// CallReducerFlags are passed along with a reducer call.
+type CallReducerFlags uint8
+
+const (
+	// FullUpdate asks to be notified about the reducer call's transaction,
+	// even if it didn't touch any subscribed rows. This is the default.
+	FullUpdate CallReducerFlags = 0
+	// NoSuccessNotify asks not to be notified about the reducer call's transaction
+	// if it succeeds without touching any subscribed rows.
+	NoSuccessNotify CallReducerFlags = 1
+)
+
+// Tags of the `ClientMessage` variants.
+const (
+	clientMsgCallReducer      = 0
+	clientMsgOneOffQuery      = 2
+	clientMsgSubscribeMulti   = 4
+	clientMsgUnsubscribeMulti = 6
+)
+
+func encodeCallReducer(reducer string, args []byte, requestID uint32, flags CallReducerFlags) []byte {
+	w := NewWriter()
+	w.WriteTag(clientMsgCallReducer)
+	w.WriteString(reducer)
+	w.WriteBytes(args)
+	w.WriteU32(requestID)
+	w.WriteU8(uint8(flags))
+	return w.Bytes()
+}
+
+func encodeOneOffQuery(messageID []byte, query string) []byte {
+	w := NewWriter()
+	w.WriteTag(clientMsgOneOffQuery)
+	w.WriteBytes(messageID)
+	w.WriteString(query)
+	return w.Bytes()
+}
+
+func encodeSubscribeMulti(queries []string, requestID, queryID uint32) []byte {
+	w := NewWriter()
+	w.WriteTag(clientMsgSubscribeMulti)
+	WriteArray(w, queries, (*Writer).WriteString)
+	w.WriteU32(requestID)
+	w.WriteU32(queryID)
+	return w.Bytes()
+}
+
+func encodeUnsubscribeMulti(requestID, queryID uint32) []byte {
+	w := NewWriter()
+	w.WriteTag(clientMsgUnsubscribeMulti)
+	w.WriteU32(requestID)
+	w.WriteU32(queryID)
+	return w.Bytes()
+}
+
+// Tags of the `ServerMessage` variants.
+const (
+	serverMsgInitialSubscription     = 0
+	serverMsgTransactionUpdate       = 1
+	serverMsgTransactionUpdateLight  = 2
+	serverMsgIdentityToken           = 3
+	serverMsgOneOffQueryResponse     = 4
+	serverMsgSubscribeApplied        = 5
+	serverMsgUnsubscribeApplied      = 6
+	serverMsgSubscriptionError       = 7
+	serverMsgSubscribeMultiApplied   = 8
+	serverMsgUnsubscribeMultiApplied = 9
+)
+
+// Compression tags which prefix each server message.
+const (
+	compressionNone   = 0
+	compressionBrotli = 1
+	compressionGzip   = 2
+)
+
+// tableUpdate holds the rows deleted from and inserted into a single table.
+type tableUpdate struct {
+	tableName string
+	deletes   [][]byte
+	inserts   [][]byte
+}
+
+type identityToken struct {
+	identity     Identity
+	token        string
+	connectionID ConnectionId
+}
+
+type transactionUpdate struct {
+	// status is one of statusCommitted, statusFailed or statusOutOfEnergy.
+	status       uint8
+	update       []tableUpdate
+	errorMessage string
+	reducer      ReducerEvent
+}
+
+const (
+	statusCommitted   = 0
+	statusFailed      = 1
+	statusOutOfEnergy = 2
+)
+
+type oneOffQueryResponse struct {
+	messageID []byte
+	err       *string
+	tables    []OneOffTable
+}
+
+type subscriptionError struct {
+	requestID *uint32
+	queryID   *uint32
+	error     string
+}
+
+// serverMessage is a decoded server message.
+// Exactly one of its fields is set, except for messages which this package ignores.
+type serverMessage struct {
+	identityToken     *identityToken
+	transactionUpdate *transactionUpdate
+	lightUpdate       []tableUpdate
+	oneOffResponse    *oneOffQueryResponse
+	subscriptionError *subscriptionError
+	multiApplied      *queryApplied
+	multiUnsubApplied *queryApplied
+}
+
+// queryApplied is a `SubscribeMultiApplied` or `UnsubscribeMultiApplied`.
+type queryApplied struct {
+	queryID uint32
+	update  []tableUpdate
+}
+
+// parseServerMessage decompresses and decodes a binary WebSocket message from the server.
+func parseServerMessage(raw []byte) (*serverMessage, error) {
+	if len(raw) == 0 {
+		return nil, errors.New("received an empty message, but messages always start with a compression tag")
+	}
+	body, err := decompress(raw[0], raw[1:])
+	if err != nil {
+		return nil, err
+	}
+	r := NewReader(body)
+	msg := &serverMessage{}
+	switch tag := r.ReadTag(); tag {
+	case serverMsgInitialSubscription, serverMsgSubscribeApplied, serverMsgUnsubscribeApplied:
+		// Responses to the legacy and single-query subscription messages, which we never send.
+		return msg, nil
+	case serverMsgTransactionUpdate:
+		msg.transactionUpdate = readTransactionUpdate(r)
+	case serverMsgTransactionUpdateLight:
+		r.ReadU32() // request_id
+		msg.lightUpdate = readDatabaseUpdate(r)
+	case serverMsgIdentityToken:
+		msg.identityToken = &identityToken{
+			identity:     r.ReadIdentity(),
+			token:        r.ReadString(),
+			connectionID: r.ReadConnectionId(),
+		}
+	case serverMsgOneOffQueryResponse:
+		msg.oneOffResponse = &oneOffQueryResponse{
+			messageID: r.ReadBytes(),
+			err:       ReadOption(r, (*Reader).ReadString),
+			tables: ReadArray(r, func(r *Reader) OneOffTable {
+				return OneOffTable{TableName: r.ReadString(), Rows: readRowList(r)}
+			}),
+		}
+		r.ReadTimeDuration() // total_host_execution_duration
+	case serverMsgSubscriptionError:
+		r.ReadU64() // total_host_execution_duration_micros
+		msg.subscriptionError = &subscriptionError{
+			requestID: ReadOption(r, (*Reader).ReadU32),
+			queryID:   ReadOption(r, (*Reader).ReadU32),
+		}
+		ReadOption(r, (*Reader).ReadU32) // table_id
+		msg.subscriptionError.error = r.ReadString()
+	case serverMsgSubscribeMultiApplied, serverMsgUnsubscribeMultiApplied:
+		r.ReadU32() // request_id
+		r.ReadU64() // total_host_execution_duration_micros
+		applied := &queryApplied{queryID: r.ReadU32()}
+		applied.update = readDatabaseUpdate(r)
+		if tag == serverMsgSubscribeMultiApplied {
+			msg.multiApplied = applied
+		} else {
+			msg.multiUnsubApplied = applied
+		}
+	default:
+		return nil, fmt.Errorf("unknown server message tag %d", tag)
+	}
+	if err := r.finish(); err != nil {
+		return nil, fmt.Errorf("malformed server message: %w", err)
+	}
+	return msg, nil
+}
+
+func readTransactionUpdate(r *Reader) *transactionUpdate {
+	tx := &transactionUpdate{}
+	switch tx.status = r.ReadTag(); tx.status {
+	case statusCommitted:
+		tx.update = readDatabaseUpdate(r)
+	case statusFailed:
+		tx.errorMessage = r.ReadString()
+	case statusOutOfEnergy:
+	default:
+		r.Fail(fmt.Errorf("unknown update status tag %d", tx.status))
+	}
+	tx.reducer.Timestamp = r.ReadTimestamp()
+	tx.reducer.CallerIdentity = r.ReadIdentity()
+	tx.reducer.CallerConnectionId = r.ReadConnectionId()
+	tx.reducer.ReducerName = r.ReadString()
+	r.ReadU32() // reducer_id
+	tx.reducer.Args = r.ReadBytes()
+	tx.reducer.RequestID = r.ReadU32()
+	tx.reducer.ReturnValue = ReadOption(r, (*Reader).ReadBytes)
+	tx.reducer.EnergyQuantaUsed = r.ReadU128()
+	tx.reducer.TotalHostExecutionDuration = r.ReadTimeDuration()
+	switch tx.status {
+	case statusCommitted:
+		tx.reducer.Status = Committed
+	case statusFailed:
+		tx.reducer.Status = Failed
+		tx.reducer.Error = tx.errorMessage
+	case statusOutOfEnergy:
+		tx.reducer.Status = OutOfEnergy
+	}
+	return tx
+}
+
+func readDatabaseUpdate(r *Reader) []tableUpdate {
+	return ReadArray(r, readTableUpdate)
+}
+
+func readTableUpdate(r *Reader) tableUpdate {
+	r.ReadU32() // table_id
+	update := tableUpdate{tableName: r.ReadString()}
+	r.ReadU64() // num_rows
+	queryUpdates := ReadArray(r, readCompressableQueryUpdate)
+	for _, qu := range queryUpdates {
+		update.deletes = append(update.deletes, qu.deletes...)
+		update.inserts = append(update.inserts, qu.inserts...)
+	}
+	return update
+}
+
+type queryUpdate struct {
+	deletes [][]byte
+	inserts [][]byte
+}
+
+func readQueryUpdate(r *Reader) queryUpdate {
+	return queryUpdate{deletes: readRowList(r), inserts: readRowList(r)}
+}
+
+func readCompressableQueryUpdate(r *Reader) queryUpdate {
+	tag := r.ReadTag()
+	if tag == 0 {
+		return readQueryUpdate(r)
+	}
+	// The `Brotli` and `Gzip` variants hold a compressed `QueryUpdate`,
+	// in the same order as the compression tags.
+	body, err := decompress(tag, r.ReadBytes())
+	if err != nil {
+		r.Fail(err)
+		return queryUpdate{}
+	}
+	qu, err := DecodeWith(body, readQueryUpdate)
+	if err != nil {
+		r.Fail(err)
+	}
+	return qu
+}
+
+// readRowList reads a `BsatnRowList` and splits it into the BSATN of each row.
+func readRowList(r *Reader) [][]byte {
+	var rowSize uint16
+	var offsets []uint64
+	switch tag := r.ReadTag(); tag {
+	case 0:
+		rowSize = r.ReadU16()
+	case 1:
+		offsets = ReadArray(r, (*Reader).ReadU64)
+	default:
+		r.Fail(fmt.Errorf("unknown RowSizeHint tag %d", tag))
+	}
+	data := r.ReadBytes()
+	if r.Err() != nil {
+		return nil
+	}
+	var rows [][]byte
+	if offsets == nil {
+		if rowSize == 0 {
+			return nil
+		}
+		for start := 0; start+int(rowSize) <= len(data); start += int(rowSize) {
+			rows = append(rows, data[start:start+int(rowSize)])
+		}
+		return rows
+	}
+	for i, start := range offsets {
+		end := uint64(len(data))
+		if i+1 < len(offsets) {
+			end = offsets[i+1]
+		}
+		if start > end || end > uint64(len(data)) {
+			r.Fail(errors.New("row offsets out of bounds"))
+			return nil
+		}
+		rows = append(rows, data[start:end])
+	}
+	return rows
+}
+
+func decompress(tag uint8, data []byte) ([]byte, error) {
+	switch tag {
+	case compressionNone:
+		return data, nil
+	case compressionGzip:
+		zr, err := gzip.NewReader(bytes.NewReader(data))
+		if err != nil {
+			return nil, fmt.Errorf("gzip: %w", err)
+		}
+		return io.ReadAll(zr)
+	case compressionBrotli:
+		return nil, errors.New("received a brotli-compressed message, but brotli is not supported; connect with Gzip or no compression")
+	default:
+		return nil, fmt.Errorf("unknown compression tag %d", tag)
+	}
+}
@@ -0,0 +1,263 @@
+package spacetimedb
+
+import (
+	"fmt"
+	"sync"
+)
+
+// CallbackID identifies a registered callback, so that it can later be removed.
+type CallbackID uint64
+
+// Event describes why rows in the client cache changed.
+type Event struct {
+	// Reducer is the reducer call whose transaction changed the rows.
+	//
+	// It is nil if the rows changed because a subscription was applied or ended,
+	// or because of a transaction the server described without its reducer call,
+	// as it does for connections in light mode.
+	Reducer *ReducerEvent
+}
+
+// TableHandle is implemented by *TableCache, and lets a Conn keep any table's cache up to date.
+type TableHandle interface {
+	TableName() string
+	applyUpdate(deletes, inserts [][]byte) (func(ev *Event), error)
+}
+
+type tableEntry[Row any] struct {
+	row Row
+	// refs counts the subscriptions which include the row.
+	refs int
+}
+
+// TableCache holds the rows of one table which match the connection's subscriptions.
+//
+// Generated module bindings construct a TableCache for each table,
+// and wrap it to add methods for their unique and indexed columns.
+// Its methods are safe to call from any goroutine.
+type TableCache[Row any] struct {
+	name       string
+	decode     func(r *Reader) Row
+	primaryKey func(row Row) any
+
+	mu   sync.RWMutex
+	rows map[string]*tableEntry[Row]
+
+	callbacksMu    sync.Mutex
+	nextCallbackID CallbackID
+	onInsert       map[CallbackID]func(ev *Event, row Row)
+	onDelete       map[CallbackID]func(ev *Event, row Row)
+	onUpdate       map[CallbackID]func(ev *Event, oldRow, newRow Row)
+}
+
+// NewTableCache returns an empty cache for the table called name, whose rows are read by decode.
+//
+// If the table has a primary key, primaryKey extracts it from a row,
+// and a row deleted and inserted with the same primary key in one transaction is reported as an update.
+// Otherwise, primaryKey is nil.
+func NewTableCache[Row any](name string, decode func(r *Reader) Row, primaryKey func(row Row) any) *TableCache[Row] {
+	return &TableCache[Row]{
+		name:       name,
+		decode:     decode,
+		primaryKey: primaryKey,
+		rows:       make(map[string]*tableEntry[Row]),
+		onInsert:   make(map[CallbackID]func(ev *Event, row Row)),
+		onDelete:   make(map[CallbackID]func(ev *Event, row Row)),
+		onUpdate:   make(map[CallbackID]func(ev *Event, oldRow, newRow Row)),
+	}
+}
+
+// TableName returns the name of the table.
+func (t *TableCache[Row]) TableName() string {
+	return t.name
+}
+
+// Count returns the number of subscribed rows in the table.
+func (t *TableCache[Row]) Count() int {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	return len(t.rows)
+}
+
+// Rows returns all the subscribed rows in the table, in no particular order.
+func (t *TableCache[Row]) Rows() []Row {
+	return t.Filter(func(Row) bool { return true })
+}
+
+// Filter returns the subscribed rows for which pred returns true, in no particular order.
+func (t *TableCache[Row]) Filter(pred func(row Row) bool) []Row {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	var out []Row
+	for _, entry := range t.rows {
+		if pred(entry.row) {
+			out = append(out, entry.row)
+		}
+	}
+	return out
+}
+
+// Find returns a subscribed row for which pred returns true, if there is one.
+func (t *TableCache[Row]) Find(pred func(row Row) bool) (Row, bool) {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	for _, entry := range t.rows {
+		if pred(entry.row) {
+			return entry.row, true
+		}
+	}
+	var zero Row
+	return zero, false
+}
+
+// OnInsert registers cb to run whenever a row is inserted into the client cache.
+func (t *TableCache[Row]) OnInsert(cb func(ev *Event, row Row)) CallbackID {
+	return addCallback(t, t.onInsert, cb)
+}
+
+// RemoveOnInsert removes a callback registered with OnInsert.
+func (t *TableCache[Row]) RemoveOnInsert(id CallbackID) {
+	removeCallback(t, t.onInsert, id)
+}
+
+// OnDelete registers cb to run whenever a row is deleted from the client cache.
+func (t *TableCache[Row]) OnDelete(cb func(ev *Event, row Row)) CallbackID {
+	return addCallback(t, t.onDelete, cb)
+}
+
+// RemoveOnDelete removes a callback registered with OnDelete.
+func (t *TableCache[Row]) RemoveOnDelete(id CallbackID) {
+	removeCallback(t, t.onDelete, id)
+}
+
+// OnUpdate registers cb to run whenever a row is replaced by another with the same primary key.
+//
+// Updates are only reported for tables with primary keys.
+// The replaced and replacing rows are not reported to OnDelete or OnInsert callbacks.
+func (t *TableCache[Row]) OnUpdate(cb func(ev *Event, oldRow, newRow Row)) CallbackID {
+	return addCallback(t, t.onUpdate, cb)
+}
+
+// RemoveOnUpdate removes a callback registered with OnUpdate.
+func (t *TableCache[Row]) RemoveOnUpdate(id CallbackID) {
+	removeCallback(t, t.onUpdate, id)
+}
+
+func addCallback[Row, F any](t *TableCache[Row], callbacks map[CallbackID]F, cb F) CallbackID {
+	t.callbacksMu.Lock()
+	defer t.callbacksMu.Unlock()
+	t.nextCallbackID++
+	callbacks[t.nextCallbackID] = cb
+	return t.nextCallbackID
+}
+
+func removeCallback[Row, F any](t *TableCache[Row], callbacks map[CallbackID]F, id CallbackID) {
+	t.callbacksMu.Lock()
+	defer t.callbacksMu.Unlock()
+	delete(callbacks, id)
+}
+
+func snapshotCallbacks[Row, F any](t *TableCache[Row], callbacks map[CallbackID]F) []F {
+	t.callbacksMu.Lock()
+	defer t.callbacksMu.Unlock()
+	out := make([]F, 0, len(callbacks))
+	for _, cb := range callbacks {
+		out = append(out, cb)
+	}
+	return out
+}
+
+type rowUpdate[Row any] struct {
+	oldRow, newRow Row
+}
+
+// applyUpdate applies the BSATN-encoded deletes and inserts to the cache,
+// and returns a function which runs the callbacks for the resulting changes.
+//
+// Rows are reference counted, as overlapping subscriptions may each include the same row.
+// If any row fails to decode, the cache is left unchanged.
+func (t *TableCache[Row]) applyUpdate(deletes, inserts [][]byte) (func(ev *Event), error) {
+	decoded := make([]Row, len(inserts))
+	for i, data := range inserts {
+		row, err := DecodeWith(data, t.decode)
+		if err != nil {
+			return nil, fmt.Errorf("decoding row of table %q: %w", t.name, err)
+		}
+		decoded[i] = row
+	}
+
+	var deleted, inserted []Row
+	t.mu.Lock()
+	for _, data := range deletes {
+		key := string(data)
+		entry, ok := t.rows[key]
+		if !ok {
+			continue
+		}
+		entry.refs--
+		if entry.refs == 0 {
+			delete(t.rows, key)
+			deleted = append(deleted, entry.row)
+		}
+	}
+	for i, data := range inserts {
+		key := string(data)
+		if entry, ok := t.rows[key]; ok {
+			entry.refs++
+			continue
+		}
+		t.rows[key] = &tableEntry[Row]{row: decoded[i], refs: 1}
+		inserted = append(inserted, decoded[i])
+	}
+	t.mu.Unlock()
+
+	deleted, inserted, updated := t.matchUpdates(deleted, inserted)
+
+	return func(ev *Event) {
+		for _, row := range deleted {
+			for _, cb := range snapshotCallbacks(t, t.onDelete) {
+				cb(ev, row)
+			}
+		}
+		for _, update := range updated {
+			for _, cb := range snapshotCallbacks(t, t.onUpdate) {
+				cb(ev, update.oldRow, update.newRow)
+			}
+		}
+		for _, row := range inserted {
+			for _, cb := range snapshotCallbacks(t, t.onInsert) {
+				cb(ev, row)
+			}
+		}
+	}, nil
+}
+
+// matchUpdates pairs deleted and inserted rows with the same primary key into updates,
+// returning the rows which remain unpaired along with the updates.
+func (t *TableCache[Row]) matchUpdates(deleted, inserted []Row) ([]Row, []Row, []rowUpdate[Row]) {
+	if t.primaryKey == nil || len(deleted) == 0 || len(inserted) == 0 {
+		return deleted, inserted, nil
+	}
+	deletedByKey := make(map[any]int, len(deleted))
+	for i, row := range deleted {
+		deletedByKey[t.primaryKey(row)] = i
+	}
+	var updated []rowUpdate[Row]
+	var onlyInserted []Row
+	paired := make([]bool, len(deleted))
+	for _, row := range inserted {
+		if i, ok := deletedByKey[t.primaryKey(row)]; ok && !paired[i] {
+			paired[i] = true
+			updated = append(updated, rowUpdate[Row]{oldRow: deleted[i], newRow: row})
+			continue
+		}
+		onlyInserted = append(onlyInserted, row)
+	}
+	var onlyDeleted []Row
+	for i, row := range deleted {
+		if !paired[i] {
+			onlyDeleted = append(onlyDeleted, row)
+		}
+	}
+	return onlyDeleted, onlyInserted, updated
+}
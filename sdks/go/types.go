@@ -0,0 +1,181 @@
+package spacetimedb
+
+import (
+	"encoding/hex"
+	"fmt"
+	"math/big"
+	"slices"
+	"time"
+)
+
+// U128 is an unsigned 128-bit integer, stored as little-endian bytes.
+type U128 [16]byte
+
+// I128 is a signed 128-bit two's complement integer, stored as little-endian bytes.
+type I128 [16]byte
+
+// U256 is an unsigned 256-bit integer, stored as little-endian bytes.
+type U256 [32]byte
+
+// I256 is a signed 256-bit two's complement integer, stored as little-endian bytes.
+type I256 [32]byte
+
+func (v U128) Big() *big.Int { return leToBig(v[:], false) }
+func (v I128) Big() *big.Int { return leToBig(v[:], true) }
+func (v U256) Big() *big.Int { return leToBig(v[:], false) }
+func (v I256) Big() *big.Int { return leToBig(v[:], true) }
+
+func (v U128) String() string { return v.Big().String() }
+func (v I128) String() string { return v.Big().String() }
+func (v U256) String() string { return v.Big().String() }
+func (v I256) String() string { return v.Big().String() }
+
+// U128FromBig converts x to a U128, failing if it is out of range.
+func U128FromBig(x *big.Int) (v U128, err error) { err = bigToLE(x, v[:], false); return }
+
+// I128FromBig converts x to an I128, failing if it is out of range.
+func I128FromBig(x *big.Int) (v I128, err error) { err = bigToLE(x, v[:], true); return }
+
+// U256FromBig converts x to a U256, failing if it is out of range.
+func U256FromBig(x *big.Int) (v U256, err error) { err = bigToLE(x, v[:], false); return }
+
+// I256FromBig converts x to an I256, failing if it is out of range.
+func I256FromBig(x *big.Int) (v I256, err error) { err = bigToLE(x, v[:], true); return }
+
+func leToBig(le []byte, signed bool) *big.Int {
+	be := slices.Clone(le)
+	slices.Reverse(be)
+	x := new(big.Int).SetBytes(be)
+	if signed && len(le) > 0 && le[len(le)-1]&0x80 != 0 {
+		x.Sub(x, new(big.Int).Lsh(big.NewInt(1), uint(8*len(le))))
+	}
+	return x
+}
+
+func bigToLE(x *big.Int, out []byte, signed bool) error {
+	bits := 8 * len(out)
+	lo, hi := new(big.Int), new(big.Int).Lsh(big.NewInt(1), uint(bits))
+	if signed {
+		hi.Rsh(hi, 1)
+		lo.Neg(hi)
+	}
+	if x.Cmp(lo) < 0 || x.Cmp(hi) >= 0 {
+		return fmt.Errorf("bsatn: %s is out of range for a %d-bit integer", x, bits)
+	}
+	y := new(big.Int).Set(x)
+	if y.Sign() < 0 {
+		y.Add(y, new(big.Int).Lsh(big.NewInt(1), uint(bits)))
+	}
+	y.FillBytes(out)
+	slices.Reverse(out)
+	return nil
+}
+
+// Unit is the type of the empty product, which has exactly one value.
+type Unit struct{}
+
+// Never is the type of the empty sum, which has no values.
+type Never struct{}
+
+// Identity identifies a SpacetimeDB user: a 256-bit unsigned integer, conventionally shown as 64 hex digits.
+type Identity U256
+
+// IdentityFromHex parses an Identity from its hex representation.
+func IdentityFromHex(s string) (Identity, error) {
+	var v Identity
+	return v, beHexToLE(s, v[:])
+}
+
+func (v Identity) String() string { return leToBeHex(v[:]) }
+
+// ConnectionId identifies a single connection to a database: a 128-bit unsigned integer, shown as 32 hex digits.
+type ConnectionId U128
+
+// ConnectionIdFromHex parses a ConnectionId from its hex representation.
+func ConnectionIdFromHex(s string) (ConnectionId, error) {
+	var v ConnectionId
+	return v, beHexToLE(s, v[:])
+}
+
+func (v ConnectionId) String() string { return leToBeHex(v[:]) }
+
+// IsZero reports whether v is the zero connection ID, which no real connection has.
+func (v ConnectionId) IsZero() bool { return v == ConnectionId{} }
+
+func leToBeHex(le []byte) string {
+	be := slices.Clone(le)
+	slices.Reverse(be)
+	return hex.EncodeToString(be)
+}
+
+func beHexToLE(s string, out []byte) error {
+	be, err := hex.DecodeString(s)
+	if err != nil {
+		return err
+	}
+	if len(be) != len(out) {
+		return fmt.Errorf("expected %d hex digits, got %d", 2*len(out), len(s))
+	}
+	copy(out, be)
+	slices.Reverse(out)
+	return nil
+}
+
+// Timestamp is a point in time, as microseconds since the Unix epoch.
+type Timestamp struct {
+	MicrosSinceUnixEpoch int64
+}
+
+// TimestampFromTime converts t to a Timestamp, truncating it to microseconds.
+func TimestampFromTime(t time.Time) Timestamp {
+	return Timestamp{MicrosSinceUnixEpoch: t.UnixMicro()}
+}
+
+func (v Timestamp) Time() time.Time { return time.UnixMicro(v.MicrosSinceUnixEpoch) }
+func (v Timestamp) String() string  { return v.Time().UTC().Format(time.RFC3339Nano) }
+
+// TimeDuration is a signed span of time, in microseconds.
+type TimeDuration struct {
+	Micros int64
+}
+
+// TimeDurationFromDuration converts d to a TimeDuration, truncating it to microseconds.
+func TimeDurationFromDuration(d time.Duration) TimeDuration {
+	return TimeDuration{Micros: d.Microseconds()}
+}
+
+func (v TimeDuration) Duration() time.Duration {
+	return time.Duration(v.Micros) * time.Microsecond
+}
+
+func (v TimeDuration) String() string { return v.Duration().String() }
+
+// ScheduleAt is when a scheduled reducer runs:
+// either a ScheduleAtInterval or a ScheduleAtTime.
+type ScheduleAt interface {
+	Encoder
+	isScheduleAt()
+}
+
+// ScheduleAtInterval runs a scheduled reducer repeatedly, every Value.
+type ScheduleAtInterval struct {
+	Value TimeDuration
+}
+
+// ScheduleAtTime runs a scheduled reducer once, at Value.
+type ScheduleAtTime struct {
+	Value Timestamp
+}
+
+func (ScheduleAtInterval) isScheduleAt() {}
+func (ScheduleAtTime) isScheduleAt()     {}
+
+func (v ScheduleAtInterval) EncodeBSATN(w *Writer) {
+	w.WriteTag(0)
+	w.WriteTimeDuration(v.Value)
+}
+
+func (v ScheduleAtTime) EncodeBSATN(w *Writer) {
+	w.WriteTag(1)
+	w.WriteTimestamp(v.Value)
+}
@@ -0,0 +1,365 @@
+//! Utilities for persisting the client cache between runs of a client,
+//! so that a restarted client can show its previously-subscribed rows at once,
+//! and then download just the changes it missed.
+//!
+//! Users are encouraged to import this module by name and refer to its contents by qualified path, like:
+//! ```ignore
+//! use spacetimedb_sdk::cache_storage;
+//! DbConnection::builder()
+//!     .with_cache_storage(cache_storage::File::new("my_app_cache.bsatn"))
+//! ```
+
+use crate::client_cache::ClientCache;
+use crate::spacetime_module::{DbUpdate, SpacetimeModule};
+use bytes::Bytes;
+use spacetimedb_client_api_messages::websocket as ws;
+use spacetimedb_lib::{bsatn, de::Deserialize, ser::Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CacheStorageError {
+    #[error("Error creating cache storage directory {path}")]
+    CreateDir {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Error writing client cache snapshot to file {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Error reading client cache snapshot from file {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Error serializing client cache snapshot")]
+    Serialize {
+        #[source]
+        source: bsatn::EncodeError,
+    },
+    #[error("Error deserializing client cache snapshot")]
+    Deserialize {
+        #[source]
+        source: bsatn::DecodeError,
+    },
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Somewhere to keep a snapshot of the client cache between runs.
+///
+/// Pass one to [`crate::DbConnectionBuilder::with_cache_storage`].
+/// The snapshot is an opaque BSATN-encoded blob;
+/// implementors need only store and return it verbatim.
+pub trait CacheStorage: Send + Sync + 'static {
+    /// Load the most recently saved snapshot,
+    /// or `None` if no snapshot has been saved.
+    fn load(&self) -> Result<Option<Vec<u8>>, CacheStorageError>;
+
+    /// Save `snapshot`, replacing any previously saved snapshot.
+    fn save(&self, snapshot: &[u8]) -> Result<(), CacheStorageError>;
+}
+
+/// A file on disk which stores, or can store, a snapshot of the client cache.
+///
+/// The file does not necessarily exist.
+/// Its parent directory is created when the snapshot is first saved.
+pub struct File {
+    path: PathBuf,
+}
+
+impl File {
+    /// Get a handle on the file at `path`.
+    ///
+    /// This method does not create the file or check that it exists.
+    ///
+    /// Connections to distinct databases should use distinct files.
+    /// A snapshot of a different database than the one being connected to is ignored.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CacheStorage for File {
+    fn load(&self) -> Result<Option<Vec<u8>>, CacheStorageError> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound) => Ok(None),
+            Err(source) => Err(CacheStorageError::Read {
+                path: self.path.clone(),
+                source,
+            }),
+        }
+    }
+
+    fn save(&self, snapshot: &[u8]) -> Result<(), CacheStorageError> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|source| CacheStorageError::CreateDir {
+                path: dir.to_owned(),
+                source,
+            })?;
+        }
+        // Write to a temporary file and rename it over the snapshot,
+        // so that a crash mid-write doesn't leave a truncated snapshot behind.
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, snapshot)
+            .and_then(|()| std::fs::rename(&tmp_path, &self.path))
+            .map_err(|source| CacheStorageError::Write {
+                path: self.path.clone(),
+                source,
+            })
+    }
+}
+
+/// A [`CacheStorage`] which keeps its snapshot in memory, for use in tests.
+///
+/// Clones share the same snapshot,
+/// so a clone may be passed to a connection and another used to inspect or seed what it saves.
+#[derive(Clone, Default)]
+pub struct InMemory {
+    snapshot: Arc<Mutex<Option<Vec<u8>>>>,
+}
+
+impl InMemory {
+    /// The most recently saved snapshot, if any.
+    pub fn snapshot(&self) -> Option<Vec<u8>> {
+        self.snapshot.lock().unwrap().clone()
+    }
+}
+
+impl CacheStorage for InMemory {
+    fn load(&self) -> Result<Option<Vec<u8>>, CacheStorageError> {
+        Ok(self.snapshot())
+    }
+
+    fn save(&self, snapshot: &[u8]) -> Result<(), CacheStorageError> {
+        *self.snapshot.lock().unwrap() = Some(snapshot.to_vec());
+        Ok(())
+    }
+}
+
+/// How often to save the client cache while transactions keep changing it.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The contents of the client cache, as saved to a [`CacheStorage`].
+#[derive(Serialize, Deserialize)]
+struct CacheSnapshot {
+    /// The host and database whose rows these are,
+    /// so that we don't restore the rows of one database into a connection to another,
+    /// even if they share a name.
+    host: String,
+    module_name: String,
+    /// The subscriptions whose rows these are.
+    subscriptions: Vec<CachedSubscription>,
+    tables: Vec<TableSnapshot>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TableSnapshot {
+    table_name: String,
+    /// The BSATN of each row in the table, repeated once per reference.
+    rows: Vec<Vec<u8>>,
+}
+
+/// A subscription whose rows are in the client cache.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CachedSubscription {
+    pub(crate) query_strings: Box<[Box<str>]>,
+    /// The last [`ws::CaughtUp`] received before saving,
+    /// if the subscription's rows reflect every transaction up to it,
+    /// so that the subscription can be resumed with just the changes since.
+    pub(crate) since: Option<ws::CaughtUp>,
+}
+
+/// A [`CacheStorage`] together with the host and name of the database whose rows it holds.
+pub(crate) struct BoundCacheStorage {
+    storage: Box<dyn CacheStorage>,
+    host: String,
+    module_name: String,
+    progress: Mutex<SaveProgress>,
+}
+
+/// What has happened to the client cache since it was last saved.
+struct SaveProgress {
+    /// The last [`ws::CaughtUp`] received, or `None` if the client cache has changed since.
+    caught_up: Option<ws::CaughtUp>,
+    /// Whether the client cache has changed since it was last saved.
+    dirty: bool,
+    /// Whether a subscription has been applied or ended since the client cache was last saved.
+    subscriptions_changed: bool,
+    last_saved: Instant,
+}
+
+impl BoundCacheStorage {
+    pub(crate) fn new(storage: Box<dyn CacheStorage>, host: String, module_name: String) -> Self {
+        Self {
+            storage,
+            host,
+            module_name,
+            progress: Mutex::new(SaveProgress {
+                caught_up: None,
+                dirty: false,
+                subscriptions_changed: false,
+                last_saved: Instant::now(),
+            }),
+        }
+    }
+
+    /// Populate the freshly-built `cache` with the rows in the saved snapshot, if any.
+    ///
+    /// Returns the subscriptions whose rows were restored,
+    /// which the caller must resume so that those rows are kept up to date, and eventually removed.
+    /// Failures are logged rather than returned, as the connection works just as well with an empty cache.
+    pub(crate) fn restore<M: SpacetimeModule>(&self, cache: &mut ClientCache<M>) -> Vec<CachedSubscription> {
+        let snapshot = match self.storage.load().and_then(|bytes| {
+            bytes
+                .map(|bytes| bsatn::from_slice::<CacheSnapshot>(&bytes))
+                .transpose()
+                .map_err(|source| CacheStorageError::Deserialize { source })
+        }) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return Vec::new(),
+            Err(e) => {
+                log::warn!("Failed to load client cache snapshot: {e}");
+                return Vec::new();
+            }
+        };
+        if (&*snapshot.host, &*snapshot.module_name) != (&*self.host, &*self.module_name) {
+            log::info!(
+                "Ignoring client cache snapshot of database {} at {}, as we are connecting to {} at {}",
+                snapshot.module_name,
+                snapshot.host,
+                self.module_name,
+                self.host,
+            );
+            return Vec::new();
+        }
+        if snapshot.subscriptions.is_empty() {
+            // Without a subscription to keep them up to date, the rows would be stale forever.
+            return Vec::new();
+        }
+
+        let update = cache.restoration_update(
+            snapshot
+                .tables
+                .into_iter()
+                .map(|table| (table.table_name, table.rows.into_iter().map(Bytes::from).collect())),
+        );
+        match M::DbUpdate::parse_update(update) {
+            Ok(update) => {
+                update.apply_to_client_cache(cache);
+                snapshot.subscriptions
+            }
+            Err(e) => {
+                // Likely the module's schema has changed since the snapshot was saved.
+                log::warn!("Ignoring client cache snapshot which does not match the module bindings: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Record that a message has changed the client cache,
+    /// and whether it applied or ended a subscription.
+    pub(crate) fn note_update(&self, subscriptions_changed: bool) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.caught_up = None;
+        progress.dirty = true;
+        progress.subscriptions_changed |= subscriptions_changed;
+    }
+
+    /// Record that the client cache reflects every transaction up to `caught_up`.
+    ///
+    /// Returns `true` if the caller should now save the client cache,
+    /// because a subscription has been applied or ended,
+    /// or because transactions have changed it for a while.
+    pub(crate) fn note_caught_up(&self, caught_up: ws::CaughtUp) -> bool {
+        let mut progress = self.progress.lock().unwrap();
+        progress.caught_up = Some(caught_up);
+        progress.dirty && (progress.subscriptions_changed || progress.last_saved.elapsed() >= SAVE_INTERVAL)
+    }
+
+    /// The [`ws::CaughtUp`] which the client cache currently reflects, if known.
+    pub(crate) fn caught_up(&self) -> Option<ws::CaughtUp> {
+        self.progress.lock().unwrap().caught_up
+    }
+
+    /// Save the rows in `cache`, which belong to `subscriptions`, as the new snapshot.
+    ///
+    /// Failures are logged rather than returned, as they don't affect the connection.
+    pub(crate) fn save<M: SpacetimeModule>(&self, cache: &ClientCache<M>, subscriptions: Vec<CachedSubscription>) {
+        let snapshot = CacheSnapshot {
+            host: self.host.clone(),
+            module_name: self.module_name.clone(),
+            subscriptions,
+            tables: cache
+                .list_all_rows_with_refs()
+                .map(|(table_name, rows)| TableSnapshot {
+                    table_name: table_name.into(),
+                    rows: rows.into_iter().map(Vec::from).collect(),
+                })
+                .collect(),
+        };
+        let res = bsatn::to_vec(&snapshot)
+            .map_err(|source| CacheStorageError::Serialize { source })
+            .and_then(|bytes| self.storage.save(&bytes));
+        match res {
+            Ok(()) => {
+                let mut progress = self.progress.lock().unwrap();
+                progress.dirty = false;
+                progress.subscriptions_changed = false;
+                progress.last_saved = Instant::now();
+            }
+            Err(e) => log::warn!("Failed to save client cache snapshot: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn in_memory_clones_share_snapshot() {
+        let storage = InMemory::default();
+        assert_eq!(storage.load().unwrap(), None);
+
+        storage.clone().save(b"snapshot").unwrap();
+        assert_eq!(storage.load().unwrap().as_deref(), Some(&b"snapshot"[..]));
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_bsatn() {
+        let subscription = CachedSubscription {
+            query_strings: ["SELECT * FROM person".into()].into(),
+            since: Some(ws::CaughtUp {
+                history_id: 7,
+                tx_offset: 42,
+            }),
+        };
+        let snapshot = CacheSnapshot {
+            host: "http://localhost:3000/".into(),
+            module_name: "my_module".into(),
+            subscriptions: vec![subscription.clone()],
+            tables: vec![TableSnapshot {
+                table_name: "person".into(),
+                rows: vec![vec![1, 2, 3], vec![1, 2, 3], vec![4]],
+            }],
+        };
+        let bytes = bsatn::to_vec(&snapshot).unwrap();
+        let decoded = bsatn::from_slice::<CacheSnapshot>(&bytes).unwrap();
+
+        assert_eq!(decoded.host, "http://localhost:3000/");
+        assert_eq!(decoded.module_name, "my_module");
+        assert_eq!(decoded.subscriptions, [subscription]);
+        assert_eq!(decoded.tables.len(), 1);
+        assert_eq!(decoded.tables[0].table_name, "person");
+        assert_eq!(decoded.tables[0].rows, snapshot.tables[0].rows);
+    }
+}
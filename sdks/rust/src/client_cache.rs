@@ -10,8 +10,11 @@ use bytes::Bytes;
 use core::any::type_name;
 use core::hash::Hash;
 use futures_channel::mpsc;
+use spacetimedb_client_api_messages::websocket::{self as ws, BsatnFormat};
 use spacetimedb_data_structures::map::{DefaultHashBuilder, Entry, HashCollectionExt, HashMap};
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::sync::Arc;
 
 /// A local mirror of the subscribed rows of one table in the database.
@@ -35,6 +38,15 @@ pub struct TableCache<Row> {
     /// Entries are added to this map during [`crate::DbConnectionBuilder::build`],
     /// via a `register_table` function autogenerated for each table.
     pub(crate) unique_indices: HashMap<&'static str, Box<dyn UniqueIndexDyn<Row = Row>>>,
+
+    /// Each of the non-unique B-tree indices on this table.
+    ///
+    /// The values of this map will all be instances of `BTreeIndexImpl`.
+    /// As with `unique_indices`, the boxing and `dyn` dispatch erase the type of the indexed columns.
+    ///
+    /// Entries are added to this map during [`crate::DbConnectionBuilder::build`],
+    /// via a `register_table` function autogenerated for each table.
+    pub(crate) btree_indices: HashMap<&'static str, Box<dyn BTreeIndexDyn<Row = Row>>>,
 }
 
 /// Stores an entry of the typed row value together with its ref count in the table cache.
@@ -64,6 +76,7 @@ impl<Row> Default for TableCache<Row> {
         Self {
             entries: Default::default(),
             unique_indices: Default::default(),
+            btree_indices: Default::default(),
         }
     }
 }
@@ -173,6 +186,15 @@ impl<'r, Row> TableAppliedDiff<'r, Row> {
     }
 }
 
+impl<Row> TableCache<Row> {
+    /// The BSATN of each row in this table, repeated once per reference.
+    fn rows_with_refs(&self) -> impl Iterator<Item = Bytes> + '_ {
+        self.entries
+            .iter()
+            .flat_map(|(bsatn, entry)| std::iter::repeat_n(bsatn.clone(), entry.ref_count as usize))
+    }
+}
+
 impl<Row: Clone + Send + Sync + 'static> TableCache<Row> {
     fn handle_delete<'r>(
         &mut self,
@@ -258,6 +280,14 @@ impl<Row: Clone + Send + Sync + 'static> TableCache<Row> {
                 index.add_row(row.clone());
             }
         }
+        for index in self.btree_indices.values_mut() {
+            for (&bsatn, &row) in delete_events.iter() {
+                index.remove_row(bsatn, row);
+            }
+            for (&bsatn, &row) in insert_events.iter() {
+                index.add_row(bsatn, row);
+            }
+        }
 
         TableAppliedDiff {
             deletes: delete_events,
@@ -279,6 +309,37 @@ impl<Row: Clone + Send + Sync + 'static> TableCache<Row> {
         index.find_row(key)
     }
 
+    /// Get the B-tree index named `index_name`, whose indexed columns are of type `Col`.
+    fn btree_index<Col: Ord + 'static>(&self, index_name: &'static str) -> &BTreeIndexImpl<Row, Col> {
+        self.btree_indices
+            .get(index_name)
+            .unwrap_or_else(|| panic!("No such B-tree index: {index_name}"))
+            .as_any()
+            .downcast_ref()
+            .unwrap_or_else(|| panic!("B-tree index {index_name} accessed with key of incorrect type"))
+    }
+
+    /// Called by the codegen when initializing the client cache during [`crate::DbConnectionBuilder::build`].
+    pub fn add_btree_index<Col>(&mut self, index_name: &'static str, get_key: fn(&Row) -> Col)
+    where
+        Col: Ord + Send + Sync + 'static,
+    {
+        assert!(self.entries.is_empty(), "Cannot add an index to a populated table; indices should only be added during initialization, before subscribing to any rows.");
+        if self
+            .btree_indices
+            .insert(
+                index_name,
+                Box::new(BTreeIndexImpl {
+                    get_key,
+                    rows: Default::default(),
+                }),
+            )
+            .is_some()
+        {
+            panic!("Duplicate B-tree index name {index_name}");
+        }
+    }
+
     /// Called by the codegen when initializing the client cache during [`crate::DbConnectionBuilder::build`].
     pub fn add_unique_constraint<Col>(&mut self, unique_index_name: &'static str, get_unique_col: fn(&Row) -> &Col)
     where
@@ -308,13 +369,23 @@ pub struct ClientCache<M: SpacetimeModule + ?Sized> {
     /// The strings are table names, since we may have multiple tables with the same row type.
     tables: Map<dyn Any + Send + Sync>,
 
+    /// For each table, a function which lists the BSATN of its cached rows,
+    /// each repeated once per reference.
+    ///
+    /// Populated by [`Self::get_or_make_table`],
+    /// so that [`Self::resumption_update`] can enumerate rows without knowing their types.
+    row_listers: HashMap<&'static str, RowLister<M>>,
+
     _module: PhantomData<M>,
 }
 
+type RowLister<M> = fn(&ClientCache<M>, &'static str) -> Vec<Bytes>;
+
 impl<M: SpacetimeModule> Default for ClientCache<M> {
     fn default() -> Self {
         Self {
             tables: Map::new(),
+            row_listers: HashMap::default(),
             _module: PhantomData,
         }
     }
@@ -337,6 +408,9 @@ impl<M: SpacetimeModule> ClientCache<M> {
         &mut self,
         table_name: &'static str,
     ) -> &mut TableCache<Row> {
+        self.row_listers
+            .entry(table_name)
+            .or_insert(Self::list_rows_with_refs::<Row>);
         self.tables
             .entry::<HashMap<&'static str, TableCache<Row>>>()
             .or_insert_with(Default::default)
@@ -359,6 +433,102 @@ impl<M: SpacetimeModule> ClientCache<M> {
 
         table.apply_diff(diff)
     }
+
+    /// List the BSATN of each row in the table `table_name`, repeated once per reference.
+    fn list_rows_with_refs<Row: InModule<Module = M> + Send + Sync + 'static>(
+        &self,
+        table_name: &'static str,
+    ) -> Vec<Bytes> {
+        self.get_table::<Row>(table_name)
+            .into_iter()
+            .flat_map(TableCache::rows_with_refs)
+            .collect()
+    }
+
+    /// List the BSATN of the rows in each table with any rows, repeated once per reference.
+    pub(crate) fn list_all_rows_with_refs(&self) -> impl Iterator<Item = (&'static str, Vec<Bytes>)> + '_ {
+        self.row_listers
+            .iter()
+            .map(|(&table_name, list_rows)| (table_name, list_rows(self, table_name)))
+            .filter(|(_, rows)| !rows.is_empty())
+    }
+
+    /// Build an update which replaces the contents of this cache
+    /// with the rows in `initial_updates`, the fresh initial rows of every resumed subscription.
+    ///
+    /// The update deletes every cached row once per reference and inserts every row in `initial_updates`.
+    /// As [`TableCache::apply_diff`] applies inserts before deletes
+    /// and only reports rows whose reference count crosses zero,
+    /// rows which were present both before and after the reconnect produce no events,
+    /// and the caller can pair the remaining deletes and inserts into updates as usual.
+    ///
+    /// This also replaces rows restored from a [`crate::cache_storage::CacheStorage`]
+    /// when the server can't send just the changes to them.
+    pub(crate) fn resumption_update(
+        &self,
+        initial_updates: Vec<ws::DatabaseUpdate<BsatnFormat>>,
+    ) -> ws::DatabaseUpdate<BsatnFormat> {
+        let mut tables = initial_updates
+            .into_iter()
+            .flat_map(|update| update.tables)
+            .collect::<Vec<_>>();
+        tables.extend(
+            self.list_all_rows_with_refs()
+                .map(|(table_name, deletes)| raw_table_update(table_name, deletes, Vec::new())),
+        );
+        ws::DatabaseUpdate { tables }
+    }
+
+    /// Build an update which inserts the rows in `tables`, a list of table names and BSATN-encoded rows,
+    /// as restored from a [`crate::cache_storage::CacheStorage`].
+    ///
+    /// Tables which are not defined by the module bindings are skipped.
+    pub(crate) fn restoration_update(
+        &self,
+        tables: impl IntoIterator<Item = (String, Vec<Bytes>)>,
+    ) -> ws::DatabaseUpdate<BsatnFormat> {
+        let tables = tables
+            .into_iter()
+            .filter_map(|(table_name, inserts)| {
+                let Some((&table_name, _)) = self.row_listers.get_key_value(&*table_name) else {
+                    log::debug!("Skipping restored rows of unknown table {table_name}");
+                    return None;
+                };
+                Some(raw_table_update(table_name, Vec::new(), inserts))
+            })
+            .collect();
+        ws::DatabaseUpdate { tables }
+    }
+}
+
+/// Build an uncompressed [`ws::TableUpdate`] for `table_name` with the given BSATN-encoded rows.
+fn raw_table_update(
+    table_name: &'static str,
+    deletes: Vec<Bytes>,
+    inserts: Vec<Bytes>,
+) -> ws::TableUpdate<BsatnFormat> {
+    ws::TableUpdate {
+        // Codegen matches table updates by name, so the ID is never read.
+        table_id: <_>::default(),
+        table_name: table_name.into(),
+        num_rows: (deletes.len() + inserts.len()) as u64,
+        updates: [ws::CompressableQueryUpdate::Uncompressed(ws::QueryUpdate {
+            deletes: bsatn_row_list(deletes),
+            inserts: bsatn_row_list(inserts),
+        })]
+        .into(),
+    }
+}
+
+/// Pack the BSATN of each of `rows` into a [`ws::BsatnRowList`].
+fn bsatn_row_list(rows: Vec<Bytes>) -> ws::BsatnRowList {
+    let mut offsets = Vec::with_capacity(rows.len());
+    let mut data = Vec::with_capacity(rows.iter().map(|row| row.len()).sum());
+    for row in rows {
+        offsets.push(data.len() as ws::RowOffset);
+        data.extend_from_slice(&row);
+    }
+    ws::BsatnRowList::new(ws::RowSizeHint::RowOffsets(offsets.into()), data.into())
 }
 
 /// Internal implementation of a generated `TableHandle` struct,
@@ -492,6 +662,15 @@ impl<Row: InModule + Send + Sync + Clone + 'static> TableHandle<Row> {
         });
     }
 
+    /// Called by autogenerated B-tree index access methods.
+    pub fn get_btree_index<Col>(&self, index_name: &'static str) -> BTreeIndexHandle<Row, Col> {
+        BTreeIndexHandle {
+            table_handle: self.clone(),
+            index_name,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Called by autogenerated unique index access methods.
     pub fn get_unique_constraint<Col>(&self, constraint_name: &'static str) -> UniqueConstraintHandle<Row, Col> {
         UniqueConstraintHandle {
@@ -598,3 +777,218 @@ where
         self.rows.get(col)
     }
 }
+
+/// A handle on a non-unique B-tree index,
+/// which allows looking up rows by the value of the indexed columns, or by a range of values.
+///
+/// Like [`UniqueConstraintHandle`], this holds a handle on the whole [`ClientCache`]
+/// rather than a reference to the index,
+/// and acquires short-lived exclusive access to it during operations.
+pub struct BTreeIndexHandle<Row: InModule, Col> {
+    table_handle: TableHandle<Row>,
+    index_name: &'static str,
+    _phantom: PhantomData<BTreeMap<Col, Row>>,
+}
+
+impl<Row: Clone + InModule + Send + Sync + 'static, Col: Ord + 'static> BTreeIndexHandle<Row, Col> {
+    /// Returns the subscribed rows whose indexed columns are equal to `col_val`.
+    pub fn filter(&self, col_val: &Col) -> impl Iterator<Item = Row> {
+        self.table_handle
+            .with_table_cache(|table| table.btree_index::<Col>(self.index_name).filter(col_val))
+            .into_iter()
+    }
+
+    /// Returns the subscribed rows whose indexed columns lie within `range`,
+    /// in ascending order of the indexed columns.
+    ///
+    /// A range which starts after it ends yields no rows.
+    pub fn range(&self, range: impl RangeBounds<Col>) -> impl Iterator<Item = Row> {
+        self.table_handle
+            .with_table_cache(|table| table.btree_index::<Col>(self.index_name).range(range))
+            .into_iter()
+    }
+}
+
+/// [`BTreeIndexImpl`], but with its `Col` type parameter erased.
+pub trait BTreeIndexDyn: Send + Sync + 'static {
+    /// The `Row` type parameter to [`BTreeIndexImpl`]; the type of rows in the indexed table.
+    type Row;
+
+    /// Insert a new row, whose BSATN encoding is `bsatn`, into the index.
+    fn add_row(&mut self, bsatn: &[u8], row: &Self::Row);
+    /// Delete the row whose BSATN encoding is `bsatn` from the index.
+    ///
+    /// Panics if the row is not resident in the index.
+    fn remove_row(&mut self, bsatn: &[u8], row: &Self::Row);
+    /// Get `self` as an `Any`, so that it can be downcast to a [`BTreeIndexImpl`] for lookups.
+    fn as_any(&self) -> &dyn std::any::Any;
+}
+
+/// A non-unique B-tree index on a table with rows of type `Row`, indexing columns whose values are of type `Col`.
+pub struct BTreeIndexImpl<Row, Col> {
+    /// All the rows in the table, grouped by the value of their indexed columns.
+    ///
+    /// As in [`UniqueIndexImpl`], the index stores duplicates of the rows.
+    /// Within a group, rows are keyed by their BSATN encoding,
+    /// so that a delete removes exactly the deleted row, even when `Row: !Eq`.
+    rows: BTreeMap<Col, HashMap<Box<[u8]>, Row>>,
+    /// Given a row, get the value of the indexed columns.
+    get_key: fn(&Row) -> Col,
+}
+
+impl<Row: Clone, Col: Ord> BTreeIndexImpl<Row, Col> {
+    fn filter(&self, col_val: &Col) -> Vec<Row> {
+        self.rows
+            .get(col_val)
+            .into_iter()
+            .flat_map(|rows| rows.values().cloned())
+            .collect()
+    }
+
+    fn range(&self, range: impl RangeBounds<Col>) -> Vec<Row> {
+        // `BTreeMap::range` panics on such ranges, which would poison the client cache's lock.
+        if is_inverted(&range) {
+            return Vec::new();
+        }
+        self.rows
+            .range(range)
+            .flat_map(|(_, rows)| rows.values().cloned())
+            .collect()
+    }
+}
+
+/// Is `range` one that `BTreeMap::range` rejects,
+/// i.e., does it start after it ends, or start and end at the same excluded value?
+///
+/// Such a range contains no values.
+fn is_inverted<T: Ord>(range: &impl RangeBounds<T>) -> bool {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        (Bound::Included(start) | Bound::Excluded(start), Bound::Included(end) | Bound::Excluded(end)) => start > end,
+        _ => false,
+    }
+}
+
+impl<Row, Col> BTreeIndexDyn for BTreeIndexImpl<Row, Col>
+where
+    Row: Clone + Send + Sync + 'static,
+    Col: Ord + Send + Sync + 'static,
+{
+    type Row = Row;
+    fn add_row(&mut self, bsatn: &[u8], row: &Self::Row) {
+        self.rows
+            .entry((self.get_key)(row))
+            .or_default()
+            .insert(bsatn.into(), row.clone());
+    }
+    fn remove_row(&mut self, bsatn: &[u8], row: &Self::Row) {
+        let key = (self.get_key)(row);
+        let rows = self
+            .rows
+            .get_mut(&key)
+            .expect("BTreeIndexDyn::remove_row for non-present row");
+        rows.remove(bsatn)
+            .expect("BTreeIndexDyn::remove_row for non-present row");
+        if rows.is_empty() {
+            self.rows.remove(&key);
+        }
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Row {
+        id: u32,
+        name: &'static str,
+    }
+
+    fn with_bsatn(id: u32, name: &'static str) -> WithBsatn<Row> {
+        WithBsatn {
+            // The cache only compares row bytes, so they needn't be real BSATN.
+            bsatn: Bytes::from(format!("{id}:{name}")),
+            row: Row { id, name },
+        }
+    }
+
+    #[test]
+    fn bsatn_row_list_round_trips() {
+        let rows = vec![Bytes::from_static(b"a"), Bytes::from_static(b"bcd"), Bytes::new()];
+        let list = bsatn_row_list(rows.clone());
+        assert_eq!((&list).into_iter().collect::<Vec<_>>(), rows);
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn btree_index_range_with_inverted_bounds_is_empty() {
+        let mut index = BTreeIndexImpl {
+            rows: BTreeMap::new(),
+            get_key: |row: &Row| row.id,
+        };
+        for id in 1..=3 {
+            let row = with_bsatn(id, "a");
+            index.add_row(&row.bsatn, &row.row);
+        }
+
+        assert_eq!(index.range(1..=2).len(), 2);
+        assert!(index.range(3..1).is_empty());
+        assert!(index.range(3..=1).is_empty());
+        assert!(index.range((Bound::Excluded(2), Bound::Excluded(2))).is_empty());
+        assert!(index.range((Bound::Excluded(2), Bound::Included(1))).is_empty());
+        assert!(index.range(2..2).is_empty());
+    }
+
+    #[test]
+    fn resumption_reports_only_changed_rows() {
+        let mut table = TableCache::<Row>::default();
+        // Row 1 is in two of the subscribed queries.
+        let before = TableUpdate {
+            inserts: vec![
+                with_bsatn(1, "a"),
+                with_bsatn(1, "a"),
+                with_bsatn(2, "b"),
+                with_bsatn(3, "c"),
+            ],
+            deletes: vec![],
+        };
+        table.apply_diff(&before);
+
+        // While disconnected, row 2 was updated, row 3 deleted and row 4 inserted.
+        // As in `ClientCache::resumption_update`, every cached row is deleted once per reference,
+        // and every fresh row inserted.
+        let deletes = table
+            .rows_with_refs()
+            .map(|bsatn| WithBsatn {
+                row: table.entries[&bsatn].row.clone(),
+                bsatn,
+            })
+            .collect();
+        let resumed = TableUpdate {
+            inserts: vec![
+                with_bsatn(1, "a"),
+                with_bsatn(1, "a"),
+                with_bsatn(2, "b2"),
+                with_bsatn(4, "d"),
+            ],
+            deletes,
+        };
+        let diff = table.apply_diff(&resumed).with_updates_by_pk(|row| &row.id);
+
+        assert_eq!(diff.inserts().cloned().collect::<Vec<_>>(), [with_bsatn(4, "d").row]);
+        assert_eq!(diff.deletes().cloned().collect::<Vec<_>>(), [with_bsatn(3, "c").row]);
+        assert_eq!(
+            diff.updates()
+                .map(|(old, new)| (old.clone(), new.clone()))
+                .collect::<Vec<_>>(),
+            [(with_bsatn(2, "b").row, with_bsatn(2, "b2").row)]
+        );
+
+        assert_eq!(table.entries.len(), 3);
+        assert_eq!(table.entries[&with_bsatn(1, "a").bsatn].ref_count, 2);
+    }
+}
@@ -19,26 +19,30 @@
 //! This module is internal, and may incompatibly change without warning.
 
 use crate::{
+    cache_storage::{BoundCacheStorage, CacheStorage},
     callbacks::{CallbackId, DbCallbacks, ReducerCallback, ReducerCallbacks, RowCallback, UpdateCallback},
     client_cache::{ClientCache, TableHandle},
     spacetime_module::{AbstractEventContext, AppliedDiff, DbConnection, DbUpdate, InModule, SpacetimeModule},
     subscription::{
-        OnAppliedCallback, OnErrorCallback, PendingUnsubscribeResult, SubscriptionHandleImpl, SubscriptionManager,
+        OnAppliedCallback, OnErrorCallback, PendingUnsubscribeResult, ResumePlan, SubscriptionHandleImpl,
+        SubscriptionManager,
     },
     websocket::{WsConnection, WsParams},
     Event, ReducerEvent, Status,
     __codegen::InternalError,
 };
 use bytes::Bytes;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use futures_channel::mpsc;
 use http::Uri;
+use rand::Rng;
 use spacetimedb_client_api_messages::websocket as ws;
 use spacetimedb_client_api_messages::websocket::{BsatnFormat, CallReducerFlags, Compression};
 use spacetimedb_lib::{bsatn, ser::Serialize, ConnectionId, Identity};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{atomic::AtomicU32, Arc, Mutex as StdMutex, OnceLock},
+    time::Duration,
 };
 use tokio::{
     runtime::{self, Runtime},
@@ -88,6 +92,12 @@ pub struct DbContextImpl<M: SpacetimeModule> {
     ///
     /// This may be none if we have not yet received the [`ws::IdentityToken`] message.
     connection_id: SharedCell<Option<ConnectionId>>,
+
+    /// `Some` if the connection was built with [`DbConnectionBuilder::with_reconnect`].
+    reconnect: Option<Arc<ReconnectState>>,
+
+    /// `Some` if the connection was built with [`DbConnectionBuilder::with_cache_storage`].
+    cache_storage: Option<Arc<BoundCacheStorage>>,
 }
 
 impl<M: SpacetimeModule> Clone for DbContextImpl<M> {
@@ -105,6 +115,8 @@ impl<M: SpacetimeModule> Clone for DbContextImpl<M> {
             pending_mutations_recv: Arc::clone(&self.pending_mutations_recv),
             identity: Arc::clone(&self.identity),
             connection_id: Arc::clone(&self.connection_id),
+            reconnect: self.reconnect.clone(),
+            cache_storage: self.cache_storage.clone(),
         }
     }
 }
@@ -147,6 +159,11 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     }
                     *conn_id_store = Some(conn_id);
                 }
+                if let Some(reconnect) = &self.reconnect {
+                    // Reconnect with the token we were issued,
+                    // so that an anonymous connection keeps its `Identity`.
+                    *reconnect.token.lock().unwrap() = Some(token.to_string());
+                }
                 let mut inner = self.inner.lock().unwrap();
                 if let Some(on_connect) = inner.on_connect.take() {
                     let ctx = <M::DbConnection as DbConnection>::new(self.clone());
@@ -165,6 +182,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     inner.subscriptions.legacy_subscription_applied(&sub_event_ctx, sub_id);
                     Event::SubscribeApplied
                 });
+                self.note_cache_update(true);
                 Ok(())
             }
 
@@ -179,6 +197,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     }
                     event
                 });
+                self.note_cache_update(false);
                 Ok(())
             }
 
@@ -201,6 +220,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     inner.subscriptions.subscription_applied(&sub_event_ctx, query_id);
                     Event::SubscribeApplied
                 });
+                self.note_cache_update(true);
                 Ok(())
             }
             ParsedMessage::UnsubscribeApplied {
@@ -212,6 +232,72 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     inner.subscriptions.unsubscribe_applied(&sub_event_ctx, query_id);
                     Event::UnsubscribeApplied
                 });
+                self.note_cache_update(true);
+                Ok(())
+            }
+            // Resumed after reconnecting:
+            // replace the rows in the client cache with the fresh rows of the resubscribed queries,
+            // invoking row callbacks only for rows which changed while we were disconnected,
+            // then apply the restored subscriptions which had yet to be resumed,
+            // and end the subscriptions whose unsubscribe was in flight.
+            ParsedMessage::Resumed {
+                initial_updates,
+                applied,
+                ended,
+            } => {
+                let update = self.cache.lock().unwrap().resumption_update(initial_updates);
+                let update = M::DbUpdate::parse_update(update)?;
+                self.apply_update(update, |inner| {
+                    let sub_event_ctx = self.make_event_ctx(());
+                    for query_id in applied {
+                        inner.subscriptions.subscription_applied(&sub_event_ctx, query_id);
+                    }
+                    for query_id in ended {
+                        inner.subscriptions.unsubscribe_applied(&sub_event_ctx, query_id);
+                    }
+                    Event::SubscribeApplied
+                });
+                self.note_cache_update(true);
+                Ok(())
+            }
+            // The subscriptions restored from `cache_storage` were resumed:
+            // apply the changes to their rows since the snapshot was saved,
+            // or, if the server could not send just those, replace the restored rows with the fresh ones,
+            // invoking row callbacks only for rows which changed.
+            // Then unsubscribe from those which no subscription made by the user has taken over.
+            ParsedMessage::SubscriptionsResumed { is_delta, updates } => {
+                let (query_ids, updates): (Vec<_>, Vec<_>) = updates.into_iter().unzip();
+                let update = if is_delta {
+                    ws::DatabaseUpdate {
+                        tables: updates.into_iter().flat_map(|update| update.tables).collect(),
+                    }
+                } else {
+                    // The restored rows are the only ones in the client cache,
+                    // as the server answers the `ResumeSubscriptions` we sent first before any other subscription.
+                    self.cache.lock().unwrap().resumption_update(updates)
+                };
+                let update = M::DbUpdate::parse_update(update)?;
+                self.apply_update(update, |inner| {
+                    let sub_event_ctx = self.make_event_ctx(());
+                    for query_id in query_ids {
+                        inner.subscriptions.subscription_applied(&sub_event_ctx, query_id);
+                    }
+                    inner.subscriptions.end_unadopted_restored_subscriptions();
+                    Event::SubscribeApplied
+                });
+                self.note_cache_update(true);
+                Ok(())
+            }
+            // The client cache reflects every transaction up to `caught_up`:
+            // a good time to save it.
+            ParsedMessage::CaughtUp(caught_up) => {
+                if self
+                    .cache_storage
+                    .as_ref()
+                    .is_some_and(|cache_storage| cache_storage.note_caught_up(caught_up))
+                {
+                    self.save_cache();
+                }
                 Ok(())
             }
             ParsedMessage::SubscriptionError { query_id, error } => {
@@ -222,8 +308,12 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                     self.invoke_disconnected(&ctx);
                     return Ok(());
                 };
-                let mut inner = self.inner.lock().unwrap();
-                inner.subscriptions.subscription_error(&ctx, query_id);
+                self.inner
+                    .lock()
+                    .unwrap()
+                    .subscriptions
+                    .subscription_error(&ctx, query_id);
+                self.note_cache_update(true);
                 Ok(())
             }
         };
@@ -231,6 +321,32 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
         res
     }
 
+    /// If this connection has a [`CacheStorage`], record that a message has changed the client cache,
+    /// and whether it applied or ended a subscription.
+    fn note_cache_update(&self, subscriptions_changed: bool) {
+        if let Some(cache_storage) = &self.cache_storage {
+            cache_storage.note_update(subscriptions_changed);
+        }
+    }
+
+    /// If this connection has a [`CacheStorage`], save the client cache to it,
+    /// together with the subscriptions whose rows it holds.
+    fn save_cache(&self) {
+        let Some(cache_storage) = &self.cache_storage else {
+            return;
+        };
+        let subscriptions = self
+            .inner
+            .lock()
+            .unwrap()
+            .subscriptions
+            .cached_subscriptions(cache_storage.caught_up());
+        match subscriptions {
+            Some(subscriptions) => cache_storage.save(&*self.cache.lock().unwrap(), subscriptions),
+            None => log::debug!("Not saving the client cache, as it holds the rows of `subscribe_to_all_tables`"),
+        }
+    }
+
     fn apply_update(
         &self,
         update: M::DbUpdate,
@@ -266,6 +382,68 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
 
         // Call the `on_disconnect` method for all subscriptions.
         inner.subscriptions.on_disconnect(ctx);
+        drop(inner);
+
+        self.save_cache();
+    }
+
+    /// Handle the channel of parsed WebSocket messages closing.
+    ///
+    /// If a reconnect has begun, return `Ok(())`.
+    /// Otherwise, invoke the on-disconnect callback and return [`crate::Error::Disconnected`].
+    fn handle_closed(&self) -> crate::Result<()> {
+        if self.begin_reconnect() {
+            return Ok(());
+        }
+        let disconnect_ctx = self.make_event_ctx(None);
+        self.invoke_disconnected(&disconnect_ctx);
+        Err(crate::Error::Disconnected)
+    }
+
+    /// If this connection has a [`ReconnectPolicy`] and the WebSocket closed without the user calling
+    /// [`Self::disconnect`], spawn a [`reconnect_loop`] and return `true`.
+    ///
+    /// The reconnect loop feeds a fresh channel which replaces `self.recv`.
+    /// Until it succeeds, outgoing messages are queued rather than sent,
+    /// so [`Self::is_active`] remains `true`.
+    fn begin_reconnect(&self) -> bool {
+        let Some(reconnect) = &self.reconnect else {
+            return false;
+        };
+        // Only resume connections which were actually established.
+        // Otherwise, e.g. a rejected token would have us retry forever.
+        if self.try_identity().is_none() {
+            return false;
+        }
+
+        let inner = self.inner.lock().unwrap();
+        let mut send_chan = self.send_chan.lock().unwrap();
+        if send_chan.is_none() {
+            // The user called `disconnect`, so the closure was expected.
+            return false;
+        }
+        let plan = inner.subscriptions.resume_plan();
+        drop(inner);
+
+        let (outgoing_send, outgoing_recv) = mpsc::unbounded();
+        *send_chan = Some(outgoing_send);
+        let (parsed_send, parsed_recv) = mpsc::unbounded();
+        *self
+            .recv
+            .try_lock()
+            .expect("Parallel calls to `advance_one_message` and its siblings are unsupported") = parsed_recv;
+
+        log::info!("Connection closed unexpectedly; reconnecting");
+        self.runtime.spawn(reconnect_loop::<M>(
+            Arc::clone(reconnect),
+            self.try_connection_id(),
+            plan,
+            outgoing_recv,
+            parsed_send,
+            Arc::clone(&self.send_chan),
+            self.runtime.clone(),
+        ));
+        true
     }
 
     fn make_event_ctx<E, Ctx: AbstractEventContext<Module = M, Event = E>>(&self, event: E) -> Ctx {
@@ -295,7 +473,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
                 let mut inner = self.inner.lock().unwrap();
                 inner
                     .subscriptions
-                    .register_legacy_subscription(sub_id, on_applied, on_error);
+                    .register_legacy_subscription(sub_id, queries.clone(), on_applied, on_error);
                 self.send_chan
                     .lock()
                     .unwrap()
@@ -311,6 +489,14 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
             // and send the `Subscribe` WS message.
             PendingMutation::SubscribeMulti { query_id, handle } => {
                 let mut inner = self.inner.lock().unwrap();
+                // Take over a subscription restored from `cache_storage` with the same queries, if any,
+                // whose rows are already in the client cache.
+                if inner
+                    .subscriptions
+                    .adopt_restored_subscription(&self.make_event_ctx(()), &handle)
+                {
+                    return Ok(());
+                }
                 // Register the subscription, so we can handle related messages from the server.
                 inner.subscriptions.register_subscription(query_id, handle.clone());
                 if let Some(msg) = handle.start() {
@@ -491,12 +677,12 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
         // returns `Err(_)`. Similar behavior as `Iterator::next` and
         // `Stream::poll_next`. No comment on whether this is a good mental
         // model or not.
-        let res = match self.recv.blocking_lock().try_next() {
-            Ok(None) => {
-                let disconnect_ctx = self.make_event_ctx(None);
-                self.invoke_disconnected(&disconnect_ctx);
-                Err(crate::Error::Disconnected)
-            }
+        //
+        // Don't hold the `self.recv` lock while handling the message,
+        // as `Self::handle_closed` may replace the receiver.
+        let msg = self.recv.blocking_lock().try_next();
+        let res = match msg {
+            Ok(None) => self.handle_closed().map(|()| false),
             Err(_) => Ok(false),
             Ok(Some(msg)) => self.process_message(msg).map(|_| true),
         };
@@ -538,11 +724,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
     pub fn advance_one_message_blocking(&self) -> crate::Result<()> {
         match self.runtime.block_on(self.get_message()) {
             Message::Local(pending) => self.apply_mutation(pending),
-            Message::Ws(None) => {
-                let disconnect_ctx = self.make_event_ctx(None);
-                self.invoke_disconnected(&disconnect_ctx);
-                Err(crate::Error::Disconnected)
-            }
+            Message::Ws(None) => self.handle_closed(),
             Message::Ws(Some(msg)) => self.process_message(msg),
         }
     }
@@ -553,11 +735,7 @@ impl<M: SpacetimeModule> DbContextImpl<M> {
     pub async fn advance_one_message_async(&self) -> crate::Result<()> {
         match self.get_message().await {
             Message::Local(pending) => self.apply_mutation(pending),
-            Message::Ws(None) => {
-                let disconnect_ctx = self.make_event_ctx(None);
-                self.invoke_disconnected(&disconnect_ctx);
-                Err(crate::Error::Disconnected)
-            }
+            Message::Ws(None) => self.handle_closed(),
             Message::Ws(Some(msg)) => self.process_message(msg),
         }
     }
@@ -770,6 +948,71 @@ pub struct DbConnectionBuilder<M: SpacetimeModule> {
     on_disconnect: Option<OnDisconnectCallback<M>>,
 
     params: WsParams,
+
+    reconnect_policy: Option<ReconnectPolicy>,
+
+    cache_storage: Option<Box<dyn CacheStorage>>,
+}
+
+/// How a `DbConnection` reconnects after its WebSocket closes unexpectedly.
+///
+/// Pass one to [`DbConnectionBuilder::with_reconnect`].
+/// Attempt `n`, counting from zero, waits `initial_backoff * multiplier^n`, capped at `max_backoff`,
+/// less a random fraction of up to `jitter` of that wait.
+#[derive(Clone, Copy, Debug)]
+pub struct ReconnectPolicy {
+    /// The number of consecutive failed attempts after which to give up,
+    /// or `None` to keep trying forever.
+    pub max_attempts: Option<u32>,
+    /// How long to wait before the first attempt.
+    pub initial_backoff: Duration,
+    /// The longest to wait between attempts.
+    pub max_backoff: Duration,
+    /// The factor by which the wait grows after each failed attempt.
+    pub multiplier: f64,
+    /// The largest fraction of each wait, between 0 and 1, by which it is randomly shortened,
+    /// so that clients disconnected at the same time don't all reconnect at the same time.
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: Some(10),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// How long to wait before the attempt numbered `attempt`, counting from zero.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// [`Self::backoff`], shortened by a random fraction of up to `jitter`.
+    fn jittered_backoff(&self, attempt: u32, rng: &mut impl Rng) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        self.backoff(attempt).mul_f64(1.0 - jitter * rng.random::<f64>())
+    }
+}
+
+/// Everything needed to open a new WebSocket connection to the same database
+/// after the previous one closed unexpectedly.
+struct ReconnectState {
+    policy: ReconnectPolicy,
+    uri: Uri,
+    module_name: String,
+    params: WsParams,
+    /// The token to authenticate with,
+    /// updated from each [`ws::IdentityToken`] message.
+    token: StdMutex<Option<String>>,
 }
 
 /// This process's global connection ID, which will be attacked to all connections it makes.
@@ -817,6 +1060,8 @@ impl<M: SpacetimeModule> DbConnectionBuilder<M> {
             on_connect_error: None,
             on_disconnect: None,
             params: <_>::default(),
+            reconnect_policy: None,
+            cache_storage: None,
         }
     }
 
@@ -858,28 +1103,58 @@ but you must call one of them, or else the connection will never progress.
         let reducer_callbacks = ReducerCallbacks::default();
 
         let connection_id_override = get_connection_id_override();
+        let uri = self.uri.unwrap();
+        let module_name = self.module_name.unwrap();
+        // We can only resume persisted subscriptions with just the changes since they were saved
+        // if we know which transaction the client cache reflects.
+        let params = WsParams {
+            tx_offsets: self.cache_storage.is_some(),
+            ..self.params
+        };
         let ws_connection = tokio::task::block_in_place(|| {
             handle.block_on(WsConnection::connect(
-                self.uri.unwrap(),
-                self.module_name.as_ref().unwrap(),
+                uri.clone(),
+                &module_name,
                 self.token.as_deref(),
                 connection_id_override,
-                self.params,
+                params,
             ))
         })
         .map_err(|source| crate::Error::FailedToConnect {
             source: InternalError::new("Failed to initiate WebSocket connection").with_cause(source),
         })?;
 
+        let mut cache = ClientCache::default();
+        M::register_tables(&mut cache);
+        let cache_storage = self
+            .cache_storage
+            .map(|storage| Arc::new(BoundCacheStorage::new(storage, uri.to_string(), module_name.clone())));
+        let restored = cache_storage
+            .as_ref()
+            .map(|cache_storage| cache_storage.restore(&mut cache))
+            .unwrap_or_default();
+        let cache = Arc::new(StdMutex::new(cache));
+
+        let (pending_mutations_send, pending_mutations_recv) = mpsc::unbounded();
+        let mut subscriptions = SubscriptionManager::default();
+        let resume = subscriptions.register_restored_subscriptions(restored, &pending_mutations_send);
+
         let (_websocket_loop_handle, raw_msg_recv, raw_msg_send) = ws_connection.spawn_message_loop(&handle);
         let (_parse_loop_handle, parsed_recv_chan) = spawn_parse_loop::<M>(raw_msg_recv, &handle);
+        if let Some(resume) = resume {
+            // Send this before any other message,
+            // so that the server answers it before applying any other subscription.
+            raw_msg_send
+                .unbounded_send(ws::ClientMessage::ResumeSubscriptions(resume))
+                .expect("Unable to send resume message: WS sender loop has dropped its recv channel");
+        }
 
         let inner = Arc::new(StdMutex::new(DbContextImplInner {
             runtime,
 
             db_callbacks,
             reducer_callbacks,
-            subscriptions: SubscriptionManager::default(),
+            subscriptions,
 
             on_connect: self.on_connect,
             on_connect_error: self.on_connect_error,
@@ -887,12 +1162,18 @@ but you must call one of them, or else the connection will never progress.
             call_reducer_flags: <_>::default(),
         }));
 
-        let mut cache = ClientCache::default();
-        M::register_tables(&mut cache);
-        let cache = Arc::new(StdMutex::new(cache));
         let send_chan = Arc::new(StdMutex::new(Some(raw_msg_send)));
 
-        let (pending_mutations_send, pending_mutations_recv) = mpsc::unbounded();
+        let reconnect = self.reconnect_policy.map(|policy| {
+            Arc::new(ReconnectState {
+                policy,
+                uri,
+                module_name,
+                params,
+                token: StdMutex::new(self.token),
+            })
+        });
+
         let ctx_imp = DbContextImpl {
             runtime: handle,
             inner,
@@ -903,6 +1184,8 @@ but you must call one of them, or else the connection will never progress.
             pending_mutations_recv: Arc::new(TokioMutex::new(pending_mutations_recv)),
             identity: Arc::new(StdMutex::new(None)),
             connection_id: Arc::new(StdMutex::new(connection_id_override)),
+            reconnect,
+            cache_storage,
         };
 
         Ok(ctx_imp)
@@ -982,6 +1265,53 @@ but you must call one of them, or else the connection will never progress.
         self
     }
 
+    /// Reconnect according to `policy` when the connection closes unexpectedly.
+    ///
+    /// The new connection reuses this connection's `ConnectionId` and token, and so its `Identity`.
+    /// Once it is established, every live subscription is re-issued,
+    /// and the client cache is reconciled against the fresh rows,
+    /// so that row callbacks run only for rows inserted, deleted or updated while disconnected.
+    /// The [`Self::on_connect`] callback does not run again.
+    ///
+    /// While reconnecting, the connection remains active,
+    /// and reducer calls and new subscriptions are queued until it resumes.
+    /// If every attempt fails, the [`Self::on_disconnect`] callback runs.
+    ///
+    /// If this method is not called, the connection never reconnects.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// Persist the contents of the client cache to `storage`,
+    /// and restore them from `storage` when building the connection.
+    ///
+    /// Restored rows are resident in the client cache as soon as [`Self::build`] returns,
+    /// so an application can show them without waiting for its subscriptions to be applied.
+    /// The subscriptions they belong to are resumed as soon as the connection is established,
+    /// and the server sends just the changes to their rows since the snapshot was saved, if it still can,
+    /// or else all of their rows, which replace the restored rows.
+    /// Either way, row callbacks run only for rows which were inserted, deleted or updated since then.
+    ///
+    /// A subscription made with the same queries as a restored one,
+    /// before the connection is established or in the [`Self::on_connect`] callback,
+    /// takes over the restored subscription, and its on-applied callback runs once that is resumed.
+    /// Restored subscriptions which are not taken over are then unsubscribed, removing their rows.
+    ///
+    /// The client cache is saved whenever a subscription is applied or ended,
+    /// at most every few seconds while transactions change it, and whenever the connection closes.
+    /// Subscriptions made by [`crate::SubscriptionBuilder::subscribe_to_all_tables`] can't be resumed,
+    /// so a client cache which holds their rows is not saved.
+    ///
+    /// Rows are stored as BSATN, and so a snapshot saved by an older version of the module bindings
+    /// may be ignored if the module's schema has since changed.
+    ///
+    /// See the [`crate::cache_storage`] module for implementations of [`CacheStorage`].
+    pub fn with_cache_storage(mut self, storage: impl CacheStorage) -> Self {
+        self.cache_storage = Some(Box::new(storage));
+        self
+    }
+
     /// Register a callback to run when the connection is successfully initiated.
     ///
     /// The callback will receive three arguments:
@@ -1063,12 +1393,36 @@ fn enter_or_create_runtime() -> crate::Result<(Option<Runtime>, runtime::Handle)
 }
 
 enum ParsedMessage<M: SpacetimeModule> {
-    InitialSubscription { db_update: M::DbUpdate, sub_id: u32 },
+    InitialSubscription {
+        db_update: M::DbUpdate,
+        sub_id: u32,
+    },
     TransactionUpdate(Event<M::Reducer>, Option<M::DbUpdate>),
     IdentityToken(Identity, Box<str>, ConnectionId),
-    SubscribeApplied { query_id: u32, initial_update: M::DbUpdate },
-    UnsubscribeApplied { query_id: u32, initial_update: M::DbUpdate },
-    SubscriptionError { query_id: Option<u32>, error: String },
+    SubscribeApplied {
+        query_id: u32,
+        initial_update: M::DbUpdate,
+    },
+    UnsubscribeApplied {
+        query_id: u32,
+        initial_update: M::DbUpdate,
+    },
+    SubscriptionError {
+        query_id: Option<u32>,
+        error: String,
+    },
+    Resumed {
+        initial_updates: Vec<ws::DatabaseUpdate<BsatnFormat>>,
+        applied: Vec<u32>,
+        ended: Vec<u32>,
+    },
+    /// The rows of the subscriptions restored from a [`CacheStorage`], left unparsed,
+    /// as they may replace the restored rows.
+    SubscriptionsResumed {
+        is_delta: bool,
+        updates: Vec<(u32, ws::DatabaseUpdate<BsatnFormat>)>,
+    },
+    CaughtUp(ws::CaughtUp),
     Error(crate::Error),
 }
 
@@ -1084,7 +1438,7 @@ fn spawn_parse_loop<M: SpacetimeModule>(
 /// A loop which reads raw WS messages from `recv`, parses them into domain types,
 /// and pushes the [`ParsedMessage`]s into `send`.
 async fn parse_loop<M: SpacetimeModule>(
-    mut recv: mpsc::UnboundedReceiver<ws::ServerMessage<BsatnFormat>>,
+    mut recv: impl Stream<Item = ws::ServerMessage<BsatnFormat>> + Unpin,
     send: mpsc::UnboundedSender<ParsedMessage<M>>,
 ) {
     while let Some(msg) = recv.next().await {
@@ -1101,36 +1455,10 @@ async fn parse_loop<M: SpacetimeModule>(
                             .into(),
                     )
                 }),
-            ws::ServerMessage::TransactionUpdate(ws::TransactionUpdate {
-                status,
-                timestamp,
-                caller_identity,
-                caller_connection_id,
-                reducer_call,
-                energy_quanta_used,
-                ..
-            }) => match Status::parse_status_and_update::<M>(status) {
-                Err(e) => ParsedMessage::Error(
-                    InternalError::failed_parse("Status", "TransactionUpdate")
-                        .with_cause(e)
-                        .into(),
-                ),
-                Ok((status, db_update)) => {
-                    let event = M::Reducer::try_from(reducer_call)
-                        .map(|reducer| {
-                            Event::Reducer(ReducerEvent {
-                                caller_connection_id: caller_connection_id.none_if_zero(),
-                                caller_identity,
-                                energy_consumed: Some(energy_quanta_used.quanta),
-                                timestamp,
-                                reducer,
-                                status,
-                            })
-                        })
-                        .unwrap_or(Event::UnknownTransaction);
-                    ParsedMessage::TransactionUpdate(event, db_update)
-                }
-            },
+            ws::ServerMessage::TransactionUpdate(tx_update) => parse_transaction_update(tx_update, None),
+            ws::ServerMessage::ReducerResult(ws::ReducerResult { update, return_value }) => {
+                parse_transaction_update(update, Some(return_value))
+            }
             ws::ServerMessage::TransactionUpdateLight(ws::TransactionUpdateLight { update, request_id: _ }) => {
                 match M::DbUpdate::parse_update(update) {
                     Err(e) => ParsedMessage::Error(
@@ -1183,6 +1511,15 @@ async fn parse_loop<M: SpacetimeModule>(
                 query_id: e.query_id,
                 error: e.error.to_string(),
             },
+            ws::ServerMessage::SubscriptionsResumed(resumed) => ParsedMessage::SubscriptionsResumed {
+                is_delta: resumed.is_delta,
+                updates: resumed
+                    .subscriptions
+                    .into_iter()
+                    .map(|sub| (sub.query_id.id, sub.update))
+                    .collect(),
+            },
+            ws::ServerMessage::CaughtUp(caught_up) => ParsedMessage::CaughtUp(caught_up),
             ws::ServerMessage::SubscribeApplied(_) => unreachable!("Rust client SDK never sends `SubscribeSingle`, but received a `SubscribeApplied` from the host... huh?"),
             ws::ServerMessage::UnsubscribeApplied(_) => unreachable!("Rust client SDK never sends `UnsubscribeSingle`, but received a `UnsubscribeApplied` from the host... huh?")
         })
@@ -1190,6 +1527,196 @@ async fn parse_loop<M: SpacetimeModule>(
     }
 }
 
+/// Parse a [`ws::TransactionUpdate`] into a [`ParsedMessage::TransactionUpdate`],
+/// attaching the `return_value` of the reducer call, if the host sent one.
+fn parse_transaction_update<M: SpacetimeModule>(
+    ws::TransactionUpdate {
+        status,
+        timestamp,
+        caller_identity,
+        caller_connection_id,
+        reducer_call,
+        energy_quanta_used,
+        ..
+    }: ws::TransactionUpdate<BsatnFormat>,
+    return_value: Option<Bytes>,
+) -> ParsedMessage<M> {
+    match Status::parse_status_and_update::<M>(status) {
+        Err(e) => ParsedMessage::Error(
+            InternalError::failed_parse("Status", "TransactionUpdate")
+                .with_cause(e)
+                .into(),
+        ),
+        Ok((status, db_update)) => {
+            let event = M::Reducer::try_from(reducer_call)
+                .map(|reducer| {
+                    Event::Reducer(ReducerEvent {
+                        caller_connection_id: caller_connection_id.none_if_zero(),
+                        caller_identity,
+                        energy_consumed: Some(energy_quanta_used.quanta),
+                        timestamp,
+                        reducer,
+                        status,
+                        return_value,
+                    })
+                })
+                .unwrap_or(Event::UnknownTransaction);
+            ParsedMessage::TransactionUpdate(event, db_update)
+        }
+    }
+}
+
+/// Reconnect to the database described by `reconnect`, backing off between attempts,
+/// re-issue the subscriptions in `plan`,
+/// then parse messages from the new connection into `parsed_send`.
+///
+/// The first message sent to `parsed_send` is a [`ParsedMessage::Resumed`]
+/// carrying the initial rows of the resubscribed queries.
+/// Messages queued in `outgoing` while reconnecting are flushed to the new connection,
+/// which then replaces `outgoing` in `send_chan`.
+///
+/// If every attempt fails, or the user disconnects meanwhile,
+/// this clears `send_chan` and drops `parsed_send`, which results in the on-disconnect callback running.
+async fn reconnect_loop<M: SpacetimeModule>(
+    reconnect: Arc<ReconnectState>,
+    connection_id: Option<ConnectionId>,
+    plan: ResumePlan,
+    mut outgoing: mpsc::UnboundedReceiver<ws::ClientMessage<Bytes>>,
+    parsed_send: mpsc::UnboundedSender<ParsedMessage<M>>,
+    send_chan: SharedCell<Option<mpsc::UnboundedSender<ws::ClientMessage<Bytes>>>>,
+    handle: runtime::Handle,
+) {
+    let policy = reconnect.policy;
+    let mut attempt = 0;
+    let (raw_send, raw_recv, initial_updates, buffered) = loop {
+        if policy.max_attempts.is_some_and(|max| attempt >= max) {
+            log::error!("Failed to reconnect after {attempt} attempts");
+            *send_chan.lock().unwrap() = None;
+            return;
+        }
+        // Don't hold the thread-local RNG across the `await`, as it isn't `Send`.
+        let backoff = policy.jittered_backoff(attempt, &mut rand::rng());
+        tokio::time::sleep(backoff).await;
+        attempt += 1;
+
+        if send_chan.lock().unwrap().is_none() {
+            // The user called `disconnect` while we were waiting.
+            return;
+        }
+
+        let token = reconnect.token.lock().unwrap().clone();
+        let ws_connection = match WsConnection::connect(
+            reconnect.uri.clone(),
+            &reconnect.module_name,
+            token.as_deref(),
+            connection_id,
+            reconnect.params,
+        )
+        .await
+        {
+            Ok(ws_connection) => ws_connection,
+            Err(e) => {
+                log::warn!("Reconnect attempt {attempt} failed: {e}");
+                continue;
+            }
+        };
+        let (_websocket_loop_handle, raw_recv, raw_send) = ws_connection.spawn_message_loop(&handle);
+        match resume_subscriptions(&plan, raw_recv, &raw_send).await {
+            Some((raw_recv, initial_updates, buffered)) => break (raw_send, raw_recv, initial_updates, buffered),
+            None => log::warn!("Connection closed while resuming subscriptions on reconnect attempt {attempt}"),
+        }
+    };
+    log::info!("Reconnected after {attempt} attempts");
+
+    let resumed = ParsedMessage::Resumed {
+        initial_updates,
+        applied: plan.applied,
+        ended: plan.ended,
+    };
+    if parsed_send.unbounded_send(resumed).is_err() {
+        // The connection has been dropped.
+        return;
+    }
+
+    {
+        let mut send_chan = send_chan.lock().unwrap();
+        if send_chan.is_none() {
+            // The user called `disconnect` while we were resuming.
+            // Dropping `raw_send` closes the new WebSocket.
+            return;
+        }
+        outgoing.close();
+        while let Ok(Some(msg)) = outgoing.try_next() {
+            // If the new WebSocket has already closed, we'll find out from `raw_recv`.
+            let _ = raw_send.unbounded_send(msg);
+        }
+        *send_chan = Some(raw_send);
+    }
+
+    parse_loop(futures::stream::iter(buffered).chain(raw_recv), parsed_send).await;
+}
+
+/// Send the subscriptions in `plan` over a new connection,
+/// then read from `raw_recv` until the server has answered every resubscribed query.
+///
+/// Returns the initial rows of the resubscribed queries,
+/// together with all other messages received meanwhile, which must be processed after those rows.
+/// Returns `None` if the connection closes first.
+async fn resume_subscriptions(
+    plan: &ResumePlan,
+    mut raw_recv: mpsc::UnboundedReceiver<ws::ServerMessage<BsatnFormat>>,
+    raw_send: &mpsc::UnboundedSender<ws::ClientMessage<Bytes>>,
+) -> Option<(
+    mpsc::UnboundedReceiver<ws::ServerMessage<BsatnFormat>>,
+    Vec<ws::DatabaseUpdate<BsatnFormat>>,
+    Vec<ws::ServerMessage<BsatnFormat>>,
+)> {
+    let subscribe_multis = plan.resubscribe.iter().chain(&plan.resend).cloned();
+    let subscribes = plan.legacy_resubscribe.iter().chain(&plan.legacy_resend).cloned();
+    for msg in subscribe_multis
+        .map(ws::ClientMessage::SubscribeMulti)
+        .chain(subscribes.map(ws::ClientMessage::Subscribe))
+    {
+        raw_send.unbounded_send(msg).ok()?;
+    }
+
+    let mut awaiting_queries = plan
+        .resubscribe
+        .iter()
+        .map(|msg| msg.query_id.id)
+        .collect::<HashSet<_>>();
+    let mut awaiting_legacy = plan.legacy_resubscribe.as_ref().map(|msg| msg.request_id);
+    let mut initial_updates = Vec::new();
+    let mut buffered = Vec::new();
+    while !awaiting_queries.is_empty() || awaiting_legacy.is_some() {
+        match raw_recv.next().await? {
+            ws::ServerMessage::SubscribeMultiApplied(applied) if awaiting_queries.remove(&applied.query_id.id) => {
+                initial_updates.push(applied.update);
+            }
+            ws::ServerMessage::InitialSubscription(sub) if awaiting_legacy == Some(sub.request_id) => {
+                awaiting_legacy = None;
+                initial_updates.push(sub.database_update);
+            }
+            ws::ServerMessage::SubscriptionError(error) => {
+                // A failed query's rows are absent from `initial_updates`, so the reconciliation deletes them.
+                // An error without a query ID is fatal, and will disconnect us once processed.
+                match error.query_id {
+                    Some(query_id) => {
+                        awaiting_queries.remove(&query_id);
+                    }
+                    None => {
+                        awaiting_queries.clear();
+                        awaiting_legacy = None;
+                    }
+                }
+                buffered.push(ws::ServerMessage::SubscriptionError(error));
+            }
+            other => buffered.push(other),
+        }
+    }
+    Some((raw_recv, initial_updates, buffered))
+}
+
 /// Operations a user can make to a `DbContext` which must be postponed
 pub(crate) enum PendingMutation<M: SpacetimeModule> {
     // TODO: Rename to `SubscribeLegacy`, or replace with `SubscribeToAllTables`.
@@ -1275,3 +1802,59 @@ static NEXT_SUBSCRIPTION_ID: AtomicU32 = AtomicU32::new(1);
 pub(crate) fn next_subscription_id() -> u32 {
     NEXT_SUBSCRIPTION_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn backoff_grows_geometrically_up_to_max() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(5), Duration::from_secs(16));
+        assert_eq!(policy.backoff(6), Duration::from_secs(30));
+        // Overflowing the wait falls back to the maximum, rather than panicking.
+        assert_eq!(policy.backoff(u32::MAX), policy.max_backoff);
+
+        let constant = ReconnectPolicy {
+            multiplier: 1.0,
+            ..policy
+        };
+        assert_eq!(constant.backoff(0), constant.backoff(100));
+    }
+
+    #[test]
+    fn jittered_backoff_stays_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for jitter in [0.0, 0.2, 1.0] {
+            let policy = ReconnectPolicy {
+                jitter,
+                ..ReconnectPolicy::default()
+            };
+            for attempt in 0..10 {
+                let max = policy.backoff(attempt);
+                let min = max.mul_f64(1.0 - jitter);
+                for _ in 0..100 {
+                    let backoff = policy.jittered_backoff(attempt, &mut rng);
+                    assert!(min <= backoff && backoff <= max, "{backoff:?} not in {min:?}..={max:?}");
+                }
+            }
+        }
+
+        // Out-of-range jitter is clamped, so the wait is never negative or longer than without jitter.
+        let policy = ReconnectPolicy {
+            jitter: 2.0,
+            ..ReconnectPolicy::default()
+        };
+        for _ in 0..100 {
+            assert!(policy.jittered_backoff(3, &mut rng) <= policy.backoff(3));
+        }
+        let policy = ReconnectPolicy {
+            jitter: -1.0,
+            ..ReconnectPolicy::default()
+        };
+        assert_eq!(policy.jittered_backoff(3, &mut rng), policy.backoff(3));
+    }
+}
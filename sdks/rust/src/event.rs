@@ -10,9 +10,12 @@
 //! You can inspect its `event` field
 //! to determine what change in your connection's state caused the callback to run.
 
+use crate::error::InternalError;
 use crate::spacetime_module::{DbUpdate as _, SpacetimeModule};
+use bytes::Bytes;
 use spacetimedb_client_api_messages::websocket as ws;
-use spacetimedb_lib::{ConnectionId, Identity, Timestamp};
+use spacetimedb_lib::de::DeserializeOwned;
+use spacetimedb_lib::{bsatn, ConnectionId, Identity, Timestamp};
 
 #[non_exhaustive]
 #[derive(Debug, Clone)]
@@ -81,6 +84,40 @@ pub struct ReducerEvent<R> {
 
     /// The `Reducer` enum defined by the `module_bindings`, which encodes which reducer ran and its arguments.
     pub reducer: R,
+
+    /// For reducers which declare return types and were called by this connection,
+    /// the BSATN-encoded value the reducer returned, if it committed,
+    /// or the BSATN-encoded error value it failed with, if it failed.
+    ///
+    /// The host sends return values only to the caller, so this is `None` for other clients' calls.
+    ///
+    /// The `module_bindings` define a function `{reducer}_result` for each such reducer,
+    /// which decodes this value with the reducer's return types.
+    pub return_value: Option<Bytes>,
+}
+
+impl<R> ReducerEvent<R> {
+    /// Decodes the value returned by the reducer as a `T`, if it committed,
+    /// or the error value it failed with as an `E`, if it failed.
+    ///
+    /// Returns `None` if the reducer does not declare return types,
+    /// or if it did not run to completion.
+    pub fn decode_return_value<T: DeserializeOwned, E: DeserializeOwned>(&self) -> Option<crate::Result<Result<T, E>>> {
+        fn decode<T: DeserializeOwned>(value: &[u8]) -> crate::Result<T> {
+            bsatn::from_slice(value).map_err(|source| {
+                InternalError::failed_parse(std::any::type_name::<T>(), "ReducerEvent")
+                    .with_cause(source)
+                    .into()
+            })
+        }
+
+        let value = self.return_value.as_deref()?;
+        match self.status {
+            Status::Committed => Some(decode(value).map(Ok)),
+            Status::Failed(_) => Some(decode(value).map(Err)),
+            Status::OutOfEnergy => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -19,13 +19,14 @@ mod spacetime_module;
 mod subscription;
 mod websocket;
 
+pub mod cache_storage;
 pub mod credentials;
 pub mod db_context;
 pub mod error;
 pub mod event;
 pub mod table;
 
-pub use db_connection::DbConnectionBuilder;
+pub use db_connection::{DbConnectionBuilder, ReconnectPolicy};
 pub use db_context::DbContext;
 pub use error::{Error, Result};
 pub use event::{Event, ReducerEvent, Status};
@@ -49,7 +50,9 @@ pub mod __codegen {
     pub use spacetimedb_sats as __sats;
 
     pub use crate::callbacks::{CallbackId, DbCallbacks};
-    pub use crate::client_cache::{ClientCache, TableAppliedDiff, TableHandle, UniqueConstraintHandle};
+    pub use crate::client_cache::{
+        BTreeIndexHandle, ClientCache, TableAppliedDiff, TableHandle, UniqueConstraintHandle,
+    };
     pub use crate::db_connection::DbContextImpl;
     pub use crate::error::{Error, InternalError, Result};
     pub use crate::spacetime_module::{
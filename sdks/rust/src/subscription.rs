@@ -4,6 +4,7 @@
 
 use crate::spacetime_module::AbstractEventContext;
 use crate::{
+    cache_storage::CachedSubscription,
     db_connection::{next_request_id, next_subscription_id, DbContextImpl, PendingMutation},
     spacetime_module::{SpacetimeModule, SubscriptionHandle},
 };
@@ -50,8 +51,9 @@ impl<M: SpacetimeModule> SubscriptionManager<M> {
     pub(crate) fn on_disconnect(&mut self, _ctx: &M::ErrorContext) {
         // We need to clear all the subscriptions.
         // TODO: is this correct? We don't remove them from the client cache,
-        // we may want to resume them in the future if we impl reconnecting,
         // and users can already register on-disconnect callbacks which will run in this case.
+        // Connections built with a `ReconnectPolicy` don't get here on a transient failure;
+        // they resume their subscriptions via `Self::resume_plan` instead.
 
         // NOTE(cloutiertyler)
         // This function previously invoke `on_error` for all subscriptions.
@@ -67,11 +69,142 @@ impl<M: SpacetimeModule> SubscriptionManager<M> {
         // For now, we will just do nothing when a subscription ends normally.
     }
 
+    /// Collect the subscriptions to re-issue on a fresh connection
+    /// after the previous one closed unexpectedly.
+    pub(crate) fn resume_plan(&self) -> ResumePlan {
+        let mut plan = ResumePlan::default();
+        for (&query_id, sub) in &self.new_subscriptions {
+            match sub.resume() {
+                ResumeAction::Resubscribe(msg) => plan.resubscribe.push(msg),
+                ResumeAction::ResubscribeRestored(msg) => {
+                    plan.resubscribe.push(msg);
+                    plan.applied.push(query_id);
+                }
+                ResumeAction::Resend(msg) => plan.resend.push(msg),
+                ResumeAction::End => plan.ended.push(query_id),
+                ResumeAction::Skip => {}
+            }
+        }
+        // Each legacy `Subscribe` replaces the previous one on the server,
+        // so only the most recent one is still live.
+        if let Some((&sub_id, sub)) = self.legacy_subscriptions.iter().max_by_key(|(sub_id, _)| **sub_id) {
+            let msg = ws::Subscribe {
+                query_strings: sub.queries.clone(),
+                request_id: sub_id,
+            };
+            if sub.is_applied {
+                plan.legacy_resubscribe = Some(msg);
+            } else {
+                plan.legacy_resend = Some(msg);
+            }
+        }
+        plan
+    }
+
+    /// Register the subscriptions whose rows were restored from a [`crate::cache_storage::CacheStorage`],
+    /// as if they had already been sent to the server,
+    /// and return the message which resumes them, if there are any.
+    ///
+    /// Until the server answers, a subscription made by the user with the same queries
+    /// takes over a restored one rather than being sent to the server;
+    /// see [`Self::adopt_restored_subscription`].
+    pub(crate) fn register_restored_subscriptions(
+        &mut self,
+        restored: Vec<CachedSubscription>,
+        pending_mutation_sender: &mpsc::UnboundedSender<PendingMutation<M>>,
+    ) -> Option<ws::ResumeSubscriptions> {
+        // The server can only send the changes since a single transaction.
+        let since = restored
+            .iter()
+            .map(|sub| sub.since)
+            .reduce(|a, b| a.filter(|_| a == b))
+            .flatten();
+        let subscriptions = restored
+            .into_iter()
+            .map(|sub| {
+                let query_id = next_subscription_id();
+                let handle = SubscriptionHandleImpl::new(SubscriptionState::restored(
+                    query_id,
+                    &sub,
+                    pending_mutation_sender.clone(),
+                ));
+                self.register_subscription(query_id, handle);
+                ws::SubscribeMulti {
+                    query_id: ws::QueryId::new(query_id),
+                    query_strings: sub.query_strings.clone(),
+                    request_id: next_request_id(),
+                }
+            })
+            .collect::<Box<[_]>>();
+        (!subscriptions.is_empty()).then(|| ws::ResumeSubscriptions {
+            request_id: next_request_id(),
+            since,
+            subscriptions,
+        })
+    }
+
+    /// If a subscription restored from a [`crate::cache_storage::CacheStorage`] has the same queries as `handle`,
+    /// and no other subscription made by the user has taken it over, hand it over to `handle` and return `true`.
+    ///
+    /// `handle` then need not be sent to the server, whose rows the client cache already holds.
+    /// If the restored subscription has already been resumed, `handle`'s on-applied callback runs at once.
+    pub(crate) fn adopt_restored_subscription(
+        &mut self,
+        ctx: &M::SubscriptionEventContext,
+        handle: &SubscriptionHandleImpl<M>,
+    ) -> bool {
+        let Some((&query_id, restored)) = self
+            .new_subscriptions
+            .iter()
+            .find(|(_, restored)| restored.is_adoptable_by(handle))
+        else {
+            return false;
+        };
+        let was_applied = handle.adopt(restored);
+        self.new_subscriptions.insert(query_id, handle.clone());
+        if was_applied {
+            self.subscription_applied(ctx, query_id);
+        }
+        true
+    }
+
+    /// Unsubscribe from the subscriptions restored from a [`crate::cache_storage::CacheStorage`]
+    /// which no subscription made by the user has taken over,
+    /// so that their rows are removed from the client cache.
+    pub(crate) fn end_unadopted_restored_subscriptions(&self) {
+        for sub in self.new_subscriptions.values() {
+            if sub.is_unadopted() {
+                // The unsubscribe is queued as a `PendingMutation`, and can't fail as nobody else has a handle.
+                let _ = sub.clone().unsubscribe_then(None);
+            }
+        }
+    }
+
+    /// The subscriptions whose rows are in the client cache, to save alongside those rows.
+    ///
+    /// Subscriptions applied on this connection reflect `caught_up`,
+    /// while restored subscriptions yet to be resumed still reflect the transaction they were saved at.
+    ///
+    /// Returns `None` if the client cache holds the rows of a legacy subscription,
+    /// which can't be resumed, and so must not be saved.
+    pub(crate) fn cached_subscriptions(&self, caught_up: Option<ws::CaughtUp>) -> Option<Vec<CachedSubscription>> {
+        if self.legacy_subscriptions.values().any(|sub| sub.is_applied) {
+            return None;
+        }
+        Some(
+            self.new_subscriptions
+                .values()
+                .filter_map(|sub| sub.cached(caught_up))
+                .collect(),
+        )
+    }
+
     /// Register a new subscription. This does not send the subscription to the server.
     /// Rather, it makes the subscription available for the next `apply_subscriptions` call.
     pub(crate) fn register_legacy_subscription(
         &mut self,
         sub_id: u32,
+        queries: Box<[Box<str>]>,
         on_applied: Option<OnAppliedCallback<M>>,
         on_error: Option<OnErrorCallback<M>>,
     ) {
@@ -79,6 +212,7 @@ impl<M: SpacetimeModule> SubscriptionManager<M> {
             .try_insert(
                 sub_id,
                 SubscribedQuery {
+                    queries,
                     on_applied,
                     on_error,
                     is_applied: false,
@@ -168,7 +302,36 @@ impl<M: SpacetimeModule> SubscriptionManager<M> {
     }
 }
 
+/// The subscriptions to re-issue when resuming after an unexpected disconnect,
+/// as computed by [`SubscriptionManager::resume_plan`].
+#[derive(Default)]
+pub(crate) struct ResumePlan {
+    /// Applied subscriptions, whose fresh initial rows will be reconciled against the client cache.
+    pub(crate) resubscribe: Vec<ws::SubscribeMulti>,
+    /// Subscriptions restored from a [`crate::cache_storage::CacheStorage`] which had yet to be resumed,
+    /// and so are also in `resubscribe`, and are applied once the connection resumes.
+    pub(crate) applied: Vec<u32>,
+    /// Subscriptions which were sent but not yet applied, and so are simply sent again.
+    pub(crate) resend: Vec<ws::SubscribeMulti>,
+    /// The live legacy subscription, if it had been applied.
+    pub(crate) legacy_resubscribe: Option<ws::Subscribe>,
+    /// The live legacy subscription, if it had not yet been applied.
+    pub(crate) legacy_resend: Option<ws::Subscribe>,
+    /// Subscriptions with an unsubscribe in flight, which end once the connection resumes.
+    pub(crate) ended: Vec<u32>,
+}
+
+/// What to do with a single subscription when resuming after an unexpected disconnect.
+enum ResumeAction {
+    Resubscribe(ws::SubscribeMulti),
+    ResubscribeRestored(ws::SubscribeMulti),
+    Resend(ws::SubscribeMulti),
+    End,
+    Skip,
+}
+
 struct SubscribedQuery<M: SpacetimeModule> {
+    queries: Box<[Box<str>]>,
     on_applied: Option<OnAppliedCallback<M>>,
     #[allow(unused)]
     on_error: Option<OnErrorCallback<M>>,
@@ -328,6 +491,15 @@ enum SubscriptionServerState {
     Error,   // There was an error that ended the subscription.
 }
 
+/// Whether a subscription restored from a [`crate::cache_storage::CacheStorage`] has been taken over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Restored {
+    /// No subscription made by the user has taken it over yet.
+    Unadopted,
+    /// A subscription made by the user with the same queries has taken it over.
+    Adopted,
+}
+
 /// We track the state of a subscription here.
 /// A reference to this is held by the `SubscriptionHandle` that clients use to unsubscribe,
 /// and by the `SubscriptionManager` that handles updates from the server.
@@ -336,6 +508,10 @@ pub(crate) struct SubscriptionState<M: SpacetimeModule> {
     query_sql: Box<[Box<str>]>,
     unsubscribe_called: bool,
     status: SubscriptionServerState,
+    /// `Some` if the subscription's rows were restored from a [`crate::cache_storage::CacheStorage`].
+    restored: Option<Restored>,
+    /// For a restored subscription, the transaction its rows reflected when saved.
+    restored_since: Option<ws::CaughtUp>,
     on_applied: Option<OnAppliedCallback<M>>,
     on_error: Option<OnErrorCallback<M>>,
     on_ended: Option<OnEndedCallback<M>>,
@@ -357,6 +533,8 @@ impl<M: SpacetimeModule> SubscriptionState<M> {
             query_sql,
             unsubscribe_called: false,
             status: SubscriptionServerState::Pending,
+            restored: None,
+            restored_since: None,
             on_applied,
             on_error,
             on_ended: None,
@@ -364,6 +542,67 @@ impl<M: SpacetimeModule> SubscriptionState<M> {
         }
     }
 
+    /// A subscription whose rows were restored from a [`crate::cache_storage::CacheStorage`],
+    /// and which is about to be resumed.
+    fn restored(
+        query_id: u32,
+        restored: &CachedSubscription,
+        pending_mutation_sender: mpsc::UnboundedSender<PendingMutation<M>>,
+    ) -> Self {
+        Self {
+            status: SubscriptionServerState::Sent,
+            restored: Some(Restored::Unadopted),
+            restored_since: restored.since,
+            ..Self::new(
+                query_id,
+                restored.query_strings.clone(),
+                pending_mutation_sender,
+                None,
+                None,
+            )
+        }
+    }
+
+    /// Take over the `restored` subscription, which has the same queries as `self`.
+    ///
+    /// Returns `true` if `restored` has already been resumed,
+    /// in which case the caller must mark `self` applied.
+    fn adopt(&mut self, restored: &mut SubscriptionState<M>) -> bool {
+        self.query_id = restored.query_id;
+        self.status = SubscriptionServerState::Sent;
+        self.restored = Some(Restored::Adopted);
+        self.restored_since = restored.restored_since;
+        restored.restored = Some(Restored::Adopted);
+        restored.status == SubscriptionServerState::Applied
+    }
+
+    /// Whether `self` is a restored subscription which `other`, made by the user, can take over.
+    fn is_adoptable_by(&self, other: &SubscriptionState<M>) -> bool {
+        self.is_unadopted()
+            && self.query_sql == other.query_sql
+            && other.status == SubscriptionServerState::Pending
+            && !other.unsubscribe_called
+    }
+
+    /// Whether this is a live restored subscription which no subscription made by the user has taken over.
+    fn is_unadopted(&self) -> bool {
+        self.restored == Some(Restored::Unadopted) && !self.unsubscribe_called && !self.is_ended()
+    }
+
+    /// This subscription as saved alongside its rows, if they are in the client cache.
+    fn cached(&self, caught_up: Option<ws::CaughtUp>) -> Option<CachedSubscription> {
+        let since = match self.status {
+            SubscriptionServerState::Applied => caught_up,
+            // The rows restored for this subscription have yet to be updated.
+            SubscriptionServerState::Sent if self.restored.is_some() => self.restored_since,
+            _ => return None,
+        };
+        Some(CachedSubscription {
+            query_strings: self.query_sql.clone(),
+            since,
+        })
+    }
+
     /// Start the subscription.
     /// This updates the state in the handle, and returns the message to be sent to the server.
     /// The caller is responsible for sending the message to the server.
@@ -387,6 +626,35 @@ impl<M: SpacetimeModule> SubscriptionState<M> {
         })
     }
 
+    /// Decide how to re-issue this subscription on a fresh connection.
+    ///
+    /// The server knows nothing of the new connection's subscriptions,
+    /// so `Sent` and `Applied` subscriptions are re-sent under their existing query ID.
+    /// Those with an unsubscribe in flight are not re-sent, and instead end once the connection resumes,
+    /// as do restored subscriptions which the user has not taken over.
+    /// The rows of restored subscriptions are already in the client cache,
+    /// so they are reconciled against the fresh rows even if they had yet to be resumed.
+    fn resume(&self) -> ResumeAction {
+        let msg = || ws::SubscribeMulti {
+            query_id: ws::QueryId::new(self.query_id),
+            query_strings: self.query_sql.clone(),
+            request_id: next_request_id(),
+        };
+        match self.status {
+            SubscriptionServerState::Sent | SubscriptionServerState::Applied
+                if self.unsubscribe_called || self.restored == Some(Restored::Unadopted) =>
+            {
+                ResumeAction::End
+            }
+            SubscriptionServerState::Sent if self.restored.is_some() => ResumeAction::ResubscribeRestored(msg()),
+            SubscriptionServerState::Sent => ResumeAction::Resend(msg()),
+            SubscriptionServerState::Applied => ResumeAction::Resubscribe(msg()),
+            SubscriptionServerState::Pending | SubscriptionServerState::Ended | SubscriptionServerState::Error => {
+                ResumeAction::Skip
+            }
+        }
+    }
+
     pub fn unsubscribe_then(&mut self, on_end: Option<OnEndedCallback<M>>) -> crate::Result<()> {
         if self.is_ended() {
             return Err(crate::Error::AlreadyEnded);
@@ -488,6 +756,27 @@ impl<M: SpacetimeModule> SubscriptionHandleImpl<M> {
         inner.start()
     }
 
+    fn resume(&self) -> ResumeAction {
+        self.inner.lock().unwrap().resume()
+    }
+
+    /// See [`SubscriptionState::adopt`].
+    fn adopt(&self, restored: &SubscriptionHandleImpl<M>) -> bool {
+        self.inner.lock().unwrap().adopt(&mut restored.inner.lock().unwrap())
+    }
+
+    fn is_adoptable_by(&self, other: &SubscriptionHandleImpl<M>) -> bool {
+        self.inner.lock().unwrap().is_adoptable_by(&other.inner.lock().unwrap())
+    }
+
+    fn is_unadopted(&self) -> bool {
+        self.inner.lock().unwrap().is_unadopted()
+    }
+
+    fn cached(&self, caught_up: Option<ws::CaughtUp>) -> Option<CachedSubscription> {
+        self.inner.lock().unwrap().cached(caught_up)
+    }
+
     pub(crate) fn is_cancelled(&self) -> bool {
         self.inner.lock().unwrap().is_cancelled()
     }
@@ -111,6 +111,8 @@ pub(crate) struct WsParams {
     /// `Some(false)` to disable them.
     /// `None` to not set the parameter and let the server choose.
     pub confirmed: Option<bool>,
+    /// Whether to ask for a `CaughtUp` message after each message which reflects a transaction.
+    pub tx_offsets: bool,
 }
 
 fn make_uri(host: Uri, db_name: &str, connection_id: Option<ConnectionId>, params: WsParams) -> Result<Uri, UriError> {
@@ -156,12 +158,20 @@ fn make_uri(host: Uri, db_name: &str, connection_id: Option<ConnectionId>, param
         path.push_str("&light=true");
     }
 
+    // Ask for the return values of our reducer calls, which this SDK knows how to decode.
+    path.push_str("&reducer_results=true");
+
     // Enable confirmed reads if requested.
     if let Some(confirmed) = params.confirmed {
         path.push_str("&confirmed=");
         path.push_str(if confirmed { "true" } else { "false" });
     }
 
+    // Ask for the offsets of the transactions our cached rows reflect, if we persist them.
+    if params.tx_offsets {
+        path.push_str("&tx_offsets=true");
+    }
+
     parts.path_and_query = Some(path.parse().map_err(|source: InvalidUri| UriError::InvalidUri {
         source: Arc::new(source),
     })?);
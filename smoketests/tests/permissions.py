@@ -77,6 +77,111 @@ class Permissions(Smoketest):
                 {"Content-type": "application/json"}
             )
 
+    def test_collaborators(self):
+        """Ensure that collaborators are permitted exactly what their role allows"""
+
+        self.publish_module()
+        owner_config = self.config_path.read_text()
+
+        self.new_identity()
+        collaborator = str(self.spacetime("login", "show")).split()[-1]
+        collaborator_config = self.config_path.read_text()
+
+        # Without a role, the collaborator can neither read logs nor publish.
+        with self.assertRaises(Exception):
+            self.spacetime("logs", self.database_identity, "-n", "10000")
+
+        self.config_path.write_text(owner_config)
+        self.spacetime("db", "grant", self.database_identity, collaborator, "--role", "read-only")
+        self.assertIn("read-only", self.spacetime("db", "collaborators", self.database_identity))
+
+        # A read-only collaborator can read logs, but not publish.
+        self.config_path.write_text(collaborator_config)
+        self.spacetime("logs", self.database_identity, "-n", "10000")
+        with self.assertRaises(Exception):
+            self.spacetime("publish", self.database_identity, "--project-path", self.project_path, "--yes")
+        # Nor grant itself a better role.
+        with self.assertRaises(Exception):
+            self.spacetime("db", "grant", self.database_identity, collaborator, "--role", "developer")
+
+        self.config_path.write_text(owner_config)
+        self.spacetime("db", "grant", self.database_identity, collaborator, "--role", "developer")
+
+        # A developer can publish, but not delete the database.
+        self.config_path.write_text(collaborator_config)
+        self.spacetime("publish", self.database_identity, "--project-path", self.project_path, "--yes")
+        with self.assertRaises(Exception):
+            self.spacetime("delete", self.database_identity)
+
+        # Once revoked, the collaborator is back to having no access.
+        self.config_path.write_text(owner_config)
+        self.spacetime("db", "revoke", self.database_identity, collaborator)
+        self.config_path.write_text(collaborator_config)
+        with self.assertRaises(Exception):
+            self.spacetime("logs", self.database_identity, "-n", "10000")
+
+        self.config_path.write_text(owner_config)
+
+    def test_service_tokens(self):
+        """Ensure that service tokens are restricted to their scope, and can be revoked"""
+
+        self.publish_module()
+        owner_config = self.config_path.read_text()
+
+        token = self.spacetime(
+            "token", "create", "--database", self.database_identity, "--action", "publish", "--description", "ci"
+        ).strip()
+        self.assertIn("ci", self.spacetime("token", "list"))
+
+        # The service token may publish, but not read logs.
+        self.spacetime("logout")
+        self.spacetime("login", "--token", token)
+        self.spacetime("publish", self.database_identity, "--project-path", self.project_path, "--yes")
+        with self.assertRaises(Exception):
+            self.spacetime("logs", self.database_identity, "-n", "10000")
+        # Nor mint further tokens.
+        with self.assertRaises(Exception):
+            self.spacetime("token", "list")
+
+        self.config_path.write_text(owner_config)
+        [token_id] = [line.split("|")[0].strip() for line in self.spacetime("token", "list").splitlines() if "ci" in line]
+        self.spacetime("token", "revoke", token_id)
+        self.assertIn("revoked", self.spacetime("token", "list"))
+
+        # Once revoked, the service token can no longer publish.
+        self.spacetime("logout")
+        self.spacetime("login", "--token", token)
+        with self.assertRaises(Exception):
+            self.spacetime("publish", self.database_identity, "--project-path", self.project_path, "--yes")
+
+        self.config_path.write_text(owner_config)
+
+    def test_auth_policy(self):
+        """Ensure that only tokens accepted by the auth policy may connect, except for the owner"""
+
+        self.publish_module()
+        owner_config = self.config_path.read_text()
+
+        self.spacetime("db", "set-auth-policy", self.database_identity, "--issuer", "https://auth.example.com")
+        self.assertIn("https://auth.example.com", self.spacetime("db", "auth-policy", self.database_identity))
+
+        # Tokens issued by the local server are no longer accepted...
+        self.new_identity()
+        with self.assertRaises(Exception):
+            self.call("say_hello")
+        # ...nor may anyone but an admin change the policy.
+        with self.assertRaises(Exception):
+            self.spacetime("db", "set-auth-policy", self.database_identity)
+
+        # The owner is exempt, and may lift the restriction.
+        self.config_path.write_text(owner_config)
+        self.call("say_hello")
+        self.spacetime("db", "set-auth-policy", self.database_identity)
+
+        self.new_identity()
+        self.call("say_hello")
+        self.config_path.write_text(owner_config)
+
 class PrivateTablePermissions(Smoketest):
     MODULE_CODE = """
 use spacetimedb::{ReducerContext, Table};